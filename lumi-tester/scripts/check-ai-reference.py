@@ -92,7 +92,7 @@ def cli_commands() -> set[str]:
         return set()
     variants: set[str] = set()
     for line in match.group(1).splitlines():
-        match_line = re.match(r"^\s*([A-Z][A-Za-z0-9]*)\s*(?:\{|,)", line)
+        match_line = re.match(r"^\s*([A-Z][A-Za-z0-9]*)\s*(?:\{|\(|,)", line)
         if match_line:
             name = match_line.group(1)
             variants.add(re.sub(r"(?<!^)([A-Z])", r"-\1", name).lower())
@@ -1382,7 +1382,7 @@ def validate_cli_help_platform_guidance() -> list[str]:
     errors: list[str] = []
     text = MAIN_RS.read_text(encoding="utf-8")
     stale_patterns = {
-        "run platform help": r"Target platform \(android, ios, web\).*parsed from file",
+        "run platform help": r"Target platform \(android, ios, web\)\.?\s*///\s*parsed from file",
         "doctor platform help": r"Target platform to check \(android, ios, web, macos, windows, all\)",
     }
     for label, pattern in stale_patterns.items():
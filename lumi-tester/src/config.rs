@@ -0,0 +1,236 @@
+//! Project-wide defaults from `lumi.toml` / `.lumirc`
+//!
+//! The file is looked up from the test path upwards (or given with `--config`).
+//! CLI flags override it and it overrides the built-in defaults:
+//!
+//! ```toml
+//! platform = "android"
+//! output = "./output"      # relative to the config file
+//! speed = "fast"           # LUMI_SPEED
+//! defaultTimeout = 15000   # default command timeout (ms), LUMI_DEFAULT_TIMEOUT
+//! tags = ["smoke"]
+//! report = ["html", "junit"]   # or true for every format
+//! snapshot = true
+//! baselines = "./baselines"  # assertScreenshot references, LUMI_BASELINES_DIR
+//! adb = "/opt/android-sdk/platform-tools/adb"  # LUMI_ADB_PATH
+//! notify = "https://hooks.slack.com/services/..."  # LUMI_NOTIFY_URL
+//! notifyFormat = "slack"   # or "json", LUMI_NOTIFY_FORMAT
+//! skipCommands = ["assertScreenshot", "startProfiling"]  # LUMI_SKIP_COMMANDS
+//! ```
+
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+
+/// File names searched in every directory, first match wins
+pub const CONFIG_FILE_NAMES: [&str; 2] = ["lumi.toml", ".lumirc"];
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ProjectConfig {
+    /// File the values came from
+    pub path: PathBuf,
+    pub platform: Option<String>,
+    pub output: Option<PathBuf>,
+    pub speed: Option<String>,
+    pub default_timeout_ms: Option<u64>,
+    pub tags: Option<Vec<String>>,
+    pub report: Option<bool>,
+    /// Formats written by `--report` (`json`, `html`, `junit`); all when unset
+    pub report_formats: Option<Vec<String>>,
+    pub snapshot: Option<bool>,
+    pub baselines: Option<PathBuf>,
+    /// adb binary (or its directory) for non-standard SDK installs
+    pub adb: Option<PathBuf>,
+    /// Webhook that receives the run summary
+    pub notify: Option<String>,
+    /// `slack` or `json` payload for `notify`
+    pub notify_format: Option<String>,
+    /// Command types reported as skipped instead of run
+    pub skip_commands: Option<Vec<String>>,
+}
+
+impl ProjectConfig {
+    /// Find the nearest config file at or above `start`
+    pub fn discover(start: &Path) -> Result<Option<Self>> {
+        let start = std::fs::canonicalize(start).unwrap_or_else(|_| start.to_path_buf());
+        let dir = if start.is_file() {
+            start.parent().map(Path::to_path_buf).unwrap_or_default()
+        } else {
+            start
+        };
+
+        for ancestor in dir.ancestors() {
+            for name in CONFIG_FILE_NAMES {
+                let candidate = ancestor.join(name);
+                if candidate.is_file() {
+                    return Self::load(&candidate).map(Some);
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config: {}", path.display()))?;
+        Self::parse(&content, path).with_context(|| format!("Invalid config: {}", path.display()))
+    }
+
+    /// Parse TOML content; relative paths are resolved against `path`'s directory
+    pub fn parse(content: &str, path: &Path) -> Result<Self> {
+        let doc: toml_edit::DocumentMut = content.parse()?;
+        let base = path.parent().unwrap_or(Path::new("."));
+        let mut config = ProjectConfig {
+            path: path.to_path_buf(),
+            ..Default::default()
+        };
+
+        for (key, item) in doc.iter() {
+            let value = item
+                .as_value()
+                .ok_or_else(|| anyhow::anyhow!("`{}` must be a value, not a table", key))?;
+            let expect = |kind: &str| anyhow::anyhow!("`{}` must be {}", key, kind);
+            match key {
+                "platform" => {
+                    config.platform = Some(value.as_str().ok_or_else(|| expect("a string"))?.into())
+                }
+                "output" => {
+                    let output = value.as_str().ok_or_else(|| expect("a string"))?;
+                    config.output = Some(base.join(output));
+                }
+                "speed" => {
+                    config.speed = Some(value.as_str().ok_or_else(|| expect("a string"))?.into())
+                }
+                // A flow header's `timeout` is a hard deadline, so the name isn't reused here
+                "timeout" => anyhow::bail!(
+                    "Unknown config key `timeout`; use `defaultTimeout` for the default command timeout"
+                ),
+                "defaultTimeout" | "default_timeout" => {
+                    let ms = value
+                        .as_integer()
+                        .filter(|ms| *ms >= 0)
+                        .ok_or_else(|| expect("a number of milliseconds"))?;
+                    config.default_timeout_ms = Some(ms as u64);
+                }
+                "tags" => config.tags = Some(string_list(value).ok_or_else(|| expect("a list"))?),
+                "report" => match value.as_bool() {
+                    Some(enabled) => config.report = Some(enabled),
+                    None => {
+                        let formats = string_list(value)
+                            .ok_or_else(|| expect("true/false or a list of formats"))?;
+                        if let Some(bad) = formats
+                            .iter()
+                            .find(|f| !matches!(f.as_str(), "json" | "html" | "junit"))
+                        {
+                            anyhow::bail!("Unknown report format `{}` (json, html, junit)", bad);
+                        }
+                        config.report = Some(true);
+                        config.report_formats = Some(formats);
+                    }
+                },
+                "snapshot" => {
+                    config.snapshot = Some(value.as_bool().ok_or_else(|| expect("true/false"))?)
+                }
+                "baselines" => {
+                    let dir = value.as_str().ok_or_else(|| expect("a string"))?;
+                    config.baselines = Some(base.join(dir));
+                }
+                "adb" => {
+                    let adb = value.as_str().ok_or_else(|| expect("a string"))?;
+                    config.adb = Some(base.join(adb));
+                }
+                "notify" => {
+                    config.notify = Some(value.as_str().ok_or_else(|| expect("a string"))?.into())
+                }
+                "notifyFormat" | "notify_format" => {
+                    let format = value.as_str().ok_or_else(|| expect("a string"))?;
+                    format.parse::<crate::report::notify::NotifyFormat>()?;
+                    config.notify_format = Some(format.into());
+                }
+                "skipCommands" | "skip_commands" => {
+                    config.skip_commands = Some(string_list(value).ok_or_else(|| expect("a list"))?)
+                }
+                _ => anyhow::bail!("Unknown config key `{}`", key),
+            }
+        }
+        Ok(config)
+    }
+}
+
+fn string_list(value: &toml_edit::Value) -> Option<Vec<String>> {
+    match value {
+        toml_edit::Value::String(s) => Some(vec![s.value().clone()]),
+        toml_edit::Value::Array(items) => items
+            .iter()
+            .map(|v| v.as_str().map(str::to_string))
+            .collect(),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_project_config() {
+        let content = r#"
+platform = "web"
+output = "out"
+speed = "fast"
+defaultTimeout = 15000
+tags = ["smoke", "login"]
+report = ["html", "junit"]
+snapshot = true
+baselines = "baselines/v2"
+adb = "/opt/sdk/platform-tools"
+notify = "https://hooks.slack.com/services/T/B/x"
+notifyFormat = "slack"
+skipCommands = ["assertScreenshot", "startProfiling"]
+"#;
+        let config = ProjectConfig::parse(content, Path::new("/proj/lumi.toml")).unwrap();
+        assert_eq!(config.platform.as_deref(), Some("web"));
+        assert_eq!(config.output, Some(PathBuf::from("/proj/out")));
+        assert_eq!(config.default_timeout_ms, Some(15000));
+        assert_eq!(config.tags, Some(vec!["smoke".into(), "login".into()]));
+        assert_eq!(config.report, Some(true));
+        assert_eq!(
+            config.report_formats,
+            Some(vec!["html".into(), "junit".into()])
+        );
+        assert_eq!(config.snapshot, Some(true));
+        assert_eq!(config.baselines, Some(PathBuf::from("/proj/baselines/v2")));
+        assert_eq!(config.adb, Some(PathBuf::from("/opt/sdk/platform-tools")));
+        assert_eq!(config.notify_format.as_deref(), Some("slack"));
+        assert_eq!(
+            config.skip_commands,
+            Some(vec!["assertScreenshot".into(), "startProfiling".into()])
+        );
+
+        assert!(ProjectConfig::parse("plaform = \"web\"", Path::new("lumi.toml")).is_err());
+        assert!(ProjectConfig::parse("report = [\"pdf\"]", Path::new("lumi.toml")).is_err());
+        assert!(ProjectConfig::parse("defaultTimeout = \"10s\"", Path::new("lumi.toml")).is_err());
+        assert!(ProjectConfig::parse("timeout = 15000", Path::new("lumi.toml")).is_err());
+        assert!(ProjectConfig::parse("notifyFormat = \"teams\"", Path::new("lumi.toml")).is_err());
+    }
+
+    #[test]
+    fn test_discover_walks_up_from_test_path() {
+        let root = std::env::temp_dir().join(format!("lumi_cfg_{}", uuid::Uuid::new_v4()));
+        let tests = root.join("tests/login");
+        std::fs::create_dir_all(&tests).unwrap();
+        std::fs::write(root.join(".lumirc"), "platform = \"ios\"\n").unwrap();
+        std::fs::write(tests.join("a.yaml"), "- back\n").unwrap();
+
+        let found = ProjectConfig::discover(&tests.join("a.yaml"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(found.platform.as_deref(), Some("ios"));
+
+        // lumi.toml wins over .lumirc in the same directory
+        std::fs::write(root.join("lumi.toml"), "platform = \"web\"\n").unwrap();
+        let found = ProjectConfig::discover(&tests).unwrap().unwrap();
+        assert_eq!(found.platform.as_deref(), Some("web"));
+
+        std::fs::remove_dir_all(&root).ok();
+    }
+}
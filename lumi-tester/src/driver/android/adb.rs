@@ -1,3 +1,4 @@
+use crate::driver::trace;
 use crate::utils::binary_resolver;
 use anyhow::{Context, Result};
 use std::process::Stdio;
@@ -13,13 +14,16 @@ pub struct Device {
 /// Get list of connected Android devices
 pub async fn get_devices() -> Result<Vec<Device>> {
     let adb_path = binary_resolver::find_adb()?;
+    let call = trace::Call::start("adb", &["devices"]);
     let output = Command::new(adb_path)
+        .kill_on_drop(true)
         .args(["devices"])
         .stdout(Stdio::piped())
         .stderr(Stdio::piped())
         .output()
-        .await
-        .context("Failed to execute adb devices")?;
+        .await;
+    call.finish(output.as_ref().is_ok_and(|o| o.status.success()));
+    let output = output.context("Failed to execute adb devices")?;
 
     let stdout = String::from_utf8_lossy(&output.stdout);
     let stderr = String::from_utf8_lossy(&output.stderr);
@@ -56,13 +60,16 @@ pub async fn shell(serial: Option<&str>, cmd: &str) -> Result<String> {
     args.push(cmd);
 
     let adb_path = binary_resolver::find_adb()?;
+    let call = trace::Call::start("adb", &args);
     let output = Command::new(adb_path)
+        .kill_on_drop(true)
         .args(&args)
         .stdout(Stdio::piped())
         .stderr(Stdio::piped())
         .output()
-        .await
-        .with_context(|| format!("Failed to execute: adb shell {}", cmd))?;
+        .await;
+    call.finish(output.as_ref().is_ok_and(|o| o.status.success()));
+    let output = output.with_context(|| format!("Failed to execute: adb shell {}", cmd))?;
 
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr);
@@ -84,13 +91,16 @@ pub async fn exec(serial: Option<&str>, args: &[&str]) -> Result<String> {
     full_args.extend_from_slice(args);
 
     let adb_path = binary_resolver::find_adb()?;
+    let call = trace::Call::start("adb", &full_args);
     let output = Command::new(adb_path)
+        .kill_on_drop(true)
         .args(&full_args)
         .stdout(Stdio::piped())
         .stderr(Stdio::piped())
         .output()
-        .await
-        .with_context(|| format!("Failed to execute: adb {:?}", full_args))?;
+        .await;
+    call.finish(output.as_ref().is_ok_and(|o| o.status.success()));
+    let output = output.with_context(|| format!("Failed to execute: adb {:?}", full_args))?;
 
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr);
@@ -100,6 +110,30 @@ pub async fn exec(serial: Option<&str>, args: &[&str]) -> Result<String> {
     Ok(String::from_utf8_lossy(&output.stdout).to_string())
 }
 
+/// Start a long-running ADB shell command with stdout piped (killed on drop)
+pub fn spawn_shell(serial: Option<&str>, cmd: &str) -> Result<tokio::process::Child> {
+    let mut args = Vec::new();
+
+    if let Some(s) = serial {
+        args.push("-s");
+        args.push(s);
+    }
+
+    args.push("shell");
+    args.push(cmd);
+
+    let adb_path = binary_resolver::find_adb()?;
+    let call = trace::Call::start("adb", &args);
+    let child = Command::new(adb_path)
+        .args(&args)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .kill_on_drop(true)
+        .spawn();
+    call.finish(child.is_ok());
+    child.with_context(|| format!("Failed to spawn: adb shell {}", cmd))
+}
+
 /// Execute ADB exec-out command (faster than shell for binary output)
 /// This avoids file I/O on device and transfers data directly to stdout
 pub async fn exec_out(serial: Option<&str>, cmd: &str) -> Result<String> {
@@ -114,13 +148,21 @@ pub async fn exec_out(serial: Option<&str>, cmd: &str) -> Result<String> {
     args.push(cmd);
 
     let adb_path = binary_resolver::find_adb()?;
+    let call = trace::Call::start("adb", &args);
     let output = Command::new(adb_path)
+        .kill_on_drop(true)
         .args(&args)
         .stdout(Stdio::piped())
         .stderr(Stdio::piped())
         .output()
-        .await
-        .with_context(|| format!("Failed to execute: adb exec-out {}", cmd))?;
+        .await;
+    // exec-out may not set the exit status; output means it ran
+    call.finish(
+        output
+            .as_ref()
+            .is_ok_and(|o| !o.stdout.is_empty() || o.status.success()),
+    );
+    let output = output.with_context(|| format!("Failed to execute: adb exec-out {}", cmd))?;
 
     // exec-out may not set exit status properly, check if we got output
     let stdout = String::from_utf8_lossy(&output.stdout).to_string();
@@ -146,13 +188,21 @@ pub async fn exec_out_binary(serial: Option<&str>, cmd: &str) -> Result<Vec<u8>>
     args.push(cmd);
 
     let adb_path = binary_resolver::find_adb()?;
+    let call = trace::Call::start("adb", &args);
     let output = Command::new(adb_path)
+        .kill_on_drop(true)
         .args(&args)
         .stdout(Stdio::piped())
         .stderr(Stdio::piped())
         .output()
-        .await
-        .with_context(|| format!("Failed to execute: adb exec-out {}", cmd))?;
+        .await;
+    // exec-out may not set the exit status; output means it ran
+    call.finish(
+        output
+            .as_ref()
+            .is_ok_and(|o| !o.stdout.is_empty() || o.status.success()),
+    );
+    let output = output.with_context(|| format!("Failed to execute: adb exec-out {}", cmd))?;
 
     if output.stdout.is_empty() && !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr);
@@ -178,13 +228,16 @@ pub async fn pull(serial: Option<&str>, remote: &str, local: &str) -> Result<()>
     let args_ref: Vec<&str> = args.iter().map(|s| s.as_str()).collect();
 
     let adb_path = binary_resolver::find_adb()?;
+    let call = trace::Call::start("adb", &args_ref);
     let output = Command::new(adb_path)
+        .kill_on_drop(true)
         .args(&args_ref)
         .stdout(Stdio::piped())
         .stderr(Stdio::piped())
         .output()
-        .await
-        .with_context(|| format!("Failed to pull {} to {}", remote, local))?;
+        .await;
+    call.finish(output.as_ref().is_ok_and(|o| o.status.success()));
+    let output = output.with_context(|| format!("Failed to pull {} to {}", remote, local))?;
 
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr);
@@ -210,13 +263,16 @@ pub async fn install(serial: Option<&str>, apk_path: &str) -> Result<()> {
     let args_ref: Vec<&str> = args.iter().map(|s| s.as_str()).collect();
 
     let adb_path = binary_resolver::find_adb()?;
+    let call = trace::Call::start("adb", &args_ref);
     let output = Command::new(adb_path)
+        .kill_on_drop(true)
         .args(&args_ref)
         .stdout(Stdio::piped())
         .stderr(Stdio::piped())
         .output()
-        .await
-        .with_context(|| format!("Failed to install {}", apk_path))?;
+        .await;
+    call.finish(output.as_ref().is_ok_and(|o| o.status.success()));
+    let output = output.with_context(|| format!("Failed to install {}", apk_path))?;
 
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr);
@@ -242,13 +298,16 @@ pub async fn push(serial: Option<&str>, local: &str, remote: &str) -> Result<()>
     let args_ref: Vec<&str> = args.iter().map(|s| s.as_str()).collect();
 
     let adb_path = binary_resolver::find_adb()?;
+    let call = trace::Call::start("adb", &args_ref);
     let output = Command::new(adb_path)
+        .kill_on_drop(true)
         .args(&args_ref)
         .stdout(Stdio::piped())
         .stderr(Stdio::piped())
         .output()
-        .await
-        .with_context(|| format!("Failed to push {} to {}", local, remote))?;
+        .await;
+    call.finish(output.as_ref().is_ok_and(|o| o.status.success()));
+    let output = output.with_context(|| format!("Failed to push {} to {}", local, remote))?;
 
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr);
@@ -2,13 +2,17 @@
 //!
 //! Connects to nl-android AudioServer (port 8890) to capture and analyze audio.
 //! Used for testing audio ducking behavior in navigation apps.
+//! Also reads playback state from `dumpsys media_session` / `dumpsys audio`.
 
 use anyhow::{anyhow, Result};
+use regex::Regex;
 use std::io::Read;
 use std::net::TcpStream;
-use std::sync::{Arc, Mutex};
+use std::sync::{Arc, Mutex, OnceLock};
 use std::time::{Duration, Instant};
 
+use super::adb;
+
 /// Audio configuration matching nl-android AudioConfig
 const SAMPLE_RATE: u32 = 48000;
 const CHANNELS: u16 = 2;
@@ -18,6 +22,15 @@ const AUDIO_PORT: u16 = 8890;
 /// Audio capture service
 pub struct AudioService;
 
+/// One media session from `dumpsys media_session`
+#[derive(Debug, Clone, PartialEq)]
+pub struct MediaSessionInfo {
+    /// Package that owns the session
+    pub package: String,
+    /// PlaybackState is STATE_PLAYING
+    pub playing: bool,
+}
+
 /// Represents an audio ducking event (volume drop then recovery)
 #[derive(Debug, Clone)]
 pub struct DuckingEvent {
@@ -129,6 +142,32 @@ impl AudioService {
         Ok(capture)
     }
 
+    /// Whether `app_id` (any app when `None`) is currently playing audio
+    pub async fn is_playing(serial: Option<&str>, app_id: Option<&str>) -> Result<bool> {
+        let sessions = adb::shell(serial, "dumpsys media_session").await?;
+        let playing = parse_media_sessions(&sessions)
+            .iter()
+            .any(|s| s.playing && app_id.is_none_or(|app| s.package == app));
+        if playing {
+            return Ok(true);
+        }
+
+        // Players without a media session still show up in the audio service,
+        // keyed by uid rather than package
+        let uid = match app_id {
+            Some(app) => {
+                let packages = adb::shell(serial, &format!("pm list packages -U {}", app)).await?;
+                match parse_package_uid(&packages, app) {
+                    Some(uid) => Some(uid),
+                    None => return Ok(false),
+                }
+            }
+            None => None,
+        };
+        let audio = adb::shell(serial, "dumpsys audio").await?;
+        Ok(has_started_player(&audio, uid))
+    }
+
     /// Setup port forward for audio
     async fn setup_port_forward(serial: Option<&str>) -> Result<()> {
         let serial_args: Vec<String> = match serial {
@@ -325,3 +364,111 @@ impl AudioAnalysis {
         valid_events.len() >= min_events
     }
 }
+
+/// Parse `dumpsys media_session` into one entry per session record
+pub fn parse_media_sessions(output: &str) -> Vec<MediaSessionInfo> {
+    // PlaybackState.STATE_PLAYING == 3, printed as `state=3` or `state=PLAYING(3)`
+    static STATE: OnceLock<Regex> = OnceLock::new();
+    let re = STATE
+        .get_or_init(|| Regex::new(r"^state=(?:\w+\()?(\d+)").expect("valid playback state regex"));
+    let mut sessions: Vec<MediaSessionInfo> = Vec::new();
+    for line in output.lines().map(str::trim) {
+        if let Some(package) = line.strip_prefix("package=") {
+            sessions.push(MediaSessionInfo {
+                package: package.to_string(),
+                playing: false,
+            });
+        } else if let Some(state) = line.strip_prefix("state=PlaybackState {") {
+            if let Some(session) = sessions.last_mut() {
+                session.playing = re.captures(state).is_some_and(|caps| &caps[1] == "3");
+            }
+        }
+    }
+    sessions
+}
+
+/// Uid of `package` in `pm list packages -U` output (`package:<name> uid:<uid>`)
+pub fn parse_package_uid(output: &str, package: &str) -> Option<u32> {
+    output.lines().find_map(|line| {
+        let (name, uid) = line.trim().strip_prefix("package:")?.split_once(" uid:")?;
+        if name != package {
+            return None;
+        }
+        uid.split(',').next()?.trim().parse().ok()
+    })
+}
+
+/// Whether `dumpsys audio` lists a started player, only counting `uid` when given.
+/// Lines look like `AudioPlaybackConfiguration piid:15 ... u/pid:10123/4321 state:started ...`
+pub fn has_started_player(output: &str, uid: Option<u32>) -> bool {
+    output.lines().any(|l| {
+        l.contains("AudioPlaybackConfiguration")
+            && l.contains("state:started")
+            && uid.is_none_or(|uid| l.contains(&format!("u/pid:{}/", uid)))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_media_sessions() {
+        let dump = "\
+MEDIA SESSION SERVICE (dumpsys media_session)
+
+  3 Sessions Stacks - have 2 sessions:
+    com.spotify.music/spotify-media-session (userId=0)
+      ownerPid=4321, ownerUid=10123, userId=0
+      package=com.spotify.music
+      active=true
+      state=PlaybackState {state=3, position=1200, buffered position=0, speed=1.0, updated=98765, actions=0, custom actions=[], active item id=-1, error=null}
+    com.google.android.youtube/YouTube (userId=0)
+      ownerPid=5555, ownerUid=10200, userId=0
+      package=com.google.android.youtube
+      active=false
+      state=PlaybackState {state=2, position=0, buffered position=0, speed=0.0, updated=1, actions=0, custom actions=[], active item id=-1, error=null}
+    com.apple.android.music/MediaSessionService (userId=0)
+      ownerPid=6010, ownerUid=10321, userId=0
+      package=com.apple.android.music
+      active=true
+      state=PlaybackState {state=PLAYING(3), position=5400, buffered position=0, speed=1.0, updated=123456, actions=0, custom actions=[], active item id=-1, error=null}
+";
+        assert_eq!(
+            parse_media_sessions(dump),
+            vec![
+                MediaSessionInfo {
+                    package: "com.spotify.music".to_string(),
+                    playing: true,
+                },
+                MediaSessionInfo {
+                    package: "com.google.android.youtube".to_string(),
+                    playing: false,
+                },
+                MediaSessionInfo {
+                    package: "com.apple.android.music".to_string(),
+                    playing: true,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_started_player_by_uid() {
+        let packages = "package:com.spotify.music uid:10123\npackage:com.spotify.lite uid:10456\n";
+        assert_eq!(
+            parse_package_uid(packages, "com.spotify.music"),
+            Some(10123)
+        );
+        assert_eq!(parse_package_uid(packages, "com.example"), None);
+
+        let audio = "\
+  players:
+  AudioPlaybackConfiguration piid:15 type:android.media.MediaPlayer u/pid:10123/4321 state:started attr:AudioAttributes: usage=USAGE_MEDIA
+  AudioPlaybackConfiguration piid:23 type:android.media.AudioTrack u/pid:10456/5555 state:paused attr:AudioAttributes: usage=USAGE_MEDIA
+";
+        assert!(has_started_player(audio, None));
+        assert!(has_started_player(audio, Some(10123)));
+        assert!(!has_started_player(audio, Some(10456)));
+    }
+}
@@ -0,0 +1,224 @@
+//! Limited CSS selector support for Android
+//!
+//! Translates one compound selector into checks on uiautomator dump attributes:
+//! a type (`Button`, `android.widget.EditText`), `#id` for resource-id, `.Class`
+//! for the widget class, and `[attr]`, `[attr=v]`, `[attr*=v]`, `[attr^=v]`,
+//! `[attr$=v]` filters. Combinators, pseudo-classes and selector lists have no
+//! uiautomator equivalent and are rejected with an error instead of never matching.
+
+use anyhow::Result;
+
+use super::driver::map_android_type;
+use super::uiautomator::UiElement;
+
+const UNSUPPORTED: &str =
+    "CSS selectors on Android support only a single `Type#id.Class[attr=value]` \
+     selector; use id/text/desc on Android";
+
+#[derive(Debug, Clone, PartialEq)]
+enum AttrOp {
+    Present,
+    Equals,
+    Contains,
+    StartsWith,
+    EndsWith,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct AttrFilter {
+    name: String,
+    op: AttrOp,
+    value: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CssQuery {
+    id: Option<String>,
+    /// Type selector and `.Class` parts, all matched against the widget class
+    classes: Vec<String>,
+    attrs: Vec<AttrFilter>,
+}
+
+impl CssQuery {
+    pub fn parse(css: &str) -> Result<Self> {
+        let css = css.trim();
+        if css.is_empty() {
+            anyhow::bail!("Empty CSS selector");
+        }
+        let mut query = CssQuery::default();
+        let mut rest = css;
+
+        // Leading type selector; `*` matches anything
+        let tag_len = rest.find(['#', '.', '[']).unwrap_or(rest.len());
+        let tag = &rest[..tag_len];
+        if !tag.is_empty() && tag != "*" {
+            query.classes.push(identifier(tag, css)?.to_string());
+        }
+        rest = &rest[tag_len..];
+
+        while let Some(c) = rest.chars().next() {
+            match c {
+                '#' | '.' => {
+                    let end = rest[1..]
+                        .find(['#', '.', '['])
+                        .map_or(rest.len(), |i| i + 1);
+                    let name = identifier(&rest[1..end], css)?.to_string();
+                    if c == '#' {
+                        query.id = Some(name);
+                    } else {
+                        query.classes.push(name);
+                    }
+                    rest = &rest[end..];
+                }
+                '[' => {
+                    let end = rest
+                        .find(']')
+                        .ok_or_else(|| anyhow::anyhow!("Unclosed '[' in CSS selector '{}'", css))?;
+                    query.attrs.push(attribute(&rest[1..end], css)?);
+                    rest = &rest[end + 1..];
+                }
+                _ => anyhow::bail!("{} (got '{}')", UNSUPPORTED, css),
+            }
+        }
+        Ok(query)
+    }
+
+    pub fn matches(&self, e: &UiElement) -> bool {
+        let id_ok = self
+            .id
+            .as_ref()
+            .is_none_or(|id| e.resource_id == *id || e.resource_id.ends_with(&format!("/{}", id)));
+        let classes_ok = self.classes.iter().all(|class| {
+            let class = map_android_type(class);
+            e.class == class || e.class.ends_with(&format!(".{}", class))
+        });
+        id_ok
+            && classes_ok
+            && self.attrs.iter().all(|filter| {
+                let actual = attribute_value(e, &filter.name);
+                match filter.op {
+                    AttrOp::Present => !actual.is_empty(),
+                    AttrOp::Equals => actual == filter.value,
+                    AttrOp::Contains => actual.contains(&filter.value),
+                    AttrOp::StartsWith => actual.starts_with(&filter.value),
+                    AttrOp::EndsWith => actual.ends_with(&filter.value),
+                }
+            })
+    }
+}
+
+fn identifier<'a>(name: &'a str, css: &str) -> Result<&'a str> {
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || matches!(c, '_' | '-'));
+    if !valid {
+        anyhow::bail!("{} (got '{}')", UNSUPPORTED, css);
+    }
+    Ok(name)
+}
+
+fn attribute(body: &str, css: &str) -> Result<AttrFilter> {
+    // The first `=` ends the name; the character before it picks the operator,
+    // so operator-like text inside a quoted value is left alone
+    let (name, op, value) = match body.find('=') {
+        Some(eq) => {
+            let (name, op) = match body[..eq].chars().last() {
+                Some('*') => (&body[..eq - 1], AttrOp::Contains),
+                Some('^') => (&body[..eq - 1], AttrOp::StartsWith),
+                Some('$') => (&body[..eq - 1], AttrOp::EndsWith),
+                _ => (&body[..eq], AttrOp::Equals),
+            };
+            (name, op, &body[eq + 1..])
+        }
+        None => (body, AttrOp::Present, ""),
+    };
+    let name = identifier(name.trim(), css)?.to_string();
+    if attribute_name(&name).is_none() {
+        anyhow::bail!(
+            "Unknown attribute '{}' in CSS selector '{}' (Android supports text, content-desc, resource-id, class, hint, clickable, enabled, focusable, scrollable)",
+            name,
+            css
+        );
+    }
+    let value = value.trim().trim_matches(['"', '\'']).to_string();
+    Ok(AttrFilter { name, op, value })
+}
+
+/// Canonical dump attribute for a CSS attribute name
+fn attribute_name(name: &str) -> Option<&'static str> {
+    Some(match name.to_lowercase().as_str() {
+        "text" => "text",
+        "content-desc" | "contentdesc" | "desc" | "aria-label" => "content-desc",
+        "resource-id" | "id" => "resource-id",
+        "class" => "class",
+        "hint" | "placeholder" => "hint",
+        "clickable" => "clickable",
+        "enabled" => "enabled",
+        "focusable" => "focusable",
+        "scrollable" => "scrollable",
+        _ => return None,
+    })
+}
+
+fn attribute_value(e: &UiElement, name: &str) -> String {
+    match attribute_name(name).unwrap_or_default() {
+        "text" => e.text.clone(),
+        "content-desc" => e.content_desc.clone(),
+        "resource-id" => e.resource_id.clone(),
+        "class" => e.class.clone(),
+        "hint" => e.hint.clone(),
+        "clickable" => e.clickable.to_string(),
+        "enabled" => e.enabled.to_string(),
+        "focusable" => e.focusable.to_string(),
+        "scrollable" => e.scrollable.to_string(),
+        _ => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_css_query_matches_dump_attributes() {
+        let login = UiElement {
+            class: "android.widget.Button".to_string(),
+            resource_id: "com.example:id/login".to_string(),
+            content_desc: "Log in now".to_string(),
+            clickable: true,
+            ..Default::default()
+        };
+        let matches = |css: &str| CssQuery::parse(css).unwrap().matches(&login);
+
+        assert!(matches("#login"));
+        assert!(matches("Button#login"));
+        assert!(matches("button.Button"));
+        assert!(matches("[content-desc^='Log in']"));
+        assert!(matches("*[clickable=true][resource-id$=\"/login\"]"));
+        assert!(!matches("EditText#login"));
+        assert!(!matches("[text]"));
+
+        let formula = UiElement {
+            text: "a*=b".to_string(),
+            content_desc: "x^=y".to_string(),
+            ..Default::default()
+        };
+        assert!(CssQuery::parse("[text=\"a*=b\"]")
+            .unwrap()
+            .matches(&formula));
+        assert!(CssQuery::parse("[text*='*=']").unwrap().matches(&formula));
+        assert!(CssQuery::parse("[content-desc$=\"x^=y\"]")
+            .unwrap()
+            .matches(&formula));
+
+        for unsupported in [
+            "div > #login",
+            "#login:first-child",
+            "#a, #b",
+            "[onclick=x]",
+        ] {
+            assert!(CssQuery::parse(unsupported).is_err(), "{}", unsupported);
+        }
+    }
+}
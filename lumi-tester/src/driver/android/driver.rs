@@ -1,4 +1,4 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 use async_trait::async_trait;
 use std::path::Path;
 use std::sync::atomic::{AtomicU32, Ordering};
@@ -8,16 +8,40 @@ use tokio::sync::{Mutex, OnceCell};
 use uuid::Uuid;
 
 use super::adb;
+use super::css;
 use super::uiautomator::{self, UiElement};
-use crate::driver::traits::{PlatformDriver, Selector, SwipeDirection};
+use crate::driver::traits::{
+    ensure_selector_supported, AppVersion, LocationTolerance, NotificationInfo, NthMatch,
+    PlatformDriver, RecordingOptions, Selector, SwipeDirection,
+};
 use colored::Colorize;
 use image::GenericImageView;
 
 use crate::driver::common;
 use crate::driver::ocr::OcrEngine;
-use crate::parser::types::SpeedMode;
+use crate::error::TesterError;
+use crate::parser::types::{DriveProfile, NetworkConditions, SpeedMode};
 use std::collections::HashMap;
 
+/// Selector kinds resolved against the uiautomator dump, screenshots or coordinates
+const SUPPORTED_SELECTORS: &[&str] = &[
+    "text",
+    "fuzzy",
+    "regex",
+    "id",
+    "description",
+    "placeholder",
+    "role",
+    "elementType",
+    "css",
+    "point",
+    "image",
+    "ocr",
+    "scrollable",
+    "relative",
+    "containsChild",
+];
+
 /// Speed profile for test execution
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum SpeedProfile {
@@ -89,7 +113,7 @@ impl SpeedProfile {
 }
 
 /// State of the background mock location process
-#[derive(Clone)]
+#[derive(Clone, Default)]
 struct MockLocationState {
     current_lat: Option<f64>,
     current_lon: Option<f64>,
@@ -97,33 +121,29 @@ struct MockLocationState {
     finished: bool,
     paused: bool,
     speed: Option<f64>,
-    speed_mode: SpeedMode,
-    speed_noise: Option<f64>,
+    drive_profile: DriveProfile,
     total_points: usize,
     current_index: usize,
     estimated_duration_ms: u64,
 }
 
-impl Default for MockLocationState {
-    fn default() -> Self {
-        Self {
-            current_lat: None,
-            current_lon: None,
-            is_running: false,
-            finished: false,
-            paused: false,
-            speed: None,
-            speed_mode: SpeedMode::Linear,
-            speed_noise: None,
-            total_points: 0,
-            current_index: 0,
-            estimated_duration_ms: 0,
-        }
-    }
-}
-
 /// UI Cache TTL in milliseconds (3 seconds for better performance)
-const UI_CACHE_TTL_MS: u64 = 3000;
+pub const UI_CACHE_TTL_MS: u64 = 3000;
+
+/// Dumps tried before a malformed or truncated hierarchy fails the command
+const UI_DUMP_ATTEMPTS: u32 = 3;
+
+/// Near-miss candidates listed when an element never became visible
+const NEAR_MISS_LIMIT: usize = 3;
+
+/// screenrecord bitrate when none is configured (4 Mbps)
+const DEFAULT_RECORDING_BITRATE: u32 = 4_000_000;
+
+/// Global setting used to simulate a secondary (Android Auto) display
+const OVERLAY_DISPLAY_SETTING: &str = "overlay_display_devices";
+
+/// Size/density of the simulated overlay display
+const OVERLAY_DISPLAY_SPEC: &str = "1024x768/120";
 
 /// Android driver implementation using ADB
 pub struct AndroidDriver {
@@ -150,6 +170,10 @@ pub struct AndroidDriver {
     /// Cached Android SDK version (API level) - used to determine feature support
     /// -d flag for input command requires API 29+ (Android 10+)
     sdk_version: u32,
+    /// `ro.product.model`, e.g. "Pixel 7"
+    device_model: Option<String>,
+    /// `ro.build.version.release`, e.g. "14"
+    os_version: Option<String>,
 }
 
 impl AndroidDriver {
@@ -162,7 +186,11 @@ impl AndroidDriver {
             if devices.len() == 1 {
                 Some(devices[0].serial.clone())
             } else if devices.is_empty() {
-                anyhow::bail!("No Android devices connected");
+                return Err(TesterError::driver_unavailable(
+                    "android",
+                    "No Android devices connected",
+                )
+                .into());
             } else {
                 anyhow::bail!("Multiple devices connected. Please specify one with --device");
             }
@@ -250,6 +278,20 @@ impl AndroidDriver {
             .parse::<u32>()
             .unwrap_or(28); // Default to API 28 (Android 9) if parsing fails
 
+        let device_model = adb::shell(selected_serial.as_deref(), "getprop ro.product.model")
+            .await
+            .ok()
+            .map(|m| m.trim().to_string())
+            .filter(|m| !m.is_empty());
+        let os_version = adb::shell(
+            selected_serial.as_deref(),
+            "getprop ro.build.version.release",
+        )
+        .await
+        .ok()
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty());
+
         Ok(Self {
             serial: selected_serial,
             screen_size,
@@ -264,9 +306,44 @@ impl AndroidDriver {
             support_unicode,
             ocr_engine: Arc::new(OnceCell::new()),
             sdk_version,
+            device_model,
+            os_version,
         })
     }
 
+    /// Use `profile` instead of the `LUMI_SPEED` one (lumi.toml `speed`)
+    pub fn set_speed_profile(&mut self, profile: SpeedProfile) {
+        self.speed_profile = profile;
+    }
+
+    /// Poll until `app_id` is the resumed activity, returning false on timeout
+    async fn wait_for_app_focus(&self, app_id: &str, timeout: Duration) -> bool {
+        let start = Instant::now();
+        let poll_interval = self.speed_profile.poll_interval_ms();
+
+        loop {
+            if self.app_has_focus(app_id).await {
+                return true;
+            }
+            if start.elapsed() >= timeout {
+                return false;
+            }
+            tokio::time::sleep(Duration::from_millis(poll_interval)).await;
+        }
+    }
+
+    async fn app_has_focus(&self, app_id: &str) -> bool {
+        // Use dumpsys activity activities which is more reliable for finding the resumed app
+        // and use simple grep to avoid compatibility issues
+        let output = adb::shell(
+            self.serial.as_deref(),
+            "dumpsys activity activities | grep ResumedActivity",
+        )
+        .await
+        .unwrap_or_default();
+        output.contains(&format!("{}/", app_id))
+    }
+
     /// Invalidate the UI cache
     async fn invalidate_cache(&self) {
         let mut cache = self.ui_cache.lock().await;
@@ -339,31 +416,66 @@ impl AndroidDriver {
             }
         }
 
-        // Cache miss or expired, dump fresh UI
+        // Cache miss or expired, dump fresh UI. A dump taken while the screen
+        // changes can come back truncated, so a bad parse re-dumps a few times.
+        let mut attempt = 1;
+        let elements = loop {
+            let xml = self.dump_ui_xml().await?;
+            match uiautomator::parse_hierarchy(&xml) {
+                Ok(elements) => break elements,
+                Err(e) if attempt < UI_DUMP_ATTEMPTS => {
+                    crate::driver::trace::log(format!(
+                        "{} {} (dump {}/{}), retrying...",
+                        "⚠".yellow(),
+                        e,
+                        attempt,
+                        UI_DUMP_ATTEMPTS
+                    ));
+                    tokio::time::sleep(Duration::from_millis(200 * attempt as u64)).await;
+                    attempt += 1;
+                }
+                Err(e) => {
+                    return Err(e.context(format!(
+                        "uiautomator dump unusable after {} attempts",
+                        UI_DUMP_ATTEMPTS
+                    )))
+                }
+            }
+        };
+
+        // Update cache
+        {
+            let mut cache = self.ui_cache.lock().await;
+            *cache = Some((Instant::now(), elements.clone()));
+        }
+
+        Ok(elements)
+    }
+
+    async fn dump_ui_xml(&self) -> Result<String> {
         // Optimization: Use exec-out with /dev/stdout to avoid file I/O
         // This is faster than writing to /sdcard and reading back
-        let xml = match adb::exec_out(self.serial.as_deref(), "uiautomator dump /dev/stdout").await
-        {
-            Ok(output) if output.contains("<?xml") => output,
+        match adb::exec_out(self.serial.as_deref(), "uiautomator dump /dev/stdout").await {
+            Ok(output) if output.contains("<?xml") => Ok(output),
             _ => {
                 // Fallback to file-based method for older Android versions
                 adb::shell(
                     self.serial.as_deref(),
                     "uiautomator dump /sdcard/window_dump.xml > /dev/null && cat /sdcard/window_dump.xml",
                 )
-                .await?
+                .await
             }
-        };
-
-        let elements = uiautomator::parse_hierarchy(&xml)?;
-
-        // Update cache
-        {
-            let mut cache = self.ui_cache.lock().await;
-            *cache = Some((Instant::now(), elements.clone()));
         }
+    }
 
-        Ok(elements)
+    /// Unsupported selector kinds and CSS outside the translatable subset fail
+    /// loudly instead of never matching
+    fn check_selector(selector: &Selector) -> Result<()> {
+        ensure_selector_supported("Android", selector, SUPPORTED_SELECTORS)?;
+        if let Selector::Css(css) = selector.unwrap_on_screen() {
+            css::CssQuery::parse(css)?;
+        }
+        Ok(())
     }
 
     /// Find element by selector
@@ -371,6 +483,8 @@ impl AndroidDriver {
         &self,
         selector: &Selector,
     ) -> Result<Option<uiautomator::UiElement>> {
+        Self::check_selector(selector)?;
+
         // Point selector, return None as it has no bounds
         if let Selector::Point { .. } = selector {
             return Ok(None);
@@ -409,12 +523,13 @@ impl AndroidDriver {
                 .await;
         }
 
+        Self::check_selector(selector)?;
         let elements = self.get_ui_hierarchy().await?;
 
         if let Some((elem, is_fallback)) = self.find_element_impl(selector, &elements) {
             // For Relative selectors, adjust the tap point based on direction
             // ONLY if fallback was triggered (meaning we are targeting a composite element like Switch)
-            if let Selector::Relative { direction, .. } = selector {
+            if let Selector::Relative { direction, .. } = selector.unwrap_on_screen() {
                 if is_fallback {
                     use crate::driver::traits::RelativeDirection;
                     let bounds = &elem.bounds;
@@ -447,6 +562,19 @@ impl AndroidDriver {
         elements: &'a [uiautomator::UiElement],
     ) -> Option<(&'a uiautomator::UiElement, bool)> {
         match selector {
+            Selector::OnScreen(inner) => {
+                // Match against on-screen elements only, then map back into the full dump
+                let on_screen = |e: &&uiautomator::UiElement| e.is_on_screen(self.screen_size);
+                let visible: Vec<uiautomator::UiElement> =
+                    elements.iter().filter(on_screen).cloned().collect();
+                let (found, is_fallback) = self.find_element_impl(inner, &visible)?;
+                let pos = visible.iter().position(|e| std::ptr::eq(e, found))?;
+                elements
+                    .iter()
+                    .filter(on_screen)
+                    .nth(pos)
+                    .map(|e| (e, is_fallback))
+            }
             Selector::Point { .. } => None,
             Selector::Image { .. } => None, // Handled by find_image_on_screen
             Selector::ScrollableItem {
@@ -494,36 +622,37 @@ impl AndroidDriver {
                 scrollables.get(*index).map(|e| (*e, false))
             }
 
+            // `exact` matches the whole text only; otherwise exact, case-insensitive,
+            // then substring, the same order as iOS and web
             Selector::Text(text, index, exact) => if *exact {
-                uiautomator::find_nth_by_text_exact(elements, text, *index as u32).or_else(|| {
-                    uiautomator::find_nth_by_text_contains(elements, text, *index as u32)
-                })
+                uiautomator::find_nth_by_text_exact(elements, text, *index)
             } else {
-                uiautomator::find_nth_by_text(elements, text, *index as u32).or_else(|| {
-                    uiautomator::find_nth_by_text_contains(elements, text, *index as u32)
-                })
+                uiautomator::find_nth_by_text(elements, text, *index)
+                    .or_else(|| uiautomator::find_nth_by_text_contains(elements, text, *index))
             }
             .map(|e| (e, false)),
 
+            Selector::FuzzyText(text, index, threshold) => {
+                uiautomator::find_nth_by_fuzzy_text(elements, text, *threshold, *index)
+                    .map(|e| (e, false))
+            }
+
             Selector::TextRegex(pattern, index) => {
-                uiautomator::find_nth_by_regex(elements, pattern, *index as u32).map(|e| (e, false))
+                uiautomator::find_nth_by_regex(elements, pattern, *index).map(|e| (e, false))
             }
 
             Selector::Id(id, index) => {
-                uiautomator::find_nth_by_id(elements, id, *index as u32).map(|e| (e, false))
+                uiautomator::find_nth_by_id(elements, id, *index).map(|e| (e, false))
             }
 
             Selector::IdRegex(pattern, index) => {
-                uiautomator::find_nth_by_id_regex(elements, pattern, *index as u32)
-                    .map(|e| (e, false))
+                uiautomator::find_nth_by_id_regex(elements, pattern, *index).map(|e| (e, false))
             }
 
-            Selector::Type(type_name, index) => uiautomator::find_by_type_index(
-                elements,
-                map_android_type(type_name),
-                *index as u32,
-            )
-            .map(|e| (e, false)),
+            Selector::Type(type_name, index) => {
+                uiautomator::find_by_type_index(elements, map_android_type(type_name), *index)
+                    .map(|e| (e, false))
+            }
 
             Selector::AccessibilityId(id) | Selector::Description(id, _) => elements
                 .iter()
@@ -531,12 +660,16 @@ impl AndroidDriver {
                 .map(|e| (e, false)),
 
             Selector::DescriptionRegex(pattern, index) => {
-                uiautomator::find_nth_by_description_regex(elements, pattern, *index as u32)
+                uiautomator::find_nth_by_description_regex(elements, pattern, *index)
                     .map(|e| (e, false))
             }
 
             Selector::XPath(_) => None,
-            Selector::Css(_) => None,
+            // Unsupported syntax is rejected by `check_selector` before the lookup
+            Selector::Css(css) => css::CssQuery::parse(css)
+                .ok()
+                .and_then(|query| elements.iter().find(|e| query.matches(e)))
+                .map(|e| (e, false)),
             Selector::Role(role, index) => {
                 let android_type = match role.to_lowercase().as_str() {
                     "button" => "android.widget.Button",
@@ -544,14 +677,12 @@ impl AndroidDriver {
                     "image" => "android.widget.ImageView",
                     _ => role,
                 };
-                uiautomator::find_by_type_index(elements, android_type, *index as u32)
-                    .map(|e| (e, false))
+                uiautomator::find_by_type_index(elements, android_type, *index).map(|e| (e, false))
             }
             Selector::Placeholder(placeholder, index) => {
                 // Android doesn't always expose placeholder,
                 // falling back to searching by text as they are often the same in the dump
-                uiautomator::find_nth_by_text(elements, placeholder, *index as u32)
-                    .map(|e| (e, false))
+                uiautomator::find_nth_by_text(elements, placeholder, *index).map(|e| (e, false))
             }
 
             Selector::AnyClickable(index) => {
@@ -559,7 +690,7 @@ impl AndroidDriver {
                 elements
                     .iter()
                     .filter(|e| e.clickable)
-                    .nth(*index)
+                    .nth_match(*index)
                     .map(|e| (e, false))
             }
 
@@ -568,6 +699,7 @@ impl AndroidDriver {
                 anchor,
                 direction,
                 max_dist,
+                angle,
             } => {
                 // Get candidates based on target
                 let candidates = match target.as_ref() {
@@ -595,8 +727,9 @@ impl AndroidDriver {
                 // Find anchor
                 let (anchor_elem, _) = self.find_element_impl(anchor, elements)?;
 
+                let max_px = max_dist.map(|d| d.to_pixels(direction.axis_length(self.screen_size)));
                 let sorted_matches =
-                    uiautomator::find_relative(candidates, anchor_elem, *direction, *max_dist);
+                    uiautomator::find_relative(candidates, anchor_elem, *direction, max_px, *angle);
 
                 // Get index from target selector
                 let target_index = match target.as_ref() {
@@ -616,7 +749,7 @@ impl AndroidDriver {
 
                 sorted_matches
                     .into_iter()
-                    .nth(target_index)
+                    .nth_match(target_index)
                     .map(|e| (e, false))
             }
             Selector::HasChild { parent, child } => {
@@ -651,7 +784,14 @@ impl AndroidDriver {
     /// Check if UiElement matches a simple selector (for HasChild matching)
     fn element_matches_selector(e: &uiautomator::UiElement, selector: &Selector) -> bool {
         match selector {
-            Selector::Text(text, _, _) => e.text.contains(text) || e.content_desc.contains(text),
+            Selector::Text(text, _, true) => e.text == *text || e.content_desc == *text,
+            Selector::Text(text, _, false) => {
+                e.text.contains(text) || e.content_desc.contains(text)
+            }
+            Selector::FuzzyText(text, _, threshold) => {
+                common::text_similarity(text, &e.text) >= *threshold
+                    || common::text_similarity(text, &e.content_desc) >= *threshold
+            }
             Selector::TextRegex(pattern, _) => {
                 if let Ok(re) = regex::Regex::new(pattern) {
                     re.is_match(&e.text) || re.is_match(&e.content_desc)
@@ -670,6 +810,7 @@ impl AndroidDriver {
                 }
             }
             Selector::Type(t, _) => e.class.contains(t),
+            Selector::Css(css) => css::CssQuery::parse(css).is_ok_and(|query| query.matches(e)),
             Selector::AccessibilityId(id) | Selector::Description(id, _) => e.content_desc == *id,
             Selector::DescriptionRegex(pattern, _) => {
                 if let Ok(re) = regex::Regex::new(pattern) {
@@ -689,7 +830,7 @@ impl AndroidDriver {
     pub async fn find_element_by_type_index(
         &self,
         element_type: &str,
-        index: u32,
+        index: i32,
     ) -> Result<Option<(i32, i32)>> {
         let elements = self.get_ui_hierarchy().await?;
         if let Some(elem) = uiautomator::find_by_type_index(&elements, element_type, index) {
@@ -702,9 +843,11 @@ impl AndroidDriver {
     /// Tap element by type and index
     pub async fn tap_at(&self, element_type: &str, index: u32) -> Result<()> {
         let (x, y) = self
-            .find_element_by_type_index(element_type, index)
+            .find_element_by_type_index(element_type, index as i32)
             .await?
-            .ok_or_else(|| anyhow::anyhow!("Element not found: {}[{}]", element_type, index))?;
+            .ok_or_else(|| TesterError::ElementNotFound {
+                selector: format!("{}[{}]", element_type, index),
+            })?;
 
         adb::shell(
             self.serial.as_deref(),
@@ -756,7 +899,7 @@ impl AndroidDriver {
     async fn find_ocr_text(
         &self,
         text: &str,
-        index: usize,
+        index: i32,
         is_regex: bool,
         region: Option<&str>,
     ) -> Result<Option<(i32, i32)>> {
@@ -879,13 +1022,13 @@ impl AndroidDriver {
             }
             _ => {
                 // Fallback to file-based method
-                let remote_path = "/sdcard/screenshot.png";
+                let remote_path = Self::remote_screenshot_path();
                 adb::shell(
                     self.serial.as_deref(),
                     &format!("screencap -p {}", remote_path),
                 )
                 .await?;
-                adb::pull(self.serial.as_deref(), remote_path, path).await?;
+                adb::pull(self.serial.as_deref(), &remote_path, path).await?;
                 adb::shell(self.serial.as_deref(), &format!("rm {}", remote_path)).await?;
                 Ok(())
             }
@@ -896,8 +1039,31 @@ impl AndroidDriver {
         common::to_ascii_fallback(text)
     }
 
+    /// On-device screenshot file, unique so concurrent sessions on one device don't race
+    fn remote_screenshot_path() -> String {
+        format!(
+            "/sdcard/lumi_screenshot_{}.png",
+            uuid::Uuid::new_v4().simple()
+        )
+    }
+
+    /// `adb install` flags for the given install params
+    fn install_flags(params: &crate::parser::types::InstallAppParams) -> Vec<&'static str> {
+        let mut flags = Vec::new();
+        if params.reinstall {
+            flags.push("-r"); // replace existing app, keeping data
+        }
+        if params.grant_all {
+            flags.push("-g"); // grant all runtime permissions
+        }
+        if params.allow_downgrade {
+            flags.push("-d"); // allow lower version code
+        }
+        flags
+    }
+
     /// Install XAPK (split APK bundle) by extracting and using install-multiple
-    async fn install_xapk(&self, xapk_path: &str) -> Result<()> {
+    async fn install_xapk(&self, xapk_path: &str, flags: &[&str]) -> Result<()> {
         use std::io::Read;
         use zip::ZipArchive;
 
@@ -945,7 +1111,8 @@ impl AndroidDriver {
         }
 
         // Build install-multiple command
-        let mut args: Vec<&str> = vec!["install-multiple", "-r", "-g"];
+        let mut args: Vec<&str> = vec!["install-multiple"];
+        args.extend_from_slice(flags);
         for apk in &apk_files {
             args.push(apk);
         }
@@ -977,6 +1144,14 @@ impl PlatformDriver for AndroidDriver {
         self.serial.clone()
     }
 
+    fn device_model(&self) -> Option<String> {
+        self.device_model.clone()
+    }
+
+    fn os_version(&self) -> Option<String> {
+        self.os_version.clone()
+    }
+
     async fn set_permissions(
         &self,
         app_id: &str,
@@ -1118,26 +1293,9 @@ impl PlatformDriver for AndroidDriver {
         }
 
         // Poll for app focus instead of fixed sleep
-        let start = Instant::now();
-        let timeout = Duration::from_secs(10);
-        let poll_interval = self.speed_profile.poll_interval_ms();
-        let mut launched = false;
-
-        while start.elapsed() < timeout {
-            // Use dumpsys activity activities which is more reliable for finding the resumed app
-            // and use simple grep to avoid compatibility issues
-            let output = adb::shell(
-                self.serial.as_deref(),
-                "dumpsys activity activities | grep ResumedActivity",
-            )
-            .await
-            .unwrap_or_default();
-            if output.contains(app_id) {
-                launched = true;
-                break;
-            }
-            tokio::time::sleep(Duration::from_millis(poll_interval)).await;
-        }
+        let launched = self
+            .wait_for_app_focus(app_id, Duration::from_secs(10))
+            .await;
 
         if !launched {
             println!(
@@ -1162,7 +1320,7 @@ impl PlatformDriver for AndroidDriver {
         let (x, y) = self
             .find_element(selector)
             .await?
-            .ok_or_else(|| anyhow::anyhow!("Element not found: {:?}", selector))?;
+            .ok_or_else(|| TesterError::element_not_found(selector))?;
 
         log::debug!("Tap coordinates: ({}, {}), selector: {:?}", x, y, selector);
 
@@ -1183,7 +1341,7 @@ impl PlatformDriver for AndroidDriver {
         let (x, y) = self
             .find_element(selector)
             .await?
-            .ok_or_else(|| anyhow::anyhow!("Element not found: {:?}", selector))?;
+            .ok_or_else(|| TesterError::element_not_found(selector))?;
 
         // Long press is simulated with swipe from same point to same point
         adb::shell(
@@ -1208,7 +1366,7 @@ impl PlatformDriver for AndroidDriver {
         let (x, y) = self
             .find_element(selector)
             .await?
-            .ok_or_else(|| anyhow::anyhow!("Element not found: {:?}", selector))?;
+            .ok_or_else(|| TesterError::element_not_found(selector))?;
 
         let prefix = self.input_prefix();
 
@@ -1401,14 +1559,33 @@ impl PlatformDriver for AndroidDriver {
         Ok(())
     }
 
-    async fn erase_text(&self, char_count: Option<u32>) -> Result<()> {
-        let count = char_count.unwrap_or(100);
+    async fn has_focused_field(&self) -> Result<bool> {
+        let elements = self.get_ui_hierarchy().await?;
+        // A focused button or list row would swallow the erase keys
+        Ok(elements.iter().any(|e| e.focused && e.is_text_input()))
+    }
 
-        // Send DEL key multiple times
+    async fn erase_text(&self, char_count: Option<u32>) -> Result<()> {
         let prefix = self.input_prefix();
-        for _ in 0..count {
-            adb::shell(self.serial.as_deref(), &format!("{} keyevent 67", prefix)).await?;
-            // KEYCODE_DEL
+        let serial = self.serial.as_deref();
+
+        if char_count.is_none() && self.sdk_version >= 33 {
+            // Whole field: Ctrl+A (KEYCODE_CTRL_LEFT + KEYCODE_A) then KEYCODE_DEL;
+            // `input keycombination` needs Android 13
+            adb::shell(serial, &format!("{} keycombination 113 29", prefix)).await?;
+            adb::shell(serial, &format!("{} keyevent 67", prefix)).await?;
+        } else {
+            // KEYCODE_MOVE_END first when erasing everything, so text after the
+            // cursor goes too; all KEYCODE_DELs are sent in one `input` call
+            let count = char_count.unwrap_or(100) as usize;
+            let mut keys = Vec::with_capacity(count + 1);
+            if char_count.is_none() {
+                keys.push("123");
+            }
+            keys.extend(std::iter::repeat_n("67", count));
+            if !keys.is_empty() {
+                adb::shell(serial, &format!("{} keyevent {}", prefix, keys.join(" "))).await?;
+            }
         }
         self.invalidate_cache().await;
 
@@ -1590,7 +1767,50 @@ impl PlatformDriver for AndroidDriver {
         Ok(false)
     }
 
+    async fn near_misses(&self, selector: &Selector) -> Vec<String> {
+        // The dump cached by the last poll, or a fresh one once it expired
+        let Ok(elements) = self.get_ui_hierarchy().await else {
+            return Vec::new();
+        };
+        let (field, needle, candidates): (&str, &str, Vec<&str>) = match selector.unwrap_on_screen()
+        {
+            Selector::Text(text, ..) | Selector::FuzzyText(text, ..) => (
+                "text",
+                text,
+                elements
+                    .iter()
+                    .flat_map(|e| [e.text.as_str(), e.content_desc.as_str()])
+                    .collect(),
+            ),
+            Selector::Id(id, _) => (
+                "id",
+                id,
+                elements
+                    .iter()
+                    .map(|e| {
+                        // Compare the short form when the selector omits the package
+                        match e.resource_id.split_once(":id/") {
+                            Some((_, short)) if !id.contains(":id/") => short,
+                            _ => e.resource_id.as_str(),
+                        }
+                    })
+                    .collect(),
+            ),
+            Selector::Description(desc, _) | Selector::AccessibilityId(desc) => (
+                "description",
+                desc,
+                elements.iter().map(|e| e.content_desc.as_str()).collect(),
+            ),
+            _ => return Vec::new(),
+        };
+        common::closest_matches(needle, candidates, NEAR_MISS_LIMIT)
+            .into_iter()
+            .map(|candidate| format!("{} \"{}\"", field, candidate))
+            .collect()
+    }
+
     async fn get_element_text(&self, selector: &Selector) -> Result<String> {
+        Self::check_selector(selector)?;
         let elements = self.get_ui_hierarchy().await?;
 
         match self.find_element_impl(selector, &elements) {
@@ -1676,7 +1896,7 @@ impl PlatformDriver for AndroidDriver {
     }
 
     async fn take_screenshot(&self, path: &str) -> Result<()> {
-        let remote_path = "/sdcard/screenshot.png";
+        let remote_path = Self::remote_screenshot_path();
 
         // Take screenshot on device
         adb::shell(
@@ -1690,7 +1910,7 @@ impl PlatformDriver for AndroidDriver {
         .await?;
 
         // Pull to local
-        adb::pull(self.serial.as_deref(), remote_path, path).await?;
+        adb::pull(self.serial.as_deref(), &remote_path, path).await?;
 
         // Cleanup
         adb::shell(self.serial.as_deref(), &format!("rm {}", remote_path)).await?;
@@ -1699,21 +1919,41 @@ impl PlatformDriver for AndroidDriver {
     }
 
     async fn start_recording(&self, path: &str) -> Result<()> {
+        self.start_recording_with_options(path, &RecordingOptions::default())
+            .await
+    }
+
+    async fn start_recording_with_options(
+        &self,
+        path: &str,
+        options: &RecordingOptions,
+    ) -> Result<()> {
         let remote_path = "/sdcard/screenrecord.mp4";
         let local_path = path.to_string();
 
+        let mut args = vec![
+            "-s".to_string(),
+            self.serial.clone().unwrap_or_default(),
+            "shell".to_string(),
+            "screenrecord".to_string(),
+            "--bit-rate".to_string(),
+            options
+                .bitrate
+                .unwrap_or(DEFAULT_RECORDING_BITRATE)
+                .to_string(),
+        ];
+        if let Some((width, height)) = options.size {
+            args.push("--size".to_string());
+            args.push(format!("{}x{}", width, height));
+        }
+        if let Some(secs) = options.time_limit_secs {
+            args.push("--time-limit".to_string());
+            args.push(secs.to_string());
+        }
+        args.push(remote_path.to_string());
+
         // Start recording in background
-        let child = tokio::process::Command::new("adb")
-            .args(&[
-                "-s",
-                self.serial.as_deref().unwrap_or(""),
-                "shell",
-                "screenrecord",
-                "--bit-rate",
-                "4000000",
-                remote_path,
-            ])
-            .spawn()?;
+        let child = tokio::process::Command::new("adb").args(&args).spawn()?;
 
         *self.recording_process.lock().await = Some(child);
         self.current_recording_path.lock().await.replace(local_path);
@@ -1802,13 +2042,11 @@ impl PlatformDriver for AndroidDriver {
         name: Option<String>,
         points: Vec<crate::parser::gps::GpsPoint>,
         speed_kmh: Option<f64>,
-        speed_mode: SpeedMode,
-        speed_noise: Option<f64>,
+        drive_profile: DriveProfile,
         interval_ms: u64,
         loop_route: bool,
     ) -> Result<()> {
         use colored::Colorize;
-        use rand::Rng;
 
         if points.is_empty() {
             anyhow::bail!("No GPS points provided for mock location");
@@ -1830,17 +2068,11 @@ impl PlatformDriver for AndroidDriver {
         let interval = std::time::Duration::from_millis(interval_ms);
 
         if let Some(speed) = speed_kmh {
-            let mode_str = match speed_mode {
-                SpeedMode::Linear => "Linear",
-                SpeedMode::Noise => format!("Noise ±{:.1}", speed_noise.unwrap_or(5.0))
-                    .to_string()
-                    .leak(),
-            };
             println!(
                 "  {} Using speed: {} km/h ({})",
                 "🚗".cyan(),
                 speed,
-                mode_str
+                drive_profile
             );
         }
 
@@ -1878,6 +2110,7 @@ impl PlatformDriver for AndroidDriver {
 
         // Spawn background task to update location
         let points_clone = points.clone();
+        let route_distances = crate::parser::gps::cumulative_distances(&points);
         let mock_states = self.mock_states.clone();
         let instance_key = instance_name.clone();
 
@@ -1891,20 +2124,16 @@ impl PlatformDriver for AndroidDriver {
             state.finished = false;
             state.paused = false;
             state.speed = speed_kmh;
-            state.speed_mode = speed_mode.clone();
-            state.speed_noise = speed_noise;
+            state.drive_profile = drive_profile.clone();
             state.total_points = points.len();
             state.current_index = 0;
 
-            // Estimate total duration based on route distance and speed
-            let mut total_dist = 0.0_f64;
-            for w in points.windows(2) {
-                total_dist +=
-                    crate::parser::gps::haversine_distance(w[0].lat, w[0].lon, w[1].lat, w[1].lon);
-            }
-            let spd = speed_kmh.unwrap_or(50.0) / 3.6; // m/s
-            state.estimated_duration_ms = if spd > 0.001 {
-                (total_dist / spd * 1000.0) as u64 + 5000 // +5s buffer
+            // Estimate total duration based on route distance, speed, ramps and stops
+            let total_dist = route_distances.last().copied().unwrap_or(0.0);
+            let spd = speed_kmh.unwrap_or(50.0);
+            state.estimated_duration_ms = if spd / 3.6 > 0.001 {
+                let secs = crate::parser::gps::estimate_route_secs(&drive_profile, spd, total_dist);
+                (secs * 1000.0) as u64 + 5000 // +5s buffer
             } else {
                 300_000 // 5 min default
             };
@@ -1989,13 +2218,10 @@ impl PlatformDriver for AndroidDriver {
                                         state.paused = paused;
                                     }
                                     // Update speed mode
-                                    if let Some(mode) =
-                                        ctrl.get("speedMode").and_then(|v| v.as_str())
-                                    {
-                                        state.speed_mode = match mode {
-                                            "noise" => SpeedMode::Noise,
-                                            _ => SpeedMode::Linear,
-                                        };
+                                    if let Some(mode) = ctrl.get("speedMode") {
+                                        state.drive_profile.mode =
+                                            serde_json::from_value(mode.clone())
+                                                .unwrap_or(SpeedMode::Linear);
                                     }
                                 }
                                 // Clear control file after reading
@@ -2014,15 +2240,15 @@ impl PlatformDriver for AndroidDriver {
                     }
 
                     // Update state
-                    let (current_speed, current_mode, _current_noise) = {
+                    let (current_speed, current_profile) = {
                         let mut states = mock_states.lock().await;
                         if let Some(state) = states.get_mut(&instance_key) {
                             state.current_lat = Some(lat);
                             state.current_lon = Some(lon);
                             state.is_running = true;
-                            (state.speed, state.speed_mode.clone(), state.speed_noise)
+                            (state.speed, state.drive_profile.clone())
                         } else {
-                            (speed_kmh, speed_mode.clone(), speed_noise)
+                            (speed_kmh, drive_profile.clone())
                         }
                     };
 
@@ -2038,17 +2264,33 @@ impl PlatformDriver for AndroidDriver {
                         0.0 // Default bearing
                     };
 
-                    // Calculate effective speed for this segment (m/s)
-                    let effective_speed_kmh = match current_mode {
-                        SpeedMode::Linear => current_speed.unwrap_or(0.0),
-                        SpeedMode::Noise => {
-                            let base = current_speed.unwrap_or(0.0);
-                            let noise_range = _current_noise.unwrap_or(5.0);
-                            let noise: f64 = rng.gen_range(-noise_range..noise_range);
-                            (base + noise).max(1.0)
-                        }
+                    // Calculate effective speed for the segment starting here, sampled at
+                    // its midpoint so ramps and stops follow the route distance
+                    let route_len = route_distances.last().copied().unwrap_or(0.0);
+                    let segment_mid = match route_distances.get(i + 1) {
+                        Some(next) => (route_distances[i] + next) / 2.0,
+                        None => route_distances[i],
+                    };
+                    let effective_speed_kmh = crate::parser::gps::profile_speed_kmh(
+                        &current_profile,
+                        current_speed.unwrap_or(0.0),
+                        segment_mid,
+                        route_len,
+                        &mut rng,
+                    );
+                    // Stops mode: halt on the first point past each stop boundary
+                    let stopping = i > 0
+                        && current_speed.is_some()
+                        && crate::parser::gps::stop_between(
+                            &current_profile,
+                            route_distances[i - 1],
+                            route_distances[i],
+                        );
+                    let speed_ms = if stopping {
+                        0.0
+                    } else {
+                        effective_speed_kmh / 3.6 // Convert km/h to m/s
                     };
-                    let speed_ms = effective_speed_kmh / 3.6; // Convert km/h to m/s
 
                     // Method 1: Use nl-android (nl-mirror) via socket - FULL SPEED SUPPORT
                     let nl_cmd = format!(
@@ -2157,26 +2399,27 @@ impl PlatformDriver for AndroidDriver {
                         let _ = adb::shell(serial.as_deref(), &geo_cmd).await;
                     }
 
+                    if stopping {
+                        let stop_ms = current_profile.stop_duration_ms();
+                        println!(
+                            "  {} Stopped at waypoint {} for {:.1}s",
+                            "🚦".yellow(),
+                            i,
+                            stop_ms as f64 / 1000.0
+                        );
+                        tokio::time::sleep(std::time::Duration::from_millis(stop_ms)).await;
+                    }
+
                     if i < points_clone.len() - 1 {
                         let next_point = &points_clone[i + 1];
-                        let delay = if let Some(base_speed) = current_speed {
-                            // Apply noise if enabled
-                            let effective_speed = match current_mode {
-                                SpeedMode::Linear => base_speed,
-                                SpeedMode::Noise => {
-                                    let noise_range = speed_noise.unwrap_or(5.0);
-                                    let noise: f64 = rng.gen_range(-noise_range..noise_range);
-                                    (base_speed + noise).max(1.0) // Minimum 1 km/h
-                                }
-                            };
-
+                        let delay = if current_speed.is_some() {
                             let dist_m = haversine_distance(
                                 point.lat,
                                 point.lon,
                                 next_point.lat,
                                 next_point.lon,
                             );
-                            let speed_ms = effective_speed / 3.6;
+                            let speed_ms = effective_speed_kmh / 3.6;
                             if speed_ms > 0.001 {
                                 (dist_m / speed_ms * 1000.0) as u64
                             } else {
@@ -2302,30 +2545,8 @@ impl PlatformDriver for AndroidDriver {
     }
 
     async fn rotate_screen(&self, mode: &str) -> Result<()> {
-        // Disable auto-rotate first
-        adb::shell(
-            self.serial.as_deref(),
-            "settings put system accelerometer_rotation 0",
-        )
-        .await?;
-
-        let rotation = match mode.to_lowercase().as_str() {
-            "portrait" => "0",
-            "landscape" => "1",
-            _ => anyhow::bail!("Invalid rotation mode. Use 'portrait' or 'landscape'"),
-        };
-
-        adb::shell(
-            self.serial.as_deref(),
-            &format!("settings put system user_rotation {}", rotation),
-        )
-        .await?;
-
-        // Wait for rotation animation
-        tokio::time::sleep(tokio::time::Duration::from_millis(1000)).await;
-        self.invalidate_cache().await;
-
-        Ok(())
+        let orientation = crate::parser::types::Orientation::from_mode(mode)?;
+        self.set_orientation(orientation).await
     }
 
     async fn press_key(&self, key: &str) -> Result<()> {
@@ -2408,6 +2629,17 @@ impl PlatformDriver for AndroidDriver {
         Ok(())
     }
 
+    async fn set_bluetooth(&self, enabled: bool) -> Result<()> {
+        let state = if enabled { "enable" } else { "disable" };
+        // `svc bluetooth` exists on most builds; older ones only have the service command
+        adb::shell(
+            self.serial.as_deref(),
+            &format!("svc bluetooth {0} || cmd bluetooth_manager {0}", state),
+        )
+        .await?;
+        Ok(())
+    }
+
     async fn toggle_airplane_mode(&self) -> Result<()> {
         // Get current state
         let output = adb::shell(
@@ -2482,24 +2714,273 @@ impl PlatformDriver for AndroidDriver {
     }
 
     async fn install_app(&self, path: &str) -> Result<()> {
+        self.install_app_with_options(&crate::parser::types::InstallAppParams::from_path(
+            path.to_string(),
+        ))
+        .await
+    }
+
+    async fn install_app_with_options(
+        &self,
+        params: &crate::parser::types::InstallAppParams,
+    ) -> Result<()> {
+        let path = params.path.as_str();
         if !std::path::Path::new(path).exists() {
             anyhow::bail!("App file not found: {}", path);
         }
 
+        let flags = Self::install_flags(params);
+
         // Check if it's an XAPK (split APK bundle)
         if path.to_lowercase().ends_with(".xapk") {
-            return self.install_xapk(path).await;
+            return self.install_xapk(path, &flags).await;
         }
 
         println!("  {} Installing app from: {}", "⬇".cyan(), path);
-        adb::exec(
+        let mut args = vec!["install"];
+        args.extend_from_slice(&flags);
+        args.push(path);
+        adb::exec(self.serial.as_deref(), &args).await?;
+        Ok(())
+    }
+
+    async fn snapshot_app_data(&self, app_id: &str, dir: &Path) -> Result<()> {
+        let serial = self.serial.as_deref();
+        let tar_cmd = format!(
+            "tar -cf - -C /data/data/{} --exclude=./lib --exclude=./cache --exclude=./code_cache .",
+            app_id
+        );
+
+        // run-as works for debuggable builds; fall back to root for release builds
+        let mut data = adb::exec_out_binary(serial, &format!("run-as {} {}", app_id, tar_cmd))
+            .await
+            .unwrap_or_default();
+        if !is_tar_archive(&data) {
+            data = adb::exec_out_binary(serial, &format!("su 0 {}", tar_cmd))
+                .await
+                .unwrap_or_default();
+        }
+        if !is_tar_archive(&data) {
+            anyhow::bail!(
+                "Cannot read data of '{}': the app must be debuggable (run-as) or the device rooted",
+                app_id
+            );
+        }
+
+        std::fs::create_dir_all(dir)?;
+        std::fs::write(dir.join(APP_DATA_ARCHIVE), &data)?;
+        Ok(())
+    }
+
+    async fn restore_app_data(&self, app_id: &str, dir: &Path) -> Result<()> {
+        let archive = dir.join(APP_DATA_ARCHIVE);
+        if !archive.exists() {
+            anyhow::bail!("No app data snapshot at {}", archive.display());
+        }
+
+        let serial = self.serial.as_deref();
+        let remote = format!("/data/local/tmp/lumi_{}_data.tar", app_id);
+        adb::shell(serial, &format!("am force-stop {}", app_id)).await?;
+        adb::push(serial, &archive.to_string_lossy(), &remote).await?;
+
+        let restore = |prefix: String, fixup: &str| {
+            format!(
+                "{prefix} sh -c 'cd /data/data/{app} && rm -rf files databases shared_prefs no_backup && tar -xf -{fixup}' < {remote}",
+                prefix = prefix,
+                app = app_id,
+                fixup = fixup,
+                remote = remote
+            )
+        };
+        // Files extracted as root must go back to the app's uid and SELinux label,
+        // otherwise the app can't open its own data
+        let root_fixup = format!(
+            " && chown -R $(stat -c %u:%g /data/data/{app}) /data/data/{app} && restorecon -R /data/data/{app}",
+            app = app_id
+        );
+        let result = match adb::shell(serial, &restore(format!("run-as {}", app_id), "")).await {
+            Ok(out) if !out.contains("not debuggable") && !out.contains("Permission denied") => {
+                Ok(())
+            }
+            _ => adb::shell(serial, &restore("su 0".to_string(), &root_fixup))
+                .await
+                .map(|_| ()),
+        };
+        let _ = adb::shell(serial, &format!("rm -f {}", remote)).await;
+
+        result.with_context(|| {
+            format!(
+                "Cannot restore data of '{}': the app must be debuggable (run-as) or the device rooted",
+                app_id
+            )
+        })
+    }
+
+    async fn get_notifications(&self) -> Result<Vec<NotificationInfo>> {
+        let output = adb::shell(self.serial.as_deref(), "dumpsys notification --noredact").await?;
+        Ok(super::notification::parse_notifications(&output))
+    }
+
+    async fn wait_for_toast(&self, text: &str, timeout_ms: u64) -> Result<bool> {
+        use tokio::io::{AsyncBufReadExt, BufReader};
+
+        let needle = text.to_lowercase();
+        let deadline = Instant::now() + Duration::from_millis(timeout_ms);
+
+        // Android 11+ never puts text toasts in a dump, but still raises an
+        // accessibility event for them. The listener starts right away, since a
+        // dump first (1-3s) could miss the toast. `uiautomator events` holds the
+        // UiAutomation connection, so it can't run alongside dumps.
+        let listener = if self.sdk_version >= 30 {
+            adb::spawn_shell(self.serial.as_deref(), "uiautomator events").ok()
+        } else {
+            None
+        };
+        if let Some(mut child) = listener {
+            if let Some(stdout) = child.stdout.take() {
+                let mut lines = BufReader::new(stdout).lines();
+                loop {
+                    let remaining = deadline.saturating_duration_since(Instant::now());
+                    if remaining.is_zero() {
+                        let _ = child.kill().await;
+                        return Ok(false);
+                    }
+                    match tokio::time::timeout(remaining, lines.next_line()).await {
+                        Ok(Ok(Some(line))) if is_toast_event(&line, &needle) => {
+                            let _ = child.kill().await;
+                            return Ok(true);
+                        }
+                        Ok(Ok(Some(_))) => {}
+                        // Process exited (unsupported) - fall back to polling
+                        Ok(_) => break,
+                        Err(_) => {
+                            let _ = child.kill().await;
+                            return Ok(false);
+                        }
+                    }
+                }
+            }
+            let _ = child.kill().await;
+        }
+
+        // Older Android versions keep the toast in the hierarchy
+        while Instant::now() < deadline {
+            self.invalidate_cache().await;
+            if let Ok(elements) = self.get_ui_hierarchy().await {
+                if elements.iter().any(|e| is_toast_element(e, &needle)) {
+                    return Ok(true);
+                }
+            }
+            tokio::time::sleep(Duration::from_millis(TOAST_POLL_INTERVAL_MS)).await;
+        }
+        Ok(false)
+    }
+
+    async fn send_sms(&self, from: &str, body: &str) -> Result<()> {
+        let serial = self.serial.as_deref().unwrap_or_default();
+        if !serial.starts_with("emulator-") {
+            anyhow::bail!(
+                "sendSms only works on emulators (device '{}' is not an emulator)",
+                serial
+            );
+        }
+        adb::exec(self.serial.as_deref(), &["emu", "sms", "send", from, body]).await?;
+        println!("  {} SMS from {}: {}", "✉".cyan(), from, body);
+        Ok(())
+    }
+
+    async fn read_latest_sms(&self) -> Result<String> {
+        let output = adb::shell(
             self.serial.as_deref(),
-            &["install", "-r", "-g", path], // -r: replace, -g: grant perms
+            "content query --uri content://sms/inbox --projection body --sort 'date DESC'",
         )
         .await?;
+        parse_latest_sms(&output).ok_or_else(|| anyhow::anyhow!("SMS inbox is empty"))
+    }
+
+    async fn set_date_time(&self, value: Option<chrono::NaiveDateTime>) -> Result<()> {
+        let serial = self.serial.as_deref();
+        let Some(dt) = value else {
+            adb::shell(serial, "settings put global auto_time 1").await?;
+            println!("  {} Restored automatic date/time", "🕒".cyan());
+            return Ok(());
+        };
+
+        adb::shell(serial, "settings put global auto_time 0").await?;
+
+        // toybox date: MMDDhhmmYYYY.ss; setting the clock needs root on most builds
+        let stamp = dt.format("%m%d%H%M%Y.%S").to_string();
+        let set = adb::shell(serial, &format!("date {}", stamp)).await;
+        if set.is_err() {
+            let _ = adb::shell(serial, &format!("su 0 date {}", stamp)).await;
+        }
+
+        let expected = dt.format("%Y-%m-%d %H:%M").to_string();
+        let actual = adb::shell(serial, "date '+%Y-%m-%d %H:%M'")
+            .await
+            .unwrap_or_default();
+        if actual.trim() != expected {
+            println!(
+                "  {} Device rejected setDateTime (needs root or an emulator); clock is {}",
+                "⚠".yellow(),
+                actual.trim()
+            );
+        } else {
+            println!("  {} Device clock set to {}", "🕒".cyan(), expected);
+        }
         Ok(())
     }
 
+    async fn handle_system_dialog(&self, button: &str) -> Result<bool> {
+        // Cheap focus check first so auto-handling doesn't dump the UI before every command
+        let focus = adb::shell(
+            self.serial.as_deref(),
+            "dumpsys window | grep mCurrentFocus",
+        )
+        .await
+        .unwrap_or_default();
+        if !is_permission_dialog_package(&focus) {
+            return Ok(false);
+        }
+
+        self.invalidate_cache().await;
+        let elements = self.get_ui_hierarchy().await?;
+        let dialog: Vec<&UiElement> = elements
+            .iter()
+            .filter(|e| is_permission_dialog_package(&e.package))
+            .collect();
+
+        let ids = permission_button_ids(button);
+        let target = if ids.is_empty() {
+            dialog.iter().find(|e| {
+                e.text.eq_ignore_ascii_case(button) || e.content_desc.eq_ignore_ascii_case(button)
+            })
+        } else {
+            ids.iter().find_map(|id| {
+                dialog
+                    .iter()
+                    .find(|e| e.resource_id.ends_with(&format!("/{}", id)))
+            })
+        };
+
+        let Some(element) = target else {
+            anyhow::bail!("System dialog is showing but has no '{}' button", button);
+        };
+        let (x, y) = element.bounds.center();
+        self.tap(&Selector::Point { x, y }).await?;
+        Ok(true)
+    }
+
+    async fn get_app_version(&self, app_id: &str) -> Result<AppVersion> {
+        let output = adb::shell(
+            self.serial.as_deref(),
+            &format!("dumpsys package {} | grep version", app_id),
+        )
+        .await?;
+        parse_dumpsys_version(&output)
+            .ok_or_else(|| anyhow::anyhow!("App '{}' is not installed", app_id))
+    }
+
     async fn uninstall_app(&self, app_id: &str) -> Result<()> {
         println!("  {} Uninstalling app: {}", "🗑".cyan(), app_id);
         adb::exec(self.serial.as_deref(), &["uninstall", app_id]).await?;
@@ -2528,6 +3009,10 @@ impl PlatformDriver for AndroidDriver {
         Ok(())
     }
 
+    async fn is_app_in_foreground(&self, app_id: &str) -> Result<bool> {
+        Ok(self.app_has_focus(app_id).await)
+    }
+
     async fn set_orientation(&self, mode: crate::parser::types::Orientation) -> Result<()> {
         use crate::parser::types::Orientation;
 
@@ -2564,7 +3049,7 @@ impl PlatformDriver for AndroidDriver {
         name: Option<String>,
         lat: f64,
         lon: f64,
-        tolerance: f64,
+        tolerance: LocationTolerance,
         timeout: u64,
     ) -> Result<()> {
         // Call inherent method
@@ -2712,36 +3197,168 @@ impl PlatformDriver for AndroidDriver {
         Ok(())
     }
 
-    async fn set_network_conditions(&self, profile: &str) -> Result<()> {
-        match profile.to_lowercase().as_str() {
-            "offline" => {
-                self.set_network_connection(Some(false), Some(false))
-                    .await?;
-            }
-            "wifi" | "wifi-only" => {
-                self.set_network_connection(Some(true), Some(false)).await?;
-            }
-            "data" | "mobile" | "4g" | "5g" | "lte" => {
-                self.set_network_connection(Some(false), Some(true)).await?;
+    async fn set_network_conditions(&self, conditions: &NetworkConditions) -> Result<()> {
+        if conditions.offline {
+            return self.set_network_connection(Some(false), Some(false)).await;
+        }
+        // Named radio profiles keep switching Wi-Fi/mobile data
+        let radios = match conditions.name.as_str() {
+            "wifi" | "wifi-only" => Some((Some(true), Some(false))),
+            "data" | "mobile" | "4g" | "lte" | "5g" => Some((Some(false), Some(true))),
+            "online" | "none" => Some((Some(true), None)),
+            _ => None,
+        };
+
+        let serial = self.serial.as_deref().unwrap_or_default();
+        let emulator = serial.starts_with("emulator-");
+        // On physical devices `4g`/`lte` only pick the radio: the real network is already 4G
+        let shape = conditions.is_throttled() && (emulator || radios.is_none());
+
+        // Check for root before touching the radios so a failing profile changes nothing
+        if shape && !emulator {
+            let root = adb::shell(self.serial.as_deref(), "su -c id 2>/dev/null")
+                .await
+                .unwrap_or_default();
+            if !root.contains("uid=0") {
+                anyhow::bail!(
+                    "Network profile '{}' needs an emulator or a rooted device: traffic shaping runs `tc` through `su`, which is not available on {}",
+                    conditions.name,
+                    serial
+                );
             }
-            _ => {
+        }
+
+        if let Some((wifi, data)) = radios {
+            self.set_network_connection(wifi, data).await?;
+        }
+
+        if emulator {
+            // Emulator console: `network speed <up>:<down>` (kbps) and `network delay <min>:<max>` (ms)
+            let speed = match (conditions.upload_kbps, conditions.download_kbps) {
+                (None, None) => "full".to_string(),
+                (up, down) => format!(
+                    "{}:{}",
+                    up.map_or(0, |v| v.round() as u64),
+                    down.map_or(0, |v| v.round() as u64)
+                ),
+            };
+            let delay = match conditions.latency_ms {
+                0 => "none".to_string(),
+                ms => format!("{}:{}", ms, ms),
+            };
+            adb::exec(self.serial.as_deref(), &["emu", "network", "speed", &speed]).await?;
+            adb::exec(self.serial.as_deref(), &["emu", "network", "delay", &delay]).await?;
+            if conditions.packet_loss > 0.0 {
                 println!(
-                    "  {} Unknown network profile '{}', defaulting to wifi on",
+                    "  {} Packet loss is not supported by the emulator console, ignoring {}%",
                     "⚠".yellow(),
-                    profile
+                    conditions.packet_loss
                 );
-                self.set_network_connection(Some(true), None).await?;
             }
+            return Ok(());
+        }
+
+        // Physical devices: traffic control, needs root. Earlier shaping is cleared on
+        // every interface (best-effort) since the radios may have switched since.
+        adb::shell(
+            self.serial.as_deref(),
+            "su -c 'for dev in /sys/class/net/*; do tc qdisc del dev ${dev##*/} root; done' 2>/dev/null; true",
+        )
+        .await
+        .ok();
+        if !shape {
+            return Ok(());
+        }
+        let interface = self.active_network_interface().await;
+        let tc = {
+            let mut netem = format!("delay {}ms", conditions.latency_ms);
+            if conditions.packet_loss > 0.0 {
+                netem.push_str(&format!(" loss {}%", conditions.packet_loss));
+            }
+            // netem only shapes egress, so only the upload rate can be enforced
+            if let Some(up) = conditions.upload_kbps {
+                netem.push_str(&format!(" rate {}kbit", up.round() as u64));
+            }
+            format!("tc qdisc replace dev {} root netem {}", interface, netem)
+        };
+        if let Some(down) = conditions.download_kbps {
+            println!(
+                "  {} Download limit of {}kbps is not enforced on physical devices (netem shapes upload only)",
+                "⚠".yellow(),
+                down
+            );
         }
+        adb::shell(self.serial.as_deref(), &format!("su -c '{}'", tc))
+            .await
+            .with_context(|| format!("Failed to apply network profile '{}'", conditions.name))?;
         Ok(())
     }
 
     async fn select_display(&self, display_id: u32) -> Result<()> {
+        // Switching back to the main display removes any simulated overlay display
+        if display_id == 0 {
+            let _ = adb::shell(
+                self.serial.as_deref(),
+                &format!("settings delete global {}", OVERLAY_DISPLAY_SETTING),
+            )
+            .await;
+            println!(
+                "  {} Removed overlay display and switched to main display",
+                "🧹".cyan()
+            );
+        }
         self.display_id.store(display_id, Ordering::Relaxed);
         println!("  {} Selected Display ID: {}", "📺".cyan(), display_id);
         Ok(())
     }
 
+    async fn select_auto_display(&self) -> Result<u32> {
+        println!("  {} Auto-detecting secondary display...", "🔍".cyan());
+
+        // 1. Prefer an existing Android Auto display
+        if let Some(id) = self.detect_android_auto_display().await? {
+            println!("  {} Selected found display ID: {}", "📺".green(), id);
+            self.select_display(id).await?;
+            return Ok(id);
+        }
+
+        // 2. Otherwise simulate one with an overlay display
+        println!(
+            "  {} No suitable display found, creating overlay...",
+            "⚠️".yellow()
+        );
+        adb::shell(
+            self.serial.as_deref(),
+            &format!(
+                "settings put global {} {}",
+                OVERLAY_DISPLAY_SETTING, OVERLAY_DISPLAY_SPEC
+            ),
+        )
+        .await?;
+        tokio::time::sleep(Duration::from_millis(1500)).await;
+
+        let id = match self.detect_android_auto_display().await? {
+            Some(id) => {
+                println!(
+                    "  {} Created and selected overlay display ID: {}",
+                    "📺".green(),
+                    id
+                );
+                id
+            }
+            None => {
+                // Overlay displays usually get the next free ID
+                println!(
+                    "  {} Created overlay display and selected Display 2 (fallback)",
+                    "📺".green()
+                );
+                2
+            }
+        };
+        self.select_display(id).await?;
+        Ok(id)
+    }
+
     async fn set_locale(&self, locale: &str) -> Result<()> {
         // Android: use adb shell to set system locale
         // Format: en-US, vi-VN, ja-JP, etc.
@@ -2905,6 +3522,10 @@ impl PlatformDriver for AndroidDriver {
         Ok(())
     }
 
+    async fn is_audio_playing(&self, app_id: Option<&str>) -> Result<bool> {
+        super::audio_service::AudioService::is_playing(self.serial.as_deref(), app_id).await
+    }
+
     /// Auto-detect Android Auto display by parsing activity and display info
     async fn detect_android_auto_display(&self) -> Result<Option<u32>> {
         // Strategy 1: Check dumpsys activity activities for display with running gearhead activity
@@ -3025,7 +3646,7 @@ impl PlatformDriver for AndroidDriver {
 }
 
 /// Map common element type aliases to Android widget classes
-fn map_android_type(t: &str) -> &str {
+pub(super) fn map_android_type(t: &str) -> &str {
     match t.to_lowercase().as_str() {
         "input" | "edittext" | "textfield" => "android.widget.EditText",
         "button" | "btn" => "android.widget.Button",
@@ -3037,12 +3658,20 @@ fn map_android_type(t: &str) -> &str {
 }
 
 impl AndroidDriver {
+    /// Interface carrying the default route (`wlan0`, `rmnet_data0`, ...), `wlan0` when unknown
+    async fn active_network_interface(&self) -> String {
+        let route = adb::shell(self.serial.as_deref(), "ip route get 8.8.8.8")
+            .await
+            .unwrap_or_default();
+        route_interface(&route).unwrap_or("wlan0").to_string()
+    }
+
     async fn wait_for_location(
         &self,
         name: Option<String>,
         lat: f64,
         lon: f64,
-        tolerance: f64,
+        tolerance: LocationTolerance,
         timeout_ms: u64,
     ) -> Result<()> {
         let start = Instant::now();
@@ -3050,7 +3679,7 @@ impl AndroidDriver {
         let instance_key = name.unwrap_or_default();
 
         println!(
-            "  {} Waiting for location ({:.4}, {:.4}) within {:.1}m...",
+            "  {} Waiting for location ({:.4}, {:.4}) within {}...",
             "⏳".cyan(),
             lat,
             lon,
@@ -3075,7 +3704,7 @@ impl AndroidDriver {
 
             if let Some((c_lat, c_lon)) = current_pos {
                 let dist = haversine_distance(c_lat, c_lon, lat, lon);
-                if dist <= tolerance {
+                if tolerance.contains((c_lat, c_lon), (lat, lon)) {
                     println!(
                         "  {} Reached location ({:.4}, {:.4}). Distance: {:.1}m",
                         "✅".green(),
@@ -3182,11 +3811,11 @@ impl AndroidDriver {
         }
 
         if let Some(mode) = speed_mode {
-            state.speed_mode = mode;
+            state.drive_profile.mode = mode;
         }
 
         if let Some(noise) = speed_noise {
-            state.speed_noise = Some(noise);
+            state.drive_profile.noise = Some(noise);
         }
 
         if pause == Some(true) {
@@ -3315,3 +3944,111 @@ fn char_to_keycode(c: char) -> Option<(u32, bool)> {
         _ => None,
     }
 }
+
+/// File name of the app data archive inside a snapshot directory
+const APP_DATA_ARCHIVE: &str = "data.tar";
+
+/// Whether bytes look like a tar archive (ustar magic at offset 257)
+fn is_tar_archive(data: &[u8]) -> bool {
+    data.len() > 262 && &data[257..262] == b"ustar"
+}
+
+/// Poll interval when falling back to hierarchy dumps for toasts
+const TOAST_POLL_INTERVAL_MS: u64 = 100;
+
+/// Whether a dumped element is a toast message containing `needle` (lowercase)
+fn is_toast_element(e: &UiElement, needle: &str) -> bool {
+    (e.class.contains("Toast") || e.resource_id == "android:id/message")
+        && e.text.to_lowercase().contains(needle)
+}
+
+/// Whether a `uiautomator events` line is a toast containing `needle` (lowercase)
+fn is_toast_event(line: &str, needle: &str) -> bool {
+    line.contains("TYPE_NOTIFICATION_STATE_CHANGED")
+        && line.contains("Toast")
+        && line.to_lowercase().contains(needle)
+}
+
+/// Whether a window/package name belongs to the runtime permission dialog
+fn is_permission_dialog_package(name: &str) -> bool {
+    name.contains("permissioncontroller") || name.contains("packageinstaller")
+}
+
+/// Permission dialog button ids for `allow`/`deny`, in preference order
+fn permission_button_ids(button: &str) -> &'static [&'static str] {
+    match button.to_lowercase().as_str() {
+        "allow" => &[
+            "permission_allow_foreground_only_button",
+            "permission_allow_button",
+            "permission_allow_all_button",
+            "permission_allow_one_time_button",
+        ],
+        "deny" => &[
+            "permission_deny_button",
+            "permission_deny_and_dont_ask_again_button",
+        ],
+        _ => &[],
+    }
+}
+
+/// Parse versionName/versionCode from `dumpsys package` output
+fn parse_dumpsys_version(output: &str) -> Option<AppVersion> {
+    let field = |name: &str| {
+        output
+            .split_whitespace()
+            .find_map(|tok| tok.strip_prefix(name))
+            .map(|v| v.to_string())
+    };
+
+    Some(AppVersion {
+        version: field("versionName=")?,
+        build: field("versionCode="),
+    })
+}
+
+/// Body of the first row of `content query --projection body`. Rows look like
+/// "Row: 0 body=Your code is 123456"; a multi-line body continues on the
+/// following lines until the next row.
+fn parse_latest_sms(output: &str) -> Option<String> {
+    let output = output.replace("\r\n", "\n");
+    let (_, rest) = output.split_once("body=")?;
+    let body = rest.split_once("\nRow: ").map_or(rest, |(body, _)| body);
+    Some(body.trim().to_string())
+}
+
+/// Device name in `ip route get` output ("8.8.8.8 via 10.0.0.1 dev wlan0 src ...")
+fn route_interface(output: &str) -> Option<&str> {
+    let mut tokens = output.split_whitespace();
+    tokens.find(|tok| *tok == "dev")?;
+    tokens.next()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_route_interface() {
+        let output =
+            "8.8.8.8 via 10.71.0.1 dev rmnet_data1 table 1003 src 10.71.0.8 uid 2000\n    cache\n";
+        assert_eq!(route_interface(output), Some("rmnet_data1"));
+        assert_eq!(
+            route_interface("RTNETLINK answers: Network is unreachable"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_parse_latest_sms_keeps_multiline_body() {
+        let output = "Row: 0 body=Your code is 123456\nValid for 5 minutes\nRow: 1 body=Older\n";
+        assert_eq!(
+            parse_latest_sms(output).unwrap(),
+            "Your code is 123456\nValid for 5 minutes"
+        );
+        assert_eq!(
+            parse_latest_sms("Row: 0 body=Hi\r\nthere\r\n").unwrap(),
+            "Hi\nthere"
+        );
+        assert_eq!(parse_latest_sms("No result found.\n"), None);
+    }
+}
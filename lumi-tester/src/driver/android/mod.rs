@@ -1,7 +1,9 @@
 pub mod adb;
 pub mod audio_service;
+pub mod css;
 pub mod driver;
 pub mod mirror_service;
+pub mod notification;
 pub mod uiautomator;
 
 pub use driver::AndroidDriver;
@@ -0,0 +1,80 @@
+use crate::driver::traits::NotificationInfo;
+
+/// Parse posted notifications from `dumpsys notification --noredact`
+pub fn parse_notifications(output: &str) -> Vec<NotificationInfo> {
+    let mut notifications = Vec::new();
+    let mut current: Option<NotificationInfo> = None;
+
+    for line in output.lines() {
+        let line = line.trim();
+
+        if line.starts_with("NotificationRecord(") {
+            if let Some(n) = current.take() {
+                notifications.push(n);
+            }
+            let app = line
+                .split_whitespace()
+                .find_map(|tok| tok.strip_prefix("pkg="))
+                .unwrap_or_default()
+                .to_string();
+            current = Some(NotificationInfo {
+                app,
+                ..Default::default()
+            });
+            continue;
+        }
+
+        let Some(n) = current.as_mut() else {
+            continue;
+        };
+        if let Some(value) = line.strip_prefix("android.title=") {
+            n.title = extra_value(value);
+        } else if let Some(value) = line.strip_prefix("android.text=") {
+            n.text = extra_value(value);
+        }
+    }
+
+    if let Some(n) = current {
+        notifications.push(n);
+    }
+    notifications
+}
+
+/// Extract the text from an extras value like `String (Hello)`
+fn extra_value(value: &str) -> String {
+    match (value.find('('), value.rfind(')')) {
+        (Some(start), Some(end)) if end > start => value[start + 1..end].to_string(),
+        _ => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_notifications() {
+        let dump = r#"
+  Notification List:
+    NotificationRecord(0x0f3a2b1c: pkg=com.example.chat user=UserHandle{0} id=7 tag=null importance=4 key=0|com.example.chat|7|null|10150: Notification(channel=messages))
+      uid=10150 userId=0
+      extras={
+        android.title=String (Alice)
+        android.text=SpannableString (See you at 5 (maybe))
+        android.showChronometer=Boolean (false)
+      }
+    NotificationRecord(0x01b2c3d4: pkg=android user=UserHandle{-1} id=17 tag=null importance=1 key=-1|android|17|null|1000: Notification(channel=DEVELOPER))
+      extras={
+        android.title=String (USB debugging connected)
+      }
+"#;
+
+        let parsed = parse_notifications(dump);
+        assert_eq!(parsed.len(), 2);
+        assert_eq!(parsed[0].app, "com.example.chat");
+        assert_eq!(parsed[0].title, "Alice");
+        assert_eq!(parsed[0].text, "See you at 5 (maybe)");
+        assert_eq!(parsed[1].app, "android");
+        assert_eq!(parsed[1].text, "");
+    }
+}
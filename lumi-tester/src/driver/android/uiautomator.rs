@@ -1,4 +1,5 @@
-use crate::driver::traits::RelativeDirection;
+use crate::driver::common::text_similarity;
+use crate::driver::traits::{NthMatch, RelativeDirection};
 use anyhow::Result;
 use quick_xml::events::Event;
 use quick_xml::Reader;
@@ -47,7 +48,7 @@ fn decode_html_entities(s: &str) -> String {
 }
 
 /// Represents a UI element from the view hierarchy
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct UiElement {
     pub class: String,
     pub text: String,
@@ -57,10 +58,35 @@ pub struct UiElement {
     pub clickable: bool,
     pub enabled: bool,
     pub focusable: bool,
+    /// Has input focus (the field the keyboard types into)
+    pub focused: bool,
     pub hint: String,
     pub scrollable: bool,
     pub index: String,
     pub package: String, // Added field
+    /// `visible-to-user` attribute (true when the dump doesn't report it)
+    pub visible_to_user: bool,
+}
+
+impl UiElement {
+    /// Whether the element is reported visible and its center lies inside the screen
+    pub fn is_on_screen(&self, (width, height): (u32, u32)) -> bool {
+        let (x, y) = self.bounds.center();
+        self.visible_to_user
+            && self.bounds.right > self.bounds.left
+            && self.bounds.bottom > self.bounds.top
+            && x >= 0
+            && y >= 0
+            && (x as u32) < width
+            && (y as u32) < height
+    }
+
+    /// Whether the element takes typed text: `EditText` and its subclasses
+    /// (`AppCompatEditText`, `TextInputEditText`, `AutoCompleteTextView`, ...)
+    pub fn is_text_input(&self) -> bool {
+        let name = self.class.rsplit(['.', '$']).next().unwrap_or(&self.class);
+        name.ends_with("EditText") || name.contains("AutoComplete")
+    }
 }
 
 #[derive(Debug, Clone, Default)]
@@ -115,9 +141,14 @@ pub fn parse_hierarchy(xml: &str) -> Result<Vec<UiElement>> {
     reader.trim_text(true);
 
     let mut buf = Vec::new();
+    // A dump cut off mid-write still parses up to the cut; only the closing
+    // root tag proves the whole tree was read
+    let mut complete = false;
 
     loop {
         match reader.read_event_into(&mut buf) {
+            Ok(Event::End(ref e)) if e.name().as_ref() == b"hierarchy" => complete = true,
+            Ok(Event::Empty(ref e)) if e.name().as_ref() == b"hierarchy" => complete = true,
             Ok(Event::Start(ref e)) | Ok(Event::Empty(ref e)) => {
                 if e.name().as_ref() == b"node" {
                     let mut element = UiElement {
@@ -129,10 +160,12 @@ pub fn parse_hierarchy(xml: &str) -> Result<Vec<UiElement>> {
                         clickable: false,
                         enabled: true,
                         focusable: false,
+                        focused: false,
                         hint: String::new(),
                         scrollable: false,
                         index: String::new(),
                         package: String::new(),
+                        visible_to_user: true,
                     };
 
                     for attr in e.attributes().filter_map(|a| a.ok()) {
@@ -152,10 +185,12 @@ pub fn parse_hierarchy(xml: &str) -> Result<Vec<UiElement>> {
                             "clickable" => element.clickable = value == "true",
                             "enabled" => element.enabled = value == "true",
                             "focusable" => element.focusable = value == "true",
+                            "focused" => element.focused = value == "true",
                             "hint" => element.hint = decode_html_entities(&value),
                             "scrollable" => element.scrollable = value == "true",
                             "index" => element.index = value.to_string(),
                             "package" => element.package = value.to_string(),
+                            "visible-to-user" => element.visible_to_user = value != "false",
                             _ => {}
                         }
                     }
@@ -164,15 +199,22 @@ pub fn parse_hierarchy(xml: &str) -> Result<Vec<UiElement>> {
                 }
             }
             Ok(Event::Eof) => break,
-            Err(e) => {
-                eprintln!("XML parse error: {:?}", e);
-                break;
-            }
+            Err(e) => anyhow::bail!(
+                "Malformed UI hierarchy at byte {}: {}",
+                reader.buffer_position(),
+                e
+            ),
             _ => {}
         }
         buf.clear();
     }
 
+    if !complete {
+        anyhow::bail!(
+            "Incomplete UI hierarchy ({} nodes before the dump ended)",
+            elements.len()
+        );
+    }
     Ok(elements)
 }
 
@@ -207,7 +249,7 @@ fn normalize_text(s: &str) -> String {
 pub fn find_nth_by_text_contains<'a>(
     elements: &'a [UiElement],
     text: &str,
-    index: u32,
+    index: i32,
 ) -> Option<&'a UiElement> {
     let text_norm = normalize_text(text);
     elements
@@ -216,7 +258,7 @@ pub fn find_nth_by_text_contains<'a>(
             normalize_text(&e.text).contains(&text_norm)
                 || normalize_text(&e.content_desc).contains(&text_norm)
         })
-        .nth(index as usize)
+        .nth_match(index)
 }
 
 /// Find all elements by class type (e.g., "EditText", "Button")
@@ -227,22 +269,22 @@ pub fn find_all_by_type<'a>(elements: &'a [UiElement], class_type: &str) -> Vec<
         .collect()
 }
 
-/// Find element by class type and index (0-based)
+/// Find element by class type and index (0-based, negative counts from the end)
 pub fn find_by_type_index<'a>(
     elements: &'a [UiElement],
     class_type: &str,
-    index: u32,
+    index: i32,
 ) -> Option<&'a UiElement> {
     find_all_by_type(elements, class_type)
-        .get(index as usize)
-        .copied()
+        .into_iter()
+        .nth_match(index)
 }
 
 /// Find nth element matching text (with case-insensitive fallback)
 pub fn find_nth_by_text<'a>(
     elements: &'a [UiElement],
     text: &str,
-    index: u32,
+    index: i32,
 ) -> Option<&'a UiElement> {
     let text_norm = normalize_text(text);
 
@@ -254,7 +296,7 @@ pub fn find_nth_by_text<'a>(
                 || normalize_text(&e.content_desc) == text_norm
                 || normalize_text(&e.hint) == text_norm
         })
-        .nth(index as usize);
+        .nth_match(index);
 
     if exact_match.is_some() {
         return exact_match;
@@ -269,14 +311,14 @@ pub fn find_nth_by_text<'a>(
                 || normalize_text(&e.content_desc).to_lowercase() == text_lower
                 || normalize_text(&e.hint).to_lowercase() == text_lower
         })
-        .nth(index as usize)
+        .nth_match(index)
 }
 
 /// Find nth element matching text (exact match only, no fallback)
 pub fn find_nth_by_text_exact<'a>(
     elements: &'a [UiElement],
     text: &str,
-    index: u32,
+    index: i32,
 ) -> Option<&'a UiElement> {
     let text_norm = normalize_text(text);
     elements
@@ -286,7 +328,27 @@ pub fn find_nth_by_text_exact<'a>(
                 || normalize_text(&e.content_desc) == text_norm
                 || normalize_text(&e.hint) == text_norm
         })
-        .nth(index as usize)
+        .nth_match(index)
+}
+
+/// Find nth element by similarity of its text or content-desc to `text`: elements
+/// at least `threshold` similar, most similar first (`fuzzy: true`)
+pub fn find_nth_by_fuzzy_text<'a>(
+    elements: &'a [UiElement],
+    text: &str,
+    threshold: f64,
+    index: i32,
+) -> Option<&'a UiElement> {
+    let mut ranked: Vec<(f64, &UiElement)> = elements
+        .iter()
+        .filter_map(|e| {
+            let score = text_similarity(text, &e.text).max(text_similarity(text, &e.content_desc));
+            (score >= threshold).then_some((score, e))
+        })
+        .collect();
+    // Stable: equally similar elements keep their hierarchy order
+    ranked.sort_by(|a, b| b.0.total_cmp(&a.0));
+    ranked.into_iter().map(|(_, e)| e).nth_match(index)
 }
 
 /// Find element matching regex pattern on text or content description
@@ -332,19 +394,19 @@ pub fn find_all_by_regex<'a>(elements: &'a [UiElement], pattern: &str) -> Vec<&'
 pub fn find_nth_by_id<'a>(
     elements: &'a [UiElement],
     id: &str,
-    index: u32,
+    index: i32,
 ) -> Option<&'a UiElement> {
     elements
         .iter()
         .filter(|e| e.resource_id == id || e.resource_id.ends_with(&format!("/{}", id)))
-        .nth(index as usize)
+        .nth_match(index)
 }
 
 /// Find nth element matching regex pattern
 pub fn find_nth_by_regex<'a>(
     elements: &'a [UiElement],
     pattern: &str,
-    index: u32,
+    index: i32,
 ) -> Option<&'a UiElement> {
     match Regex::new(pattern) {
         Ok(re) => elements
@@ -352,7 +414,7 @@ pub fn find_nth_by_regex<'a>(
             .filter(|e| {
                 re.is_match(&e.text) || re.is_match(&e.content_desc) || re.is_match(&e.hint)
             })
-            .nth(index as usize),
+            .nth_match(index),
         Err(e) => {
             eprintln!("Invalid regex '{}': {}", pattern, e);
             None
@@ -365,6 +427,7 @@ pub fn find_relative<'a>(
     anchor: &UiElement,
     direction: RelativeDirection,
     max_dist: Option<u32>,
+    angle: Option<f32>,
 ) -> Vec<&'a UiElement> {
     // Use i32::MAX as default limit to avoid overflow when casting u32::MAX to i32
     let limit = max_dist.map(|d| d as i32).unwrap_or(i32::MAX);
@@ -421,6 +484,15 @@ pub fn find_relative<'a>(
                 return None;
             }
 
+            // Angular tolerance: center-to-center line must stay close to the direction axis
+            if let Some(tol) = angle {
+                let (ax, ay) = anchor.bounds.center();
+                let (cx, cy) = candidate.bounds.center();
+                if !direction.within_angle((cx - ax) as f64, (cy - ay) as f64, tol) {
+                    return None;
+                }
+            }
+
             // Calculate bounds-based distance (edge-to-edge gap)
             let edge_dist = match direction {
                 RelativeDirection::RightOf => candidate.bounds.left - anchor.bounds.right,
@@ -522,13 +594,13 @@ pub fn find_relative<'a>(
 pub fn find_nth_by_id_regex<'a>(
     elements: &'a [UiElement],
     pattern: &str,
-    index: u32,
+    index: i32,
 ) -> Option<&'a UiElement> {
     match Regex::new(pattern) {
         Ok(re) => elements
             .iter()
             .filter(|e| re.is_match(&e.resource_id))
-            .nth(index as usize),
+            .nth_match(index),
         Err(e) => {
             eprintln!("Invalid regex '{}': {}", pattern, e);
             None
@@ -587,13 +659,13 @@ pub fn find_parent_with_child<'a>(
 pub fn find_nth_by_description_regex<'a>(
     elements: &'a [UiElement],
     pattern: &str,
-    index: u32,
+    index: i32,
 ) -> Option<&'a UiElement> {
     match Regex::new(pattern) {
         Ok(re) => elements
             .iter()
             .filter(|e| re.is_match(&e.content_desc))
-            .nth(index as usize),
+            .nth_match(index),
         Err(e) => {
             eprintln!("Invalid regex '{}': {}", pattern, e);
             None
@@ -671,4 +743,139 @@ mod tests {
         assert_eq!(elements.len(), 1);
         assert_eq!(elements[0].text, "Security\nSafe");
     }
+
+    #[test]
+    fn test_parse_hierarchy_reads_focus() {
+        let xml = r#"<?xml version='1.0'?><hierarchy><node class="android.widget.EditText" text="old" bounds="[0,0][100,50]" focusable="true" focused="true"/><node class="android.widget.EditText" text="" bounds="[0,60][100,110]" focusable="true" focused="false"/></hierarchy>"#;
+        let elements = parse_hierarchy(xml).unwrap();
+        assert!(elements[0].focused);
+        assert!(!elements[1].focused);
+    }
+
+    #[test]
+    fn test_is_text_input() {
+        let with_class = |class: &str| UiElement {
+            class: class.to_string(),
+            ..Default::default()
+        };
+        assert!(with_class("android.widget.EditText").is_text_input());
+        assert!(
+            with_class("com.google.android.material.textfield.TextInputEditText").is_text_input()
+        );
+        assert!(with_class("android.widget.SearchView$SearchAutoComplete").is_text_input());
+        assert!(!with_class("android.widget.Button").is_text_input());
+        assert!(!with_class("android.widget.TextView").is_text_input());
+    }
+
+    fn element(text: &str, left: i32, top: i32, right: i32, bottom: i32) -> UiElement {
+        UiElement {
+            class: "android.widget.TextView".to_string(),
+            text: text.to_string(),
+            resource_id: String::new(),
+            content_desc: String::new(),
+            bounds: Bounds {
+                left,
+                top,
+                right,
+                bottom,
+            },
+            clickable: true,
+            enabled: true,
+            focusable: false,
+            focused: false,
+            hint: String::new(),
+            scrollable: false,
+            index: "0".to_string(),
+            package: String::new(),
+            visible_to_user: true,
+        }
+    }
+
+    #[test]
+    fn test_find_relative_angle_tolerance() {
+        let anchor = element("Name", 0, 100, 200, 150);
+        let aligned = element("Value", 300, 100, 500, 150);
+        let diagonal = element("Other", 250, 300, 450, 350);
+        let candidates = vec![&aligned, &diagonal];
+
+        let all = find_relative(
+            candidates.clone(),
+            &anchor,
+            RelativeDirection::RightOf,
+            None,
+            None,
+        );
+        assert_eq!(all.len(), 2);
+
+        let narrow = find_relative(
+            candidates,
+            &anchor,
+            RelativeDirection::RightOf,
+            None,
+            Some(20.0),
+        );
+        assert_eq!(narrow.len(), 1);
+        assert_eq!(narrow[0].text, "Value");
+    }
+
+    #[test]
+    fn test_negative_index_counts_from_last_match() {
+        let elements = vec![
+            element("Item", 0, 0, 100, 50),
+            element("Item", 0, 60, 100, 110),
+            element("Item", 0, 120, 100, 170),
+        ];
+
+        let last = find_nth_by_text(&elements, "Item", -1).unwrap();
+        assert_eq!(last.bounds.top, 120);
+        let second_last = find_nth_by_text_exact(&elements, "Item", -2).unwrap();
+        assert_eq!(second_last.bounds.top, 60);
+        assert!(find_nth_by_text(&elements, "Item", -4).is_none());
+        assert!(find_by_type_index(&elements, "TextView", -3).is_some());
+        assert!(find_nth_by_text(&elements, "Item", 3).is_none());
+    }
+
+    #[test]
+    fn test_find_nth_by_fuzzy_text() {
+        let elements = vec![
+            element("Sign up", 0, 0, 100, 100),
+            element("Logins", 0, 100, 100, 200),
+            element("Log In", 0, 200, 100, 300),
+        ];
+        let find = |text, threshold, index| {
+            find_nth_by_fuzzy_text(&elements, text, threshold, index).map(|e| e.text.as_str())
+        };
+        assert_eq!(find("Login", 0.8, 0), Some("Log In"));
+        assert_eq!(find("Login", 0.8, 1), Some("Logins"));
+        assert_eq!(find("Login", 0.9, 1), None);
+        assert_eq!(find("Checkout", 0.8, 0), None);
+    }
+
+    #[test]
+    fn test_parse_hierarchy_rejects_truncated_dump() {
+        let xml = r#"<?xml version='1.0'?><hierarchy><node class="Button" text="OK" bounds="[0,0][100,100]"/><node class="Button" te"#;
+        assert!(parse_hierarchy(xml).is_err());
+        let cut_between_nodes = r#"<?xml version='1.0'?><hierarchy><node class="Button" text="OK" bounds="[0,0][100,100]"/>"#;
+        assert!(parse_hierarchy(cut_between_nodes).is_err());
+        assert!(parse_hierarchy("ERROR: could not get idle state.").is_err());
+        assert_eq!(
+            parse_hierarchy(r#"<?xml version='1.0'?><hierarchy rotation="0"/>"#)
+                .unwrap()
+                .len(),
+            0
+        );
+    }
+
+    #[test]
+    fn test_is_on_screen() {
+        let xml = r#"<?xml version='1.0'?><hierarchy><node class="TextView" text="Hidden" bounds="[0,100][100,200]" visible-to-user="false"/><node class="TextView" text="Shown" bounds="[0,100][100,200]"/></hierarchy>"#;
+        let elements = parse_hierarchy(xml).unwrap();
+        let screen = (1080, 2340);
+        assert!(!elements[0].is_on_screen(screen));
+        assert!(elements[1].is_on_screen(screen));
+
+        assert!(!element("Below fold", 0, 2400, 100, 2500).is_on_screen(screen));
+        assert!(!element("Scrolled up", 0, -300, 100, -100).is_on_screen(screen));
+        assert!(!element("Empty", 50, 50, 50, 50).is_on_screen(screen));
+    }
 }
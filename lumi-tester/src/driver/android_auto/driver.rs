@@ -1,5 +1,10 @@
 // Android Auto DHU Driver
-// Uses Desktop Head Unit (DHU) console commands for input
+// Uses Desktop Head Unit (DHU) console commands for taps, dpad and keys when the DHU
+// is connected. Everything else goes through the phone driver with the car display
+// selected: screenshots (`screencap -d`), text input and keys (`input -d`), app lifecycle.
+// `uiautomator dump` has no display option, so selector lookups (is_visible,
+// wait_for_element, wait_for_absence, get_element_text, scroll_until_visible, tap by
+// text/id), long press and right click are unsupported and fail loudly.
 
 use anyhow::Result;
 use async_trait::async_trait;
@@ -10,8 +15,9 @@ use std::sync::Arc;
 use tokio::io::AsyncWriteExt;
 use tokio::sync::Mutex;
 
-use crate::driver::android::adb;
-use crate::driver::traits::{PlatformDriver, Selector, SwipeDirection};
+use crate::driver::android::driver::SpeedProfile;
+use crate::driver::android::{adb, AndroidDriver};
+use crate::driver::traits::{AppVersion, PlatformDriver, Selector, SwipeDirection};
 
 /// Android Auto driver using DHU console commands
 pub struct AndroidAutoDriver {
@@ -21,6 +27,8 @@ pub struct AndroidAutoDriver {
     dhu_stdin: Arc<Mutex<Option<tokio::process::ChildStdin>>>,
     /// Device serial (for ADB fallback commands)
     serial: Option<String>,
+    /// Phone-side driver for app lifecycle and key events on the Android Auto display
+    phone: AndroidDriver,
     /// Display size (typically 800x480 for Android Auto)
     screen_size: (u32, u32),
 }
@@ -41,10 +49,13 @@ impl AndroidAutoDriver {
             }
         };
 
+        let phone = AndroidDriver::new(selected_serial.as_deref()).await?;
+
         let mut driver = Self {
             dhu_process: Arc::new(Mutex::new(None)),
             dhu_stdin: Arc::new(Mutex::new(None)),
             serial: selected_serial,
+            phone,
             screen_size: (800, 480),
         };
 
@@ -52,9 +63,23 @@ impl AndroidAutoDriver {
             driver.start_dhu().await?;
         }
 
+        // Point the phone driver's key events at the car screen
+        match driver.phone.detect_android_auto_display().await {
+            Ok(Some(id)) => driver.phone.select_display(id).await?,
+            _ => println!(
+                "  {} Android Auto display not found; use `selectDisplay: auto` once it is up",
+                "⚠️".yellow()
+            ),
+        }
+
         Ok(driver)
     }
 
+    /// Speed profile of the phone-side driver (lumi.toml `speed`)
+    pub fn set_speed_profile(&mut self, profile: SpeedProfile) {
+        self.phone.set_speed_profile(profile);
+    }
+
     /// Start the Desktop Head Unit (DHU) process
     pub async fn start_dhu(&mut self) -> Result<()> {
         let sdk_path = std::env::var("ANDROID_SDK_ROOT")
@@ -126,6 +151,11 @@ impl AndroidAutoDriver {
             anyhow::bail!("DHU not connected. Call start_dhu() first or ensure DHU is running.");
         }
     }
+
+    /// Whether DHU console commands can be sent
+    async fn dhu_connected(&self) -> bool {
+        self.dhu_stdin.lock().await.is_some()
+    }
 }
 
 #[async_trait]
@@ -151,13 +181,15 @@ impl PlatformDriver for AndroidAutoDriver {
     }
 
     async fn stop_app(&self, app_id: &str) -> Result<()> {
-        adb::shell(self.serial.as_deref(), &format!("am force-stop {}", app_id)).await?;
-        Ok(())
+        self.phone.stop_app(app_id).await
     }
 
     async fn tap(&self, selector: &Selector) -> Result<()> {
         match selector {
-            Selector::Point { x, y } => self.send_dhu_command(&format!("tap {} {}", x, y)).await,
+            Selector::Point { x, y } if self.dhu_connected().await => {
+                self.send_dhu_command(&format!("tap {} {}", x, y)).await
+            }
+            Selector::Point { .. } => self.phone.tap(selector).await,
             _ => anyhow::bail!(
                 "Android Auto only supports coordinate-based tap. Use tap with point: \"x,y\""
             ),
@@ -178,16 +210,20 @@ impl PlatformDriver for AndroidAutoDriver {
         anyhow::bail!("Right click not supported on Android Auto")
     }
 
-    async fn input_text(&self, _text: &str, _unicode: bool) -> Result<()> {
-        anyhow::bail!("Text input on Android Auto requires voice input or phone keyboard")
+    async fn input_text(&self, text: &str, unicode: bool) -> Result<()> {
+        self.phone.input_text(text, unicode).await
     }
 
-    async fn erase_text(&self, _char_count: Option<u32>) -> Result<()> {
-        anyhow::bail!("Erase text not supported on Android Auto DHU")
+    async fn erase_text(&self, char_count: Option<u32>) -> Result<()> {
+        self.phone.erase_text(char_count).await
     }
 
     async fn hide_keyboard(&self) -> Result<()> {
-        self.send_dhu_command("keycode back").await
+        if self.dhu_connected().await {
+            self.send_dhu_command("keycode back").await
+        } else {
+            self.phone.hide_keyboard().await
+        }
     }
 
     async fn swipe(
@@ -216,7 +252,9 @@ impl PlatformDriver for AndroidAutoDriver {
     }
 
     async fn is_visible(&self, _selector: &Selector) -> Result<bool> {
-        Ok(false) // Can't check visibility without UI dump
+        // No UI dump of the car display; answering "not visible" would let
+        // assertNotVisible, optional and conditions pass without looking
+        anyhow::bail!("is_visible not supported on Android Auto (no UI dump of the car display)")
     }
 
     async fn wait_for_element(&self, _selector: &Selector, _timeout_ms: u64) -> Result<bool> {
@@ -242,14 +280,17 @@ impl PlatformDriver for AndroidAutoDriver {
 
     async fn compare_screenshot(
         &self,
-        _reference_path: &Path,
-        _tolerance_percent: f64,
+        reference_path: &Path,
+        tolerance_percent: f64,
     ) -> Result<f64> {
-        anyhow::bail!("compare_screenshot not fully supported on Android Auto")
+        self.phone
+            .compare_screenshot(reference_path, tolerance_percent)
+            .await
     }
 
     async fn take_screenshot(&self, path: &str) -> Result<()> {
-        self.send_dhu_command(&format!("screenshot {}", path)).await
+        // The phone driver captures the selected car display, DHU or not
+        self.phone.take_screenshot(path).await
     }
 
     async fn start_recording(&self, _path: &str) -> Result<()> {
@@ -261,11 +302,19 @@ impl PlatformDriver for AndroidAutoDriver {
     }
 
     async fn back(&self) -> Result<()> {
-        self.send_dhu_command("keycode back").await
+        if self.dhu_connected().await {
+            self.send_dhu_command("keycode back").await
+        } else {
+            self.phone.back().await
+        }
     }
 
     async fn home(&self) -> Result<()> {
-        self.send_dhu_command("keycode home").await
+        if self.dhu_connected().await {
+            self.send_dhu_command("keycode home").await
+        } else {
+            self.phone.home().await
+        }
     }
 
     async fn get_screen_size(&self) -> Result<(u32, u32)> {
@@ -300,19 +349,52 @@ impl PlatformDriver for AndroidAutoDriver {
         Ok(())
     }
 
+    async fn clear_app_data(&self, app_id: &str) -> Result<()> {
+        self.phone.clear_app_data(app_id).await
+    }
+
+    async fn install_app(&self, path: &str) -> Result<()> {
+        self.phone.install_app(path).await
+    }
+
+    async fn uninstall_app(&self, app_id: &str) -> Result<()> {
+        self.phone.uninstall_app(app_id).await
+    }
+
+    async fn get_app_version(&self, app_id: &str) -> Result<AppVersion> {
+        self.phone.get_app_version(app_id).await
+    }
+
+    async fn detect_app_crash(&self, app_id: &str) -> Result<bool> {
+        self.phone.detect_app_crash(app_id).await
+    }
+
+    async fn is_audio_playing(&self, app_id: Option<&str>) -> Result<bool> {
+        self.phone.is_audio_playing(app_id).await
+    }
+
     async fn select_display(&self, display_id: u32) -> Result<()> {
-        println!(
-            "  Note: Android Auto display is managed by DHU (display {})",
-            display_id
-        );
-        Ok(())
+        self.phone.select_display(display_id).await
     }
 
     async fn detect_android_auto_display(&self) -> Result<Option<u32>> {
-        Ok(Some(1)) // Virtual display ID
+        self.phone.detect_android_auto_display().await
+    }
+
+    async fn select_auto_display(&self) -> Result<u32> {
+        self.phone.select_auto_display().await
     }
 
     async fn press_key(&self, key: &str) -> Result<()> {
+        if !self.dhu_connected().await {
+            return self.phone.press_key(key).await;
+        }
+        // Rotary/dpad input: "dpad_up", "dpad_rotate_left", ...
+        if let Some(action) = key.to_lowercase().strip_prefix("dpad_") {
+            return self
+                .send_dhu_command(&format!("dpad {}", action.replace('_', " ")))
+                .await;
+        }
         let keycode = match key.to_lowercase().as_str() {
             "home" => "home",
             "back" => "back",
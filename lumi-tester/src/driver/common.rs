@@ -24,6 +24,20 @@ pub fn get_pixel_from_image(img: &DynamicImage, x: u32, y: u32) -> (u8, u8, u8)
     (pixel[0], pixel[1], pixel[2])
 }
 
+/// Map a point in screen coordinates to the matching pixel of a screenshot
+///
+/// iOS reports its screen size in points while screenshots are in pixels
+/// (2x/3x), so the point is scaled by the screenshot width over `screen_width`.
+pub fn scale_point_to_image(img: &DynamicImage, screen_width: u32, x: i32, y: i32) -> (u32, u32) {
+    let scale = if screen_width == 0 {
+        1.0
+    } else {
+        img.width() as f64 / screen_width as f64
+    };
+    let scaled = |v: i32| (v.max(0) as f64 * scale).round() as u32;
+    (scaled(x), scaled(y))
+}
+
 /// Template matching result
 pub struct MatchResult {
     pub x: i32,
@@ -266,6 +280,64 @@ pub fn to_ascii_fallback(text: &str) -> String {
         .collect()
 }
 
+/// Similarity in `0.0..=1.0` from the edit distance of the two strings, compared
+/// lowercase, without diacritics, whitespace or punctuation: "Log In" vs "Login" is 1.0
+pub fn text_similarity(a: &str, b: &str) -> f64 {
+    let normalize = |s: &str| -> Vec<char> {
+        to_ascii_fallback(s)
+            .to_lowercase()
+            .chars()
+            .filter(|c| c.is_alphanumeric())
+            .collect()
+    };
+    let (a, b) = (normalize(a), normalize(b));
+    let longest = a.len().max(b.len());
+    if longest == 0 {
+        return 0.0;
+    }
+
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.iter().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+    1.0 - previous[b.len()] as f64 / longest as f64
+}
+
+/// Candidates that look like `needle` without being it, best first (at most `limit`)
+pub fn closest_matches<'a>(
+    needle: &str,
+    candidates: impl IntoIterator<Item = &'a str>,
+    limit: usize,
+) -> Vec<String> {
+    const MIN_SIMILARITY: f64 = 0.6;
+
+    let mut scored: Vec<(f64, &str)> = Vec::new();
+    for candidate in candidates {
+        let candidate = candidate.trim();
+        if candidate.is_empty()
+            || candidate == needle
+            || scored.iter().any(|(_, seen)| *seen == candidate)
+        {
+            continue;
+        }
+        let score = text_similarity(needle, candidate);
+        if score >= MIN_SIMILARITY {
+            scored.push((score, candidate));
+        }
+    }
+    scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+    scored
+        .into_iter()
+        .take(limit)
+        .map(|(_, candidate)| candidate.to_string())
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -277,6 +349,19 @@ mod tests {
         assert_eq!(to_ascii_fallback("Hello"), "Hello");
     }
 
+    #[test]
+    fn test_closest_matches() {
+        assert_eq!(text_similarity("Login", "Log In"), 1.0);
+        let screen = [
+            "Logins", "Sign up", "Logout", "", "Log In", "Log In", "Login",
+        ];
+        assert_eq!(
+            closest_matches("Login", screen, 3),
+            vec!["Log In".to_string(), "Logins".to_string()]
+        );
+        assert!(closest_matches("Checkout", screen, 3).is_empty());
+    }
+
     #[test]
     fn test_escape_android_shell() {
         assert_eq!(escape_for_android_shell("hello world"), "hello%sworld");
@@ -291,4 +376,13 @@ mod tests {
         let (r, g, b) = get_pixel_from_image(&img, 0, 0);
         assert_eq!((r, g, b), (255, 0, 0));
     }
+
+    #[test]
+    fn test_scale_point_to_image() {
+        // 390pt wide screen captured at 3x
+        let img = DynamicImage::ImageRgb8(image::RgbImage::new(1170, 2532));
+        assert_eq!(scale_point_to_image(&img, 390, 100, 200), (300, 600));
+        assert_eq!(scale_point_to_image(&img, 1170, 100, 200), (100, 200));
+        assert_eq!(scale_point_to_image(&img, 0, -5, 7), (0, 7));
+    }
 }
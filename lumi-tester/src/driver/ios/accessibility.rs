@@ -2,6 +2,7 @@
 //!
 //! Parses the output from `idb ui describe-all` to find and match UI elements.
 
+use crate::driver::traits::resolve_index;
 use anyhow::Result;
 use serde::Deserialize;
 
@@ -213,88 +214,196 @@ pub fn flatten_elements(elements: &[IosElement]) -> Vec<&IosElement> {
     result
 }
 
+/// Flattened copy of the visible elements whose center lies inside the screen
+pub fn on_screen_elements(elements: &[IosElement], (width, height): (u32, u32)) -> Vec<IosElement> {
+    flatten_elements(elements)
+        .into_iter()
+        .filter(|e| {
+            let (x, y) = e.center();
+            e.visible
+                && e.frame.width > 0.0
+                && e.frame.height > 0.0
+                && x >= 0
+                && y >= 0
+                && (x as u32) < width
+                && (y as u32) < height
+        })
+        .map(|e| IosElement {
+            children: Vec::new(),
+            ..e.clone()
+        })
+        .collect()
+}
+
+/// Keyboard keys that close the keyboard, in order of preference
+pub const KEYBOARD_DISMISS_KEYS: &[&str] = &[
+    "Done", "Xong", "Return", "return", "Go", "Search", "Next", "Send",
+];
+
+/// The keyboard's Done/Return key, looked for in the lower half of the screen
+/// so a same-named button in the app is not tapped instead
+pub fn find_keyboard_dismiss_key(
+    elements: &[IosElement],
+    screen_height: u32,
+) -> Option<&IosElement> {
+    let keys: Vec<&IosElement> = flatten_elements(elements)
+        .into_iter()
+        .filter(|e| {
+            e.visible
+                && e.center().1 > (screen_height / 2) as i32
+                && matches!(e.element_type.as_deref(), Some("Button" | "Key"))
+        })
+        .collect();
+    KEYBOARD_DISMISS_KEYS.iter().find_map(|name| {
+        keys.iter()
+            .find(|e| e.label.as_deref() == Some(*name))
+            .copied()
+    })
+}
+
+/// Element types that take keyboard input
+pub const TEXT_INPUT_TYPES: &[&str] = &[
+    "TextField",
+    "SecureTextField",
+    "SearchField",
+    "TextArea",
+    "TextView",
+];
+
+/// First visible text input (secure fields included); idb does not report
+/// which one has focus
+pub fn find_text_input(elements: &[IosElement]) -> Option<&IosElement> {
+    flatten_elements(elements).into_iter().find(|e| {
+        e.visible
+            && e.element_type
+                .as_deref()
+                .is_some_and(|t| TEXT_INPUT_TYPES.contains(&t))
+    })
+}
+
+/// Characters of `text` already typed into a field, i.e. the longest prefix of
+/// `text` that some text input's value ends with; used to undo a failed input
+/// without moving focus
+pub fn typed_prefix_len(elements: &[IosElement], text: &str) -> usize {
+    let chars: Vec<char> = text.chars().collect();
+    flatten_elements(elements)
+        .into_iter()
+        .filter(|e| {
+            e.element_type
+                .as_deref()
+                .is_some_and(|t| TEXT_INPUT_TYPES.contains(&t))
+        })
+        .filter_map(|e| e.value.as_deref())
+        .filter_map(|value| {
+            (1..=chars.len())
+                .rev()
+                .find(|&n| value.ends_with(&chars[..n].iter().collect::<String>()))
+        })
+        .max()
+        .unwrap_or(0)
+}
+
+/// Whether the software keyboard is up, i.e. some field has focus. idb has no
+/// focus attribute, so look for keyboard keys in the lower half of the screen
+pub fn keyboard_shown(elements: &[IosElement], screen_height: u32) -> bool {
+    find_keyboard_dismiss_key(elements, screen_height).is_some()
+        || flatten_elements(elements)
+            .iter()
+            .any(|e| e.visible && matches!(e.element_type.as_deref(), Some("Keyboard" | "Key")))
+}
+
 /// Find elements matching text
+///
+/// `exact` requires the whole label/value to match (case-sensitive). Otherwise
+/// exact matches win, then case-insensitive and substring matches.
 pub fn find_by_text<'a>(
     elements: &'a [IosElement],
     text: &str,
-    index: usize,
+    index: i32,
+    exact: bool,
 ) -> Option<&'a IosElement> {
     let flat = flatten_elements(elements);
-    let matches: Vec<_> = flat
+    let nth = |matches: Vec<&'a IosElement>| {
+        resolve_index(index, matches.len()).and_then(|i| matches.get(i).copied())
+    };
+    let exact_matches: Vec<_> = flat
+        .iter()
+        .copied()
+        .filter(|e| e.visible && e.matches_text_exact(text))
+        .collect();
+    if exact || !exact_matches.is_empty() {
+        return nth(exact_matches);
+    }
+    nth(flat
         .into_iter()
         .filter(|e| e.visible && e.matches_text(text))
-        .collect();
-    matches.get(index).copied()
+        .collect())
 }
 
 /// Find elements matching text regex
 pub fn find_by_text_regex<'a>(
     elements: &'a [IosElement],
     pattern: &regex::Regex,
-    index: usize,
+    index: i32,
 ) -> Option<&'a IosElement> {
     let flat = flatten_elements(elements);
     let matches: Vec<_> = flat
         .into_iter()
         .filter(|e| e.visible && e.matches_text_regex(pattern))
         .collect();
-    matches.get(index).copied()
+    matches.get(resolve_index(index, matches.len())?).copied()
 }
 
 /// Find elements by accessibility ID
-pub fn find_by_id<'a>(
-    elements: &'a [IosElement],
-    id: &str,
-    index: usize,
-) -> Option<&'a IosElement> {
+pub fn find_by_id<'a>(elements: &'a [IosElement], id: &str, index: i32) -> Option<&'a IosElement> {
     let flat = flatten_elements(elements);
     let matches: Vec<_> = flat
         .into_iter()
         .filter(|e| e.visible && e.matches_id(id))
         .collect();
-    matches.get(index).copied()
+    matches.get(resolve_index(index, matches.len())?).copied()
 }
 
 /// Find elements by accessibility ID regex
 pub fn find_by_id_regex<'a>(
     elements: &'a [IosElement],
     pattern: &regex::Regex,
-    index: usize,
+    index: i32,
 ) -> Option<&'a IosElement> {
     let flat = flatten_elements(elements);
     let matches: Vec<_> = flat
         .into_iter()
         .filter(|e| e.visible && e.matches_id_regex(pattern))
         .collect();
-    matches.get(index).copied()
+    matches.get(resolve_index(index, matches.len())?).copied()
 }
 
 /// Find elements by type
 pub fn find_by_type<'a>(
     elements: &'a [IosElement],
     element_type: &str,
-    index: usize,
+    index: i32,
 ) -> Option<&'a IosElement> {
     let flat = flatten_elements(elements);
     let matches: Vec<_> = flat
         .into_iter()
         .filter(|e| e.visible && e.matches_type(element_type))
         .collect();
-    matches.get(index).copied()
+    matches.get(resolve_index(index, matches.len())?).copied()
 }
 
 /// Find elements by placeholder
 pub fn find_by_placeholder<'a>(
     elements: &'a [IosElement],
     placeholder: &str,
-    index: usize,
+    index: i32,
 ) -> Option<&'a IosElement> {
     let flat = flatten_elements(elements);
     let matches: Vec<_> = flat
         .into_iter()
         .filter(|e| e.visible && e.matches_placeholder(placeholder))
         .collect();
-    matches.get(index).copied()
+    matches.get(resolve_index(index, matches.len())?).copied()
 }
 
 /// Find element containing a point
@@ -324,7 +433,7 @@ pub fn find_at_point<'a>(elements: &'a [IosElement], x: i32, y: i32) -> Option<&
 pub fn find_by_accessibility_id<'a>(
     elements: &'a [IosElement],
     desc: &str,
-    index: usize,
+    index: i32,
 ) -> Option<&'a IosElement> {
     let flat = flatten_elements(elements);
     let matches: Vec<_> = flat
@@ -338,14 +447,14 @@ pub fn find_by_accessibility_id<'a>(
                     || e.identifier.as_ref().map_or(false, |i| i == desc))
         })
         .collect();
-    matches.get(index).copied()
+    matches.get(resolve_index(index, matches.len())?).copied()
 }
 
 /// Find elements by accessibility ID with regex
 pub fn find_by_accessibility_id_regex<'a>(
     elements: &'a [IosElement],
     pattern: &regex::Regex,
-    index: usize,
+    index: i32,
 ) -> Option<&'a IosElement> {
     let flat = flatten_elements(elements);
     let matches: Vec<_> = flat
@@ -356,7 +465,7 @@ pub fn find_by_accessibility_id_regex<'a>(
                     || e.identifier.as_ref().map_or(false, |i| pattern.is_match(i)))
         })
         .collect();
-    matches.get(index).copied()
+    matches.get(resolve_index(index, matches.len())?).copied()
 }
 
 #[cfg(test)]
@@ -377,6 +486,86 @@ mod tests {
         assert_eq!(element.center(), (125, 215));
     }
 
+    #[test]
+    fn test_find_keyboard_dismiss_key() {
+        let key = |label: &str, y: f64| IosElement {
+            label: Some(label.to_string()),
+            element_type: Some("Button".to_string()),
+            frame: IosFrame {
+                x: 0.0,
+                y,
+                width: 40.0,
+                height: 40.0,
+            },
+            visible: true,
+            ..Default::default()
+        };
+        // An app "Done" button in the nav bar must not win over the keyboard key
+        let elements = vec![key("Done", 40.0), key("return", 800.0), key("Go", 760.0)];
+        let found = find_keyboard_dismiss_key(&elements, 844).unwrap();
+        assert_eq!(found.label.as_deref(), Some("return"));
+        assert!(find_keyboard_dismiss_key(&elements[..1], 844).is_none());
+    }
+
+    #[test]
+    fn test_find_text_input_includes_secure_fields() {
+        let elements = vec![
+            IosElement {
+                label: Some("Sign in".to_string()),
+                element_type: Some("StaticText".to_string()),
+                visible: true,
+                ..Default::default()
+            },
+            IosElement {
+                label: Some("Password".to_string()),
+                element_type: Some("SecureTextField".to_string()),
+                visible: true,
+                ..Default::default()
+            },
+        ];
+        let found = find_text_input(&elements).unwrap();
+        assert_eq!(found.label.as_deref(), Some("Password"));
+        assert!(!keyboard_shown(&elements, 844));
+    }
+
+    #[test]
+    fn test_typed_prefix_len() {
+        let field = |value: &str| IosElement {
+            element_type: Some("TextField".to_string()),
+            value: Some(value.to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            typed_prefix_len(&[field("user@exa")], "user@example.com"),
+            8
+        );
+        assert_eq!(
+            typed_prefix_len(&[field("Hello "), field("xyz")], "world"),
+            0
+        );
+        assert_eq!(typed_prefix_len(&[field("old text")], ""), 0);
+    }
+
+    #[test]
+    fn test_find_by_text_exact_rejects_substring() {
+        let labelled = |label: &str| IosElement {
+            label: Some(label.to_string()),
+            visible: true,
+            ..Default::default()
+        };
+        let elements = vec![labelled("Login with Google"), labelled("Login")];
+
+        // Non-exact prefers the exact label even when a substring match comes first
+        let found = find_by_text(&elements, "Login", 0, false).unwrap();
+        assert_eq!(found.label.as_deref(), Some("Login"));
+        let found = find_by_text(&elements, "google", 0, false).unwrap();
+        assert_eq!(found.label.as_deref(), Some("Login with Google"));
+
+        assert!(find_by_text(&elements, "Google", 0, true).is_none());
+        assert!(find_by_text(&elements, "login", 0, true).is_none());
+        assert!(find_by_text(&elements, "Login", 0, true).is_some());
+    }
+
     #[test]
     fn test_matches_text() {
         let element = IosElement {
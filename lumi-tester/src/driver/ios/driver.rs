@@ -9,6 +9,7 @@ use async_trait::async_trait;
 use regex::Regex;
 use std::collections::HashMap;
 use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tokio::sync::Mutex;
@@ -19,21 +20,45 @@ use super::idb;
 use super::wda::WdaClient;
 use crate::driver::common;
 use crate::driver::image_matcher::{find_template, ImageRegion, MatchConfig};
-use crate::driver::traits::{PlatformDriver, Selector, SwipeDirection};
-use crate::parser::types::SpeedMode;
+use crate::driver::traits::{
+    ensure_selector_supported, AppVersion, LocationTolerance, NthMatch, PlatformDriver, Selector,
+    SwipeDirection,
+};
+use crate::error::TesterError;
+use crate::parser::types::{DriveProfile, MaxDistance, NetworkConditions, SpeedMode};
 use colored::Colorize;
 use image::GenericImageView;
 use std::collections::HashMap as StdHashMap;
 
+/// Selector kinds resolved against the accessibility tree, screenshots or
+/// coordinates; XCUITest exposes no XPath/CSS and no scrollable-container index
+const SUPPORTED_SELECTORS: &[&str] = &[
+    "text",
+    "regex",
+    "id",
+    "description",
+    "placeholder",
+    "role",
+    "elementType",
+    "point",
+    "image",
+    "ocr",
+    "relative",
+    "containsChild",
+];
+
 /// iOS driver implementation
 /// - Simulators: Uses idb
 /// - Real devices: Uses WebDriverAgent (WDA)
 pub struct IosDriver {
     /// Device UDID
     udid: String,
-    /// Device name (used for logging)
-    #[allow(dead_code)]
+    /// Device name, e.g. "iPhone 15" or "QA iPhone" (logging)
     device_name: String,
+    /// Hardware model, e.g. "iPhone15,2" (`nl.deviceModel`, screenshot baselines)
+    product_type: Option<String>,
+    /// iOS version, e.g. "17.2"
+    os_version: Option<String>,
     /// Whether this is a simulator (vs physical device)
     is_simulator: bool,
     /// Cached UI hierarchy
@@ -44,18 +69,24 @@ pub struct IosDriver {
     recording_process: Arc<Mutex<Option<tokio::process::Child>>>,
     /// Current recording output path
     current_recording_path: Arc<Mutex<Option<String>>>,
-    /// Screen dimensions
+    /// Screen dimensions in portrait
     screen_size: (u32, u32),
+    /// Rotated to landscape by `rotate`; width and height are swapped
+    landscape: AtomicBool,
     /// Mock location states keyed by name ("" for default)
     mock_states: Arc<Mutex<StdHashMap<String, IosMockLocationState>>>,
     /// WDA client for real device UI automation
     wda_client: Arc<Mutex<Option<WdaClient>>>,
     /// OCR engine (lazy-initialized)
     ocr_engine: tokio::sync::OnceCell<crate::driver::ocr::OcrEngine>,
+    /// Simulator was shut down and booted by this driver
+    booted_here: bool,
+    /// Shut the simulator down at teardown if this driver booted it
+    shutdown_on_finish: bool,
 }
 
 /// State of the background mock location process for iOS
-#[derive(Clone)]
+#[derive(Clone, Default)]
 struct IosMockLocationState {
     current_lat: Option<f64>,
     current_lon: Option<f64>,
@@ -63,36 +94,37 @@ struct IosMockLocationState {
     finished: bool,
     paused: bool,
     speed: Option<f64>,
-    speed_mode: SpeedMode,
-    speed_noise: Option<f64>,
-}
-
-impl Default for IosMockLocationState {
-    fn default() -> Self {
-        Self {
-            current_lat: None,
-            current_lon: None,
-            is_running: false,
-            finished: false,
-            paused: false,
-            speed: None,
-            speed_mode: SpeedMode::Linear,
-            speed_noise: None,
-        }
-    }
+    drive_profile: DriveProfile,
 }
 
 impl IosDriver {
     /// Create a new iOS driver
+    ///
+    /// `udid` may also be a simulator name (e.g. "iPhone 15"); a shut-down
+    /// simulator is booted before connecting.
     pub async fn new(udid: Option<&str>) -> Result<Self> {
         let targets = idb::list_targets().await?;
 
-        let target = if let Some(id) = udid.filter(|s| !s.is_empty()) {
+        let mut target = if let Some(id) = udid.filter(|s| !s.is_empty()) {
+            // Several runtimes can share a simulator name; prefer the booted one
+            let by_name = |t: &&idb::IosTarget| t.name.eq_ignore_ascii_case(id);
             targets
                 .iter()
                 .find(|t| t.udid == id)
+                .or_else(|| {
+                    targets
+                        .iter()
+                        .filter(by_name)
+                        .find(|t| t.state.eq_ignore_ascii_case("Booted"))
+                })
+                .or_else(|| targets.iter().find(by_name))
                 .cloned()
-                .ok_or_else(|| anyhow::anyhow!("Device with UDID {} not found", id))?
+                .ok_or_else(|| {
+                    TesterError::driver_unavailable(
+                        "ios",
+                        format!("Device with UDID or name '{}' not found", id),
+                    )
+                })?
         } else {
             // Pick the first booted target
             targets
@@ -100,7 +132,9 @@ impl IosDriver {
                 .find(|t| t.state.eq_ignore_ascii_case("Booted"))
                 .cloned()
                 .or_else(|| targets.first().cloned())
-                .ok_or_else(|| anyhow::anyhow!("No iOS devices or simulators found"))?
+                .ok_or_else(|| {
+                    TesterError::driver_unavailable("ios", "No iOS devices or simulators found")
+                })?
         };
 
         println!(
@@ -116,31 +150,23 @@ impl IosDriver {
         );
 
         let is_simulator = target.target_type.eq_ignore_ascii_case("simulator");
+        let booted_here = is_simulator && !target.state.eq_ignore_ascii_case("Booted");
+        if booted_here {
+            println!("  {} Booting simulator {}...", "⏳".yellow(), target.name);
+            idb::boot_simulator(&target.udid).await?;
+            target.state = "Booted".to_string();
+            println!("{} Simulator booted", "✓".green());
+        }
+
+        let product_type = idb::product_type(&target.udid, is_simulator).await;
+
         let screen_size = idb::get_screen_size(&target.udid)
             .await
             .unwrap_or((390, 844));
 
         // Initialize WDA client for real devices
         let wda_client = if !is_simulator {
-            // Try to ensure WDA is running (auto-start if possible)
-            let port = super::wda::DEFAULT_WDA_PORT;
-            let _ = super::wda_setup::ensure_wda_running(&target.udid, port).await;
-
-            // Check if WDA host was found (stored in env by wda_setup)
-            let wda_host = std::env::var("WDA_HOST").unwrap_or_else(|_| "localhost".to_string());
-            let client = WdaClient::with_host(&wda_host, port);
-
-            if client.is_ready().await.unwrap_or(false) {
-                println!(
-                    "{} WebDriverAgent ready at {}:{}",
-                    "✓".green(),
-                    wda_host,
-                    port
-                );
-                Some(client)
-            } else {
-                None
-            }
+            Self::connect_wda(&target.udid).await
         } else {
             None
         };
@@ -148,18 +174,101 @@ impl IosDriver {
         Ok(Self {
             udid: target.udid,
             device_name: target.name,
+            product_type,
+            os_version: target
+                .os_version
+                .map(|v| v.trim_start_matches("iOS").trim().to_string()),
             is_simulator,
             ui_cache: Arc::new(Mutex::new(None)),
             cache_time: Arc::new(Mutex::new(None)),
             recording_process: Arc::new(Mutex::new(None)),
             current_recording_path: Arc::new(Mutex::new(None)),
             screen_size,
+            landscape: AtomicBool::new(false),
             mock_states: Arc::new(Mutex::new(StdHashMap::new())),
             wda_client: Arc::new(Mutex::new(wda_client)),
             ocr_engine: tokio::sync::OnceCell::new(),
+            booted_here,
+            shutdown_on_finish: false,
         })
     }
 
+    /// Screen size in the current orientation
+    fn screen_size(&self) -> (u32, u32) {
+        let (w, h) = self.screen_size;
+        if self.landscape.load(Ordering::Relaxed) {
+            (h, w)
+        } else {
+            (w, h)
+        }
+    }
+
+    /// Path of an app's data container (simulator only)
+    async fn app_data_container(&self, app_id: &str) -> Result<std::path::PathBuf> {
+        if !self.is_simulator {
+            anyhow::bail!("App data snapshots are only supported on iOS simulators");
+        }
+        let output = tokio::process::Command::new("xcrun")
+            .args(["simctl", "get_app_container", &self.udid, app_id, "data"])
+            .output()
+            .await
+            .context("Failed to run simctl get_app_container")?;
+        let path = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        if !output.status.success() || path.is_empty() {
+            anyhow::bail!("App '{}' is not installed on the simulator", app_id);
+        }
+        Ok(std::path::PathBuf::from(path))
+    }
+
+    /// Shut the simulator down at teardown if this driver had to boot it
+    pub fn with_shutdown_on_finish(mut self, enabled: bool) -> Self {
+        self.shutdown_on_finish = enabled;
+        self
+    }
+
+    /// Set up WDA and verify it with a health check, retrying a bounded number of times
+    async fn connect_wda(udid: &str) -> Option<WdaClient> {
+        const WDA_SETUP_ATTEMPTS: u32 = 3;
+        let port = super::wda::DEFAULT_WDA_PORT;
+
+        for attempt in 1..=WDA_SETUP_ATTEMPTS {
+            // Try to ensure WDA is running (auto-start if possible)
+            let _ = super::wda_setup::ensure_wda_running(udid, port).await;
+
+            // Check if WDA host was found (stored in env by wda_setup)
+            let wda_host = std::env::var("WDA_HOST").unwrap_or_else(|_| "localhost".to_string());
+            match super::wda::health_check(&wda_host, port, udid).await {
+                Ok(()) => {
+                    println!(
+                        "{} WebDriverAgent ready at {}:{}",
+                        "✓".green(),
+                        wda_host,
+                        port
+                    );
+                    return Some(WdaClient::with_host(&wda_host, port));
+                }
+                Err(e) if attempt < WDA_SETUP_ATTEMPTS => {
+                    println!(
+                        "{} WDA check failed (attempt {}/{}): {}",
+                        "⚠".yellow(),
+                        attempt,
+                        WDA_SETUP_ATTEMPTS,
+                        e
+                    );
+                    tokio::time::sleep(Duration::from_secs(2)).await;
+                }
+                Err(e) => {
+                    println!("{} {}", "✗".red(), e);
+                    println!(
+                        "  {} Continuing without WDA; real-device interactions will fail",
+                        "ℹ".blue()
+                    );
+                }
+            }
+        }
+        None
+    }
+
     /// Invalidate the UI cache
     pub async fn invalidate_cache(&self) {
         let mut cache = self.ui_cache.lock().await;
@@ -208,7 +317,7 @@ impl IosDriver {
     async fn find_ocr_text(
         &self,
         text: &str,
-        index: usize,
+        index: i32,
         is_regex: bool,
         region: Option<&str>,
     ) -> Result<Option<(i32, i32)>> {
@@ -321,15 +430,27 @@ impl IosDriver {
         &self,
         selector: &Selector,
     ) -> Result<Option<accessibility::IosElement>> {
+        ensure_selector_supported("iOS", selector, SUPPORTED_SELECTORS)?;
+
         // Point selector has no dimensions/element structure, return None
         if let Selector::Point { .. } = selector {
             return Ok(None);
         }
 
         let elements = self.get_ui_hierarchy().await?;
+        let (selector, elements) = match selector {
+            Selector::OnScreen(inner) => (
+                inner.unwrap_on_screen(),
+                accessibility::on_screen_elements(&elements, self.screen_size()),
+            ),
+            _ => (selector, elements),
+        };
 
         let element = match selector {
-            Selector::Text(text, index, _) => accessibility::find_by_text(&elements, text, *index),
+            Selector::OnScreen(_) => anyhow::bail!("Nested onScreen selector was not unwrapped"),
+            Selector::Text(text, index, exact) => {
+                accessibility::find_by_text(&elements, text, *index, *exact)
+            }
             Selector::TextRegex(pattern, index) => {
                 let regex = Regex::new(pattern).context("Invalid regex pattern")?;
                 accessibility::find_by_text_regex(&elements, &regex, *index)
@@ -348,6 +469,7 @@ impl IosDriver {
             Selector::AccessibilityId(id) => accessibility::find_by_id(&elements, id, 0),
             Selector::XPath(_) => None,
             Selector::Css(_) => None,
+            Selector::FuzzyText(..) => None,
             Selector::Role(role, index) => accessibility::find_by_type(&elements, role, *index),
             Selector::Description(desc, index) => {
                 accessibility::find_by_accessibility_id(&elements, desc, *index)
@@ -363,14 +485,15 @@ impl IosDriver {
                     .into_iter()
                     .filter(|e| e.visible && e.enabled)
                     .collect();
-                clickables.get(*index).copied()
+                clickables.into_iter().nth_match(*index)
             }
             Selector::Relative {
                 target,
                 anchor,
                 direction,
                 max_dist,
-            } => self.find_relative_element(&elements, target, anchor, direction, max_dist),
+                angle,
+            } => self.find_relative_element(&elements, target, anchor, direction, max_dist, angle),
             Selector::Point { .. } => unreachable!(),
             Selector::Image { .. } => None,
             Selector::OCR(..) => None, // OCR handled separately via screenshot
@@ -439,11 +562,14 @@ impl IosDriver {
         target: &Selector,
         anchor: &Selector,
         direction: &crate::driver::traits::RelativeDirection,
-        max_dist: &Option<u32>,
+        max_dist: &Option<MaxDistance>,
+        angle: &Option<f32>,
     ) -> Option<&'a IosElement> {
         // Find anchor element directly (inline logic to avoid async recursion)
         let anchor_element = match anchor {
-            Selector::Text(text, index, _) => accessibility::find_by_text(elements, text, *index),
+            Selector::Text(text, index, exact) => {
+                accessibility::find_by_text(elements, text, *index, *exact)
+            }
             Selector::Id(id, index) => accessibility::find_by_id(elements, id, *index),
             Selector::Type(t, index) => accessibility::find_by_type(elements, t, *index),
             Selector::Placeholder(p, index) => {
@@ -458,7 +584,9 @@ impl IosDriver {
         let (ax, ay) = anchor_element.center();
 
         let flat = accessibility::flatten_elements(elements);
-        let max_distance = max_dist.unwrap_or(500) as i32;
+        let max_distance = max_dist
+            .map(|d| d.to_pixels(direction.axis_length(self.screen_size())))
+            .unwrap_or(500) as i32;
 
         // Find target matching the base selector
         let target_base_matches: Vec<_> = flat
@@ -513,6 +641,9 @@ impl IosDriver {
                 Near => (dx.abs() + dy.abs()) < max_distance,
             };
 
+            let matches = matches
+                && angle.is_none_or(|tol| direction.within_angle(dx as f64, dy as f64, tol));
+
             if matches {
                 // Overlap bonus: prioritize elements that overlap on the orthogonal axis
                 let overlap_bonus = match direction {
@@ -605,13 +736,17 @@ impl IosDriver {
             _ => 0,
         };
 
-        scored_matches.into_iter().nth(target_index).map(|(e, _)| e)
+        scored_matches
+            .into_iter()
+            .nth_match(target_index)
+            .map(|(e, _)| e)
     }
 
     /// Check if element matches a selector (for relative finding)
     fn element_matches_selector(&self, element: &IosElement, selector: &Selector) -> bool {
         match selector {
-            Selector::Text(text, _, _) => element.matches_text(text),
+            Selector::Text(text, _, true) => element.matches_text_exact(text),
+            Selector::Text(text, _, false) => element.matches_text(text),
             Selector::Id(id, _) => element.matches_id(id),
             Selector::Type(t, _) => element.matches_type(t),
             Selector::Placeholder(p, _) => element.matches_placeholder(p),
@@ -660,24 +795,13 @@ impl IosDriver {
         // 2. Find target element to tap (TextField)
         // We reuse the logic from erase_text to find generic text field if we don't know where to tap
         let ui_json = idb::describe_ui(&self.udid).await?;
-        let mut tap_x = (self.screen_size.0 / 2) as i32;
-        let mut tap_y = (self.screen_size.1 / 4) as i32;
+        let mut tap_x = (self.screen_size().0 / 2) as i32;
+        let mut tap_y = (self.screen_size().1 / 4) as i32;
 
-        if let Ok(elements) = crate::driver::ios::accessibility::parse_ui_hierarchy(&ui_json) {
-            for el in crate::driver::ios::accessibility::flatten_elements(&elements) {
-                if let Some(el_type) = &el.element_type {
-                    if el_type == "TextField"
-                        || el_type == "TextArea"
-                        || el_type == "SecureTextField"
-                    {
-                        // Ideally checking for "hasKeyboardFocus" but valid JSON doesn't always have it exposed nicely
-                        // We assume the first visible text field is the one we want or the one focused
-                        let (cx, cy) = el.center();
-                        tap_x = cx;
-                        tap_y = cy;
-                        break;
-                    }
-                }
+        if let Ok(elements) = accessibility::parse_ui_hierarchy(&ui_json) {
+            // We assume the first visible text field is the one we want or the one focused
+            if let Some(el) = accessibility::find_text_input(&elements) {
+                (tap_x, tap_y) = el.center();
             }
         }
 
@@ -730,6 +854,28 @@ impl IosDriver {
         Ok(())
     }
 
+    /// Screenshot as an image: idb on simulators, WDA on real devices (idb
+    /// there needs a companion) with idb as fallback
+    async fn capture_screen(&self) -> Result<image::DynamicImage> {
+        let temp_path = std::env::temp_dir().join(format!("ios_capture_{}.png", Uuid::new_v4()));
+        let temp_path_str = temp_path.to_string_lossy().to_string();
+
+        let mut captured = false;
+        if !self.is_simulator {
+            let wda = self.wda_client.lock().await;
+            if let Some(ref client) = *wda {
+                captured = client.screenshot_to_file(&temp_path_str).await.is_ok();
+            }
+        }
+        if !captured {
+            idb::screenshot(&self.udid, &temp_path_str).await?;
+        }
+
+        let img = image::open(&temp_path).context("Failed to open iOS screenshot");
+        let _ = std::fs::remove_file(&temp_path);
+        img
+    }
+
     async fn find_paste_button(&self) -> Result<Option<IosElement>> {
         let ui_json = idb::describe_ui(&self.udid).await?;
         if let Ok(elements) = crate::driver::ios::accessibility::parse_ui_hierarchy(&ui_json) {
@@ -771,6 +917,14 @@ impl PlatformDriver for IosDriver {
         Some(self.udid.clone())
     }
 
+    fn device_model(&self) -> Option<String> {
+        self.product_type.clone()
+    }
+
+    fn os_version(&self) -> Option<String> {
+        self.os_version.clone()
+    }
+
     async fn launch_app(&self, bundle_id: &str, clear_state: bool) -> Result<()> {
         self.invalidate_cache().await;
 
@@ -848,7 +1002,7 @@ impl PlatformDriver for IosDriver {
         let pos = self
             .find_element(selector)
             .await?
-            .ok_or_else(|| anyhow::anyhow!("Element not found for tap: {:?}", selector))?;
+            .ok_or_else(|| TesterError::element_not_found(selector))?;
 
         if self.is_simulator {
             idb::tap(&self.udid, pos.0, pos.1).await?;
@@ -870,7 +1024,7 @@ impl PlatformDriver for IosDriver {
         let pos = self
             .find_element(selector)
             .await?
-            .ok_or_else(|| anyhow::anyhow!("Element not found for long_press: {:?}", selector))?;
+            .ok_or_else(|| TesterError::element_not_found(selector))?;
 
         if self.is_simulator {
             idb::long_press(&self.udid, pos.0, pos.1, duration_ms).await?;
@@ -890,7 +1044,7 @@ impl PlatformDriver for IosDriver {
         let pos = self
             .find_element(selector)
             .await?
-            .ok_or_else(|| anyhow::anyhow!("Element not found for double_tap: {:?}", selector))?;
+            .ok_or_else(|| TesterError::element_not_found(selector))?;
 
         if self.is_simulator {
             // Perform two rapid taps
@@ -919,7 +1073,25 @@ impl PlatformDriver for IosDriver {
     async fn input_text(&self, text: &str, _unicode: bool) -> Result<()> {
         if self.is_simulator {
             if text.chars().all(|c| c.is_ascii()) {
-                idb::input_text(&self.udid, text).await?;
+                if let Err(e) = idb::input_text(&self.udid, text).await {
+                    // Part of the text may already be in the field
+                    crate::driver::trace::log(format!(
+                        "{} Text input failed ({}), removing what got through and typing per character...",
+                        "⚠".yellow(),
+                        e
+                    ));
+                    // idb can't clear the focused element, and tapping a field
+                    // could move focus; delete the typed characters instead
+                    self.invalidate_cache().await;
+                    let elements = self.get_ui_hierarchy().await?;
+                    for _ in 0..accessibility::typed_prefix_len(&elements, text) {
+                        idb::press_key(&self.udid, idb::HID_KEY_DELETE).await?;
+                    }
+                    for ch in text.chars() {
+                        idb::input_text(&self.udid, &ch.to_string()).await?;
+                        tokio::time::sleep(Duration::from_millis(50)).await;
+                    }
+                }
             } else {
                 // Fallback for non-ASCII characters (e.g. Vietnamese)
                 self.input_text_clipboard(text).await?;
@@ -928,7 +1100,17 @@ impl PlatformDriver for IosDriver {
             // Use WDA for real devices - supports both ASCII and Unicode
             let mut wda = self.wda_client.lock().await;
             if let Some(ref mut client) = *wda {
-                client.input_text(text).await?;
+                if let Err(e) = client.input_text(text).await {
+                    // Secure and custom fields can drop fast input; clear what
+                    // got through and retry slowly
+                    crate::driver::trace::log(format!(
+                        "{} Text input failed ({}), clearing the field and typing per character...",
+                        "⚠".yellow(),
+                        e
+                    ));
+                    client.clear_focused_field().await?;
+                    client.input_text_slowly(text, 5).await?;
+                }
             } else {
                 // Fallback to idb (will likely fail for real devices)
                 idb::input_text(&self.udid, text).await?;
@@ -939,23 +1121,26 @@ impl PlatformDriver for IosDriver {
     }
 
     async fn erase_text(&self, _char_count: Option<u32>) -> Result<()> {
-        // For iOS, find text field and select all via triple-tap then replace
+        // WDA knows the focused element and clears it directly
+        {
+            let mut wda = self.wda_client.lock().await;
+            if let Some(ref mut client) = *wda {
+                client.clear_focused_field().await?;
+                drop(wda);
+                self.invalidate_cache().await;
+                return Ok(());
+            }
+        }
+
+        // idb: find the text field and select all via triple-tap then replace
         let ui_json = idb::describe_ui(&self.udid).await?;
 
-        // Look for TextField/SearchField in the UI to get correct coordinates
-        let mut tap_x = (self.screen_size.0 / 2) as i32;
+        let mut tap_x = (self.screen_size().0 / 2) as i32;
         let mut tap_y = 80i32; // Default to top area
 
-        if let Ok(elements) = crate::driver::ios::accessibility::parse_ui_hierarchy(&ui_json) {
-            for el in crate::driver::ios::accessibility::flatten_elements(&elements) {
-                if let Some(el_type) = &el.element_type {
-                    if el_type == "TextField" || el_type == "TextArea" {
-                        let (cx, cy) = el.center();
-                        tap_x = cx;
-                        tap_y = cy;
-                        break;
-                    }
-                }
+        if let Ok(elements) = accessibility::parse_ui_hierarchy(&ui_json) {
+            if let Some(el) = accessibility::find_text_input(&elements) {
+                (tap_x, tap_y) = el.center();
             }
         }
 
@@ -981,21 +1166,43 @@ impl PlatformDriver for IosDriver {
         Ok(())
     }
 
-    async fn hide_keyboard(&self) -> Result<()> {
-        if self.is_simulator {
-            // Try pressing return to dismiss, common pattern
-            let _ = idb::press_key(&self.udid, "XCUIKeyboardKeyReturn").await;
-
-            // Alternative: tap outside the keyboard area
-            let (_, height) = self.screen_size;
-            let _ = idb::tap(&self.udid, 50, (height / 4) as i32).await;
-        } else {
+    async fn has_focused_field(&self) -> Result<bool> {
+        {
             let mut wda = self.wda_client.lock().await;
             if let Some(ref mut client) = *wda {
-                // Press return key to dismiss keyboard
-                let _ = client.press_key("RETURN").await;
-            } else {
-                let _ = idb::press_key(&self.udid, "XCUIKeyboardKeyReturn").await;
+                return Ok(client.focused_element_id().await?.is_some());
+            }
+        }
+        self.invalidate_cache().await;
+        let elements = self.get_ui_hierarchy().await?;
+        Ok(accessibility::keyboard_shown(
+            &elements,
+            self.screen_size().1,
+        ))
+    }
+
+    async fn hide_keyboard(&self) -> Result<()> {
+        let mut wda = self.wda_client.lock().await;
+        if let Some(ref mut client) = *wda {
+            if let Err(e) = client
+                .dismiss_keyboard(accessibility::KEYBOARD_DISMISS_KEYS)
+                .await
+            {
+                log::debug!("WDA keyboard dismiss failed, pressing return: {}", e);
+                client.press_key("RETURN").await?;
+            }
+        } else {
+            drop(wda);
+            // Prefer the keyboard's own Done/Return key; the Return keycode
+            // covers keyboards whose keys are not in the hierarchy
+            self.invalidate_cache().await;
+            let elements = self.get_ui_hierarchy().await?;
+            match accessibility::find_keyboard_dismiss_key(&elements, self.screen_size().1) {
+                Some(key) => {
+                    let (x, y) = key.center();
+                    idb::tap(&self.udid, x, y).await?;
+                }
+                None => idb::press_key(&self.udid, idb::HID_KEY_RETURN).await?,
             }
         }
 
@@ -1009,7 +1216,7 @@ impl PlatformDriver for IosDriver {
         duration_ms: Option<u64>,
         from: Option<Selector>,
     ) -> Result<()> {
-        let (width, height) = self.screen_size;
+        let (width, height) = self.screen_size();
 
         // Determine swipe area
         let (area_left, area_top, area_w, area_h) = if let Some(selector) = from {
@@ -1142,6 +1349,7 @@ impl PlatformDriver for IosDriver {
     }
 
     async fn get_element_text(&self, selector: &Selector) -> Result<String> {
+        ensure_selector_supported("iOS", selector, SUPPORTED_SELECTORS)?;
         let elements = self.get_ui_hierarchy().await?;
 
         // Helper to extract text from element
@@ -1168,8 +1376,18 @@ impl PlatformDriver for IosDriver {
             return Ok(String::new());
         }
 
+        let (selector, elements) = match selector {
+            Selector::OnScreen(inner) => (
+                inner.unwrap_on_screen(),
+                accessibility::on_screen_elements(&elements, self.screen_size()),
+            ),
+            _ => (selector, elements),
+        };
+
         let element = match selector {
-            Selector::Text(text, index, _) => accessibility::find_by_text(&elements, text, *index),
+            Selector::Text(text, index, exact) => {
+                accessibility::find_by_text(&elements, text, *index, *exact)
+            }
             Selector::TextRegex(pattern, index) => {
                 let regex = Regex::new(pattern).context("Invalid regex pattern")?;
                 accessibility::find_by_text_regex(&elements, &regex, *index)
@@ -1188,7 +1406,8 @@ impl PlatformDriver for IosDriver {
                 anchor,
                 direction,
                 max_dist,
-            } => self.find_relative_element(&elements, target, anchor, direction, max_dist),
+                angle,
+            } => self.find_relative_element(&elements, target, anchor, direction, max_dist, angle),
             Selector::Image { .. } => None,
             Selector::HasChild { parent, child } => {
                 let flat = accessibility::flatten_elements(&elements);
@@ -1230,7 +1449,7 @@ impl PlatformDriver for IosDriver {
                     .into_iter()
                     .filter(|e| e.visible && e.enabled)
                     .collect();
-                clickables.get(*index).copied()
+                clickables.into_iter().nth_match(*index)
             }
             _ => None,
         };
@@ -1296,7 +1515,7 @@ impl PlatformDriver for IosDriver {
 
     async fn back(&self) -> Result<()> {
         // iOS back gesture: swipe from left edge
-        let (_, height) = self.screen_size;
+        let (_, height) = self.screen_size();
         let center_y = height as i32 / 2;
 
         if self.is_simulator {
@@ -1329,33 +1548,45 @@ impl PlatformDriver for IosDriver {
     }
 
     async fn get_screen_size(&self) -> Result<(u32, u32)> {
-        Ok(self.screen_size)
+        Ok(self.screen_size())
     }
 
     async fn dump_ui_hierarchy(&self) -> Result<String> {
+        // WDA gives the XCUITest XML; idb's accessibility JSON is the fallback
+        let mut wda = self.wda_client.lock().await;
+        if let Some(ref mut client) = *wda {
+            match client.get_source().await {
+                Ok(xml) => return Ok(xml),
+                Err(e) => log::debug!("WDA source failed, using idb: {}", e),
+            }
+        }
+        drop(wda);
         idb::describe_ui(&self.udid).await
     }
 
     async fn dump_logs(&self, limit: u32) -> Result<String> {
-        idb::get_logs(&self.udid, limit).await
+        idb::get_logs(&self.udid, limit, self.is_simulator).await
     }
 
     async fn tap_by_type_index(&self, element_type: &str, index: u32) -> Result<()> {
-        let selector = Selector::Type(element_type.to_string(), index as usize);
+        let selector = Selector::Type(element_type.to_string(), index as i32);
         self.tap(&selector).await
     }
 
     async fn input_by_type_index(&self, element_type: &str, index: u32, text: &str) -> Result<()> {
-        let selector = Selector::Type(element_type.to_string(), index as usize);
+        let selector = Selector::Type(element_type.to_string(), index as i32);
         self.tap(&selector).await?;
         tokio::time::sleep(Duration::from_millis(200)).await;
         self.input_text(text, false).await
     }
 
     async fn start_recording(&self, path: &str) -> Result<()> {
-        let child = tokio::process::Command::new("idb")
-            .args(&["record", "video", "--udid", &self.udid, path])
-            .spawn()?;
+        // A second start would leave the first recorder running unfinalized
+        if let Some(previous) = self.recording_process.lock().await.take() {
+            let _ = idb::stop_recording(previous).await;
+        }
+
+        let child = idb::start_recording(&self.udid, path, self.is_simulator).await?;
 
         *self.recording_process.lock().await = Some(child);
         self.current_recording_path
@@ -1366,69 +1597,24 @@ impl PlatformDriver for IosDriver {
     }
 
     async fn stop_recording(&self) -> Result<()> {
-        if let Some(mut child) = self.recording_process.lock().await.take() {
-            // idb record video stops on SIGINT
-            // Since child.kill() is SIGKILL, we should try to send SIGINT if possible.
-            // On MacOS/Linux we can use kill -2
-            if let Some(pid) = child.id() {
-                let _ = std::process::Command::new("kill")
-                    .args(&["-2", &pid.to_string()])
-                    .output();
-            } else {
-                let _ = child.kill().await;
-            }
-
-            let _ = child.wait().await;
+        let Some(child) = self.recording_process.lock().await.take() else {
+            return Ok(());
+        };
+        let path = self.current_recording_path.lock().await.take();
+        idb::stop_recording(child).await?;
 
-            if let Some(path) = self.current_recording_path.lock().await.take() {
-                println!("  {} Saved iOS Recording: {}", "🎥".green(), path);
+        if let Some(path) = path {
+            if !Path::new(&path).exists() {
+                anyhow::bail!("iOS recording was not written to {}", path);
             }
+            println!("  {} Saved iOS Recording: {}", "🎥".green(), path);
         }
         Ok(())
     }
 
-    async fn rotate_screen(&self, _mode: &str) -> Result<()> {
-        if self.is_simulator {
-            // Use AppleScript to rotate simulator
-            // Requires Simulator app to be running
-            println!(
-                "      {} Rotating simulator via AppleScript...",
-                "🔄".blue()
-            );
-
-            // Script to rotate left (Cmd+Left Arrow equivalent via menu)
-            let script = r#"
-                tell application "Simulator" to activate
-                tell application "System Events" 
-                    tell process "Simulator"
-                        click menu item "Rotate Left" of menu "Device" of menu bar 1
-                    end tell
-                end tell
-            "#;
-
-            use std::process::Command;
-            let output = Command::new("osascript").arg("-e").arg(script).output()?;
-
-            if !output.status.success() {
-                let err = String::from_utf8_lossy(&output.stderr);
-                // Don't fail the test, just warn, as this is brittle
-                println!(
-                    "      {} Failed to rotate simulator: {}",
-                    "⚠️".yellow(),
-                    err.trim()
-                );
-            } else {
-                // Wait a bit for rotation animation
-                tokio::time::sleep(Duration::from_millis(1000)).await;
-            }
-            Ok(())
-        } else {
-            // Physical device rotation via WDA is complex (needs orientation endpoint)
-            // For now return error to be explicit
-            anyhow::bail!(
-                "rotate_screen not yet supported on physical iOS devices (requires WDA update)"
-            );
-        }
+    async fn rotate_screen(&self, mode: &str) -> Result<()> {
+        let orientation = crate::parser::types::Orientation::from_mode(mode)?;
+        self.set_orientation(orientation).await
     }
 
     async fn press_key(&self, key: &str) -> Result<()> {
@@ -1463,14 +1649,10 @@ impl PlatformDriver for IosDriver {
     }
 
     async fn get_pixel_color(&self, x: i32, y: i32) -> Result<(u8, u8, u8)> {
-        // Take screenshot and extract pixel using common utility
-        let temp_path = format!("/tmp/ios_pixel_{}.png", Uuid::new_v4());
-        idb::screenshot(&self.udid, &temp_path).await?;
-
-        let img = image::open(&temp_path).context("Failed to open screenshot for pixel color")?;
-        let _ = std::fs::remove_file(&temp_path);
-
-        Ok(common::get_pixel_from_image(&img, x as u32, y as u32))
+        let img = self.capture_screen().await?;
+        // x/y are in points like every other iOS coordinate; the image is in pixels
+        let (px, py) = common::scale_point_to_image(&img, self.screen_size().0, x, y);
+        Ok(common::get_pixel_from_image(&img, px, py))
     }
 
     async fn set_permissions(
@@ -1571,7 +1753,7 @@ impl PlatformDriver for IosDriver {
 
     async fn open_notifications(&self) -> Result<()> {
         // Swipe down from top center
-        let (w, _h) = self.screen_size;
+        let (w, _h) = self.screen_size();
         let center_x = (w / 2) as i32;
         // Start very top (0) to 500
         idb::swipe(&self.udid, center_x, 0, center_x, 500, Some(300)).await
@@ -1579,7 +1761,7 @@ impl PlatformDriver for IosDriver {
 
     async fn open_quick_settings(&self) -> Result<()> {
         // Control Center: Swipe down from top-right
-        let (w, _h) = self.screen_size;
+        let (w, _h) = self.screen_size();
         let start_x = (w as i32) - 10;
         idb::swipe(&self.udid, start_x, 0, start_x, 400, Some(400)).await
     }
@@ -1598,7 +1780,7 @@ impl PlatformDriver for IosDriver {
         idb::press_button(&self.udid, "HOME").await?;
         // If it was locked, this might wake it. If on lock screen, might need swipe up?
         // Let's try to swipe up from bottom just in case
-        let (w, h) = self.screen_size;
+        let (w, h) = self.screen_size();
         let center_x = (w / 2) as i32;
         let bottom_y = (h as i32) - 10;
         let mid_y = (h / 2) as i32;
@@ -1616,6 +1798,175 @@ impl PlatformDriver for IosDriver {
         idb::install_app(&self.udid, path).await
     }
 
+    async fn install_app_with_options(
+        &self,
+        params: &crate::parser::types::InstallAppParams,
+    ) -> Result<()> {
+        // idb always replaces an existing install, so `reinstall` needs no flag;
+        // permissions and version codes have no idb equivalent
+        if params.allow_downgrade {
+            println!(
+                "  {} allowDowngrade is Android-only, ignoring on iOS",
+                "⚠".yellow()
+            );
+        }
+        self.install_app(&params.path).await
+    }
+
+    async fn snapshot_app_data(&self, app_id: &str, dir: &Path) -> Result<()> {
+        let container = self.app_data_container(app_id).await?;
+        if dir.exists() {
+            std::fs::remove_dir_all(dir)?;
+        }
+        std::fs::create_dir_all(dir)?;
+        copy_dir_contents(&container, dir).await
+    }
+
+    async fn restore_app_data(&self, app_id: &str, dir: &Path) -> Result<()> {
+        if !dir.exists() {
+            anyhow::bail!("No app data snapshot at {}", dir.display());
+        }
+        let container = self.app_data_container(app_id).await?;
+        idb::terminate_app(&self.udid, app_id, self.is_simulator).await?;
+
+        for folder in ["Documents", "Library", "tmp"] {
+            let path = container.join(folder);
+            if path.exists() {
+                std::fs::remove_dir_all(&path)?;
+            }
+        }
+        copy_dir_contents(dir, &container).await
+    }
+
+    async fn teardown(&self) -> Result<()> {
+        if self.shutdown_on_finish && self.booted_here {
+            println!(
+                "  {} Shutting down simulator {}",
+                "⏻".cyan(),
+                self.device_name
+            );
+            idb::shutdown_simulator(&self.udid).await?;
+        }
+        Ok(())
+    }
+
+    async fn set_date_time(&self, value: Option<chrono::NaiveDateTime>) -> Result<()> {
+        if !self.is_simulator {
+            println!(
+                "  {} setDateTime is not supported on physical iOS devices",
+                "⚠".yellow()
+            );
+            return Ok(());
+        }
+
+        // Simulators share the host clock, so only the status bar time can be overridden
+        let args: Vec<String> = match value {
+            Some(dt) => vec![
+                "override".to_string(),
+                "--time".to_string(),
+                dt.format("%Y-%m-%dT%H:%M:%S").to_string(),
+            ],
+            None => vec!["clear".to_string()],
+        };
+        let status = tokio::process::Command::new("xcrun")
+            .args(["simctl", "status_bar", &self.udid])
+            .args(&args)
+            .status()
+            .await
+            .context("Failed to run simctl status_bar")?;
+        if !status.success() {
+            anyhow::bail!("simctl status_bar {} failed", args[0]);
+        }
+        if value.is_some() {
+            println!(
+                "  {} iOS simulator: only the status bar time is overridden; the app clock follows the host",
+                "⚠".yellow()
+            );
+        }
+        Ok(())
+    }
+
+    fn has_cheap_dialog_probe(&self) -> bool {
+        !self.is_simulator
+    }
+
+    async fn handle_system_dialog(&self, button: &str) -> Result<bool> {
+        const ALLOW_LABELS: &[&str] = &["Allow While Using App", "Allow", "Allow Once", "OK"];
+        const DENY_LABELS: &[&str] = &["Don’t Allow", "Don't Allow"];
+
+        // Cheap alert probe first so auto-handling doesn't dump the UI before every
+        // command; simulators have no WDA and take the full check, which the
+        // executor only runs after an element lookup fails
+        if !self.is_simulator {
+            let mut wda = self.wda_client.lock().await;
+            if let Some(ref mut client) = *wda {
+                if !client.has_alert().await.unwrap_or(true) {
+                    return Ok(false);
+                }
+            }
+        }
+
+        self.invalidate_cache().await;
+        let elements = self.get_ui_hierarchy().await?;
+        let buttons: Vec<&IosElement> = accessibility::flatten_elements(&elements)
+            .into_iter()
+            .filter(|e| e.visible && e.element_type.as_deref() == Some("Button"))
+            .collect();
+        let has_label = |label: &str| {
+            buttons.iter().copied().find(|e| {
+                e.label
+                    .as_deref()
+                    .is_some_and(|l| l.eq_ignore_ascii_case(label))
+            })
+        };
+
+        // SpringBoard permission alerts always offer "Don't Allow"
+        let is_permission_alert = DENY_LABELS.iter().any(|l| has_label(l).is_some());
+        let target = match button.to_lowercase().as_str() {
+            "allow" if is_permission_alert => ALLOW_LABELS.iter().find_map(|l| has_label(l)),
+            "deny" if is_permission_alert => DENY_LABELS.iter().find_map(|l| has_label(l)),
+            "allow" | "deny" => return Ok(false),
+            _ => has_label(button),
+        };
+
+        let Some(element) = target else {
+            return Ok(false);
+        };
+        let (x, y) = element.center();
+        self.tap(&Selector::Point { x, y }).await?;
+        Ok(true)
+    }
+
+    async fn get_app_version(&self, app_id: &str) -> Result<AppVersion> {
+        if !self.is_simulator {
+            let (version, build) = idb::device_app_version(&self.udid, app_id)
+                .await?
+                .ok_or_else(|| anyhow::anyhow!("App '{}' is not installed", app_id))?;
+            return Ok(AppVersion { version, build });
+        }
+
+        let output = tokio::process::Command::new("xcrun")
+            .args(["simctl", "appinfo", &self.udid, app_id])
+            .output()
+            .await
+            .context("Failed to run simctl appinfo")?;
+        let info = String::from_utf8_lossy(&output.stdout);
+
+        // appinfo prints an old-style plist: `CFBundleVersion = 45;`
+        let read_key = |key: &str| {
+            Regex::new(&format!(r#"{}\s*=\s*"?([^";]+)"?;"#, key))
+                .ok()
+                .and_then(|re| re.captures(&info).map(|c| c[1].trim().to_string()))
+        };
+
+        let version = read_key("CFBundleShortVersionString")
+            .ok_or_else(|| anyhow::anyhow!("App '{}' is not installed", app_id))?;
+        Ok(AppVersion {
+            version,
+            build: read_key("CFBundleVersion"),
+        })
+    }
+
     async fn uninstall_app(&self, app_id: &str) -> Result<()> {
         println!("  {} Uninstalling app: {}", "🗑".cyan(), app_id);
         idb::uninstall_app(&self.udid, app_id).await
@@ -1637,11 +1988,36 @@ impl PlatformDriver for IosDriver {
         Ok(())
     }
 
-    async fn set_orientation(&self, _mode: crate::parser::types::Orientation) -> Result<()> {
-        println!(
-             "  {} set_orientation not reliably supported on iOS Simulators via idb (requires private APIs or XCUI)", 
-             "⚠️".yellow()
-        );
+    async fn set_orientation(&self, mode: crate::parser::types::Orientation) -> Result<()> {
+        // idb cannot rotate; simulators only get a WDA client when one is already running
+        let mut wda = self.wda_client.lock().await;
+        if wda.is_none() && self.is_simulator {
+            let port = super::wda::DEFAULT_WDA_PORT;
+            if super::wda::health_check("localhost", port, &self.udid)
+                .await
+                .is_ok()
+            {
+                *wda = Some(WdaClient::new(port));
+            }
+        }
+        let Some(ref mut client) = *wda else {
+            anyhow::bail!(
+                "Rotating iOS needs WebDriverAgent; start WDA on port {} for this {}",
+                super::wda::DEFAULT_WDA_PORT,
+                if self.is_simulator {
+                    "simulator"
+                } else {
+                    "device"
+                }
+            );
+        };
+        client.set_orientation(&mode).await?;
+        drop(wda);
+        self.landscape.store(mode.is_landscape(), Ordering::Relaxed);
+
+        // Wait for rotation animation
+        tokio::time::sleep(Duration::from_millis(1000)).await;
+        self.invalidate_cache().await;
         Ok(())
     }
 
@@ -1650,12 +2026,10 @@ impl PlatformDriver for IosDriver {
         name: Option<String>,
         points: Vec<crate::parser::gps::GpsPoint>,
         speed_kmh: Option<f64>,
-        speed_mode: SpeedMode,
-        speed_noise: Option<f64>,
+        drive_profile: DriveProfile,
         interval_ms: u64,
         loop_route: bool,
     ) -> Result<()> {
-        use rand::Rng;
         use rand::SeedableRng;
 
         if !self.is_simulator {
@@ -1686,19 +2060,16 @@ impl PlatformDriver for IosDriver {
         let interval = std::time::Duration::from_millis(interval_ms);
 
         if let Some(speed) = speed_kmh {
-            let mode_str = match speed_mode {
-                SpeedMode::Linear => "Linear",
-                SpeedMode::Noise => &format!("Noise ±{:.1}", speed_noise.unwrap_or(5.0)),
-            };
             println!(
                 "  {} Using speed: {} km/h ({})",
                 "🚗".cyan(),
                 speed,
-                mode_str
+                drive_profile
             );
         }
 
         let points_clone = points.clone();
+        let route_distances = crate::parser::gps::cumulative_distances(&points);
         let mock_states = self.mock_states.clone();
         let instance_key = instance_name.clone();
 
@@ -1712,8 +2083,7 @@ impl PlatformDriver for IosDriver {
             state.finished = false;
             state.paused = false;
             state.speed = speed_kmh;
-            state.speed_mode = speed_mode.clone();
-            state.speed_noise = speed_noise;
+            state.drive_profile = drive_profile.clone();
         }
 
         tokio::spawn(async move {
@@ -1737,15 +2107,15 @@ impl PlatformDriver for IosDriver {
                     }
 
                     // Update state
-                    let (current_speed, current_mode, current_noise) = {
+                    let (current_speed, current_profile) = {
                         let mut states = mock_states.lock().await;
                         if let Some(state) = states.get_mut(&instance_key) {
                             state.current_lat = Some(lat);
                             state.current_lon = Some(lon);
                             state.is_running = true;
-                            (state.speed, state.speed_mode.clone(), state.speed_noise)
+                            (state.speed, state.drive_profile.clone())
                         } else {
-                            (speed_kmh, speed_mode.clone(), speed_noise)
+                            (speed_kmh, drive_profile.clone())
                         }
                     };
 
@@ -1761,18 +2131,31 @@ impl PlatformDriver for IosDriver {
                         .output()
                         .await;
 
+                    // Stops mode: halt on the first point past each stop boundary
+                    if i > 0
+                        && current_speed.is_some()
+                        && crate::parser::gps::stop_between(
+                            &current_profile,
+                            route_distances[i - 1],
+                            route_distances[i],
+                        )
+                    {
+                        tokio::time::sleep(std::time::Duration::from_millis(
+                            current_profile.stop_duration_ms(),
+                        ))
+                        .await;
+                    }
+
                     if i < points_clone.len() - 1 {
                         let next_point = &points_clone[i + 1];
                         let delay = if let Some(base_speed) = current_speed {
-                            // Apply noise if enabled
-                            let effective_speed = match current_mode {
-                                SpeedMode::Linear => base_speed,
-                                SpeedMode::Noise => {
-                                    let noise_range = current_noise.unwrap_or(5.0);
-                                    let noise: f64 = rng.gen_range(-noise_range..noise_range);
-                                    (base_speed + noise).max(1.0)
-                                }
-                            };
+                            let effective_speed = crate::parser::gps::profile_speed_kmh(
+                                &current_profile,
+                                base_speed,
+                                (route_distances[i] + route_distances[i + 1]) / 2.0,
+                                route_distances.last().copied().unwrap_or(0.0),
+                                &mut rng,
+                            );
 
                             let dist_m =
                                 haversine_distance_ios(lat, lon, next_point.lat, next_point.lon);
@@ -1826,7 +2209,7 @@ impl PlatformDriver for IosDriver {
         name: Option<String>,
         lat: f64,
         lon: f64,
-        tolerance: f64,
+        tolerance: LocationTolerance,
         timeout_ms: u64,
     ) -> Result<()> {
         let start = Instant::now();
@@ -1834,7 +2217,7 @@ impl PlatformDriver for IosDriver {
         let instance_key = name.unwrap_or_default();
 
         println!(
-            "  {} Waiting for location ({:.4}, {:.4}) within {:.1}m...",
+            "  {} Waiting for location ({:.4}, {:.4}) within {}...",
             "⏳".cyan(),
             lat,
             lon,
@@ -1859,7 +2242,7 @@ impl PlatformDriver for IosDriver {
 
             if let Some((c_lat, c_lon)) = current_pos {
                 let dist = haversine_distance_ios(c_lat, c_lon, lat, lon);
-                if dist <= tolerance {
+                if tolerance.contains((c_lat, c_lon), (lat, lon)) {
                     println!(
                         "  {} Reached location ({:.4}, {:.4}). Distance: {:.1}m",
                         "✅".green(),
@@ -1943,11 +2326,11 @@ impl PlatformDriver for IosDriver {
         }
 
         if let Some(mode) = speed_mode {
-            state.speed_mode = mode;
+            state.drive_profile.mode = mode;
         }
 
         if let Some(noise) = speed_noise {
-            state.speed_noise = Some(noise);
+            state.drive_profile.noise = Some(noise);
         }
 
         if pause == Some(true) {
@@ -2048,7 +2431,7 @@ impl PlatformDriver for IosDriver {
         Ok(())
     }
 
-    async fn set_network_conditions(&self, _profile: &str) -> Result<()> {
+    async fn set_network_conditions(&self, _conditions: &NetworkConditions) -> Result<()> {
         // Network link conditioner is system-wide, hard to control via CLI without external tools
         println!(
             "  {} Network emulation not supported on iOS directly",
@@ -2145,3 +2528,18 @@ fn calculate_bearing_ios(lat1: f64, lon1: f64, lat2: f64, lon2: f64) -> f64 {
 
     (bearing_deg + 360.0) % 360.0
 }
+
+/// Recursively copy the contents of `src` into `dest` (preserving attributes)
+async fn copy_dir_contents(src: &Path, dest: &Path) -> Result<()> {
+    let status = tokio::process::Command::new("cp")
+        .arg("-Rp")
+        .arg(format!("{}/.", src.display()))
+        .arg(dest)
+        .status()
+        .await
+        .context("Failed to run cp")?;
+    if !status.success() {
+        anyhow::bail!("Failed to copy {} to {}", src.display(), dest.display());
+    }
+    Ok(())
+}
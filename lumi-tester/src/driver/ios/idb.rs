@@ -2,6 +2,7 @@
 //!
 //! Provides functions to interact with iOS devices and simulators via idb CLI.
 
+use crate::driver::trace;
 use crate::utils::binary_resolver;
 use anyhow::{Context, Result};
 use serde::Deserialize;
@@ -16,18 +17,23 @@ pub struct IosTarget {
     #[serde(rename = "type")]
     pub target_type: String,
     pub state: String,
+    /// e.g. "iOS 17.2" from idb, "17.2" from xctrace
+    #[serde(default)]
+    pub os_version: Option<String>,
 }
 
 /// Run idb command and return stdout
 async fn run_idb_command(args: &[&str]) -> Result<String> {
     let idb_path = binary_resolver::find_idb()?;
+    let call = trace::Call::start("idb", args);
     let output = Command::new(idb_path)
         .args(args)
         .stdout(Stdio::piped())
         .stderr(Stdio::piped())
         .output()
-        .await
-        .context("Failed to execute idb command. Is idb installed?")?;
+        .await;
+    call.finish(output.as_ref().is_ok_and(|o| o.status.success()));
+    let output = output.context("Failed to execute idb command. Is idb installed?")?;
 
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr);
@@ -202,11 +208,15 @@ fn parse_xctrace_device_line(line: &str, is_offline: bool) -> Option<IosTarget>
     // Get the name (everything before the UDID parenthesis)
     let name_part = line[..last_paren_start?].trim();
 
-    // Remove trailing version info like "(18.5)" from name
-    let name = if let Some(last_open) = name_part.rfind('(') {
-        name_part[..last_open].trim().to_string()
+    // Split off trailing version info like "(18.5)" from name
+    let (name, os_version) = if let Some(last_open) = name_part.rfind('(') {
+        let version = name_part[last_open + 1..].trim_end_matches(')').trim();
+        (
+            name_part[..last_open].trim().to_string(),
+            Some(version.to_string()).filter(|v| !v.is_empty()),
+        )
     } else {
-        name_part.to_string()
+        (name_part.to_string(), None)
     };
 
     Some(IosTarget {
@@ -218,6 +228,7 @@ fn parse_xctrace_device_line(line: &str, is_offline: bool) -> Option<IosTarget>
         } else {
             "Booted".to_string()
         },
+        os_version,
     })
 }
 
@@ -285,6 +296,153 @@ pub async fn terminate_app(udid: &str, bundle_id: &str, is_simulator: bool) -> R
     Ok(())
 }
 
+/// Version and build number of an app installed on a real device, via devicectl
+pub async fn device_app_version(
+    udid: &str,
+    bundle_id: &str,
+) -> Result<Option<(String, Option<String>)>> {
+    let json_path = std::env::temp_dir().join(format!("devicectl_apps_{}.json", udid));
+    let json_arg = json_path.to_string_lossy().to_string();
+    let output = Command::new("xcrun")
+        .args([
+            "devicectl",
+            "device",
+            "info",
+            "apps",
+            "--device",
+            udid,
+            "--bundle-id",
+            bundle_id,
+            "--json-output",
+            &json_arg,
+        ])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .output()
+        .await
+        .context("Failed to list apps with devicectl")?;
+    let json = std::fs::read_to_string(&json_path);
+    let _ = std::fs::remove_file(&json_path);
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!("Failed to read app info: {}", stderr.trim());
+    }
+    let json = json.context("devicectl wrote no app info")?;
+    Ok(parse_devicectl_app_version(&json, bundle_id))
+}
+
+/// `version`/`bundleVersion` of `bundle_id` in `devicectl device info apps` JSON
+fn parse_devicectl_app_version(json: &str, bundle_id: &str) -> Option<(String, Option<String>)> {
+    let value: serde_json::Value = serde_json::from_str(json).ok()?;
+    let app = value["result"]["apps"]
+        .as_array()?
+        .iter()
+        .find(|app| app["bundleIdentifier"] == bundle_id)?;
+    let version = app["version"].as_str()?.to_string();
+    let build = app["bundleVersion"].as_str().map(str::to_string);
+    Some((version, build))
+}
+
+/// Hardware model identifier, e.g. "iPhone15,2". Simulators report the model
+/// they emulate; real devices are read via devicectl, then ideviceinfo.
+pub async fn product_type(udid: &str, is_simulator: bool) -> Option<String> {
+    let non_empty = |out: std::process::Output| {
+        let value = String::from_utf8_lossy(&out.stdout).trim().to_string();
+        (out.status.success() && !value.is_empty()).then_some(value)
+    };
+    if is_simulator {
+        let output = Command::new("xcrun")
+            .args(["simctl", "getenv", udid, "SIMULATOR_MODEL_IDENTIFIER"])
+            .output()
+            .await
+            .ok()?;
+        return non_empty(output);
+    }
+
+    let json_path = std::env::temp_dir().join(format!("devicectl_details_{}.json", udid));
+    let json_arg = json_path.to_string_lossy().to_string();
+    let status = Command::new("xcrun")
+        .args([
+            "devicectl",
+            "device",
+            "info",
+            "details",
+            "--device",
+            udid,
+            "--json-output",
+            &json_arg,
+        ])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .await;
+    let json = std::fs::read_to_string(&json_path);
+    let _ = std::fs::remove_file(&json_path);
+    if let (Ok(status), Ok(json)) = (status, json) {
+        if let Some(product) = status
+            .success()
+            .then(|| parse_devicectl_product_type(&json))
+            .flatten()
+        {
+            return Some(product);
+        }
+    }
+
+    let output = Command::new("ideviceinfo")
+        .args(["-u", udid, "-k", "ProductType"])
+        .output()
+        .await
+        .ok()?;
+    non_empty(output)
+}
+
+/// `productType` in `devicectl device info details` JSON
+fn parse_devicectl_product_type(json: &str) -> Option<String> {
+    let value: serde_json::Value = serde_json::from_str(json).ok()?;
+    value["result"]["hardwareProperties"]["productType"]
+        .as_str()
+        .map(str::to_string)
+}
+
+/// Boot a simulator and block until it has finished booting
+pub async fn boot_simulator(udid: &str) -> Result<()> {
+    let output = tokio::process::Command::new("xcrun")
+        .args(["simctl", "boot", udid])
+        .output()
+        .await
+        .context("Failed to run simctl boot")?;
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    // Racing another boot is fine; bootstatus below waits either way
+    if !output.status.success() && !stderr.contains("current state: Booted") {
+        anyhow::bail!("Failed to boot simulator {}: {}", udid, stderr.trim());
+    }
+
+    let status = tokio::process::Command::new("xcrun")
+        .args(["simctl", "bootstatus", udid, "-b"])
+        .stdout(std::process::Stdio::null())
+        .status()
+        .await
+        .context("Failed to run simctl bootstatus")?;
+    if !status.success() {
+        anyhow::bail!("Simulator {} did not finish booting", udid);
+    }
+    Ok(())
+}
+
+/// Shut down a simulator
+pub async fn shutdown_simulator(udid: &str) -> Result<()> {
+    let status = tokio::process::Command::new("xcrun")
+        .args(["simctl", "shutdown", udid])
+        .status()
+        .await
+        .context("Failed to run simctl shutdown")?;
+    if !status.success() {
+        anyhow::bail!("Failed to shut down simulator {}", udid);
+    }
+    Ok(())
+}
+
 /// Uninstall an app (for clear state)
 pub async fn uninstall_app(udid: &str, bundle_id: &str) -> Result<()> {
     run_idb_command_with_target(udid, &["uninstall", bundle_id]).await?;
@@ -330,7 +488,13 @@ pub async fn press_button(udid: &str, button: &str) -> Result<()> {
     Ok(())
 }
 
-/// Press keyboard key (e.g., XCUIKeyboardKeyDelete)
+/// HID usage code of the Return key, for [`press_key`]
+pub const HID_KEY_RETURN: &str = "40";
+
+/// HID usage code of the Delete (backspace) key, for [`press_key`]
+pub const HID_KEY_DELETE: &str = "42";
+
+/// Press keyboard key by HID usage code (e.g. [`HID_KEY_RETURN`])
 pub async fn press_key(udid: &str, key: &str) -> Result<()> {
     run_idb_command_with_target(udid, &["ui", "key", key]).await?;
     Ok(())
@@ -392,28 +556,124 @@ pub async fn pull_file(udid: &str, src: &str, dest: &str) -> Result<()> {
     Ok(())
 }
 
-/// Get system logs
-pub async fn get_logs(udid: &str, limit: u32) -> Result<String> {
-    // idb log streams continuously, we'll capture for a brief moment
-    let idb_path = binary_resolver::find_idb()?;
-    let output = Command::new(idb_path)
-        .args(&["--udid", udid, "log", "--", "--style", "compact"])
+/// How far back device logs are read
+const LOG_WINDOW: &str = "2m";
+
+/// Lines kept when the caller passes no limit (`0`)
+const DEFAULT_LOG_LINES: u32 = 1000;
+
+/// Get the last `limit` system log lines (`0` for the default)
+///
+/// Simulators keep a queryable log store, read with `simctl spawn log show`.
+/// Real devices are copied into a log archive with `log collect` first.
+pub async fn get_logs(udid: &str, limit: u32, is_simulator: bool) -> Result<String> {
+    let limit = if limit == 0 { DEFAULT_LOG_LINES } else { limit };
+    let logs = if is_simulator {
+        run_log_command(
+            "xcrun",
+            &[
+                "simctl", "spawn", udid, "log", "show", "--style", "compact", "--last", LOG_WINDOW,
+            ],
+        )
+        .await?
+    } else {
+        collect_device_logs(udid).await?
+    };
+    Ok(tail_lines(&logs, limit))
+}
+
+/// Recent log of a real device, through a temporary `.logarchive`
+async fn collect_device_logs(udid: &str) -> Result<String> {
+    let archive = std::env::temp_dir().join(format!(
+        "lumi-{}-{}.logarchive",
+        udid,
+        uuid::Uuid::new_v4().simple()
+    ));
+    let archive_str = archive.to_string_lossy().to_string();
+    let collected = run_log_command(
+        "log",
+        &[
+            "collect",
+            "--device-udid",
+            udid,
+            "--last",
+            LOG_WINDOW,
+            "--output",
+            &archive_str,
+        ],
+    )
+    .await;
+    let logs = match collected {
+        Ok(_) => {
+            run_log_command(
+                "log",
+                &["show", "--archive", &archive_str, "--style", "compact"],
+            )
+            .await
+        }
+        Err(e) => Err(e),
+    };
+    let _ = std::fs::remove_dir_all(&archive);
+    logs
+}
+
+/// Run a `log` tool invocation and return its stdout
+async fn run_log_command(program: &str, args: &[&str]) -> Result<String> {
+    let call = trace::Call::start(program, args);
+    let output = Command::new(program)
+        .args(args)
         .stdout(Stdio::piped())
         .stderr(Stdio::piped())
         .output()
-        .await
-        .context("Failed to get logs")?;
+        .await;
+    call.finish(output.as_ref().is_ok_and(|o| o.status.success()));
+    let output = output.with_context(|| format!("Failed to run {} {}", program, args[0]))?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "{} {} failed: {}",
+            program,
+            args[..args.len().min(4)].join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
 
-    let logs = String::from_utf8_lossy(&output.stdout);
-    let lines: Vec<&str> = logs.lines().take(limit as usize).collect();
-    Ok(lines.join("\n"))
+/// The last `limit` lines of `text`
+fn tail_lines(text: &str, limit: u32) -> String {
+    let lines: Vec<&str> = text.lines().collect();
+    let start = lines.len().saturating_sub(limit as usize);
+    lines[start..].join("\n")
 }
 
 /// Start video recording (returns the child process for later termination)
-pub async fn start_recording(udid: &str, output_path: &str) -> Result<tokio::process::Child> {
-    let idb_path = binary_resolver::find_idb()?;
-    let child = Command::new(idb_path)
-        .args(&["--udid", udid, "record", "video", output_path])
+///
+/// Real devices record through `idb record-video`; simulators through
+/// `xcrun simctl io recordVideo`, which needs no idb companion.
+pub async fn start_recording(
+    udid: &str,
+    output_path: &str,
+    is_simulator: bool,
+) -> Result<tokio::process::Child> {
+    let mut command = if is_simulator {
+        let mut command = Command::new("xcrun");
+        command.args([
+            "simctl",
+            "io",
+            udid,
+            "recordVideo",
+            "--codec=h264",
+            "--force",
+            output_path,
+        ]);
+        command
+    } else {
+        let mut command = Command::new(binary_resolver::find_idb()?);
+        command.args(["record-video", "--udid", udid, output_path]);
+        command
+    };
+    let child = command
+        .stdin(Stdio::null())
         .stdout(Stdio::null())
         .stderr(Stdio::null())
         .spawn()
@@ -422,6 +682,33 @@ pub async fn start_recording(udid: &str, output_path: &str) -> Result<tokio::pro
     Ok(child)
 }
 
+/// Stop a recording started by [`start_recording`]
+///
+/// Both tools only finalize the MP4 on SIGINT, so the process is interrupted
+/// and given time to flush before it is killed.
+pub async fn stop_recording(mut child: tokio::process::Child) -> Result<()> {
+    match child.id() {
+        Some(pid) => {
+            let _ = Command::new("kill")
+                .args(["-INT", &pid.to_string()])
+                .output()
+                .await;
+        }
+        None => return Ok(()), // already exited
+    }
+
+    match tokio::time::timeout(std::time::Duration::from_secs(10), child.wait()).await {
+        Ok(status) => {
+            status.context("Failed to wait for video recording")?;
+        }
+        Err(_) => {
+            let _ = child.kill().await;
+            anyhow::bail!("Video recording did not stop within 10s");
+        }
+    }
+    Ok(())
+}
+
 /// Get screen dimensions from device description
 pub async fn get_screen_size(udid: &str) -> Result<(u32, u32)> {
     let output = describe(udid).await?;
@@ -448,14 +735,51 @@ pub async fn get_screen_size(udid: &str) -> Result<(u32, u32)> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_tail_lines_keeps_newest() {
+        let logs = "a\nb\nc\nd";
+        assert_eq!(tail_lines(logs, 2), "c\nd");
+        assert_eq!(tail_lines(logs, 10), logs);
+        assert_eq!(tail_lines(logs, 0), "");
+    }
+
     #[test]
     fn test_target_parsing() {
-        let json =
-            r#"{"udid":"12345-ABCDE","name":"iPhone 15","type":"simulator","state":"Booted"}"#;
+        let json = r#"{"udid":"12345-ABCDE","name":"iPhone 15","type":"simulator","state":"Booted","os_version":"iOS 17.2"}"#;
         let target: IosTarget = serde_json::from_str(json).unwrap();
         assert_eq!(target.udid, "12345-ABCDE");
         assert_eq!(target.name, "iPhone 15");
         assert_eq!(target.target_type, "simulator");
         assert_eq!(target.state, "Booted");
+        assert_eq!(target.os_version.as_deref(), Some("iOS 17.2"));
+
+        let device =
+            parse_xctrace_device_line("NghiNV (18.5) (00008020-0012446C1ADA002E)", false).unwrap();
+        assert_eq!(device.name, "NghiNV");
+        assert_eq!(device.os_version.as_deref(), Some("18.5"));
+    }
+
+    #[test]
+    fn test_parse_devicectl_app_version() {
+        let json = r#"{"info":{"outcome":"success"},"result":{"apps":[
+            {"bundleIdentifier":"com.other","version":"9.9"},
+            {"bundleIdentifier":"com.example.app","name":"Example","version":"1.2.3","bundleVersion":"45"}
+        ]}}"#;
+        assert_eq!(
+            parse_devicectl_app_version(json, "com.example.app"),
+            Some(("1.2.3".to_string(), Some("45".to_string())))
+        );
+        assert_eq!(parse_devicectl_app_version(json, "com.missing"), None);
+    }
+
+    #[test]
+    fn test_parse_devicectl_product_type() {
+        let json = r#"{"info":{"outcome":"success"},"result":{"deviceProperties":{"name":"QA iPhone"},
+            "hardwareProperties":{"marketingName":"iPhone 14 Pro","productType":"iPhone15,2"}}}"#;
+        assert_eq!(
+            parse_devicectl_product_type(json).as_deref(),
+            Some("iPhone15,2")
+        );
+        assert_eq!(parse_devicectl_product_type(r#"{"result":{}}"#), None);
     }
 }
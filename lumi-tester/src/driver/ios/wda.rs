@@ -3,6 +3,7 @@
 //! Provides functions to interact with iOS real devices via WebDriverAgent HTTP API.
 //! WDA runs on port 8100 by default and uses XCTest framework under the hood.
 
+use crate::parser::types::Orientation;
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 use std::time::Duration;
@@ -10,6 +11,56 @@ use std::time::Duration;
 /// Default WDA port
 pub const DEFAULT_WDA_PORT: u16 = 8100;
 
+/// Check that WDA answers `/status` and is ready, with a remediation hint on failure
+pub async fn health_check(host: &str, port: u16, udid: &str) -> Result<()> {
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(5))
+        .build()?;
+    let url = format!("http://{}:{}/status", host, port);
+
+    let resp = match client.get(&url).send().await {
+        Ok(resp) => resp,
+        Err(e) if e.is_timeout() => anyhow::bail!(
+            "WDA on {}:{} did not respond within 5s; it may have crashed. \
+             Restart WebDriverAgentRunner on the device and try again",
+            host,
+            port
+        ),
+        Err(e) if e.is_connect() => anyhow::bail!(
+            "WDA not reachable on {}:{}. Forward the port with `iproxy {} {} -u {}` \
+             and make sure WebDriverAgentRunner is running on the device",
+            host,
+            port,
+            port,
+            port,
+            udid
+        ),
+        Err(e) => return Err(e).with_context(|| format!("WDA request to {} failed", url)),
+    };
+
+    if !resp.status().is_success() {
+        anyhow::bail!(
+            "WDA on {}:{} returned HTTP {}; restart WebDriverAgentRunner",
+            host,
+            port,
+            resp.status()
+        );
+    }
+
+    let status: WdaStatus = resp
+        .json()
+        .await
+        .with_context(|| format!("Unexpected /status response from {}", url))?;
+    if !status.value.ready {
+        anyhow::bail!(
+            "WDA on {}:{} is running but not ready yet (device locked or still launching?)",
+            host,
+            port
+        );
+    }
+    Ok(())
+}
+
 /// WDA HTTP Client for real iOS device automation
 pub struct WdaClient {
     /// Base URL for WDA (e.g., "http://localhost:8100")
@@ -86,6 +137,9 @@ struct DragAction {
 #[derive(Debug, Serialize)]
 struct KeysAction {
     value: Vec<String>,
+    /// Keys per second; WDA's default is fast enough to drop characters
+    #[serde(skip_serializing_if = "Option::is_none")]
+    frequency: Option<u32>,
 }
 
 /// Button press action
@@ -260,20 +314,109 @@ impl WdaClient {
 
     /// Input text
     pub async fn input_text(&mut self, text: &str) -> Result<()> {
+        self.type_keys(text, None).await
+    }
+
+    /// Type one key at a time at `frequency` keys per second, for fields
+    /// that drop or reject fast input (secure fields, custom inputs)
+    pub async fn input_text_slowly(&mut self, text: &str, frequency: u32) -> Result<()> {
+        self.type_keys(text, Some(frequency)).await
+    }
+
+    /// WDA id of the element with keyboard focus (`element/active`), if any
+    pub async fn focused_element_id(&mut self) -> Result<Option<String>> {
+        let session_id = self.ensure_session().await?;
+        let url = format!("{}/session/{}/element/active", self.base_url, session_id);
+        let resp = self
+            .client
+            .get(&url)
+            .send()
+            .await
+            .context("Failed to get focused element")?;
+        if !resp.status().is_success() {
+            return Ok(None);
+        }
+        let active: serde_json::Value = resp
+            .json()
+            .await
+            .context("Failed to parse focused element")?;
+        Ok(active["value"]["ELEMENT"]
+            .as_str()
+            .or_else(|| active["value"]["element-6066-11e4-a07c-4a2b4c6fa6e2"].as_str())
+            .map(str::to_string))
+    }
+
+    /// Clear the field that has keyboard focus
+    pub async fn clear_focused_field(&mut self) -> Result<()> {
+        let element_id = self
+            .focused_element_id()
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("No focused text field to clear"))?;
+        let session_id = self.ensure_session().await?;
+        let url = format!(
+            "{}/session/{}/element/{}/clear",
+            self.base_url, session_id, element_id
+        );
+        let resp = self
+            .client
+            .post(&url)
+            .json(&serde_json::json!({}))
+            .send()
+            .await
+            .context("Failed to clear text field")?;
+        if !resp.status().is_success() {
+            let body = resp.text().await.unwrap_or_default();
+            anyhow::bail!("WDA could not clear the text field: {}", body);
+        }
+        Ok(())
+    }
+
+    async fn type_keys(&mut self, text: &str, frequency: Option<u32>) -> Result<()> {
         let session_id = self.ensure_session().await?;
         let url = format!("{}/session/{}/wda/keys", self.base_url, session_id);
 
         // WDA expects each character as a separate string in the array
         let chars: Vec<String> = text.chars().map(|c| c.to_string()).collect();
-        let action = KeysAction { value: chars };
+        let action = KeysAction {
+            value: chars,
+            frequency,
+        };
 
-        self.client
+        let resp = self
+            .client
             .post(&url)
             .json(&action)
             .send()
             .await
             .context("Failed to input text")?;
 
+        if !resp.status().is_success() {
+            let body = resp.text().await.unwrap_or_default();
+            anyhow::bail!("WDA rejected text input: {}", body);
+        }
+        Ok(())
+    }
+
+    /// Dismiss the keyboard, tapping the first of `key_names` it shows
+    pub async fn dismiss_keyboard(&mut self, key_names: &[&str]) -> Result<()> {
+        let session_id = self.ensure_session().await?;
+        let url = format!(
+            "{}/session/{}/wda/keyboard/dismiss",
+            self.base_url, session_id
+        );
+
+        let resp = self
+            .client
+            .post(&url)
+            .json(&serde_json::json!({ "keyNames": key_names }))
+            .send()
+            .await
+            .context("Failed to dismiss keyboard")?;
+
+        if !resp.status().is_success() {
+            let body = resp.text().await.unwrap_or_default();
+            anyhow::bail!("WDA could not dismiss the keyboard: {}", body);
+        }
         Ok(())
     }
 
@@ -296,7 +439,41 @@ impl WdaClient {
         Ok(())
     }
 
-    /// Get UI hierarchy (source)
+    /// Rotate the device (`/orientation`); fails when the app does not support it
+    pub async fn set_orientation(&mut self, orientation: &Orientation) -> Result<()> {
+        let session_id = self.ensure_session().await?;
+        let url = format!("{}/session/{}/orientation", self.base_url, session_id);
+
+        let resp = self
+            .client
+            .post(&url)
+            .json(&serde_json::json!({ "orientation": wda_orientation(orientation) }))
+            .send()
+            .await
+            .context("Failed to set orientation")?;
+
+        if !resp.status().is_success() {
+            let body = resp.text().await.unwrap_or_default();
+            anyhow::bail!("WDA rejected orientation {:?}: {}", orientation, body);
+        }
+        Ok(())
+    }
+
+    /// Whether a system or app alert is showing; a single cheap request,
+    /// WDA answers "no such alert" with an error status
+    pub async fn has_alert(&mut self) -> Result<bool> {
+        let session_id = self.ensure_session().await?;
+        let url = format!("{}/session/{}/alert/text", self.base_url, session_id);
+        let resp = self
+            .client
+            .get(&url)
+            .send()
+            .await
+            .context("Failed to query alert")?;
+        Ok(resp.status().is_success())
+    }
+
+    /// Get UI hierarchy (source) as XCUITest XML
     pub async fn get_source(&mut self) -> Result<String> {
         let session_id = self.ensure_session().await?;
         let url = format!("{}/session/{}/source", self.base_url, session_id);
@@ -308,8 +485,11 @@ impl WdaClient {
             .await
             .context("Failed to get source")?;
 
-        let body = resp.text().await?;
-        Ok(body)
+        let response: WdaResponse<String> = resp
+            .json()
+            .await
+            .context("Unexpected WDA source response")?;
+        Ok(response.value)
     }
 
     /// Take screenshot (returns base64 encoded PNG)
@@ -395,10 +575,34 @@ impl WdaClient {
     }
 }
 
+/// WDA orientation names; landscape means home button on the right (LANDSCAPE_LEFT)
+fn wda_orientation(orientation: &Orientation) -> &'static str {
+    match orientation {
+        Orientation::Portrait => "PORTRAIT",
+        Orientation::Landscape | Orientation::LandscapeLeft => "LANDSCAPE",
+        Orientation::LandscapeRight => "UIA_DEVICE_ORIENTATION_LANDSCAPERIGHT",
+        Orientation::UpsideDown => "UIA_DEVICE_ORIENTATION_PORTRAIT_UPSIDEDOWN",
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_orientation_names() {
+        let mode = Orientation::from_mode("Landscape-Right").unwrap();
+        assert_eq!(
+            wda_orientation(&mode),
+            "UIA_DEVICE_ORIENTATION_LANDSCAPERIGHT"
+        );
+        assert_eq!(
+            wda_orientation(&Orientation::from_mode("portrait").unwrap()),
+            "PORTRAIT"
+        );
+        assert!(Orientation::from_mode("sideways").is_err());
+    }
+
     #[test]
     fn test_client_creation() {
         let client = WdaClient::new(8100);
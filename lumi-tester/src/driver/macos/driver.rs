@@ -8,9 +8,26 @@ use std::process::{Command, Stdio};
 use std::sync::Mutex;
 use std::time::{Duration, Instant};
 
-use crate::driver::traits::{PlatformDriver, Selector, SwipeDirection};
+use crate::driver::traits::{
+    ensure_selector_supported, NthMatch, PlatformDriver, Selector, SwipeDirection,
+};
 use crate::parser::types::{DesktopClearMode, DesktopState};
 
+/// Selector kinds matched against the accessibility (AX) tree; `css`/`xpath` compare the
+/// automation identifier
+const SUPPORTED_SELECTORS: &[&str] = &[
+    "text",
+    "regex",
+    "id",
+    "description",
+    "placeholder",
+    "role",
+    "elementType",
+    "css",
+    "xpath",
+    "point",
+];
+
 /// Native macOS driver MVP backed by built-in command line tools.
 pub struct MacosDriver {
     device_name: Option<String>,
@@ -268,6 +285,7 @@ Grant permission to your terminal app or lumi-tester in System Settings > Privac
     }
 
     fn find_element(selector: &Selector) -> Result<Option<MacosAxElement>> {
+        ensure_selector_supported("macOS", selector, SUPPORTED_SELECTORS)?;
         let elements = Self::ax_elements()?;
         let mut matched = Vec::new();
 
@@ -278,7 +296,7 @@ Grant permission to your terminal app or lumi-tester in System Settings > Privac
         }
 
         let index = selector_index(selector).unwrap_or(0);
-        Ok(matched.into_iter().nth(index))
+        Ok(matched.into_iter().nth_match(index))
     }
 
     fn current_desktop_state(&self) -> Result<Option<DesktopStateRuntime>> {
@@ -783,8 +801,8 @@ fn applescript_string(value: &str) -> String {
     format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
 }
 
-fn selector_index(selector: &Selector) -> Option<usize> {
-    match selector {
+fn selector_index(selector: &Selector) -> Option<i32> {
+    match selector.unwrap_on_screen() {
         Selector::Text(_, index, _)
         | Selector::TextRegex(_, index)
         | Selector::Id(_, index)
@@ -805,7 +823,7 @@ fn element_matches_selector(element: &MacosAxElement, selector: &Selector) -> Re
         element.description.as_str(),
         element.value.as_str(),
     ];
-    match selector {
+    match selector.unwrap_on_screen() {
         Selector::Text(text, _, exact) => {
             if *exact {
                 Ok(text_fields.iter().any(|value| *value == text))
@@ -5,6 +5,7 @@ pub mod image_matcher;
 pub mod ios;
 pub mod macos;
 pub mod ocr;
+pub mod trace;
 pub mod traits;
 pub mod web;
 pub mod windows;
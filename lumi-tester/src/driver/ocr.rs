@@ -5,6 +5,7 @@
 //! - Windows: Windows.Media.Ocr API (via PowerShell)
 //! - Linux/Fallback: Tesseract CLI
 
+use crate::driver::traits::NthMatch;
 use anyhow::{Context, Result};
 use log;
 use regex::Regex;
@@ -63,6 +64,38 @@ impl OcrEngine {
         }
     }
 
+    /// Check the platform backend can run (helper binary / language data);
+    /// returns a short description of what will be used
+    pub fn check_backend() -> Result<String> {
+        match Self::detect_backend() {
+            OcrBackend::MacOSVision => {
+                Self::find_ocr_helper().map(|p| format!("Vision helper {}", p.display()))
+            }
+            OcrBackend::WindowsOcr => Ok("Windows.Media.Ocr".to_string()),
+            OcrBackend::Tesseract => {
+                let output = Command::new("tesseract")
+                    .arg("--list-langs")
+                    .output()
+                    .context("Tesseract not found")?;
+                // Language list goes to stdout on 4.x and stderr on older builds
+                let langs = format!(
+                    "{}{}",
+                    String::from_utf8_lossy(&output.stdout),
+                    String::from_utf8_lossy(&output.stderr)
+                );
+                let missing: Vec<&str> = ["eng", "vie"]
+                    .into_iter()
+                    .filter(|lang| !langs.lines().any(|l| l.trim() == *lang))
+                    .collect();
+                if missing.is_empty() {
+                    Ok("Tesseract (eng+vie)".to_string())
+                } else {
+                    anyhow::bail!("Tesseract language data missing: {}", missing.join(", "))
+                }
+            }
+        }
+    }
+
     pub fn is_regex_pattern(text: &str) -> bool {
         text.contains('*')
             || text.contains('+')
@@ -142,10 +175,10 @@ impl OcrEngine {
         image_data: &[u8],
         search_text: &str,
         is_regex: bool,
-        index: usize,
+        index: i32,
     ) -> Result<Option<OcrMatch>> {
         let matches = self.find_text(image_data, search_text, is_regex)?;
-        Ok(matches.into_iter().nth(index))
+        Ok(matches.into_iter().nth_match(index))
     }
 
     // ==================== macOS Vision Framework ====================
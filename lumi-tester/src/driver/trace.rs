@@ -0,0 +1,112 @@
+//! Device call tracing (`--trace`)
+//!
+//! Each adb/idb/CDP call and its duration is reported as a `Log` event on the
+//! emitter of the executor whose command issued it, so it shows in the console
+//! and in Studio. Calls made outside a command (driver setup) print directly.
+//! Driver warnings go through [`log`] the same way, with or without `--trace`.
+
+use crate::runner::events::{EventEmitter, TestEvent};
+use colored::Colorize;
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Instant;
+
+/// Set once by `--trace` before any driver starts
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Long shell scripts are cut to keep one line per call
+const MAX_ARGS_LEN: usize = 240;
+
+tokio::task_local! {
+    static SINK: TraceSink;
+}
+
+#[derive(Clone)]
+struct TraceSink {
+    emitter: EventEmitter,
+    depth: usize,
+}
+
+/// Turn call tracing on for the rest of the process
+pub fn enable() {
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Run `fut` with its device calls and driver logs reported on `emitter`
+pub async fn scope<F: Future>(emitter: &EventEmitter, depth: usize, fut: F) -> F::Output {
+    let sink = TraceSink {
+        emitter: emitter.clone(),
+        depth,
+    };
+    SINK.scope(sink, fut).await
+}
+
+/// A device call being timed; free when tracing is off
+pub struct Call(Option<(String, Instant)>);
+
+impl Call {
+    pub fn start(tool: &str, args: &[&str]) -> Self {
+        if !is_enabled() {
+            return Self(None);
+        }
+        Self(Some((
+            format!("{} {}", tool, args.join(" ")),
+            Instant::now(),
+        )))
+    }
+
+    pub fn finish(self, ok: bool) {
+        let Some((call, started)) = self.0 else {
+            return;
+        };
+        log(format_call(&call, started.elapsed().as_millis(), ok));
+    }
+}
+
+/// Report a driver message as a `Log` event of the running command
+pub fn log(message: String) {
+    let emitted = SINK.try_with(|sink| {
+        sink.emitter.emit(TestEvent::Log {
+            message: message.clone(),
+            depth: sink.depth,
+        })
+    });
+    if emitted.is_err() {
+        println!("{}", message);
+    }
+}
+
+fn format_call(call: &str, elapsed_ms: u128, ok: bool) -> String {
+    let call = match call.char_indices().nth(MAX_ARGS_LEN) {
+        Some((cut, _)) => format!("{}…", &call[..cut]),
+        None => call.to_string(),
+    };
+    let status = if ok {
+        format!("{}ms", elapsed_ms)
+    } else {
+        format!("{}ms, failed", elapsed_ms).red().to_string()
+    };
+    format!("{} {} ({})", "⇢".dimmed(), call.dimmed(), status)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_call_truncates_long_scripts() {
+        colored::control::set_override(false);
+        assert_eq!(
+            format_call("adb -s emulator-5554 shell input tap 10 20", 42, true),
+            "⇢ adb -s emulator-5554 shell input tap 10 20 (42ms)"
+        );
+        let long = format!("adb shell {}", "x".repeat(500));
+        let line = format_call(&long, 7, false);
+        assert!(line.ends_with("… (7ms, failed)"), "{}", line);
+        assert!(line.chars().count() < 270);
+    }
+}
@@ -1,22 +1,36 @@
-use crate::parser::types::{DesktopState, Orientation, SpeedMode};
+use crate::parser::types::{
+    is_regex_string, DesktopState, DriveProfile, MaxDistance, NetworkConditions, Orientation,
+    SelectorDsl, SpeedMode, TapParams,
+};
 use anyhow::Result;
 use async_trait::async_trait;
 use std::path::Path;
 
+/// Minimum similarity for `fuzzy: true` when `fuzzyThreshold` is not set
+pub const DEFAULT_FUZZY_THRESHOLD: f64 = 0.8;
+
 /// Element selector for UI elements
-#[derive(Debug, Clone)]
+///
+/// Element indices are 0-based; negative indices count from the last match
+/// (`-1` is the last one), see [`resolve_index`].
+#[derive(Debug, Clone, PartialEq)]
 pub enum Selector {
     /// Select by visible text with index and exact match flag
     /// (text, index, exact) - if exact=false, use case-insensitive fallback
-    Text(String, usize, bool),
+    Text(String, i32, bool),
+    /// Select the visible text most similar to the given one (`fuzzy: true`)
+    /// (text, index, threshold) - similarity from 0.0 to 1.0, see [`text_similarity`]
+    ///
+    /// [`text_similarity`]: crate::driver::common::text_similarity
+    FuzzyText(String, i32, f64),
     /// Select by Regex pattern on text with index
-    TextRegex(String, usize),
+    TextRegex(String, i32),
     /// Select by resource ID with index
-    Id(String, usize),
+    Id(String, i32),
     /// Select by Regex pattern on resource ID with index
-    IdRegex(String, usize),
+    IdRegex(String, i32),
     /// Select by class/element type with index
-    Type(String, usize),
+    Type(String, i32),
     /// Select by point coordinates
     Point { x: i32, y: i32 },
     /// Select by accessibility ID
@@ -24,7 +38,7 @@ pub enum Selector {
     AccessibilityId(String),
     /// Select by XPath
     XPath(String),
-    /// Select by CSS selector (Web only)
+    /// Select by CSS selector (Web; simple selectors on Android)
     Css(String),
     /// Select by Image template with optional region constraint
     Image {
@@ -33,13 +47,13 @@ pub enum Selector {
         region: Option<String>,
     },
     /// Select by placeholder text with index
-    Placeholder(String, usize),
+    Placeholder(String, i32),
     /// Select by role with index
-    Role(String, usize),
+    Role(String, i32),
     /// Select by accessibility description/content-desc with index
-    Description(String, usize),
+    Description(String, i32),
     /// Select by Regex pattern on description with index
-    DescriptionRegex(String, usize),
+    DescriptionRegex(String, i32),
     /// Select by scrollable container index and item index
     ScrollableItem {
         scrollable_index: usize,
@@ -48,13 +62,15 @@ pub enum Selector {
     /// Select scrollable container by index
     Scrollable(usize),
     /// Select any clickable element (used as default target for relative-only selectors)
-    AnyClickable(usize),
+    AnyClickable(i32),
     /// Select relative to another element
     Relative {
         target: Box<Selector>,
         anchor: Box<Selector>,
         direction: RelativeDirection,
-        max_dist: Option<u32>,
+        max_dist: Option<MaxDistance>,
+        /// Angular tolerance in degrees around the search direction
+        angle: Option<f32>,
     },
     /// Select parent containing a child
     HasChild {
@@ -63,11 +79,172 @@ pub enum Selector {
     },
     /// Select by OCR text recognition from screenshot
     /// (text_or_regex, index, is_regex, region)
-    OCR(String, usize, bool, Option<String>),
+    OCR(String, i32, bool, Option<String>),
+    /// Only match elements that are actually inside the screen/viewport
+    OnScreen(Box<Selector>),
+}
+
+impl Selector {
+    /// Wrap in [`Selector::OnScreen`] when `only_visible` is set.
+    /// Point, image and OCR selectors already work on what is on screen.
+    pub fn on_screen_if(self, only_visible: bool) -> Selector {
+        let screen_based = matches!(
+            self,
+            Selector::OnScreen(_)
+                | Selector::Point { .. }
+                | Selector::Image { .. }
+                | Selector::OCR(..)
+        );
+        if only_visible && !screen_based {
+            Selector::OnScreen(Box::new(self))
+        } else {
+            self
+        }
+    }
+
+    /// Turn a text selector into [`Selector::FuzzyText`]. Fails for other kinds
+    /// (fuzzy only compares text) and when `threshold` is outside 0.0-1.0.
+    pub fn into_fuzzy(self, threshold: Option<f64>) -> Result<Selector> {
+        if !matches!(self.unwrap_on_screen(), Selector::Text(..)) {
+            anyhow::bail!(
+                "fuzzy needs a plain text selector, got {}",
+                self.unwrap_on_screen().kind().unwrap_or("another selector")
+            );
+        }
+        let threshold = threshold.unwrap_or(DEFAULT_FUZZY_THRESHOLD);
+        if !(0.0..=1.0).contains(&threshold) {
+            anyhow::bail!(
+                "fuzzyThreshold must be between 0.0 and 1.0, got {}",
+                threshold
+            );
+        }
+        Ok(self.with_fuzzy_threshold(threshold))
+    }
+
+    fn with_fuzzy_threshold(self, threshold: f64) -> Selector {
+        match self {
+            Selector::Text(text, index, _) => Selector::FuzzyText(text, index, threshold),
+            Selector::OnScreen(inner) => {
+                Selector::OnScreen(Box::new(inner.with_fuzzy_threshold(threshold)))
+            }
+            other => other,
+        }
+    }
+
+    /// The selector without an [`Selector::OnScreen`] wrapper
+    pub fn unwrap_on_screen(&self) -> &Selector {
+        match self {
+            Selector::OnScreen(inner) => inner.unwrap_on_screen(),
+            other => other,
+        }
+    }
+
+    /// YAML key of the selector kind, as listed in unsupported-selector errors.
+    /// `None` for wrappers and internal variants that every driver resolves.
+    pub fn kind(&self) -> Option<&'static str> {
+        Some(match self {
+            Selector::Text(..) => "text",
+            Selector::FuzzyText(..) => "fuzzy",
+            Selector::TextRegex(..) => "regex",
+            Selector::Id(..) | Selector::IdRegex(..) => "id",
+            Selector::Type(..) => "elementType",
+            Selector::Point { .. } => "point",
+            Selector::XPath(_) => "xpath",
+            Selector::Css(_) => "css",
+            Selector::Image { .. } => "image",
+            Selector::Placeholder(..) => "placeholder",
+            Selector::Role(..) => "role",
+            Selector::Description(..) | Selector::DescriptionRegex(..) => "description",
+            Selector::ScrollableItem { .. } | Selector::Scrollable(_) => "scrollable",
+            Selector::Relative { .. } => "relative",
+            Selector::HasChild { .. } => "containsChild",
+            Selector::OCR(..) => "ocr",
+            Selector::AccessibilityId(_) | Selector::AnyClickable(_) | Selector::OnScreen(_) => {
+                return None
+            }
+        })
+    }
+
+    /// Parse a compact selector string such as `text=Login`, `id=submit_btn[2]`,
+    /// `css=.item` or `role=button` (see [`SelectorDsl`]). The string desugars
+    /// into the structured fields like any string selector; `None` when it has
+    /// no known `key=` prefix and isn't a quoted literal.
+    pub fn from_dsl(input: &str) -> Option<Selector> {
+        let p: TapParams = SelectorDsl::desugar(input)?;
+        let idx = p.index.unwrap_or(0);
+        // Same precedence as the executor's structured selectors
+        Some(if let Some(r) = p.regex {
+            Selector::TextRegex(r, idx)
+        } else if let Some(t) = p.text {
+            Selector::Text(t, idx, p.exact)
+        } else if let Some(id) = p.id {
+            if is_regex_string(&id) {
+                Selector::IdRegex(id, idx)
+            } else {
+                Selector::Id(id, idx)
+            }
+        } else if let Some(d) = p.description {
+            if is_regex_string(&d) {
+                Selector::DescriptionRegex(d, idx)
+            } else {
+                Selector::Description(d, idx)
+            }
+        } else if let Some(ph) = p.placeholder {
+            Selector::Placeholder(ph, idx)
+        } else if let Some(role) = p.role {
+            Selector::Role(role, idx)
+        } else if let Some(e) = p.element_type {
+            Selector::Type(e, idx)
+        } else if let Some(c) = p.css {
+            Selector::Css(c)
+        } else if let Some(path) = p.image {
+            Selector::Image { path, region: None }
+        } else if let Some(x) = p.xpath {
+            Selector::XPath(x)
+        } else {
+            let point = p.point?;
+            let (x, y) = point.split_once(',')?;
+            Selector::Point {
+                x: x.trim().parse().ok()?,
+                y: y.trim().parse().ok()?,
+            }
+        })
+    }
+}
+
+/// Fail when `selector`, or a selector nested in it, is a kind the platform
+/// can't resolve, instead of letting the lookup report "element not found".
+pub fn ensure_selector_supported(
+    platform: &str,
+    selector: &Selector,
+    supported: &[&str],
+) -> Result<()> {
+    if let Some(kind) = selector.kind() {
+        if !supported.contains(&kind) {
+            anyhow::bail!(
+                "`{}` selectors are not supported on {}. Supported selectors: {}",
+                kind,
+                platform,
+                supported.join(", ")
+            );
+        }
+    }
+    match selector {
+        Selector::OnScreen(inner) => ensure_selector_supported(platform, inner, supported),
+        Selector::Relative { target, anchor, .. } => {
+            ensure_selector_supported(platform, target, supported)?;
+            ensure_selector_supported(platform, anchor, supported)
+        }
+        Selector::HasChild { parent, child } => {
+            ensure_selector_supported(platform, parent, supported)?;
+            ensure_selector_supported(platform, child, supported)
+        }
+        _ => Ok(()),
+    }
 }
 
 /// Direction for relative selection
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum RelativeDirection {
     LeftOf,
     RightOf,
@@ -76,6 +253,61 @@ pub enum RelativeDirection {
     Near,
 }
 
+/// Resolve a selector index against the number of matches.
+///
+/// Negative indices count from the end (`-1` is the last match).
+/// Returns `None` when the index is out of range.
+pub fn resolve_index(index: i32, len: usize) -> Option<usize> {
+    let resolved = if index < 0 {
+        len.checked_sub(index.unsigned_abs() as usize)?
+    } else {
+        index as usize
+    };
+    (resolved < len).then_some(resolved)
+}
+
+/// `nth` that understands negative selector indices
+pub trait NthMatch: Iterator + Sized {
+    /// Pick the `index`-th item; `-1` is the last one
+    fn nth_match(mut self, index: i32) -> Option<Self::Item> {
+        if index >= 0 {
+            return self.nth(index as usize);
+        }
+        let mut items: Vec<Self::Item> = self.collect();
+        let i = resolve_index(index, items.len())?;
+        Some(items.swap_remove(i))
+    }
+}
+
+impl<I: Iterator> NthMatch for I {}
+
+impl RelativeDirection {
+    /// Screen length along the search axis, used to resolve percentage distances
+    pub fn axis_length(&self, (width, height): (u32, u32)) -> u32 {
+        match self {
+            RelativeDirection::LeftOf | RelativeDirection::RightOf => width,
+            RelativeDirection::Above | RelativeDirection::Below => height,
+            RelativeDirection::Near => ((width as f64).hypot(height as f64)) as u32,
+        }
+    }
+
+    /// Whether the offset (dx, dy) from the anchor center lies within
+    /// `tolerance_deg` of this direction. `Near` accepts any angle.
+    pub fn within_angle(&self, dx: f64, dy: f64, tolerance_deg: f32) -> bool {
+        let (along, across) = match self {
+            RelativeDirection::RightOf => (dx, dy),
+            RelativeDirection::LeftOf => (-dx, dy),
+            RelativeDirection::Below => (dy, dx),
+            RelativeDirection::Above => (-dy, dx),
+            RelativeDirection::Near => return true,
+        };
+        if along <= 0.0 {
+            return across == 0.0 && along == 0.0;
+        }
+        across.abs().atan2(along).to_degrees() <= tolerance_deg as f64
+    }
+}
+
 /// Swipe direction
 #[derive(Debug, Clone, Copy)]
 pub enum SwipeDirection {
@@ -85,6 +317,138 @@ pub enum SwipeDirection {
     Right,
 }
 
+/// Installed app version info
+#[derive(Debug, Clone, PartialEq)]
+pub struct AppVersion {
+    /// User-facing version (Android versionName / iOS CFBundleShortVersionString)
+    pub version: String,
+    /// Build number (Android versionCode / iOS CFBundleVersion)
+    pub build: Option<String>,
+}
+
+/// A posted notification
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct NotificationInfo {
+    /// Package / bundle id that posted it
+    pub app: String,
+    pub title: String,
+    pub text: String,
+}
+
+/// How close `waitForLocation` must get to its target
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LocationTolerance {
+    /// Great-circle distance in meters
+    Meters(f64),
+    /// Per-axis difference in degrees (legacy web `tolerance`)
+    Degrees(f64),
+}
+
+impl LocationTolerance {
+    /// Whether `current` is within tolerance of `target`, both (lat, lon)
+    pub fn contains(&self, current: (f64, f64), target: (f64, f64)) -> bool {
+        match *self {
+            Self::Meters(m) => {
+                crate::parser::gps::haversine_distance(current.0, current.1, target.0, target.1)
+                    <= m
+            }
+            Self::Degrees(d) => {
+                (current.0 - target.0).abs() <= d && (current.1 - target.1).abs() <= d
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for LocationTolerance {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Meters(m) => write!(f, "{:.1}m", m),
+            Self::Degrees(d) => write!(f, "{}°", d),
+        }
+    }
+}
+
+/// Screen recording quality; `None` keeps the platform default
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RecordingOptions {
+    /// Bits per second
+    pub bitrate: Option<u32>,
+    /// Output size as (width, height)
+    pub size: Option<(u32, u32)>,
+    /// Stop recording after this many seconds
+    pub time_limit_secs: Option<u32>,
+}
+
+impl RecordingOptions {
+    const BITRATE_RANGE: std::ops::RangeInclusive<u32> = 100_000..=100_000_000;
+    /// Android screenrecord refuses anything above 180 seconds
+    const TIME_LIMIT_RANGE: std::ops::RangeInclusive<u32> = 1..=180;
+
+    /// Build options from user input; invalid or out-of-range values are errors
+    pub fn parse(
+        bitrate: Option<&str>,
+        size: Option<&str>,
+        time_limit_secs: Option<u32>,
+    ) -> Result<Self> {
+        let bitrate = bitrate
+            .map(|b| match parse_bitrate(b) {
+                Some(bps) if Self::BITRATE_RANGE.contains(&bps) => Ok(bps),
+                _ => Err(anyhow::anyhow!(
+                    "Invalid recording bitrate '{}': expected 100K to 100M bits per second",
+                    b
+                )),
+            })
+            .transpose()?;
+        let size = size
+            .map(|s| {
+                parse_size(s).ok_or_else(|| {
+                    anyhow::anyhow!("Invalid recording size '{}': expected WIDTHxHEIGHT", s)
+                })
+            })
+            .transpose()?;
+        if let Some(t) = time_limit_secs.filter(|t| !Self::TIME_LIMIT_RANGE.contains(t)) {
+            anyhow::bail!(
+                "Invalid recording time limit {}s: expected 1 to 180 seconds",
+                t
+            );
+        }
+
+        Ok(Self {
+            bitrate,
+            size,
+            time_limit_secs,
+        })
+    }
+
+    /// Fill unset fields from `fallback`
+    pub fn or(self, fallback: &RecordingOptions) -> Self {
+        Self {
+            bitrate: self.bitrate.or(fallback.bitrate),
+            size: self.size.or(fallback.size),
+            time_limit_secs: self.time_limit_secs.or(fallback.time_limit_secs),
+        }
+    }
+}
+
+/// Parse a bitrate like `4000000`, `"4M"`, `"2.5m"` or `"800K"` into bits per second
+pub fn parse_bitrate(value: &str) -> Option<u32> {
+    let value = value.trim();
+    let (number, multiplier) = match value.char_indices().last()? {
+        (i, 'k' | 'K') => (&value[..i], 1_000.0),
+        (i, 'm' | 'M') => (&value[..i], 1_000_000.0),
+        _ => (value, 1.0),
+    };
+    let bps = number.trim().parse::<f64>().ok()? * multiplier;
+    (bps.is_finite() && bps >= 1.0 && bps <= u32::MAX as f64).then_some(bps as u32)
+}
+
+/// Parse a `WIDTHxHEIGHT` size such as `1280x720`
+pub fn parse_size(value: &str) -> Option<(u32, u32)> {
+    let (w, h) = value.trim().split_once(['x', 'X'])?;
+    let (w, h) = (w.trim().parse().ok()?, h.trim().parse().ok()?);
+    (w > 0 && h > 0).then_some((w, h))
+}
+
 /// Platform-agnostic driver interface
 ///
 /// This trait defines all the operations that a platform driver must implement
@@ -99,6 +463,22 @@ pub trait PlatformDriver: Send + Sync {
     /// Get the device serial or ID
     fn device_serial(&self) -> Option<String>;
 
+    /// Device model read at init (e.g. "Pixel 7"), used to pick screenshot baselines
+    fn device_model(&self) -> Option<String> {
+        None
+    }
+
+    /// OS version read at init (e.g. "14" on Android, "17.2" on iOS)
+    fn os_version(&self) -> Option<String> {
+        None
+    }
+
+    /// Release device resources at the end of a run
+    async fn teardown(&self) -> Result<()> {
+        // Default: nothing to release
+        Ok(())
+    }
+
     /// Configure desktop state clearing for the current flow.
     fn set_desktop_state(&self, _state: Option<DesktopState>, _base_dir: &Path) -> Result<()> {
         Ok(())
@@ -139,6 +519,11 @@ pub trait PlatformDriver: Send + Sync {
     /// * `char_count` - Number of characters to erase. If None, erase all.
     async fn erase_text(&self, char_count: Option<u32>) -> Result<()>;
 
+    /// Whether some input field has focus; drivers that can't tell report true
+    async fn has_focused_field(&self) -> Result<bool> {
+        Ok(true)
+    }
+
     /// Hide the on-screen keyboard
     async fn hide_keyboard(&self) -> Result<()>;
 
@@ -187,6 +572,12 @@ pub trait PlatformDriver: Send + Sync {
     /// Wait for an element to disappear
     async fn wait_for_absence(&self, selector: &Selector, timeout_ms: u64) -> Result<bool>;
 
+    /// Elements on screen that almost match a selector that matched nothing, e.g.
+    /// `text "Log In"` for `text: Login`; appended to timeout errors as a hint
+    async fn near_misses(&self, _selector: &Selector) -> Vec<String> {
+        Vec::new()
+    }
+
     /// Get the text content of an element
     ///
     /// # Arguments
@@ -218,9 +609,31 @@ pub trait PlatformDriver: Send + Sync {
     /// * `path` - Where to save the recording
     async fn start_recording(&self, path: &str) -> Result<()>;
 
+    /// Start recording with quality options.
+    ///
+    /// Platforms without such controls fall back to a plain recording.
+    async fn start_recording_with_options(
+        &self,
+        path: &str,
+        _options: &RecordingOptions,
+    ) -> Result<()> {
+        self.start_recording(path).await
+    }
+
     /// Stop recording the screen
     async fn stop_recording(&self) -> Result<()>;
 
+    /// Start a trace of the current flow, written to `path` by [`Self::stop_trace`].
+    /// Returns `false` when the driver was not asked to trace.
+    async fn start_trace(&self, _path: &Path) -> Result<bool> {
+        Ok(false)
+    }
+
+    /// Finish the trace started by [`Self::start_trace`]
+    async fn stop_trace(&self) -> Result<()> {
+        Ok(())
+    }
+
     /// Press the back button
     async fn back(&self) -> Result<()>;
 
@@ -276,8 +689,7 @@ pub trait PlatformDriver: Send + Sync {
     /// * `name` - Optional name for this mock instance
     /// * `points` - List of GPS coordinates to simulate
     /// * `speed_kmh` - Optional speed override in km/h (ignores timestamps)
-    /// * `speed_mode` - Speed simulation mode (linear or noise)
-    /// * `speed_noise` - Speed noise range in km/h
+    /// * `speed_profile` - Speed simulation mode and its curve parameters
     /// * `interval_ms` - Update interval in milliseconds
     /// * `loop_route` - Whether to loop the route
     async fn start_mock_location(
@@ -285,8 +697,7 @@ pub trait PlatformDriver: Send + Sync {
         _name: Option<String>,
         _points: Vec<crate::parser::gps::GpsPoint>,
         _speed_kmh: Option<f64>,
-        _speed_mode: SpeedMode,
-        _speed_noise: Option<f64>,
+        _speed_profile: DriveProfile,
         _interval_ms: u64,
         _loop_route: bool,
     ) -> Result<()> {
@@ -382,6 +793,20 @@ pub trait PlatformDriver: Send + Sync {
         ))
     }
 
+    /// Get the URL of the current page (web only)
+    async fn get_current_url(&self) -> Result<String> {
+        Err(anyhow::anyhow!(
+            "get_current_url not implemented for this platform"
+        ))
+    }
+
+    /// Evaluate JavaScript in the current page and return the result as text (web only)
+    async fn execute_js(&self, _script: &str) -> Result<String> {
+        Err(anyhow::anyhow!(
+            "executeScript is only supported on the web platform"
+        ))
+    }
+
     /// Clear iOS Simulator Keychain (iOS only)
     ///
     /// This clears all keychain items for the simulator.
@@ -398,6 +823,11 @@ pub trait PlatformDriver: Send + Sync {
         Err(anyhow::anyhow!("set_network_connection not implemented"))
     }
 
+    /// Turn Bluetooth on or off
+    async fn set_bluetooth(&self, _enabled: bool) -> Result<()> {
+        Err(anyhow::anyhow!("set_bluetooth not implemented"))
+    }
+
     /// Toggle airplane mode
     async fn toggle_airplane_mode(&self) -> Result<()> {
         Err(anyhow::anyhow!("toggle_airplane_mode not implemented"))
@@ -433,6 +863,69 @@ pub trait PlatformDriver: Send + Sync {
         Err(anyhow::anyhow!("install_app not implemented"))
     }
 
+    /// Install an application with install flags (grant permissions, downgrade, reinstall)
+    ///
+    /// Platforms without such flags fall back to a plain install.
+    async fn install_app_with_options(
+        &self,
+        params: &crate::parser::types::InstallAppParams,
+    ) -> Result<()> {
+        self.install_app(&params.path).await
+    }
+
+    /// Copy an app's private data into `dir`
+    async fn snapshot_app_data(&self, _app_id: &str, _dir: &Path) -> Result<()> {
+        Err(anyhow::anyhow!("snapshot_app_data not implemented"))
+    }
+
+    /// Replace an app's private data with a snapshot taken into `dir`
+    async fn restore_app_data(&self, _app_id: &str, _dir: &Path) -> Result<()> {
+        Err(anyhow::anyhow!("restore_app_data not implemented"))
+    }
+
+    /// List currently posted notifications
+    async fn get_notifications(&self) -> Result<Vec<NotificationInfo>> {
+        Err(anyhow::anyhow!("get_notifications not implemented"))
+    }
+
+    /// Watch for a toast containing `text`; returns false if none appears in time
+    async fn wait_for_toast(&self, _text: &str, _timeout_ms: u64) -> Result<bool> {
+        Err(anyhow::anyhow!("wait_for_toast not implemented"))
+    }
+
+    /// Inject an incoming SMS (emulators only)
+    async fn send_sms(&self, _from: &str, _body: &str) -> Result<()> {
+        Err(anyhow::anyhow!("send_sms not implemented"))
+    }
+
+    /// Read the body of the most recent inbox SMS
+    async fn read_latest_sms(&self) -> Result<String> {
+        Err(anyhow::anyhow!("read_latest_sms not implemented"))
+    }
+
+    /// Override the device clock; `None` restores automatic (network) time
+    async fn set_date_time(&self, _value: Option<chrono::NaiveDateTime>) -> Result<()> {
+        Err(anyhow::anyhow!("set_date_time not implemented"))
+    }
+
+    /// Tap a button on an OS permission/system dialog if one is showing.
+    /// `button` is `allow`, `deny`, or a literal button label.
+    /// Returns false when no system dialog is present.
+    async fn handle_system_dialog(&self, _button: &str) -> Result<bool> {
+        Err(anyhow::anyhow!("handle_system_dialog not implemented"))
+    }
+
+    /// Whether `handle_system_dialog` can rule out a dialog without dumping the UI.
+    /// When false, `handleSystemDialogs` only checks after an element is not found.
+    fn has_cheap_dialog_probe(&self) -> bool {
+        true
+    }
+
+    /// Get the installed version of an app
+    async fn get_app_version(&self, _app_id: &str) -> Result<AppVersion> {
+        Err(anyhow::anyhow!("get_app_version not implemented"))
+    }
+
     /// Uninstall an application
     async fn uninstall_app(&self, _app_id: &str) -> Result<()> {
         Err(anyhow::anyhow!("uninstall_app not implemented"))
@@ -443,6 +936,11 @@ pub trait PlatformDriver: Send + Sync {
         Err(anyhow::anyhow!("background_app not implemented"))
     }
 
+    /// Check whether the given app is currently in the foreground
+    async fn is_app_in_foreground(&self, _app_id: &str) -> Result<bool> {
+        Err(anyhow::anyhow!("is_app_in_foreground not implemented"))
+    }
+
     /// Set device orientation
     async fn set_orientation(&self, _mode: Orientation) -> Result<()> {
         Err(anyhow::anyhow!("set_orientation not implemented"))
@@ -454,7 +952,7 @@ pub trait PlatformDriver: Send + Sync {
         _name: Option<String>,
         _lat: f64,
         _lon: f64,
-        _tolerance: f64,
+        _tolerance: LocationTolerance,
         _timeout: u64,
     ) -> Result<()> {
         Err(anyhow::anyhow!("wait_for_location not implemented"))
@@ -516,8 +1014,8 @@ pub trait PlatformDriver: Send + Sync {
     /// Set network conditions
     ///
     /// # Arguments
-    /// * `profile` - Network profile name (e.g., "Slow 3G", "Fast 3G", "Offline", or custom JSON)
-    async fn set_network_conditions(&self, _profile: &str) -> Result<()> {
+    /// * `conditions` - Resolved profile (`offline`, `3g`, ...) or custom latency/throughput/loss
+    async fn set_network_conditions(&self, _conditions: &NetworkConditions) -> Result<()> {
         Err(anyhow::anyhow!("set_network_conditions not implemented"))
     }
 
@@ -531,6 +1029,14 @@ pub trait PlatformDriver: Send + Sync {
         Ok(None)
     }
 
+    /// Select the Android Auto display, creating an overlay display when none exists.
+    /// Returns the selected display ID.
+    async fn select_auto_display(&self) -> Result<u32> {
+        Err(anyhow::anyhow!(
+            "selectDisplay: auto is only supported on Android"
+        ))
+    }
+
     /// Set device locale for i18n testing
     ///
     /// # Arguments
@@ -584,4 +1090,125 @@ pub trait PlatformDriver: Send + Sync {
     async fn verify_audio_ducking(&self, _min_events: usize, _drop_threshold: f64) -> Result<()> {
         Err(anyhow::anyhow!("verify_audio_ducking not implemented"))
     }
+
+    /// Whether `app_id` (any app when `None`) is currently playing audio
+    async fn is_audio_playing(&self, _app_id: Option<&str>) -> Result<bool> {
+        Err(anyhow::anyhow!("is_audio_playing not implemented"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recording_options_parse() {
+        assert_eq!(parse_bitrate("4000000"), Some(4_000_000));
+        assert_eq!(parse_bitrate("4M"), Some(4_000_000));
+        assert_eq!(parse_bitrate("2.5m"), Some(2_500_000));
+        assert_eq!(parse_bitrate("800K"), Some(800_000));
+        assert_eq!(parse_bitrate("fast"), None);
+        assert_eq!(parse_size("1280x720"), Some((1280, 720)));
+        assert_eq!(parse_size("0x720"), None);
+
+        assert!(RecordingOptions::parse(Some("1K"), None, None).is_err());
+        assert!(RecordingOptions::parse(None, Some("720"), None).is_err());
+        assert!(RecordingOptions::parse(None, None, Some(600)).is_err());
+        let options = RecordingOptions::parse(None, Some("720X1280"), None).unwrap();
+        assert_eq!(
+            options,
+            RecordingOptions {
+                bitrate: None,
+                size: Some((720, 1280)),
+                time_limit_secs: None,
+            }
+        );
+
+        let cli = RecordingOptions::parse(Some("8M"), None, Some(30)).unwrap();
+        let merged = options.or(&cli);
+        assert_eq!(merged.bitrate, Some(8_000_000));
+        assert_eq!(merged.size, Some((720, 1280)));
+        assert_eq!(merged.time_limit_secs, Some(30));
+    }
+
+    #[test]
+    fn test_into_fuzzy_validates_threshold() {
+        let text = Selector::Text("Log in".into(), 0, false).on_screen_if(true);
+        assert_eq!(
+            text.clone().into_fuzzy(Some(0.6)).unwrap(),
+            Selector::OnScreen(Box::new(Selector::FuzzyText("Log in".into(), 0, 0.6)))
+        );
+        assert!(text.clone().into_fuzzy(Some(1.5)).is_err());
+        assert!(text.into_fuzzy(Some(-0.1)).is_err());
+
+        let id = Selector::Id("login".into(), 0);
+        let message = id.into_fuzzy(None).unwrap_err().to_string();
+        assert!(message.contains("got id"), "{}", message);
+    }
+
+    #[test]
+    fn test_unsupported_selector_names_supported_kinds() {
+        let supported = ["text", "id", "relative"];
+        let nested = Selector::OnScreen(Box::new(Selector::Relative {
+            target: Box::new(Selector::Text("Save".to_string(), 0, false)),
+            anchor: Box::new(Selector::XPath("//form".to_string())),
+            direction: RelativeDirection::Below,
+            max_dist: None,
+            angle: None,
+        }));
+        let error = ensure_selector_supported("android", &nested, &supported)
+            .unwrap_err()
+            .to_string();
+        assert_eq!(
+            error,
+            "`xpath` selectors are not supported on android. Supported selectors: text, id, relative"
+        );
+        assert!(
+            ensure_selector_supported("android", &Selector::AnyClickable(0), &supported).is_ok()
+        );
+    }
+
+    #[test]
+    fn test_selector_from_dsl() {
+        assert_eq!(
+            Selector::from_dsl("text=Login"),
+            Some(Selector::Text("Login".to_string(), 0, false))
+        );
+        assert_eq!(
+            Selector::from_dsl("id=submit_btn[2]"),
+            Some(Selector::Id("submit_btn".to_string(), 2))
+        );
+        assert_eq!(
+            Selector::from_dsl("css=input[name=email]"),
+            Some(Selector::Css("input[name=email]".to_string()))
+        );
+        assert_eq!(
+            Selector::from_dsl("role = \"button\""),
+            Some(Selector::Role("button".to_string(), 0))
+        );
+        assert_eq!(
+            Selector::from_dsl("point=120, 340"),
+            Some(Selector::Point { x: 120, y: 340 })
+        );
+        assert_eq!(
+            Selector::from_dsl("\"type=password\""),
+            Some(Selector::Text("type=password".to_string(), 0, false))
+        );
+        assert_eq!(Selector::from_dsl("Login"), None);
+        assert_eq!(Selector::from_dsl("Id=42"), None);
+        assert_eq!(Selector::from_dsl("a=b"), None);
+        assert_eq!(Selector::from_dsl("text="), None);
+    }
+
+    #[test]
+    fn test_location_tolerance_meters_follows_latitude() {
+        // 0.001° of longitude is ~111 m at the equator but ~56 m at 60°N
+        let within_60m = LocationTolerance::Meters(60.0);
+        assert!(within_60m.contains((60.0, 10.001), (60.0, 10.0)));
+        assert!(!within_60m.contains((0.0, 10.001), (0.0, 10.0)));
+
+        let degrees = LocationTolerance::Degrees(0.001);
+        assert!(degrees.contains((0.0, 10.001), (0.0, 10.0)));
+        assert!(!degrees.contains((0.0, 10.002), (0.0, 10.0)));
+    }
 }
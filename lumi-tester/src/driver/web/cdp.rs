@@ -0,0 +1,131 @@
+//! Minimal Chrome DevTools Protocol client
+//!
+//! playwright-rust does not expose `newCDPSession`, so commands that need raw
+//! CDP (network emulation, tracing) attach to the page target through the browser's
+//! remote debugging endpoint. The websocket stays open for the lifetime of the
+//! session because Chrome drops emulation overrides when the client detaches.
+
+use crate::driver::trace;
+use anyhow::{Context, Result};
+use futures_util::{SinkExt, StreamExt};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::sync::{mpsc, oneshot, Mutex};
+use tokio_tungstenite::tungstenite::Message;
+
+type Pending = Arc<Mutex<HashMap<u64, oneshot::Sender<serde_json::Value>>>>;
+type Subscribers = Arc<Mutex<HashMap<String, mpsc::UnboundedSender<serde_json::Value>>>>;
+
+pub struct CdpSession {
+    outgoing: mpsc::UnboundedSender<String>,
+    pending: Pending,
+    subscribers: Subscribers,
+    next_id: AtomicU64,
+}
+
+impl CdpSession {
+    /// Attach to the page target showing `page_url` (first page target otherwise)
+    pub async fn attach(endpoint: &str, page_url: &str) -> Result<Self> {
+        let list_url = format!("{}/json/list", endpoint.trim_end_matches('/'));
+        let targets: Vec<serde_json::Value> = reqwest::get(&list_url)
+            .await
+            .with_context(|| format!("Failed to reach CDP endpoint {}", endpoint))?
+            .json()
+            .await
+            .context("Invalid CDP target list")?;
+        let pages: Vec<&serde_json::Value> =
+            targets.iter().filter(|t| t["type"] == "page").collect();
+        let target = pages
+            .iter()
+            .find(|t| t["url"] == page_url)
+            .or_else(|| pages.first())
+            .context("No page target found on the CDP endpoint")?;
+        let ws_url = target["webSocketDebuggerUrl"]
+            .as_str()
+            .context("Page target has no webSocketDebuggerUrl")?;
+
+        let (socket, _) = tokio_tungstenite::connect_async(ws_url)
+            .await
+            .with_context(|| format!("Failed to open CDP websocket {}", ws_url))?;
+        let (mut write, mut read) = socket.split();
+        let (outgoing, mut queue) = mpsc::unbounded_channel::<String>();
+        let pending: Pending = Arc::new(Mutex::new(HashMap::new()));
+
+        let subscribers: Subscribers = Arc::new(Mutex::new(HashMap::new()));
+
+        let responses = pending.clone();
+        let events = subscribers.clone();
+        tokio::spawn(async move {
+            loop {
+                tokio::select! {
+                    message = queue.recv() => match message {
+                        Some(text) => {
+                            if write.send(Message::Text(text)).await.is_err() {
+                                break;
+                            }
+                        }
+                        None => break,
+                    },
+                    frame = read.next() => match frame {
+                        Some(Ok(Message::Text(text))) => {
+                            let Ok(value) = serde_json::from_str::<serde_json::Value>(&text) else {
+                                continue;
+                            };
+                            if let Some(id) = value["id"].as_u64() {
+                                if let Some(reply) = responses.lock().await.remove(&id) {
+                                    let _ = reply.send(value);
+                                }
+                            } else if let Some(method) = value["method"].as_str() {
+                                if let Some(subscriber) = events.lock().await.get(method) {
+                                    let _ = subscriber.send(value["params"].clone());
+                                }
+                            }
+                        }
+                        Some(Ok(_)) => {}
+                        _ => break,
+                    },
+                }
+            }
+        });
+
+        Ok(Self {
+            outgoing,
+            pending,
+            subscribers,
+            next_id: AtomicU64::new(1),
+        })
+    }
+
+    /// Receive the params of every `method` event from now on; replaces an earlier subscriber
+    pub async fn subscribe(&self, method: &str) -> mpsc::UnboundedReceiver<serde_json::Value> {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        self.subscribers
+            .lock()
+            .await
+            .insert(method.to_string(), sender);
+        receiver
+    }
+
+    /// Send a CDP method and wait for its result
+    pub async fn send(&self, method: &str, params: serde_json::Value) -> Result<serde_json::Value> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let (reply, response) = oneshot::channel();
+        self.pending.lock().await.insert(id, reply);
+        let call = trace::Call::start("cdp", &[method, &params.to_string()]);
+        let message = serde_json::json!({ "id": id, "method": method, "params": params });
+        self.outgoing
+            .send(message.to_string())
+            .map_err(|_| anyhow::anyhow!("CDP session closed"))?;
+
+        let value = tokio::time::timeout(std::time::Duration::from_secs(10), response).await;
+        call.finish(matches!(&value, Ok(Ok(v)) if v.get("error").is_none()));
+        let value = value
+            .with_context(|| format!("CDP {} timed out", method))?
+            .map_err(|_| anyhow::anyhow!("CDP session closed"))?;
+        if let Some(error) = value.get("error") {
+            anyhow::bail!("CDP {} failed: {}", method, error);
+        }
+        Ok(value["result"].clone())
+    }
+}
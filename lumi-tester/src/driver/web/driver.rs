@@ -8,17 +8,43 @@ use playwright::api::{Browser, BrowserContext, Page, Viewport};
 use playwright::Playwright;
 // Import RecordVideo manually if not exported in api prelude
 use playwright::api::browser_type::RecordVideo;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tokio::sync::Mutex;
 
+use super::cdp::CdpSession;
 use crate::driver::common;
 use crate::driver::image_matcher::{find_template, ImageRegion, MatchConfig};
-use crate::driver::traits::{PlatformDriver, RelativeDirection, Selector, SwipeDirection};
+use crate::driver::traits::{
+    ensure_selector_supported, LocationTolerance, NthMatch, PlatformDriver, RelativeDirection,
+    Selector, SwipeDirection,
+};
+use crate::error::TesterError;
+use crate::parser::types::NetworkConditions;
 use colored::Colorize;
 use std::sync::Mutex as StdMutex;
 use std::sync::OnceLock;
 
+/// Selector kinds translated to Playwright selectors or resolved on screenshots;
+/// pages have no scrollable-container index
+const SUPPORTED_SELECTORS: &[&str] = &[
+    "text",
+    "fuzzy",
+    "regex",
+    "id",
+    "description",
+    "placeholder",
+    "role",
+    "elementType",
+    "css",
+    "xpath",
+    "point",
+    "image",
+    "ocr",
+    "relative",
+    "containsChild",
+];
+
 /// Global storage for persistent browser - prevents browser from closing when closeWhenFinish is false
 fn get_persistent_browser() -> &'static StdMutex<Option<PersistentBrowserState>> {
     static PERSISTENT_BROWSER: OnceLock<StdMutex<Option<PersistentBrowserState>>> = OnceLock::new();
@@ -44,8 +70,19 @@ pub struct WebDriverConfig {
     pub viewport_height: u32,
     /// CDP endpoint to connect to existing browser (e.g. http://localhost:9222)
     pub cdp_endpoint: Option<String>,
+    /// Remote browser to attach to instead of launching one: an http:// or ws:// CDP
+    /// endpoint, or a Selenium Grid 4 / WebDriver hub url that gets a new Chrome session.
+    /// Unlike `cdp_endpoint` there is no local fallback and the remote session is reused.
+    pub connect_url: Option<String>,
     /// Whether to close browser when test finishes (default: true)
     pub close_when_finish: bool,
+    /// Playwright slowMo: delay every browser operation by this many ms (debugging)
+    pub slow_mo_ms: Option<u64>,
+    /// Write a Chrome performance trace of every flow (`--web-trace`, Chromium only)
+    pub trace: bool,
+    /// Open a remote debugging port on launched Chromium so `setNetworkConditions`
+    /// can throttle over raw CDP
+    pub network_emulation: bool,
 }
 
 impl Default for WebDriverConfig {
@@ -56,6 +93,7 @@ impl Default for WebDriverConfig {
 
         // Check for CDP endpoint from env
         let cdp_endpoint = std::env::var("LUMI_CDP_ENDPOINT").ok();
+        let connect_url = std::env::var("LUMI_CONNECT_URL").ok();
 
         Self {
             browser_type: BrowserType::Chromium,
@@ -64,7 +102,11 @@ impl Default for WebDriverConfig {
             viewport_width: 1280,
             viewport_height: 720,
             cdp_endpoint,
+            connect_url,
             close_when_finish: true,
+            slow_mo_ms: None,
+            trace: false,
+            network_emulation: false,
         }
     }
 }
@@ -85,6 +127,21 @@ pub struct WebDriver {
     console_logs: Arc<Mutex<Vec<String>>>,
     /// OCR engine (lazy-initialized)
     ocr_engine: tokio::sync::OnceCell<crate::driver::ocr::OcrEngine>,
+    /// Remote debugging endpoint of the Chromium browser (None for Firefox/WebKit)
+    cdp_endpoint: Option<String>,
+    /// Page-level CDP session, attached on first use and kept open
+    cdp_session: Mutex<Option<CdpSession>>,
+    /// Flow trace being recorded for `--web-trace`
+    active_trace: Mutex<Option<ActiveTrace>>,
+    /// WebDriver session opened on a Selenium Grid for `connectUrl`, ended on teardown
+    webdriver_session: Option<String>,
+}
+
+/// Chrome trace in progress; the browser streams events until `Tracing.end`
+struct ActiveTrace {
+    path: PathBuf,
+    data: tokio::sync::mpsc::UnboundedReceiver<serde_json::Value>,
+    complete: tokio::sync::mpsc::UnboundedReceiver<serde_json::Value>,
 }
 
 impl WebDriver {
@@ -114,102 +171,115 @@ impl WebDriver {
             .await
             .context("Failed to initialize Playwright")?;
 
-        // Launch or connect to browser based on config
-        let browser = match config.browser_type {
-            BrowserType::Chromium => {
-                let chromium = playwright.chromium();
-
-                // Try to connect to existing browser via CDP if endpoint provided
-                // or if closeWhenFinish is false (persistent mode)
-                let cdp_endpoint = config.cdp_endpoint.clone().or_else(|| {
-                    if !config.close_when_finish {
-                        // Check if browser is running on default port
-                        Some("http://localhost:9222".to_string())
-                    } else {
-                        None
-                    }
-                });
-
-                if let Some(ref endpoint) = cdp_endpoint {
-                    // Try to connect to existing browser
-                    println!(
-                        "{} Trying to connect to browser at: {}",
-                        "🔌".blue(),
-                        endpoint
-                    );
-                    match chromium
-                        .connect_over_cdp_builder(endpoint)
-                        .connect_over_cdp()
-                        .await
-                    {
-                        Ok(b) => {
-                            println!("{} Connected to existing browser!", "✅".green());
-                            b
+        // Launch or connect to browser based on config; Chromium keeps a CDP endpoint
+        // when one is known, for raw DevTools commands (network emulation)
+        let mut webdriver_session = None;
+        let (browser, cdp_endpoint) = if let Some(ref url) = config.connect_url {
+            let remote = connect_remote_browser(&playwright, url, &config).await?;
+            webdriver_session = remote.webdriver_session;
+            (remote.browser, remote.cdp_endpoint)
+        } else {
+            match config.browser_type {
+                BrowserType::Chromium => {
+                    let chromium = playwright.chromium();
+
+                    // Try to connect to existing browser via CDP if endpoint provided
+                    // or if closeWhenFinish is false (persistent mode)
+                    let cdp_endpoint = config.cdp_endpoint.clone().or_else(|| {
+                        if !config.close_when_finish {
+                            // Check if browser is running on default port
+                            Some("http://localhost:9222".to_string())
+                        } else {
+                            None
                         }
-                        Err(e) => {
-                            println!(
-                                "{} Could not connect to existing browser: {}",
-                                "⚠️".yellow(),
-                                e
-                            );
-                            if !config.close_when_finish {
-                                // Launch Chrome externally to keep it running after test
+                    });
+
+                    if let Some(ref endpoint) = cdp_endpoint {
+                        // Try to connect to existing browser
+                        println!(
+                            "{} Trying to connect to browser at: {}",
+                            "🔌".blue(),
+                            endpoint
+                        );
+                        match connect_chromium_over_cdp(&chromium, endpoint, &config).await {
+                            Ok(b) => {
+                                println!("{} Connected to existing browser!", "✅".green());
+                                (b, Some(endpoint.clone()))
+                            }
+                            Err(e) => {
                                 println!(
+                                    "{} Could not connect to existing browser: {}",
+                                    "⚠️".yellow(),
+                                    e
+                                );
+                                if !config.close_when_finish {
+                                    // Launch Chrome externally to keep it running after test
+                                    println!(
                                     "{} Launching Chrome externally with remote debugging port...",
                                     "🚀".blue()
                                 );
-                                launch_chrome_externally()?;
-
-                                // Wait a moment for Chrome to start
-                                tokio::time::sleep(tokio::time::Duration::from_millis(2000)).await;
-
-                                // Now connect to it via CDP
-                                match chromium
-                                    .connect_over_cdp_builder(endpoint)
-                                    .connect_over_cdp()
-                                    .await
-                                {
-                                    Ok(b) => {
-                                        println!(
-                                            "{} Connected to externally launched Chrome!",
-                                            "✅".green()
-                                        );
-                                        b
-                                    }
-                                    Err(e2) => {
-                                        anyhow::bail!(
+                                    launch_chrome_externally()?;
+
+                                    // Wait a moment for Chrome to start
+                                    tokio::time::sleep(tokio::time::Duration::from_millis(2000))
+                                        .await;
+
+                                    // Now connect to it via CDP
+                                    match connect_chromium_over_cdp(&chromium, endpoint, &config)
+                                        .await
+                                    {
+                                        Ok(b) => {
+                                            println!(
+                                                "{} Connected to externally launched Chrome!",
+                                                "✅".green()
+                                            );
+                                            (b, Some(endpoint.clone()))
+                                        }
+                                        Err(e2) => {
+                                            anyhow::bail!(
                                             "Failed to connect to Chrome after external launch: {}",
                                             e2
                                         );
+                                        }
                                     }
+                                } else {
+                                    // Normal launch via Playwright
+                                    launch_chromium_browser(&chromium, &config).await?
                                 }
-                            } else {
-                                // Normal launch via Playwright
-                                launch_chromium_browser(&chromium, &config).await?
                             }
                         }
+                    } else {
+                        launch_chromium_browser(&chromium, &config).await?
                     }
-                } else {
-                    launch_chromium_browser(&chromium, &config).await?
                 }
-            }
-            BrowserType::Firefox => {
-                playwright
-                    .firefox()
-                    .launcher()
-                    .headless(config.headless)
-                    .launch()
-                    .await?
-            }
-            BrowserType::Webkit => {
-                playwright
-                    .webkit()
-                    .launcher()
-                    .headless(config.headless)
-                    .launch()
-                    .await?
+                BrowserType::Firefox => {
+                    let browser_type = playwright.firefox();
+                    let mut launcher = browser_type.launcher().headless(config.headless);
+                    if let Some(ms) = config.slow_mo_ms {
+                        launcher = launcher.slowmo(ms as f64);
+                    }
+                    let browser = launcher
+                        .launch()
+                        .await
+                        .with_context(|| browser_launch_hint("firefox"))?;
+                    (browser, None)
+                }
+                BrowserType::Webkit => {
+                    let browser_type = playwright.webkit();
+                    let mut launcher = browser_type.launcher().headless(config.headless);
+                    if let Some(ms) = config.slow_mo_ms {
+                        launcher = launcher.slowmo(ms as f64);
+                    }
+                    let browser = launcher
+                        .launch()
+                        .await
+                        .with_context(|| browser_launch_hint("webkit"))?;
+                    (browser, None)
+                }
             }
         };
+        // A remote session is reused as-is so pre-authenticated state carries over
+        let reuse_session = !config.close_when_finish || config.connect_url.is_some();
 
         // Create browser context
         let record_video = std::env::var("LUMI_VIDEO_RECORD")
@@ -217,7 +287,7 @@ impl WebDriver {
             .unwrap_or(false);
 
         // Try to reuse existing context for persistence
-        let reused_context = if !config.close_when_finish {
+        let reused_context = if reuse_session {
             let contexts = browser.contexts()?;
             if let Some(ctx) = contexts.into_iter().next() {
                 println!("{} Reusing existing browser context", "♻️".green());
@@ -231,23 +301,21 @@ impl WebDriver {
 
         let context = if let Some(ctx) = reused_context {
             ctx
-        } else if record_video {
+        } else {
             let temp_dir = std::env::temp_dir().join("lumi_tester_videos");
-            std::fs::create_dir_all(&temp_dir).ok();
-            browser
-                .context_builder()
-                .record_video(RecordVideo {
+            let mut builder = browser.context_builder();
+            if record_video {
+                std::fs::create_dir_all(&temp_dir).ok();
+                builder = builder.record_video(RecordVideo {
                     dir: &temp_dir,
                     size: None,
-                })
-                .build()
-                .await?
-        } else {
-            browser.context_builder().build().await?
+                });
+            }
+            builder.build().await?
         };
 
         // Create or reuse page
-        let page = if !config.close_when_finish {
+        let page = if reuse_session {
             // Need a small delay for Playwright to sync pages from CDP
             tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
 
@@ -298,6 +366,10 @@ impl WebDriver {
             current_recording_path: Arc::new(Mutex::new(None)),
             console_logs,
             ocr_engine: tokio::sync::OnceCell::new(),
+            cdp_endpoint,
+            cdp_session: Mutex::new(None),
+            active_trace: Mutex::new(None),
+            webdriver_session,
         })
     }
 
@@ -312,7 +384,7 @@ impl WebDriver {
     async fn find_ocr_text(
         &self,
         text: &str,
-        index: usize,
+        index: i32,
         is_regex: bool,
         region: Option<&str>,
     ) -> Result<Option<(i32, i32)>> {
@@ -427,25 +499,29 @@ impl WebDriver {
     /// Convert Selector to Playwright selector string
     fn selector_to_playwright(&self, selector: &Selector) -> String {
         match selector {
-            Selector::Text(text, index, _) => {
-                if *index == 0 {
-                    format!("text=\"{}\"", text)
-                } else {
-                    format!("xpath=(//*[text()=\"{}\"])[{}]", text, index + 1)
-                }
-            }
+            // The viewport check runs separately (`in_viewport`); Playwright's own
+            // visibility counts elements scrolled out of view
+            Selector::OnScreen(inner) => self.selector_to_playwright(inner),
+            Selector::Text(text, index, exact) => text_selector(text, *index, *exact),
+            // Every lookup resolves fuzzy text through `find_element_handle`, and
+            // `into_fuzzy` keeps it out of nested selectors
+            Selector::FuzzyText(text, index, _) => text_selector(text, *index, false),
             Selector::TextRegex(regex, index) => {
                 if *index == 0 {
                     format!("text=/{}/", regex)
                 } else {
-                    format!("xpath=(//*[matches(text(), \"{}\")])[{}]", regex, index + 1)
+                    format!(
+                        "xpath=(//*[matches(text(), \"{}\")])[{}]",
+                        regex,
+                        xpath_position(*index)
+                    )
                 }
             }
             Selector::Id(id, index) => {
                 if *index == 0 {
                     format!("#{}", id)
                 } else {
-                    format!("xpath=(//*[@id=\"{}\"])[{}]", id, index + 1)
+                    format!("xpath=(//*[@id=\"{}\"])[{}]", id, xpath_position(*index))
                 }
             }
             Selector::IdRegex(regex, _index) => {
@@ -461,20 +537,32 @@ impl WebDriver {
                 if *index == 0 {
                     base
                 } else {
-                    format!("xpath=({})[{}]", self.to_xpath(&base), index + 1)
+                    format!(
+                        "xpath=({})[{}]",
+                        self.to_xpath(&base),
+                        xpath_position(*index)
+                    )
                 }
             }
             Selector::Role(role, index) => {
                 if *index == 0 {
                     format!("[role=\"{}\"]", role)
                 } else {
-                    format!("xpath=(//*[@role=\"{}\"])[{}]", role, index + 1)
+                    format!(
+                        "xpath=(//*[@role=\"{}\"])[{}]",
+                        role,
+                        xpath_position(*index)
+                    )
                 }
             }
             Selector::Css(css) => css.clone(),
             Selector::XPath(xpath) => format!("xpath={}", xpath),
             Selector::Placeholder(p, index) => {
-                format!("xpath=(//*[@placeholder=\"{}\"])[{}]", p, index + 1)
+                format!(
+                    "xpath=(//*[@placeholder=\"{}\"])[{}]",
+                    p,
+                    xpath_position(*index)
+                )
             }
             Selector::Point { .. } => String::new(), // Handle separately
             Selector::AccessibilityId(id) | Selector::Description(id, _) => {
@@ -487,7 +575,7 @@ impl WebDriver {
                     format!(
                         "xpath=(//*[matches(@aria-label, \"{}\")])[{}]",
                         regex,
-                        index + 1
+                        xpath_position(*index)
                     )
                 }
             }
@@ -497,7 +585,14 @@ impl WebDriver {
                 anchor,
                 direction,
                 max_dist,
+                angle,
             } => {
+                if angle.is_some() {
+                    println!(
+                        "  {} angle tolerance not supported on Web, using layout pseudo-classes",
+                        "⚠️".yellow()
+                    );
+                }
                 let pseudo = match direction {
                     RelativeDirection::LeftOf => "left-of",
                     RelativeDirection::RightOf => "right-of",
@@ -508,7 +603,7 @@ impl WebDriver {
 
                 // Get anchor selector that is compatible with CSS pseudo-classes
                 let anchor_sel = match anchor.as_ref() {
-                    Selector::Text(t, _, _) => format!(":text(\"{}\")", t),
+                    Selector::Text(t, _, exact) => text_pseudo(t, *exact),
                     Selector::Id(id, _) => format!("#{}", id),
                     Selector::Type(t, _) => map_web_type(t),
                     Selector::Placeholder(p, _) => format!("[placeholder=\"{}\"]", p),
@@ -517,12 +612,15 @@ impl WebDriver {
                     _ => self.selector_to_playwright(anchor),
                 };
 
-                let dist_suffix = max_dist.map(|d| format!(", {}", d)).unwrap_or_default();
+                let viewport = (self.config.viewport_width, self.config.viewport_height);
+                let dist_suffix = max_dist
+                    .map(|d| format!(", {}", d.to_pixels(direction.axis_length(viewport))))
+                    .unwrap_or_default();
 
                 // Get base selector and index from target
                 // We prefer simple selectors as the base for layout pseudo-classes
                 let (base, index) = match target.as_ref() {
-                    Selector::Text(t, idx, _) => (format!(":text(\"{}\")", t), *idx),
+                    Selector::Text(t, idx, exact) => (text_pseudo(t, *exact), *idx),
                     Selector::Id(id, idx) => (format!("#{}", id), *idx),
                     Selector::Type(t, idx) => (map_web_type(t), *idx),
                     Selector::Placeholder(p, idx) => (format!("[placeholder=\"{}\"]", p), *idx),
@@ -562,7 +660,7 @@ impl WebDriver {
                 } else {
                     format!(
                         "xpath=(//button|//a|//*[@onclick]|//*[@role='button'])[{}]",
-                        index + 1
+                        xpath_position(*index)
                     )
                 }
             }
@@ -607,7 +705,7 @@ impl WebDriver {
     async fn find_element_by_id_regex(
         &self,
         regex: &str,
-        index: usize,
+        index: i32,
     ) -> Result<Option<playwright::api::JsHandle>> {
         let page = self.page.lock().await;
 
@@ -617,17 +715,10 @@ impl WebDriver {
             () => {{
                 try {{
                     const pattern = new RegExp("{}");
-                    const allElements = document.querySelectorAll('[id]');
-                    let count = 0;
-                    for (const el of allElements) {{
-                        if (pattern.test(el.id)) {{
-                            if (count === {}) {{
-                                return el;
-                            }}
-                            count++;
-                        }}
-                    }}
-                    return null;
+                    const matches = Array.from(document.querySelectorAll('[id]'))
+                        .filter((el) => pattern.test(el.id));
+                    const index = {};
+                    return matches[index < 0 ? matches.length + index : index] ?? null;
                 }} catch (e) {{
                     return null;
                 }}
@@ -654,6 +745,99 @@ impl WebDriver {
             Ok(Some(handle))
         }
     }
+
+    /// Element handle of the visible text most similar to `text`; `None` when nothing
+    /// is similar enough. The handle points at the ranked element itself, so quotes
+    /// in the text and repeated labels need no selector escaping or occurrence count.
+    async fn find_fuzzy_text(
+        &self,
+        text: &str,
+        index: i32,
+        threshold: f64,
+    ) -> Result<Option<playwright::api::JsHandle>> {
+        let page = self.page.lock().await;
+        let texts: Vec<String> = page
+            .evaluate(
+                &format!("() => ({})().map(([, text]) => text)", VISIBLE_TEXTS_JS),
+                (),
+            )
+            .await?;
+        let mut ranked: Vec<(f64, usize)> = texts
+            .iter()
+            .enumerate()
+            .filter_map(|(i, candidate)| {
+                let score = common::text_similarity(text, candidate);
+                (score >= threshold).then_some((score, i))
+            })
+            .collect();
+        ranked.sort_by(|a, b| b.0.total_cmp(&a.0));
+        let Some((_, best)) = ranked.into_iter().nth_match(index) else {
+            return Ok(None);
+        };
+        let handle = page
+            .evaluate_js_handle(
+                &format!("() => ({})()[{}]?.[0] ?? null", VISIBLE_TEXTS_JS, best),
+                Some(()),
+            )
+            .await?;
+        Ok(Some(handle))
+    }
+
+    /// The page's CDP session, attached on first use
+    async fn cdp_session(&self) -> Result<tokio::sync::MutexGuard<'_, Option<CdpSession>>> {
+        let mut session = self.cdp_session.lock().await;
+        if session.is_none() {
+            let endpoint = self.cdp_endpoint.as_deref().context(
+                "No CDP endpoint available; the browser opens one only for --web-trace or when a flow file uses setNetworkConditions",
+            )?;
+            let page_url = self.page.lock().await.url().unwrap_or_default();
+            let attached = CdpSession::attach(endpoint, &page_url).await?;
+            attached
+                .send("Network.enable", serde_json::json!({}))
+                .await?;
+            *session = Some(attached);
+        }
+        Ok(session)
+    }
+
+    /// Element handle for the selectors Playwright cannot express (`IdRegex`, `FuzzyText`)
+    async fn find_element_handle(
+        &self,
+        selector: &Selector,
+    ) -> Result<Option<playwright::api::JsHandle>> {
+        match selector.unwrap_on_screen() {
+            Selector::IdRegex(regex, index) => self.find_element_by_id_regex(regex, *index).await,
+            Selector::FuzzyText(text, index, threshold) => {
+                self.find_fuzzy_text(text, *index, *threshold).await
+            }
+            other => anyhow::bail!("No element handle lookup for {:?}", other),
+        }
+    }
+
+    /// Whether the element intersects the viewport (`onlyVisible`). Image and OCR
+    /// matches come from a screenshot, so they are always inside it.
+    async fn in_viewport(&self, selector: &Selector) -> Result<bool> {
+        match selector.unwrap_on_screen() {
+            Selector::IdRegex(..) | Selector::FuzzyText(..) => {
+                match self.find_element_handle(selector).await? {
+                    Some(handle) => {
+                        let page = self.page.lock().await;
+                        Ok(page.evaluate(IN_VIEWPORT_JS, handle).await?)
+                    }
+                    None => Ok(false),
+                }
+            }
+            Selector::Image { .. } | Selector::OCR(..) | Selector::Point { .. } => Ok(true),
+            _ => {
+                let page = self.page.lock().await;
+                let sel = self.selector_to_playwright(selector);
+                Ok(page
+                    .evaluate_on_selector::<bool, _>(&sel, IN_VIEWPORT_JS, None::<bool>)
+                    .await
+                    .unwrap_or(false))
+            }
+        }
+    }
 }
 
 #[async_trait]
@@ -666,7 +850,74 @@ impl PlatformDriver for WebDriver {
         Some(format!("{:?}", self.config.browser_type))
     }
 
-    async fn launch_app(&self, url: &str, _clear_state: bool) -> Result<()> {
+    async fn teardown(&self) -> Result<()> {
+        if let Some(session) = &self.webdriver_session {
+            end_webdriver_session(session).await?;
+        }
+        Ok(())
+    }
+
+    async fn start_trace(&self, path: &Path) -> Result<bool> {
+        if !self.config.trace {
+            return Ok(false);
+        }
+        if !matches!(self.config.browser_type, BrowserType::Chromium) {
+            anyhow::bail!(
+                "--web-trace records over the Chrome DevTools Protocol, which only Chromium supports (browser: {:?})",
+                self.config.browser_type
+            );
+        }
+        let session = self.cdp_session().await?;
+        let session = session.as_ref().expect("attached by cdp_session");
+        let data = session.subscribe("Tracing.dataCollected").await;
+        let complete = session.subscribe("Tracing.tracingComplete").await;
+        session
+            .send(
+                "Tracing.start",
+                serde_json::json!({
+                    "transferMode": "ReportEvents",
+                    "categories": TRACE_CATEGORIES,
+                }),
+            )
+            .await?;
+        *self.active_trace.lock().await = Some(ActiveTrace {
+            path: path.to_path_buf(),
+            data,
+            complete,
+        });
+        Ok(true)
+    }
+
+    async fn stop_trace(&self) -> Result<()> {
+        let Some(mut trace) = self.active_trace.lock().await.take() else {
+            return Ok(());
+        };
+        {
+            let session = self.cdp_session().await?;
+            let session = session.as_ref().expect("attached by cdp_session");
+            session.send("Tracing.end", serde_json::json!({})).await?;
+        }
+        tokio::time::timeout(TRACE_FLUSH_TIMEOUT, trace.complete.recv())
+            .await
+            .context("Timed out waiting for the browser to flush the trace")?;
+        let mut events = Vec::new();
+        while let Ok(chunk) = trace.data.try_recv() {
+            if let Some(values) = chunk["value"].as_array() {
+                events.extend(values.iter().cloned());
+            }
+        }
+        if let Some(parent) = trace.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(
+            &trace.path,
+            serde_json::to_vec(&serde_json::json!({ "traceEvents": events }))?,
+        )
+        .with_context(|| format!("Failed to write web trace {}", trace.path.display()))?;
+        Ok(())
+    }
+
+    async fn launch_app(&self, url: &str, clear_state: bool) -> Result<()> {
         let page = self.page.lock().await;
 
         // If URL is relative and we have a base URL, combine them
@@ -683,6 +934,16 @@ impl PlatformDriver for WebDriver {
             .await
             .context("Failed to navigate to URL")?;
 
+        // Storage is scoped to the origin, so it can only be wiped once the
+        // page is there; reload so the app starts from the clean state.
+        if clear_state {
+            clear_browser_state(&page).await?;
+            page.reload_builder()
+                .reload()
+                .await
+                .context("Failed to reload page after clearing state")?;
+        }
+
         Ok(())
     }
 
@@ -693,15 +954,16 @@ impl PlatformDriver for WebDriver {
     }
 
     async fn tap(&self, selector: &Selector) -> Result<()> {
-        match selector {
-            Selector::IdRegex(regex, index) => {
-                if let Some(handle) = self.find_element_by_id_regex(regex, *index).await? {
+        ensure_selector_supported("Web", selector, SUPPORTED_SELECTORS)?;
+        match selector.unwrap_on_screen() {
+            Selector::IdRegex(..) | Selector::FuzzyText(..) => {
+                if let Some(handle) = self.find_element_handle(selector).await? {
                     // Click using JS since we have a JsHandle
                     // Use page.evaluate to execute click on the handle
                     let page = self.page.lock().await;
                     page.evaluate::<_, ()>("el => el.click()", handle).await?;
                 } else {
-                    anyhow::bail!("Element not found for IdRegex: {}", regex);
+                    return Err(TesterError::element_not_found(selector).into());
                 }
             }
             Selector::Point { x, y } => {
@@ -763,13 +1025,14 @@ impl PlatformDriver for WebDriver {
     }
 
     async fn long_press(&self, selector: &Selector, duration_ms: u64) -> Result<()> {
+        ensure_selector_supported("Web", selector, SUPPORTED_SELECTORS)?;
         let page = self.page.lock().await;
 
-        let (x, y) = match selector {
-            Selector::IdRegex(regex, index) => {
+        let (x, y) = match selector.unwrap_on_screen() {
+            Selector::IdRegex(..) | Selector::FuzzyText(..) => {
                 // Find element using JS
                 drop(page); // release lock for helper
-                if let Some(handle) = self.find_element_by_id_regex(regex, *index).await? {
+                if let Some(handle) = self.find_element_handle(selector).await? {
                     // Get bounding box via JS
                     let page = self.page.lock().await;
                     let json: serde_json::Value = page
@@ -789,7 +1052,7 @@ impl PlatformDriver for WebDriver {
 
                     (x + w / 2.0, y + h / 2.0)
                 } else {
-                    anyhow::bail!("Element not found for IdRegex: {}", regex);
+                    return Err(TesterError::element_not_found(selector).into());
                 }
             }
             _ => {
@@ -827,9 +1090,10 @@ impl PlatformDriver for WebDriver {
     }
 
     async fn double_tap(&self, selector: &Selector) -> Result<()> {
-        match selector {
-            Selector::IdRegex(regex, index) => {
-                if let Some(handle) = self.find_element_by_id_regex(regex, *index).await? {
+        ensure_selector_supported("Web", selector, SUPPORTED_SELECTORS)?;
+        match selector.unwrap_on_screen() {
+            Selector::IdRegex(..) | Selector::FuzzyText(..) => {
+                if let Some(handle) = self.find_element_handle(selector).await? {
                     // Dispatch dblclick event
                     let page = self.page.lock().await;
                     page.evaluate::<_, ()>(
@@ -838,7 +1102,7 @@ impl PlatformDriver for WebDriver {
                     )
                     .await?;
                 } else {
-                    anyhow::bail!("Element not found for IdRegex: {}", regex);
+                    return Err(TesterError::element_not_found(selector).into());
                 }
             }
             _ => {
@@ -851,14 +1115,15 @@ impl PlatformDriver for WebDriver {
     }
 
     async fn right_click(&self, selector: &Selector) -> Result<()> {
-        match selector {
-            Selector::IdRegex(regex, index) => {
-                if let Some(handle) = self.find_element_by_id_regex(regex, *index).await? {
+        ensure_selector_supported("Web", selector, SUPPORTED_SELECTORS)?;
+        match selector.unwrap_on_screen() {
+            Selector::IdRegex(..) | Selector::FuzzyText(..) => {
+                if let Some(handle) = self.find_element_handle(selector).await? {
                     // Dispatch contextmenu event for right click simulation
                     let page = self.page.lock().await;
                     page.evaluate::<_, ()>("el => el.dispatchEvent(new MouseEvent('contextmenu', { bubbles: true, button: 2, buttons: 2 }))", handle).await?;
                 } else {
-                    anyhow::bail!("Element not found for IdRegex: {}", regex);
+                    return Err(TesterError::element_not_found(selector).into());
                 }
             }
             _ => {
@@ -912,11 +1177,12 @@ impl PlatformDriver for WebDriver {
         };
 
         if let Some(selector) = from {
+            ensure_selector_supported("Web", &selector, SUPPORTED_SELECTORS)?;
             // If explicit scroll source provided, scroll that element
-            match selector {
-                Selector::IdRegex(regex, index) => {
-                    // Check specific id regex handling
-                    if let Some(handle) = self.find_element_by_id_regex(&regex, index).await? {
+            match selector.unwrap_on_screen() {
+                Selector::IdRegex(..) | Selector::FuzzyText(..) => {
+                    // Regex ids and fuzzy text resolve to a JS handle
+                    if let Some(handle) = self.find_element_handle(&selector).await? {
                         let js = format!("el => el.scrollBy({}, {})", dx, dy);
                         let page = self.page.lock().await;
                         page.evaluate::<_, ()>(&js, handle).await?;
@@ -961,9 +1227,13 @@ impl PlatformDriver for WebDriver {
     }
 
     async fn is_visible(&self, selector: &Selector) -> Result<bool> {
-        match selector {
-            Selector::IdRegex(regex, index) => {
-                let handle = self.find_element_by_id_regex(regex, *index).await?;
+        ensure_selector_supported("Web", selector, SUPPORTED_SELECTORS)?;
+        if let Selector::OnScreen(inner) = selector {
+            return Ok(self.is_visible(inner).await? && self.in_viewport(selector).await?);
+        }
+        match selector.unwrap_on_screen() {
+            Selector::IdRegex(..) | Selector::FuzzyText(..) => {
+                let handle = self.find_element_handle(selector).await?;
                 if let Some(h) = handle {
                     // Check visibility using JS
                     let page = self.page.lock().await;
@@ -1011,7 +1281,10 @@ impl PlatformDriver for WebDriver {
             el.click_builder().click().await?;
             Ok(())
         } else {
-            anyhow::bail!("Element not found: {} at index {}", element_type, index)
+            Err(TesterError::ElementNotFound {
+                selector: format!("{} at index {}", element_type, index),
+            }
+            .into())
         }
     }
 
@@ -1022,23 +1295,39 @@ impl PlatformDriver for WebDriver {
             el.fill_builder(text).fill().await?;
             Ok(())
         } else {
-            anyhow::bail!("Element not found: {} at index {}", element_type, index)
+            Err(TesterError::ElementNotFound {
+                selector: format!("{} at index {}", element_type, index),
+            }
+            .into())
         }
     }
 
     async fn wait_for_element(&self, selector: &Selector, timeout_ms: u64) -> Result<bool> {
-        match selector {
-            Selector::IdRegex(regex, index) => {
+        ensure_selector_supported("Web", selector, SUPPORTED_SELECTORS)?;
+        match selector.unwrap_on_screen() {
+            // `wait_for_selector` accepts elements scrolled out of view
+            _ if matches!(selector, Selector::OnScreen(_)) => {
+                let start = std::time::Instant::now();
+                while start.elapsed().as_millis() < timeout_ms as u128 {
+                    if self.is_visible(selector).await? {
+                        return Ok(true);
+                    }
+                    tokio::time::sleep(tokio::time::Duration::from_millis(200)).await;
+                }
+                Ok(false)
+            }
+            Selector::IdRegex(..) => {
                 let start = std::time::Instant::now();
                 while start.elapsed().as_millis() < timeout_ms as u128 {
-                    if let Some(_) = self.find_element_by_id_regex(regex, *index).await? {
+                    if self.find_element_handle(selector).await?.is_some() {
                         return Ok(true);
                     }
                     tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
                 }
                 Ok(false)
             }
-            Selector::Image { .. } | Selector::OCR(..) => {
+            // Fuzzy text is resolved against the page on every poll
+            Selector::Image { .. } | Selector::OCR(..) | Selector::FuzzyText(..) => {
                 let start = std::time::Instant::now();
                 while start.elapsed().as_millis() < timeout_ms as u128 {
                     if self.is_visible(selector).await? {
@@ -1078,15 +1367,16 @@ impl PlatformDriver for WebDriver {
     }
 
     async fn get_element_text(&self, selector: &Selector) -> Result<String> {
-        match selector {
-            Selector::IdRegex(regex, index) => {
-                if let Some(handle) = self.find_element_by_id_regex(regex, *index).await? {
+        ensure_selector_supported("Web", selector, SUPPORTED_SELECTORS)?;
+        match selector.unwrap_on_screen() {
+            Selector::IdRegex(..) | Selector::FuzzyText(..) => {
+                if let Some(handle) = self.find_element_handle(selector).await? {
                     let page = self.page.lock().await;
                     let js = "el => el.value || el.innerText || el.textContent || ''";
                     let text: String = page.evaluate(js, handle).await?;
                     Ok(text)
                 } else {
-                    Ok(String::new())
+                    Err(TesterError::element_not_found(selector).into())
                 }
             }
             _ => {
@@ -1102,7 +1392,7 @@ impl PlatformDriver for WebDriver {
                     .await
                 {
                     Ok(text) => Ok(text),
-                    Err(_) => Ok(String::new()),
+                    Err(_) => Err(TesterError::element_not_found(selector).into()),
                 }
             }
         }
@@ -1230,19 +1520,8 @@ impl PlatformDriver for WebDriver {
     }
 
     async fn rotate_screen(&self, mode: &str) -> Result<()> {
-        let (w, h) = (self.config.viewport_width, self.config.viewport_height);
-        let (new_w, new_h) = if mode.eq_ignore_ascii_case("landscape") {
-            (w.max(h), w.min(h))
-        } else {
-            (w.min(h), w.max(h))
-        };
-        let page = self.page.lock().await;
-        page.set_viewport_size(Viewport {
-            width: new_w as i32,
-            height: new_h as i32,
-        })
-        .await?;
-        Ok(())
+        let orientation = crate::parser::types::Orientation::from_mode(mode)?;
+        self.set_orientation(orientation).await
     }
 
     async fn start_recording(&self, path: &str) -> Result<()> {
@@ -1315,13 +1594,7 @@ impl PlatformDriver for WebDriver {
 
     async fn clear_app_data(&self, _app_id: &str) -> Result<()> {
         let page = self.page.lock().await;
-        page.context().clear_cookies().await?;
-        page.evaluate::<_, ()>(
-            "() => { localStorage.clear(); sessionStorage.clear(); }",
-            (),
-        )
-        .await?;
-        Ok(())
+        clear_browser_state(&page).await
     }
 
     async fn set_clipboard(&self, text: &str) -> Result<()> {
@@ -1341,6 +1614,25 @@ impl PlatformDriver for WebDriver {
         Ok(text)
     }
 
+    async fn get_current_url(&self) -> Result<String> {
+        let page = self.page.lock().await;
+        Ok(page.url()?)
+    }
+
+    async fn execute_js(&self, script: &str) -> Result<String> {
+        let page = self.page.lock().await;
+        let value: serde_json::Value = page
+            .evaluate(script, ())
+            .await
+            .context("executeScript failed in page")?;
+        // Strings are stored as-is, everything else as JSON
+        Ok(match value {
+            serde_json::Value::Null => String::new(),
+            serde_json::Value::String(s) => s,
+            other => other.to_string(),
+        })
+    }
+
     // New Commands Implementation
 
     async fn set_network_connection(&self, _wifi: Option<bool>, _data: Option<bool>) -> Result<()> {
@@ -1373,8 +1665,7 @@ impl PlatformDriver for WebDriver {
         _name: Option<String>,
         points: Vec<crate::parser::gps::GpsPoint>,
         _speed_kmh: Option<f64>,
-        _speed_mode: crate::parser::types::SpeedMode,
-        _speed_noise: Option<f64>,
+        _speed_profile: crate::parser::types::DriveProfile,
         _interval_ms: u64,
         _loop_route: bool,
     ) -> Result<()> {
@@ -1418,7 +1709,7 @@ impl PlatformDriver for WebDriver {
         _name: Option<String>,
         lat: f64,
         lon: f64,
-        tolerance: f64,
+        tolerance: LocationTolerance,
         timeout: u64,
     ) -> Result<()> {
         let page = self.page.lock().await;
@@ -1454,9 +1745,7 @@ impl PlatformDriver for WebDriver {
                     obj.get("lat").and_then(|v| v.as_f64()),
                     obj.get("lon").and_then(|v| v.as_f64()),
                 ) {
-                    let d_lat = (c_lat - lat).abs();
-                    let d_lon = (c_lon - lon).abs();
-                    if d_lat <= tolerance && d_lon <= tolerance {
+                    if tolerance.contains((c_lat, c_lon), (lat, lon)) {
                         return Ok(());
                     }
                 }
@@ -1468,9 +1757,10 @@ impl PlatformDriver for WebDriver {
         }
 
         Err(anyhow::anyhow!(
-            "Timeout waiting for location {}, {}",
+            "Timeout waiting for location {}, {} (within {})",
             lat,
-            lon
+            lon,
+            tolerance
         ))
     }
 
@@ -1522,16 +1812,16 @@ impl PlatformDriver for WebDriver {
     }
 
     async fn set_orientation(&self, mode: crate::parser::types::Orientation) -> Result<()> {
-        use crate::parser::types::Orientation;
-
-        let w = self.config.viewport_width;
-        let h = self.config.viewport_height;
-
-        let (new_w, new_h) = match mode {
-            Orientation::Portrait | Orientation::UpsideDown => (w, h),
-            Orientation::Landscape | Orientation::LandscapeLeft | Orientation::LandscapeRight => {
-                (h, w)
-            } // Swap
+        // A browser has no rotation: swap the viewport so the long side matches.
+        // Taken from the configured viewport, so repeating a mode is a no-op.
+        if mode == crate::parser::types::Orientation::UpsideDown {
+            anyhow::bail!("Web viewports can't be rotated upside down; use portrait or landscape");
+        }
+        let (w, h) = (self.config.viewport_width, self.config.viewport_height);
+        let (new_w, new_h) = if mode.is_landscape() {
+            (w.max(h), w.min(h))
+        } else {
+            (w.min(h), w.max(h))
         };
 
         let page = self.page.lock().await;
@@ -1618,26 +1908,142 @@ impl PlatformDriver for WebDriver {
         Ok(())
     }
 
-    async fn set_network_conditions(&self, profile: &str) -> Result<()> {
-        // Chromium only - emulate network
-        if matches!(self.config.browser_type, BrowserType::Chromium) {
-            let _context = &self.context;
-            // Offline
-            // slow 3g, fast 3g
+    async fn set_network_conditions(&self, conditions: &NetworkConditions) -> Result<()> {
+        // Offline works in every browser through the context
+        self.context.set_offline(conditions.offline).await?;
+        if !matches!(self.config.browser_type, BrowserType::Chromium) {
+            if conditions.is_throttled() {
+                anyhow::bail!(
+                    "Latency/throughput emulation needs Chromium (CDP); only offline/online work on {:?}",
+                    self.config.browser_type
+                );
+            }
+            return Ok(());
+        }
 
-            // Playwright doesn't have a direct 'emulateNetwork' on context at crate level yet in all versions
-            // But let's check if we can simply warn for now as this requires CDP session access
-            println!("  {} Network emulation '{}' only supported if underlying driver exposes CDP session. Skipping.", "⚠️".yellow(), profile);
-        } else {
-            println!(
-                "  {} Network emulation only supported on Chromium",
-                "⚠️".yellow()
-            );
+        let session = self.cdp_session().await?;
+        // CDP throughput is bytes/s; -1 disables throttling
+        let bytes_per_sec = |kbps: Option<f64>| kbps.map_or(-1.0, |v| v * 1000.0 / 8.0);
+        let mut params = serde_json::json!({
+            "offline": conditions.offline,
+            "latency": conditions.latency_ms,
+            "downloadThroughput": bytes_per_sec(conditions.download_kbps),
+            "uploadThroughput": bytes_per_sec(conditions.upload_kbps),
+        });
+        if conditions.packet_loss > 0.0 {
+            params["packetLoss"] = serde_json::json!(conditions.packet_loss);
         }
+        session
+            .as_ref()
+            .expect("attached by cdp_session")
+            .send("Network.emulateNetworkConditions", params)
+            .await?;
         Ok(())
     }
 }
 
+/// Wipes storage for the page's origin: Web Storage, IndexedDB, Cache Storage
+/// and service workers. Each step is guarded because opaque origins such as
+/// `about:blank` throw on access.
+const CLEAR_STORAGE_JS: &str = r#"async () => {
+    try { localStorage.clear(); } catch (e) {}
+    try { sessionStorage.clear(); } catch (e) {}
+    try {
+        if (indexedDB.databases) {
+            const dbs = await indexedDB.databases();
+            await Promise.all(dbs.filter(db => db.name).map(db => new Promise(resolve => {
+                const req = indexedDB.deleteDatabase(db.name);
+                req.onsuccess = req.onerror = req.onblocked = () => resolve();
+            })));
+        }
+    } catch (e) {}
+    try {
+        if (self.caches) {
+            const keys = await caches.keys();
+            await Promise.all(keys.map(key => caches.delete(key)));
+        }
+    } catch (e) {}
+    try {
+        if (navigator.serviceWorker) {
+            const regs = await navigator.serviceWorker.getRegistrations();
+            await Promise.all(regs.map(reg => reg.unregister()));
+        }
+    } catch (e) {}
+}"#;
+
+/// Clears cookies for the whole browser context plus storage for the current origin.
+async fn clear_browser_state(page: &Page) -> Result<()> {
+    page.context()
+        .clear_cookies()
+        .await
+        .context("Failed to clear browser cookies")?;
+    page.evaluate::<_, ()>(CLEAR_STORAGE_JS, ())
+        .await
+        .context("Failed to clear browser storage")?;
+    Ok(())
+}
+
+/// Trace categories matching a DevTools Performance recording, with screenshots
+const TRACE_CATEGORIES: &str = "-*,devtools.timeline,disabled-by-default-devtools.timeline,disabled-by-default-devtools.timeline.frame,disabled-by-default-devtools.screenshot,blink.user_timing,loading,v8.execute";
+
+/// How long the browser gets to stream the remaining trace events after `Tracing.end`
+/// Element box intersects the visible part of the page
+const IN_VIEWPORT_JS: &str = r#"el => {
+    const r = el.getBoundingClientRect();
+    return r.width > 0 && r.height > 0 && r.bottom > 0 && r.right > 0
+        && r.top < window.innerHeight && r.left < window.innerWidth;
+}"#;
+
+const TRACE_FLUSH_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// `[element, own text]` of every rendered element with text, in document order;
+/// candidates for `fuzzy: true`
+const VISIBLE_TEXTS_JS: &str = r#"() => Array.from(document.querySelectorAll('body *'))
+    .filter(el => !['SCRIPT', 'STYLE', 'NOSCRIPT'].includes(el.tagName) && el.getClientRects().length > 0)
+    .map(el => [el, Array.from(el.childNodes)
+        .filter(node => node.nodeType === Node.TEXT_NODE)
+        .map(node => node.textContent)
+        .join('')
+        .replace(/\s+/g, ' ')
+        .trim()])
+    .filter(([, text]) => text.length > 0)"#;
+
+/// Playwright selector for visible text. Like Android and iOS, `exact` matches the
+/// whole text case-sensitively (`:text-is`); otherwise a case-insensitive substring
+/// matches too (`:text`), in DOM order.
+fn text_selector(text: &str, index: i32, exact: bool) -> String {
+    match index {
+        0 => text_pseudo(text, exact),
+        i if i > 0 => format!(":nth-match({}, {})", text_pseudo(text, exact), i + 1),
+        i if exact => format!("xpath=(//*[text()=\"{}\"])[{}]", text, xpath_position(i)),
+        // Counting from the end needs XPath, which has no case-insensitive contains
+        i => format!(
+            "xpath=(//*[text()[contains(., \"{}\")]])[{}]",
+            text,
+            xpath_position(i)
+        ),
+    }
+}
+
+/// Text pseudo-class for composing CSS selectors (relative anchors and targets):
+/// `:text-is` matches the whole text when `exact`, `:text` a case-insensitive substring
+fn text_pseudo(text: &str, exact: bool) -> String {
+    if exact {
+        format!(":text-is(\"{}\")", text)
+    } else {
+        format!(":text(\"{}\")", text)
+    }
+}
+
+/// 1-based XPath position for a selector index; negative indices count from `last()`
+fn xpath_position(index: i32) -> String {
+    match index {
+        i if i >= 0 => (i + 1).to_string(),
+        -1 => "last()".to_string(),
+        i => format!("last()-{}", -i - 1),
+    }
+}
+
 /// Map common element type aliases to HTML tags
 fn map_web_type(t: &str) -> String {
     match t.to_lowercase().as_str() {
@@ -1652,13 +2058,204 @@ fn map_web_type(t: &str) -> String {
     }
 }
 
-/// Launch a new Chromium browser with optional remote debugging support
-async fn launch_chromium_browser(
+/// Attach to a running Chromium over CDP, honouring the configured slowMo
+async fn connect_chromium_over_cdp(
     chromium: &playwright::api::BrowserType,
+    endpoint: &str,
     config: &WebDriverConfig,
 ) -> Result<playwright::api::Browser> {
+    let mut builder = chromium.connect_over_cdp_builder(endpoint);
+    if let Some(ms) = config.slow_mo_ms {
+        builder = builder.slowmo(ms as f64);
+    }
+    Ok(builder.connect_over_cdp().await?)
+}
+
+/// Browser attached for `connectUrl`
+struct RemoteBrowser {
+    browser: playwright::api::Browser,
+    /// HTTP endpoint for raw CDP (`/json/list`); None behind a Selenium Grid
+    cdp_endpoint: Option<String>,
+    /// WebDriver session url to delete when the run ends (Selenium Grid only)
+    webdriver_session: Option<String>,
+}
+
+/// Attach to a remote browser: a Chrome started with `--remote-debugging-port`, a
+/// cloud provider's CDP endpoint, or a Chrome session started on a Selenium Grid
+async fn connect_remote_browser(
+    playwright: &Playwright,
+    url: &str,
+    config: &WebDriverConfig,
+) -> Result<RemoteBrowser> {
+    if !matches!(config.browser_type, BrowserType::Chromium) {
+        anyhow::bail!(
+            "connectUrl attaches over the Chrome DevTools Protocol, which only Chromium-based browsers support (browser: {:?})",
+            config.browser_type
+        );
+    }
+    check_remote_url(url)?;
+    // An http url is either CDP (serves the browser's ws url at /json/version) or a
+    // WebDriver hub (answers /status)
+    let mut cdp_url = url.to_string();
+    let mut webdriver_session = None;
+    if let Some(endpoint) = cdp_http_endpoint(url).filter(|_| url.starts_with("http")) {
+        let version = format!("{}/json/version", endpoint);
+        let is_cdp = match reqwest::get(&version).await {
+            Ok(response) => response
+                .json::<serde_json::Value>()
+                .await
+                .is_ok_and(|v| v.get("webSocketDebuggerUrl").is_some()),
+            Err(_) => false,
+        };
+        if !is_cdp {
+            if !is_webdriver_hub(url).await {
+                anyhow::bail!(
+                    "{} serves neither /json/version nor a WebDriver /status. {}",
+                    url,
+                    REMOTE_URL_HINT
+                );
+            }
+            println!("{} Starting a Chrome session on {}", "🔌".blue(), url);
+            let (session, se_cdp) = start_webdriver_session(url, config).await?;
+            cdp_url = se_cdp;
+            webdriver_session = Some(session);
+        }
+    }
+    println!(
+        "{} Connecting to remote browser at: {}",
+        "🔌".blue(),
+        cdp_url
+    );
+    let browser = match connect_chromium_over_cdp(&playwright.chromium(), &cdp_url, config).await {
+        Ok(browser) => browser,
+        Err(e) => {
+            if let Some(session) = &webdriver_session {
+                let _ = end_webdriver_session(session).await;
+            }
+            return Err(e).with_context(|| {
+                format!(
+                    "Failed to connect to remote browser at {}. {}",
+                    cdp_url, REMOTE_URL_HINT
+                )
+            });
+        }
+    };
+    println!("{} Connected to remote browser", "✅".green());
+    Ok(RemoteBrowser {
+        browser,
+        // The Grid only proxies the session's websocket, not /json/list
+        cdp_endpoint: webdriver_session
+            .is_none()
+            .then(|| cdp_http_endpoint(url))
+            .flatten(),
+        webdriver_session,
+    })
+}
+
+/// playwright-rust has no `browserType.connect`, so Playwright servers can't be used
+const REMOTE_URL_HINT: &str = "connectUrl must be a Chrome DevTools Protocol endpoint (http://host:9222 or ws://host/devtools/browser/<id>) or a Selenium Grid 4 url (http://grid:4444) with a Chrome node; Playwright server (run-server/launchServer) urls are not supported";
+
+/// Reject urls that can't be a CDP endpoint or WebDriver hub
+fn check_remote_url(url: &str) -> Result<()> {
+    let scheme = url.split_once("://").map(|(scheme, _)| scheme);
+    if !matches!(scheme, Some("http" | "https" | "ws" | "wss")) {
+        anyhow::bail!("Unsupported connectUrl '{}': {}", url, REMOTE_URL_HINT);
+    }
+    Ok(())
+}
+
+/// Whether `url` answers the WebDriver `/status` endpoint (Selenium Grid, `/wd/hub`)
+async fn is_webdriver_hub(url: &str) -> bool {
+    let status = format!("{}/status", url.trim_end_matches('/'));
+    match reqwest::get(&status).await {
+        Ok(response) => response
+            .json::<serde_json::Value>()
+            .await
+            .is_ok_and(|v| v["value"].get("ready").is_some()),
+        Err(_) => false,
+    }
+}
+
+/// Open a Chrome session on a WebDriver hub; returns the session url and the CDP
+/// websocket Selenium Grid 4 proxies to that browser (`se:cdp`)
+async fn start_webdriver_session(hub: &str, config: &WebDriverConfig) -> Result<(String, String)> {
+    let hub = hub.trim_end_matches('/');
+    let args: Vec<&str> = if config.headless {
+        vec!["--headless=new"]
+    } else {
+        Vec::new()
+    };
+    let body = serde_json::json!({
+        "capabilities": {
+            "alwaysMatch": {
+                "browserName": "chrome",
+                "goog:chromeOptions": { "args": args }
+            }
+        }
+    });
+    let response: serde_json::Value = reqwest::Client::new()
+        .post(format!("{}/session", hub))
+        .json(&body)
+        .send()
+        .await
+        .with_context(|| format!("Failed to start a WebDriver session on {}", hub))?
+        .json()
+        .await
+        .context("WebDriver hub returned an invalid new session response")?;
+    parse_webdriver_session(hub, &response)
+}
+
+/// Session url and `se:cdp` websocket from a WebDriver new session response
+fn parse_webdriver_session(hub: &str, response: &serde_json::Value) -> Result<(String, String)> {
+    let value = &response["value"];
+    if let Some(message) = value["message"].as_str() {
+        anyhow::bail!("WebDriver hub refused the session: {}", message);
+    }
+    let id = value["sessionId"]
+        .as_str()
+        .context("WebDriver hub returned no sessionId")?;
+    let session = format!("{}/session/{}", hub, id);
+    let Some(cdp) = value["capabilities"]["se:cdp"].as_str() else {
+        anyhow::bail!(
+            "WebDriver hub returned no se:cdp endpoint; connectUrl needs Selenium Grid 4 with a Chromium node"
+        );
+    };
+    Ok((session, cdp.to_string()))
+}
+
+/// Quit the browser of a WebDriver session
+async fn end_webdriver_session(session: &str) -> Result<()> {
+    reqwest::Client::new()
+        .delete(session)
+        .send()
+        .await
+        .with_context(|| format!("Failed to end WebDriver session {}", session))?;
+    Ok(())
+}
+
+/// HTTP endpoint serving `/json/list` for a CDP url; ws urls map to their host
+fn cdp_http_endpoint(url: &str) -> Option<String> {
+    let (scheme, rest) = url.split_once("://")?;
+    let scheme = match scheme {
+        "http" | "ws" => "http",
+        "https" | "wss" => "https",
+        _ => return None,
+    };
+    let host = rest.split(['/', '?']).next().filter(|h| !h.is_empty())?;
+    Some(format!("{}://{}", scheme, host))
+}
+
+/// Launch a new Chromium browser; the CDP endpoint is returned only when a remote
+/// debugging port was opened (persistent mode, network emulation or tracing)
+async fn launch_chromium_browser(
+    chromium: &playwright::api::BrowserType,
+    config: &WebDriverConfig,
+) -> Result<(playwright::api::Browser, Option<String>)> {
     let mut launcher = chromium.launcher();
     launcher = launcher.headless(config.headless);
+    if let Some(ms) = config.slow_mo_ms {
+        launcher = launcher.slowmo(ms as f64);
+    }
 
     let env_path = std::env::var("PLAYWRIGHT_CHROMIUM_EXECUTABLE_PATH")
         .ok()
@@ -1703,18 +2300,45 @@ async fn launch_chromium_browser(
     .map(|s| s.to_string())
     .collect();
 
-    // Enable remote debugging for browser persistence
-    if !config.close_when_finish {
-        args.push("--remote-debugging-port=9222".to_string());
+    // Enable remote debugging for browser persistence; network emulation and tracing get
+    // a free port instead. Otherwise the browser exposes no debugging port at all.
+    let debug_port = if !config.close_when_finish {
         println!(
             "{} Browser will stay open for reuse (closeWhenFinish: false)",
             "📌".cyan()
         );
+        Some(9222)
+    } else if config.network_emulation || config.trace {
+        Some(
+            std::net::TcpListener::bind("127.0.0.1:0")?
+                .local_addr()?
+                .port(),
+        )
+    } else {
+        None
+    };
+    if let Some(port) = debug_port {
+        args.push(format!("--remote-debugging-port={}", port));
     }
 
     launcher = launcher.args(&args);
 
-    Ok(launcher.launch().await?)
+    let browser = launcher
+        .launch()
+        .await
+        .with_context(|| browser_launch_hint("chromium"))?;
+    Ok((
+        browser,
+        debug_port.map(|port| format!("http://127.0.0.1:{}", port)),
+    ))
+}
+
+/// Launch failures usually mean the browser was never downloaded
+fn browser_launch_hint(browser: &str) -> String {
+    format!(
+        "Failed to launch {0}. If it is not installed, run `lumi-tester system install --platform web --browser {0}`",
+        browser
+    )
 }
 
 fn find_system_browser() -> Option<std::path::PathBuf> {
@@ -1915,3 +2539,76 @@ fn find_chrome_explicitly() -> Option<std::path::PathBuf> {
 
     None
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_text_selector_honours_exact() {
+        assert_eq!(text_selector("Log", 0, true), ":text-is(\"Log\")");
+        assert_eq!(text_selector("Log", 0, false), ":text(\"Log\")");
+        assert_eq!(
+            text_selector("Log", 2, false),
+            ":nth-match(:text(\"Log\"), 3)"
+        );
+        assert_eq!(
+            text_selector("Log", -1, true),
+            "xpath=(//*[text()=\"Log\"])[last()]"
+        );
+        assert_eq!(
+            text_selector("Log", -1, false),
+            "xpath=(//*[text()[contains(., \"Log\")]])[last()]"
+        );
+        assert_eq!(text_pseudo("Log", false), ":text(\"Log\")");
+    }
+
+    #[test]
+    fn test_text_selector_substring_only_without_exact() {
+        // "Log" on a page showing "Login": Playwright's `:text` matches substrings,
+        // `:text-is` and `text()=` only the whole text
+        for index in [0, 1, -1] {
+            let loose = text_selector("Log", index, false);
+            let strict = text_selector("Log", index, true);
+            assert!(
+                loose.contains(":text(\"Log\")") || loose.contains("contains(., \"Log\")"),
+                "{}",
+                loose
+            );
+            assert!(
+                strict.contains(":text-is(\"Log\")") || strict.contains("text()=\"Log\""),
+                "{}",
+                strict
+            );
+        }
+    }
+
+    #[test]
+    fn test_connect_url_scheme() {
+        assert!(check_remote_url("http://localhost:9222").is_ok());
+        assert!(check_remote_url("ws://grid:4444/session/abc/se/cdp").is_ok());
+        assert!(check_remote_url("http://grid:4444/wd/hub").is_ok());
+        assert!(check_remote_url("localhost:9222").is_err());
+    }
+
+    #[test]
+    fn test_parse_webdriver_session() {
+        let response = serde_json::json!({"value": {"sessionId": "abc", "capabilities": {
+            "browserName": "chrome",
+            "se:cdp": "ws://grid:4444/session/abc/se/cdp"
+        }}});
+        let (session, cdp) = parse_webdriver_session("http://grid:4444", &response).unwrap();
+        assert_eq!(session, "http://grid:4444/session/abc");
+        assert_eq!(cdp, "ws://grid:4444/session/abc/se/cdp");
+
+        let refused = serde_json::json!({"value": {"error": "session not created",
+            "message": "No nodes support the capabilities"}});
+        let message = parse_webdriver_session("http://grid:4444", &refused)
+            .unwrap_err()
+            .to_string();
+        assert!(message.contains("No nodes support"), "{}", message);
+
+        let no_cdp = serde_json::json!({"value": {"sessionId": "abc", "capabilities": {}}});
+        assert!(parse_webdriver_session("http://grid:4444", &no_cdp).is_err());
+    }
+}
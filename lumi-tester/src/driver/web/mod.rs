@@ -1,3 +1,4 @@
+mod cdp;
 mod driver;
 
 pub use driver::{BrowserType, WebDriver, WebDriverConfig};
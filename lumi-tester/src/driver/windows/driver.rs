@@ -8,9 +8,26 @@ use std::process::{Command, Stdio};
 use std::sync::Mutex;
 use std::time::{Duration, Instant};
 
-use crate::driver::traits::{PlatformDriver, Selector, SwipeDirection};
+use crate::driver::traits::{
+    ensure_selector_supported, NthMatch, PlatformDriver, Selector, SwipeDirection,
+};
 use crate::parser::types::{DesktopClearMode, DesktopState};
 
+/// Selector kinds matched against the UI Automation tree; `css`/`xpath` compare the
+/// automation identifier
+const SUPPORTED_SELECTORS: &[&str] = &[
+    "text",
+    "regex",
+    "id",
+    "description",
+    "placeholder",
+    "role",
+    "elementType",
+    "css",
+    "xpath",
+    "point",
+];
+
 /// Native Windows driver MVP backed by PowerShell and built-in Win32/.NET APIs.
 pub struct WindowsDriver {
     device_name: Option<String>,
@@ -176,6 +193,7 @@ Add-Type -AssemblyName System.Windows.Forms
     }
 
     fn find_element(&self, selector: &Selector) -> Result<Option<WindowsUiElement>> {
+        ensure_selector_supported("Windows", selector, SUPPORTED_SELECTORS)?;
         let mut matched = Vec::new();
         for element in self.ui_elements()? {
             if element_matches_selector(&element, selector)? {
@@ -184,7 +202,7 @@ Add-Type -AssemblyName System.Windows.Forms
         }
 
         let index = selector_index(selector).unwrap_or(0);
-        Ok(matched.into_iter().nth(index))
+        Ok(matched.into_iter().nth_match(index))
     }
 
     fn current_desktop_state(&self) -> Result<Option<DesktopStateRuntime>> {
@@ -865,8 +883,8 @@ fn validate_windows_registry_key(key: &str) -> Result<()> {
     Ok(())
 }
 
-fn selector_index(selector: &Selector) -> Option<usize> {
-    match selector {
+fn selector_index(selector: &Selector) -> Option<i32> {
+    match selector.unwrap_on_screen() {
         Selector::Text(_, index, _)
         | Selector::TextRegex(_, index)
         | Selector::Id(_, index)
@@ -886,7 +904,7 @@ fn element_matches_selector(element: &WindowsUiElement, selector: &Selector) ->
         return Ok(false);
     }
 
-    match selector {
+    match selector.unwrap_on_screen() {
         Selector::Text(text, _, exact) => {
             if *exact {
                 Ok(element.name == *text)
@@ -0,0 +1,109 @@
+//! Structured errors for embedders (Studio, library users)
+//!
+//! Driver and executor paths return `anyhow::Result` as before; the errors below
+//! travel inside it, so callers match on the category with [`TesterError::of`]
+//! instead of parsing messages.
+
+use std::path::PathBuf;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TesterError {
+    /// No element matched the selector
+    ElementNotFound { selector: String },
+    /// An operation ran past its time budget
+    Timeout { operation: String, timeout_ms: u64 },
+    /// No device, simulator or browser to drive
+    DriverUnavailable { platform: String, reason: String },
+    /// A test file or command could not be parsed
+    Parse {
+        path: Option<PathBuf>,
+        message: String,
+    },
+    /// An assertion did not hold
+    AssertionFailed { message: String },
+}
+
+impl TesterError {
+    /// The first `TesterError` in the error chain, context layers included
+    pub fn of(error: &anyhow::Error) -> Option<&Self> {
+        error
+            .downcast_ref::<Self>()
+            .or_else(|| error.chain().find_map(|e| e.downcast_ref::<Self>()))
+    }
+
+    /// Stable category name for reports and integrations
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::ElementNotFound { .. } => "elementNotFound",
+            Self::Timeout { .. } => "timeout",
+            Self::DriverUnavailable { .. } => "driverUnavailable",
+            Self::Parse { .. } => "parseError",
+            Self::AssertionFailed { .. } => "assertionFailed",
+        }
+    }
+
+    pub fn element_not_found(selector: impl std::fmt::Debug) -> Self {
+        Self::ElementNotFound {
+            selector: format!("{:?}", selector),
+        }
+    }
+
+    pub fn driver_unavailable(platform: &str, reason: impl Into<String>) -> Self {
+        Self::DriverUnavailable {
+            platform: platform.to_string(),
+            reason: reason.into(),
+        }
+    }
+
+    pub fn assertion(message: impl Into<String>) -> Self {
+        Self::AssertionFailed {
+            message: message.into(),
+        }
+    }
+}
+
+impl std::fmt::Display for TesterError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::ElementNotFound { selector } => write!(f, "Element not found: {}", selector),
+            Self::Timeout {
+                operation,
+                timeout_ms,
+            } => write!(f, "{} timed out after {}ms", operation, timeout_ms),
+            Self::DriverUnavailable { reason, .. } => write!(f, "{}", reason),
+            Self::Parse {
+                path: Some(path),
+                message,
+            } => write!(f, "Failed to parse {}: {}", path.display(), message),
+            Self::Parse {
+                path: None,
+                message,
+            } => write!(f, "{}", message),
+            Self::AssertionFailed { message } => write!(f, "{}", message),
+        }
+    }
+}
+
+impl std::error::Error for TesterError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Context;
+
+    #[test]
+    fn test_category_survives_anyhow_context() {
+        let result: anyhow::Result<()> = Err(TesterError::element_not_found("login").into());
+        let error = result.context("tap failed").unwrap_err();
+        let found = TesterError::of(&error).unwrap();
+        assert_eq!(found.kind(), "elementNotFound");
+        assert_eq!(found.to_string(), "Element not found: \"login\"");
+
+        let timeout = anyhow::Error::from(TesterError::Timeout {
+            operation: "Flow".to_string(),
+            timeout_ms: 500,
+        });
+        assert_eq!(timeout.to_string(), "Flow timed out after 500ms");
+        assert!(TesterError::of(&anyhow::anyhow!("plain")).is_none());
+    }
+}
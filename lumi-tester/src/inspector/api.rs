@@ -13,6 +13,7 @@ use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 
 use super::screen_capture::{self, ScreenCapture};
+use crate::driver::android::driver::UI_CACHE_TTL_MS;
 use crate::driver::android::uiautomator;
 use crate::recorder::selector_scorer::SelectorScorer;
 use crate::recorder::yaml_generator::YamlGenerator;
@@ -29,6 +30,21 @@ pub struct AppState {
 /// Cached parsed hierarchy
 pub struct CachedHierarchy {
     pub elements: Vec<uiautomator::UiElement>,
+    pub captured_at: std::time::Instant,
+}
+
+impl CachedHierarchy {
+    pub fn new(elements: Vec<uiautomator::UiElement>) -> Self {
+        Self {
+            elements,
+            captured_at: std::time::Instant::now(),
+        }
+    }
+
+    /// Whether the cache is still within the driver's UI cache TTL
+    pub fn is_fresh(&self) -> bool {
+        self.captured_at.elapsed() < std::time::Duration::from_millis(UI_CACHE_TTL_MS)
+    }
 }
 
 /// Response for screenshot endpoint
@@ -120,6 +136,7 @@ pub fn api_router() -> Router<Arc<AppState>> {
         .route("/api/screenshot", get(get_screenshot))
         .route("/api/element-at", get(get_element_at))
         .route("/api/hierarchy", get(get_hierarchy))
+        .route("/api/element-tree", get(get_element_tree))
         .route("/api/packages", get(get_packages))
         .route("/api/command", post(manage_command))
         .route("/api/append-command", post(append_command))
@@ -161,7 +178,7 @@ async fn get_screenshot(
     if let Ok(hierarchy_xml) = hierarchy_result {
         if let Ok(elements) = uiautomator::parse_hierarchy(&hierarchy_xml) {
             let mut cache = state.cached_hierarchy.lock().unwrap();
-            *cache = Some(CachedHierarchy { elements });
+            *cache = Some(CachedHierarchy::new(elements));
         }
     }
 
@@ -347,6 +364,105 @@ async fn get_hierarchy(State(state): State<Arc<AppState>>) -> impl IntoResponse
     Json(serde_json::json!({ "elements": infos }))
 }
 
+/// Query params for element-tree endpoint
+#[derive(Deserialize)]
+pub struct ElementTreeQuery {
+    pub refresh: Option<bool>,
+}
+
+/// Element entry for the overlay tree
+#[derive(Serialize)]
+pub struct ElementTreeNode {
+    pub index: usize,
+    pub class: String,
+    pub text: String,
+    pub id: String,
+    pub description: String,
+    pub clickable: bool,
+    pub scrollable: bool,
+    pub bounds: BoundsInfo,
+}
+
+/// Response for element-tree endpoint
+#[derive(Serialize)]
+pub struct ElementTreeResponse {
+    pub elements: Vec<ElementTreeNode>,
+    pub cached: bool,
+    pub age_ms: u64,
+}
+
+/// GET /api/element-tree?refresh=true - Parsed UI hierarchy for drawing element overlays
+///
+/// Serves the cached hierarchy while it is younger than the driver's UI cache TTL,
+/// otherwise (or when `refresh` is set) dumps a fresh one and updates the cache.
+async fn get_element_tree(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<ElementTreeQuery>,
+) -> impl IntoResponse {
+    let refresh = params.refresh.unwrap_or(false);
+
+    let cached = if refresh {
+        None
+    } else {
+        let cache = state.cached_hierarchy.lock().unwrap();
+        cache.as_ref().filter(|c| c.is_fresh()).map(|c| {
+            (
+                c.elements.clone(),
+                c.captured_at.elapsed().as_millis() as u64,
+            )
+        })
+    };
+
+    let (elements, cached, age_ms) = match cached {
+        Some((elements, age_ms)) => (elements, true, age_ms),
+        None => {
+            let xml =
+                match screen_capture::get_hierarchy_android(state.device_serial.as_deref()).await {
+                    Ok(xml) => xml,
+                    Err(e) => {
+                        return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response()
+                    }
+                };
+            let elements = match uiautomator::parse_hierarchy(&xml) {
+                Ok(e) => e,
+                Err(e) => {
+                    return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response()
+                }
+            };
+            let mut cache = state.cached_hierarchy.lock().unwrap();
+            *cache = Some(CachedHierarchy::new(elements.clone()));
+            (elements, false, 0)
+        }
+    };
+
+    let nodes = elements
+        .iter()
+        .enumerate()
+        .map(|(index, e)| ElementTreeNode {
+            index,
+            class: e.class.clone(),
+            text: e.text.clone(),
+            id: e.resource_id.clone(),
+            description: e.content_desc.clone(),
+            clickable: e.clickable,
+            scrollable: e.scrollable,
+            bounds: BoundsInfo {
+                left: e.bounds.left,
+                top: e.bounds.top,
+                right: e.bounds.right,
+                bottom: e.bounds.bottom,
+            },
+        })
+        .collect();
+
+    Json(ElementTreeResponse {
+        elements: nodes,
+        cached,
+        age_ms,
+    })
+    .into_response()
+}
+
 /// Request for managing commands (insert/delete)
 #[derive(Deserialize)]
 struct ManageCommandRequest {
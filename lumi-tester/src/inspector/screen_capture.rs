@@ -51,6 +51,14 @@ impl ScreenCapture {
         }
     }
 
+    /// Capture a JPEG frame for streaming, optionally downscaled
+    ///
+    /// `quality` is the JPEG quality (1-100), `scale` the resize factor (0.1-1.0).
+    pub async fn capture_jpeg(&self, quality: u8, scale: f32) -> Result<Vec<u8>> {
+        let png_bytes = self.capture_bytes().await?;
+        encode_jpeg_frame(&png_bytes, quality, scale)
+    }
+
     /// Get screen dimensions
     pub fn dimensions(&self) -> (u32, u32) {
         (self.screen_width, self.screen_height)
@@ -153,18 +161,29 @@ impl ScreenCapture {
 
 /// Convert PNG bytes to JPEG for smaller file size
 fn convert_png_to_webp(png_bytes: &[u8]) -> Result<Vec<u8>> {
+    // Quality 70 is a good balance between size and quality
+    encode_jpeg_frame(png_bytes, 70, 1.0)
+}
+
+/// Re-encode PNG bytes as a JPEG frame with the given quality and scale
+pub fn encode_jpeg_frame(png_bytes: &[u8], quality: u8, scale: f32) -> Result<Vec<u8>> {
     use image::codecs::jpeg::JpegEncoder;
     use image::io::Reader as ImageReader;
     use std::io::Cursor;
 
-    // Load PNG
-    let img = ImageReader::new(Cursor::new(png_bytes))
+    let mut img = ImageReader::new(Cursor::new(png_bytes))
         .with_guessed_format()?
         .decode()?;
 
-    // Encode as JPEG with quality 70 (good balance between size and quality)
+    let scale = scale.clamp(0.1, 1.0);
+    if scale < 1.0 {
+        let width = ((img.width() as f32 * scale) as u32).max(1);
+        let height = ((img.height() as f32 * scale) as u32).max(1);
+        img = img.resize(width, height, image::imageops::FilterType::Triangle);
+    }
+
     let mut jpeg_bytes = Vec::new();
-    let mut encoder = JpegEncoder::new_with_quality(&mut jpeg_bytes, 70);
+    let mut encoder = JpegEncoder::new_with_quality(&mut jpeg_bytes, quality.clamp(1, 100));
     encoder.encode_image(&img)?;
 
     Ok(jpeg_bytes)
@@ -4,12 +4,18 @@
 
 use anyhow::Result;
 use axum::{
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        Query, State,
+    },
     response::{Html, IntoResponse},
     routing::get,
     Router,
 };
+use serde::Deserialize;
 use std::net::SocketAddr;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tower_http::cors::CorsLayer;
 
 use super::api::{self, AppState};
@@ -61,6 +67,7 @@ impl InspectorServer {
         // Build router
         let app = Router::new()
             .route("/", get(serve_index))
+            .route("/ws/screen", get(stream_screen))
             .merge(api::api_router())
             .layer(CorsLayer::permissive())
             .with_state(state);
@@ -97,3 +104,79 @@ async fn serve_index() -> impl IntoResponse {
 
     Html(html)
 }
+
+/// Query params for the screen stream
+#[derive(Deserialize)]
+pub struct StreamQuery {
+    /// JPEG quality (1-100, default 70)
+    pub quality: Option<u8>,
+    /// Resize factor (0.1-1.0, default 1.0)
+    pub scale: Option<f32>,
+    /// Upper bound on frames per second (default 10)
+    pub max_fps: Option<u32>,
+}
+
+/// GET /ws/screen?quality=50&scale=0.5 - Push JPEG frames over a WebSocket
+async fn stream_screen(
+    ws: WebSocketUpgrade,
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<StreamQuery>,
+) -> impl IntoResponse {
+    ws.on_upgrade(move |socket| push_frames(socket, state, params))
+}
+
+/// Send frames until the client disconnects or sends a Close frame
+///
+/// The delay between frames adapts to how long the device takes to capture:
+/// a slow device gets more breathing room instead of a queue of stale frames.
+async fn push_frames(mut socket: WebSocket, state: Arc<AppState>, params: StreamQuery) {
+    let quality = params.quality.unwrap_or(70);
+    let scale = params.scale.unwrap_or(1.0);
+    let min_interval =
+        Duration::from_millis(1000 / params.max_fps.unwrap_or(10).clamp(1, 60) as u64);
+
+    // Smoothed capture time, used to back off on slow devices
+    let mut avg_capture = Duration::ZERO;
+
+    loop {
+        let started = Instant::now();
+        // Keep reading while capturing so Ping/Close frames are handled promptly
+        let capture = state.screen_capture.capture_jpeg(quality, scale);
+        tokio::pin!(capture);
+        let frame = loop {
+            tokio::select! {
+                frame = &mut capture => break frame,
+                incoming = socket.recv() => if client_gone(&incoming) { return; },
+            }
+        };
+        let elapsed = started.elapsed();
+        avg_capture = if avg_capture.is_zero() {
+            elapsed
+        } else {
+            (avg_capture * 4 + elapsed) / 5
+        };
+
+        let message = match frame {
+            Ok(bytes) => Message::Binary(bytes),
+            Err(e) => Message::Text(serde_json::json!({ "error": e.to_string() }).to_string()),
+        };
+        if socket.send(message).await.is_err() {
+            break;
+        }
+
+        let delay = min_interval.saturating_sub(elapsed).max(avg_capture / 4);
+        let sleep = tokio::time::sleep(delay);
+        tokio::pin!(sleep);
+        loop {
+            tokio::select! {
+                _ = &mut sleep => break,
+                incoming = socket.recv() => if client_gone(&incoming) { return; },
+            }
+        }
+    }
+}
+
+/// Whether a received message means the client closed or dropped the connection
+fn client_gone(incoming: &Option<Result<Message, axum::Error>>) -> bool {
+    matches!(incoming, None | Some(Err(_)) | Some(Ok(Message::Close(_))))
+}
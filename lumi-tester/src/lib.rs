@@ -1,7 +1,11 @@
 pub mod commands;
+pub mod config;
 pub mod driver;
+pub mod error;
 pub mod inspector;
 pub mod parser;
+pub mod plugin;
+pub mod plugins;
 pub mod recorder;
 pub mod report;
 pub mod runner;
@@ -9,5 +13,6 @@ pub mod utils;
 
 // Re-export common items
 pub use driver::list_devices;
+pub use error::TesterError;
 pub use report::generate_report;
 pub use runner::run_tests;
@@ -1,11 +1,11 @@
-use clap::{Parser, Subcommand};
+use clap::{Args, Parser, Subcommand};
 use colored::Colorize;
 use serde::Serialize;
 use std::path::PathBuf;
 
 mod ai;
 
-use lumi_tester::{driver, recorder, report, runner, utils};
+use lumi_tester::{config, driver, plugins, recorder, report, runner, utils};
 
 #[derive(Parser)]
 #[command(name = "lumi-tester")]
@@ -15,64 +15,16 @@ use lumi_tester::{driver, recorder, report, runner, utils};
 struct Cli {
     #[command(subcommand)]
     command: Commands,
+
+    /// Disable colored output (NO_COLOR is also honoured)
+    #[arg(long, global = true, default_value = "false")]
+    no_color: bool,
 }
 
 #[derive(Subcommand)]
 enum Commands {
     /// Run test file(s) or directory
-    Run {
-        /// Path to test file or directory
-        path: PathBuf,
-
-        /// Target platform (android, android_auto, ios, web, macos, windows).
-        /// Parsed from file if not provided.
-        #[arg(short, long)]
-        platform: Option<String>,
-
-        /// Device serial(s) (Android) or UDID(s) (iOS). Can be specified multiple times.
-        #[arg(short, long)]
-        device: Vec<String>,
-
-        /// Run tests in parallel across multiple devices
-        #[arg(long, default_value = "false")]
-        parallel: bool,
-
-        /// Output directory for reports and artifacts
-        #[arg(short, long, default_value = "./output")]
-        output: PathBuf,
-
-        /// Continue on failure
-        #[arg(long, default_value = "false")]
-        continue_on_failure: bool,
-
-        /// Enable video recording during test execution
-        #[arg(long, short = 'r', default_value = "false")]
-        record: bool,
-
-        /// Enable screenshot capture on failures
-        #[arg(long, short = 's', default_value = "false")]
-        snapshot: bool,
-
-        /// Generate reports (JSON, HTML, JUnit)
-        #[arg(long, default_value = "false")]
-        report: bool,
-
-        /// Write machine-readable execution events to output/events.jsonl
-        #[arg(long, default_value = "false")]
-        events_jsonl: bool,
-
-        /// Filter tests by tags (comma-separated)
-        #[arg(short, long, value_delimiter = ',')]
-        tags: Option<Vec<String>>,
-
-        /// Run only a specific command by index (0-based)
-        #[arg(long)]
-        command_index: Option<usize>,
-
-        /// Run only a specific command by name (first match)
-        #[arg(long)]
-        command_name: Option<String>,
-    },
+    Run(Box<RunArgs>),
 
     /// List connected devices
     Devices {
@@ -161,7 +113,11 @@ enum Commands {
         #[arg(short, long)]
         output: PathBuf,
 
-        /// Device serial (Android)
+        /// Platform to record on (android, ios)
+        #[arg(short, long, default_value = "android")]
+        platform: String,
+
+        /// Device serial (Android) or UDID (iOS)
         #[arg(short, long)]
         device: Option<String>,
 
@@ -180,6 +136,26 @@ enum Commands {
         /// Include selector alternatives as comments
         #[arg(long, default_value = "true")]
         include_comments: bool,
+
+        /// Insert into an existing output file (at `# RECORD_HERE` or before its last command)
+        #[arg(long, alias = "merge")]
+        append: bool,
+
+        /// Prefer resource IDs / test-ids over visible text when picking selectors
+        #[arg(long, conflicts_with = "prefer_text")]
+        prefer_id: bool,
+
+        /// Prefer visible text over resource IDs when picking selectors
+        #[arg(long)]
+        prefer_text: bool,
+
+        /// Also record the device's location trace to this GPX file (Android), for `mockLocation`
+        #[arg(long, value_name = "FILE")]
+        gps: Option<PathBuf>,
+
+        /// How often to sample the location for --gps (ms)
+        #[arg(long, default_value_t = 1000, requires = "gps")]
+        gps_interval: u64,
     },
 
     /// Start web-based inspector for visual test creation
@@ -202,6 +178,160 @@ enum Commands {
     },
 }
 
+/// Options of `lumi-tester run`, boxed to keep `Commands` small
+#[derive(Args)]
+struct RunArgs {
+    /// Path to test file or directory
+    path: PathBuf,
+
+    /// Target platform (android, android_auto, ios, web, macos, windows).
+    /// Parsed from file if not provided.
+    #[arg(short, long)]
+    platform: Option<String>,
+
+    /// Device serial(s) (Android) or UDID(s)/simulator name(s) (iOS). Can be specified multiple times.
+    #[arg(short, long)]
+    device: Vec<String>,
+
+    /// Run tests in parallel across multiple devices (with --report, device reports are merged into one)
+    #[arg(long, default_value = "false")]
+    parallel: bool,
+
+    /// Max concurrent test sessions; above the browser count a web browser hosts several (implies --parallel)
+    #[arg(long, visible_alias = "parallel-workers", value_name = "N")]
+    workers: Option<usize>,
+
+    /// File order: alphabetical (default), mtime (newest first) or random[:seed]
+    #[arg(long, default_value = "alphabetical", value_name = "ORDER")]
+    order: runner::FileOrder,
+
+    /// Output directory for reports and artifacts [default: ./output]
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+
+    /// Write this run under <output>/<timestamp>-<id>/ so earlier runs are kept
+    #[arg(long, default_value = "false", conflicts_with = "resume")]
+    timestamped_output: bool,
+
+    /// Project config (default: nearest lumi.toml or .lumirc above the test path)
+    #[arg(long, value_name = "FILE")]
+    config: Option<PathBuf>,
+
+    /// Continue on failure
+    #[arg(long, default_value = "false")]
+    continue_on_failure: bool,
+
+    /// Stop the whole suite at the first failing file (default: run remaining files)
+    #[arg(long, default_value = "false")]
+    fail_fast: bool,
+
+    /// Enable video recording during test execution
+    #[arg(long, short = 'r', default_value = "false")]
+    record: bool,
+
+    /// Record like --record but keep videos only for flows that fail
+    #[arg(long, default_value = "false")]
+    record_on_failure: bool,
+
+    /// Recording bitrate, e.g. 4000000, 4M or 800K (Android)
+    #[arg(long, value_name = "BPS")]
+    bitrate: Option<String>,
+
+    /// Recording size as WIDTHxHEIGHT, e.g. 1280x720 (Android)
+    #[arg(long, value_name = "WxH")]
+    size: Option<String>,
+
+    /// Stop each recording after this many seconds, max 180 (Android)
+    #[arg(long, value_name = "SECS")]
+    time_limit: Option<u32>,
+
+    /// Enable screenshot capture on failures
+    #[arg(long, short = 's', default_value = "false")]
+    snapshot: bool,
+
+    /// Directory with assertScreenshot references (default: screenshots/ next to each flow)
+    #[arg(long, value_name = "DIR")]
+    baselines_dir: Option<PathBuf>,
+
+    /// Generate reports (JSON, HTML, JUnit)
+    #[arg(long, default_value = "false")]
+    report: bool,
+
+    /// Write machine-readable execution events to output/events.jsonl
+    #[arg(long, default_value = "false")]
+    events_jsonl: bool,
+
+    /// Filter tests by tags (comma-separated)
+    #[arg(short, long, value_delimiter = ',')]
+    tags: Option<Vec<String>>,
+
+    /// Run only a specific command by index (0-based)
+    #[arg(long)]
+    command_index: Option<usize>,
+
+    /// Run only a specific command by name (first match)
+    #[arg(long)]
+    command_name: Option<String>,
+
+    /// Shut down iOS simulators that were booted for this run when it finishes
+    #[arg(long, default_value = "false")]
+    shutdown_simulator: bool,
+
+    /// Web: show the browser window (overrides LUMI_HEADLESS)
+    #[arg(long, default_value = "false")]
+    headed: bool,
+
+    /// Web: slow down every browser action by this many milliseconds
+    #[arg(long, value_name = "MS")]
+    slow_mo: Option<u64>,
+
+    /// Web: save a Chrome trace of every flow to the output directory (Chromium)
+    #[arg(long, default_value = "false")]
+    web_trace: bool,
+
+    /// Web: attach to a running browser over CDP (http:// or ws:// endpoint), or start one on a
+    /// Selenium Grid 4 url, instead of launching one
+    #[arg(long, value_name = "URL")]
+    connect_url: Option<String>,
+
+    /// Write a one-line JSON summary to this file when the run ends; `-` prints it
+    /// on stdout and moves console output to stderr
+    #[arg(long, value_name = "FILE")]
+    json_summary: Option<PathBuf>,
+
+    /// Log every adb/idb/CDP call issued by each command, with its duration
+    #[arg(long, default_value = "false")]
+    trace: bool,
+
+    /// Report every command of this type as skipped instead of running it (repeatable, e.g. assertScreenshot)
+    #[arg(long, value_name = "COMMAND", value_delimiter = ',')]
+    skip_command: Option<Vec<String>>,
+
+    /// Skip flows recorded as passed in <output>/progress.json by an interrupted run
+    #[arg(long, default_value = "false")]
+    resume: bool,
+
+    /// Run only flows changed since this git ref, or whose subflows/scripts/data changed
+    #[arg(long, value_name = "GIT_REF")]
+    changed_since: Option<String>,
+
+    /// POST the run summary to this webhook when the run ends (Slack or generic JSON)
+    #[arg(long, value_name = "WEBHOOK_URL")]
+    notify: Option<String>,
+
+    /// Payload for --notify: slack or json (default: slack for hooks.slack.com URLs)
+    #[arg(long, value_name = "FORMAT")]
+    notify_format: Option<String>,
+
+    /// Compact console output: one PASS/FAIL line per flow plus errors
+    #[arg(long, short = 'q', default_value = "false", conflicts_with = "verbose")]
+    quiet: bool,
+
+    /// Also print flow paths and full command errors
+    #[arg(long, short = 'v', default_value = "false")]
+    verbose: bool,
+}
+
 #[derive(Subcommand)]
 enum SystemCommands {
     /// Install required drivers and tools
@@ -209,6 +339,20 @@ enum SystemCommands {
         /// Install all components
         #[arg(long)]
         all: bool,
+
+        /// Scope: android, web or all
+        #[arg(short, long, default_value = "all")]
+        platform: String,
+
+        /// Browsers to download for web tests (chromium, firefox, webkit or all). Defaults to chromium
+        #[arg(long = "browser", value_delimiter = ',')]
+        browsers: Vec<String>,
+    },
+    /// Diagnose drivers, devices, browsers and OCR, printing OK/FIX per item
+    Doctor {
+        /// Scope: android, ios, web or all
+        #[arg(short, long, default_value = "all")]
+        platform: String,
     },
 }
 
@@ -268,62 +412,227 @@ fn main() -> anyhow::Result<()> {
 async fn async_main() -> anyhow::Result<()> {
     env_logger::init();
     let cli = Cli::parse();
+    if cli.no_color {
+        colored::control::set_override(false);
+    }
+    plugins::register_builtin();
 
     match cli.command {
-        Commands::Run {
-            path,
-            platform,
-            device,
-            parallel,
-            output,
-            continue_on_failure,
-            record,
-            snapshot,
-            report,
-            events_jsonl,
-            tags,
-            command_index,
-            command_name,
-        } => {
-            let platform_val = if let Some(p) = platform {
-                normalize_platform(&p)
+        Commands::Run(args) => {
+            let RunArgs {
+                path,
+                platform,
+                device,
+                parallel,
+                workers,
+                order,
+                output,
+                timestamped_output,
+                config,
+                continue_on_failure,
+                fail_fast,
+                record,
+                record_on_failure,
+                snapshot,
+                baselines_dir,
+                report,
+                events_jsonl,
+                tags,
+                command_index,
+                command_name,
+                shutdown_simulator,
+                headed,
+                slow_mo,
+                web_trace,
+                connect_url,
+                bitrate,
+                size,
+                time_limit,
+                quiet,
+                verbose,
+                json_summary,
+                trace,
+                skip_command,
+                notify,
+                notify_format,
+                resume,
+                changed_since,
+            } = *args;
+            // `--json-summary -` owns stdout; console output goes to stderr
+            if json_summary.as_deref() == Some(std::path::Path::new("-")) {
+                utils::console::reserve_stdout()?;
+            }
+            let verbosity = if quiet {
+                runner::ConsoleVerbosity::Quiet
+            } else if verbose {
+                runner::ConsoleVerbosity::Verbose
             } else {
-                detect_platform(&path)?.unwrap_or_else(|| "android".to_string())
+                runner::ConsoleVerbosity::Normal
             };
 
-            println!(
-                "{} Running tests from: {}",
-                "▶".green().bold(),
-                path.display()
-            );
-            println!("  Platform: {}", platform_val.cyan());
-            if !device.is_empty() {
-                println!("  Devices: {}", device.join(", ").cyan());
+            // Project defaults; CLI flags take precedence
+            let project = match &config {
+                Some(file) => Some(config::ProjectConfig::load(file)?),
+                None => config::ProjectConfig::discover(&path)?,
+            };
+            if trace {
+                driver::trace::enable();
+            }
+            let defaults = project.clone().unwrap_or_default();
+            if let Some(adb) = &defaults.adb {
+                utils::binary_resolver::set_configured_path("adb", adb.clone());
             }
-            if parallel {
-                println!("  Parallel: {}", "Enabled".yellow());
+
+            // CLI flags win, then variables the user exported, then lumi.toml
+            let baselines_dir = match baselines_dir {
+                Some(dir) => Some(std::path::absolute(dir)?),
+                None => env_value("LUMI_BASELINES_DIR")
+                    .map(PathBuf::from)
+                    .or(defaults.baselines),
+            };
+            let skip_commands = skip_command
+                .or_else(|| env_value("LUMI_SKIP_COMMANDS").map(|names| comma_list(&names)))
+                .or(defaults.skip_commands.clone())
+                .unwrap_or_default();
+            // Only the list in effect: an overridden lumi.toml entry doesn't matter
+            for name in &skip_commands {
+                if !lumi_tester::parser::types::is_command_kind(name) {
+                    anyhow::bail!(
+                        "Unknown command '{}' in --skip-command/skipCommands (use YAML command names such as tapOn or assertScreenshot)",
+                        name
+                    );
+                }
             }
-            if let Some(ref tags_list) = tags {
-                println!("  Tags: {}", tags_list.join(", ").yellow());
+            if let Some(format) = &notify_format {
+                format.parse::<report::notify::NotifyFormat>()?;
             }
-            println!("  Output: {}", output.display().to_string().cyan());
-            if record {
-                println!("  Recording: {}", "Enabled".green());
+            let notify = match notify
+                .or_else(|| env_value(report::notify::NOTIFY_URL_ENV))
+                .or(defaults.notify)
+            {
+                Some(url) => {
+                    let format = notify_format
+                        .or_else(|| env_value(report::notify::NOTIFY_FORMAT_ENV))
+                        .or(defaults.notify_format);
+                    Some(report::notify::NotifyTarget::new(url, format.as_deref())?)
+                }
+                None => None,
+            };
+            let speed = env_value("LUMI_SPEED").or(defaults.speed);
+            let default_timeout_ms = env_value("LUMI_DEFAULT_TIMEOUT")
+                .and_then(|ms| ms.trim().parse().ok())
+                .or(defaults.default_timeout_ms);
+            let report_formats = env_value("LUMI_REPORT_FORMATS")
+                .map(|formats| comma_list(&formats))
+                .or(defaults.report_formats);
+            let record = record || record_on_failure;
+            let platform = platform.or(defaults.platform);
+            let mut output = output
+                .or(defaults.output)
+                .unwrap_or_else(|| PathBuf::from("./output"));
+            if timestamped_output {
+                let run_id = uuid::Uuid::new_v4().simple().to_string();
+                output = output.join(format!(
+                    "{}-{}",
+                    chrono::Local::now().format("%Y%m%d-%H%M%S"),
+                    &run_id[..8]
+                ));
             }
-            if snapshot {
-                println!("  Snapshots: {}", "Enabled".green());
+            let tags = tags.or(defaults.tags);
+            let report = report || defaults.report.unwrap_or(false);
+            let snapshot = snapshot || defaults.snapshot.unwrap_or(false);
+
+            let platform_val = if let Some(p) = platform {
+                normalize_platform(&p)
+            } else {
+                detect_platform(&path)?.unwrap_or_else(|| "android".to_string())
+            };
+
+            if !quiet {
+                println!(
+                    "{} Running tests from: {}",
+                    "▶".green().bold(),
+                    path.display()
+                );
+                println!("  Platform: {}", platform_val.cyan());
+                if let Some(ref project) = project {
+                    println!("  Config: {}", project.path.display().to_string().cyan());
+                }
+                if !device.is_empty() {
+                    println!("  Devices: {}", device.join(", ").cyan());
+                }
+                if parallel {
+                    println!("  Parallel: {}", "Enabled".yellow());
+                }
+                if let Some(n) = workers {
+                    println!("  Workers: {}", n.to_string().yellow());
+                }
+                if order != runner::FileOrder::Alphabetical {
+                    println!("  Order: {}", format!("{:?}", order).yellow());
+                }
+                if fail_fast {
+                    println!("  Fail fast: {}", "Enabled".yellow());
+                }
+                if let Some(ref tags_list) = tags {
+                    println!("  Tags: {}", tags_list.join(", ").yellow());
+                }
+                println!("  Output: {}", output.display().to_string().cyan());
+                if record_on_failure {
+                    println!("  Recording: {}", "Failures only".green());
+                } else if record {
+                    println!("  Recording: {}", "Enabled".green());
+                }
+                if snapshot {
+                    println!("  Snapshots: {}", "Enabled".green());
+                }
+                if let Some(ref dir) = baselines_dir {
+                    println!("  Baselines: {}", dir.display().to_string().cyan());
+                }
+                if report {
+                    println!("  Reports: {}", "Enabled".green());
+                }
+                if events_jsonl {
+                    println!("  Events JSONL: {}", "Enabled".green());
+                }
+                if let Some(idx) = command_index {
+                    println!("  Command Index: {}", idx.to_string().yellow());
+                }
+                if let Some(ref name) = command_name {
+                    println!("  Command Name: {}", name.cyan());
+                }
+                if headed {
+                    println!("  Browser: {}", "Headed".green());
+                }
+                if let Some(ms) = slow_mo {
+                    println!("  Slow-mo: {}", format!("{}ms", ms).yellow());
+                }
+                if web_trace {
+                    println!("  Web Trace: {}", "per flow".green());
+                }
+                if let Some(ref url) = connect_url {
+                    println!("  Remote Browser: {}", url.cyan());
+                }
+                if let Some(ref target) = notify {
+                    println!("  Notify: {}", report::notify::mask_url(&target.url).cyan());
+                }
             }
-            if report {
-                println!("  Reports: {}", "Enabled".green());
+            let recording = driver::traits::RecordingOptions::parse(
+                bitrate.as_deref(),
+                size.as_deref(),
+                time_limit,
+            )?;
+            let mut quality = Vec::new();
+            if let Some(bps) = recording.bitrate {
+                quality.push(format!("{} bps", bps));
             }
-            if events_jsonl {
-                println!("  Events JSONL: {}", "Enabled".green());
+            if let Some((w, h)) = recording.size {
+                quality.push(format!("{}x{}", w, h));
             }
-            if let Some(idx) = command_index {
-                println!("  Command Index: {}", idx.to_string().yellow());
+            if let Some(secs) = recording.time_limit_secs {
+                quality.push(format!("max {}s", secs));
             }
-            if let Some(ref name) = command_name {
-                println!("  Command Name: {}", name.cyan());
+            if !quality.is_empty() && !quiet {
+                println!("  Recording: {}", quality.join(", ").yellow());
             }
 
             runner::run_tests(
@@ -335,15 +644,39 @@ async fn async_main() -> anyhow::Result<()> {
                     Some(device)
                 },
                 &output,
-                continue_on_failure,
-                parallel,
-                record,
-                snapshot,
-                report,
-                events_jsonl,
-                tags,
-                command_index,
-                command_name,
+                runner::RunOptions {
+                    continue_on_failure,
+                    fail_fast,
+                    parallel,
+                    workers,
+                    order,
+                    record,
+                    snapshot,
+                    report,
+                    events_jsonl,
+                    json_summary,
+                    tags,
+                    command_index,
+                    command_name,
+                    shutdown_simulator,
+                    web: runner::WebRunOptions {
+                        headed,
+                        slow_mo_ms: slow_mo,
+                        web_trace,
+                        connect_url,
+                    },
+                    recording,
+                    resume,
+                    changed_since,
+                    verbosity,
+                    speed,
+                    default_timeout_ms,
+                    report_formats,
+                    baselines_dir,
+                    skip_commands,
+                    record_on_failure,
+                    notify,
+                },
             )
             .await?;
         }
@@ -418,9 +751,23 @@ async fn async_main() -> anyhow::Result<()> {
         }
 
         Commands::System { command } => match command {
-            SystemCommands::Install { all } => {
-                utils::system::handle_system_command(utils::system::SystemCommand::Install { all })
-                    .await?;
+            SystemCommands::Install {
+                all,
+                platform,
+                browsers,
+            } => {
+                utils::system::handle_system_command(utils::system::SystemCommand::Install {
+                    all,
+                    platform: normalize_platform(&platform),
+                    browsers: browsers.iter().map(|b| normalize_platform(b)).collect(),
+                })
+                .await?;
+            }
+            SystemCommands::Doctor { platform } => {
+                utils::system::handle_system_command(utils::system::SystemCommand::Doctor {
+                    platform: normalize_platform(&platform),
+                })
+                .await?;
             }
         },
 
@@ -447,19 +794,46 @@ async fn async_main() -> anyhow::Result<()> {
 
         Commands::Record {
             output,
+            platform,
             device,
             app,
             name,
             include_waits,
             include_comments,
+            append,
+            prefer_id,
+            prefer_text,
+            gps,
+            gps_interval,
         } => {
             println!("{} Starting record mode...", "🔴".to_string().red().bold());
+            if gps.is_some() && normalize_platform(&platform) != "android" {
+                anyhow::bail!("--gps is only supported on Android");
+            }
 
             // Create event recorder
-            let event_recorder = recorder::EventRecorder::new(device.as_deref()).await?;
+            let event_recorder = match normalize_platform(&platform).as_str() {
+                "android" => recorder::EventRecorder::new(device.as_deref()).await?,
+                "ios" => recorder::EventRecorder::new_ios(device.as_deref()).await?,
+                other => anyhow::bail!("Recording is not supported on platform: {}", other),
+            };
+            let event_recorder = if prefer_id {
+                event_recorder.with_weights(recorder::ScoringWeights::prefer_id())
+            } else if prefer_text {
+                event_recorder.with_weights(recorder::ScoringWeights::prefer_text())
+            } else {
+                event_recorder
+            };
 
             // Start recording
             event_recorder.start_recording().await?;
+            let gps_recorder = gps.as_ref().map(|path| {
+                println!("📍 Recording GPS trace to {}", path.display());
+                recorder::GpsTraceRecorder::start(
+                    device.clone(),
+                    std::time::Duration::from_millis(gps_interval.max(100)),
+                )
+            });
 
             // Set up Ctrl+C handler with atomic flag
             let stop_flag = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
@@ -470,116 +844,145 @@ async fn async_main() -> anyhow::Result<()> {
                 stop_flag_handler.store(true, std::sync::atomic::Ordering::SeqCst);
             })?;
 
-            // Start real-time touch capture using getevent
-            println!("\n📲 Monitoring device interactions...");
-            println!("   Tap, type, and swipe on your device.");
-            println!("   Press Ctrl+C when done.\n");
-
-            // Find touch device
-            let getevent_info =
-                lumi_tester::driver::android::adb::shell(device.as_deref(), "getevent -pl")
-                    .await
-                    .unwrap_or_default();
-
-            let touch_device = find_touch_device(&getevent_info);
-
-            if let Some(ref dev) = touch_device {
-                println!("📲 Found touch device: {}", dev);
-
-                // Get max touch coordinates for scaling
-                let (max_x, max_y) = parse_touch_range(&getevent_info);
-                println!("   Touch range: {}x{}", max_x, max_y);
-
-                // Start getevent stream
-                let adb_path = lumi_tester::utils::binary_resolver::find_adb()?;
-                let mut args = Vec::new();
-                if let Some(ref d) = device {
-                    args.push("-s".to_string());
-                    args.push(d.clone());
-                }
-                args.push("shell".to_string());
-                args.push(format!("getevent -lt {}", dev));
-
-                let mut child = tokio::process::Command::new(&adb_path)
-                    .args(&args)
-                    .stdout(std::process::Stdio::piped())
-                    .stderr(std::process::Stdio::null())
-                    .spawn()?;
-
-                let stdout = child.stdout.take().unwrap();
-                let reader = tokio::io::BufReader::new(stdout);
-
-                use tokio::io::AsyncBufReadExt;
-                let mut lines = reader.lines();
-
-                let mut current_x: Option<i32> = None;
-                let mut current_y: Option<i32> = None;
-                let mut touch_down_time: Option<std::time::Instant> = None;
-
-                loop {
-                    tokio::select! {
-                        line = lines.next_line() => {
-                            if stop_flag.load(std::sync::atomic::Ordering::SeqCst) {
-                                break;
-                            }
-                            match line {
-                                Ok(Some(line)) => {
-                                    // Parse getevent output
-                                    if line.contains("ABS_MT_POSITION_X") {
-                                        if let Some(val) = parse_hex_value(&line) {
-                                            current_x = Some((val as f64 / max_x as f64 * event_recorder.screen_width as f64) as i32);
-                                        }
-                                    } else if line.contains("ABS_MT_POSITION_Y") {
-                                        if let Some(val) = parse_hex_value(&line) {
-                                            current_y = Some((val as f64 / max_y as f64 * event_recorder.screen_height as f64) as i32);
-                                        }
-                                    } else if line.contains("BTN_TOUCH") && line.contains("DOWN") {
-                                        touch_down_time = Some(std::time::Instant::now());
-                                    } else if line.contains("BTN_TOUCH") && line.contains("UP") {
-                                        if let (Some(x), Some(y)) = (current_x, current_y) {
-                                            let duration = touch_down_time
-                                                .map(|t| t.elapsed().as_millis())
-                                                .unwrap_or(0);
-
-                                            if duration > 500 {
-                                                // Long press - for now just log
-                                                println!("  👆 longPress at ({}, {})", x, y);
-                                            } else {
-                                                // Record tap
-                                                if let Err(e) = event_recorder.record_tap(x, y).await {
-                                                    eprintln!("  ⚠️ Failed to record tap: {}", e);
+            if event_recorder.platform() == recorder::RecorderPlatform::Ios {
+                drive_ios_session(&event_recorder, &stop_flag).await;
+            } else {
+                // Start real-time touch capture using getevent
+                println!("\n📲 Monitoring device interactions...");
+                println!("   Tap, type, and swipe on your device.");
+                println!("   Press Ctrl+C when done.\n");
+
+                // Find touch device
+                let getevent_info =
+                    lumi_tester::driver::android::adb::shell(device.as_deref(), "getevent -pl")
+                        .await
+                        .unwrap_or_default();
+
+                let touch_device = find_touch_device(&getevent_info);
+
+                if let Some(ref dev) = touch_device {
+                    println!("📲 Found touch device: {}", dev);
+
+                    // Get max touch coordinates for scaling
+                    let (max_x, max_y) = parse_touch_range(&getevent_info);
+                    println!("   Touch range: {}x{}", max_x, max_y);
+
+                    // Start getevent stream
+                    let adb_path = lumi_tester::utils::binary_resolver::find_adb()?;
+                    let mut args = Vec::new();
+                    if let Some(ref d) = device {
+                        args.push("-s".to_string());
+                        args.push(d.clone());
+                    }
+                    args.push("shell".to_string());
+                    args.push(format!("getevent -lt {}", dev));
+
+                    let mut child = tokio::process::Command::new(&adb_path)
+                        .args(&args)
+                        .stdout(std::process::Stdio::piped())
+                        .stderr(std::process::Stdio::null())
+                        .spawn()?;
+
+                    let stdout = child.stdout.take().unwrap();
+                    let reader = tokio::io::BufReader::new(stdout);
+
+                    use tokio::io::AsyncBufReadExt;
+                    let mut lines = reader.lines();
+
+                    let mut current_x: Option<i32> = None;
+                    let mut current_y: Option<i32> = None;
+                    // First position of the current touch, used to tell swipes from taps
+                    let mut start_x: Option<i32> = None;
+                    let mut start_y: Option<i32> = None;
+                    let mut move_samples = 0u32;
+                    let mut touch_down_time: Option<std::time::Instant> = None;
+
+                    loop {
+                        tokio::select! {
+                            line = lines.next_line() => {
+                                if stop_flag.load(std::sync::atomic::Ordering::SeqCst) {
+                                    break;
+                                }
+                                match line {
+                                    Ok(Some(line)) => {
+                                        // Parse getevent output
+                                        if line.contains("ABS_MT_POSITION_X") {
+                                            if let Some(val) = parse_hex_value(&line) {
+                                                current_x = Some((val as f64 / max_x as f64 * event_recorder.screen_width as f64) as i32);
+                                                start_x = start_x.or(current_x);
+                                                move_samples += 1;
+                                            }
+                                        } else if line.contains("ABS_MT_POSITION_Y") {
+                                            if let Some(val) = parse_hex_value(&line) {
+                                                current_y = Some((val as f64 / max_y as f64 * event_recorder.screen_height as f64) as i32);
+                                                start_y = start_y.or(current_y);
+                                                move_samples += 1;
+                                            }
+                                        } else if line.contains("BTN_TOUCH") && line.contains("DOWN") {
+                                            touch_down_time = Some(std::time::Instant::now());
+                                        } else if line.contains("BTN_TOUCH") && line.contains("UP") {
+                                            if let (Some(x), Some(y)) = (current_x, current_y) {
+                                                let duration = touch_down_time
+                                                    .map(|t| t.elapsed().as_millis())
+                                                    .unwrap_or(0);
+                                                let start = (start_x.unwrap_or(x), start_y.unwrap_or(y));
+
+                                                if move_samples > 2 && recorder::event_recorder::is_swipe(start, (x, y)) {
+                                                    if let Err(e) = event_recorder.record_swipe(start, (x, y), duration as u64).await {
+                                                        eprintln!("  ⚠️ Failed to record swipe: {}", e);
+                                                    }
+                                                } else if duration > 500 {
+                                                    // Long press - for now just log
+                                                    println!("  👆 longPress at ({}, {})", x, y);
+                                                } else {
+                                                    // Record tap
+                                                    if let Err(e) = event_recorder.record_tap(x, y).await {
+                                                        eprintln!("  ⚠️ Failed to record tap: {}", e);
+                                                    }
                                                 }
                                             }
+                                            current_x = None;
+                                            current_y = None;
+                                            start_x = None;
+                                            start_y = None;
+                                            move_samples = 0;
+                                            touch_down_time = None;
                                         }
-                                        current_x = None;
-                                        current_y = None;
-                                        touch_down_time = None;
                                     }
+                                    Ok(None) => break,
+                                    Err(_) => break,
                                 }
-                                Ok(None) => break,
-                                Err(_) => break,
                             }
-                        }
-                        _ = tokio::time::sleep(std::time::Duration::from_millis(100)) => {
-                            if stop_flag.load(std::sync::atomic::Ordering::SeqCst) {
-                                break;
+                            _ = tokio::time::sleep(std::time::Duration::from_millis(100)) => {
+                                if stop_flag.load(std::sync::atomic::Ordering::SeqCst) {
+                                    break;
+                                }
+                                // Pick up text typed since the last dump
+                                let _ = event_recorder
+                                    .poll_text_input(std::time::Duration::from_secs(2))
+                                    .await;
                             }
                         }
                     }
-                }
 
-                let _ = child.kill().await;
-            } else {
-                println!("⚠️ No touch device found. Running in passive mode.");
-                // Fallback: just wait for Ctrl+C
-                loop {
-                    tokio::time::sleep(std::time::Duration::from_millis(500)).await;
-                    if stop_flag.load(std::sync::atomic::Ordering::SeqCst) {
-                        break;
+                    let _ = child.kill().await;
+                } else {
+                    println!("⚠️ No touch device found. Running in passive mode.");
+                    // Fallback: just wait for Ctrl+C
+                    loop {
+                        tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+                        if stop_flag.load(std::sync::atomic::Ordering::SeqCst) {
+                            break;
+                        }
                     }
                 }
             }
 
+            // Capture text typed after the last interaction
+            let _ = event_recorder
+                .poll_text_input(std::time::Duration::ZERO)
+                .await;
+
             // Generate YAML - this now runs after Ctrl+C
             let actions = event_recorder.stop_recording().await?;
             let detected_app = event_recorder.get_current_app().await;
@@ -594,7 +997,29 @@ async fn async_main() -> anyhow::Result<()> {
             };
 
             let generator = recorder::YamlGenerator::with_config(config);
-            generator.save_to_file(&actions, app_id, name.as_deref(), &output)?;
+            if append && output.exists() {
+                generator.append_to_file(&actions, &output)?;
+            } else {
+                generator.save_to_file(&actions, app_id, name.as_deref(), &output)?;
+            }
+
+            if let (Some(gps_recorder), Some(gps_path)) = (gps_recorder, gps.as_ref()) {
+                let points = gps_recorder.stop().await;
+                if points.is_empty() {
+                    println!(
+                        "{} No location fixes were reported; is location enabled and is an app requesting it?",
+                        "⚠️".yellow()
+                    );
+                } else {
+                    let trace_name = name.as_deref().unwrap_or("Recorded route");
+                    recorder::gps_recorder::save_gpx(&points, trace_name, gps_path)?;
+                    println!(
+                        "📍 Saved {} GPS points. Replay with: - mockLocation: {}",
+                        points.len(),
+                        gps_path.display()
+                    );
+                }
+            }
 
             println!("\n{} Recording complete!", "✅".green().bold());
             println!("   Output: {}", output.display().to_string().cyan());
@@ -623,6 +1048,19 @@ async fn async_main() -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Value of an environment variable the user set, if not blank
+fn env_value(name: &str) -> Option<String> {
+    std::env::var(name).ok().filter(|v| !v.trim().is_empty())
+}
+
+fn comma_list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(|item| item.trim().to_string())
+        .filter(|item| !item.is_empty())
+        .collect()
+}
+
 fn normalize_platform(platform: &str) -> String {
     platform
         .trim()
@@ -1137,6 +1575,72 @@ mod tests {
     }
 }
 
+/// Drive an iOS device from terminal commands and record each action
+///
+/// iOS exposes no system-wide touch stream, so taps and swipes made on the
+/// device itself can't be seen: they are typed here, forwarded through idb and
+/// recorded against the accessibility tree. Text typed on the device is still
+/// picked up by diffing the text fields between hierarchy dumps.
+async fn drive_ios_session(
+    event_recorder: &recorder::EventRecorder,
+    stop_flag: &std::sync::atomic::AtomicBool,
+) {
+    use tokio::io::AsyncBufReadExt;
+
+    println!(
+        "\n📲 Recording on iOS {}",
+        event_recorder.serial().unwrap_or("")
+    );
+    println!("   Taps and swipes are only recorded when sent from this terminal:");
+    println!("   tap <x> <y> | type <text> | swipe <up|down|left|right> | done");
+    println!("   Text typed on the device is recorded too.");
+    println!("   Press Ctrl+C or type 'done' when finished.\n");
+
+    let mut lines = tokio::io::BufReader::new(tokio::io::stdin()).lines();
+
+    loop {
+        tokio::select! {
+            line = lines.next_line() => {
+                let line = match line {
+                    Ok(Some(line)) => line,
+                    _ => break,
+                };
+                let line = line.trim();
+                let (cmd, rest) = line.split_once(' ').unwrap_or((line, ""));
+                let result = match cmd {
+                    "" => Ok(()),
+                    "done" | "exit" | "quit" => break,
+                    "tap" => {
+                        let coords: Vec<i32> = rest
+                            .split_whitespace()
+                            .filter_map(|v| v.parse().ok())
+                            .collect();
+                        if coords.len() == 2 {
+                            event_recorder.perform_ios_tap(coords[0], coords[1]).await
+                        } else {
+                            Err(anyhow::anyhow!("Usage: tap <x> <y>"))
+                        }
+                    }
+                    "type" => event_recorder.perform_ios_input(rest).await,
+                    "swipe" => event_recorder.perform_ios_swipe(rest.trim()).await,
+                    other => Err(anyhow::anyhow!("Unknown command: {}", other)),
+                };
+                if let Err(e) = result {
+                    eprintln!("  ⚠️ {}", e);
+                }
+            }
+            _ = tokio::time::sleep(std::time::Duration::from_millis(100)) => {
+                if stop_flag.load(std::sync::atomic::Ordering::SeqCst) {
+                    break;
+                }
+                let _ = event_recorder
+                    .poll_text_input(std::time::Duration::from_secs(2))
+                    .await;
+            }
+        }
+    }
+}
+
 /// Find the primary touch input device from getevent -pl output
 fn find_touch_device(getevent_output: &str) -> Option<String> {
     let mut current_device: Option<String> = None;
@@ -6,6 +6,8 @@ use chrono::{DateTime, Utc};
 use quick_xml::events::Event;
 use quick_xml::Reader;
 
+use crate::parser::types::{DriveProfile, SpeedMode};
+
 /// A single GPS coordinate point with optional metadata
 #[derive(Debug, Clone)]
 pub struct GpsPoint {
@@ -55,8 +57,8 @@ pub fn parse_gpx(content: &str) -> Result<Vec<GpsPoint>> {
             Ok(Event::Empty(ref e)) => match e.name().as_ref() {
                 b"trkpt" | b"wpt" => {
                     // Self-closing tag like <wpt lat="..." lon="..."/>
-                    let mut lat = 0.0;
-                    let mut lon = 0.0;
+                    let mut lat = f64::NAN;
+                    let mut lon = f64::NAN;
 
                     for attr in e.attributes().flatten() {
                         match attr.key.as_ref() {
@@ -64,13 +66,13 @@ pub fn parse_gpx(content: &str) -> Result<Vec<GpsPoint>> {
                                 lat = std::str::from_utf8(&attr.value)
                                     .unwrap_or("0")
                                     .parse()
-                                    .unwrap_or(0.0);
+                                    .unwrap_or(f64::NAN);
                             }
                             b"lon" => {
                                 lon = std::str::from_utf8(&attr.value)
                                     .unwrap_or("0")
                                     .parse()
-                                    .unwrap_or(0.0);
+                                    .unwrap_or(f64::NAN);
                             }
                             _ => {}
                         }
@@ -88,8 +90,8 @@ pub fn parse_gpx(content: &str) -> Result<Vec<GpsPoint>> {
                 let local_name = e.local_name();
                 match local_name.as_ref() {
                     b"trkpt" | b"wpt" => {
-                        let mut lat = 0.0;
-                        let mut lon = 0.0;
+                        let mut lat = f64::NAN;
+                        let mut lon = f64::NAN;
 
                         for attr in e.attributes().flatten() {
                             match attr.key.as_ref() {
@@ -97,13 +99,13 @@ pub fn parse_gpx(content: &str) -> Result<Vec<GpsPoint>> {
                                     lat = std::str::from_utf8(&attr.value)
                                         .unwrap_or("0")
                                         .parse()
-                                        .unwrap_or(0.0);
+                                        .unwrap_or(f64::NAN);
                                 }
                                 b"lon" => {
                                     lon = std::str::from_utf8(&attr.value)
                                         .unwrap_or("0")
                                         .parse()
-                                        .unwrap_or(0.0);
+                                        .unwrap_or(f64::NAN);
                                 }
                                 _ => {}
                             }
@@ -256,8 +258,8 @@ pub fn parse_kml(content: &str) -> Result<Vec<GpsPoint>> {
                     for coord_str in text.split_whitespace() {
                         let parts: Vec<&str> = coord_str.trim().split(',').collect();
                         if parts.len() >= 2 {
-                            let lon: f64 = parts[0].parse().unwrap_or(0.0);
-                            let lat: f64 = parts[1].parse().unwrap_or(0.0);
+                            let lon: f64 = parts[0].parse().unwrap_or(f64::NAN);
+                            let lat: f64 = parts[1].parse().unwrap_or(f64::NAN);
                             let alt: Option<f64> = parts.get(2).and_then(|s| s.parse().ok());
 
                             let mut point = GpsPoint::new(lat, lon);
@@ -331,8 +333,8 @@ pub fn parse_google_json(content: &str) -> Result<Vec<GpsPoint>> {
                             if let Some(coords) = point_str.strip_prefix("geo:") {
                                 let parts: Vec<&str> = coords.split(',').collect();
                                 if parts.len() >= 2 {
-                                    let lat: f64 = parts[0].parse().unwrap_or(0.0);
-                                    let lon: f64 = parts[1].parse().unwrap_or(0.0);
+                                    let lat: f64 = parts[0].parse().unwrap_or(f64::NAN);
+                                    let lon: f64 = parts[1].parse().unwrap_or(f64::NAN);
                                     points.push(GpsPoint::new(lat, lon));
                                 }
                             }
@@ -386,9 +388,43 @@ pub fn haversine_distance(lat1: f64, lon1: f64, lat2: f64, lon2: f64) -> f64 {
     R * c
 }
 
-/// Auto-detect format and parse GPS file
+/// Serialize points as a GPX 1.1 track that `parse_gpx` reads back
+pub fn write_gpx(points: &[GpsPoint], name: &str) -> String {
+    let escape = |s: &str| {
+        s.replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;")
+    };
+    let mut gpx = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<gpx version=\"1.1\" creator=\"lumi-tester\" xmlns=\"http://www.topografix.com/GPX/1/1\">\n",
+    );
+    gpx.push_str(&format!(
+        "  <trk>\n    <name>{}</name>\n    <trkseg>\n",
+        escape(name)
+    ));
+    for point in points {
+        gpx.push_str(&format!(
+            "      <trkpt lat=\"{}\" lon=\"{}\">",
+            point.lat, point.lon
+        ));
+        if let Some(alt) = point.altitude {
+            gpx.push_str(&format!("<ele>{}</ele>", alt));
+        }
+        if let Some(time) = point.timestamp {
+            gpx.push_str(&format!(
+                "<time>{}</time>",
+                time.to_rfc3339_opts(chrono::SecondsFormat::Millis, true)
+            ));
+        }
+        gpx.push_str("</trkpt>\n");
+    }
+    gpx.push_str("    </trkseg>\n  </trk>\n</gpx>\n");
+    gpx
+}
+
+/// Auto-detect format and parse GPS file; every point must have valid coordinates
 pub fn parse_gps_file(content: &str, extension: &str) -> Result<Vec<GpsPoint>> {
-    match extension.to_lowercase().as_str() {
+    let points = match extension.to_lowercase().as_str() {
         "gpx" => parse_gpx(content),
         "kml" => parse_kml(content),
         "json" => parse_google_json(content),
@@ -396,6 +432,154 @@ pub fn parse_gps_file(content: &str, extension: &str) -> Result<Vec<GpsPoint>> {
             "Unsupported GPS file format: {}",
             extension
         )),
+    }?;
+    validate_points(&points)?;
+    Ok(points)
+}
+
+/// Reject missing/malformed coordinates (parsed as NaN) and out-of-range lat/lon
+pub fn validate_points(points: &[GpsPoint]) -> Result<()> {
+    for (i, point) in points.iter().enumerate() {
+        if !(-90.0..=90.0).contains(&point.lat) || !(-180.0..=180.0).contains(&point.lon) {
+            anyhow::bail!(
+                "GPS point #{} has invalid coordinates (lat {}, lon {}); expected lat -90..90 and lon -180..180",
+                i + 1,
+                point.lat,
+                point.lon
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Resample a route so consecutive points are `step_m` meters apart along the path.
+/// Sparse segments get intermediate points, dense ones are thinned; the first and
+/// last points are kept. Altitude and timestamps are interpolated.
+pub fn interpolate_route(points: &[GpsPoint], step_m: f64) -> Result<Vec<GpsPoint>> {
+    if step_m.is_nan() || step_m <= 0.0 {
+        anyhow::bail!(
+            "interpolate must be a positive distance in meters, got {}",
+            step_m
+        );
+    }
+    let (Some(first), Some(last)) = (points.first(), points.last()) else {
+        return Ok(Vec::new());
+    };
+
+    let mut resampled = vec![first.clone()];
+    // Distance along the route still needed before the next emitted point
+    let mut remaining = step_m;
+    for pair in points.windows(2) {
+        let (a, b) = (&pair[0], &pair[1]);
+        let segment = haversine_distance(a.lat, a.lon, b.lat, b.lon);
+        let mut travelled = 0.0;
+        while segment - travelled >= remaining {
+            travelled += remaining;
+            resampled.push(lerp_point(a, b, travelled / segment));
+            remaining = step_m;
+        }
+        remaining -= segment - travelled;
+    }
+    let end = resampled.last().expect("starts with the first point");
+    if haversine_distance(end.lat, end.lon, last.lat, last.lon) > step_m * 0.01 {
+        resampled.push(last.clone());
+    }
+
+    for point in &mut resampled {
+        point.speed = None;
+    }
+    calculate_speeds(&mut resampled);
+    Ok(resampled)
+}
+
+/// Point `t` (0..1) of the way from `a` to `b`
+fn lerp_point(a: &GpsPoint, b: &GpsPoint, t: f64) -> GpsPoint {
+    let mut point = GpsPoint::new(a.lat + (b.lat - a.lat) * t, a.lon + (b.lon - a.lon) * t);
+    point.altitude = match (a.altitude, b.altitude) {
+        (Some(x), Some(y)) => Some(x + (y - x) * t),
+        (x, y) => x.or(y),
+    };
+    point.timestamp = match (a.timestamp, b.timestamp) {
+        (Some(x), Some(y)) => {
+            let offset = ((y - x).num_milliseconds() as f64 * t) as i64;
+            Some(x + chrono::Duration::milliseconds(offset))
+        }
+        _ => None,
+    };
+    point
+}
+
+/// Distance in meters from the first point to each point along the route
+pub fn cumulative_distances(points: &[GpsPoint]) -> Vec<f64> {
+    let mut total = 0.0;
+    let mut distances = Vec::with_capacity(points.len());
+    for (i, point) in points.iter().enumerate() {
+        if i > 0 {
+            let prev = &points[i - 1];
+            total += haversine_distance(prev.lat, prev.lon, point.lat, point.lon);
+        }
+        distances.push(total);
+    }
+    distances
+}
+
+/// Speed in km/h for the segment `travelled_m` meters into a `route_m` long route.
+/// Ramps follow v = sqrt(2·a·d), so the curve modes never exceed `base_kmh`.
+pub fn profile_speed_kmh(
+    profile: &DriveProfile,
+    base_kmh: f64,
+    travelled_m: f64,
+    route_m: f64,
+    rng: &mut impl rand::Rng,
+) -> f64 {
+    // Floor at 1 km/h so a segment starting from standstill still moves
+    let ramp = |d: f64| ((2.0 * profile.acceleration() * d.max(0.0)).sqrt() * 3.6).max(1.0);
+    match profile.mode {
+        SpeedMode::Linear => base_kmh,
+        SpeedMode::Noise => {
+            let range = profile.noise();
+            let noise = if range > 0.0 {
+                rng.gen_range(-range..range)
+            } else {
+                0.0
+            };
+            (base_kmh + noise).max(1.0) // Minimum 1 km/h
+        }
+        SpeedMode::Accelerate => base_kmh.min(ramp(travelled_m)),
+        SpeedMode::Decelerate => base_kmh.min(ramp(route_m - travelled_m)),
+        SpeedMode::Stops => {
+            let every = profile.stop_every();
+            let since_stop = travelled_m % every;
+            base_kmh.min(ramp(since_stop)).min(ramp(every - since_stop))
+        }
+    }
+}
+
+/// Whether the stops profile halts somewhere in (`from_m`, `to_m`] along the route
+pub fn stop_between(profile: &DriveProfile, from_m: f64, to_m: f64) -> bool {
+    if profile.mode != SpeedMode::Stops {
+        return false;
+    }
+    let every = profile.stop_every();
+    (to_m / every).floor() > (from_m / every).floor()
+}
+
+/// Estimated seconds to drive `route_m` at `base_kmh`, including ramps and stops
+pub fn estimate_route_secs(profile: &DriveProfile, base_kmh: f64, route_m: f64) -> f64 {
+    let speed = base_kmh / 3.6;
+    if speed <= 0.001 {
+        return 0.0;
+    }
+    let cruise = route_m / speed;
+    // A full ramp to/from cruise speed costs v/2a seconds over driving it at v
+    let ramp = speed / (2.0 * profile.acceleration());
+    match profile.mode {
+        SpeedMode::Linear | SpeedMode::Noise => cruise,
+        SpeedMode::Accelerate | SpeedMode::Decelerate => cruise + ramp,
+        SpeedMode::Stops => {
+            let stops = (route_m / profile.stop_every()).floor();
+            cruise + ramp * (1.0 + 2.0 * stops) + stops * profile.stop_duration_ms() as f64 / 1000.0
+        }
     }
 }
 
@@ -511,4 +695,119 @@ mod tests {
         assert!((parse_lockito_value("random:2.0:4.0").unwrap() - 2.0).abs() < 0.01);
         assert!((parse_lockito_value("fixed:0.0").unwrap() - 0.0).abs() < 0.01);
     }
+
+    #[test]
+    fn test_validate_rejects_malformed_and_out_of_range_points() {
+        let bad_lat = r#"<gpx><wpt lat="95.0" lon="106.7"/></gpx>"#;
+        assert!(parse_gps_file(bad_lat, "gpx").is_err());
+        let malformed = r#"<gpx><wpt lat="abc" lon="106.7"/></gpx>"#;
+        let err = parse_gps_file(malformed, "gpx").unwrap_err().to_string();
+        assert!(err.contains("GPS point #1"), "{}", err);
+        let kml = "<kml><coordinates>106.7,10.7 200.0,10.8</coordinates></kml>";
+        assert!(parse_gps_file(kml, "kml").is_err());
+    }
+
+    #[test]
+    fn test_interpolate_route_evens_out_spacing() {
+        // ~1.1 km apart along a meridian
+        let sparse = vec![GpsPoint::new(10.0, 106.0), GpsPoint::new(10.01, 106.0)];
+        let route = interpolate_route(&sparse, 100.0).unwrap();
+        assert_eq!(route.len(), 13);
+        for pair in route[..route.len() - 1].windows(2) {
+            let d = haversine_distance(pair[0].lat, pair[0].lon, pair[1].lat, pair[1].lon);
+            assert!((d - 100.0).abs() < 0.5, "{}", d);
+        }
+        assert!((route.last().unwrap().lat - 10.01).abs() < 1e-9);
+
+        // Points every ~11 m thinned to every ~100 m
+        let dense: Vec<GpsPoint> = (0..=100)
+            .map(|i| GpsPoint::new(10.0 + i as f64 * 0.0001, 106.0))
+            .collect();
+        assert_eq!(interpolate_route(&dense, 100.0).unwrap().len(), 13);
+        assert!(interpolate_route(&dense, 0.0).is_err());
+    }
+
+    #[test]
+    fn test_write_gpx_round_trips() {
+        let start = DateTime::parse_from_rfc3339("2026-01-01T08:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let mut a = GpsPoint::new(10.7769, 106.7009);
+        a.altitude = Some(12.5);
+        a.timestamp = Some(start);
+        let mut b = GpsPoint::new(10.7779, 106.7019);
+        b.timestamp = Some(start + chrono::Duration::seconds(10));
+
+        let points = parse_gpx(&write_gpx(&[a, b], "Commute & back")).unwrap();
+        assert_eq!(points.len(), 2);
+        assert_eq!((points[1].lat, points[1].lon), (10.7779, 106.7019));
+        assert_eq!(points[0].altitude, Some(12.5));
+        assert_eq!(
+            points[1].timestamp,
+            Some(start + chrono::Duration::seconds(10))
+        );
+        assert!(points[1].speed.is_some());
+    }
+
+    #[test]
+    fn test_speed_profiles_ramp_and_stop() {
+        use rand::SeedableRng;
+        let mut rng = rand::rngs::StdRng::seed_from_u64(7);
+        let profile = |mode| DriveProfile {
+            mode,
+            acceleration: Some(2.0),
+            stop_every: Some(400.0),
+            ..Default::default()
+        };
+
+        // sqrt(2 * 2 m/s² * 25 m) = 10 m/s = 36 km/h
+        let accelerate = profile(SpeedMode::Accelerate);
+        let ramping = profile_speed_kmh(&accelerate, 50.0, 25.0, 1000.0, &mut rng);
+        assert!((ramping - 36.0).abs() < 0.01, "{}", ramping);
+        assert_eq!(
+            profile_speed_kmh(&accelerate, 50.0, 900.0, 1000.0, &mut rng),
+            50.0
+        );
+        assert_eq!(
+            profile_speed_kmh(&accelerate, 50.0, 0.0, 1000.0, &mut rng),
+            1.0
+        );
+
+        let decelerate = profile(SpeedMode::Decelerate);
+        assert_eq!(
+            profile_speed_kmh(&decelerate, 50.0, 100.0, 1000.0, &mut rng),
+            50.0
+        );
+        let braking = profile_speed_kmh(&decelerate, 50.0, 975.0, 1000.0, &mut rng);
+        assert!((braking - 36.0).abs() < 0.01, "{}", braking);
+
+        let stops = profile(SpeedMode::Stops);
+        assert_eq!(
+            profile_speed_kmh(&stops, 50.0, 200.0, 1000.0, &mut rng),
+            50.0
+        );
+        let approaching = profile_speed_kmh(&stops, 50.0, 375.0, 1000.0, &mut rng);
+        assert!((approaching - 36.0).abs() < 0.01, "{}", approaching);
+        let leaving = profile_speed_kmh(&stops, 50.0, 425.0, 1000.0, &mut rng);
+        assert!((leaving - 36.0).abs() < 0.01, "{}", leaving);
+
+        assert!(stop_between(&stops, 390.0, 410.0));
+        assert!(!stop_between(&stops, 410.0, 790.0));
+        assert!(!stop_between(&profile(SpeedMode::Linear), 390.0, 410.0));
+    }
+
+    #[test]
+    fn test_estimate_route_secs_includes_stops() {
+        let linear = DriveProfile::default();
+        assert!((estimate_route_secs(&linear, 36.0, 1000.0) - 100.0).abs() < 0.01);
+        let stops = DriveProfile {
+            mode: SpeedMode::Stops,
+            acceleration: Some(2.0),
+            stop_every: Some(400.0),
+            stop_duration_ms: Some(10_000),
+            ..Default::default()
+        };
+        // 100s cruise + 5 ramps of 2.5s + 2 stops of 10s
+        assert!((estimate_route_secs(&stops, 36.0, 1000.0) - 132.5).abs() < 0.01);
+    }
 }
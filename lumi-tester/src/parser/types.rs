@@ -25,12 +25,24 @@ pub struct TestFlow {
     #[serde(default, alias = "defaultTimeout")]
     pub default_timeout_ms: Option<u64>,
 
+    /// Hard limit for the whole flow in ms; the running command fails when it passes
+    #[serde(default, alias = "timeout")]
+    pub timeout_ms: Option<u64>,
+
     #[serde(default)]
     pub commands: Vec<TestCommand>,
 
     #[serde(default)]
     pub tags: Vec<String>,
 
+    /// Human readable flow title shown in reports
+    #[serde(default, alias = "name")]
+    pub title: Option<String>,
+
+    /// What the flow covers, shown in reports
+    #[serde(default)]
+    pub description: Option<String>,
+
     /// Speed profile for this flow: "turbo", "fast", "normal", "safe"
     #[serde(default)]
     pub speed: Option<String>,
@@ -43,9 +55,29 @@ pub struct TestFlow {
     #[serde(default)]
     pub close_when_finish: Option<bool>,
 
+    /// Attach to a remote browser over CDP instead of launching one (web)
+    #[serde(default, alias = "wsEndpoint")]
+    pub connect_url: Option<String>,
+
     /// Desktop app state clearing configuration for macOS and Windows.
     #[serde(default)]
     pub desktop_state: Option<DesktopState>,
+
+    /// Auto-answer OS permission dialogs before each command
+    #[serde(default)]
+    pub handle_system_dialogs: Option<SystemDialogAction>,
+
+    /// Fail the flow when logcat reports a crash or ANR for the app under test
+    #[serde(default)]
+    pub detect_crashes: Option<bool>,
+
+    /// Directory holding assertScreenshot references, relative to the flow
+    #[serde(default)]
+    pub baselines: Option<String>,
+
+    /// Flows (file name, stem or title) that must run and pass before this one
+    #[serde(default)]
+    pub depends_on: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -116,6 +148,32 @@ pub struct ScrollableParams {
     pub item_index: Option<u32>,
 }
 
+/// Button `handleSystemDialogs` taps on OS permission dialogs
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum SystemDialogAction {
+    Allow,
+    Deny,
+}
+
+impl SystemDialogAction {
+    /// Parse a `handleSystemDialogs` value; case-insensitive
+    pub fn from_name(name: &str) -> anyhow::Result<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "allow" => Ok(Self::Allow),
+            "deny" => Ok(Self::Deny),
+            _ => anyhow::bail!("Invalid handleSystemDialogs '{}'. Use allow or deny", name),
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Allow => "allow",
+            Self::Deny => "deny",
+        }
+    }
+}
+
 /// Device orientation modes
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
@@ -127,15 +185,49 @@ pub enum Orientation {
     LandscapeRight,
 }
 
+impl Orientation {
+    /// Parse a `rotate` mode; accepts the enum names in any case
+    pub fn from_mode(mode: &str) -> anyhow::Result<Self> {
+        match mode.trim().to_lowercase().replace('-', "_").as_str() {
+            "portrait" => Ok(Self::Portrait),
+            "landscape" => Ok(Self::Landscape),
+            "upside_down" | "upsidedown" => Ok(Self::UpsideDown),
+            "landscape_left" | "landscapeleft" => Ok(Self::LandscapeLeft),
+            "landscape_right" | "landscaperight" => Ok(Self::LandscapeRight),
+            _ => anyhow::bail!(
+                "Invalid orientation '{}'. Use portrait, landscape, upside_down, landscape_left or landscape_right",
+                mode
+            ),
+        }
+    }
+
+    pub fn is_landscape(&self) -> bool {
+        matches!(
+            self,
+            Self::Landscape | Self::LandscapeLeft | Self::LandscapeRight
+        )
+    }
+}
+
 // Forward declarations for new param types used in TestCommand enum
 // (Full definitions are below)
 
 /// Parameters for assertTrue command
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct AssertTrueCondition {
     pub condition: String,
     #[serde(default)]
     pub soft: bool,
+    /// Shown on failure instead of the raw expression
+    #[serde(default)]
+    pub message: Option<String>,
+    /// Extra evaluations before failing (polled every 500ms)
+    #[serde(default)]
+    pub retry: Option<u32>,
+    /// Keep polling until the condition holds or this many ms pass
+    #[serde(default)]
+    pub timeout: Option<u64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -163,7 +255,7 @@ pub struct CopyTextFromParams {
     )]
     pub description: Option<String>,
     #[serde(default)]
-    pub index: Option<usize>,
+    pub index: Option<i32>,
     /// OCR text recognition selector
     #[serde(default)]
     pub ocr: Option<OcrSelectorInput>,
@@ -193,7 +285,7 @@ pub struct OcrSelectorParams {
     pub text: String,
     /// Index when multiple matches found (0-based)
     #[serde(default)]
-    pub index: Option<u32>,
+    pub index: Option<i32>,
     /// Region to search: "top-left", "top-right", "bottom-left", "bottom-right",
     /// "top-half", "bottom-half", "left-half", "right-half", "center"
     #[serde(default)]
@@ -218,10 +310,10 @@ impl OcrSelectorInput {
         }
     }
 
-    pub fn index(&self) -> usize {
+    pub fn index(&self) -> i32 {
         match self {
             Self::String(_) => 0,
-            Self::Struct(p) => p.index.unwrap_or(0) as usize,
+            Self::Struct(p) => p.index.unwrap_or(0),
         }
     }
 
@@ -366,6 +458,18 @@ pub enum TestCommand {
     Wait(WaitParamsInput),
     Repeat(RepeatParams),
     Retry(RetryParams),
+    ForEach(ForEachParams),
+    /// Wait until one of several selectors is visible, then run its branch
+    #[serde(alias = "waitForFirst")]
+    WaitForAny(WaitForAnyParams),
+    /// Run every assertion of the group and report their failures together
+    AssertAll(AssertAllParams),
+    /// Leave the innermost repeat/forEach
+    Break,
+    /// Skip to the next repeat/forEach iteration
+    Continue,
+    /// End the current flow early as passed
+    StopFlow,
     RunFlow(RunFlowParamsInput),
 
     // Variables
@@ -398,6 +502,9 @@ pub enum TestCommand {
     Navigate(NavigateParams),
     Click(ClickParams),
     Type(TypeParams),
+    AssertUrl(AssertUrlParams),
+    CaptureUrl(CaptureUrlParams),
+    ExecuteJs(ExecuteJsParams),
 
     // GPS Mock Location
     #[serde(alias = "gps")]
@@ -461,6 +568,12 @@ pub enum TestCommand {
     // Network & Connectivity
     #[serde(alias = "setNetwork")]
     SetNetwork(NetworkParams),
+    #[serde(alias = "setWifi", alias = "toggleWifi")]
+    SetWifi(bool),
+    #[serde(alias = "setMobileData", alias = "setData", alias = "toggleData")]
+    SetMobileData(bool),
+    #[serde(alias = "setBluetooth", alias = "toggleBluetooth")]
+    SetBluetooth(bool),
     #[serde(alias = "airplaneMode")]
     ToggleAirplaneMode,
 
@@ -472,9 +585,18 @@ pub enum TestCommand {
     UnlockDevice,
 
     // App Management
-    InstallApp(String),
+    InstallApp(InstallAppParams),
     UninstallApp(String),
     BackgroundApp(BackgroundAppParams),
+    AssertAppVersion(AssertAppVersionParams),
+    SetPermission(SetPermissionParams),
+    TapSystemButton(String),
+    SetDateTime(SetDateTimeParams),
+    SendSms(SendSmsParams),
+    AssertToast(AssertToastParams),
+    AssertNotification(AssertNotificationParams),
+    SnapshotAppData(AppDataSnapshotParams),
+    RestoreAppData(AppDataSnapshotParams),
 
     // Device Orientation
     #[serde(alias = "setOrientation")]
@@ -493,7 +615,7 @@ pub enum TestCommand {
     #[serde(alias = "setCpuThrottling")]
     SetCpuThrottling(f64),
     #[serde(alias = "setNetworkConditions")]
-    SetNetworkConditions(String),
+    SetNetworkConditions(NetworkConditionsInput),
 
     #[serde(alias = "display")]
     SelectDisplay(String),
@@ -513,6 +635,30 @@ pub enum TestCommand {
     StopAudioCapture,
     #[serde(alias = "verifyAudioDucking")]
     VerifyAudioDucking(VerifyAudioDuckingParams),
+    #[serde(alias = "assertAudioPlaying")]
+    AssertAudioPlaying(AssertAudioPlayingParams),
+
+    /// Command provided by a registered `CommandPlugin`
+    Plugin(PluginCommand),
+
+    /// Any command with `timeout:` next to its key
+    Timed(TimedCommand),
+}
+
+/// Hard time limit around a single command, including hung driver calls
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TimedCommand {
+    pub timeout_ms: u64,
+    pub command: Box<TestCommand>,
+}
+
+/// A command key claimed by a plugin, with its params left as raw YAML
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginCommand {
+    pub name: String,
+    #[serde(default)]
+    pub params: serde_json::Value,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -524,8 +670,12 @@ pub struct WaitForLocationParams {
 
     pub lat: f64,
     pub lon: f64,
-    #[serde(default = "default_tolerance_meters")]
-    pub tolerance: f64,
+    /// Legacy tolerance: meters on Android/iOS, degrees per axis on web
+    #[serde(default)]
+    pub tolerance: Option<f64>,
+    /// Great-circle distance in meters; takes precedence over `tolerance`
+    #[serde(default, alias = "radius")]
+    pub tolerance_meters: Option<f64>,
     #[serde(default = "default_wait_ms")]
     pub timeout: u64,
 }
@@ -592,6 +742,25 @@ fn default_volume_drop_threshold() -> f64 {
     30.0
 }
 
+/// Assert whether media is playing (Android media sessions)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AssertAudioPlayingParams {
+    /// Package that must be playing (any app when omitted)
+    #[serde(default, alias = "appId")]
+    pub app: Option<String>,
+    /// Expected state; `false` asserts nothing is playing
+    #[serde(default = "default_true")]
+    pub playing: bool,
+    /// How long to wait for the state (defaults to defaultTimeout)
+    #[serde(default, alias = "timeoutMs")]
+    pub timeout: Option<u64>,
+}
+
+fn default_true() -> bool {
+    true
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct StartProfilingParams {
@@ -615,10 +784,166 @@ pub struct AssertPerformanceParams {
     pub limit: String, // e.g. "200MB", "60fps"
 }
 
-fn default_tolerance_meters() -> f64 {
-    50.0
+/// `setNetworkConditions`: a named profile or custom `{ latency, download, upload, packetLoss }`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum NetworkConditionsInput {
+    Profile(String),
+    Custom(NetworkConditionsParams),
+}
+
+/// Custom network conditions; fields override `profile` (no throttling when unset)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkConditionsParams {
+    pub profile: Option<String>,
+    /// Round-trip latency (ms)
+    pub latency: Option<u64>,
+    /// Download throughput (kbps)
+    pub download: Option<f64>,
+    /// Upload throughput (kbps)
+    pub upload: Option<f64>,
+    /// Packet loss (percent)
+    pub packet_loss: Option<f64>,
+    pub offline: Option<bool>,
+}
+
+/// Resolved network conditions handed to the drivers
+#[derive(Debug, Clone, PartialEq)]
+pub struct NetworkConditions {
+    /// Profile name, or `custom`
+    pub name: String,
+    pub offline: bool,
+    pub latency_ms: u64,
+    /// `None` means unlimited
+    pub download_kbps: Option<f64>,
+    pub upload_kbps: Option<f64>,
+    pub packet_loss: f64,
+}
+
+/// Named profiles: (names, latency ms, download kbps, upload kbps)
+const NETWORK_PROFILES: [(&[&str], u64, f64, f64); 4] = [
+    (&["edge", "2g"], 840, 240.0, 200.0),
+    (&["slow-3g"], 2000, 400.0, 400.0),
+    (&["3g", "fast-3g"], 560, 1475.0, 675.0),
+    (&["4g", "lte"], 50, 12000.0, 6000.0),
+];
+
+impl NetworkConditions {
+    /// Look up a named profile (`offline`, `edge`, `slow-3g`, `3g`, `4g`, `online`).
+    /// Case, spaces and underscores are ignored, so `Slow 3G` is `slow-3g`.
+    /// `wifi`/`data` mean no throttling; Android also switches radios for them.
+    pub fn profile(name: &str) -> anyhow::Result<Self> {
+        let name = name
+            .trim()
+            .to_lowercase()
+            .split([' ', '_', '-'])
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<_>>()
+            .join("-");
+        let mut conditions = Self {
+            name: name.clone(),
+            offline: false,
+            latency_ms: 0,
+            download_kbps: None,
+            upload_kbps: None,
+            packet_loss: 0.0,
+        };
+        match name.as_str() {
+            "offline" => conditions.offline = true,
+            "online" | "none" | "wifi" | "wifi-only" | "data" | "mobile" | "5g" => {}
+            _ => {
+                let (_, latency, download, upload) = NETWORK_PROFILES
+                    .iter()
+                    .find(|(names, ..)| names.contains(&name.as_str()))
+                    .ok_or_else(|| {
+                        anyhow::anyhow!(
+                            "Unknown network profile '{}' (offline, edge, slow-3g, 3g, 4g, online, or {{ latency, download, upload, packetLoss }})",
+                            name
+                        )
+                    })?;
+                conditions.latency_ms = *latency;
+                conditions.download_kbps = Some(*download);
+                conditions.upload_kbps = Some(*upload);
+            }
+        }
+        Ok(conditions)
+    }
+
+    /// Whether latency, bandwidth or loss need shaping (offline excluded)
+    pub fn is_throttled(&self) -> bool {
+        self.latency_ms > 0
+            || self.download_kbps.is_some()
+            || self.upload_kbps.is_some()
+            || self.packet_loss > 0.0
+    }
+}
+
+impl std::fmt::Display for NetworkConditions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name)?;
+        if self.offline {
+            return if self.name == "offline" {
+                Ok(())
+            } else {
+                write!(f, " (offline)")
+            };
+        }
+        if !self.is_throttled() {
+            return Ok(());
+        }
+        let kbps = |v: Option<f64>| v.map_or("∞".to_string(), |v| format!("{}kbps", v));
+        write!(
+            f,
+            " ({}ms, ↓{} ↑{}",
+            self.latency_ms,
+            kbps(self.download_kbps),
+            kbps(self.upload_kbps)
+        )?;
+        if self.packet_loss > 0.0 {
+            write!(f, ", {}% loss", self.packet_loss)?;
+        }
+        write!(f, ")")
+    }
+}
+
+impl NetworkConditionsInput {
+    pub fn resolve(&self) -> anyhow::Result<NetworkConditions> {
+        match self {
+            Self::Profile(name) => NetworkConditions::profile(name),
+            Self::Custom(params) => {
+                let mut conditions = match &params.profile {
+                    Some(name) => NetworkConditions::profile(name)?,
+                    None => NetworkConditions::profile("online")?,
+                };
+                conditions.name = "custom".to_string();
+                if let Some(latency) = params.latency {
+                    conditions.latency_ms = latency;
+                }
+                if let Some(download) = params.download {
+                    conditions.download_kbps = Some(download);
+                }
+                if let Some(upload) = params.upload {
+                    conditions.upload_kbps = Some(upload);
+                }
+                if let Some(loss) = params.packet_loss {
+                    if !(0.0..=100.0).contains(&loss) {
+                        anyhow::bail!("packetLoss must be a percentage (0-100), got {}", loss);
+                    }
+                    conditions.packet_loss = loss;
+                }
+                if let Some(offline) = params.offline {
+                    conditions.offline = offline;
+                }
+                Ok(conditions)
+            }
+        }
+    }
 }
 
+/// `waitForLocation` radius when no tolerance is given
+pub const DEFAULT_LOCATION_TOLERANCE_METERS: f64 = 50.0;
+
 // Parameter types
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -715,7 +1040,7 @@ pub struct TapParams {
     pub point: Option<String>, // "x,y" format
 
     #[serde(default)]
-    pub index: Option<u32>,
+    pub index: Option<i32>,
 
     /// Element class/type (e.g., "EditText", "Button")
     #[serde(default, alias = "type")]
@@ -743,6 +1068,19 @@ pub struct TapParams {
     #[serde(default)]
     pub exact: bool,
 
+    /// Match the most similar visible text instead of exact/substring text
+    /// (typos, localized or slightly varying labels)
+    #[serde(default)]
+    pub fuzzy: bool,
+
+    /// Minimum similarity (0.0-1.0) for `fuzzy` (default 0.8)
+    #[serde(default)]
+    pub fuzzy_threshold: Option<f64>,
+
+    /// Scroll to the element (like scrollUntilVisible) when it isn't on screen
+    #[serde(default)]
+    pub scroll: bool,
+
     // Relative position aliases (shorthand for relative param)
     #[serde(default, alias = "rightOf")]
     pub right_of: Option<RelativeAnchorInput>,
@@ -756,8 +1094,26 @@ pub struct TapParams {
     #[serde(default)]
     pub below: Option<RelativeAnchorInput>,
 
+    /// Relative shorthand anchored on a resource-id / accessibility identifier
+    #[serde(default)]
+    pub right_of_id: Option<String>,
+    #[serde(default)]
+    pub left_of_id: Option<String>,
+    #[serde(default)]
+    pub above_id: Option<String>,
+    #[serde(default)]
+    pub below_id: Option<String>,
+
     #[serde(default)]
     pub scrollable: Option<ScrollableParams>,
+
+    /// Ignore matches that lie outside the screen (scrolled away / not visible to user)
+    #[serde(default, alias = "visibleInViewport")]
+    pub only_visible: bool,
+
+    /// Alternative selectors: tap the first one that is visible
+    #[serde(default)]
+    pub any_of: Option<Vec<TapParamsInput>>,
 }
 
 /// Tap element by type and index (e.g., tap 2nd EditText)
@@ -786,6 +1142,9 @@ pub struct InputAtParams {
     pub index: u32,
     /// Text to input
     pub text: String,
+
+    #[serde(default)]
+    pub optional: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -804,6 +1163,9 @@ pub struct InputTextParams {
     /// Enable Unicode input mode (uses ADBKeyBoard, slower but reliable)
     #[serde(default)]
     pub unicode: bool,
+    /// Erase the focused field's current content before typing
+    #[serde(default)]
+    pub clear_first: bool,
 
     #[serde(default)]
     pub label: Option<String>,
@@ -823,6 +1185,7 @@ impl InputTextParamsInput {
             Self::String(text) => InputTextParams {
                 text,
                 unicode: false, // default: fast mode
+                clear_first: false,
                 label: None,
             },
             Self::Struct(s) => s,
@@ -842,6 +1205,13 @@ impl InputTextParamsInput {
             Self::Struct(p) => p.unicode,
         }
     }
+
+    pub fn clear_first(&self) -> bool {
+        match self {
+            Self::String(_) => false,
+            Self::Struct(p) => p.clear_first,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -975,7 +1345,16 @@ pub struct AssertParams {
     pub ocr: Option<OcrSelectorInput>,
 
     #[serde(default)]
-    pub index: Option<u32>,
+    pub index: Option<i32>,
+
+    /// Match the most similar visible text instead of exact/substring text
+    /// (typos, localized or slightly varying labels)
+    #[serde(default)]
+    pub fuzzy: bool,
+
+    /// Minimum similarity (0.0-1.0) for `fuzzy` (default 0.8)
+    #[serde(default)]
+    pub fuzzy_threshold: Option<f64>,
 
     #[serde(default)]
     pub timeout: Option<u64>,
@@ -992,11 +1371,37 @@ pub struct AssertParams {
     #[serde(default)]
     pub below: Option<RelativeAnchorInput>,
 
+    /// Relative shorthand anchored on a resource-id / accessibility identifier
+    #[serde(default)]
+    pub right_of_id: Option<String>,
+    #[serde(default)]
+    pub left_of_id: Option<String>,
+    #[serde(default)]
+    pub above_id: Option<String>,
+    #[serde(default)]
+    pub below_id: Option<String>,
+
     #[serde(default)]
     pub scrollable: Option<ScrollableParams>,
 
+    /// Ignore matches that lie outside the screen (scrolled away / not visible to user)
+    #[serde(default, alias = "visibleInViewport")]
+    pub only_visible: bool,
+
+    /// Alternative selectors: passes as soon as any of them is visible
+    #[serde(default)]
+    pub any_of: Option<Vec<AssertParamsInput>>,
+
     #[serde(default)]
     pub soft: bool,
+
+    /// Capture a screenshot when the assertion passes, attached to the report
+    #[serde(default)]
+    pub screenshot: bool,
+
+    /// Scroll to the element (like scrollUntilVisible) when it isn't on screen
+    #[serde(default)]
+    pub scroll: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -1036,6 +1441,71 @@ fn default_max_retries() -> u32 {
     3
 }
 
+/// Branches of `waitForAny`, checked in order on every poll
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WaitForAnyParams {
+    pub options: Vec<WaitForAnyOption>,
+
+    /// Defaults to the flow's `defaultTimeout`
+    #[serde(default)]
+    pub timeout: Option<u64>,
+
+    /// Continue without running a branch when nothing appears
+    #[serde(default)]
+    pub optional: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WaitForAnyOption {
+    pub when: AssertParamsInput,
+
+    #[serde(default)]
+    pub then: Vec<TestCommand>,
+}
+
+/// Assertions checked as one step: a failing one doesn't stop the others, the
+/// block fails at its end with all failures (soft when `soft: true`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AssertAllParams {
+    pub assertions: Vec<TestCommand>,
+
+    #[serde(default)]
+    pub soft: bool,
+
+    #[serde(default)]
+    pub label: Option<String>,
+}
+
+/// Run commands (or a subflow) once per item of a list or data file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ForEachParams {
+    /// Inline items; objects are bound as JSON so `${item.field}` works
+    #[serde(default)]
+    pub items: Vec<serde_json::Value>,
+
+    /// CSV (one object per row) or JSON array file, relative to the flow
+    #[serde(default)]
+    pub data_file: Option<String>,
+
+    /// Variable bound to the current item (default: "item")
+    #[serde(default = "default_for_each_var", rename = "as")]
+    pub var_name: String,
+
+    #[serde(default)]
+    pub commands: Vec<TestCommand>,
+
+    /// Subflow file to run for each item instead of inline commands
+    #[serde(default)]
+    pub flow: Option<String>,
+}
+
+fn default_for_each_var() -> String {
+    "item".to_string()
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct RunFlowParams {
@@ -1090,6 +1560,10 @@ pub struct SetVarParams {
 
     /// Variable value (can use ${var} syntax for substitution)
     pub value: String,
+
+    /// Evaluate `value` as a JavaScript expression and store the result
+    #[serde(default)]
+    pub eval: bool,
 }
 
 /// Assert a variable has expected value
@@ -1099,8 +1573,113 @@ pub struct AssertVarParams {
     /// Variable name
     pub name: String,
 
-    /// Expected value
-    pub expected: String,
+    /// How `expected` is compared with the variable; `==` when omitted
+    #[serde(default)]
+    pub operator: VarOperator,
+
+    /// Expected value (can use ${var} syntax for substitution)
+    #[serde(alias = "value")]
+    pub expected: ScalarValue,
+}
+
+/// YAML scalar kept as written, so `expected: 3` works as well as `expected: "3"`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ScalarValue {
+    String(String),
+    Integer(i64),
+    Float(f64),
+    Bool(bool),
+}
+
+impl std::fmt::Display for ScalarValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::String(s) => write!(f, "{}", s),
+            Self::Integer(n) => write!(f, "{}", n),
+            Self::Float(n) => write!(f, "{}", n),
+            Self::Bool(b) => write!(f, "{}", b),
+        }
+    }
+}
+
+/// Comparison used by `assertVar`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum VarOperator {
+    #[default]
+    #[serde(rename = "==", alias = "eq", alias = "equals")]
+    Eq,
+    #[serde(rename = "!=", alias = "ne", alias = "notEquals")]
+    Ne,
+    #[serde(rename = "equalsNumber")]
+    NumEq,
+    #[serde(rename = ">", alias = "gt")]
+    Gt,
+    #[serde(rename = ">=", alias = "gte")]
+    Ge,
+    #[serde(rename = "<", alias = "lt")]
+    Lt,
+    #[serde(rename = "<=", alias = "lte")]
+    Le,
+    #[serde(rename = "contains")]
+    Contains,
+    #[serde(rename = "matches", alias = "regex")]
+    Matches,
+}
+
+impl VarOperator {
+    /// Whether `expected` is compared as text; an unquoted decimal loses its
+    /// written form (`3.10` reads as `3.1`), so these need it quoted
+    pub fn is_textual(self) -> bool {
+        matches!(self, Self::Eq | Self::Ne | Self::Contains | Self::Matches)
+    }
+
+    /// Compare `actual` with `expected`. `==`/`!=` compare text exactly and
+    /// `equalsNumber` numbers only; ordering operators compare numerically when
+    /// both sides parse as numbers, as strings otherwise. `matches` takes
+    /// `expected` as a regex.
+    pub fn compare(self, actual: &str, expected: &str) -> anyhow::Result<bool> {
+        let numbers = actual
+            .trim()
+            .parse::<f64>()
+            .ok()
+            .zip(expected.trim().parse::<f64>().ok());
+        let ordering = match numbers {
+            Some((a, e)) => a.partial_cmp(&e),
+            None => Some(actual.cmp(expected)),
+        };
+        use std::cmp::Ordering::*;
+        Ok(match self {
+            Self::Eq => actual == expected,
+            Self::Ne => actual != expected,
+            Self::NumEq => numbers.is_some() && ordering == Some(Equal),
+            Self::Gt => ordering == Some(Greater),
+            Self::Ge => matches!(ordering, Some(Greater | Equal)),
+            Self::Lt => ordering == Some(Less),
+            Self::Le => matches!(ordering, Some(Less | Equal)),
+            Self::Contains => actual.contains(expected),
+            Self::Matches => regex::Regex::new(expected)
+                .map_err(|e| anyhow::anyhow!("Invalid regex '{}': {}", expected, e))?
+                .is_match(actual),
+        })
+    }
+}
+
+impl std::fmt::Display for VarOperator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let symbol = match self {
+            Self::Eq => "==",
+            Self::Ne => "!=",
+            Self::NumEq => "equalsNumber",
+            Self::Gt => ">",
+            Self::Ge => ">=",
+            Self::Lt => "<",
+            Self::Le => "<=",
+            Self::Contains => "contains",
+            Self::Matches => "matches",
+        };
+        write!(f, "{}", symbol)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -1136,7 +1715,10 @@ impl RecordingParamsInput {
     pub fn into_inner(self) -> RecordingParams {
         match self {
             Self::Struct(s) => s,
-            Self::String(s) => RecordingParams { path: s },
+            Self::String(s) => RecordingParams {
+                path: s,
+                ..Default::default()
+            },
         }
     }
 }
@@ -1145,6 +1727,32 @@ impl RecordingParamsInput {
 #[serde(rename_all = "camelCase")]
 pub struct RecordingParams {
     pub path: String,
+    /// Bits per second: `4000000`, `"4M"` or `"800K"` (Android)
+    #[serde(default)]
+    pub bitrate: Option<ScalarInput>,
+    /// Output size `WIDTHxHEIGHT` (Android)
+    #[serde(default)]
+    pub size: Option<String>,
+    /// Stop after this many seconds, at most 180 (Android)
+    #[serde(default, alias = "timeLimitSecs")]
+    pub time_limit: Option<u32>,
+}
+
+/// YAML scalar given either as a number or as text
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ScalarInput {
+    Number(u64),
+    Text(String),
+}
+
+impl std::fmt::Display for ScalarInput {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Number(n) => write!(f, "{}", n),
+            Self::Text(s) => f.write_str(s),
+        }
+    }
 }
 
 // GIF Recording params
@@ -1224,6 +1832,20 @@ pub struct BuildGifParams {
     /// Specific loop count (overrides loop_gif)
     #[serde(default)]
     pub loop_count: Option<u16>,
+
+    /// Output format: gif (default), webp or apng
+    #[serde(default)]
+    pub format: AnimationFormat,
+}
+
+/// Encoding used for animated captures
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum AnimationFormat {
+    #[default]
+    Gif,
+    Webp,
+    Apng,
 }
 
 fn default_gif_delay() -> u32 {
@@ -1254,6 +1876,10 @@ pub struct StartGifCaptureParams {
     /// Resize width for captured frames
     #[serde(default)]
     pub width: Option<u32>,
+
+    /// Crop region applied to every frame: "left%,top%,width%,height%"
+    #[serde(default)]
+    pub crop: Option<String>,
 }
 
 fn default_capture_interval() -> u64 {
@@ -1281,6 +1907,10 @@ pub struct StopGifCaptureParams {
     /// Loop count (None = infinite)
     #[serde(default)]
     pub loop_count: Option<u16>,
+
+    /// Output format: gif (default), webp or apng
+    #[serde(default)]
+    pub format: AnimationFormat,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -1304,6 +1934,41 @@ pub struct NavigateParams {
     pub url: String,
 }
 
+/// Assert the current page URL (web)
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct AssertUrlParams {
+    /// URL must match exactly
+    #[serde(default)]
+    pub equals: Option<String>,
+    /// URL must contain this substring
+    #[serde(default)]
+    pub contains: Option<String>,
+    /// URL must match this regex
+    #[serde(default)]
+    pub matches: Option<String>,
+}
+
+/// Save the current page URL (web) into a variable
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CaptureUrlParams {
+    /// Variable name
+    #[serde(alias = "name")]
+    pub into: String,
+}
+
+/// Run JavaScript inside the browser page (web)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExecuteJsParams {
+    /// Expression or function body passed to Playwright `evaluate`
+    pub script: String,
+    /// Variable that receives the result
+    #[serde(default, alias = "name")]
+    pub into: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct NetworkParams {
@@ -1313,12 +1978,220 @@ pub struct NetworkParams {
     pub data: Option<bool>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InstallAppParams {
+    pub path: String,
+    /// Grant all runtime permissions on install (Android `-g`)
+    #[serde(default = "default_true")]
+    pub grant_all: bool,
+    /// Allow installing a lower version code (Android `-d`)
+    #[serde(default)]
+    pub allow_downgrade: bool,
+    /// Replace an existing install, keeping its data (Android `-r`)
+    #[serde(default = "default_true")]
+    pub reinstall: bool,
+}
+
+impl InstallAppParams {
+    /// Params for the short `installApp: <path>` form
+    pub fn from_path(path: String) -> Self {
+        Self {
+            path,
+            grant_all: true,
+            allow_downgrade: false,
+            reinstall: true,
+        }
+    }
+}
+
+/// Save or restore an app's data directory under `<output>/snapshots`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AppDataSnapshotParams {
+    /// Snapshot name
+    pub name: String,
+    /// App to snapshot (defaults to the flow appId)
+    #[serde(default, alias = "appId")]
+    pub app: Option<String>,
+}
+
+/// Assert that a notification is posted, optionally tapping it
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct AssertNotificationParams {
+    /// Title must contain this (case-insensitive)
+    #[serde(default)]
+    pub title: Option<String>,
+    /// Body text must contain this (case-insensitive)
+    #[serde(default)]
+    pub text: Option<String>,
+    /// Package / bundle id that posted it
+    #[serde(default, alias = "appId")]
+    pub app: Option<String>,
+    /// Open the shade and tap the matched notification
+    #[serde(default)]
+    pub tap: bool,
+    /// How long to wait for the notification (defaults to defaultTimeout)
+    #[serde(default, alias = "timeoutMs")]
+    pub timeout: Option<u64>,
+}
+
+/// Assert that a transient toast message appears
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AssertToastParams {
+    /// Text the toast must contain (case-insensitive)
+    pub text: String,
+    /// How long to watch for the toast (defaults to defaultTimeout)
+    #[serde(default, alias = "timeoutMs")]
+    pub timeout: Option<u64>,
+}
+
+/// Inject an SMS on an emulator and/or read the latest one into a variable
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SendSmsParams {
+    /// Sender phone number
+    #[serde(default = "default_sms_sender")]
+    pub from: String,
+    /// Message text; omit to only read the inbox
+    #[serde(default)]
+    pub body: Option<String>,
+    /// Variable that receives the latest inbox message (or the `regex` match)
+    #[serde(default, alias = "var")]
+    pub save_to: Option<String>,
+    /// Extract part of the message, e.g. "(\\d{6})"; the first capture group wins
+    #[serde(default)]
+    pub regex: Option<String>,
+}
+
+fn default_sms_sender() -> String {
+    "5551234".to_string()
+}
+
+/// Override the device clock (`reset` restores network time)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetDateTimeParams {
+    /// Local date/time, e.g. "2026-01-15 09:30", "2026-01-15T09:30:00", or "reset"
+    pub value: String,
+}
+
+impl SetDateTimeParams {
+    /// Parse the value; `None` means reset to automatic time
+    pub fn parse(value: &str) -> anyhow::Result<Option<chrono::NaiveDateTime>> {
+        let value = value.trim();
+        if matches!(value.to_lowercase().as_str(), "reset" | "auto" | "network") {
+            return Ok(None);
+        }
+
+        const FORMATS: &[&str] = &[
+            "%Y-%m-%dT%H:%M:%S",
+            "%Y-%m-%d %H:%M:%S",
+            "%Y-%m-%dT%H:%M",
+            "%Y-%m-%d %H:%M",
+        ];
+        for fmt in FORMATS {
+            if let Ok(dt) = chrono::NaiveDateTime::parse_from_str(value, fmt) {
+                return Ok(Some(dt));
+            }
+        }
+        if let Ok(date) = chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+            return Ok(date.and_hms_opt(0, 0, 0));
+        }
+        anyhow::bail!(
+            "Invalid setDateTime value '{}': expected YYYY-MM-DD[ HH:MM[:SS]] or 'reset'",
+            value
+        )
+    }
+}
+
+/// Grant or revoke a single runtime permission mid-flow
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetPermissionParams {
+    /// App to update (defaults to the flow appId)
+    #[serde(default, alias = "app")]
+    pub app_id: Option<String>,
+    /// Permission name (same short names as `launchApp.permissions`)
+    pub permission: String,
+    #[serde(default)]
+    pub state: PermissionState,
+}
+
+/// Target state of `setPermission`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum PermissionState {
+    #[default]
+    Allow,
+    Deny,
+}
+
+impl PermissionState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Allow => "allow",
+            Self::Deny => "deny",
+        }
+    }
+}
+
+impl TryFrom<String> for PermissionState {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.trim().to_lowercase().as_str() {
+            "allow" | "grant" | "granted" => Ok(Self::Allow),
+            "deny" | "denied" | "revoke" | "revoked" => Ok(Self::Deny),
+            _ => Err(format!(
+                "Invalid permission state '{}'. Use allow (grant, granted) or deny (denied, revoke, revoked)",
+                value
+            )),
+        }
+    }
+}
+
+impl From<PermissionState> for String {
+    fn from(state: PermissionState) -> Self {
+        state.as_str().to_string()
+    }
+}
+
+/// Assert the installed version of an app
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AssertAppVersionParams {
+    /// App to check (defaults to the flow appId)
+    #[serde(default, alias = "app")]
+    pub app_id: Option<String>,
+    /// Exact version name (e.g. "2.4.1")
+    #[serde(default)]
+    pub equals: Option<String>,
+    /// Minimum version name, compared component by component
+    #[serde(default)]
+    pub min_version: Option<String>,
+    /// Exact build number (Android versionCode / iOS CFBundleVersion)
+    #[serde(default)]
+    pub build_number: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct BackgroundAppParams {
     pub app_id: Option<String>,
     #[serde(default = "default_background_duration")]
     pub duration_ms: u64,
+    /// Fail unless the app is back in the foreground after resuming
+    #[serde(default)]
+    pub verify_resume: bool,
+    /// Element that must be visible once the app resumed (implies verifyResume)
+    #[serde(default)]
+    pub wait_for: Option<TapParams>,
+    /// How long to wait for the app (and `waitFor` element) to come back
+    #[serde(default)]
+    pub resume_timeout_ms: Option<u64>,
 }
 
 fn default_background_duration() -> u64 {
@@ -1390,7 +2263,7 @@ pub struct AssertVisibleParams {
     #[serde(default)]
     pub image: Option<String>,
     #[serde(default)]
-    pub index: Option<usize>,
+    pub index: Option<i32>,
     #[serde(default)]
     pub scrollable: Option<bool>,
 
@@ -1407,6 +2280,153 @@ pub struct AssertVisibleParams {
     pub selector: Option<String>,
 }
 
+/// Whether `name` is a built-in or registered plugin command type (case-insensitive)
+pub fn is_command_kind(name: &str) -> bool {
+    COMMAND_KINDS.iter().any(|k| k.eq_ignore_ascii_case(name)) || crate::plugin::is_registered(name)
+}
+
+/// Declares each built-in command's YAML name once, generating both
+/// [`COMMAND_KINDS`] and [`TestCommand::kind`] so the two can't drift apart
+macro_rules! command_kinds {
+    ($($variant:pat => $name:literal,)*) => {
+        /// Every built-in [`TestCommand::kind`]; `--skip-command` names are checked against it
+        pub const COMMAND_KINDS: &[&str] = &[$($name),*];
+
+        impl TestCommand {
+            /// YAML command name of the command type (`assertScreenshot`, `tapOn`, plugin name),
+            /// without labels or params; used by `--skip-command`
+            pub fn kind(&self) -> &str {
+                match self {
+                    $($variant => $name,)*
+                    TestCommand::Plugin(p) => &p.name,
+                    TestCommand::Timed(t) => t.command.kind(),
+                }
+            }
+        }
+    };
+}
+
+command_kinds! {
+    TestCommand::LaunchApp(_) => "launchApp",
+    TestCommand::StopApp => "stopApp",
+    TestCommand::Find(_) => "find",
+    TestCommand::TapOn(_) => "tapOn",
+    TestCommand::LongPressOn(_) => "longPressOn",
+    TestCommand::DoubleTapOn(_) => "doubleTapOn",
+    TestCommand::InputText(_) => "inputText",
+    TestCommand::EraseText(_) => "eraseText",
+    TestCommand::HideKeyboard => "hideKeyboard",
+    TestCommand::RightClick(_) => "rightClick",
+    TestCommand::TapAt(_) => "tapAt",
+    TestCommand::InputAt(_) => "inputAt",
+    TestCommand::SwipeLeft => "swipeLeft",
+    TestCommand::SwipeRight => "swipeRight",
+    TestCommand::SwipeUp => "swipeUp",
+    TestCommand::SwipeDown => "swipeDown",
+    TestCommand::ManualScroll(_) => "swipe",
+    TestCommand::ScrollUntilVisible(_) => "scrollUntilVisible",
+    TestCommand::AssertVisible(_) => "assertVisible",
+    TestCommand::AssertNotVisible(_) => "assertNotVisible",
+    TestCommand::WaitUntilVisible(_) => "waitUntilVisible",
+    TestCommand::WaitUntilNotVisible(_) => "waitUntilNotVisible",
+    TestCommand::SendLarkMessage(_) => "sendLarkMessage",
+    TestCommand::WaitForAnimationToEnd => "waitForAnimationToEnd",
+    TestCommand::Wait(_) => "wait",
+    TestCommand::Repeat(_) => "repeat",
+    TestCommand::Retry(_) => "retry",
+    TestCommand::ForEach(_) => "forEach",
+    TestCommand::WaitForAny(_) => "waitForAny",
+    TestCommand::AssertAll(_) => "assertAll",
+    TestCommand::Break => "break",
+    TestCommand::Continue => "continue",
+    TestCommand::StopFlow => "stopFlow",
+    TestCommand::RunFlow(_) => "runFlow",
+    TestCommand::SetVar(_) => "setVar",
+    TestCommand::AssertVar(_) => "assertVar",
+    TestCommand::OpenLink(_) => "openLink",
+    TestCommand::AssertScreenshot(_) => "assertScreenshot",
+    TestCommand::TakeScreenshot(_) => "screenshot",
+    TestCommand::StartRecording(_) => "startRecording",
+    TestCommand::StopRecording => "stopRecording",
+    TestCommand::ExportReport(_) => "exportReport",
+    TestCommand::Back => "back",
+    TestCommand::PressHome => "pressHome",
+    TestCommand::Generate(_) => "generate",
+    TestCommand::HttpRequest(_) => "httpRequest",
+    TestCommand::RunScript(_) => "runScript",
+    TestCommand::Conditional(_) => "conditional",
+    TestCommand::Navigate(_) => "navigate",
+    TestCommand::Click(_) => "click",
+    TestCommand::Type(_) => "type",
+    TestCommand::AssertUrl(_) => "assertUrl",
+    TestCommand::CaptureUrl(_) => "captureUrl",
+    TestCommand::ExecuteJs(_) => "executeScript",
+    TestCommand::MockLocation(_) => "mockLocation",
+    TestCommand::StopMockLocation => "stopMockLocation",
+    TestCommand::MockLocationControl(_) => "mockLocationControl",
+    TestCommand::AssertColor(_) => "assertColor",
+    TestCommand::CaptureGifFrame(_) => "captureGifFrame",
+    TestCommand::BuildGif(_) => "buildGif",
+    TestCommand::StartGifCapture(_) => "startGifCapture",
+    TestCommand::StopGifCapture(_) => "stopGifCapture",
+    TestCommand::RotateScreen(_) => "rotate",
+    TestCommand::PressKey(_) => "press",
+    TestCommand::PushFile(_) => "pushFile",
+    TestCommand::PullFile(_) => "pullFile",
+    TestCommand::ClearAppData(_) => "clearAppData",
+    TestCommand::SetClipboard(_) => "setClipboard",
+    TestCommand::GetClipboard(_) => "getClipboard",
+    TestCommand::AssertClipboard(_) => "assertClipboard",
+    TestCommand::AssertTrue(_) => "assertTrue",
+    TestCommand::EvalScript(_) => "evalScript",
+    TestCommand::CopyTextFrom(_) => "copyTextFrom",
+    TestCommand::PasteText => "pasteText",
+    TestCommand::InputRandomEmail => "inputRandomEmail",
+    TestCommand::InputRandomNumber(_) => "inputRandomNumber",
+    TestCommand::InputRandomPersonName => "inputRandomPersonName",
+    TestCommand::InputRandomText(_) => "inputRandomText",
+    TestCommand::ExtendedWaitUntil(_) => "extendedWaitUntil",
+    TestCommand::DbQuery(_) => "dbQuery",
+    TestCommand::SetNetwork(_) => "setNetwork",
+    TestCommand::SetWifi(_) => "setWifi",
+    TestCommand::SetMobileData(_) => "setMobileData",
+    TestCommand::SetBluetooth(_) => "setBluetooth",
+    TestCommand::ToggleAirplaneMode => "airplaneMode",
+    TestCommand::OpenNotifications => "openNotifications",
+    TestCommand::OpenQuickSettings => "openQuickSettings",
+    TestCommand::SetVolume(_) => "setVolume",
+    TestCommand::LockDevice => "lockDevice",
+    TestCommand::UnlockDevice => "unlockDevice",
+    TestCommand::InstallApp(_) => "installApp",
+    TestCommand::UninstallApp(_) => "uninstallApp",
+    TestCommand::BackgroundApp(_) => "backgroundApp",
+    TestCommand::AssertAppVersion(_) => "assertAppVersion",
+    TestCommand::SetPermission(_) => "setPermission",
+    TestCommand::TapSystemButton(_) => "tapSystemButton",
+    TestCommand::SetDateTime(_) => "setDateTime",
+    TestCommand::SendSms(_) => "sendSms",
+    TestCommand::AssertToast(_) => "assertToast",
+    TestCommand::AssertNotification(_) => "assertNotification",
+    TestCommand::SnapshotAppData(_) => "snapshotAppData",
+    TestCommand::RestoreAppData(_) => "restoreAppData",
+    TestCommand::SetOrientation(_) => "setOrientation",
+    TestCommand::WaitForLocation(_) => "waitForLocation",
+    TestCommand::WaitForMockCompletion(_) => "waitForMockCompletion",
+    TestCommand::StartProfiling(_) => "startProfiling",
+    TestCommand::StopProfiling(_) => "stopProfiling",
+    TestCommand::AssertPerformance(_) => "assertPerformance",
+    TestCommand::SetCpuThrottling(_) => "setCpuThrottling",
+    TestCommand::SetNetworkConditions(_) => "setNetworkConditions",
+    TestCommand::SelectDisplay(_) => "selectDisplay",
+    TestCommand::SetLocale(_) => "setLocale",
+    TestCommand::PlayMedia(_) => "playMedia",
+    TestCommand::StopMedia => "stopMedia",
+    TestCommand::StartAudioCapture(_) => "startAudioCapture",
+    TestCommand::StopAudioCapture => "stopAudioCapture",
+    TestCommand::VerifyAudioDucking(_) => "verifyAudioDucking",
+    TestCommand::AssertAudioPlaying(_) => "assertAudioPlaying",
+}
+
 impl TestCommand {
     /// Get a display name for the command
     pub fn display_name(&self) -> String {
@@ -1651,6 +2671,15 @@ impl TestCommand {
                 }
             }
             TestCommand::Retry(p) => format!("retry(max: {})", p.max_retries),
+            TestCommand::WaitForAny(p) => format!("waitForAny({} options)", p.options.len()),
+            TestCommand::AssertAll(p) => match &p.label {
+                Some(label) => label.clone(),
+                None => format!("assertAll({} assertions)", p.assertions.len()),
+            },
+            TestCommand::ForEach(p) => match &p.data_file {
+                Some(file) => format!("forEach({} in \"{}\")", p.var_name, file),
+                None => format!("forEach({} in {} items)", p.var_name, p.items.len()),
+            },
             TestCommand::RunFlow(p_input) => {
                 let p = p_input.clone().into_inner();
                 if let Some(path) = &p.path {
@@ -1666,6 +2695,11 @@ impl TestCommand {
             TestCommand::StopRecording => "stopRecording".to_string(),
             TestCommand::ExportReport(_) => "exportReport".to_string(),
             TestCommand::Back => "back".to_string(),
+            TestCommand::Break => "break".to_string(),
+            TestCommand::Continue => "continue".to_string(),
+            TestCommand::StopFlow => "stopFlow".to_string(),
+            TestCommand::Plugin(p) => p.name.clone(),
+            TestCommand::Timed(t) => t.command.display_name(),
             TestCommand::PressHome => "pressHome".to_string(),
             TestCommand::Navigate(_) => "navigate".to_string(),
             TestCommand::Click(_) => "click".to_string(),
@@ -1673,6 +2707,24 @@ impl TestCommand {
                 let sel = p.selector.as_deref().unwrap_or("focused");
                 format!("type(\"{}\", \"{}\")", p.text, sel)
             }
+            TestCommand::AssertUrl(p) => {
+                let mut parts = Vec::new();
+                if let Some(v) = &p.equals {
+                    parts.push(format!("== {}", v));
+                }
+                if let Some(v) = &p.contains {
+                    parts.push(format!("contains {}", v));
+                }
+                if let Some(v) = &p.matches {
+                    parts.push(format!("matches {}", v));
+                }
+                format!("assertUrl({})", parts.join(", "))
+            }
+            TestCommand::CaptureUrl(p) => format!("captureUrl({})", p.into),
+            TestCommand::ExecuteJs(p) => match &p.into {
+                Some(var) => format!("executeScript({} = {})", var, p.script),
+                None => format!("executeScript({})", p.script),
+            },
             TestCommand::TapAt(p) => {
                 format!("tapAt({}[{}])", p.element_type, p.index)
             }
@@ -1693,10 +2745,14 @@ impl TestCommand {
                 }
             }
             TestCommand::SetVar(p) => {
-                format!("setVar({} = \"{}\")", p.name, p.value)
+                if p.eval {
+                    format!("setVar({} = {})", p.name, p.value)
+                } else {
+                    format!("setVar({} = \"{}\")", p.name, p.value)
+                }
             }
             TestCommand::AssertVar(p) => {
-                format!("assertVar({} == \"{}\")", p.name, p.expected)
+                format!("assertVar({} {} \"{}\")", p.name, p.operator, p.expected)
             }
             TestCommand::Generate(p) => {
                 format!("generate({}: {})", p.name, p.data_type)
@@ -1842,14 +2898,46 @@ impl TestCommand {
                 }
                 format!("setNetwork({})", parts.join(", "))
             }
+            TestCommand::SetWifi(on) => format!("setWifi({})", on),
+            TestCommand::SetMobileData(on) => format!("setMobileData({})", on),
+            TestCommand::SetBluetooth(on) => format!("setBluetooth({})", on),
             TestCommand::ToggleAirplaneMode => "airplaneMode".to_string(),
             TestCommand::OpenNotifications => "openNotifications".to_string(),
             TestCommand::OpenQuickSettings => "openQuickSettings".to_string(),
             TestCommand::SetVolume(v) => format!("setVolume({})", v),
             TestCommand::LockDevice => "lockDevice".to_string(),
             TestCommand::UnlockDevice => "unlockDevice".to_string(),
-            TestCommand::InstallApp(path) => format!("installApp(\"{}\")", path),
+            TestCommand::InstallApp(p) => format!("installApp(\"{}\")", p.path),
             TestCommand::UninstallApp(pkg) => format!("uninstallApp(\"{}\")", pkg),
+            TestCommand::AssertAppVersion(p) => {
+                let mut parts = vec![p.app_id.as_deref().unwrap_or("current").to_string()];
+                if let Some(v) = &p.equals {
+                    parts.push(format!("== {}", v));
+                }
+                if let Some(v) = &p.min_version {
+                    parts.push(format!(">= {}", v));
+                }
+                if let Some(v) = &p.build_number {
+                    parts.push(format!("build {}", v));
+                }
+                format!("assertAppVersion({})", parts.join(", "))
+            }
+            TestCommand::SnapshotAppData(p) => format!("snapshotAppData({})", p.name),
+            TestCommand::RestoreAppData(p) => format!("restoreAppData({})", p.name),
+            TestCommand::AssertNotification(p) => {
+                let target = p.title.as_deref().or(p.text.as_deref()).unwrap_or("any");
+                format!("assertNotification({})", target)
+            }
+            TestCommand::AssertToast(p) => format!("assertToast({})", p.text),
+            TestCommand::SendSms(p) => match &p.body {
+                Some(body) => format!("sendSms({}: {})", p.from, body),
+                None => "readSms".to_string(),
+            },
+            TestCommand::SetDateTime(p) => format!("setDateTime({})", p.value),
+            TestCommand::TapSystemButton(b) => format!("tapSystemButton({})", b),
+            TestCommand::SetPermission(p) => {
+                format!("setPermission({} = {})", p.permission, p.state.as_str())
+            }
             TestCommand::BackgroundApp(p) => format!(
                 "backgroundApp({}, {}ms)",
                 p.app_id.as_deref().unwrap_or("current"),
@@ -1857,9 +2945,14 @@ impl TestCommand {
             ),
             TestCommand::SetOrientation(p) => format!("setOrientation({:?})", p.mode),
             TestCommand::WaitForLocation(p) => {
+                let tolerance = match (p.tolerance_meters, p.tolerance) {
+                    (Some(m), _) => format!("{:.1}m", m),
+                    (None, Some(t)) => format!("{:.1}", t),
+                    (None, None) => format!("{:.1}m", DEFAULT_LOCATION_TOLERANCE_METERS),
+                };
                 format!(
-                    "waitForLocation({:.4}, {:.4}, tol: {:.1})",
-                    p.lat, p.lon, p.tolerance
+                    "waitForLocation({:.4}, {:.4}, tol: {})",
+                    p.lat, p.lon, tolerance
                 )
             }
             TestCommand::WaitForMockCompletion(p) => {
@@ -1877,9 +2970,19 @@ impl TestCommand {
                 format!("assertPerformance({} check {})", p.metric, p.limit)
             }
             TestCommand::SetCpuThrottling(rate) => format!("setCpuThrottling({}x)", rate),
-            TestCommand::SetNetworkConditions(profile) => {
+            TestCommand::SetNetworkConditions(NetworkConditionsInput::Profile(profile)) => {
                 format!("setNetworkConditions(\"{}\")", profile)
             }
+            TestCommand::SetNetworkConditions(NetworkConditionsInput::Custom(params)) => {
+                format!(
+                    "setNetworkConditions(custom{})",
+                    params
+                        .profile
+                        .as_ref()
+                        .map(|p| format!(" from {}", p))
+                        .unwrap_or_default()
+                )
+            }
             TestCommand::SelectDisplay(id) => format!("selectDisplay({})", id),
             TestCommand::SetLocale(locale) => format!("setLocale(\"{}\")", locale),
             TestCommand::PlayMedia(p) => format!("playMedia(\"{}\")", p.file),
@@ -1887,6 +2990,11 @@ impl TestCommand {
             TestCommand::StartAudioCapture(p) => format!("startAudioCapture({}ms)", p.duration),
             TestCommand::StopAudioCapture => "stopAudioCapture".to_string(),
             TestCommand::VerifyAudioDucking(_) => "verifyAudioDucking".to_string(),
+            TestCommand::AssertAudioPlaying(p) => format!(
+                "assertAudioPlaying({}, {})",
+                p.app.as_deref().unwrap_or("any app"),
+                p.playing
+            ),
         }
     }
 }
@@ -1999,6 +3107,72 @@ pub enum SpeedMode {
     Linear,
     /// Speed with random noise within +/- speedNoise km/h
     Noise,
+    /// Ramp up from standstill at the start of the route
+    Accelerate,
+    /// Slow down to a standstill at the end of the route
+    Decelerate,
+    /// Stop every `stopEvery` meters for `stopDurationMs` (traffic lights)
+    Stops,
+}
+
+/// Speed mode plus the parameters its curve needs
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DriveProfile {
+    pub mode: SpeedMode,
+    /// Noise range in km/h (noise mode)
+    pub noise: Option<f64>,
+    /// Acceleration/braking rate in m/s² (accelerate, decelerate, stops)
+    pub acceleration: Option<f64>,
+    /// Distance between stops in meters (stops mode)
+    pub stop_every: Option<f64>,
+    /// How long each stop lasts in milliseconds (stops mode)
+    pub stop_duration_ms: Option<u64>,
+}
+
+impl DriveProfile {
+    pub const DEFAULT_NOISE_KMH: f64 = 5.0;
+    pub const DEFAULT_ACCELERATION: f64 = 2.0;
+    pub const DEFAULT_STOP_EVERY_M: f64 = 500.0;
+    pub const DEFAULT_STOP_DURATION_MS: u64 = 20_000;
+
+    pub fn noise(&self) -> f64 {
+        self.noise.unwrap_or(Self::DEFAULT_NOISE_KMH)
+    }
+
+    pub fn acceleration(&self) -> f64 {
+        self.acceleration
+            .filter(|a| *a > 0.0)
+            .unwrap_or(Self::DEFAULT_ACCELERATION)
+    }
+
+    pub fn stop_every(&self) -> f64 {
+        self.stop_every
+            .filter(|d| *d > 0.0)
+            .unwrap_or(Self::DEFAULT_STOP_EVERY_M)
+    }
+
+    pub fn stop_duration_ms(&self) -> u64 {
+        self.stop_duration_ms
+            .unwrap_or(Self::DEFAULT_STOP_DURATION_MS)
+    }
+}
+
+impl std::fmt::Display for DriveProfile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.mode {
+            SpeedMode::Linear => write!(f, "Linear"),
+            SpeedMode::Noise => write!(f, "Noise ±{:.1}", self.noise()),
+            SpeedMode::Accelerate => write!(f, "Accelerate {:.1} m/s²", self.acceleration()),
+            SpeedMode::Decelerate => write!(f, "Decelerate {:.1} m/s²", self.acceleration()),
+            SpeedMode::Stops => write!(
+                f,
+                "Stops every {:.0}m for {:.1}s, {:.1} m/s²",
+                self.stop_every(),
+                self.stop_duration_ms() as f64 / 1000.0,
+                self.acceleration()
+            ),
+        }
+    }
 }
 
 /// Mock location parameters for GPS simulation
@@ -2016,7 +3190,7 @@ pub struct MockLocationParams {
     #[serde(default)]
     pub speed: Option<f64>,
 
-    /// Speed simulation mode: linear (constant) or noise (variable)
+    /// Speed simulation mode: linear, noise, accelerate, decelerate or stops
     #[serde(default)]
     pub speed_mode: SpeedMode,
 
@@ -2024,6 +3198,18 @@ pub struct MockLocationParams {
     #[serde(default)]
     pub speed_noise: Option<f64>,
 
+    /// Acceleration/braking rate in m/s² for accelerate, decelerate and stops (default: 2.0)
+    #[serde(default)]
+    pub acceleration: Option<f64>,
+
+    /// Distance between stops in meters for the stops mode (default: 500)
+    #[serde(default)]
+    pub stop_every: Option<f64>,
+
+    /// Duration of each stop in milliseconds for the stops mode (default: 20000)
+    #[serde(default)]
+    pub stop_duration_ms: Option<u64>,
+
     /// Loop the route continuously
     #[serde(default, rename = "loop")]
     pub loop_route: bool,
@@ -2039,6 +3225,10 @@ pub struct MockLocationParams {
     /// Fixed altitude in meters (overrides altitude from file)
     #[serde(default)]
     pub altitude: Option<f64>,
+
+    /// Resample the route every N meters so playback is smooth whatever the file's density
+    #[serde(default)]
+    pub interpolate: Option<f64>,
 }
 
 /// Mock location control parameters for dynamic speed adjustment
@@ -2087,15 +3277,31 @@ impl MockLocationParamsInput {
                 speed: None,
                 speed_mode: SpeedMode::Linear,
                 speed_noise: None,
+                acceleration: None,
+                stop_every: None,
+                stop_duration_ms: None,
                 loop_route: false,
                 start_index: None,
                 interval_ms: None,
                 altitude: None,
+                interpolate: None,
             },
         }
     }
 }
 
+impl MockLocationParams {
+    pub fn speed_profile(&self) -> DriveProfile {
+        DriveProfile {
+            mode: self.speed_mode.clone(),
+            noise: self.speed_noise,
+            acceleration: self.acceleration,
+            stop_every: self.stop_every,
+            stop_duration_ms: self.stop_duration_ms,
+        }
+    }
+}
+
 /// Assert color at a specific point on screen
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -2215,6 +3421,14 @@ pub enum RelativeAnchorInput {
 }
 
 impl RelativeAnchorInput {
+    /// Anchor matched by resource-id / accessibility identifier
+    pub fn id(id: &str) -> Self {
+        Self::Struct(AnchorParams {
+            id: Some(id.to_string()),
+            ..Default::default()
+        })
+    }
+
     pub fn as_string(&self) -> Option<&str> {
         match self {
             Self::String(s) => Some(s),
@@ -2230,8 +3444,138 @@ pub struct RelativeParams {
     pub left_of: Option<RelativeAnchorInput>,
     pub above: Option<RelativeAnchorInput>,
     pub below: Option<RelativeAnchorInput>,
+    /// Anchor on a resource-id / accessibility identifier instead of visible text
+    #[serde(default)]
+    pub right_of_id: Option<String>,
+    #[serde(default)]
+    pub left_of_id: Option<String>,
+    #[serde(default)]
+    pub above_id: Option<String>,
+    #[serde(default)]
+    pub below_id: Option<String>,
     #[serde(alias = "maxDistance")]
-    pub max_dist: Option<u32>,
+    pub max_dist: Option<MaxDistance>,
+    /// Max angle (degrees) between the search direction and the anchor→candidate line
+    #[serde(default, alias = "angleTolerance")]
+    pub angle: Option<f32>,
+}
+
+/// Distance limit for relative selectors: pixels (`200`, `"200px"`) or a share
+/// of the screen along the search direction (`"25%"`)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "MaxDistanceInput", into = "MaxDistanceInput")]
+pub enum MaxDistance {
+    Pixels(u32),
+    ScreenPercent(f32),
+}
+
+/// Raw YAML form of [`MaxDistance`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum MaxDistanceInput {
+    Number(u32),
+    Text(String),
+}
+
+impl TryFrom<MaxDistanceInput> for MaxDistance {
+    type Error = String;
+
+    fn try_from(input: MaxDistanceInput) -> Result<Self, Self::Error> {
+        match input {
+            MaxDistanceInput::Number(px) => Ok(MaxDistance::Pixels(px)),
+            MaxDistanceInput::Text(s) => {
+                let t = s.trim();
+                if let Some(pct) = t.strip_suffix('%') {
+                    pct.trim()
+                        .parse::<f32>()
+                        .ok()
+                        .filter(|p| *p > 0.0 && *p <= 100.0)
+                        .map(MaxDistance::ScreenPercent)
+                        .ok_or_else(|| format!("Invalid maxDist percentage: {}", s))
+                } else {
+                    t.trim_end_matches("px")
+                        .trim()
+                        .parse::<u32>()
+                        .map(MaxDistance::Pixels)
+                        .map_err(|_| format!("Invalid maxDist '{}', expected pixels or a percentage like \"25%\"", s))
+                }
+            }
+        }
+    }
+}
+
+impl From<MaxDistance> for MaxDistanceInput {
+    fn from(d: MaxDistance) -> Self {
+        match d {
+            MaxDistance::Pixels(px) => MaxDistanceInput::Number(px),
+            MaxDistance::ScreenPercent(p) => MaxDistanceInput::Text(format!("{}%", p)),
+        }
+    }
+}
+
+impl MaxDistance {
+    /// Resolve to pixels, given the screen length along the search axis
+    pub fn to_pixels(&self, axis_length: u32) -> u32 {
+        match self {
+            MaxDistance::Pixels(px) => *px,
+            MaxDistance::ScreenPercent(p) => (axis_length as f32 * p / 100.0).round() as u32,
+        }
+    }
+}
+
+impl std::fmt::Display for MaxDistance {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MaxDistance::Pixels(px) => write!(f, "{}", px),
+            MaxDistance::ScreenPercent(p) => write!(f, "{}%", p),
+        }
+    }
+}
+
+impl RelativeParams {
+    /// Merge the top-level shorthand (`rightOf`, `belowId`, ...) of a tap/assert
+    /// command into its `relative` block. Shorthand wins over `relative`.
+    pub fn merge_shorthand(
+        relative: &Option<RelativeParams>,
+        anchors: [&Option<RelativeAnchorInput>; 4],
+        ids: [&Option<String>; 4],
+    ) -> Option<RelativeParams> {
+        if anchors.iter().all(|a| a.is_none()) && ids.iter().all(|i| i.is_none()) {
+            return relative.clone();
+        }
+        let mut r = relative.clone().unwrap_or_default();
+        let [right_of, left_of, above, below] = anchors;
+        let [right_of_id, left_of_id, above_id, below_id] = ids;
+        for (slot, anchor, id) in [
+            (&mut r.right_of, right_of, right_of_id),
+            (&mut r.left_of, left_of, left_of_id),
+            (&mut r.above, above, above_id),
+            (&mut r.below, below, below_id),
+        ] {
+            if anchor.is_some() {
+                *slot = anchor.clone();
+            } else if let Some(id) = id {
+                *slot = Some(RelativeAnchorInput::id(id));
+            }
+        }
+        Some(r)
+    }
+
+    /// Turn `rightOfId`-style fields into regular id anchors
+    pub fn resolve_id_anchors(&self) -> RelativeParams {
+        let mut r = self.clone();
+        for (slot, id) in [
+            (&mut r.right_of, self.right_of_id.as_ref()),
+            (&mut r.left_of, self.left_of_id.as_ref()),
+            (&mut r.above, self.above_id.as_ref()),
+            (&mut r.below, self.below_id.as_ref()),
+        ] {
+            if slot.is_none() {
+                *slot = id.map(|id| RelativeAnchorInput::id(id));
+            }
+        }
+        r
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
@@ -2292,7 +3636,7 @@ pub struct AnchorParams {
     pub exact: bool,
 
     #[serde(default)]
-    pub index: Option<u32>,
+    pub index: Option<i32>,
 }
 
 impl Default for WaitParams {
@@ -2340,6 +3684,98 @@ pub fn is_regex_string(s: &str) -> bool {
         || s.contains('|')
 }
 
+/// DSL keys and the structured selector field each one sets. Keys are
+/// lowercase and case-sensitive, so `Id=42` stays plain text.
+const SELECTOR_DSL_KEYS: &[(&str, &str)] = &[
+    ("text", "text"),
+    ("regex", "regex"),
+    ("id", "id"),
+    ("desc", "description"),
+    ("description", "description"),
+    ("css", "css"),
+    ("xpath", "xpath"),
+    ("role", "role"),
+    ("placeholder", "placeholder"),
+    ("type", "elementType"),
+    ("image", "image"),
+    ("point", "point"),
+];
+
+/// Compact selector string: `<key>=<value>` with an optional `[index]` suffix,
+/// e.g. `text=Login`, `id=submit_btn[2]`, `css=.item`, `role=button`.
+/// Desugars into the structured selector fields; strings without a known key
+/// stay plain text/regex selectors, and a string wrapped in double quotes is
+/// always literal text (`"type=password"`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct SelectorDsl {
+    /// Structured field the key maps to (`text`, `id`, `elementType`, ...)
+    pub field: &'static str,
+    pub value: String,
+    pub index: Option<i32>,
+}
+
+impl SelectorDsl {
+    pub fn parse(input: &str) -> Option<Self> {
+        let (key, rest) = input.trim().split_once('=')?;
+        let key = key.trim();
+        let field = SELECTOR_DSL_KEYS
+            .iter()
+            .find_map(|(k, field)| (*k == key).then_some(*field))?;
+
+        let mut value = rest.trim();
+        let mut index = None;
+        // CSS, XPath and regex use brackets in their own syntax
+        if !matches!(field, "css" | "xpath" | "regex") {
+            if let Some((head, tail)) = value.rsplit_once('[') {
+                if let Some(n) = tail.strip_suffix(']').and_then(|n| n.trim().parse().ok()) {
+                    value = head.trim_end();
+                    index = Some(n);
+                }
+            }
+        }
+        let value = value
+            .strip_prefix('"')
+            .and_then(|v| v.strip_suffix('"'))
+            .or_else(|| value.strip_prefix('\'').and_then(|v| v.strip_suffix('\'')))
+            .unwrap_or(value);
+        if value.is_empty() {
+            return None;
+        }
+
+        Some(Self {
+            field,
+            value: value.to_string(),
+            index,
+        })
+    }
+
+    /// Structured params (`TapParams`, `AssertParams`, ...) with the selector field set.
+    /// Fields the target doesn't have (e.g. `index` on scrollUntilVisible) are dropped.
+    pub fn to_params<T: serde::de::DeserializeOwned>(&self) -> Option<T> {
+        let mut map = serde_json::Map::new();
+        map.insert(self.field.to_string(), self.value.clone().into());
+        if let Some(index) = self.index {
+            map.insert("index".to_string(), index.into());
+        }
+        serde_json::from_value(serde_json::Value::Object(map)).ok()
+    }
+
+    /// Structured params for a string selector that is a double-quoted literal
+    /// (`text` set to the unquoted string) or a `key=value` DSL selector; `None`
+    /// for other strings, which callers treat as text/regex
+    pub fn desugar<T: serde::de::DeserializeOwned>(input: &str) -> Option<T> {
+        let trimmed = input.trim();
+        if let Some(text) = trimmed
+            .strip_prefix('"')
+            .and_then(|t| t.strip_suffix('"'))
+            .filter(|t| !t.is_empty())
+        {
+            return serde_json::from_value(serde_json::json!({ "text": text })).ok();
+        }
+        Self::parse(trimmed)?.to_params()
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(untagged)]
 pub enum TapParamsInput {
@@ -2350,7 +3786,9 @@ impl TapParamsInput {
     pub fn into_inner(self) -> TapParams {
         match self {
             TapParamsInput::String(s) => {
-                if is_regex_string(&s) {
+                if let Some(p) = SelectorDsl::desugar(&s) {
+                    p
+                } else if is_regex_string(&s) {
                     TapParams {
                         regex: Some(s),
                         ..Default::default()
@@ -2377,7 +3815,9 @@ impl AssertParamsInput {
     pub fn into_inner(self) -> AssertParams {
         match self {
             AssertParamsInput::String(s) => {
-                if is_regex_string(&s) {
+                if let Some(p) = SelectorDsl::desugar(&s) {
+                    p
+                } else if is_regex_string(&s) {
                     AssertParams {
                         regex: Some(s),
                         ..Default::default()
@@ -2419,7 +3859,9 @@ impl ScrollUntilVisibleInput {
     pub fn into_inner(self) -> ScrollUntilVisibleParams {
         match self {
             ScrollUntilVisibleInput::String(s) => {
-                if is_regex_string(&s) {
+                if let Some(p) = SelectorDsl::desugar(&s) {
+                    p
+                } else if is_regex_string(&s) {
                     ScrollUntilVisibleParams {
                         regex: Some(s),
                         label: None,
@@ -2448,7 +3890,9 @@ impl AnchorParamsInput {
     pub fn into_inner(self) -> AnchorParams {
         match self {
             AnchorParamsInput::String(s) => {
-                if is_regex_string(&s) {
+                if let Some(p) = SelectorDsl::desugar(&s) {
+                    p
+                } else if is_regex_string(&s) {
                     AnchorParams {
                         regex: Some(s),
                         ..Default::default()
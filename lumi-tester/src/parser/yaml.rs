@@ -1,10 +1,13 @@
 use super::types::{
-    AssertColorParams, AssertParams, AssertParamsInput, AssertVarParams, BuildGifParams,
-    CaptureGifFrameParamsInput, ConditionalParams, GenerateParams, HttpRequestParams,
-    InputAtParams, LaunchAppParams, MockLocationParamsInput, Platform, RepeatParams, ReportParams,
-    RetryParams, ScrollUntilVisibleInput, ScrollUntilVisibleParams, SetVarParams, TapAtParams,
-    TapParams, TapParamsInput, TestCommand, TestFlow, WaitParams, WaitParamsInput,
+    AssertAllParams, AssertColorParams, AssertParams, AssertParamsInput, AssertVarParams,
+    BuildGifParams, CaptureGifFrameParamsInput, ConditionalParams, ForEachParams, GenerateParams,
+    HttpRequestParams, InputAtParams, LaunchAppParams, MockLocationParamsInput, PermissionState,
+    Platform, RepeatParams, ReportParams, RetryParams, ScalarValue, ScrollUntilVisibleInput,
+    ScrollUntilVisibleParams, SelectorDsl, SetVarParams, SystemDialogAction, TapAtParams,
+    TapParams, TapParamsInput, TestCommand, TestFlow, WaitForAnyOption, WaitForAnyParams,
+    WaitParams, WaitParamsInput,
 };
+use crate::error::TesterError;
 use anyhow::{Context, Result};
 use std::path::Path;
 
@@ -13,7 +16,13 @@ pub fn parse_test_file(path: &Path) -> Result<TestFlow> {
     let content = std::fs::read_to_string(path)
         .with_context(|| format!("Failed to read file: {}", path.display()))?;
 
-    parse_yaml_content(&content, path)
+    parse_yaml_content(&content, path).map_err(|e| {
+        TesterError::Parse {
+            path: Some(path.to_path_buf()),
+            message: format!("{:#}", e),
+        }
+        .into()
+    })
 }
 
 /// Parse YAML content into a TestFlow
@@ -38,12 +47,20 @@ pub fn parse_yaml_content(content: &str, _source_path: &Path) -> Result<TestFlow
                 env: None,
                 data: None,
                 default_timeout_ms: None,
+                timeout_ms: None,
                 commands: Vec::new(),
                 tags: Vec::new(),
+                title: None,
+                description: None,
                 speed: None,
                 browser: None,
                 close_when_finish: None,
+                connect_url: None,
                 desktop_state: None,
+                handle_system_dialogs: None,
+                detect_crashes: None,
+                baselines: None,
+                depends_on: Vec::new(),
             }
         };
         // Parse commands
@@ -60,12 +77,20 @@ pub fn parse_yaml_content(content: &str, _source_path: &Path) -> Result<TestFlow
             env: None,
             data: None,
             default_timeout_ms: None,
+            timeout_ms: None,
             commands,
             tags: Vec::new(),
+            title: None,
+            description: None,
             speed: None,
             browser: None,
             close_when_finish: None,
+            connect_url: None,
             desktop_state: None,
+            handle_system_dialogs: None,
+            detect_crashes: None,
+            baselines: None,
+            depends_on: Vec::new(),
         });
     }
 
@@ -91,12 +116,20 @@ pub fn parse_yaml_content(content: &str, _source_path: &Path) -> Result<TestFlow
             env: None,
             data: None,
             default_timeout_ms: None,
+            timeout_ms: None,
             commands: Vec::new(),
             tags: Vec::new(),
+            title: None,
+            description: None,
             speed: None,
             browser: None,
             close_when_finish: None,
+            connect_url: None,
             desktop_state: None,
+            handle_system_dialogs: None,
+            detect_crashes: None,
+            baselines: None,
+            depends_on: Vec::new(),
         };
 
         if let Some(val) = map.get(&serde_yaml::Value::String("data".to_string())) {
@@ -111,6 +144,22 @@ pub fn parse_yaml_content(content: &str, _source_path: &Path) -> Result<TestFlow
             }
         }
 
+        for key in ["title", "name"] {
+            if let Some(s) = map
+                .get(&serde_yaml::Value::String(key.to_string()))
+                .and_then(|v| v.as_str())
+            {
+                flow.title = Some(s.to_string());
+                break;
+            }
+        }
+
+        if let Some(val) = map.get(&serde_yaml::Value::String("description".to_string())) {
+            if let Some(s) = val.as_str() {
+                flow.description = Some(s.to_string());
+            }
+        }
+
         if let Some(val) = map.get(&serde_yaml::Value::String("appId".to_string())) {
             if let Some(s) = val.as_str() {
                 flow.app_id = Some(s.to_string());
@@ -143,14 +192,46 @@ pub fn parse_yaml_content(content: &str, _source_path: &Path) -> Result<TestFlow
             flow.default_timeout_ms = val.as_u64();
         }
 
+        if let Some(val) = map.get(&serde_yaml::Value::String("timeout".to_string())) {
+            flow.timeout_ms = val.as_u64();
+        }
+
         if let Some(val) = map.get(&serde_yaml::Value::String("closeWhenFinish".to_string())) {
             flow.close_when_finish = val.as_bool();
         }
 
+        if let Some(val) = map
+            .get(serde_yaml::Value::String("connectUrl".to_string()))
+            .or_else(|| map.get(serde_yaml::Value::String("wsEndpoint".to_string())))
+        {
+            flow.connect_url = val.as_str().map(|s| s.to_string());
+        }
+
         if let Some(val) = map.get(&serde_yaml::Value::String("desktopState".to_string())) {
             flow.desktop_state = Some(serde_yaml::from_value(val.clone())?);
         }
 
+        if let Some(val) = map.get(&serde_yaml::Value::String(
+            "handleSystemDialogs".to_string(),
+        )) {
+            let name = val
+                .as_str()
+                .ok_or_else(|| anyhow::anyhow!("handleSystemDialogs must be allow or deny"))?;
+            flow.handle_system_dialogs = Some(SystemDialogAction::from_name(name)?);
+        }
+
+        if let Some(val) = map.get(&serde_yaml::Value::String("detectCrashes".to_string())) {
+            flow.detect_crashes = val.as_bool();
+        }
+
+        if let Some(val) = map.get(&serde_yaml::Value::String("baselines".to_string())) {
+            flow.baselines = val.as_str().map(str::to_string);
+        }
+
+        if let Some(val) = map.get(serde_yaml::Value::String("dependsOn".to_string())) {
+            flow.depends_on = serde_yaml::from_value::<DependsOn>(val.clone())?.into_vec();
+        }
+
         let env_val = map
             .get(&serde_yaml::Value::String("env".to_string()))
             .or_else(|| map.get(&serde_yaml::Value::String("vars".to_string())))
@@ -188,6 +269,26 @@ pub fn parse_yaml_content(content: &str, _source_path: &Path) -> Result<TestFlow
     anyhow::bail!("Invalid YAML format: expected sequence or TestFlow map");
 }
 
+/// `dependsOn` accepts a single flow name or a list
+#[derive(Debug, Default, serde::Deserialize)]
+#[serde(untagged)]
+enum DependsOn {
+    #[default]
+    None,
+    One(String),
+    Many(Vec<String>),
+}
+
+impl DependsOn {
+    fn into_vec(self) -> Vec<String> {
+        match self {
+            DependsOn::None => Vec::new(),
+            DependsOn::One(name) => vec![name],
+            DependsOn::Many(names) => names,
+        }
+    }
+}
+
 /// Parse the header section of a YAML test file
 fn parse_header(header: &str, base_path: &Path) -> Result<TestFlow> {
     #[derive(serde::Deserialize)]
@@ -206,15 +307,31 @@ fn parse_header(header: &str, base_path: &Path) -> Result<TestFlow> {
         #[serde(default, alias = "defaultTimeout")]
         default_timeout: Option<u64>,
         #[serde(default)]
+        timeout: Option<u64>,
+        #[serde(default)]
         tags: Vec<String>,
+        #[serde(default, alias = "name")]
+        title: Option<String>,
+        #[serde(default)]
+        description: Option<String>,
         #[serde(default)]
         speed: Option<String>,
         #[serde(default)]
         browser: Option<String>,
         #[serde(default)]
         close_when_finish: Option<bool>,
+        #[serde(default, alias = "wsEndpoint")]
+        connect_url: Option<String>,
         #[serde(default)]
         desktop_state: Option<crate::parser::types::DesktopState>,
+        #[serde(default)]
+        handle_system_dialogs: Option<String>,
+        #[serde(default)]
+        detect_crashes: Option<bool>,
+        #[serde(default)]
+        baselines: Option<String>,
+        #[serde(default)]
+        depends_on: DependsOn,
     }
 
     let parsed: Header = serde_yaml::from_str(header).context("Failed to parse YAML header")?;
@@ -281,12 +398,24 @@ fn parse_header(header: &str, base_path: &Path) -> Result<TestFlow> {
         env,
         data: parsed.data,
         default_timeout_ms: parsed.default_timeout,
+        timeout_ms: parsed.timeout,
         commands: Vec::new(),
         tags: parsed.tags,
+        title: parsed.title,
+        description: parsed.description,
         speed: parsed.speed,
         browser: parsed.browser,
         close_when_finish: parsed.close_when_finish,
+        connect_url: parsed.connect_url,
         desktop_state: parsed.desktop_state,
+        handle_system_dialogs: parsed
+            .handle_system_dialogs
+            .as_deref()
+            .map(SystemDialogAction::from_name)
+            .transpose()?,
+        detect_crashes: parsed.detect_crashes,
+        baselines: parsed.baselines,
+        depends_on: parsed.depends_on.into_vec(),
     })
 }
 
@@ -344,6 +473,23 @@ pub fn parse_command_value(value: &serde_yaml::Value) -> Result<Option<TestComma
 
         // Command with parameters like "- tapOn:\n    text: 'Login'"
         serde_yaml::Value::Mapping(map) => {
+            // `timeout:` next to the command key bounds the whole command
+            let timeout_key = serde_yaml::Value::String("timeout".to_string());
+            if map.len() == 2 && map.contains_key(&timeout_key) {
+                let timeout_ms = map[&timeout_key].as_u64().ok_or_else(|| {
+                    anyhow::anyhow!("Command timeout must be a number of milliseconds")
+                })?;
+                let mut inner = map.clone();
+                inner.remove(&timeout_key);
+                let command = parse_command_value(&serde_yaml::Value::Mapping(inner))?;
+                return Ok(command.map(|command| {
+                    TestCommand::Timed(crate::parser::types::TimedCommand {
+                        timeout_ms,
+                        command: Box::new(command),
+                    })
+                }));
+            }
+
             if map.len() != 1 {
                 anyhow::bail!("Invalid command format: expected single key mapping");
             }
@@ -385,6 +531,9 @@ fn parse_simple_command(name: &str) -> Result<Option<TestCommand>> {
         "waitForAnimationToEnd" => TestCommand::WaitForAnimationToEnd,
         "stopRecording" | "stopRecord" => TestCommand::StopRecording,
         "back" => TestCommand::Back,
+        "break" => TestCommand::Break,
+        "continue" => TestCommand::Continue,
+        "stopFlow" | "endFlow" => TestCommand::StopFlow,
         "pressHome" | "home" => TestCommand::PressHome,
         "eraseText" | "clear" => TestCommand::EraseText(None),
         "stopMockLocation" | "stopGps" => TestCommand::StopMockLocation,
@@ -404,6 +553,12 @@ fn parse_simple_command(name: &str) -> Result<Option<TestCommand>> {
             selector: None,
             text: None,
         }),
+        _ if crate::plugin::is_registered(name) => {
+            TestCommand::Plugin(crate::parser::types::PluginCommand {
+                name: name.to_string(),
+                params: serde_json::Value::Null,
+            })
+        }
         _ => return Ok(None),
     };
 
@@ -444,6 +599,9 @@ fn parse_command_with_params(
                 serde_yaml::from_value(params.clone())?
             } else {
                 let inner: TapParams = serde_yaml::from_value(params.clone())?;
+                if inner.scroll && (inner.any_of.is_some() || inner.point.is_some()) {
+                    anyhow::bail!("{}: scroll can't be combined with anyOf or point", name);
+                }
                 TapParamsInput::Struct(inner)
             };
             TestCommand::TapOn(p)
@@ -516,6 +674,9 @@ fn parse_command_with_params(
                 serde_yaml::from_value(params.clone())?
             } else {
                 let inner: AssertParams = serde_yaml::from_value(params.clone())?;
+                if inner.scroll && inner.any_of.is_some() {
+                    anyhow::bail!("{}: scroll can't be combined with anyOf", name);
+                }
                 AssertParamsInput::Struct(inner)
             };
             TestCommand::AssertVisible(p)
@@ -603,6 +764,110 @@ fn parse_command_with_params(
             })
         }
 
+        "assertAll" => {
+            // `assertAll: [...]` or `assertAll: { assertions: [...], soft: true }`
+            let (assertions, soft, label) = match params.as_mapping() {
+                Some(map) => {
+                    let get = |key: &str| map.get(key);
+                    let assertions = get("assertions")
+                        .ok_or_else(|| anyhow::anyhow!("assertAll requires assertions"))?;
+                    (
+                        parse_commands_from_value(assertions)?,
+                        get("soft").and_then(|v| v.as_bool()).unwrap_or(false),
+                        get("label").and_then(|v| v.as_str()).map(String::from),
+                    )
+                }
+                None => (parse_commands_from_value(params)?, false, None),
+            };
+            if assertions.is_empty() {
+                anyhow::bail!("assertAll requires at least one assertion");
+            }
+            TestCommand::AssertAll(AssertAllParams {
+                assertions,
+                soft,
+                label,
+            })
+        }
+
+        "waitForAny" | "waitForFirst" => {
+            let map = params
+                .as_mapping()
+                .ok_or_else(|| anyhow::anyhow!("waitForAny requires a mapping"))?;
+            let get = |key: &str| map.get(&serde_yaml::Value::String(key.to_string()));
+
+            let options = get("options")
+                .and_then(|v| v.as_sequence())
+                .ok_or_else(|| anyhow::anyhow!("waitForAny requires a list of options"))?
+                .iter()
+                .map(|option| {
+                    let when = option
+                        .get("when")
+                        .ok_or_else(|| anyhow::anyhow!("waitForAny option requires `when`"))?;
+                    let when = if when.is_string() {
+                        serde_yaml::from_value(when.clone())?
+                    } else {
+                        AssertParamsInput::Struct(serde_yaml::from_value(when.clone())?)
+                    };
+                    let then = match option.get("then") {
+                        Some(cmds) => parse_commands_from_value(cmds)?,
+                        None => Vec::new(),
+                    };
+                    Ok(WaitForAnyOption { when, then })
+                })
+                .collect::<Result<Vec<_>>>()?;
+            if options.is_empty() {
+                anyhow::bail!("waitForAny requires at least one option");
+            }
+
+            TestCommand::WaitForAny(WaitForAnyParams {
+                options,
+                timeout: get("timeout").and_then(|v| v.as_u64()),
+                optional: get("optional").and_then(|v| v.as_bool()).unwrap_or(false),
+            })
+        }
+
+        "forEach" | "for" => {
+            let map = params
+                .as_mapping()
+                .ok_or_else(|| anyhow::anyhow!("forEach requires a mapping"))?;
+            let get = |key: &str| map.get(&serde_yaml::Value::String(key.to_string()));
+
+            let items: Vec<serde_json::Value> = match get("items") {
+                Some(v) => {
+                    serde_yaml::from_value(v.clone()).context("forEach items must be a list")?
+                }
+                None => Vec::new(),
+            };
+            let data_file = get("dataFile")
+                .or_else(|| get("file"))
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string());
+            if items.is_empty() && data_file.is_none() {
+                anyhow::bail!("forEach requires items or dataFile");
+            }
+
+            let var_name = get("as")
+                .and_then(|v| v.as_str())
+                .unwrap_or("item")
+                .to_string();
+            let commands = match get("commands") {
+                Some(v) => parse_commands_from_value(v)?,
+                None => Vec::new(),
+            };
+            let flow = get("flow").and_then(|v| v.as_str()).map(|s| s.to_string());
+            if commands.is_empty() && flow.is_none() {
+                anyhow::bail!("forEach requires commands or flow");
+            }
+
+            TestCommand::ForEach(ForEachParams {
+                items,
+                data_file,
+                var_name,
+                commands,
+                flow,
+            })
+        }
+
         "runFlow" => {
             use super::types::{RunFlowParams, RunFlowParamsInput};
             match params {
@@ -675,6 +940,14 @@ fn parse_command_with_params(
 
         "assertVar" => {
             let p: AssertVarParams = serde_yaml::from_value(params.clone())?;
+            if matches!(p.expected, ScalarValue::Float(_)) && p.operator.is_textual() {
+                anyhow::bail!(
+                    "assertVar '{}': quote decimal values compared with '{}' (expected: \"{}\" keeps trailing zeros), or use operator: equalsNumber",
+                    p.name,
+                    p.operator,
+                    p.expected
+                );
+            }
             TestCommand::AssertVar(p)
         }
 
@@ -727,10 +1000,10 @@ fn parse_command_with_params(
         "rightClick" | "contextClick" => {
             let p: TapParams = if params.is_string() {
                 let text = params.as_str().unwrap().to_string();
-                TapParams {
+                SelectorDsl::desugar(&text).unwrap_or(TapParams {
                     text: Some(text),
                     ..Default::default()
-                }
+                })
             } else {
                 serde_yaml::from_value(params.clone())?
             };
@@ -738,6 +1011,9 @@ fn parse_command_with_params(
         }
 
         "back" => TestCommand::Back,
+        "break" => TestCommand::Break,
+        "continue" => TestCommand::Continue,
+        "stopFlow" | "endFlow" => TestCommand::StopFlow,
         "stopRecording" | "stopRecord" => TestCommand::StopRecording,
         "stopApp" | "stop" => TestCommand::StopApp,
         "pressHome" | "home" => TestCommand::PressHome,
@@ -865,11 +1141,7 @@ fn parse_command_with_params(
         }
 
         "setNetworkConditions" => {
-            let profile = match params {
-                serde_yaml::Value::String(s) => s.clone(),
-                _ => serde_yaml::from_value(params.clone())?,
-            };
-            TestCommand::SetNetworkConditions(profile)
+            TestCommand::SetNetworkConditions(serde_yaml::from_value(params.clone())?)
         }
 
         "mockLocationControl" => {
@@ -900,6 +1172,7 @@ fn parse_command_with_params(
                 crate::parser::types::SetVarParams {
                     name: params.as_str().unwrap().to_string(),
                     value: String::new(),
+                    eval: false,
                 }
             } else {
                 serde_yaml::from_value(params.clone())?
@@ -969,6 +1242,14 @@ fn parse_command_with_params(
             TestCommand::SetNetwork(p)
         }
 
+        "setWifi" | "toggleWifi" => TestCommand::SetWifi(parse_switch(name, params)?),
+        "setMobileData" | "setData" | "toggleData" => {
+            TestCommand::SetMobileData(parse_switch(name, params)?)
+        }
+        "setBluetooth" | "toggleBluetooth" => {
+            TestCommand::SetBluetooth(parse_switch(name, params)?)
+        }
+
         "airplaneMode" | "toggleAirplaneMode" => TestCommand::ToggleAirplaneMode,
 
         "openNotifications" => TestCommand::OpenNotifications,
@@ -987,11 +1268,13 @@ fn parse_command_with_params(
         "unlockDevice" => TestCommand::UnlockDevice,
 
         "installApp" => {
-            let path = match params {
-                serde_yaml::Value::String(s) => s.clone(),
+            let p = match params {
+                serde_yaml::Value::String(s) => {
+                    crate::parser::types::InstallAppParams::from_path(s.clone())
+                }
                 _ => serde_yaml::from_value(params.clone())?,
             };
-            TestCommand::InstallApp(path)
+            TestCommand::InstallApp(p)
         }
 
         "uninstallApp" => {
@@ -1002,6 +1285,107 @@ fn parse_command_with_params(
             TestCommand::UninstallApp(pkg)
         }
 
+        "snapshotAppData" | "restoreAppData" => {
+            let p = match params {
+                serde_yaml::Value::String(s) => crate::parser::types::AppDataSnapshotParams {
+                    name: s.clone(),
+                    app: None,
+                },
+                _ => serde_yaml::from_value(params.clone())?,
+            };
+            if name == "snapshotAppData" {
+                TestCommand::SnapshotAppData(p)
+            } else {
+                TestCommand::RestoreAppData(p)
+            }
+        }
+
+        "assertNotification" => {
+            let p = match params {
+                serde_yaml::Value::String(s) => crate::parser::types::AssertNotificationParams {
+                    title: Some(s.clone()),
+                    ..Default::default()
+                },
+                _ => serde_yaml::from_value(params.clone())?,
+            };
+            TestCommand::AssertNotification(p)
+        }
+
+        "assertToast" => {
+            let p = match params {
+                serde_yaml::Value::String(s) => crate::parser::types::AssertToastParams {
+                    text: s.clone(),
+                    timeout: None,
+                },
+                _ => serde_yaml::from_value(params.clone())?,
+            };
+            TestCommand::AssertToast(p)
+        }
+
+        "sendSms" | "receiveSms" => {
+            let p: crate::parser::types::SendSmsParams = serde_yaml::from_value(params.clone())?;
+            if p.body.is_none() && p.save_to.is_none() {
+                anyhow::bail!("{} requires 'body' and/or 'saveTo'", name);
+            }
+            TestCommand::SendSms(p)
+        }
+
+        "setDateTime" => {
+            let p = match params {
+                serde_yaml::Value::String(s) => {
+                    crate::parser::types::SetDateTimeParams { value: s.clone() }
+                }
+                _ => serde_yaml::from_value(params.clone())?,
+            };
+            TestCommand::SetDateTime(p)
+        }
+
+        "tapSystemButton" => {
+            let button = match params {
+                serde_yaml::Value::String(s) => s.clone(),
+                _ => serde_yaml::from_value::<String>(
+                    params
+                        .get("button")
+                        .cloned()
+                        .ok_or_else(|| anyhow::anyhow!("tapSystemButton requires 'button'"))?,
+                )?,
+            };
+            TestCommand::TapSystemButton(button)
+        }
+
+        "setPermission" | "grantPermission" | "revokePermission" => {
+            let mut p: crate::parser::types::SetPermissionParams =
+                if let Some(perm) = params.as_str() {
+                    crate::parser::types::SetPermissionParams {
+                        app_id: None,
+                        permission: perm.to_string(),
+                        state: PermissionState::Allow,
+                    }
+                } else {
+                    serde_yaml::from_value(params.clone())?
+                };
+            match name {
+                "grantPermission" => p.state = PermissionState::Allow,
+                "revokePermission" => p.state = PermissionState::Deny,
+                _ => {}
+            }
+            TestCommand::SetPermission(p)
+        }
+
+        "assertAppVersion" => {
+            let p: crate::parser::types::AssertAppVersionParams = if params.is_string() {
+                crate::parser::types::AssertAppVersionParams {
+                    app_id: None,
+                    equals: params.as_str().map(|s| s.to_string()),
+                    min_version: None,
+                    build_number: None,
+                }
+            } else {
+                serde_yaml::from_value(params.clone())?
+            };
+            TestCommand::AssertAppVersion(p)
+        }
+
         "backgroundApp" => {
             let p: crate::parser::types::BackgroundAppParams =
                 serde_yaml::from_value(params.clone())?;
@@ -1044,6 +1428,41 @@ fn parse_command_with_params(
             TestCommand::Navigate(p)
         }
 
+        "assertUrl" => {
+            let p = if params.is_string() {
+                crate::parser::types::AssertUrlParams {
+                    equals: params.as_str().map(|s| s.to_string()),
+                    ..Default::default()
+                }
+            } else {
+                serde_yaml::from_value(params.clone())?
+            };
+            TestCommand::AssertUrl(p)
+        }
+
+        "captureUrl" => {
+            let p = if params.is_string() {
+                crate::parser::types::CaptureUrlParams {
+                    into: params.as_str().unwrap().to_string(),
+                }
+            } else {
+                serde_yaml::from_value(params.clone())?
+            };
+            TestCommand::CaptureUrl(p)
+        }
+
+        "executeScript" | "executeJs" => {
+            let p = if params.is_string() {
+                crate::parser::types::ExecuteJsParams {
+                    script: params.as_str().unwrap().to_string(),
+                    into: None,
+                }
+            } else {
+                serde_yaml::from_value(params.clone())?
+            };
+            TestCommand::ExecuteJs(p)
+        }
+
         "click" => {
             let p = if params.is_string() {
                 crate::parser::types::ClickParams {
@@ -1097,15 +1516,47 @@ fn parse_command_with_params(
             TestCommand::VerifyAudioDucking(p)
         }
 
+        "assertAudioPlaying" => {
+            let p: crate::parser::types::AssertAudioPlayingParams = match params {
+                serde_yaml::Value::Null => serde_yaml::from_str("{}")?,
+                serde_yaml::Value::String(s) => crate::parser::types::AssertAudioPlayingParams {
+                    app: Some(s.clone()),
+                    playing: true,
+                    timeout: None,
+                },
+                _ => serde_yaml::from_value(params.clone())?,
+            };
+            TestCommand::AssertAudioPlaying(p)
+        }
+
+        _ if crate::plugin::is_registered(name) => {
+            TestCommand::Plugin(crate::parser::types::PluginCommand {
+                name: name.to_string(),
+                params: serde_json::to_value(params)?,
+            })
+        }
         _ => return Ok(None),
     };
 
     Ok(Some(cmd))
 }
 
+/// On/off value of `setWifi`/`setMobileData`/`setBluetooth`: a bool or on/off/enable/disable
+fn parse_switch(name: &str, params: &serde_yaml::Value) -> Result<bool> {
+    if let Some(on) = params.as_bool() {
+        return Ok(on);
+    }
+    match params.as_str().map(|s| s.trim().to_lowercase()).as_deref() {
+        Some("on" | "true" | "enable" | "enabled") => Ok(true),
+        Some("off" | "false" | "disable" | "disabled") => Ok(false),
+        _ => anyhow::bail!("{} expects true/false (or on/off), got {:?}", name, params),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::parser::types::VarOperator;
 
     #[test]
     fn test_parse_simple_flow() {
@@ -1126,6 +1577,164 @@ appId: com.example.app
         assert_eq!(flow.commands.len(), 4);
     }
 
+    #[test]
+    fn selector_strings_desugar_into_structured_fields() {
+        let yaml = r#"
+- tapOn: "id=submit_btn[2]"
+- assertVisible: "css=.item"
+- scrollUntilVisible: "type=android.widget.Button"
+- rightClick: "role=button"
+- tapOn: "Sign in (2)"
+- tapOn: '"type=password"'
+- tapOn: "Id=42"
+"#;
+
+        let flow = parse_yaml_content(yaml, Path::new("test.yaml")).unwrap();
+        match &flow.commands[0] {
+            TestCommand::TapOn(p) => {
+                let p = p.clone().into_inner();
+                assert_eq!(p.id.as_deref(), Some("submit_btn"));
+                assert_eq!(p.index, Some(2));
+                assert!(p.text.is_none());
+            }
+            other => panic!("unexpected command {:?}", other),
+        }
+        match &flow.commands[1] {
+            TestCommand::AssertVisible(p) => {
+                assert_eq!(p.clone().into_inner().css.as_deref(), Some(".item"))
+            }
+            other => panic!("unexpected command {:?}", other),
+        }
+        match &flow.commands[2] {
+            TestCommand::ScrollUntilVisible(p) => assert_eq!(
+                p.clone().into_inner().element_type.as_deref(),
+                Some("android.widget.Button")
+            ),
+            other => panic!("unexpected command {:?}", other),
+        }
+        match &flow.commands[3] {
+            TestCommand::RightClick(p) => assert_eq!(p.role.as_deref(), Some("button")),
+            other => panic!("unexpected command {:?}", other),
+        }
+        // Strings without a selector key keep the old text/regex behaviour
+        match &flow.commands[4] {
+            TestCommand::TapOn(p) => {
+                assert_eq!(p.clone().into_inner().regex.as_deref(), Some("Sign in (2)"))
+            }
+            other => panic!("unexpected command {:?}", other),
+        }
+        // A quoted string is literal text, and keys are case-sensitive
+        for (i, text) in [(5, "type=password"), (6, "Id=42")] {
+            match &flow.commands[i] {
+                TestCommand::TapOn(p) => {
+                    assert_eq!(p.clone().into_inner().text.as_deref(), Some(text))
+                }
+                other => panic!("unexpected command {:?}", other),
+            }
+        }
+
+        let dsl = SelectorDsl::parse("role = \"button\"").unwrap();
+        assert_eq!((dsl.field, dsl.value.as_str()), ("role", "button"));
+        let dsl = SelectorDsl::parse("css=input[name=email]").unwrap();
+        assert_eq!((dsl.value.as_str(), dsl.index), ("input[name=email]", None));
+        assert!(SelectorDsl::parse("a=b").is_none());
+        assert!(SelectorDsl::parse("text=").is_none());
+    }
+
+    #[test]
+    fn command_kind_ignores_labels_and_params() {
+        let yaml = r#"
+- tapOn:
+    text: "Login"
+    label: "Sign in"
+- assertScreenshot: home.png
+- stopApp
+"#;
+
+        let flow = parse_yaml_content(yaml, Path::new("test.yaml")).unwrap();
+        let kinds: Vec<&str> = flow.commands.iter().map(|c| c.kind()).collect();
+        assert_eq!(kinds, vec!["tapOn", "assertScreenshot", "stopApp"]);
+        assert!(kinds
+            .iter()
+            .all(|k| crate::parser::types::is_command_kind(k)));
+        assert!(crate::parser::types::is_command_kind("ASSERTSCREENSHOT"));
+        assert!(!crate::parser::types::is_command_kind("tapOnn"));
+    }
+
+    #[test]
+    fn set_var_eval_flag_defaults_to_literal() {
+        let yaml = r#"
+- setVar:
+    name: total
+    value: "price * quantity"
+    eval: true
+- setVar:
+    name: label
+    value: "price * quantity"
+"#;
+
+        let flow = parse_yaml_content(yaml, Path::new("test.yaml")).unwrap();
+        let evals: Vec<bool> = flow
+            .commands
+            .iter()
+            .map(|c| match c {
+                TestCommand::SetVar(p) => p.eval,
+                other => panic!("expected setVar, got {:?}", other),
+            })
+            .collect();
+        assert_eq!(evals, vec![true, false]);
+    }
+
+    #[test]
+    fn assert_var_accepts_operators_and_numbers() {
+        let yaml = r#"
+- assertVar:
+    name: count
+    operator: ">"
+    expected: 3
+- assertVar:
+    name: role
+    expected: admin
+"#;
+
+        let flow = parse_yaml_content(yaml, Path::new("test.yaml")).unwrap();
+        let TestCommand::AssertVar(p) = &flow.commands[0] else {
+            panic!("expected assertVar");
+        };
+        assert_eq!(p.operator, VarOperator::Gt);
+        assert_eq!(p.expected.to_string(), "3");
+        let TestCommand::AssertVar(p) = &flow.commands[1] else {
+            panic!("expected assertVar");
+        };
+        assert_eq!(p.operator, VarOperator::Eq);
+
+        assert!(VarOperator::Gt.compare("10", "9").unwrap());
+        assert!(!VarOperator::Gt.compare("10", "9x").unwrap());
+        assert!(!VarOperator::Eq.compare("3.0", "3").unwrap());
+        assert!(VarOperator::Eq.compare("007", "007").unwrap());
+        assert!(VarOperator::Ne.compare("3.10", "3.1").unwrap());
+        assert!(VarOperator::NumEq.compare("3.0", "3").unwrap());
+        assert!(!VarOperator::NumEq.compare("abc", "abc").unwrap());
+        assert!(VarOperator::Ne.compare("abc", "abd").unwrap());
+        assert!(VarOperator::Le.compare("apple", "banana").unwrap());
+        assert!(VarOperator::Contains
+            .compare("hello world", "lo w")
+            .unwrap());
+        assert!(VarOperator::Matches
+            .compare("ORD-42", r"^ORD-\d+$")
+            .unwrap());
+        assert!(VarOperator::Matches.compare("x", "(").is_err());
+
+        let unquoted = "- assertVar:\n    name: price\n    expected: 3.10\n";
+        assert!(parse_yaml_content(unquoted, Path::new("test.yaml")).is_err());
+        let quoted = "- assertVar:\n    name: price\n    expected: \"3.10\"\n";
+        let flow = parse_yaml_content(quoted, Path::new("test.yaml")).unwrap();
+        let TestCommand::AssertVar(p) = &flow.commands[0] else {
+            panic!("expected assertVar");
+        };
+        assert_eq!(p.expected.to_string(), "3.10");
+    }
+
     #[test]
     fn unknown_parameterized_command_is_rejected() {
         let yaml = r#"
@@ -1140,6 +1749,480 @@ appId: com.example.app
         assert!(err.to_string().contains("tappp"));
     }
 
+    #[test]
+    fn permission_aliases_set_state() {
+        let yaml = r#"
+appId: com.example.app
+---
+- grantPermission: location
+- revokePermission:
+    permission: camera
+- setPermission:
+    permission: microphone
+    state: deny
+"#;
+
+        let flow = parse_yaml_content(yaml, Path::new("test.yaml")).unwrap();
+        let states: Vec<(String, bool)> = flow
+            .commands
+            .iter()
+            .map(|c| match c {
+                TestCommand::SetPermission(p) => {
+                    (p.permission.clone(), p.state == PermissionState::Deny)
+                }
+                other => panic!("unexpected command {:?}", other),
+            })
+            .collect();
+        assert_eq!(
+            states,
+            vec![
+                ("location".to_string(), false),
+                ("camera".to_string(), true),
+                ("microphone".to_string(), true),
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_permission_state() {
+        let yaml =
+            "appId: com.example\n---\n- setPermission:\n    permission: camera\n    state: denyy\n";
+        let err = parse_yaml_content(yaml, Path::new("test.yaml")).unwrap_err();
+        let message = format!("{:#}", err);
+        assert!(
+            message.contains("Invalid permission state 'denyy'"),
+            "{}",
+            message
+        );
+    }
+
+    #[test]
+    fn rejects_scroll_with_any_of_or_point() {
+        for command in [
+            "- tap:\n    point: \"50%,50%\"\n    scroll: true\n",
+            "- tap:\n    anyOf: [\"OK\", \"Done\"]\n    scroll: true\n",
+            "- see:\n    anyOf: [\"OK\", \"Done\"]\n    scroll: true\n",
+        ] {
+            let yaml = format!("appId: com.example\n---\n{}", command);
+            let err = parse_yaml_content(&yaml, Path::new("test.yaml")).unwrap_err();
+            let message = format!("{:#}", err);
+            assert!(message.contains("scroll can't be combined"), "{}", message);
+        }
+    }
+
+    #[test]
+    fn parses_for_each_items_and_data_file() {
+        let yaml = r#"
+- forEach:
+    items: ["Home", "Settings", { label: "Profile", id: "nav_profile" }]
+    as: menu
+    commands:
+      - tap: "${menu}"
+- forEach:
+    dataFile: users.csv
+    flow: subflows/login.yaml
+"#;
+
+        let flow = parse_yaml_content(yaml, Path::new("test.yaml")).unwrap();
+        match &flow.commands[0] {
+            TestCommand::ForEach(p) => {
+                assert_eq!(p.var_name, "menu");
+                assert_eq!(p.items.len(), 3);
+                assert_eq!(p.items[2]["id"], "nav_profile");
+                assert_eq!(p.commands.len(), 1);
+            }
+            other => panic!("unexpected command {:?}", other),
+        }
+        match &flow.commands[1] {
+            TestCommand::ForEach(p) => {
+                assert_eq!(p.var_name, "item");
+                assert_eq!(p.data_file.as_deref(), Some("users.csv"));
+                assert_eq!(p.flow.as_deref(), Some("subflows/login.yaml"));
+            }
+            other => panic!("unexpected command {:?}", other),
+        }
+
+        assert!(parse_yaml_content(
+            "- forEach:\n    as: x\n    commands: [back]\n",
+            Path::new("t.yaml")
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn parses_assert_true_polling_options() {
+        use crate::parser::types::AssertTrueParams;
+
+        let yaml = r#"
+- assertTrue: "${count} > 0"
+- assertTrue:
+    condition: "${progress} >= 100"
+    message: "Download did not finish"
+    retry: 2
+    timeout: 5000
+"#;
+
+        let flow = parse_yaml_content(yaml, Path::new("test.yaml")).unwrap();
+        assert!(matches!(
+            &flow.commands[0],
+            TestCommand::AssertTrue(AssertTrueParams::Expression(e)) if e == "${count} > 0"
+        ));
+        match &flow.commands[1] {
+            TestCommand::AssertTrue(AssertTrueParams::Condition(c)) => {
+                assert_eq!(c.message.as_deref(), Some("Download did not finish"));
+                assert_eq!(c.retry, Some(2));
+                assert_eq!(c.timeout, Some(5000));
+                assert!(!c.soft);
+            }
+            other => panic!("unexpected command {:?}", other),
+        }
+    }
+
+    #[test]
+    fn parses_command_and_flow_timeouts() {
+        let yaml = r#"
+appId: com.example.app
+timeout: 120000
+---
+- tapOn: "Login"
+  timeout: 5000
+- assertVisible:
+    text: "Home"
+    timeout: 3000
+"#;
+
+        let flow = parse_yaml_content(yaml, Path::new("test.yaml")).unwrap();
+        assert_eq!(flow.timeout_ms, Some(120000));
+        match &flow.commands[0] {
+            TestCommand::Timed(t) => {
+                assert_eq!(t.timeout_ms, 5000);
+                assert!(matches!(*t.command, TestCommand::TapOn(_)));
+                assert_eq!(flow.commands[0].display_name(), t.command.display_name());
+            }
+            other => panic!("unexpected command {:?}", other),
+        }
+        // A timeout inside the params stays the command's own wait timeout
+        assert!(!matches!(flow.commands[1], TestCommand::Timed(_)));
+
+        assert!(parse_yaml_content("- back:\n  timeout: soon\n", Path::new("t.yaml")).is_err());
+    }
+
+    #[test]
+    fn parses_any_of_selectors() {
+        use crate::parser::types::{AssertParamsInput, TapParamsInput};
+
+        let yaml = r#"
+- assertVisible:
+    anyOf:
+      - "Welcome"
+      - id: login_expired
+- tapOn:
+    anyOf: ["Accept all", { text: "OK", exact: true }]
+    optional: true
+"#;
+
+        let flow = parse_yaml_content(yaml, Path::new("test.yaml")).unwrap();
+        let TestCommand::AssertVisible(AssertParamsInput::Struct(assert)) = &flow.commands[0]
+        else {
+            panic!("unexpected command {:?}", flow.commands[0]);
+        };
+        let alternatives = assert.any_of.as_ref().unwrap();
+        assert!(matches!(&alternatives[0], AssertParamsInput::String(s) if s == "Welcome"));
+        assert!(
+            matches!(&alternatives[1], AssertParamsInput::Struct(p) if p.id.as_deref() == Some("login_expired"))
+        );
+
+        let TestCommand::TapOn(TapParamsInput::Struct(tap)) = &flow.commands[1] else {
+            panic!("unexpected command {:?}", flow.commands[1]);
+        };
+        assert!(tap.optional);
+        assert!(matches!(&tap.any_of.as_ref().unwrap()[1], TapParamsInput::Struct(p) if p.exact));
+    }
+
+    #[test]
+    fn scroll_fallback_flag_on_tap_and_assert() {
+        let yaml = r#"
+- tapOn:
+    text: "Privacy policy"
+    scroll: true
+- assertVisible:
+    id: footer
+    scroll: true
+"#;
+
+        let flow = parse_yaml_content(yaml, Path::new("test.yaml")).unwrap();
+        let TestCommand::TapOn(TapParamsInput::Struct(tap)) = &flow.commands[0] else {
+            panic!("unexpected command {:?}", flow.commands[0]);
+        };
+        assert!(tap.scroll);
+        let TestCommand::AssertVisible(AssertParamsInput::Struct(assert)) = &flow.commands[1]
+        else {
+            panic!("unexpected command {:?}", flow.commands[1]);
+        };
+        assert!(assert.scroll);
+    }
+
+    #[test]
+    fn assert_screenshot_flag_is_opt_in() {
+        let yaml = r#"
+- assertVisible:
+    text: "Order placed"
+    screenshot: true
+- assertVisible: "Order placed"
+"#;
+
+        let flow = parse_yaml_content(yaml, Path::new("test.yaml")).unwrap();
+        let TestCommand::AssertVisible(AssertParamsInput::Struct(assert)) = &flow.commands[0]
+        else {
+            panic!("unexpected command {:?}", flow.commands[0]);
+        };
+        assert!(assert.screenshot);
+        let TestCommand::AssertVisible(input) = &flow.commands[1] else {
+            panic!("unexpected command {:?}", flow.commands[1]);
+        };
+        assert!(!input.clone().into_inner().screenshot);
+    }
+
+    #[test]
+    fn parses_wait_for_any_options() {
+        let yaml = r#"
+- waitForFirst:
+    timeout: 8000
+    options:
+      - when: "Accept cookies"
+        then:
+          - tapOn: "Accept cookies"
+      - when:
+          id: home_tab
+"#;
+
+        let flow = parse_yaml_content(yaml, Path::new("test.yaml")).unwrap();
+        let TestCommand::WaitForAny(p) = &flow.commands[0] else {
+            panic!("unexpected command {:?}", flow.commands[0]);
+        };
+        assert_eq!(p.timeout, Some(8000));
+        assert!(!p.optional);
+        assert_eq!(p.options.len(), 2);
+        assert!(
+            matches!(&p.options[0].when, AssertParamsInput::String(s) if s == "Accept cookies")
+        );
+        assert!(matches!(p.options[0].then[0], TestCommand::TapOn(_)));
+        assert!(p.options[1].then.is_empty());
+
+        assert!(
+            parse_yaml_content("- waitForAny:\n    options: []\n", Path::new("t.yaml")).is_err()
+        );
+    }
+
+    #[test]
+    fn parses_assert_all_block() {
+        let yaml = r#"
+- assertAll:
+    soft: true
+    assertions:
+      - assertVisible: "Total"
+      - assertVar:
+          name: count
+          expected: "3"
+- assertAll:
+    - see: "Header"
+"#;
+
+        let flow = parse_yaml_content(yaml, Path::new("test.yaml")).unwrap();
+        let TestCommand::AssertAll(block) = &flow.commands[0] else {
+            panic!("unexpected command {:?}", flow.commands[0]);
+        };
+        assert!(block.soft);
+        assert_eq!(block.assertions.len(), 2);
+        assert!(matches!(block.assertions[1], TestCommand::AssertVar(_)));
+        let TestCommand::AssertAll(list) = &flow.commands[1] else {
+            panic!("unexpected command {:?}", flow.commands[1]);
+        };
+        assert!(!list.soft);
+        assert!(matches!(list.assertions[0], TestCommand::AssertVisible(_)));
+
+        assert!(parse_yaml_content("- assertAll: []\n", Path::new("t.yaml")).is_err());
+    }
+
+    #[test]
+    fn parses_optional_on_gesture_commands() {
+        let yaml = r#"
+- longPressOn:
+    text: "Delete"
+    optional: true
+- rightClick:
+    id: "row"
+    optional: true
+- inputAt:
+    type: EditText
+    text: "hi"
+    optional: true
+"#;
+
+        let flow = parse_yaml_content(yaml, Path::new("test.yaml")).unwrap();
+        assert!(
+            matches!(&flow.commands[0], TestCommand::LongPressOn(TapParamsInput::Struct(p)) if p.optional)
+        );
+        assert!(matches!(&flow.commands[1], TestCommand::RightClick(p) if p.optional));
+        assert!(matches!(&flow.commands[2], TestCommand::InputAt(p) if p.optional && p.index == 0));
+    }
+
+    #[test]
+    fn parses_input_text_clear_first() {
+        let yaml = r#"
+- inputText:
+    text: "new@example.com"
+    clearFirst: true
+- write: "plain"
+"#;
+
+        let flow = parse_yaml_content(yaml, Path::new("test.yaml")).unwrap();
+        assert!(matches!(&flow.commands[0], TestCommand::InputText(p) if p.clear_first()));
+        assert!(matches!(&flow.commands[1], TestCommand::InputText(p) if !p.clear_first()));
+    }
+
+    #[test]
+    fn parses_handle_system_dialogs() {
+        let yaml = "appId: com.example\nhandleSystemDialogs: Deny\n---\n- back\n";
+        let flow = parse_yaml_content(yaml, Path::new("test.yaml")).unwrap();
+        assert_eq!(flow.handle_system_dialogs, Some(SystemDialogAction::Deny));
+
+        let yaml = "appId: com.example\nhandleSystemDialogs: alow\n---\n- back\n";
+        let err = parse_yaml_content(yaml, Path::new("test.yaml")).unwrap_err();
+        assert!(format!("{:#}", err).contains("Use allow or deny"));
+    }
+
+    #[test]
+    fn parses_control_flow_commands() {
+        let yaml = r#"
+- repeat:
+    times: 3
+    commands:
+      - continue
+      - break
+- endFlow
+- stopFlow
+"#;
+
+        let flow = parse_yaml_content(yaml, Path::new("test.yaml")).unwrap();
+        match &flow.commands[0] {
+            TestCommand::Repeat(p) => {
+                assert!(matches!(p.commands[0], TestCommand::Continue));
+                assert!(matches!(p.commands[1], TestCommand::Break));
+            }
+            other => panic!("unexpected command {:?}", other),
+        }
+        assert!(matches!(flow.commands[1], TestCommand::StopFlow));
+        assert!(matches!(flow.commands[2], TestCommand::StopFlow));
+    }
+
+    #[test]
+    fn assert_url_and_capture_url_shorthands() {
+        let yaml = r#"
+url: https://example.com
+---
+- assertUrl: https://example.com/home
+- assertUrl:
+    contains: /orders/
+    matches: "/orders/\\d+$"
+- captureUrl: orderUrl
+"#;
+
+        let flow = parse_yaml_content(yaml, Path::new("test.yaml")).unwrap();
+        match &flow.commands[0] {
+            TestCommand::AssertUrl(p) => {
+                assert_eq!(p.equals.as_deref(), Some("https://example.com/home"));
+                assert!(p.contains.is_none());
+            }
+            other => panic!("unexpected command {:?}", other),
+        }
+        match &flow.commands[1] {
+            TestCommand::AssertUrl(p) => {
+                assert!(p.equals.is_none());
+                assert_eq!(p.contains.as_deref(), Some("/orders/"));
+                assert_eq!(p.matches.as_deref(), Some("/orders/\\d+$"));
+            }
+            other => panic!("unexpected command {:?}", other),
+        }
+        match &flow.commands[2] {
+            TestCommand::CaptureUrl(p) => assert_eq!(p.into, "orderUrl"),
+            other => panic!("unexpected command {:?}", other),
+        }
+    }
+
+    #[test]
+    fn relative_id_anchor_shorthand() {
+        use crate::parser::types::{AssertParamsInput, RelativeAnchorInput, RelativeParams};
+
+        let yaml = r#"
+appId: com.example.app
+---
+- assertVisible:
+    type: EditText
+    rightOfId: username_label
+"#;
+
+        let flow = parse_yaml_content(yaml, Path::new("test.yaml")).unwrap();
+        let TestCommand::AssertVisible(AssertParamsInput::Struct(p)) = &flow.commands[0] else {
+            panic!("unexpected command {:?}", flow.commands[0]);
+        };
+        let relative = RelativeParams::merge_shorthand(
+            &p.relative,
+            [&p.right_of, &p.left_of, &p.above, &p.below],
+            [&p.right_of_id, &p.left_of_id, &p.above_id, &p.below_id],
+        )
+        .unwrap();
+        match relative.right_of {
+            Some(RelativeAnchorInput::Struct(a)) => {
+                assert_eq!(a.id.as_deref(), Some("username_label"));
+                assert!(a.text.is_none());
+            }
+            other => panic!("expected id anchor, got {:?}", other),
+        }
+
+        let nested: RelativeParams =
+            serde_yaml::from_str("belowId: header_title\nmaxDist: 200").unwrap();
+        assert!(matches!(
+            nested.resolve_id_anchors().below,
+            Some(RelativeAnchorInput::Struct(ref a)) if a.id.as_deref() == Some("header_title")
+        ));
+    }
+
+    #[test]
+    fn relative_max_dist_units_and_angle() {
+        use crate::parser::types::{MaxDistance, RelativeParams};
+
+        let px: RelativeParams = serde_yaml::from_str("below: Title\nmaxDist: 200").unwrap();
+        assert_eq!(px.max_dist, Some(MaxDistance::Pixels(200)));
+
+        let suffixed: RelativeParams =
+            serde_yaml::from_str("below: Title\nmaxDist: 150px").unwrap();
+        assert_eq!(suffixed.max_dist, Some(MaxDistance::Pixels(150)));
+
+        let pct: RelativeParams =
+            serde_yaml::from_str("rightOf: Name\nmaxDist: \"25%\"\nangle: 15").unwrap();
+        assert_eq!(pct.max_dist, Some(MaxDistance::ScreenPercent(25.0)));
+        assert_eq!(pct.max_dist.unwrap().to_pixels(1080), 270);
+        assert_eq!(pct.angle, Some(15.0));
+
+        assert!(serde_yaml::from_str::<RelativeParams>("maxDist: \"150%\"").is_err());
+        assert!(serde_yaml::from_str::<RelativeParams>("maxDist: far").is_err());
+    }
+
+    #[test]
+    fn set_date_time_accepts_date_formats() {
+        use crate::parser::types::SetDateTimeParams;
+
+        let dt = SetDateTimeParams::parse("2026-01-15 09:30")
+            .unwrap()
+            .unwrap();
+        assert_eq!(dt.to_string(), "2026-01-15 09:30:00");
+        let dt = SetDateTimeParams::parse("2026-01-15").unwrap().unwrap();
+        assert_eq!(dt.to_string(), "2026-01-15 00:00:00");
+        assert!(SetDateTimeParams::parse("reset").unwrap().is_none());
+        assert!(SetDateTimeParams::parse("15/01/2026").is_err());
+    }
+
     #[test]
     fn map_format_preserves_supported_header_fields() {
         let yaml = r#"
@@ -1150,6 +2233,10 @@ browser: Firefox
 speed: fast
 defaultTimeout: 3000
 closeWhenFinish: false
+wsEndpoint: ws://grid.local:4444/devtools/browser/abc
+name: Welcome screen
+description: Shows the greeting
+baselines: ./baselines/web
 steps:
   - see: "Welcome"
 "#;
@@ -1162,6 +2249,13 @@ steps:
         assert_eq!(flow.speed, Some("fast".to_string()));
         assert_eq!(flow.default_timeout_ms, Some(3000));
         assert_eq!(flow.close_when_finish, Some(false));
+        assert_eq!(
+            flow.connect_url,
+            Some("ws://grid.local:4444/devtools/browser/abc".to_string())
+        );
+        assert_eq!(flow.title.as_deref(), Some("Welcome screen"));
+        assert_eq!(flow.description.as_deref(), Some("Shows the greeting"));
+        assert_eq!(flow.baselines.as_deref(), Some("./baselines/web"));
         assert_eq!(flow.commands.len(), 1);
     }
 
@@ -1321,4 +2415,64 @@ platform: android
             TestCommand::SetNetworkConditions(_)
         ));
     }
+
+    #[test]
+    fn parses_network_condition_profiles_and_custom() {
+        use crate::parser::types::{NetworkConditions, NetworkConditionsInput};
+
+        let yaml = r#"
+platform: web
+---
+- setNetworkConditions: 3g
+- setNetworkConditions:
+    profile: edge
+    latency: 300
+    packetLoss: 2
+"#;
+        let flow = parse_yaml_content(yaml, Path::new("test.yaml")).unwrap();
+        let resolved: Vec<NetworkConditions> = flow
+            .commands
+            .iter()
+            .map(|cmd| match cmd {
+                TestCommand::SetNetworkConditions(input) => input.resolve().unwrap(),
+                other => panic!("unexpected {:?}", other),
+            })
+            .collect();
+        assert_eq!(resolved[0].latency_ms, 560);
+        assert_eq!(resolved[0].download_kbps, Some(1475.0));
+        assert_eq!(resolved[1].name, "custom");
+        assert_eq!(resolved[1].latency_ms, 300);
+        assert_eq!(resolved[1].download_kbps, Some(240.0));
+        assert_eq!(resolved[1].packet_loss, 2.0);
+
+        assert!(NetworkConditions::profile("offline").unwrap().offline);
+        assert!(!NetworkConditions::profile("online").unwrap().is_throttled());
+        assert_eq!(
+            NetworkConditions::profile("Slow 3G").unwrap().name,
+            "slow-3g"
+        );
+        assert_eq!(
+            NetworkConditions::profile("fast_3g").unwrap().latency_ms,
+            560
+        );
+        assert!(NetworkConditionsInput::Profile("5g-ultra".into())
+            .resolve()
+            .is_err());
+    }
+
+    #[test]
+    fn parses_discrete_radio_commands() {
+        let yaml = r#"
+platform: android
+---
+- setWifi: false
+- setMobileData: on
+- toggleBluetooth: "off"
+"#;
+        let flow = parse_yaml_content(yaml, Path::new("test.yaml")).unwrap();
+        assert!(matches!(flow.commands[0], TestCommand::SetWifi(false)));
+        assert!(matches!(flow.commands[1], TestCommand::SetMobileData(true)));
+        assert!(matches!(flow.commands[2], TestCommand::SetBluetooth(false)));
+        assert!(parse_yaml_content("---\n- setWifi: maybe\n", Path::new("t.yaml")).is_err());
+    }
 }
@@ -0,0 +1,106 @@
+//! Custom commands compiled into the binary
+//!
+//! A plugin claims a command key that lumi-tester does not know. Register it
+//! with `lumi_tester::plugin::register(MyAuthStep)` before parsing or
+//! running tests; plugins built into the CLI are registered at startup by
+//! [`crate::plugins::register_builtin`].
+//!
+//! The parser then accepts `- myAuthStep: {...}` (and the bare `- myAuthStep`
+//! form) and the executor hands the raw params to the plugin. Built-in command
+//! names always win over a plugin with the same name.
+
+use crate::driver::traits::PlatformDriver;
+use crate::runner::context::TestContext;
+use anyhow::Result;
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::sync::{Arc, OnceLock, RwLock};
+
+#[async_trait]
+pub trait CommandPlugin: Send + Sync {
+    /// Command key used in YAML, e.g. `loginWithSso`
+    fn name(&self) -> &str;
+
+    /// Run the command. `params` is the YAML value under the key (`null` for
+    /// the bare form); use `ctx.substitute_vars` to expand `${var}` in strings.
+    async fn execute(
+        &self,
+        ctx: &mut TestContext,
+        driver: &dyn PlatformDriver,
+        params: &serde_json::Value,
+    ) -> Result<()>;
+}
+
+fn registry() -> &'static RwLock<HashMap<String, Arc<dyn CommandPlugin>>> {
+    static PLUGINS: OnceLock<RwLock<HashMap<String, Arc<dyn CommandPlugin>>>> = OnceLock::new();
+    PLUGINS.get_or_init(Default::default)
+}
+
+/// Register a plugin; a later plugin with the same name replaces the earlier one
+pub fn register(plugin: impl CommandPlugin + 'static) {
+    let name = plugin.name().to_string();
+    registry().write().unwrap().insert(name, Arc::new(plugin));
+}
+
+/// Plugin registered for a command key
+pub fn find(name: &str) -> Option<Arc<dyn CommandPlugin>> {
+    registry().read().unwrap().get(name).cloned()
+}
+
+pub fn is_registered(name: &str) -> bool {
+    registry().read().unwrap().contains_key(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::types::TestCommand;
+    use crate::parser::yaml::parse_yaml_content;
+    use std::path::Path;
+
+    struct SsoLogin;
+
+    #[async_trait]
+    impl CommandPlugin for SsoLogin {
+        fn name(&self) -> &str {
+            "testSsoLogin"
+        }
+
+        async fn execute(
+            &self,
+            ctx: &mut TestContext,
+            _driver: &dyn PlatformDriver,
+            params: &serde_json::Value,
+        ) -> Result<()> {
+            ctx.set_var("sso_user", params["user"].as_str().unwrap_or("guest"))?;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_registered_plugin_commands_parse() {
+        let yaml = "- testSsoLogin:\n    user: alice\n- testSsoLogin\n";
+        assert!(parse_yaml_content(yaml, Path::new("t.yaml")).is_err());
+
+        register(SsoLogin);
+        assert!(is_registered("testSsoLogin"));
+
+        let flow = parse_yaml_content(yaml, Path::new("t.yaml")).unwrap();
+        match &flow.commands[0] {
+            TestCommand::Plugin(p) => {
+                assert_eq!(p.name, "testSsoLogin");
+                assert_eq!(p.params["user"], "alice");
+            }
+            other => panic!("unexpected command {:?}", other),
+        }
+        assert!(matches!(&flow.commands[1], TestCommand::Plugin(p) if p.params.is_null()));
+
+        // Built-in names are never handed to plugins
+        assert!(matches!(
+            parse_yaml_content("- back\n", Path::new("t.yaml"))
+                .unwrap()
+                .commands[0],
+            TestCommand::Back
+        ));
+    }
+}
@@ -0,0 +1,9 @@
+//! Plugins compiled into the `lumi-tester` binary
+//!
+//! Put each plugin in its own module here and add a [`crate::plugin::register`]
+//! call to [`register_builtin`]; `main` runs it at startup, before any test file
+//! is parsed. Embedders using the library call `register` themselves.
+
+/// Register every plugin shipped with the binary, e.g.
+/// `crate::plugin::register(sso::SsoLogin)`
+pub fn register_builtin() {}
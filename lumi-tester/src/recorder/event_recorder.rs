@@ -1,9 +1,12 @@
-//! Event Recorder for capturing user interactions on Android devices
+//! Event Recorder for capturing user interactions on Android and iOS devices
 //!
 //! This module captures touch events and maps them to UI elements using
-//! the UIAutomator hierarchy dump.
+//! the UIAutomator hierarchy dump (Android) or the idb accessibility tree (iOS).
+//! iOS elements are converted to `UiElement` so selector scoring and YAML
+//! generation stay platform-agnostic.
 
 use anyhow::{Context, Result};
+use std::collections::HashMap;
 use std::process::Stdio;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
@@ -11,10 +14,11 @@ use tokio::process::Command;
 use tokio::sync::Mutex;
 
 use crate::driver::android::adb;
-use crate::driver::android::uiautomator::{self, UiElement};
+use crate::driver::android::uiautomator::{self, Bounds, UiElement};
+use crate::driver::ios::{accessibility, idb};
 use crate::utils::binary_resolver;
 
-use super::selector_scorer::{SelectorCandidate, SelectorScorer};
+use super::selector_scorer::{ScoringWeights, SelectorCandidate, SelectorScorer};
 
 /// Types of recorded actions
 #[derive(Debug, Clone)]
@@ -42,6 +46,9 @@ pub enum RecordedAction {
     /// Swipe gesture
     Swipe {
         direction: String,
+        start: (i32, i32),
+        end: (i32, i32),
+        duration_ms: u64,
         timestamp: Instant,
     },
     /// Wait/pause
@@ -51,6 +58,8 @@ pub enum RecordedAction {
     },
     /// App opened
     OpenApp { app_id: String, timestamp: Instant },
+    /// Text that appeared after a tap, offered as an assertion to review
+    SuggestedAssertion { text: String, timestamp: Instant },
 }
 
 impl RecordedAction {
@@ -63,7 +72,35 @@ impl RecordedAction {
             RecordedAction::Swipe { timestamp, .. } => *timestamp,
             RecordedAction::Wait { timestamp, .. } => *timestamp,
             RecordedAction::OpenApp { timestamp, .. } => *timestamp,
+            RecordedAction::SuggestedAssertion { timestamp, .. } => *timestamp,
+        }
+    }
+}
+
+/// Maximum finger travel (px) still treated as a tap rather than a swipe
+pub const TAP_MAX_DISTANCE_PX: i32 = 30;
+
+/// Whether a touch from `start` to `end` moved far enough to be a swipe
+pub fn is_swipe(start: (i32, i32), end: (i32, i32)) -> bool {
+    let dx = (end.0 - start.0) as f64;
+    let dy = (end.1 - start.1) as f64;
+    (dx * dx + dy * dy).sqrt() > TAP_MAX_DISTANCE_PX as f64
+}
+
+/// Direction of finger movement along the dominant axis ("up", "down", "left", "right")
+pub fn swipe_direction(start: (i32, i32), end: (i32, i32)) -> &'static str {
+    let dx = end.0 - start.0;
+    let dy = end.1 - start.1;
+    if dx.abs() > dy.abs() {
+        if dx > 0 {
+            "right"
+        } else {
+            "left"
         }
+    } else if dy > 0 {
+        "down"
+    } else {
+        "up"
     }
 }
 
@@ -85,9 +122,18 @@ enum TouchEventType {
     Move,
 }
 
+/// Platform the recorder is attached to
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RecorderPlatform {
+    Android,
+    Ios,
+}
+
 /// Event recorder that captures user interactions
 pub struct EventRecorder {
-    /// Device serial
+    /// Target platform
+    platform: RecorderPlatform,
+    /// Device serial (Android) or UDID (iOS)
     serial: Option<String>,
     /// Screen dimensions
     pub screen_width: u32,
@@ -102,6 +148,10 @@ pub struct EventRecorder {
     last_dump: Arc<Mutex<Instant>>,
     /// Current foreground app
     current_app: Arc<Mutex<Option<String>>>,
+    /// Last seen value of each text field, keyed by `field_key`
+    field_values: Arc<Mutex<Option<HashMap<String, String>>>>,
+    /// Selector scoring weights
+    weights: ScoringWeights,
 }
 
 impl EventRecorder {
@@ -109,8 +159,46 @@ impl EventRecorder {
     pub async fn new(serial: Option<&str>) -> Result<Self> {
         let (width, height) = adb::get_screen_size(serial).await?;
 
-        Ok(Self {
-            serial: serial.map(|s| s.to_string()),
+        Ok(Self::with_platform(
+            RecorderPlatform::Android,
+            serial.map(|s| s.to_string()),
+            width,
+            height,
+        ))
+    }
+
+    /// Create a new event recorder for an iOS simulator or device
+    ///
+    /// Falls back to the first booted target when no UDID is given.
+    pub async fn new_ios(udid: Option<&str>) -> Result<Self> {
+        let udid = match udid {
+            Some(u) => u.to_string(),
+            None => idb::list_targets()
+                .await?
+                .into_iter()
+                .find(|t| t.state == "Booted")
+                .map(|t| t.udid)
+                .ok_or_else(|| anyhow::anyhow!("No booted iOS simulator or device found"))?,
+        };
+        let (width, height) = idb::get_screen_size(&udid).await?;
+
+        Ok(Self::with_platform(
+            RecorderPlatform::Ios,
+            Some(udid),
+            width,
+            height,
+        ))
+    }
+
+    fn with_platform(
+        platform: RecorderPlatform,
+        serial: Option<String>,
+        width: u32,
+        height: u32,
+    ) -> Self {
+        Self {
+            platform,
+            serial,
             screen_width: width,
             screen_height: height,
             is_recording: Arc::new(Mutex::new(false)),
@@ -118,7 +206,34 @@ impl EventRecorder {
             ui_cache: Arc::new(Mutex::new(None)),
             last_dump: Arc::new(Mutex::new(Instant::now())),
             current_app: Arc::new(Mutex::new(None)),
-        })
+            field_values: Arc::new(Mutex::new(None)),
+            weights: ScoringWeights::default(),
+        }
+    }
+
+    /// Use custom selector scoring weights
+    pub fn with_weights(mut self, weights: ScoringWeights) -> Self {
+        self.weights = weights;
+        self
+    }
+
+    fn scorer(&self, elements: Vec<UiElement>) -> SelectorScorer {
+        SelectorScorer::with_weights(
+            self.screen_width,
+            self.screen_height,
+            elements,
+            self.weights,
+        )
+    }
+
+    /// Platform this recorder is attached to
+    pub fn platform(&self) -> RecorderPlatform {
+        self.platform
+    }
+
+    /// Device serial (Android) or UDID (iOS)
+    pub fn serial(&self) -> Option<&str> {
+        self.serial.as_deref()
     }
 
     /// Start recording user interactions
@@ -168,6 +283,11 @@ impl EventRecorder {
 
     /// Get the current foreground app package
     async fn get_foreground_app(&self) -> Result<Option<String>> {
+        // idb has no foreground-app query; iOS relies on --app
+        if self.platform == RecorderPlatform::Ios {
+            return Ok(None);
+        }
+
         // Don't use grep in shell - it may fail on some devices
         // Instead, get full output and filter in Rust
         let output = adb::shell(self.serial.as_deref(), "dumpsys activity activities")
@@ -195,6 +315,23 @@ impl EventRecorder {
 
     /// Refresh the UI hierarchy cache
     async fn refresh_ui_cache(&self) -> Result<()> {
+        let elements = match self.platform {
+            RecorderPlatform::Android => self.dump_android_hierarchy().await?,
+            RecorderPlatform::Ios => self.dump_ios_hierarchy().await?,
+        };
+
+        self.capture_text_changes(&elements).await;
+
+        let mut cache = self.ui_cache.lock().await;
+        *cache = Some(elements);
+
+        let mut last_dump = self.last_dump.lock().await;
+        *last_dump = Instant::now();
+
+        Ok(())
+    }
+
+    async fn dump_android_hierarchy(&self) -> Result<Vec<UiElement>> {
         let serial = self.serial.as_deref();
 
         // Dump UI hierarchy
@@ -206,14 +343,89 @@ impl EventRecorder {
 
         // Parse hierarchy
         let xml_content = std::fs::read_to_string(&temp_path)?;
-        let elements = uiautomator::parse_hierarchy(&xml_content)?;
+        uiautomator::parse_hierarchy(&xml_content)
+    }
 
-        let mut cache = self.ui_cache.lock().await;
-        *cache = Some(elements);
+    async fn dump_ios_hierarchy(&self) -> Result<Vec<UiElement>> {
+        let udid = self
+            .serial
+            .as_deref()
+            .ok_or_else(|| anyhow::anyhow!("No iOS UDID set"))?;
+        let json = idb::describe_ui(udid).await?;
+        let tree = accessibility::parse_ui_hierarchy(&json)?;
+
+        Ok(accessibility::flatten_elements(&tree)
+            .into_iter()
+            .filter(|e| e.visible)
+            .map(ios_to_ui_element)
+            .collect())
+    }
 
-        let mut last_dump = self.last_dump.lock().await;
-        *last_dump = Instant::now();
+    /// Record typed text by diffing text field values between hierarchy dumps
+    ///
+    /// Keyboards (including ADBKeyBoard) don't expose keystrokes to `getevent`,
+    /// so the recorder compares each field's value with the previous dump and
+    /// emits an input action for the ones that changed. Repeated changes to the
+    /// same field collapse into one action holding the latest value. Fields the
+    /// previous dump didn't have (a new screen, prefilled values) only seed the
+    /// baseline, since nothing was typed into them yet.
+    async fn capture_text_changes(&self, elements: &[UiElement]) {
+        let current: HashMap<String, String> = elements
+            .iter()
+            .filter(|e| is_text_field(e))
+            .map(|e| (field_key(e), field_value(e)))
+            .collect();
+
+        let mut snapshot = self.field_values.lock().await;
+        let previous = match snapshot.replace(current.clone()) {
+            Some(previous) => previous,
+            // First dump only establishes the baseline
+            None => return,
+        };
+        drop(snapshot);
+
+        for element in elements.iter().filter(|e| is_text_field(e)) {
+            let key = field_key(element);
+            let value = &current[&key];
+            match previous.get(&key) {
+                Some(old) if old != value && !value.is_empty() => {}
+                _ => continue,
+            }
+
+            let mut actions = self.actions.lock().await;
+            if let Some(RecordedAction::Input {
+                element: last,
+                text,
+                ..
+            }) = actions.last_mut()
+            {
+                if field_key(last) == key {
+                    *text = value.clone();
+                    continue;
+                }
+            }
+
+            let scorer = self.scorer(elements.to_vec());
+            let mut selectors = scorer.score_element(element);
+            // The typed value is not a stable way to find the field again
+            selectors.retain(|s| !(s.selector_type == "text" && &s.value == value));
+
+            println!("  ⌨️  inputText: {}", key);
+            actions.push(RecordedAction::Input {
+                element: element.clone(),
+                selectors,
+                text: value.clone(),
+                timestamp: Instant::now(),
+            });
+        }
+    }
 
+    /// Dump the hierarchy if it is older than `interval`, picking up typed text
+    pub async fn poll_text_input(&self, interval: Duration) -> Result<()> {
+        let last_dump = *self.last_dump.lock().await;
+        if last_dump.elapsed() > interval {
+            self.refresh_ui_cache().await?;
+        }
         Ok(())
     }
 
@@ -253,10 +465,20 @@ impl EventRecorder {
 
     /// Record a tap at specific coordinates
     pub async fn record_tap(&self, x: i32, y: i32) -> Result<()> {
+        let before = self.capture_tap(x, y).await?;
+
+        // Refresh UI after tap (state may have changed)
+        self.refresh_after_tap(&before).await
+    }
+
+    /// Map a tap to an element and store it, without refreshing the UI cache
+    ///
+    /// Returns the hierarchy the tap was resolved against.
+    async fn capture_tap(&self, x: i32, y: i32) -> Result<Vec<UiElement>> {
         let elements = self.get_ui_hierarchy().await?;
 
         if let Some(element) = self.find_element_at(&elements, x, y) {
-            let scorer = SelectorScorer::new(self.screen_width, self.screen_height, elements);
+            let scorer = self.scorer(elements.clone());
             let selectors = scorer.score_element(&element);
 
             let best = selectors
@@ -284,9 +506,22 @@ impl EventRecorder {
             );
         }
 
-        // Refresh UI after tap (state may have changed)
+        Ok(elements)
+    }
+
+    /// Refresh the hierarchy after a tap and suggest an assertion for new text
+    async fn refresh_after_tap(&self, before: &[UiElement]) -> Result<()> {
         self.refresh_ui_cache().await?;
 
+        let after = self.ui_cache.lock().await.clone().unwrap_or_default();
+        if let Some(text) = suggest_assertion_text(before, &after) {
+            let mut actions = self.actions.lock().await;
+            actions.push(RecordedAction::SuggestedAssertion {
+                text,
+                timestamp: Instant::now(),
+            });
+        }
+
         Ok(())
     }
 
@@ -298,8 +533,7 @@ impl EventRecorder {
         let focused = elements.iter().find(|e| e.focusable && e.enabled);
 
         if let Some(element) = focused {
-            let scorer =
-                SelectorScorer::new(self.screen_width, self.screen_height, elements.clone());
+            let scorer = self.scorer(elements.clone());
             let selectors = scorer.score_element(element);
 
             // Mask sensitive data
@@ -326,15 +560,28 @@ impl EventRecorder {
         Ok(())
     }
 
-    /// Record a swipe gesture
-    pub async fn record_swipe(&self, direction: &str) -> Result<()> {
-        println!("  👆 swipe: {}", direction);
+    /// Record a swipe gesture from `start` to `end` (screen coordinates)
+    pub async fn record_swipe(
+        &self,
+        start: (i32, i32),
+        end: (i32, i32),
+        duration_ms: u64,
+    ) -> Result<()> {
+        let direction = swipe_direction(start, end);
+        println!(
+            "  👆 swipe: {} ({},{} → {},{}, {}ms)",
+            direction, start.0, start.1, end.0, end.1, duration_ms
+        );
 
         let mut actions = self.actions.lock().await;
         actions.push(RecordedAction::Swipe {
             direction: direction.to_string(),
+            start,
+            end,
+            duration_ms,
             timestamp: Instant::now(),
         });
+        drop(actions);
 
         // Refresh UI after swipe
         self.refresh_ui_cache().await?;
@@ -342,6 +589,51 @@ impl EventRecorder {
         Ok(())
     }
 
+    /// Tap on the iOS device and record it
+    ///
+    /// iOS has no system-wide touch event stream, so the recorder drives the
+    /// device itself and records each forwarded action.
+    pub async fn perform_ios_tap(&self, x: i32, y: i32) -> Result<()> {
+        // Resolve the element before the tap changes the screen
+        let udid = self.ios_udid()?;
+        let before = self.capture_tap(x, y).await?;
+        idb::tap(udid, x, y).await?;
+        self.refresh_after_tap(&before).await
+    }
+
+    /// Type text on the iOS device and record it
+    pub async fn perform_ios_input(&self, text: &str) -> Result<()> {
+        let udid = self.ios_udid()?;
+        idb::input_text(udid, text).await?;
+        // The refresh diffs the fields and records the new value
+        self.refresh_ui_cache().await
+    }
+
+    /// Swipe on the iOS device and record it
+    pub async fn perform_ios_swipe(&self, direction: &str) -> Result<()> {
+        let udid = self.ios_udid()?;
+        let w = self.screen_width as i32;
+        let h = self.screen_height as i32;
+        let (x1, y1, x2, y2) = match direction {
+            "up" => (w / 2, h * 3 / 4, w / 2, h / 4),
+            "down" => (w / 2, h / 4, w / 2, h * 3 / 4),
+            "left" => (w * 3 / 4, h / 2, w / 4, h / 2),
+            "right" => (w / 4, h / 2, w * 3 / 4, h / 2),
+            _ => anyhow::bail!("Unknown swipe direction: {}", direction),
+        };
+        idb::swipe(udid, x1, y1, x2, y2, None).await?;
+        self.record_swipe((x1, y1), (x2, y2), 300).await
+    }
+
+    fn ios_udid(&self) -> Result<&str> {
+        if self.platform != RecorderPlatform::Ios {
+            anyhow::bail!("Not an iOS recorder");
+        }
+        self.serial
+            .as_deref()
+            .ok_or_else(|| anyhow::anyhow!("No iOS UDID set"))
+    }
+
     /// Poll for touch events using getevent
     /// This is a simplified polling approach that reads periodic UI state
     pub async fn poll_events(&self) -> Result<()> {
@@ -501,3 +793,141 @@ impl EventRecorder {
         self.current_app.lock().await.clone()
     }
 }
+
+/// Pick the most prominent text that appeared between two hierarchy dumps
+///
+/// Taller bounds are used as a proxy for larger fonts (titles, headers); ties
+/// go to the element closest to the top of the screen. Editable fields and
+/// long or purely numeric texts are skipped as they rarely make good assertions.
+pub fn suggest_assertion_text(before: &[UiElement], after: &[UiElement]) -> Option<String> {
+    let previous: std::collections::HashSet<&str> =
+        before.iter().map(|e| e.text.as_str()).collect();
+
+    after
+        .iter()
+        .filter(|e| {
+            let text = e.text.trim();
+            !text.is_empty()
+                && text.chars().count() <= 40
+                && !text
+                    .chars()
+                    .all(|c| c.is_ascii_digit() || c.is_ascii_punctuation())
+                && !is_text_field(e)
+                && !previous.contains(e.text.as_str())
+        })
+        .max_by_key(|e| (e.bounds.bottom - e.bounds.top, -e.bounds.top))
+        .map(|e| e.text.trim().to_string())
+}
+
+/// Whether the element is an editable text field
+fn is_text_field(el: &UiElement) -> bool {
+    el.class.contains("EditText") || el.class.contains("TextField")
+}
+
+/// Stable key for a text field across dumps
+fn field_key(el: &UiElement) -> String {
+    if el.resource_id.is_empty() {
+        format!(
+            "[{},{}][{},{}]",
+            el.bounds.left, el.bounds.top, el.bounds.right, el.bounds.bottom
+        )
+    } else {
+        el.resource_id.clone()
+    }
+}
+
+/// Current value of a text field, ignoring the hint shown when empty
+///
+/// Password fields only expose bullets, which are replaced by a mask so the
+/// generator treats them as sensitive.
+fn field_value(el: &UiElement) -> String {
+    if el.text == el.hint {
+        return String::new();
+    }
+    if !el.text.is_empty() && el.text.chars().all(|c| c == '•' || c == '*') {
+        return "********".to_string();
+    }
+    el.text.clone()
+}
+
+/// Convert an iOS accessibility element into the shared `UiElement` shape
+///
+/// The label doubles as text and content description, the accessibility
+/// identifier maps to `resource_id`.
+fn ios_to_ui_element(el: &accessibility::IosElement) -> UiElement {
+    let class = el.element_type.clone().unwrap_or_default();
+    let text = el
+        .label
+        .clone()
+        .filter(|l| !l.is_empty())
+        .or_else(|| el.value.clone())
+        .unwrap_or_default();
+    let is_input = class.contains("TextField") || class.contains("TextView");
+
+    UiElement {
+        class: class.clone(),
+        text,
+        resource_id: el.identifier.clone().unwrap_or_default(),
+        content_desc: el.label.clone().unwrap_or_default(),
+        bounds: Bounds {
+            left: el.frame.x as i32,
+            top: el.frame.y as i32,
+            right: (el.frame.x + el.frame.width) as i32,
+            bottom: (el.frame.y + el.frame.height) as i32,
+        },
+        clickable: el.enabled
+            && (class.contains("Button")
+                || class.contains("Cell")
+                || class.contains("Link")
+                || is_input),
+        enabled: el.enabled,
+        focusable: is_input,
+        focused: false,
+        hint: el.placeholder.clone().unwrap_or_default(),
+        scrollable: class.contains("ScrollView")
+            || class.contains("Table")
+            || class.contains("CollectionView"),
+        index: String::new(),
+        package: String::new(),
+        visible_to_user: el.visible,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn field(id: &str, text: &str) -> UiElement {
+        UiElement {
+            class: "android.widget.EditText".to_string(),
+            resource_id: id.to_string(),
+            text: text.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_text_changes_ignore_prefilled_fields_on_new_screen() {
+        let recorder = EventRecorder::with_platform(
+            RecorderPlatform::Android,
+            Some("emulator-5554".to_string()),
+            1080,
+            1920,
+        );
+        recorder.capture_text_changes(&[field("login", "")]).await;
+        // A new screen shows up with a prefilled field
+        recorder
+            .capture_text_changes(&[field("email", "saved@example.com")])
+            .await;
+        assert!(recorder.actions.lock().await.is_empty());
+
+        recorder
+            .capture_text_changes(&[field("email", "typed@example.com")])
+            .await;
+        let actions = recorder.actions.lock().await;
+        assert!(matches!(
+            actions.as_slice(),
+            [RecordedAction::Input { text, .. }] if text == "typed@example.com"
+        ));
+    }
+}
@@ -0,0 +1,205 @@
+//! GPS trace recorder for `lumi-tester record --gps`
+//!
+//! Polls the device's last known location from `dumpsys location` while the
+//! recording runs, keeps only fixes newer than the previous one (by their `et=`
+//! realtime stamp) and writes the trace as GPX, ready to replay with
+//! `mockLocation`. nl-mirror only accepts location pushes (`set_location`), so
+//! reading goes through the location service, which works on any device.
+//!
+//! The location service only refreshes its last known fix while some app
+//! requests updates. When no new fix arrives for [`STALE_AFTER`] the recorder
+//! warns once, so an idle device doesn't silently produce an empty trace.
+
+use anyhow::{Context, Result};
+use chrono::Utc;
+use regex::Regex;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+use tokio::sync::Mutex;
+
+use crate::driver::android::adb;
+use crate::parser::gps::{self, GpsPoint};
+
+/// Providers in order of preference when several report a location
+const PROVIDERS: [&str; 3] = ["gps", "fused", "network"];
+
+/// How long without a new fix before warning that nothing requests location
+pub const STALE_AFTER: Duration = Duration::from_secs(15);
+
+pub struct GpsTraceRecorder {
+    points: Arc<Mutex<Vec<GpsPoint>>>,
+    stop: Arc<AtomicBool>,
+    task: tokio::task::JoinHandle<()>,
+}
+
+impl GpsTraceRecorder {
+    /// Start polling `serial` every `interval`
+    pub fn start(serial: Option<String>, interval: Duration) -> Self {
+        let points = Arc::new(Mutex::new(Vec::new()));
+        let stop = Arc::new(AtomicBool::new(false));
+        let (trace, stopped) = (points.clone(), stop.clone());
+        let task = tokio::spawn(async move {
+            // Realtime of the newest fix seen; `None` until the first poll, whose
+            // cached fix may predate the recording and is skipped
+            let mut newest: Option<Option<Duration>> = None;
+            let mut last_fresh = std::time::Instant::now();
+            let mut warned = false;
+            while !stopped.load(Ordering::SeqCst) {
+                if let Ok(output) = adb::shell(serial.as_deref(), "dumpsys location").await {
+                    if let Some(fix) = parse_dumpsys_location(&output) {
+                        let fresh = match (newest, fix.elapsed) {
+                            (None, _) => false,
+                            (Some(Some(previous)), Some(elapsed)) => elapsed > previous,
+                            // No `et=` on this build: fall back to the movement check
+                            _ => true,
+                        };
+                        if newest.is_none_or(|n| fix.elapsed > n) {
+                            newest = Some(fix.elapsed);
+                        }
+                        let mut point = fix.point;
+                        point.timestamp = Some(Utc::now());
+                        let mut trace = trace.lock().await;
+                        // Only keep a fix when the device actually moved
+                        let moved = trace
+                            .last()
+                            .is_none_or(|p: &GpsPoint| p.lat != point.lat || p.lon != point.lon);
+                        if fresh {
+                            last_fresh = std::time::Instant::now();
+                        }
+                        if fresh && moved {
+                            trace.push(point);
+                        }
+                    }
+                }
+                if !warned && last_fresh.elapsed() >= STALE_AFTER {
+                    warned = true;
+                    println!(
+                        "⚠️  No new location fix for {}s. The device only updates its location \
+                         while an app requests it; keep a maps or navigation app open while recording.",
+                        STALE_AFTER.as_secs()
+                    );
+                }
+                tokio::time::sleep(interval).await;
+            }
+        });
+        Self { points, stop, task }
+    }
+
+    /// Stop polling and return the recorded trace
+    pub async fn stop(self) -> Vec<GpsPoint> {
+        self.stop.store(true, Ordering::SeqCst);
+        self.task.abort();
+        let _ = self.task.await;
+        let points = self.points.lock().await;
+        points.clone()
+    }
+}
+
+/// A last known location and when it was taken
+#[derive(Debug, Clone)]
+pub struct LocationFix {
+    pub point: GpsPoint,
+    /// Device uptime at the fix (`et=`), when reported
+    pub elapsed: Option<Duration>,
+}
+
+/// Best last known location in `dumpsys location` output, e.g.
+/// `Location[gps 10.776900,106.700900 hAcc=5 et=+1d2h alt=12.0 vel=0.0 ...]`
+pub fn parse_dumpsys_location(output: &str) -> Option<LocationFix> {
+    static LOCATION: OnceLock<Regex> = OnceLock::new();
+    let re = LOCATION.get_or_init(|| {
+        Regex::new(r"Location\[(\w+) (-?\d+(?:\.\d+)?),(-?\d+(?:\.\d+)?)([^\]]*)\]")
+            .expect("valid location regex")
+    });
+    let mut best: Option<(usize, LocationFix)> = None;
+    for caps in re.captures_iter(output) {
+        let Some(rank) = PROVIDERS.iter().position(|p| *p == &caps[1]) else {
+            continue;
+        };
+        if best.as_ref().is_some_and(|(r, _)| *r <= rank) {
+            continue;
+        }
+        let (Ok(lat), Ok(lon)) = (caps[2].parse::<f64>(), caps[3].parse::<f64>()) else {
+            continue;
+        };
+        let field = |name: &str| {
+            caps[4]
+                .split_whitespace()
+                .find_map(|field| field.strip_prefix(name))
+                .map(str::to_string)
+        };
+        let mut point = GpsPoint::new(lat, lon);
+        point.altitude = field("alt=").and_then(|alt| alt.parse().ok());
+        let elapsed = field("et=").and_then(|et| parse_elapsed(&et));
+        best = Some((rank, LocationFix { point, elapsed }));
+    }
+    best.map(|(_, fix)| fix)
+}
+
+/// Android duration format used by `et=`, e.g. `+1d2h3m4s567ms`
+fn parse_elapsed(value: &str) -> Option<Duration> {
+    let mut rest = value.strip_prefix('+').unwrap_or(value);
+    let mut total = Duration::ZERO;
+    while !rest.is_empty() {
+        let digits = rest.find(|c: char| !c.is_ascii_digit())?;
+        let amount: u64 = rest[..digits].parse().ok()?;
+        rest = &rest[digits..];
+        let (unit_ms, len) = if rest.starts_with("ms") {
+            (1, 2)
+        } else {
+            match rest.chars().next()? {
+                'd' => (86_400_000, 1),
+                'h' => (3_600_000, 1),
+                'm' => (60_000, 1),
+                's' => (1_000, 1),
+                _ => return None,
+            }
+        };
+        total += Duration::from_millis(amount * unit_ms);
+        rest = &rest[len..];
+    }
+    Some(total)
+}
+
+/// Write the trace to `path` as GPX
+pub fn save_gpx(points: &[GpsPoint], name: &str, path: &Path) -> Result<()> {
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, gps::write_gpx(points, name))
+        .with_context(|| format!("Failed to write GPS trace: {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_dumpsys_location_prefers_gps() {
+        let output = r#"
+Location Manager State:
+  network provider:
+    last location=Location[network 10.700000,106.600000 hAcc=20.0 et=+5m alt=3.0]
+  gps provider:
+    last location=Location[gps 10.776900,106.700900 hAcc=5.0 et=+1d2h alt=12.5 vel=3.1 bear=90.0]
+"#;
+        let fix = parse_dumpsys_location(output).unwrap();
+        assert_eq!((fix.point.lat, fix.point.lon), (10.7769, 106.7009));
+        assert_eq!(fix.point.altitude, Some(12.5));
+        assert_eq!(fix.elapsed, Some(Duration::from_secs(26 * 3600)));
+        assert!(parse_dumpsys_location("Location Manager State:\n").is_none());
+    }
+
+    #[test]
+    fn test_parse_elapsed() {
+        assert_eq!(
+            parse_elapsed("+1d2h3m4s567ms"),
+            Some(Duration::from_millis(93_784_567))
+        );
+        assert_eq!(parse_elapsed("+5m"), Some(Duration::from_secs(300)));
+        assert_eq!(parse_elapsed("+0ms"), Some(Duration::ZERO));
+        assert_eq!(parse_elapsed("+5x"), None);
+    }
+}
@@ -4,11 +4,14 @@
 //! - Smart selector scoring to choose the best element selector
 //! - Event recording to capture user interactions
 //! - YAML generation to output recorded actions
+//! - GPS trace recording to GPX for `mockLocation` replay
 
 pub mod event_recorder;
+pub mod gps_recorder;
 pub mod selector_scorer;
 pub mod yaml_generator;
 
-pub use event_recorder::EventRecorder;
-pub use selector_scorer::{SelectorCandidate, SelectorScorer};
+pub use event_recorder::{EventRecorder, RecorderPlatform};
+pub use gps_recorder::GpsTraceRecorder;
+pub use selector_scorer::{ScoringWeights, SelectorCandidate, SelectorScorer};
 pub use yaml_generator::YamlGenerator;
@@ -13,6 +13,9 @@
 //! - **XPath**: 50 points
 //! - **Type + Index**: 40 points
 //! - **Coordinates (%)**: 20 points
+//!
+//! Base scores can be tuned with [`ScoringWeights`] (e.g. teams that prefer
+//! test-ids over visible text).
 
 use crate::driver::android::uiautomator::{Bounds, UiElement};
 use regex::Regex;
@@ -155,19 +158,97 @@ static DYNAMIC_PATTERNS: LazyLock<Vec<(Regex, &'static str, &'static str)>> = La
     ]
 });
 
+/// Multipliers applied to base selector scores
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScoringWeights {
+    /// Resource ID / accessibility identifier
+    pub id: f32,
+    /// Content description (accessibility label)
+    pub accessibility_id: f32,
+    /// Visible text (and regex derived from it)
+    pub text: f32,
+    /// Applied on top of the others when a selector needs an index to be unique
+    pub indexed: f32,
+}
+
+impl Default for ScoringWeights {
+    fn default() -> Self {
+        Self {
+            id: 1.0,
+            accessibility_id: 1.0,
+            text: 1.0,
+            indexed: 1.0,
+        }
+    }
+}
+
+impl ScoringWeights {
+    /// Favor resource IDs / test-ids over visible text
+    pub fn prefer_id() -> Self {
+        Self {
+            id: 1.0,
+            accessibility_id: 0.85,
+            text: 0.75,
+            indexed: 0.8,
+        }
+    }
+
+    /// Favor visible text over resource IDs
+    pub fn prefer_text() -> Self {
+        Self {
+            id: 0.7,
+            accessibility_id: 0.95,
+            text: 1.2,
+            indexed: 1.0,
+        }
+    }
+
+    fn apply(&self, candidate: &SelectorCandidate, is_content_desc: bool) -> u32 {
+        let weight = match candidate.selector_type.as_str() {
+            "id" => self.id,
+            "text" if is_content_desc => self.accessibility_id,
+            "text" | "regex" => self.text,
+            _ => 1.0,
+        };
+        let weight = if candidate.index.is_some() {
+            weight * self.indexed
+        } else {
+            weight
+        };
+        ((candidate.score as f32 * weight).round() as u32).min(100)
+    }
+}
+
 /// Smart selector scorer
 pub struct SelectorScorer {
     screen_width: u32,
     screen_height: u32,
     all_elements: Vec<UiElement>,
+    weights: ScoringWeights,
 }
 
 impl SelectorScorer {
     pub fn new(screen_width: u32, screen_height: u32, all_elements: Vec<UiElement>) -> Self {
+        Self::with_weights(
+            screen_width,
+            screen_height,
+            all_elements,
+            ScoringWeights::default(),
+        )
+    }
+
+    /// Create a scorer with custom weights
+    pub fn with_weights(
+        screen_width: u32,
+        screen_height: u32,
+        all_elements: Vec<UiElement>,
+        weights: ScoringWeights,
+    ) -> Self {
         Self {
             screen_width,
             screen_height,
             all_elements,
+            weights,
         }
     }
 
@@ -317,6 +398,15 @@ impl SelectorScorer {
             is_stable: false,
         });
 
+        // Apply weights before ranking
+        if self.weights != ScoringWeights::default() {
+            for cand in &mut candidates {
+                let is_content_desc =
+                    !element.content_desc.is_empty() && cand.value == element.content_desc;
+                cand.score = self.weights.apply(cand, is_content_desc);
+            }
+        }
+
         // Sort by score (descending)
         candidates.sort_by(|a, b| b.score.cmp(&a.score));
 
@@ -672,3 +762,50 @@ fn get_short_type(class_name: &str) -> String {
             .to_string()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn element() -> UiElement {
+        UiElement {
+            class: "android.widget.Button".to_string(),
+            text: "Login".to_string(),
+            resource_id: "com.app:id/btn_login".to_string(),
+            content_desc: String::new(),
+            bounds: Bounds {
+                left: 0,
+                top: 0,
+                right: 100,
+                bottom: 50,
+            },
+            clickable: true,
+            enabled: true,
+            focusable: true,
+            focused: false,
+            hint: String::new(),
+            scrollable: false,
+            index: "0".to_string(),
+            package: "com.app".to_string(),
+            visible_to_user: true,
+        }
+    }
+
+    #[test]
+    fn test_weights_change_best_selector() {
+        let el = element();
+
+        let scorer = SelectorScorer::new(1080, 1920, vec![el.clone()]);
+        assert_eq!(scorer.score_element(&el)[0].selector_type, "id");
+
+        let scorer = SelectorScorer::with_weights(
+            1080,
+            1920,
+            vec![el.clone()],
+            ScoringWeights::prefer_text(),
+        );
+        let best = &scorer.score_element(&el)[0];
+        assert_eq!(best.selector_type, "text");
+        assert!(best.score <= 100);
+    }
+}
@@ -9,6 +9,7 @@ use std::path::Path;
 
 use super::event_recorder::RecordedAction;
 use super::selector_scorer::SelectorCandidate;
+use crate::driver::android::uiautomator::UiElement;
 
 /// YAML Generator configuration
 #[derive(Debug, Clone)]
@@ -68,11 +69,38 @@ impl YamlGenerator {
         output.push_str(&self.generate_header(app_id, name));
         output.push_str("---\n");
 
+        output.push_str(&self.generate_steps(actions));
+        output
+    }
+
+    /// Generate only the command steps, without a header
+    pub fn generate_steps(&self, actions: &[RecordedAction]) -> String {
+        let mut output = String::new();
+
         // Steps
         let mut prev_timestamp = None;
         let mut step_num = 0;
 
-        for action in actions {
+        for (i, action) in actions.iter().enumerate() {
+            // Suggestions are review comments attached to the previous step
+            if let RecordedAction::SuggestedAssertion { text, .. } = action {
+                if self.config.suggest_assertions {
+                    output.push_str(&format!(
+                        "# Suggested assertion (review, then uncomment):\n# - assertVisible: \"{}\"\n",
+                        text.replace('"', "\\\"")
+                    ));
+                }
+                continue;
+            }
+
+            // Earlier swipes of a scroll run are folded into the last one
+            if matches!(actions.get(i + 1), Some(RecordedAction::Swipe { .. }))
+                && self.scroll_target(actions, i).is_some()
+            {
+                prev_timestamp = Some(action.timestamp());
+                continue;
+            }
+
             step_num += 1;
 
             // Add wait if there was a significant pause
@@ -88,7 +116,10 @@ impl YamlGenerator {
 
             // Generate step
             output.push_str(&format!("\n# Step {}\n", step_num));
-            output.push_str(&self.generate_step(action));
+            match self.scroll_target(actions, i) {
+                Some(step) => output.push_str(&step),
+                None => output.push_str(&self.generate_step(action)),
+            }
 
             prev_timestamp = Some(action.timestamp());
         }
@@ -130,11 +161,24 @@ impl YamlGenerator {
             } => self.generate_long_press_step(selectors, *duration_ms),
 
             RecordedAction::Input {
-                selectors, text, ..
-            } => self.generate_input_step(selectors, text),
+                element,
+                selectors,
+                text,
+                ..
+            } => {
+                let sensitive_field = is_sensitive_field(element);
+                self.generate_input_step(selectors, text, sensitive_field)
+            }
 
-            RecordedAction::Swipe { direction, .. } => {
-                format!("- swipe: \"{}\"\n", direction)
+            RecordedAction::Swipe {
+                direction,
+                duration_ms,
+                ..
+            } => {
+                format!(
+                    "- swipe:\n    direction: \"{}\"\n    duration: {}\n",
+                    direction, duration_ms
+                )
             }
 
             RecordedAction::Wait { duration_ms, .. } => {
@@ -144,9 +188,49 @@ impl YamlGenerator {
             RecordedAction::OpenApp { app_id, .. } => {
                 format!("- open: \"{}\"\n", app_id)
             }
+
+            RecordedAction::SuggestedAssertion { .. } => String::new(),
         }
     }
 
+    /// Render a vertical swipe as `scrollUntilVisible` when the swipes lead to a tap
+    ///
+    /// Consecutive swipes in the same direction form one run, since
+    /// `scrollUntilVisible` repeats the scroll on its own.
+    fn scroll_target(&self, actions: &[RecordedAction], i: usize) -> Option<String> {
+        let RecordedAction::Swipe { direction, .. } = &actions[i] else {
+            return None;
+        };
+        // Swipes record the finger; scrollUntilVisible takes the scroll direction,
+        // so a finger moving up scrolls the content down
+        let scroll_direction = match direction.as_str() {
+            "up" => "down",
+            "down" => "up",
+            _ => return None,
+        };
+
+        let mut next = i + 1;
+        while let Some(RecordedAction::Swipe { direction: d, .. }) = actions.get(next) {
+            if d != direction {
+                return None;
+            }
+            next += 1;
+        }
+
+        let RecordedAction::Tap { selectors, .. } = actions.get(next)? else {
+            return None;
+        };
+        let best = selectors
+            .first()
+            .filter(|s| matches!(s.selector_type.as_str(), "id" | "text"))?;
+
+        Some(format!(
+            "{}\n    direction: \"{}\"\n",
+            best.to_yaml("scrollUntilVisible"),
+            scroll_direction
+        ))
+    }
+
     /// Generate YAML for a single selector candidate with comments
     pub fn generate_candidate_yaml(&self, candidate: &SelectorCandidate, action: &str) -> String {
         let mut output = String::new();
@@ -235,11 +319,18 @@ impl YamlGenerator {
     }
 
     /// Generate input step with masking for sensitive data
-    fn generate_input_step(&self, selectors: &[SelectorCandidate], text: &str) -> String {
+    fn generate_input_step(
+        &self,
+        selectors: &[SelectorCandidate],
+        text: &str,
+        sensitive_field: bool,
+    ) -> String {
         let mut output = String::new();
 
         // Detect and mask sensitive data
-        let (display_text, is_sensitive) = if self.config.mask_sensitive {
+        let (display_text, is_sensitive) = if self.config.mask_sensitive && sensitive_field {
+            ("********".to_string(), true)
+        } else if self.config.mask_sensitive {
             self.mask_sensitive_text(text)
         } else {
             (text.to_string(), false)
@@ -286,6 +377,11 @@ impl YamlGenerator {
 
     /// Mask sensitive data (passwords, tokens, etc.)
     fn mask_sensitive_text(&self, text: &str) -> (String, bool) {
+        // Already masked by the device (password fields)
+        if !text.is_empty() && text.chars().all(|c| c == '*') {
+            return ("********".to_string(), true);
+        }
+
         // Check for password patterns
         if text.len() >= 6 && !text.contains(' ') {
             // Could be a password if it has mixed chars or is all the same type
@@ -322,6 +418,92 @@ impl YamlGenerator {
 
         Ok(())
     }
+
+    /// Insert recorded steps into an existing flow, keeping its header
+    ///
+    /// Steps go before a `# RECORD_HERE` marker when present (the marker is
+    /// kept so later recordings land in the same place), otherwise before the
+    /// last command. The recorded app launch is dropped since the flow already
+    /// sets up its app.
+    pub fn append_to_file(&self, actions: &[RecordedAction], path: &Path) -> std::io::Result<()> {
+        let existing = std::fs::read_to_string(path)?;
+        let actions: Vec<RecordedAction> = actions
+            .iter()
+            .filter(|a| !matches!(a, RecordedAction::OpenApp { .. }))
+            .cloned()
+            .collect();
+        let content = merge_steps(&existing, &self.generate_steps(&actions));
+
+        std::fs::write(path, content)?;
+
+        println!("✅ Appended {} steps to: {}", actions.len(), path.display());
+
+        Ok(())
+    }
+}
+
+/// Marker line where `record --append` inserts new steps
+pub const RECORD_MARKER: &str = "# RECORD_HERE";
+
+/// Merge generated steps into existing flow content
+fn merge_steps(existing: &str, steps: &str) -> String {
+    let lines: Vec<&str> = existing.lines().collect();
+    let steps = steps.trim_start_matches('\n');
+
+    let insert_at = lines
+        .iter()
+        .position(|l| l.trim() == RECORD_MARKER)
+        .or_else(|| {
+            // Commands start after the header separator, if any
+            let body_start = lines
+                .iter()
+                .position(|l| l.trim() == "---")
+                .map(|i| i + 1)
+                .unwrap_or(0);
+            let mut last = lines
+                .iter()
+                .rposition(|l| l.starts_with("- "))
+                .filter(|&i| i >= body_start)?;
+            // Keep comments that belong to the last command attached to it
+            while last > body_start && lines[last - 1].trim_start().starts_with('#') {
+                last -= 1;
+            }
+            Some(last)
+        });
+
+    let mut output = String::new();
+    match insert_at {
+        Some(i) => {
+            for line in &lines[..i] {
+                output.push_str(line);
+                output.push('\n');
+            }
+            output.push_str(steps);
+            output.push('\n');
+            for line in &lines[i..] {
+                output.push_str(line);
+                output.push('\n');
+            }
+        }
+        None => {
+            output.push_str(existing.trim_end());
+            output.push_str("\n\n");
+            output.push_str(steps);
+        }
+    }
+    output
+}
+
+/// Whether the field's id, hint or description suggests a secret value
+fn is_sensitive_field(element: &UiElement) -> bool {
+    let haystack = format!(
+        "{} {} {}",
+        element.resource_id, element.hint, element.content_desc
+    )
+    .to_lowercase();
+    ["password", "passwd", "passcode", "otp", "secret"]
+        .iter()
+        .any(|k| haystack.contains(k))
 }
 
 impl Default for YamlGenerator {
@@ -333,7 +515,7 @@ impl Default for YamlGenerator {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::driver::android::uiautomator::{Bounds, UiElement};
+    use crate::driver::android::uiautomator::Bounds;
     use std::time::Instant;
 
     #[test]
@@ -354,10 +536,12 @@ mod tests {
             clickable: true,
             enabled: true,
             focusable: true,
+            focused: false,
             hint: String::new(),
             scrollable: false,
             index: "0".to_string(),
             package: "com.example".to_string(),
+            visible_to_user: true,
         };
 
         let selectors = vec![
@@ -407,5 +591,131 @@ mod tests {
         let (masked2, is_sensitive2) = generator.mask_sensitive_text("Hello World");
         assert!(!is_sensitive2);
         assert_eq!(masked2, "Hello World");
+
+        let (masked3, is_sensitive3) = generator.mask_sensitive_text("****");
+        assert!(is_sensitive3);
+        assert_eq!(masked3, "********");
+    }
+
+    #[test]
+    fn test_swipes_before_tap_become_scroll_until_visible() {
+        let generator = YamlGenerator::new();
+        let now = Instant::now();
+        let swipe = || RecordedAction::Swipe {
+            direction: "up".to_string(),
+            start: (500, 1500),
+            end: (500, 500),
+            duration_ms: 250,
+            timestamp: now,
+        };
+        let tap = RecordedAction::Tap {
+            element: UiElement {
+                class: "TextView".to_string(),
+                text: "Settings".to_string(),
+                resource_id: String::new(),
+                content_desc: String::new(),
+                bounds: Bounds::default(),
+                clickable: true,
+                enabled: true,
+                focusable: false,
+                focused: false,
+                hint: String::new(),
+                scrollable: false,
+                index: "0".to_string(),
+                package: String::new(),
+                visible_to_user: true,
+            },
+            selectors: vec![SelectorCandidate {
+                selector_type: "text".to_string(),
+                value: "Settings".to_string(),
+                score: 80,
+                reason: "Text".to_string(),
+                is_stable: true,
+                index: None,
+                relative_anchor: None,
+                relative_direction: None,
+            }],
+            timestamp: now,
+        };
+
+        let yaml = generator.generate(&[swipe(), swipe(), tap.clone()], None, None);
+        assert_eq!(yaml.matches("scrollUntilVisible").count(), 1);
+        // Finger up reveals content further down
+        assert!(yaml.contains("    direction: \"down\""));
+        assert!(!yaml.contains("- swipe:"));
+
+        let swipe_down = RecordedAction::Swipe {
+            direction: "down".to_string(),
+            start: (500, 500),
+            end: (500, 1500),
+            duration_ms: 250,
+            timestamp: now,
+        };
+        let yaml = generator.generate(&[swipe_down, tap], None, None);
+        assert!(yaml.contains("    direction: \"up\""));
+
+        let yaml = generator.generate(&[swipe()], None, None);
+        assert!(yaml.contains("- swipe:\n    direction: \"up\"\n    duration: 250"));
+    }
+
+    #[test]
+    fn test_suggested_assertion_rendered_as_comment() {
+        use crate::recorder::event_recorder::suggest_assertion_text;
+
+        let text_element = |text: &str, top: i32, bottom: i32| UiElement {
+            class: "android.widget.TextView".to_string(),
+            text: text.to_string(),
+            resource_id: String::new(),
+            content_desc: String::new(),
+            bounds: Bounds {
+                left: 0,
+                top,
+                right: 100,
+                bottom,
+            },
+            clickable: false,
+            enabled: true,
+            focusable: false,
+            focused: false,
+            hint: String::new(),
+            scrollable: false,
+            index: "0".to_string(),
+            package: String::new(),
+            visible_to_user: true,
+        };
+
+        let before = vec![text_element("Login", 0, 50)];
+        let after = vec![
+            text_element("Login", 0, 50),
+            text_element("Welcome back", 100, 180),
+            text_element("Signed in", 200, 240),
+            text_element("42", 300, 400),
+        ];
+        let text = suggest_assertion_text(&before, &after).unwrap();
+        assert_eq!(text, "Welcome back");
+
+        let actions = vec![RecordedAction::SuggestedAssertion {
+            text,
+            timestamp: Instant::now(),
+        }];
+        let yaml = YamlGenerator::new().generate(&actions, None, None);
+        assert!(yaml.contains("# - assertVisible: \"Welcome back\""));
+        assert!(!yaml.contains("# Step"));
+    }
+
+    #[test]
+    fn test_merge_steps_at_marker_and_before_last_command() {
+        let existing =
+            "appId: com.example\ntags: [smoke]\n---\n- launchApp\n# RECORD_HERE\n- stopApp\n";
+        let merged = merge_steps(existing, "\n- tap: \"Login\"\n");
+        assert_eq!(
+            merged,
+            "appId: com.example\ntags: [smoke]\n---\n- launchApp\n- tap: \"Login\"\n\n# RECORD_HERE\n- stopApp\n"
+        );
+
+        let existing = "appId: com.example\n---\n- launchApp\n# Cleanup\n- stopApp\n";
+        let merged = merge_steps(existing, "- tap: \"Login\"\n");
+        assert!(merged.starts_with("appId: com.example\n---\n- launchApp\n- tap: \"Login\"\n"));
+        assert!(merged.ends_with("# Cleanup\n- stopApp\n"));
     }
 }
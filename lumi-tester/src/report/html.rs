@@ -1,5 +1,5 @@
-use super::types::TestResults;
-use crate::runner::state::{CommandStatus, FlowStatus};
+use super::types::{PerformanceSample, TestResults};
+use crate::runner::state::{CommandStateReport, CommandStatus, FlowStatus};
 use anyhow::Result;
 use std::path::Path;
 
@@ -30,6 +30,7 @@ fn generate_html(results: &TestResults) -> String {
         let (flow_status_text, flow_status_class) = match flow.status {
             FlowStatus::Passed => ("Passed", "passed"),
             FlowStatus::Failed => ("Failed", "failed"),
+            FlowStatus::Skipped { .. } => ("Skipped", "skipped"),
             _ => ("Partial", "partial"),
         };
 
@@ -100,9 +101,25 @@ fn generate_html(results: &TestResults) -> String {
 
         let duration_html = flow
             .total_duration_ms
-            .map(|d| format!("<span class=\"duration\">{}ms</span>", d))
+            .map(|d| match flow.nested_duration_ms {
+                Some(nested) => format!(
+                    "<span class=\"duration\">{}ms (nested flows {}ms)</span>",
+                    d, nested
+                ),
+                None => format!("<span class=\"duration\">{}ms</span>", d),
+            })
             .unwrap_or_default();
 
+        // Nested runFlow/repeat/retry flows follow their parent, indented by depth
+        let (nested_class, nested_style) = if flow.depth > 0 {
+            (
+                " nested",
+                format!(r#" style="margin-left: {}rem""#, flow.depth * 2),
+            )
+        } else {
+            ("", String::new())
+        };
+
         let video_html = if let Some(path) = &flow.video_path {
             format!(
                 r#"
@@ -122,25 +139,81 @@ fn generate_html(results: &TestResults) -> String {
             String::new()
         };
 
+        let waterfall_html = render_waterfall(&flow.commands);
+
+        let perf_html = results
+            .performance
+            .iter()
+            .find(|p| p.flow_path == flow.flow_path)
+            .map(|p| render_performance_charts(&p.samples, &flow.commands))
+            .unwrap_or_default();
+
+        let device_html = flow
+            .device
+            .as_ref()
+            .map(|d| format!(r#"<span class="device-badge">📱 {}</span>"#, html_escape(d)))
+            .unwrap_or_default();
+
+        let tags_html: String = flow
+            .metadata
+            .tags
+            .iter()
+            .map(|t| format!(r#"<span class="tag-badge">#{}</span>"#, html_escape(t)))
+            .collect();
+        let data_tags = html_escape(&flow.metadata.tags.join(" "));
+
+        let mut info_html = String::new();
+        if let Some(title) = &flow.metadata.title {
+            info_html.push_str(&format!(
+                r#"<div class="flow-title">{}</div>"#,
+                html_escape(title)
+            ));
+        }
+        if let Some(description) = &flow.metadata.description {
+            info_html.push_str(&format!(
+                r#"<div class="flow-description">{}</div>"#,
+                html_escape(description)
+            ));
+        }
+        if let FlowStatus::Skipped { reason } = &flow.status {
+            info_html.push_str(&format!(
+                r#"<div class="flow-description">{}</div>"#,
+                html_escape(reason)
+            ));
+        }
+        if !info_html.is_empty() {
+            info_html = format!(r#"<div class="flow-info">{}</div>"#, info_html);
+        }
+
         flows_html.push_str(&format!(
             r#"
-            <div class="flow {flow_status_class}">
+            <div class="flow {flow_status_class}{nested_class}"{nested_style} data-tags="{data_tags}">
                 <div class="flow-header">
-                    <h3>{} <span class="flow-status-badge">{flow_status_text}</span></h3>
+                    <h3>{} <span class="flow-status-badge">{flow_status_text}</span>{device_html}{tags_html}</h3>
                     {duration_html}
                 </div>
+                {info_html}
                 <div class="commands">
                     {commands_html}
                 </div>
+                {waterfall_html}
+                {perf_html}
                 {video_html}
             </div>
             </div>
         "#,
             html_escape(&flow.flow_name),
+            device_html = device_html,
+            tags_html = tags_html,
+            info_html = info_html,
+            waterfall_html = waterfall_html,
+            perf_html = perf_html,
             video_html = video_html
         ));
     }
 
+    let tag_filter_html = render_tag_filter(results);
+
     format!(
         r#"<!DOCTYPE html>
 <html lang="en">
@@ -269,6 +342,10 @@ fn generate_html(results: &TestResults) -> String {
             box-shadow: 0 4px 6px -1px rgba(0, 0, 0, 0.1);
         }}
         
+        .flow.nested {{
+            border-left: 3px solid var(--border);
+        }}
+
         .flow-header {{
             padding: 1.5rem;
             background: var(--glass);
@@ -294,8 +371,64 @@ fn generate_html(results: &TestResults) -> String {
             text-transform: uppercase;
         }}
         
+        .device-badge {{
+            padding: 0.25rem 0.75rem;
+            border-radius: 9999px;
+            font-size: 0.75rem;
+            font-weight: 600;
+            font-family: 'JetBrains Mono', monospace;
+            background: rgba(59, 130, 246, 0.1);
+            color: var(--blue);
+        }}
+        
+        .tag-badge {{
+            padding: 0.25rem 0.625rem;
+            border-radius: 9999px;
+            font-size: 0.75rem;
+            font-weight: 600;
+            background: rgba(139, 92, 246, 0.1);
+            color: var(--purple);
+        }}
+        
+        .flow-info {{
+            padding: 0.75rem 1.5rem 0;
+        }}
+        
+        .flow-title {{
+            font-weight: 600;
+        }}
+        
+        .flow-description {{
+            font-size: 0.875rem;
+            color: var(--text-secondary);
+            margin-top: 0.25rem;
+        }}
+        
+        .tag-filter {{
+            display: flex;
+            flex-wrap: wrap;
+            gap: 0.5rem;
+            margin-bottom: 1.5rem;
+        }}
+        
+        .tag-filter button {{
+            padding: 0.375rem 0.875rem;
+            border-radius: 9999px;
+            border: 1px solid rgba(139, 92, 246, 0.3);
+            background: transparent;
+            color: var(--text-secondary);
+            font-size: 0.8125rem;
+            cursor: pointer;
+        }}
+        
+        .tag-filter button.active {{
+            background: rgba(139, 92, 246, 0.15);
+            color: var(--purple);
+        }}
+        
         .flow.passed .flow-status-badge {{ background: rgba(16, 185, 129, 0.1); color: var(--green); }}
         .flow.failed .flow-status-badge {{ background: rgba(239, 68, 68, 0.1); color: var(--red); }}
+        .flow.skipped .flow-status-badge {{ background: rgba(245, 158, 11, 0.1); color: var(--yellow); }}
         
         .commands {{
             padding: 1rem 1.5rem;
@@ -417,6 +550,98 @@ fn generate_html(results: &TestResults) -> String {
             display: flex;
         }}
         
+        .waterfall {{
+            margin: 0rem 1.5rem 1rem 1.5rem;
+            padding: 1rem;
+            background: rgba(0, 0, 0, 0.2);
+            border-radius: 0.75rem;
+            border: 1px solid var(--border);
+        }}
+        
+        .waterfall summary {{
+            cursor: pointer;
+            font-weight: 600;
+            color: var(--blue);
+            outline: none;
+            user-select: none;
+        }}
+        
+        .waterfall-row {{
+            display: grid;
+            grid-template-columns: minmax(0, 14rem) 1fr 4.5rem;
+            gap: 0.75rem;
+            align-items: center;
+            margin-top: 0.375rem;
+            font-size: 0.75rem;
+        }}
+        
+        .waterfall-label {{
+            font-family: 'JetBrains Mono', monospace;
+            color: var(--text-secondary);
+            white-space: nowrap;
+            overflow: hidden;
+            text-overflow: ellipsis;
+        }}
+        
+        .waterfall-track {{
+            position: relative;
+            height: 0.75rem;
+            background: var(--bg-tertiary);
+            border-radius: 0.25rem;
+        }}
+        
+        .waterfall-bar {{
+            position: absolute;
+            top: 0;
+            height: 100%;
+            min-width: 2px;
+            border-radius: 0.25rem;
+            background: var(--green);
+        }}
+        
+        .waterfall-bar.failed {{ background: var(--red); }}
+        .waterfall-bar.slow {{ background: var(--yellow); }}
+        
+        .waterfall-duration {{
+            text-align: right;
+            color: var(--text-secondary);
+            font-family: 'JetBrains Mono', monospace;
+        }}
+        
+        .perf-details {{
+            margin: 0rem 1.5rem 1rem 1.5rem;
+            padding: 1rem;
+            background: rgba(0, 0, 0, 0.2);
+            border-radius: 0.75rem;
+            border: 1px solid var(--border);
+        }}
+        
+        .perf-details summary {{
+            cursor: pointer;
+            font-weight: 600;
+            color: var(--purple);
+            outline: none;
+            user-select: none;
+        }}
+        
+        .perf-chart {{
+            margin-top: 1rem;
+        }}
+        
+        .perf-chart-title {{
+            font-size: 0.8125rem;
+            font-weight: 600;
+            color: var(--text-secondary);
+            margin-bottom: 0.25rem;
+        }}
+        
+        .perf-chart svg {{
+            width: 100%;
+            max-width: 640px;
+            height: auto;
+            display: block;
+        }}
+        
         .video-details {{
             margin: 0rem 1.5rem 1rem 1.5rem;
             padding: 1rem;
@@ -494,6 +719,7 @@ fn generate_html(results: &TestResults) -> String {
             </div>
         </div>
         
+        {tag_filter_html}
         {flows_html}
         
         <div class="meta">
@@ -514,6 +740,15 @@ fn generate_html(results: &TestResults) -> String {
             modal.classList.add('active');
             event.stopPropagation();
         }}
+
+        function filterByTag(button) {{
+            const tag = button.dataset.tag;
+            document.querySelectorAll('.tag-filter button').forEach(b => b.classList.toggle('active', b === button));
+            document.querySelectorAll('.flow').forEach(flow => {{
+                const tags = (flow.dataset.tags || '').split(' ');
+                flow.style.display = !tag || tags.includes(tag) ? '' : 'none';
+            }});
+        }}
     </script>
 </body>
 </html>"#,
@@ -528,6 +763,232 @@ fn generate_html(results: &TestResults) -> String {
     )
 }
 
+/// Buttons that show only the flows carrying one tag; empty when no flow is tagged
+fn render_tag_filter(results: &TestResults) -> String {
+    let mut tags: Vec<&String> = results
+        .flows
+        .iter()
+        .flat_map(|f| f.metadata.tags.iter())
+        .collect();
+    tags.sort();
+    tags.dedup();
+    if tags.is_empty() {
+        return String::new();
+    }
+
+    let buttons: String = tags
+        .iter()
+        .map(|t| {
+            let tag = html_escape(t);
+            format!(
+                r#"<button data-tag="{tag}" onclick="filterByTag(this)">#{tag}</button>"#,
+                tag = tag
+            )
+        })
+        .collect();
+    format!(
+        r#"<div class="tag-filter"><button class="active" data-tag="" onclick="filterByTag(this)">All</button>{}</div>"#,
+        buttons
+    )
+}
+
+/// Commands taking at least this share of the flow are highlighted as slow
+const SLOW_COMMAND_SHARE: f64 = 0.25;
+
+/// Render a horizontal waterfall of command durations, laid out back to back
+fn render_waterfall(commands: &[CommandStateReport]) -> String {
+    let total: u64 = commands.iter().filter_map(|c| c.duration_ms).sum();
+    if total == 0 {
+        return String::new();
+    }
+
+    let mut rows = String::new();
+    let mut offset = 0u64;
+    for cmd in commands {
+        let Some(duration) = cmd.duration_ms else {
+            continue;
+        };
+        let left = offset as f64 / total as f64 * 100.0;
+        let width = duration as f64 / total as f64 * 100.0;
+        offset += duration;
+
+        let bar_class = if matches!(cmd.status, CommandStatus::Failed { .. }) {
+            "failed"
+        } else if width / 100.0 >= SLOW_COMMAND_SHARE {
+            "slow"
+        } else {
+            ""
+        };
+
+        rows.push_str(&format!(
+            r#"
+                        <div class="waterfall-row" title="{label} – {duration}ms">
+                            <div class="waterfall-label">{index}. {label}</div>
+                            <div class="waterfall-track"><div class="waterfall-bar {bar_class}" style="left: {left:.2}%; width: {width:.2}%"></div></div>
+                            <div class="waterfall-duration">{duration_text}</div>
+                        </div>"#,
+            label = html_escape(&cmd.command_display),
+            index = cmd.index + 1,
+            duration = duration,
+            bar_class = bar_class,
+            left = left,
+            width = width,
+            duration_text = format_duration(duration),
+        ));
+    }
+
+    format!(
+        r#"
+                <div class="waterfall">
+                    <details>
+                        <summary>⏱ Timing ({})</summary>
+                        {}
+                    </details>
+                </div>
+            "#,
+        format_duration(total),
+        rows
+    )
+}
+
+/// Chart width/height in SVG user units
+const CHART_WIDTH: f64 = 640.0;
+const CHART_HEIGHT: f64 = 160.0;
+/// Left/bottom padding for axis labels
+const CHART_PAD_X: f64 = 56.0;
+const CHART_PAD_Y: f64 = 20.0;
+
+/// Render one inline SVG line chart per metric (no external JS)
+fn render_performance_charts(
+    samples: &[PerformanceSample],
+    commands: &[CommandStateReport],
+) -> String {
+    if samples.is_empty() {
+        return String::new();
+    }
+
+    let mut names: Vec<&String> = samples.iter().flat_map(|s| s.metrics.keys()).collect();
+    names.sort();
+    names.dedup();
+
+    let max_elapsed = samples
+        .iter()
+        .map(|s| s.elapsed_ms)
+        .max()
+        .unwrap_or(0)
+        .max(1) as f64;
+    let plot_w = CHART_WIDTH - CHART_PAD_X - 8.0;
+    let plot_h = CHART_HEIGHT - CHART_PAD_Y * 2.0;
+
+    let mut charts = String::new();
+    for name in names {
+        let points: Vec<(&PerformanceSample, f64)> = samples
+            .iter()
+            .filter_map(|s| s.metrics.get(name).map(|v| (s, *v)))
+            .collect();
+        let min = points.iter().map(|(_, v)| *v).fold(f64::INFINITY, f64::min);
+        let max = points
+            .iter()
+            .map(|(_, v)| *v)
+            .fold(f64::NEG_INFINITY, f64::max);
+        // Flat series still get a visible band
+        let (lo, hi) = if (max - min).abs() < f64::EPSILON {
+            (min - 1.0, max + 1.0)
+        } else {
+            (min, max)
+        };
+        let unit = metric_unit(name);
+
+        let coords: Vec<(f64, f64)> = points
+            .iter()
+            .map(|(s, v)| {
+                let x = CHART_PAD_X + s.elapsed_ms as f64 / max_elapsed * plot_w;
+                let y = CHART_PAD_Y + (hi - v) / (hi - lo) * plot_h;
+                (x, y)
+            })
+            .collect();
+        let polyline = coords
+            .iter()
+            .map(|(x, y)| format!("{:.1},{:.1}", x, y))
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        let mut dots = String::new();
+        for ((sample, value), (x, y)) in points.iter().zip(&coords) {
+            let label = commands
+                .get(sample.command_index)
+                .map(|c| c.command_display.as_str())
+                .unwrap_or("");
+            dots.push_str(&format!(
+                r##"<circle cx="{:.1}" cy="{:.1}" r="3" fill="#8b5cf6"><title>#{} {} @ {}: {:.1}{}</title></circle>"##,
+                x,
+                y,
+                sample.command_index + 1,
+                html_escape(label),
+                format_duration(sample.elapsed_ms),
+                value,
+                unit
+            ));
+        }
+
+        charts.push_str(&format!(
+            r##"
+                    <div class="perf-chart">
+                        <div class="perf-chart-title">{title} (min {min:.1}{unit}, max {max:.1}{unit})</div>
+                        <svg viewBox="0 0 {w} {h}" xmlns="http://www.w3.org/2000/svg">
+                            <line x1="{px}" y1="{top}" x2="{px}" y2="{bottom}" stroke="#374151"/>
+                            <line x1="{px}" y1="{bottom}" x2="{right}" y2="{bottom}" stroke="#374151"/>
+                            <text x="{lx}" y="{top_label}" fill="#9ca3af" font-size="11" text-anchor="end">{hi:.1}</text>
+                            <text x="{lx}" y="{bottom}" fill="#9ca3af" font-size="11" text-anchor="end">{lo:.1}</text>
+                            <text x="{right}" y="{h}" fill="#9ca3af" font-size="11" text-anchor="end">{elapsed}</text>
+                            <polyline points="{polyline}" fill="none" stroke="#8b5cf6" stroke-width="2"/>
+                            {dots}
+                        </svg>
+                    </div>"##,
+            title = html_escape(name),
+            min = min,
+            max = max,
+            unit = unit,
+            w = CHART_WIDTH,
+            h = CHART_HEIGHT,
+            px = CHART_PAD_X,
+            top = CHART_PAD_Y,
+            top_label = CHART_PAD_Y + 4.0,
+            bottom = CHART_PAD_Y + plot_h,
+            right = CHART_PAD_X + plot_w,
+            lx = CHART_PAD_X - 6.0,
+            hi = hi,
+            lo = lo,
+            elapsed = format_duration(max_elapsed as u64),
+            polyline = polyline,
+            dots = dots,
+        ));
+    }
+
+    format!(
+        r#"
+                <div class="perf-details">
+                    <details open>
+                        <summary>⚡ Performance ({} samples)</summary>
+                        {}
+                    </details>
+                </div>
+            "#,
+        samples.len(),
+        charts
+    )
+}
+
+/// Display unit for well-known metrics from get_performance_metrics
+fn metric_unit(name: &str) -> &'static str {
+    match name.to_lowercase().as_str() {
+        "memory" => " MB",
+        "cpu" => "%",
+        "fps" => " fps",
+        _ => "",
+    }
+}
+
 fn html_escape(s: &str) -> String {
     s.replace('&', "&amp;")
         .replace('<', "&lt;")
@@ -546,3 +1007,145 @@ fn format_duration(ms: u64) -> String {
         format!("{}m {:.0}s", minutes, seconds)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn command(index: usize, display: &str, duration_ms: Option<u64>) -> CommandStateReport {
+        CommandStateReport {
+            index,
+            command_name: display.to_string(),
+            command_display: display.to_string(),
+            status: CommandStatus::Passed,
+            duration_ms,
+            screenshot_path: None,
+            ui_hierarchy_path: None,
+            log_path: None,
+            retry_count: 0,
+        }
+    }
+
+    #[test]
+    fn test_render_waterfall() {
+        let commands = vec![
+            command(0, "launchApp", Some(1000)),
+            command(1, "wait", Some(5000)),
+            command(2, "tapOn", Some(4000)),
+            command(3, "assertVisible", None),
+        ];
+
+        let html = render_waterfall(&commands);
+        assert!(html.contains("Timing (10.0s)"));
+        assert_eq!(html.matches("waterfall-row").count(), 3);
+        // Bars are laid out back to back
+        assert!(html.contains("left: 10.00%; width: 50.00%"));
+        assert!(html.contains("left: 60.00%; width: 40.00%"));
+        assert_eq!(html.matches("waterfall-bar slow").count(), 2);
+
+        assert!(render_waterfall(&[command(0, "back", None)]).is_empty());
+    }
+
+    #[test]
+    fn test_render_flow_metadata_and_tag_filter() {
+        use crate::runner::state::{FlowMetadata, FlowStateReport, TestSummary};
+
+        let flow = |name: &str, tags: &[&str]| FlowStateReport {
+            id: String::new(),
+            depth: 0,
+            parent: None,
+            nested_duration_ms: None,
+            flow_name: name.to_string(),
+            flow_path: format!("{}.yaml", name),
+            status: FlowStatus::Passed,
+            commands: vec![command(0, "launchApp", Some(10))],
+            total_duration_ms: Some(10),
+            error: None,
+            video_path: None,
+            device: None,
+            metadata: FlowMetadata {
+                tags: tags.iter().map(|t| t.to_string()).collect(),
+                title: Some("Checkout <guest>".to_string()),
+                description: Some("Pays without an account".to_string()),
+            },
+        };
+        let results = TestResults {
+            session_id: "s".to_string(),
+            flows: vec![
+                flow("checkout", &["payments", "smoke"]),
+                flow("login", &["smoke"]),
+            ],
+            summary: TestSummary {
+                session_id: "s".to_string(),
+                total_flows: 2,
+                total_commands: 2,
+                passed: 2,
+                failed: 0,
+                skipped: 0,
+                total_duration_ms: Some(20),
+                failed_flows: Vec::new(),
+            },
+            generated_at: String::new(),
+            performance: Vec::new(),
+        };
+
+        let html = generate_html(&results);
+        assert!(html.contains(r#"data-tags="payments smoke""#));
+        assert!(html.contains(r##"<span class="tag-badge">#payments</span>"##));
+        assert!(html.contains("Checkout &lt;guest&gt;"));
+        assert!(html.contains("Pays without an account"));
+        // One filter button per distinct tag, plus "All"
+        assert_eq!(render_tag_filter(&results).matches("<button").count(), 3);
+
+        let json = serde_json::to_value(&results.flows[0]).unwrap();
+        assert_eq!(json["tags"], serde_json::json!(["payments", "smoke"]));
+        assert_eq!(json["title"], "Checkout <guest>");
+
+        // Performance charts follow the flow file, not the (repeatable) name
+        let mut results = results;
+        results.flows[1].flow_name = "checkout".to_string();
+        results.performance = vec![crate::report::types::FlowPerformance {
+            flow_name: "checkout".to_string(),
+            flow_path: "login.yaml".to_string(),
+            samples: vec![PerformanceSample {
+                elapsed_ms: 0,
+                command_index: 0,
+                metrics: HashMap::from([("memory".to_string(), 100.0)]),
+            }],
+        }];
+        assert_eq!(
+            generate_html(&results)
+                .matches("Performance (1 samples)")
+                .count(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_render_performance_charts() {
+        let sample = |elapsed_ms, command_index, memory| PerformanceSample {
+            elapsed_ms,
+            command_index,
+            metrics: HashMap::from([("memory".to_string(), memory), ("cpu".to_string(), 12.0)]),
+        };
+        let samples = vec![
+            sample(0, 0, 100.0),
+            sample(1500, 1, 180.0),
+            sample(3000, 2, 140.0),
+        ];
+
+        let html = render_performance_charts(&samples, &[]);
+        assert!(html.contains("Performance (3 samples)"));
+        assert_eq!(html.matches("<svg").count(), 2);
+        assert!(html.contains("memory (min 100.0 MB, max 180.0 MB)"));
+        // Peak memory is drawn at the top of the plot area
+        assert!(html.contains(&format!(
+            "{:.1},{:.1}",
+            CHART_PAD_X + (CHART_WIDTH - CHART_PAD_X - 8.0) / 2.0,
+            CHART_PAD_Y
+        )));
+
+        assert!(render_performance_charts(&[], &[]).is_empty());
+    }
+}
@@ -1,13 +1,21 @@
 use super::types::TestResults;
-use crate::runner::state::{FlowStateReport, FlowStatus};
+use crate::runner::state::{CommandStatus, FlowStateReport, FlowStatus};
 use anyhow::Result;
 use quick_xml::events::{BytesDecl, BytesEnd, BytesStart, Event};
 use quick_xml::Writer;
 use std::io::Cursor;
 use std::path::Path;
 
+/// Number of trailing lines kept from each captured failure log
+const LOG_TAIL_LINES: usize = 200;
+
 /// Generate JUnit XML report string from TestResults
 pub fn generate_junit_xml(results: &TestResults) -> Result<String> {
+    generate_junit_xml_in(results, None)
+}
+
+/// Generate JUnit XML, resolving relative artifact paths (e.g. videos) against `output_dir`
+fn generate_junit_xml_in(results: &TestResults, output_dir: Option<&Path>) -> Result<String> {
     let mut writer = Writer::new(Cursor::new(Vec::new()));
 
     // Write XML declaration
@@ -25,7 +33,11 @@ pub fn generate_junit_xml(results: &TestResults) -> Result<String> {
             )
         })
         .count();
-    let skipped = 0;
+    let skipped = results
+        .flows
+        .iter()
+        .filter(|f| matches!(f.status, FlowStatus::Skipped { .. }))
+        .count();
     let total_duration: u64 = results
         .flows
         .iter()
@@ -60,7 +72,7 @@ pub fn generate_junit_xml(results: &TestResults) -> Result<String> {
     writer.write_event(Event::Start(suite_start))?;
 
     for flow in &results.flows {
-        write_test_case(&mut writer, flow)?;
+        write_test_case(&mut writer, flow, output_dir)?;
     }
 
     writer.write_event(Event::End(BytesEnd::new("testsuite")))?;
@@ -74,6 +86,7 @@ pub fn generate_junit_xml(results: &TestResults) -> Result<String> {
 fn write_test_case<W: std::io::Write>(
     writer: &mut Writer<W>,
     flow: &FlowStateReport,
+    output_dir: Option<&Path>,
 ) -> Result<()> {
     let mut case_start = BytesStart::new("testcase");
     // Classname is usually package.class, here we can use the file path or directory
@@ -81,15 +94,49 @@ fn write_test_case<W: std::io::Write>(
 
     case_start.push_attribute(("name", flow.flow_name.as_str()));
     case_start.push_attribute(("classname", classname.as_str()));
-    case_start.push_attribute((
-        "time",
-        (flow.total_duration_ms.unwrap_or(0) as f64 / 1000.0)
-            .to_string()
-            .as_str(),
-    ));
+    // Fall back to the sum of command durations when the flow total is missing
+    let duration_ms = flow
+        .total_duration_ms
+        .unwrap_or_else(|| flow.commands.iter().filter_map(|c| c.duration_ms).sum());
+    case_start.push_attribute(("time", (duration_ms as f64 / 1000.0).to_string().as_str()));
 
     writer.write_event(Event::Start(case_start))?;
 
+    // Artifact paths as properties so CI dashboards can link them
+    let resolve = |path: &str| match output_dir {
+        Some(dir) if Path::new(path).is_relative() => dir.join(path).display().to_string(),
+        _ => path.to_string(),
+    };
+    let mut properties: Vec<(String, String)> = Vec::new();
+    for cmd in &flow.commands {
+        let n = cmd.index + 1;
+        if let Some(p) = &cmd.screenshot_path {
+            properties.push((format!("screenshot.{}", n), resolve(p)));
+        }
+        if let Some(p) = &cmd.ui_hierarchy_path {
+            properties.push((format!("ui_hierarchy.{}", n), resolve(p)));
+        }
+        if let Some(p) = &cmd.log_path {
+            properties.push((format!("log.{}", n), resolve(p)));
+        }
+    }
+    if let Some(p) = &flow.video_path {
+        properties.push(("video".to_string(), resolve(p)));
+    }
+    if let Some(device) = &flow.device {
+        properties.push(("device".to_string(), device.clone()));
+    }
+    if !properties.is_empty() {
+        writer.write_event(Event::Start(BytesStart::new("properties")))?;
+        for (name, value) in &properties {
+            let mut prop = BytesStart::new("property");
+            prop.push_attribute(("name", name.as_str()));
+            prop.push_attribute(("value", value.as_str()));
+            writer.write_event(Event::Empty(prop))?;
+        }
+        writer.write_event(Event::End(BytesEnd::new("properties")))?;
+    }
+
     match flow.status {
         FlowStatus::Failed | FlowStatus::PartiallyPassed { .. } => {
             let mut fail_start = BytesStart::new("failure");
@@ -104,12 +151,78 @@ fn write_test_case<W: std::io::Write>(
 
             writer.write_event(Event::End(BytesEnd::new("failure")))?;
         }
+        FlowStatus::Skipped { ref reason } => {
+            let mut skipped = BytesStart::new("skipped");
+            skipped.push_attribute(("message", reason.as_str()));
+            writer.write_event(Event::Empty(skipped))?;
+        }
         _ => {}
     }
 
-    // Add system-out for logs if needed? JUnit usually puts logs in system-out
-    // We could format commands history here but it might be too verbose.
-    // For now, let's keep it clean.
+    // <system-out>: command timeline, attachments and the captured failure logs
+    let mut out = String::new();
+    for cmd in &flow.commands {
+        let icon = match cmd.status {
+            CommandStatus::Passed => "✓",
+            CommandStatus::Failed { .. } => "✗",
+            _ => "○",
+        };
+        out.push_str(&format!(
+            "{} {}. {}",
+            icon,
+            cmd.index + 1,
+            cmd.command_display
+        ));
+        if let Some(d) = cmd.duration_ms {
+            out.push_str(&format!(" ({}ms)", d));
+        }
+        out.push('\n');
+    }
+    // Jenkins (junit-attachments) and GitLab pick up this marker
+    for (name, value) in &properties {
+        if name.starts_with("screenshot") || name == "video" {
+            out.push_str(&format!("[[ATTACHMENT|{}]]\n", value));
+        }
+    }
+    for cmd in &flow.commands {
+        let Some(log_path) = &cmd.log_path else {
+            continue;
+        };
+        if let Ok(logs) = std::fs::read_to_string(resolve(log_path)) {
+            let lines: Vec<&str> = logs.lines().collect();
+            let tail = &lines[lines.len().saturating_sub(LOG_TAIL_LINES)..];
+            out.push_str(&format!(
+                "\n--- Device log at failure of command {} ({}) ---\n{}\n",
+                cmd.index + 1,
+                log_path,
+                tail.join("\n")
+            ));
+        }
+    }
+    if !out.is_empty() {
+        writer.write_event(Event::Start(BytesStart::new("system-out")))?;
+        writer.write_event(Event::Text(quick_xml::events::BytesText::new(&out)))?;
+        writer.write_event(Event::End(BytesEnd::new("system-out")))?;
+    }
+
+    // <system-err>: every failed command's error, not only the flow-level one
+    let errors: Vec<String> = flow
+        .commands
+        .iter()
+        .filter_map(|c| match &c.status {
+            CommandStatus::Failed { error } => {
+                Some(format!("{}. {}: {}", c.index + 1, c.command_display, error))
+            }
+            _ => None,
+        })
+        .collect();
+    if !errors.is_empty() {
+        writer.write_event(Event::Start(BytesStart::new("system-err")))?;
+        writer.write_event(Event::Text(quick_xml::events::BytesText::new(
+            &errors.join("\n"),
+        )))?;
+        writer.write_event(Event::End(BytesEnd::new("system-err")))?;
+    }
 
     writer.write_event(Event::End(BytesEnd::new("testcase")))?;
     Ok(())
@@ -117,7 +230,7 @@ fn write_test_case<W: std::io::Write>(
 
 /// Write report to file
 pub fn write_report(results: &TestResults, output_dir: &Path) -> Result<()> {
-    let xml = generate_junit_xml(results)?;
+    let xml = generate_junit_xml_in(results, Some(output_dir))?;
     let path = output_dir.join("junit.xml");
     std::fs::write(&path, xml)?;
     println!("    Generated JUnit report: {}", path.display());
@@ -128,7 +241,7 @@ pub fn write_report(results: &TestResults, output_dir: &Path) -> Result<()> {
 mod tests {
     use super::*;
     use crate::report::types::TestResults;
-    use crate::runner::state::{FlowStateReport, FlowStatus, TestSummary};
+    use crate::runner::state::{CommandStateReport, FlowStateReport, FlowStatus, TestSummary};
 
     #[test]
     fn test_generate_junit_xml() {
@@ -136,6 +249,10 @@ mod tests {
             session_id: "test-session".to_string(),
             flows: vec![
                 FlowStateReport {
+                    id: String::new(),
+                    depth: 0,
+                    parent: None,
+                    nested_duration_ms: None,
                     flow_name: "Login Flow".to_string(),
                     flow_path: "flows/login.yaml".to_string(),
                     status: FlowStatus::Passed,
@@ -143,8 +260,14 @@ mod tests {
                     error: None,
                     commands: vec![],
                     video_path: None,
+                    device: None,
+                    metadata: Default::default(),
                 },
                 FlowStateReport {
+                    id: String::new(),
+                    depth: 0,
+                    parent: None,
+                    nested_duration_ms: None,
                     flow_name: "Checkout Flow".to_string(),
                     flow_path: "flows/checkout.yaml".to_string(),
                     status: FlowStatus::Failed,
@@ -152,6 +275,8 @@ mod tests {
                     error: Some("Element not found".to_string()),
                     commands: vec![],
                     video_path: None,
+                    device: None,
+                    metadata: Default::default(),
                 },
             ],
             summary: TestSummary {
@@ -162,8 +287,10 @@ mod tests {
                 failed: 1,
                 skipped: 0,
                 total_duration_ms: Some(3500),
+                failed_flows: Vec::new(),
             },
             generated_at: "2023-01-01 12:00:00".to_string(),
+            performance: vec![],
         };
 
         let xml = generate_junit_xml(&results).expect("Failed to generate XML");
@@ -174,4 +301,75 @@ mod tests {
         assert!(xml.contains(r#"<testcase name="Login Flow""#));
         assert!(xml.contains(r#"message="Element not found""#));
     }
+
+    #[test]
+    fn test_junit_artifacts_and_logs() {
+        let dir = std::env::temp_dir().join(format!("lumi-junit-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let log_path = dir.join("fail.log");
+        std::fs::write(&log_path, "E AndroidRuntime: FATAL EXCEPTION: main\n").unwrap();
+
+        let command = |index: usize, status: CommandStatus| CommandStateReport {
+            index,
+            command_name: "tapOn".to_string(),
+            command_display: "tapOn".to_string(),
+            status,
+            duration_ms: Some(250),
+            screenshot_path: None,
+            ui_hierarchy_path: None,
+            log_path: None,
+            retry_count: 0,
+        };
+        let mut failed = command(
+            1,
+            CommandStatus::Failed {
+                error: "Element not found".to_string(),
+            },
+        );
+        failed.screenshot_path = Some("/out/fail.png".to_string());
+        failed.log_path = Some(log_path.display().to_string());
+
+        let results = TestResults {
+            session_id: "s".to_string(),
+            flows: vec![FlowStateReport {
+                id: String::new(),
+                depth: 0,
+                parent: None,
+                nested_duration_ms: None,
+                flow_name: "Login".to_string(),
+                flow_path: "login.yaml".to_string(),
+                status: FlowStatus::Failed,
+                commands: vec![command(0, CommandStatus::Passed), failed],
+                total_duration_ms: None,
+                error: Some("Element not found".to_string()),
+                video_path: Some("video_login.mp4".to_string()),
+                device: None,
+                metadata: Default::default(),
+            }],
+            summary: TestSummary {
+                session_id: "s".to_string(),
+                total_flows: 1,
+                total_commands: 2,
+                passed: 1,
+                failed: 1,
+                skipped: 0,
+                total_duration_ms: None,
+                failed_flows: Vec::new(),
+            },
+            generated_at: "2023-01-01 12:00:00".to_string(),
+            performance: vec![],
+        };
+
+        let xml = generate_junit_xml_in(&results, Some(&dir)).unwrap();
+        std::fs::remove_dir_all(&dir).ok();
+
+        assert!(xml.contains(r#"<testcase name="Login" classname="login.yaml" time="0.5">"#));
+        assert!(xml.contains(r#"<property name="screenshot.2" value="/out/fail.png"/>"#));
+        let video = dir.join("video_login.mp4").display().to_string();
+        assert!(xml.contains(&format!(r#"<property name="video" value="{}"/>"#, video)));
+        assert!(xml.contains("[[ATTACHMENT|/out/fail.png]]"));
+        assert!(xml.contains("✗ 2. tapOn (250ms)"));
+        assert!(xml.contains("FATAL EXCEPTION: main"));
+        assert!(xml.contains("<system-err>2. tapOn: Element not found</system-err>"));
+    }
 }
@@ -1,10 +1,13 @@
 pub mod html;
 pub mod json;
 pub mod junit;
+pub mod notify;
 pub mod types;
 
+use crate::runner::state::{TestSessionReport, TestSummary};
 use anyhow::Result;
 use std::path::Path;
+use std::time::SystemTime;
 
 /// Generate report from test results
 pub async fn generate_report(
@@ -21,3 +24,222 @@ pub async fn generate_report(
         _ => anyhow::bail!("Unknown format: {}", format),
     }
 }
+
+/// Whether `--report` should write `format` (json, html, junit).
+///
+/// `formats` (lumi.toml `report = [...]` or `LUMI_REPORT_FORMATS`) narrows the
+/// list; every format is written when it is unset.
+pub fn format_enabled(formats: Option<&[String]>, format: &str) -> bool {
+    match formats {
+        Some(list) if !list.is_empty() => {
+            list.iter().any(|f| f.trim().eq_ignore_ascii_case(format))
+        }
+        _ => true,
+    }
+}
+
+/// Combine the per-device `run.json` files written under `output_dir` since `since`
+/// into one result set, tagging each flow with its device directory.
+pub fn merge_device_reports(
+    output_dir: &Path,
+    since: SystemTime,
+) -> Result<Option<types::TestResults>> {
+    // `--workers` nests device directories one level deeper (worker-N/<device>)
+    let mut device_dirs: Vec<_> = walkdir::WalkDir::new(output_dir)
+        .min_depth(1)
+        .max_depth(2)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_dir())
+        .map(|e| e.into_path())
+        .collect();
+    device_dirs.sort();
+
+    let mut flows = Vec::new();
+    let mut summary = TestSummary {
+        session_id: uuid::Uuid::new_v4().to_string(),
+        total_flows: 0,
+        total_commands: 0,
+        passed: 0,
+        failed: 0,
+        skipped: 0,
+        total_duration_ms: None,
+        failed_flows: Vec::new(),
+    };
+
+    for dir in device_dirs {
+        let run_path = dir.join("run.json");
+        // Skip leftovers from earlier runs
+        let fresh = std::fs::metadata(&run_path)
+            .and_then(|m| m.modified())
+            .is_ok_and(|modified| modified >= since);
+        if !fresh {
+            continue;
+        }
+        let report: TestSessionReport =
+            match serde_json::from_str(&std::fs::read_to_string(&run_path)?) {
+                Ok(r) => r,
+                Err(e) => {
+                    log::warn!("Skipping unreadable report {}: {}", run_path.display(), e);
+                    continue;
+                }
+            };
+        let device_path = dir
+            .strip_prefix(output_dir)
+            .unwrap_or(&dir)
+            .to_string_lossy()
+            .replace('\\', "/");
+        let device = dir
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+
+        summary.total_flows += report.summary.total_flows;
+        summary.total_commands += report.summary.total_commands;
+        summary.passed += report.summary.passed;
+        summary.failed += report.summary.failed;
+        summary.skipped += report.summary.skipped;
+        // Devices run side by side, so the session lasts as long as the slowest one
+        summary.total_duration_ms = summary
+            .total_duration_ms
+            .max(report.summary.total_duration_ms);
+        summary.failed_flows.extend(report.summary.failed_flows);
+
+        for mut flow in report.flows {
+            // Videos are stored relative to the device directory
+            flow.video_path = flow.video_path.map(|v| {
+                if Path::new(&v).is_relative() {
+                    format!("{}/{}", device_path, v)
+                } else {
+                    v
+                }
+            });
+            if flow.device.is_none() {
+                flow.device = Some(device.clone());
+            }
+            flows.push(flow);
+        }
+    }
+
+    if flows.is_empty() {
+        return Ok(None);
+    }
+
+    Ok(Some(types::TestResults {
+        session_id: summary.session_id.clone(),
+        flows,
+        summary,
+        generated_at: chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+        performance: Vec::new(),
+    }))
+}
+
+/// Write the consolidated JSON/HTML/JUnit reports for a parallel run into `output_dir`
+pub async fn write_merged_report(
+    output_dir: &Path,
+    since: SystemTime,
+    formats: Option<&[String]>,
+) -> Result<()> {
+    let Some(results) = merge_device_reports(output_dir, since)? else {
+        return Ok(());
+    };
+
+    if format_enabled(formats, "json") {
+        let json_path = output_dir.join("test-results.json");
+        std::fs::write(&json_path, serde_json::to_string_pretty(&results)?)?;
+        println!("Combined JSON report saved to: {}", json_path.display());
+    }
+    if format_enabled(formats, "html") {
+        html::generate(&results, Some(&output_dir.join("report.html"))).await?;
+    }
+    if format_enabled(formats, "junit") {
+        junit::write_report(&results, output_dir)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runner::state::{FlowStateReport, FlowStatus};
+
+    fn write_run(dir: &Path, flow_name: &str, passed: u32, failed: u32, duration_ms: u64) {
+        std::fs::create_dir_all(dir).unwrap();
+        let report = TestSessionReport {
+            session_id: "s".to_string(),
+            flows: vec![FlowStateReport {
+                id: String::new(),
+                depth: 0,
+                parent: None,
+                nested_duration_ms: None,
+                flow_name: flow_name.to_string(),
+                flow_path: format!("{}.yaml", flow_name),
+                status: if failed > 0 {
+                    FlowStatus::Failed
+                } else {
+                    FlowStatus::Passed
+                },
+                commands: vec![],
+                total_duration_ms: Some(duration_ms),
+                error: None,
+                video_path: Some("video.mp4".to_string()),
+                device: None,
+                metadata: Default::default(),
+            }],
+            summary: TestSummary {
+                session_id: "s".to_string(),
+                total_flows: 1,
+                total_commands: passed + failed,
+                passed,
+                failed,
+                skipped: 0,
+                total_duration_ms: Some(duration_ms),
+                failed_flows: Vec::new(),
+            },
+        };
+        std::fs::write(
+            dir.join("run.json"),
+            serde_json::to_string(&report).unwrap(),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_merge_device_reports() {
+        let out = std::env::temp_dir().join(format!("lumi-merge-{}", uuid::Uuid::new_v4()));
+        let since = SystemTime::now() - std::time::Duration::from_secs(5);
+        write_run(&out.join("emulator-5554"), "login", 3, 0, 4000);
+        write_run(&out.join("emulator-5556"), "checkout", 2, 1, 7000);
+        write_run(
+            &out.join("worker-3").join("emulator-5554"),
+            "search",
+            1,
+            0,
+            2000,
+        );
+        std::fs::create_dir_all(out.join("empty")).unwrap();
+
+        let merged = merge_device_reports(&out, since).unwrap().unwrap();
+        let stale =
+            merge_device_reports(&out, SystemTime::now() + std::time::Duration::from_secs(60))
+                .unwrap();
+        std::fs::remove_dir_all(&out).ok();
+
+        assert_eq!(merged.flows.len(), 3);
+        assert_eq!(merged.flows[0].device.as_deref(), Some("emulator-5554"));
+        assert_eq!(
+            merged.flows[1].video_path.as_deref(),
+            Some("emulator-5556/video.mp4")
+        );
+        assert_eq!(merged.flows[2].device.as_deref(), Some("emulator-5554"));
+        assert_eq!(
+            merged.flows[2].video_path.as_deref(),
+            Some("worker-3/emulator-5554/video.mp4")
+        );
+        assert_eq!(merged.summary.total_flows, 3);
+        assert_eq!(merged.summary.passed, 6);
+        assert_eq!(merged.summary.failed, 1);
+        assert_eq!(merged.summary.total_duration_ms, Some(7000));
+        assert!(stale.is_none());
+    }
+}
@@ -0,0 +1,355 @@
+//! Run-completion notifications (`--notify`)
+//!
+//! Posts the session summary to a webhook when the run ends: a Slack message for
+//! Slack incoming webhooks, the `--json-summary` object for anything else.
+
+use crate::runner::state::TestSummary;
+use anyhow::Result;
+use colored::Colorize;
+use std::path::Path;
+
+/// Webhook to post to when neither `--notify` nor lumi.toml `notify` gives one
+pub const NOTIFY_URL_ENV: &str = "LUMI_NOTIFY_URL";
+/// Payload format (`slack` or `json`) for `LUMI_NOTIFY_URL`; guessed from the URL when unset
+pub const NOTIFY_FORMAT_ENV: &str = "LUMI_NOTIFY_FORMAT";
+/// Public link to the report (e.g. the CI artifact page); the local output path otherwise
+pub const REPORT_URL_ENV: &str = "LUMI_REPORT_URL";
+
+/// Failed flows listed in a Slack message before the rest are summarised
+const MAX_LISTED_FLOWS: usize = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NotifyFormat {
+    /// Generic webhook: the JSON summary plus status and report link
+    Json,
+    /// Slack incoming webhook message
+    Slack,
+}
+
+impl std::str::FromStr for NotifyFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "json" | "webhook" | "generic" => Ok(Self::Json),
+            "slack" => Ok(Self::Slack),
+            other => anyhow::bail!("Unknown notify format `{}` (slack, json)", other),
+        }
+    }
+}
+
+impl NotifyFormat {
+    pub fn detect(url: &str) -> Self {
+        if url.contains("hooks.slack.com") {
+            Self::Slack
+        } else {
+            Self::Json
+        }
+    }
+}
+
+/// Webhook that receives the run summary
+#[derive(Debug, Clone, PartialEq)]
+pub struct NotifyTarget {
+    pub url: String,
+    pub format: NotifyFormat,
+}
+
+impl NotifyTarget {
+    /// `format` is guessed from the URL when not given
+    pub fn new(url: String, format: Option<&str>) -> Result<Self> {
+        let format = match format.filter(|f| !f.trim().is_empty()) {
+            Some(name) => name.parse()?,
+            None => NotifyFormat::detect(&url),
+        };
+        Ok(Self { url, format })
+    }
+}
+
+/// Post the run summary to `target`, if any. Failures are reported but never
+/// fail the run.
+pub async fn notify(
+    target: Option<&NotifyTarget>,
+    summary: &TestSummary,
+    passed: bool,
+    output: &Path,
+    report: bool,
+) {
+    let Some(NotifyTarget { url, format }) = target else {
+        return;
+    };
+    let (url, format) = (url.clone(), *format);
+
+    let mut secrets = secret_env_values();
+    secrets.push(url.clone());
+    // Masked field by field: the serialized JSON would also match numbers, `true`
+    // and keys, and hold secrets in their escaped form
+    let summary = mask_summary(summary, &secrets);
+    let link = report_link(output, report).map(|link| mask_secrets(&link, &secrets));
+    let body = build_payload(format, &summary, passed, link.as_deref()).to_string();
+
+    match post(&url, body).await {
+        Ok(()) => println!(
+            "{} Run summary sent to {}",
+            "📨".cyan(),
+            mask_url(&url).cyan()
+        ),
+        Err(e) => println!(
+            "{} Failed to send run summary to {}: {}",
+            "⚠️".yellow(),
+            mask_url(&url),
+            mask_secrets(&e.to_string(), &secrets)
+        ),
+    }
+}
+
+async fn post(url: &str, body: String) -> Result<()> {
+    let client = reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(10))
+        .build()?;
+    let response = client
+        .post(url)
+        .header("Content-Type", "application/json")
+        .body(body)
+        .send()
+        .await
+        .map_err(|e| e.without_url())?;
+    let status = response.status();
+    if !status.is_success() {
+        let text = response.text().await.unwrap_or_default();
+        anyhow::bail!("HTTP {} {}", status, text.trim());
+    }
+    Ok(())
+}
+
+/// `LUMI_REPORT_URL` when set, else the output directory when reports were written
+pub fn report_link(output: &Path, report: bool) -> Option<String> {
+    if let Ok(url) = std::env::var(REPORT_URL_ENV) {
+        if !url.trim().is_empty() {
+            return Some(url);
+        }
+    }
+    report.then(|| {
+        std::path::absolute(output)
+            .unwrap_or_else(|_| output.to_path_buf())
+            .display()
+            .to_string()
+    })
+}
+
+pub fn build_payload(
+    format: NotifyFormat,
+    summary: &TestSummary,
+    passed: bool,
+    report: Option<&str>,
+) -> serde_json::Value {
+    let status = if passed { "passed" } else { "failed" };
+    match format {
+        NotifyFormat::Json => {
+            let mut value = serde_json::to_value(summary).unwrap_or_default();
+            value["status"] = serde_json::json!(status);
+            if let Some(report) = report {
+                value["report"] = serde_json::json!(report);
+            }
+            value
+        }
+        NotifyFormat::Slack => {
+            let icon = if passed { "✅" } else { "❌" };
+            let duration = summary
+                .total_duration_ms
+                .map(|ms| format!(" in {:.1}s", ms as f64 / 1000.0))
+                .unwrap_or_default();
+            let headline = format!(
+                "{} lumi-tester run {}: {} flows ({} failed), {} commands: {} passed, {} failed, {} skipped{}",
+                icon,
+                status,
+                summary.total_flows,
+                summary.failed_flows.len(),
+                summary.total_commands,
+                summary.passed,
+                summary.failed,
+                summary.skipped,
+                duration
+            );
+
+            let mut text = format!("*{}*", headline);
+            if !summary.failed_flows.is_empty() {
+                text.push_str("\n*Failed flows:*");
+                for name in summary.failed_flows.iter().take(MAX_LISTED_FLOWS) {
+                    text.push_str(&format!("\n• {}", name));
+                }
+                let hidden = summary.failed_flows.len().saturating_sub(MAX_LISTED_FLOWS);
+                if hidden > 0 {
+                    text.push_str(&format!("\n…and {} more", hidden));
+                }
+            }
+            match report {
+                Some(link) if link.starts_with("http://") || link.starts_with("https://") => {
+                    text.push_str(&format!("\n<{}|View report>", link))
+                }
+                Some(path) => text.push_str(&format!("\nReport: `{}`", path)),
+                None => {}
+            }
+
+            serde_json::json!({
+                "text": headline,
+                "blocks": [{
+                    "type": "section",
+                    "text": { "type": "mrkdwn", "text": text }
+                }]
+            })
+        }
+    }
+}
+
+/// Webhook URL with everything after the host hidden; the path is the credential
+pub fn mask_url(url: &str) -> String {
+    match url.split_once("://") {
+        Some((scheme, rest)) => {
+            let host = rest.split(['/', '?']).next().unwrap_or_default();
+            format!("{}://{}/***", scheme, host)
+        }
+        None => "***".to_string(),
+    }
+}
+
+/// Copy of `summary` with secrets masked in its free-text fields
+pub fn mask_summary(summary: &TestSummary, secrets: &[String]) -> TestSummary {
+    TestSummary {
+        session_id: mask_secrets(&summary.session_id, secrets),
+        failed_flows: summary
+            .failed_flows
+            .iter()
+            .map(|name| mask_secrets(name, secrets))
+            .collect(),
+        ..summary.clone()
+    }
+}
+
+/// Replace every occurrence of a secret value with `***`
+pub fn mask_secrets(text: &str, secrets: &[String]) -> String {
+    let mut secrets: Vec<&String> = secrets.iter().filter(|s| s.len() >= 4).collect();
+    // Longest first so a secret containing another is masked whole
+    secrets.sort_by_key(|s| std::cmp::Reverse(s.len()));
+    secrets.into_iter().fold(text.to_string(), |text, secret| {
+        text.replace(secret.as_str(), "***")
+    })
+}
+
+/// Values of environment variables whose names look like credentials
+fn secret_env_values() -> Vec<String> {
+    std::env::vars()
+        .filter(|(name, _)| is_secret_name(name))
+        .map(|(_, value)| value)
+        .collect()
+}
+
+fn is_secret_name(name: &str) -> bool {
+    let name = name.to_uppercase();
+    [
+        "TOKEN",
+        "SECRET",
+        "PASSWORD",
+        "PASSWD",
+        "API_KEY",
+        "APIKEY",
+        "PRIVATE_KEY",
+        "CREDENTIAL",
+        "WEBHOOK",
+    ]
+    .iter()
+    .any(|marker| name.contains(marker))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn summary(failed_flows: &[&str]) -> TestSummary {
+        TestSummary {
+            session_id: "s".to_string(),
+            total_flows: 3,
+            total_commands: 10,
+            passed: 8,
+            failed: failed_flows.len() as u32,
+            skipped: 0,
+            total_duration_ms: Some(12_500),
+            failed_flows: failed_flows.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_format_detection_and_parsing() {
+        assert_eq!(
+            NotifyFormat::detect("https://hooks.slack.com/services/T0/B0/x"),
+            NotifyFormat::Slack
+        );
+        assert_eq!(
+            NotifyFormat::detect("https://ci.example.com/hook"),
+            NotifyFormat::Json
+        );
+        assert_eq!(
+            "Slack".parse::<NotifyFormat>().unwrap(),
+            NotifyFormat::Slack
+        );
+        assert!("teams".parse::<NotifyFormat>().is_err());
+
+        let target = NotifyTarget::new("https://hooks.slack.com/services/x".into(), None).unwrap();
+        assert_eq!(target.format, NotifyFormat::Slack);
+        let target =
+            NotifyTarget::new("https://hooks.slack.com/services/x".into(), Some("json")).unwrap();
+        assert_eq!(target.format, NotifyFormat::Json);
+        assert!(NotifyTarget::new("https://ci.example.com/hook".into(), Some("teams")).is_err());
+    }
+
+    #[test]
+    fn test_build_payloads() {
+        let failing = summary(&["checkout", "login"]);
+        let json = build_payload(NotifyFormat::Json, &failing, false, Some("/out"));
+        assert_eq!(json["status"], "failed");
+        assert_eq!(json["failedFlows"][1], "login");
+        assert_eq!(json["report"], "/out");
+
+        let slack = build_payload(
+            NotifyFormat::Slack,
+            &failing,
+            false,
+            Some("https://ci.example.com/42"),
+        );
+        let text = slack["blocks"][0]["text"]["text"].as_str().unwrap();
+        assert_eq!(
+            slack["text"],
+            "❌ lumi-tester run failed: 3 flows (2 failed), 10 commands: 8 passed, 2 failed, 0 skipped in 12.5s"
+        );
+        assert!(text.contains("• checkout"), "{}", text);
+        assert!(text.contains("<https://ci.example.com/42|View report>"));
+    }
+
+    #[test]
+    fn test_masking() {
+        assert_eq!(
+            mask_url("https://hooks.slack.com/services/T0/B0/secret"),
+            "https://hooks.slack.com/***"
+        );
+        let secrets = vec!["hunter22".to_string(), "abc".to_string()];
+        assert_eq!(
+            mask_secrets("login as hunter22 with abc", &secrets),
+            "login as *** with abc"
+        );
+        // Secrets that look like JSON values don't touch counts or flags
+        let secrets = vec!["true".to_string(), "1234".to_string(), "pa\"ss".to_string()];
+        let mut failing = summary(&["login pa\"ss", "true story"]);
+        failing.total_commands = 1234;
+        let json = build_payload(
+            NotifyFormat::Json,
+            &mask_summary(&failing, &secrets),
+            false,
+            None,
+        );
+        assert_eq!(json["totalCommands"], 1234);
+        assert_eq!(json["failedFlows"][0], "login ***");
+        assert_eq!(json["failedFlows"][1], "*** story");
+        assert!(is_secret_name("GITHUB_TOKEN"));
+        assert!(!is_secret_name("HOME"));
+    }
+}
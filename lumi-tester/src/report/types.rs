@@ -1,5 +1,6 @@
 use crate::runner::state::{FlowStateReport, TestSummary};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 /// Test results for report generation
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -9,4 +10,30 @@ pub struct TestResults {
     pub flows: Vec<FlowStateReport>,
     pub summary: TestSummary,
     pub generated_at: String,
+    /// Performance samples collected between startProfiling and stopProfiling
+    #[serde(default)]
+    pub performance: Vec<FlowPerformance>,
+}
+
+/// Performance time series for one flow
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FlowPerformance {
+    pub flow_name: String,
+    /// Flow file the samples belong to; names can repeat across files
+    #[serde(default)]
+    pub flow_path: String,
+    pub samples: Vec<PerformanceSample>,
+}
+
+/// One metrics snapshot taken after a command
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PerformanceSample {
+    /// Milliseconds since profiling started
+    pub elapsed_ms: u64,
+    /// Index of the command that just ran
+    pub command_index: usize,
+    /// Metric name -> value (memory in MB, cpu in %, fps, ...)
+    pub metrics: HashMap<String, f64>,
 }
@@ -0,0 +1,222 @@
+//! Incremental runs (`--changed-since <git-ref>`)
+//!
+//! Keeps only the collected flow files that changed since a git ref, or that
+//! reference a changed file: subflows (`runFlow`), scripts, data files. References
+//! are followed recursively, so editing a shared subflow reruns every flow using it.
+//! A changed `setup.yaml`/`teardown.yaml` next to the suite reruns everything.
+
+use anyhow::{Context, Result};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Extensions of files a flow can pull in by relative path
+const REFERENCE_EXTENSIONS: &[&str] = &["yaml", "yml", "js", "json", "csv"];
+
+/// Files changed since `git_ref` in the repository containing `path`:
+/// committed and uncommitted changes plus untracked files, as absolute paths.
+pub fn changed_files(path: &Path, git_ref: &str) -> Result<HashSet<PathBuf>> {
+    let dir = if path.is_dir() {
+        path
+    } else {
+        path.parent()
+            .filter(|p| !p.as_os_str().is_empty())
+            .unwrap_or(Path::new("."))
+    };
+    let root = PathBuf::from(git(dir, &["rev-parse", "--show-toplevel"])?.trim());
+
+    let diff = git(&root, &["diff", "--name-only", git_ref, "--"])?;
+    let untracked = git(&root, &["ls-files", "--others", "--exclude-standard"])?;
+    Ok(diff
+        .lines()
+        .chain(untracked.lines())
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| normalize(&root.join(line.trim())))
+        .collect())
+}
+
+/// The subset of `files` affected by `changed`, in the original order
+pub fn filter_changed(
+    files: Vec<PathBuf>,
+    hooks: &[PathBuf],
+    changed: &HashSet<PathBuf>,
+) -> Vec<PathBuf> {
+    if hooks
+        .iter()
+        .any(|hook| hook.exists() && is_affected(hook, changed))
+    {
+        return files;
+    }
+    files
+        .into_iter()
+        .filter(|file| is_affected(file, changed))
+        .collect()
+}
+
+fn is_affected(file: &Path, changed: &HashSet<PathBuf>) -> bool {
+    let root_dir = file.parent().unwrap_or(Path::new("."));
+    depends_on_changed(file, root_dir, changed, &mut HashSet::new())
+}
+
+/// Whether `file` or anything it references changed. Subflow commands run with the
+/// top-level flow's base directory, so references resolve against `root_dir` as
+/// well as the referencing file's own directory.
+fn depends_on_changed(
+    file: &Path,
+    root_dir: &Path,
+    changed: &HashSet<PathBuf>,
+    visited: &mut HashSet<PathBuf>,
+) -> bool {
+    let file = normalize(file);
+    if changed.contains(&file) {
+        return true;
+    }
+    if !visited.insert(file.clone()) {
+        return false;
+    }
+    let is_yaml = file
+        .extension()
+        .is_some_and(|ext| ext == "yaml" || ext == "yml");
+    if !is_yaml {
+        return false;
+    }
+    let Ok(content) = std::fs::read_to_string(&file) else {
+        return false;
+    };
+
+    let own_dir = file.parent().unwrap_or(Path::new(".")).to_path_buf();
+    for reference in references(&content) {
+        let candidates = [root_dir.join(&reference), own_dir.join(&reference)];
+        if candidates
+            .iter()
+            .any(|candidate| depends_on_changed(candidate, root_dir, changed, visited))
+        {
+            return true;
+        }
+    }
+    false
+}
+
+/// Every string in the flow that looks like a relative file path. Walking all values
+/// instead of specific commands also covers nested blocks, hooks and data files.
+fn references(content: &str) -> Vec<String> {
+    let mut found = Vec::new();
+    for document in serde_yaml::Deserializer::from_str(content) {
+        if let Ok(value) = serde::Deserialize::deserialize(document) {
+            collect_references(&value, &mut found);
+        }
+    }
+    found
+}
+
+fn collect_references(value: &serde_yaml::Value, found: &mut Vec<String>) {
+    match value {
+        serde_yaml::Value::String(s) => {
+            let s = s.trim();
+            let has_extension = Path::new(s)
+                .extension()
+                .and_then(|ext| ext.to_str())
+                .is_some_and(|ext| {
+                    REFERENCE_EXTENSIONS.contains(&ext.to_ascii_lowercase().as_str())
+                });
+            if has_extension && !s.contains("${") && !s.contains("://") && !s.contains('\n') {
+                found.push(s.to_string());
+            }
+        }
+        serde_yaml::Value::Sequence(items) => {
+            for item in items {
+                collect_references(item, found);
+            }
+        }
+        serde_yaml::Value::Mapping(map) => {
+            for (_, item) in map {
+                collect_references(item, found);
+            }
+        }
+        serde_yaml::Value::Tagged(tagged) => collect_references(&tagged.value, found),
+        _ => {}
+    }
+}
+
+fn git(dir: &Path, args: &[&str]) -> Result<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()
+        .context("Failed to run git (required by --changed-since)")?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Absolute path with `.`/`..` resolved; deleted files can't be canonicalized
+fn normalize(path: &Path) -> PathBuf {
+    if let Ok(path) = std::fs::canonicalize(path) {
+        return path;
+    }
+    let absolute = std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf());
+    let mut normalized = PathBuf::new();
+    for component in absolute.components() {
+        match component {
+            std::path::Component::CurDir => {}
+            std::path::Component::ParentDir => {
+                normalized.pop();
+            }
+            other => normalized.push(other),
+        }
+    }
+    normalized
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_changed_subflow_selects_dependent_flows() {
+        let dir = std::env::temp_dir().join(format!("lumi-changed-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(dir.join("subflows")).unwrap();
+        std::fs::write(
+            dir.join("subflows/login.yaml"),
+            "- runScript: ../helpers/otp.js\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("checkout.yaml"),
+            "appId: com.example\n---\n- runFlow:\n    path: subflows/login.yaml\n",
+        )
+        .unwrap();
+        std::fs::write(dir.join("search.yaml"), "appId: com.example\n---\n- back\n").unwrap();
+        std::fs::create_dir_all(dir.join("helpers")).unwrap();
+        std::fs::write(dir.join("helpers/otp.js"), "output.code = 1").unwrap();
+        let files = vec![dir.join("checkout.yaml"), dir.join("search.yaml")];
+
+        let changed: HashSet<PathBuf> = [normalize(&dir.join("helpers/otp.js"))].into();
+        assert_eq!(
+            filter_changed(files.clone(), &[], &changed),
+            vec![dir.join("checkout.yaml")]
+        );
+
+        let changed: HashSet<PathBuf> = [normalize(&dir.join("search.yaml"))].into();
+        assert_eq!(
+            filter_changed(files.clone(), &[], &changed),
+            vec![dir.join("search.yaml")]
+        );
+
+        // A changed setup hook affects the whole suite
+        std::fs::write(dir.join("setup.yaml"), "- back\n").unwrap();
+        let changed: HashSet<PathBuf> = [normalize(&dir.join("setup.yaml"))].into();
+        assert_eq!(
+            filter_changed(files.clone(), &[dir.join("setup.yaml")], &changed).len(),
+            2
+        );
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
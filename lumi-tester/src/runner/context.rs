@@ -1,8 +1,27 @@
-use crate::parser::types::TestFlow;
+use crate::parser::types::{SystemDialogAction, TestFlow};
 use regex::Regex;
 use std::collections::HashMap;
 use std::path::Path;
 
+/// Namespace of built-in runtime variables (`${nl.platform}`); flows can read
+/// them but no variable write can overwrite them
+pub const BUILTIN_PREFIX: &str = "nl.";
+
+/// Reject user variable names that would be shadowed by the built-ins
+pub fn check_var_name(name: &str) -> anyhow::Result<()> {
+    if name.starts_with(BUILTIN_PREFIX) {
+        anyhow::bail!(
+            "Cannot set '{}': the `{}` prefix is reserved for built-in variables",
+            name,
+            BUILTIN_PREFIX
+        );
+    }
+    Ok(())
+}
+
+/// Implicit wait when neither the flow nor lumi.toml sets a timeout
+pub const DEFAULT_TIMEOUT_MS: u64 = 10000;
+
 /// Test execution context that holds runtime information
 pub struct TestContext {
     /// Base directory for test files (for resolving relative paths)
@@ -23,6 +42,9 @@ pub struct TestContext {
     /// User-defined variables (set via setVar command)
     pub vars: HashMap<String, String>,
 
+    /// Built-in runtime values, keyed without the `nl.` prefix
+    pub builtins: HashMap<String, String>,
+
     /// Continue running tests even if one fails
     pub continue_on_failure: bool,
 
@@ -31,6 +53,38 @@ pub struct TestContext {
 
     /// Default timeout for implicit waits
     pub default_timeout_ms: u64,
+
+    /// Button to tap on OS permission dialogs before each command (`handleSystemDialogs`)
+    pub system_dialog_action: Option<SystemDialogAction>,
+
+    /// Scan logcat for crashes/ANRs after each command (`detectCrashes`)
+    pub detect_crashes: bool,
+
+    /// assertScreenshot reference directory from `--baselines-dir` / lumi.toml
+    pub baselines_dir: Option<std::path::PathBuf>,
+
+    /// `baselines:` header of the running file; wins over `baselines_dir`
+    pub flow_baselines_dir: Option<std::path::PathBuf>,
+}
+
+/// Per-device artifact directory (`<output>/<device-serial>/`), so parallel
+/// runs never mix screenshots and videos from different devices
+pub fn device_output_dir(output: &Path, device: &str) -> std::path::PathBuf {
+    // Serials like "192.168.1.5:5555" or simulator names need a safe directory name
+    output.join(safe_dir_name(device))
+}
+
+/// Replace characters that are awkward in directory names with `_`
+pub fn safe_dir_name(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
 }
 
 impl TestContext {
@@ -47,7 +101,7 @@ impl TestContext {
         });
 
         if let Some(ref id) = device_id {
-            output.push(id.replace(':', "_")); // Ensure valid directory name
+            output = device_output_dir(&output, id);
         }
 
         // Always ensure output directory exists
@@ -60,14 +114,24 @@ impl TestContext {
             url: None,
             env: HashMap::new(),
             vars: HashMap::new(),
+            builtins: HashMap::new(),
             continue_on_failure,
             device_id,
-            default_timeout_ms: 10000, // Default 10s
+            default_timeout_ms: DEFAULT_TIMEOUT_MS,
+            system_dialog_action: None,
+            detect_crashes: false,
+            baselines_dir: None,
+            flow_baselines_dir: None,
         }
     }
 
     /// Update context from a test flow's header
-    pub fn update_from_flow(&mut self, flow: &TestFlow) {
+    pub fn update_from_flow(&mut self, flow: &TestFlow) -> anyhow::Result<()> {
+        if let Some(ref env) = flow.env {
+            for name in env.keys() {
+                check_var_name(name)?;
+            }
+        }
         if let Some(ref app_id) = flow.app_id {
             self.app_id = Some(app_id.clone());
         }
@@ -82,6 +146,43 @@ impl TestContext {
         if let Some(timeout) = flow.default_timeout_ms {
             self.default_timeout_ms = timeout;
         }
+        if let Some(action) = flow.handle_system_dialogs {
+            self.system_dialog_action = Some(action);
+        }
+        if let Some(detect) = flow.detect_crashes {
+            self.detect_crashes = detect;
+        }
+        self.flow_baselines_dir = flow.baselines.as_deref().map(|dir| self.resolve_path(dir));
+        Ok(())
+    }
+
+    /// Where to look for an assertScreenshot reference, most specific first.
+    /// Each root (the baselines directory, then the flow's `screenshots/`) is
+    /// tried with every device subfolder (model, resolution, platform) and
+    /// then on its own.
+    pub fn baseline_candidates(
+        &self,
+        filename: &str,
+        device_dirs: &[String],
+    ) -> Vec<std::path::PathBuf> {
+        let mut roots = Vec::new();
+        if let Some(dir) = self
+            .flow_baselines_dir
+            .as_ref()
+            .or(self.baselines_dir.as_ref())
+        {
+            roots.push(dir.clone());
+        }
+        roots.push(self.resolve_path("screenshots"));
+
+        let mut candidates = Vec::new();
+        for root in roots {
+            for sub in device_dirs {
+                candidates.push(root.join(sub).join(filename));
+            }
+            candidates.push(root.join(filename));
+        }
+        candidates
     }
 
     /// Resolve a relative path to an absolute path
@@ -99,8 +200,11 @@ impl TestContext {
         self.output_dir.join(filename)
     }
 
-    /// Get a variable from env or vars
+    /// Get a variable from env or vars; `nl.*` names only read the built-ins
     pub fn get_var(&self, name: &str) -> Option<String> {
+        if let Some(key) = name.strip_prefix(BUILTIN_PREFIX) {
+            return self.builtins.get(key).cloned();
+        }
         self.vars
             .get(name)
             .cloned()
@@ -109,10 +213,22 @@ impl TestContext {
     }
 
     /// Set a variable
-    pub fn set_var(&mut self, name: &str, value: &str) {
+    pub fn set_var(&mut self, name: &str, value: &str) -> anyhow::Result<()> {
         // Substitute any ${varname} in the value
         let substituted = self.substitute_vars(value);
-        self.vars.insert(name.to_string(), substituted);
+        self.insert_var(name, substituted)
+    }
+
+    /// Set a variable to `value` as is, without substitution
+    pub fn insert_var(&mut self, name: &str, value: String) -> anyhow::Result<()> {
+        check_var_name(name)?;
+        self.vars.insert(name.to_string(), value);
+        Ok(())
+    }
+
+    /// Set a built-in variable, read back as `${nl.<key>}`
+    pub fn set_builtin(&mut self, key: &str, value: &str) {
+        self.builtins.insert(key.to_string(), value.to_string());
     }
 
     /// Substitute ${varname} or ${varname.json.path} patterns in a string
@@ -171,10 +287,64 @@ impl TestContext {
     }
 
     /// Merge variables from a nested flow call
-    pub fn merge_vars(&mut self, vars: &HashMap<String, String>) {
+    pub fn merge_vars(&mut self, vars: &HashMap<String, String>) -> anyhow::Result<()> {
         for (k, v) in vars {
-            let substituted = self.substitute_vars(v);
-            self.vars.insert(k.clone(), substituted);
+            self.set_var(k, v)?;
         }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtins_are_namespaced() {
+        let tmp = std::env::temp_dir().join(format!("lumi_ctx_{}", uuid::Uuid::new_v4()));
+        let mut ctx = TestContext::new(Path::new("/flows"), Some(&tmp), false, None);
+        ctx.set_builtin("platform", "android");
+        ctx.set_var("platform", "user value").unwrap();
+        ctx.vars
+            .insert("nl.platform".to_string(), "shadow".to_string());
+
+        assert_eq!(
+            ctx.substitute_vars("${nl.platform}/${platform}"),
+            "android/user value"
+        );
+        assert_eq!(ctx.substitute_vars("${nl.missing}"), "${nl.missing}");
+        assert!(ctx.set_var("nl.platform", "ios").is_err());
+        assert!(ctx.insert_var("nl.appId", "x".to_string()).is_err());
+        let nested = HashMap::from([("nl.platform".to_string(), "web".to_string())]);
+        assert!(ctx.merge_vars(&nested).is_err());
+        assert_eq!(ctx.substitute_vars("${nl.platform}"), "android");
+        std::fs::remove_dir_all(&tmp).ok();
+    }
+
+    #[test]
+    fn test_baseline_candidates_prefer_device_folders() {
+        let tmp = std::env::temp_dir().join(format!("lumi_ctx_{}", uuid::Uuid::new_v4()));
+        let mut ctx = TestContext::new(Path::new("/flows"), Some(&tmp), false, None);
+        ctx.baselines_dir = None;
+        ctx.flow_baselines_dir = Some(Path::new("/proj/baselines").to_path_buf());
+
+        let dirs = vec![safe_dir_name("Pixel 7"), "1080x2400".to_string()];
+        let candidates = ctx.baseline_candidates("home.png", &dirs);
+        let expected = [
+            "/proj/baselines/Pixel_7/home.png",
+            "/proj/baselines/1080x2400/home.png",
+            "/proj/baselines/home.png",
+            "/flows/screenshots/Pixel_7/home.png",
+            "/flows/screenshots/1080x2400/home.png",
+            "/flows/screenshots/home.png",
+        ];
+        assert_eq!(
+            candidates,
+            expected
+                .iter()
+                .map(std::path::PathBuf::from)
+                .collect::<Vec<_>>()
+        );
+        std::fs::remove_dir_all(&tmp).ok();
     }
 }
@@ -0,0 +1,215 @@
+//! Flow prerequisites from the `dependsOn` header
+//!
+//! A flow names the flows it needs by file name, file stem or header title.
+//! Files are reordered so prerequisites run first (otherwise keeping the chosen
+//! `--order`), and a cycle is an error. When a prerequisite does not pass, its
+//! dependents are reported as skipped instead of run.
+
+use anyhow::Result;
+use colored::Colorize;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use crate::parser::yaml::parse_test_file;
+
+/// Prerequisites of each collected flow file
+#[derive(Debug, Clone, Default)]
+pub struct FlowDependencies {
+    prerequisites: HashMap<PathBuf, Vec<PathBuf>>,
+}
+
+impl FlowDependencies {
+    /// Resolve the `dependsOn` headers of `files` against each other. Names that
+    /// match no collected file are ignored with a warning: the prerequisite is not
+    /// part of this run (single file, `--resume`, `--changed-since`).
+    pub fn resolve(files: &[PathBuf]) -> Result<Self> {
+        let declared: Vec<(PathBuf, Vec<String>, Option<String>)> = files
+            .iter()
+            .filter_map(|file| {
+                let flow = parse_test_file(file).ok()?;
+                Some((file.clone(), flow.depends_on, flow.title))
+            })
+            .collect();
+        let titles: HashMap<&PathBuf, &str> = declared
+            .iter()
+            .filter_map(|(file, _, title)| title.as_deref().map(|t| (file, t)))
+            .collect();
+
+        let mut prerequisites = HashMap::new();
+        for (file, names, _) in &declared {
+            let mut resolved = Vec::new();
+            for name in names {
+                let matches: Vec<&PathBuf> = files
+                    .iter()
+                    .filter(|candidate| {
+                        flow_matches(candidate, titles.get(candidate).copied(), name)
+                    })
+                    .collect();
+                match matches.as_slice() {
+                    [] => println!(
+                        "{} {}: dependsOn '{}' matches no flow in this run, ignored",
+                        "⚠️".yellow(),
+                        file.display(),
+                        name
+                    ),
+                    [only] if *only == file => anyhow::bail!(
+                        "{}: a flow cannot depend on itself ('{}')",
+                        file.display(),
+                        name
+                    ),
+                    [only] => resolved.push((*only).clone()),
+                    _ => anyhow::bail!(
+                        "{}: dependsOn '{}' is ambiguous, it matches {}",
+                        file.display(),
+                        name,
+                        matches
+                            .iter()
+                            .map(|m| m.display().to_string())
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    ),
+                }
+            }
+            if !resolved.is_empty() {
+                prerequisites.insert(file.clone(), resolved);
+            }
+        }
+        Ok(Self { prerequisites })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.prerequisites.is_empty()
+    }
+
+    pub fn prerequisites(&self, file: &Path) -> &[PathBuf] {
+        self.prerequisites
+            .get(file)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Stable topological sort: each file runs as early as its prerequisites
+    /// allow, so files without dependencies keep their relative order.
+    pub fn sort(&self, files: Vec<PathBuf>) -> Result<Vec<PathBuf>> {
+        let in_run: HashSet<&PathBuf> = files.iter().collect();
+        let mut placed: HashSet<PathBuf> = HashSet::new();
+        let mut remaining = files.clone();
+        let mut sorted = Vec::with_capacity(files.len());
+
+        while !remaining.is_empty() {
+            let ready = remaining.iter().position(|file| {
+                self.prerequisites(file)
+                    .iter()
+                    .all(|p| placed.contains(p) || !in_run.contains(p))
+            });
+            let Some(index) = ready else {
+                let cycle: Vec<String> =
+                    remaining.iter().map(|f| f.display().to_string()).collect();
+                anyhow::bail!("dependsOn cycle between: {}", cycle.join(", "));
+            };
+            let file = remaining.remove(index);
+            placed.insert(file.clone());
+            sorted.push(file);
+        }
+        Ok(sorted)
+    }
+
+    /// Split `files` (already sorted) into groups linked by dependencies, so a
+    /// parallel run keeps each chain on one worker, in order.
+    pub fn groups(&self, files: &[PathBuf]) -> Vec<Vec<PathBuf>> {
+        let index: HashMap<&PathBuf, usize> =
+            files.iter().enumerate().map(|(i, f)| (f, i)).collect();
+        let mut parent: Vec<usize> = (0..files.len()).collect();
+        fn root(parent: &mut [usize], mut i: usize) -> usize {
+            while parent[i] != i {
+                parent[i] = parent[parent[i]];
+                i = parent[i];
+            }
+            i
+        }
+        for (i, file) in files.iter().enumerate() {
+            for prerequisite in self.prerequisites(file) {
+                if let Some(&j) = index.get(prerequisite) {
+                    let (a, b) = (root(&mut parent, i), root(&mut parent, j));
+                    parent[a] = b;
+                }
+            }
+        }
+
+        let mut groups: Vec<Vec<PathBuf>> = Vec::new();
+        let mut group_of: HashMap<usize, usize> = HashMap::new();
+        for (i, file) in files.iter().enumerate() {
+            let r = root(&mut parent, i);
+            let g = *group_of.entry(r).or_insert_with(|| {
+                groups.push(Vec::new());
+                groups.len() - 1
+            });
+            groups[g].push(file.clone());
+        }
+        groups
+    }
+}
+
+/// `name` refers to `file` by file name (`login.yaml`), stem (`login`) or title
+fn flow_matches(file: &Path, title: Option<&str>, name: &str) -> bool {
+    let name = name.trim();
+    file.file_name().is_some_and(|n| n == name)
+        || file.file_stem().is_some_and(|s| s == name)
+        || title.is_some_and(|t| t == name)
+        || (name.contains('/') && file.ends_with(name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_flows(flows: &[(&str, &str)]) -> (PathBuf, Vec<PathBuf>) {
+        let dir = std::env::temp_dir().join(format!("lumi-deps-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let files = flows
+            .iter()
+            .map(|(name, header)| {
+                let path = dir.join(name);
+                std::fs::write(&path, format!("{}\n---\n- back\n", header)).unwrap();
+                path
+            })
+            .collect();
+        (dir, files)
+    }
+
+    #[test]
+    fn test_prerequisites_run_first_and_cycles_fail() {
+        let (dir, files) = write_flows(&[
+            ("a_checkout.yaml", "appId: x\ndependsOn: [login]"),
+            ("b_search.yaml", "appId: x"),
+            ("login.yaml", "appId: x\ndependsOn: Create account"),
+            ("signup.yaml", "appId: x\nname: Create account"),
+        ]);
+        let deps = FlowDependencies::resolve(&files).unwrap();
+        let sorted = deps.sort(files.clone()).unwrap();
+        let names: Vec<_> = sorted
+            .iter()
+            .map(|f| f.file_name().unwrap().to_str().unwrap())
+            .collect();
+        assert_eq!(
+            names,
+            [
+                "b_search.yaml",
+                "signup.yaml",
+                "login.yaml",
+                "a_checkout.yaml"
+            ]
+        );
+        assert_eq!(deps.groups(&sorted).len(), 2);
+
+        let (cycle_dir, cycle) = write_flows(&[
+            ("a.yaml", "appId: x\ndependsOn: b"),
+            ("b.yaml", "appId: x\ndependsOn: a"),
+        ]);
+        let deps = FlowDependencies::resolve(&cycle).unwrap();
+        assert!(deps.sort(cycle).is_err());
+
+        std::fs::remove_dir_all(&dir).ok();
+        std::fs::remove_dir_all(&cycle_dir).ok();
+    }
+}
@@ -15,6 +15,12 @@ pub enum TestEvent {
         summary: TestSummary,
     },
 
+    // Suite events, around the main test files of a session (not setup/teardown)
+    SuiteStarted {
+        total_flows: usize,
+    },
+    SuiteFinished,
+
     // Flow events
     FlowStarted {
         flow_name: String,
@@ -79,6 +85,7 @@ pub enum TestEvent {
 }
 
 /// Event emitter for broadcasting test events
+#[derive(Clone)]
 pub struct EventEmitter {
     sender: broadcast::Sender<TestEvent>,
 }
@@ -133,15 +140,107 @@ impl JsonlEventListener {
 use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
 use std::time::Duration as StdDuration;
 
+/// How much the console listener prints (`--quiet` / `--verbose`)
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum ConsoleVerbosity {
+    /// One `PASS`/`FAIL` line per flow plus errors, no spinners
+    Quiet,
+    #[default]
+    Normal,
+    /// Normal output plus flow paths and full command errors
+    Verbose,
+}
+
+/// Compact CI output used by `ConsoleVerbosity::Quiet`
+#[derive(Debug, Default)]
+struct QuietFormatter {
+    /// Depth of the last printed failure; the enclosing commands fail with it
+    failed_depth: Option<usize>,
+}
+
+impl QuietFormatter {
+    fn line(&mut self, event: &TestEvent) -> Option<String> {
+        use colored::Colorize;
+
+        match event {
+            TestEvent::CommandStarted { .. } => {
+                self.failed_depth = None;
+                None
+            }
+            TestEvent::CommandFailed {
+                flow_name,
+                index,
+                error,
+                depth,
+                ..
+            } => {
+                let nested = self.failed_depth.is_some_and(|d| d > *depth);
+                self.failed_depth = Some(*depth);
+                (!nested)
+                    .then(|| format!("  {} {} [{}]: {}", "error".red(), flow_name, index, error))
+            }
+            TestEvent::FlowFinished {
+                flow_name,
+                status,
+                duration_ms,
+                depth: 0,
+            } => {
+                let label = match status {
+                    FlowStatus::Passed => "PASS".green(),
+                    FlowStatus::Failed => "FAIL".red(),
+                    FlowStatus::PartiallyPassed { .. } => "PARTIAL".yellow(),
+                    _ => "SKIP".yellow(),
+                };
+                let duration = duration_ms
+                    .map(|ms| format!(" ({:.1}s)", ms as f64 / 1000.0))
+                    .unwrap_or_default();
+                Some(format!("{} {}{}", label, flow_name, duration))
+            }
+            TestEvent::AppCrashed { app_id, .. } => Some(format!(
+                "  {} {} is no longer running",
+                "crash".red(),
+                app_id
+            )),
+            TestEvent::SessionFinished { summary } => {
+                let duration = summary
+                    .total_duration_ms
+                    .map(|ms| format!(" in {:.1}s", ms as f64 / 1000.0))
+                    .unwrap_or_default();
+                Some(format!(
+                    "{} flows, {} commands: {} passed, {} failed, {} skipped{}",
+                    summary.total_flows,
+                    summary.total_commands,
+                    summary.passed,
+                    summary.failed,
+                    summary.skipped,
+                    duration
+                ))
+            }
+            _ => None,
+        }
+    }
+}
+
 /// Console event listener for printing real-time updates
 pub struct ConsoleEventListener;
 
 impl ConsoleEventListener {
-    pub async fn listen(mut receiver: broadcast::Receiver<TestEvent>) {
+    pub async fn listen(mut receiver: broadcast::Receiver<TestEvent>, verbosity: ConsoleVerbosity) {
         use colored::Colorize;
         use indicatif::ProgressDrawTarget;
         use std::io::IsTerminal;
 
+        if verbosity == ConsoleVerbosity::Quiet {
+            let mut formatter = QuietFormatter::default();
+            while let Ok(event) = receiver.recv().await {
+                if let Some(line) = formatter.line(&event) {
+                    println!("{}", line);
+                }
+            }
+            return;
+        }
+        let verbose = verbosity == ConsoleVerbosity::Verbose;
+
         // Create MultiProgress with appropriate draw target based on TTY detection
         let multi = if std::io::stdout().is_terminal() {
             MultiProgress::new()
@@ -150,6 +249,9 @@ impl ConsoleEventListener {
             MultiProgress::with_draw_target(ProgressDrawTarget::hidden())
         };
 
+        // Overall `[done/total]` bar while a suite runs; hidden with the rest off a TTY
+        let mut suite: Option<(ProgressBar, usize)> = None;
+
         // Keep track of the current spinners by depth
         let mut spinners: Vec<Option<ProgressBar>> = Vec::new();
         let mut command_texts: Vec<String> = Vec::new();
@@ -168,7 +270,28 @@ impl ConsoleEventListener {
                         .ok();
                 }
 
+                TestEvent::SuiteStarted { total_flows } => {
+                    let pb = multi.add(ProgressBar::new(total_flows as u64));
+                    pb.set_style(
+                        ProgressStyle::default_bar()
+                            .template("  [{pos}/{len}] flows finished {bar:30.cyan/blue} {msg}")
+                            .unwrap()
+                            .progress_chars("=> "),
+                    );
+                    suite = Some((pb, 0));
+                }
+
+                TestEvent::SuiteFinished => {
+                    if let Some((pb, _)) = suite.take() {
+                        pb.finish_and_clear();
+                    }
+                }
+
                 TestEvent::SessionFinished { summary } => {
+                    if let Some((pb, _)) = suite.take() {
+                        pb.finish_and_clear();
+                    }
+
                     // Finish all spinners (but don't clear to preserve output)
                     for pb in spinners.drain(..).flatten() {
                         // Just finish the spinner, don't clear to preserve the message
@@ -196,9 +319,9 @@ impl ConsoleEventListener {
 
                 TestEvent::FlowStarted {
                     flow_name,
+                    flow_path,
                     command_count,
                     depth,
-                    ..
                 } => {
                     // Finish spinners at lower depths when nested flow starts to prevent loop
                     // We finish the spinner to stop it from ticking, but don't print message yet
@@ -224,6 +347,9 @@ impl ConsoleEventListener {
                         flow_name.white().bold(),
                         command_count
                     );
+                    if verbose {
+                        println!("{}    {}", indent, flow_path.dimmed());
+                    }
                 }
 
                 TestEvent::FlowFinished {
@@ -247,6 +373,20 @@ impl ConsoleEventListener {
                     // already finished when nested flow started. The CommandPassed event
                     // will handle printing the final message.
 
+                    if depth == 0 {
+                        if let Some((pb, failed)) = suite.as_mut() {
+                            if status == FlowStatus::Failed {
+                                *failed += 1;
+                                pb.set_message(format!("{} failed", failed).red().to_string());
+                            }
+                            // Data-driven files run one flow per row
+                            if pb.position() >= pb.length().unwrap_or(0) {
+                                pb.inc_length(1);
+                            }
+                            pb.inc(1);
+                        }
+                    }
+
                     let status_str = match status {
                         FlowStatus::Passed => "PASSED".green().bold(),
                         FlowStatus::Failed => "FAILED".red().bold(),
@@ -255,6 +395,9 @@ impl ConsoleEventListener {
                                 .yellow()
                                 .bold()
                         }
+                        FlowStatus::Skipped { reason } => {
+                            format!("SKIPPED: {}", reason).yellow().bold()
+                        }
                         _ => "UNKNOWN".white().bold(),
                     };
                     let indent = "    ".repeat(depth);
@@ -338,7 +481,7 @@ impl ConsoleEventListener {
                 }
 
                 TestEvent::CommandFailed {
-                    error: _error, // Bind 'error' field to '_error' to ignore unused warning
+                    error,
                     duration_ms,
                     depth,
                     ..
@@ -366,6 +509,11 @@ impl ConsoleEventListener {
                                 duration_ms
                             );
                         }
+                        if verbose {
+                            multi
+                                .println(format!("{}      {}", indent, error.red()))
+                                .ok();
+                        }
                     }
                 }
 
@@ -441,3 +589,73 @@ impl ConsoleEventListener {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn failed(index: usize, depth: usize, error: &str) -> TestEvent {
+        TestEvent::CommandFailed {
+            flow_name: "login".to_string(),
+            index,
+            error: error.to_string(),
+            duration_ms: 5,
+            depth,
+        }
+    }
+
+    #[test]
+    fn test_quiet_formatter_prints_flow_results_and_innermost_errors() {
+        colored::control::set_override(false);
+        let mut f = QuietFormatter::default();
+
+        let started = |depth| TestEvent::CommandStarted {
+            flow_name: "login".to_string(),
+            index: 0,
+            command: "tap".to_string(),
+            depth,
+        };
+        assert_eq!(f.line(&started(0)), None);
+        assert_eq!(f.line(&started(1)), None);
+        assert_eq!(
+            f.line(&failed(2, 1, "Element not found")).as_deref(),
+            Some("  error login [2]: Element not found")
+        );
+        // The runFlow that wraps the failure is not repeated
+        assert_eq!(f.line(&failed(0, 0, "Flow failed: sub")), None);
+
+        assert_eq!(
+            f.line(&TestEvent::FlowFinished {
+                flow_name: "login".to_string(),
+                status: FlowStatus::Failed,
+                duration_ms: Some(1234),
+                depth: 0,
+            })
+            .as_deref(),
+            Some("FAIL login (1.2s)")
+        );
+        assert_eq!(
+            f.line(&TestEvent::Log {
+                message: "noise".to_string(),
+                depth: 0
+            }),
+            None
+        );
+        assert_eq!(
+            f.line(&TestEvent::SessionFinished {
+                summary: TestSummary {
+                    session_id: "s".to_string(),
+                    total_flows: 2,
+                    total_commands: 12,
+                    passed: 10,
+                    failed: 1,
+                    skipped: 1,
+                    total_duration_ms: Some(4200),
+                    failed_flows: vec!["login".to_string()],
+                },
+            })
+            .as_deref(),
+            Some("2 flows, 12 commands: 10 passed, 1 failed, 1 skipped in 4.2s")
+        );
+    }
+}
@@ -3,16 +3,30 @@ use colored::Colorize;
 use std::path::Path;
 use uuid::Uuid;
 
-use super::context::TestContext;
-use super::events::{ConsoleEventListener, EventEmitter, JsonlEventListener, TestEvent};
-use super::state::{CommandState, FlowState, TestSessionState};
-use crate::driver::traits::PlatformDriver;
-use crate::parser::types::TestCommand;
+use super::context::{check_var_name, TestContext};
+use super::events::{
+    ConsoleEventListener, ConsoleVerbosity, EventEmitter, JsonlEventListener, TestEvent,
+};
+use super::state::{
+    CommandState, CommandStatus, FlowMetadata, FlowParent, FlowState, SummarySink, TestSessionState,
+};
+use crate::driver::traits::{PlatformDriver, RecordingOptions};
+use crate::error::TesterError;
+use crate::parser::types::{NetworkConditionsInput, TestCommand};
 use crate::parser::yaml::{parse_commands_from_value, parse_test_file};
 use serde_json;
 use std::collections::HashMap;
 use std::fs::File;
 
+/// Number of recent logcat lines scanned for crashes after each command
+const CRASH_SCAN_LINES: u32 = 2000;
+
+/// `scroll: true`: how long an element may take to appear before scrolling starts
+const SCROLL_FALLBACK_WAIT_MS: u64 = 1500;
+
+/// `scroll: true`: swipes tried before the element counts as missing
+const SCROLL_FALLBACK_MAX_SCROLLS: u32 = 10;
+
 pub struct TestExecutor {
     driver: Box<dyn PlatformDriver>,
     context: TestContext,
@@ -27,14 +41,90 @@ pub struct TestExecutor {
     auto_capture_interval: u64,
     auto_capture_max: u32,
     auto_capture_width: Option<u32>,
+    auto_capture_crop: Option<String>,
     auto_capture_last_time: std::time::Instant,
     depth: usize,
+    /// Running flows by depth, mirrored from `run_commands_set` after every command so a
+    /// flow abandoned by a timeout can still be finished and reported
+    flow_stack: Vec<FlowState>,
     target_tags: Option<Vec<String>>,
+    /// Header tags/title/description of the file being run, attached to its top-level flow
+    flow_metadata: FlowMetadata,
     soft_errors: Vec<String>,
+    /// Depth of the innermost `assertAll` block's own commands; their failures go to
+    /// `block_failures` and the block goes on. Nested flows at deeper levels still stop.
+    collect_failures: Option<usize>,
+    /// Failures collected by the running `assertAll` block, one per failed member
+    block_failures: Vec<String>,
+    /// State set aside by each running `assertAll` block, innermost last
+    assert_all_frames: Vec<AssertAllFrame>,
+    /// Values shadowed by each running `forEach` loop variable, innermost last
+    loop_vars: Vec<(String, Option<String>)>,
     video_enabled: bool,
+    /// Recording quality from the CLI; startRecording params override it
+    recording_options: RecordingOptions,
     #[allow(dead_code)]
     snapshot_enabled: bool,
     report_enabled: bool,
+    /// Set by setDateTime so the clock is restored when the file finishes
+    date_time_overridden: bool,
+    /// Last logcat line seen by crash detection (`detectCrashes`)
+    crash_log_marker: Option<String>,
+    /// Set while startProfiling is active; samples are timed from this instant
+    profiling_started: Option<std::time::Instant>,
+    /// Minimum gap between samples (`samplingIntervalMs`) and time of the last one
+    perf_sample_interval_ms: u64,
+    perf_last_sample: Option<std::time::Instant>,
+    /// Performance time series per flow, rendered as charts in the HTML report
+    perf_samples: Vec<crate::report::types::FlowPerformance>,
+    /// Receives the session summary on `finish` (`--json-summary`)
+    summary_sink: Option<SummarySink>,
+    /// End of the running file's `timeout` budget and the budget in ms
+    flow_deadline: Option<(tokio::time::Instant, u64)>,
+    /// Set by an `optional` command whose target was missing; reported as skipped
+    skipped_reason: Option<String>,
+    /// Screenshot taken by a passing assertion with `screenshot: true`
+    assert_screenshot: Option<String>,
+    /// Command types reported as skipped instead of run (`--skip-command`)
+    skip_commands: Vec<String>,
+    /// Keep a flow's video only when it failed (`--record-on-failure`)
+    record_on_failure: bool,
+    /// Formats `finish` writes with `--report`; all when unset
+    report_formats: Option<Vec<String>>,
+}
+
+/// `break`/`continue`/`stopFlow`, carried up as an error until a loop or flow catches it
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ControlSignal {
+    Break,
+    Continue,
+    StopFlow,
+}
+
+impl ControlSignal {
+    fn of(error: &anyhow::Error) -> Option<Self> {
+        error.downcast_ref::<Self>().copied()
+    }
+}
+
+impl std::fmt::Display for ControlSignal {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Break => write!(f, "break"),
+            Self::Continue => write!(f, "continue"),
+            Self::StopFlow => write!(f, "stopFlow"),
+        }
+    }
+}
+
+impl std::error::Error for ControlSignal {}
+
+/// What an `assertAll` block puts back when it ends (or is abandoned by a timeout)
+#[derive(Debug)]
+struct AssertAllFrame {
+    failures: Vec<String>,
+    soft_errors: Vec<String>,
+    collect_failures: Option<usize>,
 }
 
 #[derive(Debug, Clone, Default)]
@@ -63,6 +153,7 @@ impl TestExecutor {
             report,
             target_tags,
             false,
+            ConsoleVerbosity::Normal,
         )
     }
 
@@ -75,6 +166,7 @@ impl TestExecutor {
         report: bool,
         target_tags: Option<Vec<String>>,
         events_jsonl: bool,
+        verbosity: ConsoleVerbosity,
     ) -> Self {
         let (emitter, receiver) = EventEmitter::new();
         let device_id = driver.device_serial();
@@ -82,7 +174,7 @@ impl TestExecutor {
         let context = TestContext::new(Path::new("."), output_dir, continue_on_failure, device_id);
 
         // Start console listener in background
-        tokio::spawn(ConsoleEventListener::listen(receiver));
+        tokio::spawn(ConsoleEventListener::listen(receiver, verbosity));
 
         if events_jsonl {
             let events_receiver = emitter.subscribe();
@@ -106,18 +198,176 @@ impl TestExecutor {
             emitter,
             continue_on_failure,
             depth: 0,
+            flow_stack: Vec::new(),
             gif_frames: HashMap::new(),
             auto_capture_frames: Vec::new(),
             auto_capture_active: false,
             auto_capture_interval: 200,
             auto_capture_max: 150,
             auto_capture_width: None,
+            auto_capture_crop: None,
             auto_capture_last_time: std::time::Instant::now(),
             target_tags,
+            flow_metadata: FlowMetadata::default(),
             soft_errors: Vec::new(),
+            collect_failures: None,
+            block_failures: Vec::new(),
+            assert_all_frames: Vec::new(),
+            loop_vars: Vec::new(),
             video_enabled: record,
+            recording_options: RecordingOptions::default(),
             snapshot_enabled: snapshot,
             report_enabled: report,
+            date_time_overridden: false,
+            crash_log_marker: None,
+            profiling_started: None,
+            perf_sample_interval_ms: 1000,
+            perf_last_sample: None,
+            perf_samples: Vec::new(),
+            summary_sink: None,
+            flow_deadline: None,
+            skipped_reason: None,
+            assert_screenshot: None,
+            skip_commands: Vec::new(),
+            record_on_failure: false,
+            report_formats: None,
+        }
+    }
+
+    /// Set the default recording quality for `--record` and `startRecording`
+    pub fn with_recording_options(mut self, options: RecordingOptions) -> Self {
+        self.recording_options = options;
+        self
+    }
+
+    /// Report commands of these types as skipped instead of running them
+    pub fn with_skip_commands(mut self, names: Vec<String>) -> Self {
+        self.skip_commands = names;
+        self
+    }
+
+    /// Delete the recordings of flows that passed (`--record-on-failure`)
+    pub fn with_record_on_failure(mut self, enabled: bool) -> Self {
+        self.record_on_failure = enabled;
+        self
+    }
+
+    /// Limit the `--report` output to these formats (json, html, junit)
+    pub fn with_report_formats(mut self, formats: Option<Vec<String>>) -> Self {
+        self.report_formats = formats;
+        self
+    }
+
+    /// Command timeout for flows without `defaultTimeout`
+    pub fn with_default_timeout(mut self, timeout_ms: u64) -> Self {
+        self.context.default_timeout_ms = timeout_ms;
+        self
+    }
+
+    /// Reference directory for `assertScreenshot` (`--baselines-dir`)
+    pub fn with_baselines_dir(mut self, dir: Option<std::path::PathBuf>) -> Self {
+        self.context.baselines_dir = dir;
+        self
+    }
+
+    /// Collect the session summary into `sink` when the executor finishes
+    pub fn with_summary_sink(mut self, sink: SummarySink) -> Self {
+        self.summary_sink = Some(sink);
+        self
+    }
+
+    /// Run `command`, abandoning it at `deadline` (`None` when it timed out).
+    ///
+    /// The abandoned future may stop anywhere inside nested blocks, so the state
+    /// they set aside is unwound here; adb children are killed when their future drops.
+    async fn execute_until(
+        &mut self,
+        command: &TestCommand,
+        deadline: tokio::time::Instant,
+    ) -> Option<Result<()>> {
+        let depth = self.depth;
+        // Flows deeper than the one running `command` were started by it
+        let flows = depth + 1;
+        let assert_all_frames = self.assert_all_frames.len();
+        let loop_vars = self.loop_vars.len();
+        let result = tokio::time::timeout_at(deadline, self.execute_command(command))
+            .await
+            .ok();
+        if result.is_none() {
+            self.abandon_flows(flows);
+            while self.assert_all_frames.len() > assert_all_frames {
+                self.leave_assert_all();
+            }
+            while self.loop_vars.len() > loop_vars {
+                self.leave_loop_var();
+            }
+        }
+        self.depth = depth;
+        result
+    }
+
+    /// Finish and record the nested flows above `len` that a timeout cut short
+    fn abandon_flows(&mut self, len: usize) {
+        while self.flow_stack.len() > len {
+            let Some(mut flow) = self.flow_stack.pop() else {
+                break;
+            };
+            if let Some(cmd) = flow.current_command() {
+                if !matches!(cmd.status, CommandStatus::Pending) && !cmd.status.is_terminal() {
+                    cmd.fail("Timed out".to_string());
+                }
+            }
+            flow.skip_remaining("Timed out");
+            flow.finish();
+            flow.status = crate::runner::state::FlowStatus::Failed;
+            flow.error = Some("Timed out".to_string());
+            self.emitter.emit(TestEvent::FlowFinished {
+                flow_name: flow.flow_name.clone(),
+                status: flow.status.clone(),
+                duration_ms: flow.total_duration_ms,
+                depth: flow.depth,
+            });
+            self.session.add_flow(flow);
+        }
+    }
+
+    /// Scope failures to a new `assertAll` block at the current depth
+    fn enter_assert_all(&mut self) {
+        self.assert_all_frames.push(AssertAllFrame {
+            failures: std::mem::take(&mut self.block_failures),
+            soft_errors: std::mem::take(&mut self.soft_errors),
+            collect_failures: self.collect_failures.replace(self.depth),
+        });
+    }
+
+    /// End the innermost `assertAll` block and return its collected failures.
+    /// Its soft failures join the enclosing flow's.
+    fn leave_assert_all(&mut self) -> Vec<String> {
+        let Some(frame) = self.assert_all_frames.pop() else {
+            return Vec::new();
+        };
+        self.collect_failures = frame.collect_failures;
+        let member_soft = std::mem::replace(&mut self.soft_errors, frame.soft_errors);
+        self.soft_errors.extend(member_soft);
+        std::mem::replace(&mut self.block_failures, frame.failures)
+    }
+
+    /// Set a `forEach` loop variable, remembering the value it shadows
+    fn enter_loop_var(&mut self, name: &str) {
+        let previous = self.context.vars.get(name).cloned();
+        self.loop_vars.push((name.to_string(), previous));
+    }
+
+    /// Restore the variable shadowed by the innermost `forEach`
+    fn leave_loop_var(&mut self) {
+        match self.loop_vars.pop() {
+            Some((name, Some(value))) => {
+                self.context.vars.insert(name, value);
+            }
+            Some((name, None)) => {
+                self.context.vars.remove(&name);
+            }
+            None => {}
         }
     }
 
@@ -126,6 +376,57 @@ impl TestExecutor {
         self.emitter.subscribe()
     }
 
+    /// Commands that failed so far in this session
+    pub fn failed_commands(&self) -> u32 {
+        self.session.summary().failed
+    }
+
+    /// Broadcast an event from outside the executor (e.g. suite progress)
+    pub fn emit(&self, event: TestEvent) {
+        self.emitter.emit(event);
+    }
+
+    /// Record a test file as skipped without running it (e.g. a `dependsOn`
+    /// prerequisite did not pass); its commands show up as skipped in reports.
+    pub fn skip_file(&mut self, path: &Path, reason: &str) {
+        let commands = parse_test_file(path)
+            .map(|flow| flow.commands)
+            .unwrap_or_default();
+        let flow_name = path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("unknown");
+        let command_states = commands
+            .iter()
+            .enumerate()
+            .map(|(i, cmd)| {
+                let mut state = CommandState::new(i, &cmd.display_name(), &cmd.display_name());
+                state.skip(reason.to_string());
+                state
+            })
+            .collect();
+
+        let mut flow_state = FlowState::new(flow_name, &path.to_string_lossy(), command_states);
+        flow_state.device = self.context.device_id.clone();
+        flow_state.status = crate::runner::state::FlowStatus::Skipped {
+            reason: reason.to_string(),
+        };
+
+        self.emitter.emit(TestEvent::FlowStarted {
+            flow_name: flow_name.to_string(),
+            flow_path: path.to_string_lossy().to_string(),
+            command_count: commands.len(),
+            depth: self.depth,
+        });
+        self.emitter.emit(TestEvent::FlowFinished {
+            flow_name: flow_name.to_string(),
+            status: flow_state.status.clone(),
+            duration_ms: None,
+            depth: self.depth,
+        });
+        self.session.add_flow(flow_state);
+    }
+
     /// Run a single test file
     pub async fn run_file(
         &mut self,
@@ -158,10 +459,17 @@ impl TestExecutor {
             }
         }
 
+        self.flow_metadata = FlowMetadata {
+            tags: flow.tags.clone(),
+            title: flow.title.clone(),
+            description: flow.description.clone(),
+        };
+
         // Update context from flow header
-        self.context.update_from_flow(&flow);
+        self.context.update_from_flow(&flow)?;
         self.driver
             .set_desktop_state(flow.desktop_state.clone(), &self.context.base_dir)?;
+        self.set_flow_builtins().await;
 
         // Note: Web driver config (closeWhenFinish, browser type) is now pre-parsed and applied
         // in run_on_device before executor is created, so no re-init needed here.
@@ -201,7 +509,7 @@ impl TestExecutor {
         for (iter_idx, vars) in iterations.iter().enumerate() {
             // Apply variables from data row
             for (k, v) in vars {
-                self.context.vars.insert(k.clone(), v.clone());
+                self.context.insert_var(k, v.clone())?;
             }
 
             let flow_name = if iterations.len() > 1 {
@@ -248,13 +556,90 @@ impl TestExecutor {
                 flow.commands.clone()
             };
 
-            self.run_commands_set(&commands_to_run, &flow_name, &path.display().to_string())
-                .await?;
+            if self.context.detect_crashes {
+                self.mark_crash_logs().await;
+            }
+
+            self.flow_deadline = flow.timeout_ms.map(|ms| {
+                (
+                    tokio::time::Instant::now() + std::time::Duration::from_millis(ms),
+                    ms,
+                )
+            });
+            let result = self
+                .run_commands_set(&commands_to_run, &flow_name, &path.display().to_string())
+                .await;
+            self.flow_deadline = None;
+
+            let result = match result {
+                Err(e) => match ControlSignal::of(&e) {
+                    Some(ControlSignal::StopFlow) => Ok(()),
+                    Some(signal) => Err(anyhow::anyhow!(
+                        "`{}` used outside of repeat/forEach",
+                        signal
+                    )),
+                    None => Err(e),
+                },
+                ok => ok,
+            };
+            if result.is_err() {
+                self.restore_date_time().await;
+            }
+            result?;
         }
 
+        self.restore_date_time().await;
         Ok(())
     }
 
+    /// Undo a setDateTime override at the end of a file
+    async fn restore_date_time(&mut self) {
+        if !self.date_time_overridden {
+            return;
+        }
+        self.date_time_overridden = false;
+        if let Err(e) = self.driver.set_date_time(None).await {
+            self.emitter.emit(TestEvent::Log {
+                message: format!("{} Failed to restore date/time: {}", "⚠️".yellow(), e),
+                depth: self.depth,
+            });
+        }
+    }
+
+    /// Remember the current end of the device log so crash scans only see newer lines
+    async fn mark_crash_logs(&mut self) {
+        if let Ok(logs) = self.driver.dump_logs(1).await {
+            self.crash_log_marker = crate::utils::crash::last_log_line(&logs);
+        }
+    }
+
+    /// Fail when the app crashed or hit an ANR since the last scan (`detectCrashes`)
+    async fn check_for_crash(&mut self) -> Result<()> {
+        if !self.context.detect_crashes {
+            return Ok(());
+        }
+        let logs = match self.driver.dump_logs(CRASH_SCAN_LINES).await {
+            Ok(logs) => logs,
+            Err(e) => {
+                log::debug!("Crash scan skipped: {}", e);
+                return Ok(());
+            }
+        };
+        let new_logs =
+            crate::utils::crash::logs_after_marker(&logs, self.crash_log_marker.as_deref());
+        let crash = crate::utils::crash::find_crash(new_logs, self.context.app_id.as_deref());
+        if let Some(marker) = crate::utils::crash::last_log_line(&logs) {
+            self.crash_log_marker = Some(marker);
+        }
+        match crash {
+            Some(trace) => anyhow::bail!(
+                "App crashed or stopped responding (detectCrashes):\n{}",
+                trace
+            ),
+            None => Ok(()),
+        }
+    }
+
     /// Run a set of commands as a flow
     async fn run_commands_set(
         &mut self,
@@ -269,6 +654,18 @@ impl TestExecutor {
             .collect();
 
         let mut flow_state = FlowState::new(flow_name, flow_path, command_states);
+        flow_state.device = self.context.device_id.clone();
+        if self.depth == 0 {
+            flow_state.metadata = self.flow_metadata.clone();
+        }
+
+        // Entries deeper than us belong to flows that already ended (or were abandoned)
+        self.flow_stack.truncate(self.depth);
+        flow_state.depth = self.depth;
+        flow_state.parent = self.flow_stack.last().map(|running| FlowParent {
+            flow_id: running.id.clone(),
+            command_index: running.current_index,
+        });
 
         // Emit flow started event
         self.emitter.emit(TestEvent::FlowStarted {
@@ -279,28 +676,21 @@ impl TestExecutor {
         });
 
         flow_state.start();
+        self.flow_stack.push(flow_state.clone());
 
         // Video Recording Setup
         let video_active = self.video_enabled;
         let mut video_rel_path = None;
+        // Sanitize flow name safely
+        let safe_name: String = flow_name
+            .chars()
+            .map(|c| if c.is_alphanumeric() { c } else { '_' })
+            .collect();
+        let artifact_id: String = Uuid::new_v4().to_string().chars().take(8).collect();
 
         if video_active {
             let out_dir = &self.context.output_dir;
-            // Sanitize flow name safely
-            let safe_name: String = flow_name
-                .chars()
-                .map(|c| if c.is_alphanumeric() { c } else { '_' })
-                .collect();
-
-            let filename = format!(
-                "video_{}_{}.mp4",
-                safe_name,
-                Uuid::new_v4()
-                    .to_string()
-                    .chars()
-                    .take(8)
-                    .collect::<String>()
-            );
+            let filename = format!("video_{}_{}.mp4", safe_name, artifact_id);
 
             let abs_path = out_dir.join(&filename);
             let abs_path_str = abs_path.to_string_lossy().to_string();
@@ -315,7 +705,11 @@ impl TestExecutor {
                 depth: self.depth,
             });
 
-            if let Err(e) = self.driver.start_recording(&abs_path_str).await {
+            if let Err(e) = self
+                .driver
+                .start_recording_with_options(&abs_path_str, &self.recording_options)
+                .await
+            {
                 self.emitter.emit(TestEvent::Log {
                     message: format!("{} Failed to start recording: {}", "⚠️".yellow(), e),
                     depth: self.depth,
@@ -325,10 +719,34 @@ impl TestExecutor {
             }
         }
 
+        // Driver trace (--web-trace) of each top-level flow
+        let mut trace_path = None;
+        if self.depth == 0 {
+            let path = self
+                .context
+                .output_dir
+                .join(format!("trace_{}_{}.json", safe_name, artifact_id));
+            match self.driver.start_trace(&path).await {
+                Ok(true) => trace_path = Some(path),
+                Ok(false) => {}
+                Err(e) => self.emitter.emit(TestEvent::Log {
+                    message: format!("{} Failed to start trace: {}", "⚠️".yellow(), e),
+                    depth: self.depth,
+                }),
+            }
+        }
+
+        // Set by break/continue/stopFlow; re-raised once the flow is recorded
+        let mut signal = None;
+
         // Execute commands
         for (i, command) in commands.iter().enumerate() {
             if let Some(cmd_state) = flow_state.commands.get_mut(i) {
                 cmd_state.start();
+                if let Some(running) = self.flow_stack.get_mut(self.depth) {
+                    running.current_index = i;
+                    running.commands[i] = cmd_state.clone();
+                }
 
                 self.emitter.emit(TestEvent::CommandStarted {
                     flow_name: flow_name.to_string(),
@@ -337,9 +755,63 @@ impl TestExecutor {
                     depth: self.depth,
                 });
 
-                match self.execute_command(command).await {
+                let answers_dialogs = !matches!(command, TestCommand::TapSystemButton(_));
+                if answers_dialogs && self.driver.has_cheap_dialog_probe() {
+                    self.dismiss_system_dialogs().await;
+                }
+
+                let mut result = self.run_traced(command).await;
+                // Without a cheap probe, a dialog is only looked for once it hides
+                // the element a command needs; answer it and run the command again
+                if answers_dialogs
+                    && !self.driver.has_cheap_dialog_probe()
+                    && matches!(
+                        result.as_ref().map_err(TesterError::of),
+                        Err(Some(TesterError::ElementNotFound { .. }))
+                    )
+                    && self.dismiss_system_dialogs().await
+                {
+                    result = self.run_traced(command).await;
+                }
+                let skipped = self.skipped_reason.take();
+                let assert_screenshot = self.assert_screenshot.take();
+                let result = match result {
+                    Ok(()) => self.check_for_crash().await,
+                    Err(e) => Err(e),
+                };
+
+                if self.profiling_started.is_some() {
+                    // Always sample after assertPerformance so failures show the trend
+                    let force = matches!(command, TestCommand::AssertPerformance(_));
+                    self.record_perf_sample(flow_name, flow_path, i, force)
+                        .await;
+                }
+
+                let result = match result {
+                    Err(e) => match ControlSignal::of(&e) {
+                        Some(s) => {
+                            signal = Some(s);
+                            Ok(())
+                        }
+                        None => Err(e),
+                    },
+                    ok => ok,
+                };
+
+                match result {
+                    Ok(()) if skipped.is_some() => {
+                        let reason = skipped.unwrap_or_default();
+                        cmd_state.skip(reason.clone());
+                        self.emitter.emit(TestEvent::CommandSkipped {
+                            flow_name: flow_name.to_string(),
+                            index: i,
+                            reason,
+                            depth: self.depth,
+                        });
+                    }
                     Ok(()) => {
                         cmd_state.pass();
+                        cmd_state.screenshot_path = assert_screenshot;
                         let duration = cmd_state.duration_ms.unwrap_or(0);
 
                         // Auto-capture GIF frame if active
@@ -374,7 +846,17 @@ impl TestExecutor {
                             depth: self.depth,
                         });
 
-                        if !self.continue_on_failure {
+                        let timed_out = self
+                            .flow_deadline
+                            .is_some_and(|(deadline, _)| tokio::time::Instant::now() >= deadline);
+                        if timed_out {
+                            flow_state.skip_remaining("Flow timed out");
+                            break;
+                        }
+                        if self.collect_failures == Some(self.depth) {
+                            // Reported together when the assertAll block ends
+                            self.block_failures.push(e.to_string());
+                        } else if !self.continue_on_failure {
                             // Skip remaining commands
                             flow_state.skip_remaining("Previous command failed");
                             break;
@@ -384,9 +866,29 @@ impl TestExecutor {
             }
 
             flow_state.current_index = i + 1;
+            if let Some(running) = self.flow_stack.get_mut(self.depth) {
+                running.commands[i] = flow_state.commands[i].clone();
+            }
+
+            if let Some(s) = signal {
+                flow_state.skip_remaining(&format!("Skipped by {}", s));
+                break;
+            }
         }
 
         flow_state.finish();
+        self.flow_stack.truncate(self.depth);
+
+        if let Some(path) = trace_path {
+            let message = match self.driver.stop_trace().await {
+                Ok(()) => format!("{} Trace saved: {}", "🕸️".cyan(), path.display()),
+                Err(e) => format!("{} Failed to save trace: {}", "⚠️".yellow(), e),
+            };
+            self.emitter.emit(TestEvent::Log {
+                message,
+                depth: self.depth,
+            });
+        }
 
         if let Some(rel_path) = video_rel_path {
             if let Err(e) = self.driver.stop_recording().await {
@@ -394,6 +896,16 @@ impl TestExecutor {
                     message: format!("{} Failed to stop recording: {}", "⚠️".yellow(), e),
                     depth: self.depth,
                 });
+            } else if self.record_on_failure
+                && flow_state.status == crate::runner::state::FlowStatus::Passed
+                // Soft assertion failures fail the flow below
+                && self.soft_errors.is_empty()
+            {
+                let _ = std::fs::remove_file(self.context.output_dir.join(&rel_path));
+                self.emitter.emit(TestEvent::Log {
+                    message: format!("{} Flow passed, recording discarded", "🎥".dimmed()),
+                    depth: self.depth,
+                });
             } else {
                 // Check if file exists (optional, driver should ensure)
                 flow_state.video_path = Some(rel_path);
@@ -410,8 +922,8 @@ impl TestExecutor {
             depth: self.depth,
         });
 
-        // Check for soft errors
-        if !self.soft_errors.is_empty() {
+        // Check for soft errors; an assertAll block leaves them to the flow it runs in
+        if !self.soft_errors.is_empty() && self.collect_failures != Some(self.depth) {
             let error_msg = format!(
                 "Flow completed with {} soft assertion failures:\n{}",
                 self.soft_errors.len(),
@@ -439,7 +951,10 @@ impl TestExecutor {
             anyhow::bail!("Flow failed: {}", flow_name);
         }
 
-        Ok(())
+        match signal {
+            Some(s) => Err(s.into()),
+            None => Ok(()),
+        }
     }
 
     /// Handle assertion result with soft mode support
@@ -462,6 +977,32 @@ impl TestExecutor {
         }
     }
 
+    /// Saves evidence for a passing assertion that asked for `screenshot: true`.
+    /// A capture failure is only logged so it never fails the assertion itself.
+    async fn capture_assert_screenshot(&mut self) {
+        let index = self.flow_stack.last().map(|f| f.current_index).unwrap_or(0);
+        let filename = format!(
+            "assert_cmd{}_{}_{}.png",
+            index,
+            chrono::Local::now().format("%H%M%S"),
+            &Uuid::new_v4().to_string()[..8]
+        );
+        let path = self.context.output_path(&filename);
+        match self.driver.take_screenshot(&path.to_string_lossy()).await {
+            Ok(_) => {
+                self.emitter.emit(TestEvent::Log {
+                    message: format!("  {} Saved Screenshot: {}", "📸".green(), path.display()),
+                    depth: self.depth,
+                });
+                self.assert_screenshot = Some(path.display().to_string());
+            }
+            Err(e) => self.emitter.emit(TestEvent::Log {
+                message: format!("  {} Failed to take screenshot: {}", "⚠".yellow(), e),
+                depth: self.depth,
+            }),
+        }
+    }
+
     fn resolve_tap_params(
         &self,
         input: &crate::parser::types::TapParamsInput,
@@ -475,9 +1016,12 @@ impl TestExecutor {
                         return p;
                     }
                 }
-                crate::parser::types::TapParams {
-                    text: Some(subst),
-                    ..Default::default()
+                match crate::parser::types::SelectorDsl::desugar(&subst) {
+                    Some(p) => p,
+                    None => crate::parser::types::TapParams {
+                        text: Some(subst),
+                        ..Default::default()
+                    },
                 }
             }
         };
@@ -548,9 +1092,26 @@ impl TestExecutor {
                     if element_params.below.is_some() {
                         params.below = element_params.below;
                     }
+                    if element_params.right_of_id.is_some() {
+                        params.right_of_id = element_params.right_of_id;
+                    }
+                    if element_params.left_of_id.is_some() {
+                        params.left_of_id = element_params.left_of_id;
+                    }
+                    if element_params.above_id.is_some() {
+                        params.above_id = element_params.above_id;
+                    }
+                    if element_params.below_id.is_some() {
+                        params.below_id = element_params.below_id;
+                    }
                     if element_params.scrollable.is_some() {
                         params.scrollable = element_params.scrollable;
                     }
+                    params.only_visible |= element_params.only_visible;
+                    params.fuzzy |= element_params.fuzzy;
+                    if element_params.fuzzy_threshold.is_some() {
+                        params.fuzzy_threshold = element_params.fuzzy_threshold;
+                    }
                 }
             }
         }
@@ -571,9 +1132,12 @@ impl TestExecutor {
                         return p;
                     }
                 }
-                crate::parser::types::AssertParams {
-                    text: Some(subst),
-                    ..Default::default()
+                match crate::parser::types::SelectorDsl::desugar(&subst) {
+                    Some(p) => p,
+                    None => crate::parser::types::AssertParams {
+                        text: Some(subst),
+                        ..Default::default()
+                    },
                 }
             }
         };
@@ -644,9 +1208,26 @@ impl TestExecutor {
                     if params.below.is_none() {
                         params.below = element_params.below;
                     }
+                    if params.right_of_id.is_none() {
+                        params.right_of_id = element_params.right_of_id;
+                    }
+                    if params.left_of_id.is_none() {
+                        params.left_of_id = element_params.left_of_id;
+                    }
+                    if params.above_id.is_none() {
+                        params.above_id = element_params.above_id;
+                    }
+                    if params.below_id.is_none() {
+                        params.below_id = element_params.below_id;
+                    }
                     if params.scrollable.is_none() {
                         params.scrollable = element_params.scrollable;
                     }
+                    params.only_visible |= element_params.only_visible;
+                    params.fuzzy |= element_params.fuzzy;
+                    if element_params.fuzzy_threshold.is_some() {
+                        params.fuzzy_threshold = element_params.fuzzy_threshold;
+                    }
                 }
             }
         }
@@ -654,8 +1235,348 @@ impl TestExecutor {
         params
     }
 
+    /// `optional: true` and the target is not on screen: mark the command skipped.
+    /// Points are always "there" and images are matched by the command itself, so
+    /// neither gets a separate visibility check.
+    async fn skip_if_missing(
+        &mut self,
+        optional: bool,
+        selector: &crate::driver::traits::Selector,
+    ) -> Result<bool> {
+        use crate::driver::traits::Selector;
+
+        let screen_based = matches!(
+            selector.unwrap_on_screen(),
+            Selector::Point { .. } | Selector::Image { .. }
+        );
+        if !optional || screen_based || self.driver.is_visible(selector).await? {
+            return Ok(false);
+        }
+        self.skipped_reason = Some(format!("Optional element not found: {:?}", selector));
+        Ok(true)
+    }
+
+    /// Timeout failure for an element that never showed up, with the driver's
+    /// near misses ("did you mean …?") when it has any
+    async fn not_visible_error(
+        &self,
+        selector: &crate::driver::traits::Selector,
+        timeout: u64,
+    ) -> anyhow::Error {
+        let mut message = format!("Element not visible within {}ms: {:?}", timeout, selector);
+        let near_misses = self.driver.near_misses(selector).await;
+        if !near_misses.is_empty() {
+            message.push_str(&format!(". Did you mean {}?", near_misses.join(", ")));
+        }
+        TesterError::assertion(message).into()
+    }
+
+    /// `scroll: true` fallback: give the element a short moment to appear in place,
+    /// then scroll for it with the `scrollUntilVisible` defaults.
+    async fn scroll_into_view(
+        &self,
+        selector: &crate::driver::traits::Selector,
+        timeout_ms: u64,
+    ) -> Result<bool> {
+        let wait_ms = timeout_ms.min(SCROLL_FALLBACK_WAIT_MS);
+        if self.driver.wait_for_element(selector, wait_ms).await? {
+            return Ok(true);
+        }
+        self.emitter.emit(TestEvent::Log {
+            message: format!(
+                "      📜 Not on screen, scrolling (max_scrolls: {})",
+                SCROLL_FALLBACK_MAX_SCROLLS
+            ),
+            depth: self.depth,
+        });
+        self.driver
+            .scroll_until_visible(selector, SCROLL_FALLBACK_MAX_SCROLLS, None, None)
+            .await
+    }
+
+    /// Selector for a resolved tapOn target (relative shorthands, image region, onlyVisible)
+    fn tap_selector(
+        &self,
+        params: &crate::parser::types::TapParams,
+    ) -> Result<Option<crate::driver::traits::Selector>> {
+        let relative = crate::parser::types::RelativeParams::merge_shorthand(
+            &params.relative,
+            [
+                &params.right_of,
+                &params.left_of,
+                &params.above,
+                &params.below,
+            ],
+            [
+                &params.right_of_id,
+                &params.left_of_id,
+                &params.above_id,
+                &params.below_id,
+            ],
+        );
+
+        let Some(mut selector) = self.build_selector(
+            &params.text,
+            &params.regex,
+            &params.id,
+            &params.description,
+            &relative,
+            &params.css,
+            &params.xpath,
+            &params.placeholder,
+            &params.role,
+            &params.element_type,
+            &params.image,
+            params.index,
+            &params.scrollable,
+            params.exact,
+            &params.ocr,
+        ) else {
+            return Ok(None);
+        };
+
+        // Inject imageRegion for Image selectors
+        if let crate::driver::traits::Selector::Image { ref mut region, .. } = selector {
+            if params.image_region.is_some() {
+                *region = params.image_region.clone();
+            }
+        }
+        if params.fuzzy {
+            selector = selector.into_fuzzy(params.fuzzy_threshold)?;
+        }
+        Ok(Some(selector.on_screen_if(params.only_visible)))
+    }
+
+    /// Selector for a resolved assertion target, including `containsChild`
+    fn assert_selector(
+        &self,
+        params: &crate::parser::types::AssertParams,
+    ) -> Result<Option<crate::driver::traits::Selector>> {
+        let relative = crate::parser::types::RelativeParams::merge_shorthand(
+            &params.relative,
+            [
+                &params.right_of,
+                &params.left_of,
+                &params.above,
+                &params.below,
+            ],
+            [
+                &params.right_of_id,
+                &params.left_of_id,
+                &params.above_id,
+                &params.below_id,
+            ],
+        );
+
+        let Some(mut selector) = self.build_selector(
+            &params.text,
+            &params.regex,
+            &params.id,
+            &params.description,
+            &relative,
+            &params.css,
+            &params.xpath,
+            &params.placeholder,
+            &params.role,
+            &params.element_type,
+            &params.image,
+            params.index,
+            &params.scrollable,
+            false,
+            &params.ocr,
+        ) else {
+            return Ok(None);
+        };
+        if params.fuzzy {
+            if params.contains_child.is_some() {
+                anyhow::bail!("fuzzy can't be combined with containsChild");
+            }
+            selector = selector.into_fuzzy(params.fuzzy_threshold)?;
+        }
+
+        if let Some(child_p) = &params.contains_child {
+            let child_params = &**child_p;
+            let child_sel = self
+                .build_selector(
+                    &child_params.text,
+                    &child_params.regex,
+                    &child_params.id,
+                    &child_params.description,
+                    &child_params.relative,
+                    &child_params.css,
+                    &child_params.xpath,
+                    &child_params.placeholder,
+                    &child_params.role,
+                    &child_params.element_type,
+                    &child_params.image,
+                    child_params.index,
+                    &params.scrollable,
+                    false,
+                    &child_params.ocr,
+                )
+                .ok_or(anyhow::anyhow!("Invalid child selector in containsChild"))?;
+
+            selector = crate::driver::traits::Selector::HasChild {
+                parent: Box::new(selector),
+                child: Box::new(child_sel),
+            };
+        }
+
+        Ok(Some(selector.on_screen_if(params.only_visible)))
+    }
+
+    /// Poll every selector until one is visible; returns the index of the first match
+    async fn wait_for_any(
+        &self,
+        selectors: &[crate::driver::traits::Selector],
+        timeout_ms: u64,
+    ) -> Result<Option<usize>> {
+        let deadline = std::time::Instant::now() + std::time::Duration::from_millis(timeout_ms);
+        loop {
+            for (i, selector) in selectors.iter().enumerate() {
+                if self.driver.is_visible(selector).await.unwrap_or(false) {
+                    return Ok(Some(i));
+                }
+            }
+            if std::time::Instant::now() >= deadline {
+                return Ok(None);
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(300)).await;
+        }
+    }
+
+    /// Assertion side of `anyOf`: passes once any alternative is visible
+    async fn wait_for_any_of(
+        &mut self,
+        alternatives: &[crate::parser::types::AssertParamsInput],
+        timeout_ms: u64,
+    ) -> Result<()> {
+        let mut selectors = Vec::with_capacity(alternatives.len());
+        for alt in alternatives {
+            let selector = self
+                .assert_selector(&self.resolve_assert_params(alt))?
+                .ok_or_else(|| anyhow::anyhow!("Empty selector in anyOf"))?;
+            selectors.push(selector);
+        }
+        match self.wait_for_any(&selectors, timeout_ms).await? {
+            Some(i) => {
+                self.note_any_of_match("anyOf", i, &selectors[i]);
+                Ok(())
+            }
+            None => Err(TesterError::assertion(format!(
+                "None of the anyOf selectors visible within {}ms: {:?}",
+                timeout_ms, selectors
+            ))
+            .into()),
+        }
+    }
+
+    /// Fill the `nl.*` runtime variables read by `${nl.platform}` and friends.
+    /// Values the driver can't report (no screen yet) are left unset.
+    async fn set_flow_builtins(&mut self) {
+        let platform = self.driver.platform_name().to_string();
+        self.context.set_builtin("platform", &platform);
+        if let Some(serial) = self.driver.device_serial() {
+            self.context.set_builtin("deviceSerial", &serial);
+        }
+        if let Some(model) = self.driver.device_model() {
+            self.context.set_builtin("deviceModel", &model);
+        }
+        if let Some(version) = self.driver.os_version() {
+            self.context.set_builtin("osVersion", &version);
+        }
+        if let Some(app_id) = self.context.app_id.clone() {
+            self.context.set_builtin("appId", &app_id);
+        }
+        self.set_screen_builtins().await;
+        self.context
+            .set_builtin("timestamp", &chrono::Utc::now().timestamp().to_string());
+    }
+
+    /// `nl.screenWidth`/`nl.screenHeight` in the current orientation
+    async fn set_screen_builtins(&mut self) {
+        if let Ok((width, height)) = self.driver.get_screen_size().await {
+            self.context.set_builtin("screenWidth", &width.to_string());
+            self.context
+                .set_builtin("screenHeight", &height.to_string());
+        }
+    }
+
+    /// Record which alternative matched in `${nl.anyOfIndex}` (0-based)
+    fn note_any_of_match(
+        &mut self,
+        source: &str,
+        index: usize,
+        selector: &crate::driver::traits::Selector,
+    ) {
+        self.context.set_builtin("anyOfIndex", &index.to_string());
+        self.emitter.emit(TestEvent::Log {
+            message: format!("{}[{}] matched: {:?}", source, index, selector),
+            depth: self.depth,
+        });
+    }
+
+    /// Answer any OS permission dialog when the flow sets `handleSystemDialogs`;
+    /// true when one was answered. Runs before every command on drivers with a
+    /// cheap focus/alert probe, otherwise after an element is not found.
+    async fn dismiss_system_dialogs(&mut self) -> bool {
+        let Some(action) = self.context.system_dialog_action else {
+            return false;
+        };
+        match self.driver.handle_system_dialog(action.as_str()).await {
+            Ok(true) => {
+                self.emitter.emit(TestEvent::Log {
+                    message: format!("{} Answered system dialog: {}", "🛡".cyan(), action.as_str()),
+                    depth: self.depth,
+                });
+                true
+            }
+            Ok(false) => false,
+            Err(e) => {
+                log::debug!("System dialog check failed: {}", e);
+                false
+            }
+        }
+    }
+
+    /// Run one command under the flow deadline; with `--trace`, device calls
+    /// made by the command become Log events
+    async fn run_traced(&mut self, command: &TestCommand) -> Result<()> {
+        let emitter = self.emitter.clone();
+        let depth = self.depth;
+        crate::driver::trace::scope(&emitter, depth, async {
+            match self.flow_deadline {
+                Some((deadline, ms)) => {
+                    self.execute_until(command, deadline)
+                        .await
+                        .unwrap_or_else(|| {
+                            Err(TesterError::Timeout {
+                                operation: "Flow".to_string(),
+                                timeout_ms: ms,
+                            }
+                            .into())
+                        })
+                }
+                None => self.execute_command(command).await,
+            }
+        })
+        .await
+    }
+
     /// Execute a single command
     pub async fn execute_command(&mut self, command: &TestCommand) -> Result<()> {
+        if !self.skip_commands.is_empty() {
+            let kind = command.kind();
+            if self
+                .skip_commands
+                .iter()
+                .any(|s| s.eq_ignore_ascii_case(kind))
+            {
+                self.skipped_reason = Some(format!("Skipped by --skip-command {}", kind));
+                return Ok(());
+            }
+        }
+
         match command {
             TestCommand::LaunchApp(params_input) => {
                 let params_struct = params_input.as_ref().map(|p| p.clone().into_inner());
@@ -736,7 +1657,7 @@ impl TestExecutor {
             TestCommand::Find(params) => {
                 // Serialize the selector part (TapParams) to JSON
                 let json_val = serde_json::to_string(&params.selector)?;
-                self.context.vars.insert(params.name.clone(), json_val);
+                self.context.insert_var(&params.name, json_val)?;
                 Ok(())
             }
 
@@ -749,8 +1670,33 @@ impl TestExecutor {
 
             TestCommand::TapOn(params_input) => {
                 let params = self.resolve_tap_params(params_input);
+                if let Some(alternatives) = &params.any_of {
+                    let selectors = alternatives
+                        .iter()
+                        .map(|alt| {
+                            self.tap_selector(&self.resolve_tap_params(alt))?
+                                .ok_or_else(|| anyhow::anyhow!("Empty selector in tapOn anyOf"))
+                        })
+                        .collect::<Result<Vec<_>>>()?;
+                    let timeout = if params.optional {
+                        0
+                    } else {
+                        self.context.default_timeout_ms
+                    };
+                    match self.wait_for_any(&selectors, timeout).await? {
+                        Some(i) => {
+                            self.note_any_of_match("anyOf", i, &selectors[i]);
+                            self.driver.tap(&selectors[i]).await
+                        }
+                        None if params.optional => {
+                            self.skipped_reason =
+                                Some(format!("Optional element not found: anyOf {:?}", selectors));
+                            Ok(())
+                        }
+                        None => Err(TesterError::element_not_found(selectors).into()),
+                    }
                 // If point is specified, use TapAt
-                if let Some(point_str) = &params.point {
+                } else if let Some(point_str) = &params.point {
                     let parts: Vec<&str> = point_str.split(',').collect();
                     if parts.len() == 2 {
                         // Parse point - supports both absolute "500,1000" and percentage "50%,80%"
@@ -788,73 +1734,26 @@ impl TestExecutor {
                         anyhow::bail!("Invalid point format: {}", point_str);
                     }
                 } else {
-                    // Merge relative aliases
-                    let mut relative = params.relative.clone();
-                    if params.right_of.is_some()
-                        || params.left_of.is_some()
-                        || params.above.is_some()
-                        || params.below.is_some()
-                    {
-                        let mut r = relative.unwrap_or(crate::parser::types::RelativeParams {
-                            right_of: None,
-                            left_of: None,
-                            above: None,
-                            below: None,
-                            max_dist: None,
-                        });
-                        if params.right_of.is_some() {
-                            r.right_of = params.right_of.clone();
-                        }
-                        if params.left_of.is_some() {
-                            r.left_of = params.left_of.clone();
-                        }
-                        if params.above.is_some() {
-                            r.above = params.above.clone();
-                        }
-                        if params.below.is_some() {
-                            r.below = params.below.clone();
-                        }
-                        relative = Some(r);
-                    }
-
-                    let mut selector = self
-                        .build_selector(
-                            &params.text,
-                            &params.regex,
-                            &params.id,
-                            &params.description,
-                            &relative,
-                            &params.css,
-                            &params.xpath,
-                            &params.placeholder,
-                            &params.role,
-                            &params.element_type,
-                            &params.image,
-                            params.index,
-                            &params.scrollable,
-                            params.exact,
-                            &params.ocr,
-                        )
+                    let selector = self
+                        .tap_selector(&params)?
                         .ok_or_else(|| anyhow::anyhow!("No selector specified for tapOn"))?;
 
-                    // Inject imageRegion for Image selectors
-                    if let crate::driver::traits::Selector::Image { ref mut region, .. } = selector
-                    {
-                        if params.image_region.is_some() {
-                            *region = params.image_region.clone();
-                        }
-                    }
-
-                    if params.optional {
-                        if self.driver.is_visible(&selector).await? {
+                    if params.scroll {
+                        let timeout = self.context.default_timeout_ms;
+                        if self.scroll_into_view(&selector, timeout).await? {
                             self.driver.tap(&selector).await
+                        } else if params.optional {
+                            self.skipped_reason =
+                                Some(format!("Optional element not found: {:?}", selector));
+                            Ok(())
                         } else {
-                            println!(
-                                "  {} Optional element not found, skipping tap: {:?}",
-                                "ℹ".blue(),
-                                selector
-                            );
+                            Err(TesterError::element_not_found(selector).into())
+                        }
+                    } else if params.optional {
+                        if self.skip_if_missing(true, &selector).await? {
                             Ok(())
+                        } else {
+                            self.driver.tap(&selector).await
                         }
                     } else {
                         let timeout = self.context.default_timeout_ms;
@@ -886,7 +1785,11 @@ impl TestExecutor {
                         params.exact,
                         &params.ocr,
                     )
-                    .ok_or_else(|| anyhow::anyhow!("No selector specified for longPressOn"))?;
+                    .ok_or_else(|| anyhow::anyhow!("No selector specified for longPressOn"))?
+                    .on_screen_if(params.only_visible);
+                if self.skip_if_missing(params.optional, &selector).await? {
+                    return Ok(());
+                }
                 let timeout = self.context.default_timeout_ms;
                 if !matches!(selector, crate::driver::traits::Selector::Point { .. }) {
                     let _ = self.driver.wait_for_element(&selector, timeout).await;
@@ -914,7 +1817,11 @@ impl TestExecutor {
                         params.exact,
                         &params.ocr,
                     )
-                    .ok_or_else(|| anyhow::anyhow!("No selector specified for doubleTapOn"))?;
+                    .ok_or_else(|| anyhow::anyhow!("No selector specified for doubleTapOn"))?
+                    .on_screen_if(params.only_visible);
+                if self.skip_if_missing(params.optional, &selector).await? {
+                    return Ok(());
+                }
                 let timeout = self.context.default_timeout_ms;
                 if !matches!(selector, crate::driver::traits::Selector::Point { .. }) {
                     let _ = self.driver.wait_for_element(&selector, timeout).await;
@@ -942,6 +1849,9 @@ impl TestExecutor {
                         &params.ocr,
                     )
                     .ok_or_else(|| anyhow::anyhow!("No selector specified for rightClick"))?;
+                if self.skip_if_missing(params.optional, &selector).await? {
+                    return Ok(());
+                }
                 let timeout = self.context.default_timeout_ms;
                 if !matches!(selector, crate::driver::traits::Selector::Point { .. }) {
                     let _ = self.driver.wait_for_element(&selector, timeout).await;
@@ -953,6 +1863,16 @@ impl TestExecutor {
                 let text = params_input.text();
                 let unicode = params_input.unicode();
                 let substituted = self.context.substitute_vars(text);
+                if params_input.clear_first() {
+                    // Erasing clears whatever has focus; with nothing focused the
+                    // text would land nowhere, so fail instead of guessing
+                    if !self.driver.has_focused_field().await? {
+                        anyhow::bail!(
+                            "inputText clearFirst: no field is focused; tap the field first"
+                        );
+                    }
+                    self.driver.erase_text(None).await?;
+                }
                 self.driver.input_text(&substituted, unicode).await
             }
 
@@ -981,188 +1901,65 @@ impl TestExecutor {
             TestCommand::SwipeDown => {
                 use crate::driver::traits::SwipeDirection;
                 self.driver.swipe(SwipeDirection::Down, None, None).await
-            }
-
-            TestCommand::AssertVisible(params_input) => {
-                let params = self.resolve_assert_params(params_input);
-                let verification_result = async {
-                    // Merge relative aliases
-                    let mut relative = params.relative.clone();
-                    if params.right_of.is_some()
-                        || params.left_of.is_some()
-                        || params.above.is_some()
-                        || params.below.is_some()
-                    {
-                        let mut r = relative.unwrap_or(crate::parser::types::RelativeParams {
-                            right_of: None,
-                            left_of: None,
-                            above: None,
-                            below: None,
-                            max_dist: None,
-                        });
-                        if params.right_of.is_some() {
-                            r.right_of = params.right_of.clone();
-                        }
-                        if params.left_of.is_some() {
-                            r.left_of = params.left_of.clone();
-                        }
-                        if params.above.is_some() {
-                            r.above = params.above.clone();
-                        }
-                        if params.below.is_some() {
-                            r.below = params.below.clone();
-                        }
-                        relative = Some(r);
-                    }
-
-                    let mut selector = self
-                        .build_selector(
-                            &params.text,
-                            &params.regex,
-                            &params.id,
-                            &params.description,
-                            &relative,
-                            &params.css,
-                            &params.xpath,
-                            &params.placeholder,
-                            &params.role,
-                            &params.element_type,
-                            &params.image,
-                            params.index,
-                            &params.scrollable,
-                            false,
-                            &params.ocr,
-                        )
-                        .ok_or_else(|| {
-                            anyhow::anyhow!("No selector specified for assertVisible")
-                        })?;
-
-                    // Handle contains_child
-                    if let Some(child_p) = &params.contains_child {
-                        let child_params = &**child_p;
-                        let child_sel = self
-                            .build_selector(
-                                &child_params.text,
-                                &child_params.regex,
-                                &child_params.id,
-                                &child_params.description,
-                                &child_params.relative,
-                                &child_params.css,
-                                &child_params.xpath,
-                                &child_params.placeholder,
-                                &child_params.role,
-                                &child_params.element_type,
-                                &child_params.image,
-                                child_params.index,
-                                &params.scrollable,
-                                false,
-                                &child_params.ocr,
-                            )
-                            .ok_or(anyhow::anyhow!("Invalid child selector in containsChild"))?;
-
-                        selector = crate::driver::traits::Selector::HasChild {
-                            parent: Box::new(selector),
-                            child: Box::new(child_sel),
-                        };
-                    }
-
-                    let timeout = params.timeout.unwrap_or(5000);
-                    let visible = self.driver.wait_for_element(&selector, timeout).await?;
-
-                    if visible {
-                        Ok(())
-                    } else {
-                        anyhow::bail!("Element not visible within {}ms: {:?}", timeout, selector)
-                    }
-                }
-                .await;
-                self.handle_assertion(verification_result, params.soft)
-            }
-
-            TestCommand::WaitUntilVisible(params_input) => {
-                let params = self.resolve_assert_params(params_input);
-                // Identical logic to AssertVisible but semantically different
-                // It's a wait command, but can be treated as an assertion that the element appears
-                let verification_result = async {
-                    // Merge relative aliases
-                    let mut relative = params.relative.clone();
-                    if params.right_of.is_some()
-                        || params.left_of.is_some()
-                        || params.above.is_some()
-                        || params.below.is_some()
-                    {
-                        let mut r = relative.unwrap_or(crate::parser::types::RelativeParams {
-                            right_of: None,
-                            left_of: None,
-                            above: None,
-                            below: None,
-                            max_dist: None,
-                        });
-                        if params.right_of.is_some() {
-                            r.right_of = params.right_of.clone();
-                        }
-                        if params.left_of.is_some() {
-                            r.left_of = params.left_of.clone();
-                        }
-                        if params.above.is_some() {
-                            r.above = params.above.clone();
-                        }
-                        if params.below.is_some() {
-                            r.below = params.below.clone();
-                        }
-                        relative = Some(r);
-                    }
-
-                    let mut selector = self
-                        .build_selector(
-                            &params.text,
-                            &params.regex,
-                            &params.id,
-                            &params.description,
-                            &relative,
-                            &params.css,
-                            &params.xpath,
-                            &params.placeholder,
-                            &params.role,
-                            &params.element_type,
-                            &params.image,
-                            params.index,
-                            &params.scrollable,
-                            false,
-                            &params.ocr,
-                        )
-                        .ok_or_else(|| {
-                            anyhow::anyhow!("No selector specified for waitUntilVisible")
-                        })?;
-
-                    // Handle contains_child
-                    if let Some(child_p) = &params.contains_child {
-                        let child_params = &**child_p;
-                        let child_sel = self
-                            .build_selector(
-                                &child_params.text,
-                                &child_params.regex,
-                                &child_params.id,
-                                &child_params.description,
-                                &child_params.relative,
-                                &child_params.css,
-                                &child_params.xpath,
-                                &child_params.placeholder,
-                                &child_params.role,
-                                &child_params.element_type,
-                                &child_params.image,
-                                child_params.index,
-                                &params.scrollable,
-                                false,
-                                &child_params.ocr,
-                            )
-                            .ok_or(anyhow::anyhow!("Invalid child selector in containsChild"))?;
+            }
 
-                        selector = crate::driver::traits::Selector::HasChild {
-                            parent: Box::new(selector),
-                            child: Box::new(child_sel),
-                        };
+            TestCommand::AssertVisible(params_input) => {
+                let params = self.resolve_assert_params(params_input);
+                if let Some(alternatives) = &params.any_of {
+                    let timeout = params.timeout.unwrap_or(5000);
+                    let result = self.wait_for_any_of(alternatives, timeout).await;
+                    if params.screenshot && result.is_ok() {
+                        self.capture_assert_screenshot().await;
+                    }
+                    return self.handle_assertion(result, params.soft);
+                }
+                let verification_result = async {
+                    let selector = self.assert_selector(&params)?.ok_or_else(|| {
+                        anyhow::anyhow!("No selector specified for assertVisible")
+                    })?;
+
+                    let timeout = params.timeout.unwrap_or(5000);
+                    let visible = if params.scroll {
+                        self.scroll_into_view(&selector, timeout).await?
+                    } else {
+                        self.driver.wait_for_element(&selector, timeout).await?
+                    };
+
+                    if visible {
+                        Ok(())
+                    } else if params.scroll {
+                        Err(TesterError::assertion(format!(
+                            "Element not visible after scrolling {} times: {:?}",
+                            SCROLL_FALLBACK_MAX_SCROLLS, selector
+                        ))
+                        .into())
+                    } else {
+                        Err(self.not_visible_error(&selector, timeout).await)
+                    }
+                }
+                .await;
+                if params.screenshot && verification_result.is_ok() {
+                    self.capture_assert_screenshot().await;
+                }
+                self.handle_assertion(verification_result, params.soft)
+            }
+
+            TestCommand::WaitUntilVisible(params_input) => {
+                let params = self.resolve_assert_params(params_input);
+                if let Some(alternatives) = &params.any_of {
+                    let timeout = params.timeout.unwrap_or(self.context.default_timeout_ms);
+                    let result = self.wait_for_any_of(alternatives, timeout).await;
+                    if params.screenshot && result.is_ok() {
+                        self.capture_assert_screenshot().await;
                     }
+                    return self.handle_assertion(result, params.soft);
+                }
+                // Identical logic to AssertVisible but semantically different
+                // It's a wait command, but can be treated as an assertion that the element appears
+                let verification_result = async {
+                    let selector = self.assert_selector(&params)?.ok_or_else(|| {
+                        anyhow::anyhow!("No selector specified for waitUntilVisible")
+                    })?;
 
                     // Default timeout for wait is usually higher or same as assertion?
                     // Using context default timeout (default: 10s)
@@ -1172,10 +1969,13 @@ impl TestExecutor {
                     if visible {
                         Ok(())
                     } else {
-                        anyhow::bail!("Element not visible within {}ms: {:?}", timeout, selector)
+                        Err(self.not_visible_error(&selector, timeout).await)
                     }
                 }
                 .await;
+                if params.screenshot && verification_result.is_ok() {
+                    self.capture_assert_screenshot().await;
+                }
                 // Wait command is effectively a hard assertion (it fails if not found)
                 // But we support soft mode if user really wants to continue
                 self.handle_assertion(verification_result, params.soft)
@@ -1185,33 +1985,21 @@ impl TestExecutor {
                 let params = self.resolve_assert_params(params_input);
                 let verification_result = async {
                     // Merge relative aliases
-                    let mut relative = params.relative.clone();
-                    if params.right_of.is_some()
-                        || params.left_of.is_some()
-                        || params.above.is_some()
-                        || params.below.is_some()
-                    {
-                        let mut r = relative.unwrap_or(crate::parser::types::RelativeParams {
-                            right_of: None,
-                            left_of: None,
-                            above: None,
-                            below: None,
-                            max_dist: None,
-                        });
-                        if params.right_of.is_some() {
-                            r.right_of = params.right_of.clone();
-                        }
-                        if params.left_of.is_some() {
-                            r.left_of = params.left_of.clone();
-                        }
-                        if params.above.is_some() {
-                            r.above = params.above.clone();
-                        }
-                        if params.below.is_some() {
-                            r.below = params.below.clone();
-                        }
-                        relative = Some(r);
-                    }
+                    let relative = crate::parser::types::RelativeParams::merge_shorthand(
+                        &params.relative,
+                        [
+                            &params.right_of,
+                            &params.left_of,
+                            &params.above,
+                            &params.below,
+                        ],
+                        [
+                            &params.right_of_id,
+                            &params.left_of_id,
+                            &params.above_id,
+                            &params.below_id,
+                        ],
+                    );
 
                     let mut selector = self
                         .build_selector(
@@ -1262,15 +2050,24 @@ impl TestExecutor {
                         };
                     }
 
+                    let selector = selector.on_screen_if(params.only_visible);
+
                     let visible = self.driver.is_visible(&selector).await?;
 
                     if !visible {
                         Ok(())
                     } else {
-                        anyhow::bail!("Element is visible but should not be: {:?}", selector)
+                        Err(TesterError::assertion(format!(
+                            "Element is visible but should not be: {:?}",
+                            selector
+                        ))
+                        .into())
                     }
                 }
                 .await;
+                if params.screenshot && verification_result.is_ok() {
+                    self.capture_assert_screenshot().await;
+                }
                 self.handle_assertion(verification_result, params.soft)
             }
 
@@ -1278,33 +2075,21 @@ impl TestExecutor {
                 let params = self.resolve_assert_params(params_input);
 
                 // Merge relative aliases
-                let mut relative = params.relative.clone();
-                if params.right_of.is_some()
-                    || params.left_of.is_some()
-                    || params.above.is_some()
-                    || params.below.is_some()
-                {
-                    let mut r = relative.unwrap_or(crate::parser::types::RelativeParams {
-                        right_of: None,
-                        left_of: None,
-                        above: None,
-                        below: None,
-                        max_dist: None,
-                    });
-                    if params.right_of.is_some() {
-                        r.right_of = params.right_of.clone();
-                    }
-                    if params.left_of.is_some() {
-                        r.left_of = params.left_of.clone();
-                    }
-                    if params.above.is_some() {
-                        r.above = params.above.clone();
-                    }
-                    if params.below.is_some() {
-                        r.below = params.below.clone();
-                    }
-                    relative = Some(r);
-                }
+                let relative = crate::parser::types::RelativeParams::merge_shorthand(
+                    &params.relative,
+                    [
+                        &params.right_of,
+                        &params.left_of,
+                        &params.above,
+                        &params.below,
+                    ],
+                    [
+                        &params.right_of_id,
+                        &params.left_of_id,
+                        &params.above_id,
+                        &params.below_id,
+                    ],
+                );
 
                 let mut selector = self
                     .build_selector(
@@ -1355,10 +2140,15 @@ impl TestExecutor {
                     };
                 }
 
+                let selector = selector.on_screen_if(params.only_visible);
+
                 let timeout = params.timeout.unwrap_or(self.context.default_timeout_ms);
                 let ok = self.driver.wait_for_absence(&selector, timeout).await?;
 
                 if ok {
+                    if params.screenshot {
+                        self.capture_assert_screenshot().await;
+                    }
                     Ok(())
                 } else {
                     anyhow::bail!(
@@ -1383,7 +2173,7 @@ impl TestExecutor {
 
             TestCommand::TakeScreenshot(params_input) => {
                 let params = params_input.clone().into_inner();
-                let path = params.path.clone();
+                let path = self.context.substitute_vars(&params.path);
                 let output_path = self.context.output_path(&path);
                 self.driver
                     .take_screenshot(output_path.to_str().unwrap())
@@ -1396,16 +2186,23 @@ impl TestExecutor {
                 } else {
                     format!("{}.png", name)
                 };
-                let reference_path = self
-                    .context
-                    .resolve_path(&format!("screenshots/{}", filename));
-
-                if !reference_path.exists() {
+                let mut device_dirs = Vec::new();
+                if let Some(model) = self.driver.device_model() {
+                    device_dirs.push(crate::runner::context::safe_dir_name(&model));
+                }
+                if let Ok((w, h)) = self.driver.get_screen_size().await {
+                    device_dirs.push(format!("{}x{}", w, h));
+                }
+                device_dirs.push(self.driver.platform_name().to_string());
+                let candidates = self.context.baseline_candidates(&filename, &device_dirs);
+                let Some(reference_path) = candidates.iter().find(|p| p.exists()).cloned() else {
+                    let searched: Vec<String> =
+                        candidates.iter().map(|p| p.display().to_string()).collect();
                     anyhow::bail!(
-                        "Reference screenshot not found: {}",
-                        reference_path.display()
+                        "Reference screenshot not found. Searched: {}",
+                        searched.join(", ")
                     );
-                }
+                };
 
                 let diff = self.driver.compare_screenshot(&reference_path, 1.0).await?;
                 if diff > 1.0 {
@@ -1413,9 +2210,10 @@ impl TestExecutor {
                     anyhow::bail!("Visual regression detected! Difference: {:.2}%", diff);
                 } else {
                     println!(
-                        "  {} Visual check passed (diff: {:.2}%)",
+                        "  {} Visual check passed (diff: {:.2}%, baseline: {})",
                         "✨".green(),
-                        diff
+                        diff,
+                        reference_path.display()
                     );
                     Ok(())
                 }
@@ -1423,9 +2221,18 @@ impl TestExecutor {
 
             TestCommand::StartRecording(params_input) => {
                 let params = params_input.clone().into_inner();
-                let path = self.context.output_path(&params.path);
+                let path = self
+                    .context
+                    .output_path(&self.context.substitute_vars(&params.path));
+                let bitrate = params.bitrate.as_ref().map(|b| b.to_string());
+                let options = RecordingOptions::parse(
+                    bitrate.as_deref(),
+                    params.size.as_deref(),
+                    params.time_limit,
+                )?
+                .or(&self.recording_options);
                 self.driver
-                    .start_recording(&path.display().to_string())
+                    .start_recording_with_options(&path.display().to_string(), &options)
                     .await
             }
 
@@ -1469,7 +2276,7 @@ impl TestExecutor {
                 if let Some(cmds) = commands_to_run {
                     // Merge variables
                     if let Some(ref vars) = params.vars {
-                        self.context.merge_vars(vars);
+                        self.context.merge_vars(vars)?;
                     }
 
                     self.depth += 1;
@@ -1482,6 +2289,12 @@ impl TestExecutor {
                     self.depth -= 1;
 
                     if let Err(e) = res {
+                        // stopFlow ends just this subflow; break/continue reach the enclosing loop
+                        match ControlSignal::of(&e) {
+                            Some(ControlSignal::StopFlow) => return Ok(()),
+                            Some(_) => return Err(e),
+                            None => {}
+                        }
                         if params.optional.unwrap_or(false) {
                             self.emitter.emit(TestEvent::Log {
                                 message: format!(
@@ -1508,6 +2321,13 @@ impl TestExecutor {
 
             // InputAt - input text at element by type and index
             TestCommand::InputAt(params) => {
+                let selector = crate::driver::traits::Selector::Type(
+                    params.element_type.clone(),
+                    params.index as i32,
+                );
+                if self.skip_if_missing(params.optional, &selector).await? {
+                    return Ok(());
+                }
                 let text = self.context.substitute_vars(&params.text);
                 self.driver
                     .input_by_type_index(&params.element_type, params.index, &text)
@@ -1516,23 +2336,49 @@ impl TestExecutor {
 
             // SetVar - set a variable
             TestCommand::SetVar(params) => {
-                self.context.set_var(&params.name, &params.value);
+                if !params.eval {
+                    return self.context.set_var(&params.name, &params.value);
+                }
+                // Before evaluating, so a reserved name fails without running the script
+                check_var_name(&params.name)?;
+
+                use super::js_engine::JsEngine;
+                let mut engine = JsEngine::new();
+                engine.set_vars(&self.context.vars);
+                engine.set_builtins(&self.context.builtins);
+                engine.set_vars(&self.context.env);
+
+                let expression = self.context.substitute_vars(&params.value);
+                let value = engine
+                    .eval(&expression)
+                    .map_err(|e| anyhow::anyhow!("setVar {} eval error: {}", params.name, e))?;
+                self.emitter.emit(TestEvent::Log {
+                    message: format!("{} setVar: {} = {}", "📝".blue(), params.name, value),
+                    depth: self.depth,
+                });
+                // The result is final; a `${...}` in it is data, not a reference
+                self.context.vars.insert(params.name.clone(), value);
                 Ok(())
             }
 
             // AssertVar - assert variable has expected value
             TestCommand::AssertVar(params) => {
-                let expected = self.context.substitute_vars(&params.expected);
+                let expected = self.context.substitute_vars(&params.expected.to_string());
                 let actual = self.context.get_var(&params.name).unwrap_or_default();
-                if actual == expected {
+                if params.operator.compare(&actual, &expected)? {
                     Ok(())
-                } else {
-                    anyhow::bail!(
+                } else if params.operator == crate::parser::types::VarOperator::Eq {
+                    Err(TesterError::assertion(format!(
                         "Variable {} expected '{}', got '{}'",
-                        params.name,
-                        expected,
-                        actual
-                    )
+                        params.name, expected, actual
+                    ))
+                    .into())
+                } else {
+                    Err(TesterError::assertion(format!(
+                        "Variable {} expected {} '{}', got '{}'",
+                        params.name, params.operator, expected, actual
+                    ))
+                    .into())
                 }
             }
 
@@ -1566,7 +2412,13 @@ impl TestExecutor {
                     let res =
                         Box::pin(self.run_commands_set(&params.commands, &label, "repeat")).await;
                     self.depth -= 1;
-                    res?;
+                    if let Err(e) = res {
+                        match ControlSignal::of(&e) {
+                            Some(ControlSignal::Break) => break,
+                            Some(ControlSignal::Continue) => {}
+                            _ => return Err(e),
+                        }
+                    }
 
                     // Safety break for extremely large repeats
                     if iteration > 1000 {
@@ -1576,6 +2428,92 @@ impl TestExecutor {
                 Ok(())
             }
 
+            TestCommand::Break => Err(ControlSignal::Break.into()),
+            TestCommand::Continue => Err(ControlSignal::Continue.into()),
+            TestCommand::StopFlow => Err(ControlSignal::StopFlow.into()),
+
+            TestCommand::Timed(timed) => {
+                let deadline = tokio::time::Instant::now()
+                    + std::time::Duration::from_millis(timed.timeout_ms);
+                match Box::pin(self.execute_until(&timed.command, deadline)).await {
+                    Some(result) => result,
+                    None => Err(TesterError::Timeout {
+                        operation: timed.command.display_name(),
+                        timeout_ms: timed.timeout_ms,
+                    }
+                    .into()),
+                }
+            }
+
+            TestCommand::Plugin(cmd) => {
+                let plugin = crate::plugin::find(&cmd.name)
+                    .ok_or_else(|| anyhow::anyhow!("No plugin registered for `{}`", cmd.name))?;
+                plugin
+                    .execute(&mut self.context, self.driver.as_ref(), &cmd.params)
+                    .await
+            }
+
+            // ForEach - run commands once per list item or data row
+            TestCommand::ForEach(params) => {
+                let mut items = params.items.clone();
+                if let Some(ref data_file) = params.data_file {
+                    let data_path = self
+                        .context
+                        .resolve_path(&self.context.substitute_vars(data_file));
+                    items.extend(load_for_each_items(&data_path)?);
+                }
+
+                let commands = match params.flow {
+                    Some(ref path) => {
+                        let flow_path = self
+                            .context
+                            .resolve_path(&self.context.substitute_vars(path));
+                        parse_test_file(&flow_path)?.commands
+                    }
+                    None => params.commands.clone(),
+                };
+                let flow_path = params.flow.clone().unwrap_or_else(|| "forEach".to_string());
+
+                check_var_name(&params.var_name)?;
+                // Restore the loop variable afterwards so it doesn't leak into later commands
+                self.enter_loop_var(&params.var_name);
+                let mut result = Ok(());
+                for (i, item) in items.iter().enumerate() {
+                    let value = match item {
+                        serde_json::Value::String(s) => s.clone(),
+                        other => other.to_string(),
+                    };
+                    self.context
+                        .vars
+                        .insert(params.var_name.clone(), value.clone());
+
+                    let label = format!("{} #{} ({})", params.var_name, i + 1, value);
+                    self.depth += 1;
+                    let res = Box::pin(self.run_commands_set(&commands, &label, &flow_path)).await;
+                    self.depth -= 1;
+                    if let Err(e) = res {
+                        match ControlSignal::of(&e) {
+                            Some(ControlSignal::Break) => break,
+                            Some(ControlSignal::Continue) => continue,
+                            // stopFlow ends the item's `flow` file like runFlow; inline
+                            // commands belong to the enclosing flow, which it stops
+                            Some(ControlSignal::StopFlow) if params.flow.is_some() => continue,
+                            Some(ControlSignal::StopFlow) => {
+                                result = Err(e);
+                                break;
+                            }
+                            None => {}
+                        }
+                        result =
+                            Err(e.context(format!("forEach failed at item {} ({})", i + 1, value)));
+                        break;
+                    }
+                }
+
+                self.leave_loop_var();
+                result
+            }
+
             // Retry - retry commands on failure
             TestCommand::Retry(params) => {
                 let mut last_error = None;
@@ -1588,6 +2526,7 @@ impl TestExecutor {
 
                     match res {
                         Ok(()) => return Ok(()),
+                        Err(e) if ControlSignal::of(&e).is_some() => return Err(e),
                         Err(e) => {
                             last_error = Some(e);
                             if attempt < params.max_retries - 1 {
@@ -1610,6 +2549,75 @@ impl TestExecutor {
                 )
             }
 
+            TestCommand::AssertAll(params) => {
+                // Failures are scoped to the block; an enclosing block's and the flow's
+                // earlier soft failures are kept aside. Soft member assertions are
+                // added to the flow's soft failures afterwards.
+                let label = params.label.as_deref().unwrap_or("assertAll");
+                self.depth += 1;
+                self.enter_assert_all();
+                let res =
+                    Box::pin(self.run_commands_set(&params.assertions, label, "assertAll")).await;
+                let failures = self.leave_assert_all();
+                self.depth -= 1;
+
+                if failures.is_empty() {
+                    return res;
+                }
+                let message = format!(
+                    "{} of {} assertions failed:\n{}",
+                    failures.len(),
+                    params.assertions.len(),
+                    failures
+                        .iter()
+                        .map(|failure| format!("  - {}", failure))
+                        .collect::<Vec<_>>()
+                        .join("\n")
+                );
+                self.handle_assertion(Err(TesterError::assertion(message).into()), params.soft)
+            }
+
+            TestCommand::WaitForAny(params) => {
+                let mut selectors = Vec::with_capacity(params.options.len());
+                for option in &params.options {
+                    let selector = self
+                        .assert_selector(&self.resolve_assert_params(&option.when))?
+                        .ok_or_else(|| anyhow::anyhow!("Empty `when` selector in waitForAny"))?;
+                    selectors.push(selector);
+                }
+
+                let timeout = params.timeout.unwrap_or(self.context.default_timeout_ms);
+                let Some(i) = self.wait_for_any(&selectors, timeout).await? else {
+                    if params.optional {
+                        self.emitter.emit(TestEvent::Log {
+                            message: format!(
+                                "{} No waitForAny option appeared within {}ms, skipping",
+                                "ℹ".blue(),
+                                timeout
+                            ),
+                            depth: self.depth,
+                        });
+                        return Ok(());
+                    }
+                    anyhow::bail!(
+                        "None of the waitForAny options visible within {}ms: {:?}",
+                        timeout,
+                        selectors
+                    );
+                };
+                self.note_any_of_match("waitForAny", i, &selectors[i]);
+
+                let branch = &params.options[i].then;
+                if branch.is_empty() {
+                    return Ok(());
+                }
+                let label = format!("waitForAny option #{}", i + 1);
+                self.depth += 1;
+                let res = Box::pin(self.run_commands_set(branch, &label, "waitForAny")).await;
+                self.depth -= 1;
+                res
+            }
+
             // ScrollUntilVisible
             TestCommand::ScrollUntilVisible(params_input) => {
                 use crate::driver::traits::SwipeDirection;
@@ -1732,7 +2740,7 @@ impl TestExecutor {
                         println!("      ✅ Blind scroll completed (no target specified)");
                         Ok(())
                     } else {
-                        anyhow::bail!("Element not found after scrolling: {:?}", selector)
+                        Err(TesterError::element_not_found(selector).into())
                     }
                 }
             }
@@ -1760,7 +2768,11 @@ impl TestExecutor {
                     });
 
                     for cmd in cmds {
-                        Box::pin(self.execute_command(&cmd)).await?;
+                        let res = Box::pin(self.execute_command(&cmd)).await;
+                        // A nested skip or screenshot belongs to that command, not the block
+                        self.skipped_reason = None;
+                        self.assert_screenshot = None;
+                        res?;
                     }
                 }
                 Ok(())
@@ -1801,7 +2813,7 @@ impl TestExecutor {
                     }
                     _ => "unknown".to_string(),
                 };
-                self.context.set_var(&params.name, &value);
+                self.context.set_var(&params.name, &value)?;
                 Ok(())
             }
 
@@ -1821,12 +2833,13 @@ impl TestExecutor {
 
                         // Set current context variables
                         engine.set_vars(&self.context.vars);
+                        engine.set_builtins(&self.context.builtins);
 
                         // Execute script
                         match engine.execute_script_with_output(&script_content) {
                             Ok(output_json) => {
                                 // Update 'output' variable in context
-                                self.context.set_var("output", &output_json);
+                                self.context.set_var("output", &output_json)?;
                                 self.emitter.emit(TestEvent::Log {
                                     message: format!(
                                         "{} Executed JS script: {}",
@@ -1864,7 +2877,7 @@ impl TestExecutor {
 
                 if let Some(var_name) = &params.save_output {
                     let out_str = String::from_utf8_lossy(&output.stdout).trim().to_string();
-                    self.context.set_var(var_name, &out_str);
+                    self.context.set_var(var_name, &out_str)?;
                 }
                 Ok(())
             }
@@ -1941,7 +2954,7 @@ impl TestExecutor {
                             }
                         };
 
-                        self.context.set_var(var_name, &val_to_save);
+                        self.context.set_var(var_name, &val_to_save)?;
                     }
                 }
                 Ok(())
@@ -1970,6 +2983,10 @@ impl TestExecutor {
                     }
                 }
 
+                if let Some(step) = p.interpolate {
+                    points = crate::parser::gps::interpolate_route(&points, step)?;
+                }
+
                 let interval_ms = p.interval_ms.unwrap_or(1000);
 
                 // Apply fixed altitude if specified
@@ -1986,13 +3003,13 @@ impl TestExecutor {
                     file_path.file_name().unwrap_or_default().to_string_lossy()
                 );
 
+                let speed_profile = p.speed_profile();
                 self.driver
                     .start_mock_location(
                         p.name,
                         points,
                         p.speed,
-                        p.speed_mode,
-                        p.speed_noise,
+                        speed_profile,
                         interval_ms,
                         p.loop_route,
                     )
@@ -2055,43 +3072,374 @@ impl TestExecutor {
             TestCommand::RotateScreen(params_input) => {
                 let params = params_input.clone().into_inner();
                 // Deprecated: use SetOrientation
-                self.driver.rotate_screen(&params.mode).await
+                self.driver.rotate_screen(&params.mode).await?;
+                self.set_screen_builtins().await;
+                Ok(())
             }
 
             TestCommand::SetOrientation(params) => {
-                self.driver.set_orientation(params.mode.clone()).await
+                self.driver.set_orientation(params.mode.clone()).await?;
+                self.set_screen_builtins().await;
+                Ok(())
+            }
+
+            TestCommand::SetNetwork(params) => {
+                self.driver
+                    .set_network_connection(params.wifi, params.data)
+                    .await
+            }
+
+            TestCommand::SetWifi(on) => self.driver.set_network_connection(Some(*on), None).await,
+
+            TestCommand::SetMobileData(on) => {
+                self.driver.set_network_connection(None, Some(*on)).await
+            }
+
+            TestCommand::SetBluetooth(on) => self.driver.set_bluetooth(*on).await,
+
+            TestCommand::ToggleAirplaneMode => self.driver.toggle_airplane_mode().await,
+
+            TestCommand::OpenNotifications => self.driver.open_notifications().await,
+
+            TestCommand::OpenQuickSettings => self.driver.open_quick_settings().await,
+
+            TestCommand::SetVolume(level) => self.driver.set_volume(*level).await,
+
+            TestCommand::LockDevice => self.driver.lock_device().await,
+
+            TestCommand::UnlockDevice => self.driver.unlock_device().await,
+
+            TestCommand::InstallApp(params) => {
+                let resolved_path = self.context.resolve_path(&params.path);
+                let params = crate::parser::types::InstallAppParams {
+                    path: resolved_path.to_string_lossy().to_string(),
+                    ..params.clone()
+                };
+                self.driver.install_app_with_options(&params).await
+            }
+
+            TestCommand::UninstallApp(pkg) => self.driver.uninstall_app(pkg).await,
+
+            TestCommand::SnapshotAppData(params) | TestCommand::RestoreAppData(params) => {
+                let app_id = params
+                    .app
+                    .as_ref()
+                    .or(self.context.app_id.as_ref())
+                    .map(|a| self.context.substitute_vars(a))
+                    .ok_or_else(|| anyhow::anyhow!("App data snapshots require appId"))?;
+                let name = self.context.substitute_vars(&params.name);
+                let dir = self
+                    .context
+                    .output_dir
+                    .join("snapshots")
+                    .join(app_id.replace(['/', '\\'], "_"))
+                    .join(&name);
+
+                if matches!(command, TestCommand::SnapshotAppData(_)) {
+                    self.driver.snapshot_app_data(&app_id, &dir).await?;
+                    self.emitter.emit(TestEvent::Log {
+                        message: format!("{} Saved app data snapshot '{}'", "💾".cyan(), name),
+                        depth: self.depth,
+                    });
+                } else {
+                    self.driver.restore_app_data(&app_id, &dir).await?;
+                    self.emitter.emit(TestEvent::Log {
+                        message: format!("{} Restored app data snapshot '{}'", "💾".cyan(), name),
+                        depth: self.depth,
+                    });
+                }
+                Ok(())
+            }
+
+            TestCommand::AssertNotification(params) => {
+                let title = params
+                    .title
+                    .as_ref()
+                    .map(|t| self.context.substitute_vars(t));
+                let text = params
+                    .text
+                    .as_ref()
+                    .map(|t| self.context.substitute_vars(t));
+                let app = params.app.as_ref().map(|a| self.context.substitute_vars(a));
+                let Some(label) = title.clone().or_else(|| text.clone()) else {
+                    anyhow::bail!("assertNotification requires 'title' and/or 'text'");
+                };
+                let timeout = params.timeout.unwrap_or(self.context.default_timeout_ms);
+
+                let contains = |haystack: &str, needle: &Option<String>| {
+                    needle
+                        .as_ref()
+                        .is_none_or(|n| haystack.to_lowercase().contains(&n.to_lowercase()))
+                };
+                let deadline =
+                    std::time::Instant::now() + std::time::Duration::from_millis(timeout);
+
+                // Drivers without a notification API fall back to reading the shade
+                let found = loop {
+                    let Ok(list) = self.driver.get_notifications().await else {
+                        break None;
+                    };
+                    let matched = list.into_iter().find(|n| {
+                        contains(&n.title, &title)
+                            && contains(&n.text, &text)
+                            && app.as_ref().is_none_or(|a| &n.app == a)
+                    });
+                    if matched.is_some() {
+                        break matched;
+                    }
+                    if std::time::Instant::now() >= deadline {
+                        anyhow::bail!(
+                            "No notification matching '{}' was posted within {}ms",
+                            label,
+                            timeout
+                        );
+                    }
+                    tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+                };
+
+                if found.is_some() && !params.tap {
+                    return Ok(());
+                }
+
+                // The shade only shows text, so every given filter must be on screen there
+                if found.is_none() {
+                    if let Some(app) = &app {
+                        anyhow::bail!(
+                            "assertNotification can't check `app: {}` on this platform; match by title/text instead",
+                            app
+                        );
+                    }
+                }
+                let shade_labels: Vec<String> = match &found {
+                    Some(n) if !n.title.is_empty() => vec![n.title.clone()],
+                    Some(n) => vec![n.text.clone()],
+                    None => title.iter().chain(text.iter()).cloned().collect(),
+                };
+                self.driver.open_notifications().await?;
+                for shade_label in &shade_labels {
+                    let selector =
+                        crate::driver::traits::Selector::Text(shade_label.clone(), 0, false);
+                    if !self.driver.wait_for_element(&selector, timeout).await? {
+                        let _ = self.driver.back().await;
+                        anyhow::bail!(
+                            "Notification '{}' not found in the notification shade",
+                            shade_label
+                        );
+                    }
+                }
+                let selector =
+                    crate::driver::traits::Selector::Text(shade_labels[0].clone(), 0, false);
+                if params.tap {
+                    self.driver.tap(&selector).await
+                } else {
+                    self.driver.back().await
+                }
+            }
+
+            TestCommand::AssertToast(params) => {
+                let text = self.context.substitute_vars(&params.text);
+                let timeout = params.timeout.unwrap_or(self.context.default_timeout_ms);
+                if self.driver.wait_for_toast(&text, timeout).await? {
+                    Ok(())
+                } else {
+                    anyhow::bail!("Toast '{}' did not appear within {}ms", text, timeout)
+                }
+            }
+
+            TestCommand::SendSms(params) => {
+                let sent = params
+                    .body
+                    .as_ref()
+                    .map(|b| self.context.substitute_vars(b));
+                if let Some(body) = &sent {
+                    let from = self.context.substitute_vars(&params.from);
+                    self.driver.send_sms(&from, body).await?;
+                }
+
+                if let Some(var) = &params.save_to {
+                    // An injected SMS takes a moment to reach the inbox provider
+                    let deadline = std::time::Instant::now()
+                        + std::time::Duration::from_millis(self.context.default_timeout_ms);
+                    let message = loop {
+                        let latest = self.driver.read_latest_sms().await;
+                        match latest {
+                            Ok(msg) if sent.as_ref().is_none_or(|b| &msg == b) => break msg,
+                            Err(e) if std::time::Instant::now() >= deadline => return Err(e),
+                            Ok(_) if std::time::Instant::now() >= deadline => {
+                                anyhow::bail!("Sent SMS did not reach the inbox in time")
+                            }
+                            _ => tokio::time::sleep(std::time::Duration::from_millis(500)).await,
+                        }
+                    };
+
+                    let value = match &params.regex {
+                        Some(pattern) => {
+                            let re = regex::Regex::new(pattern)
+                                .with_context(|| format!("Invalid regex: {}", pattern))?;
+                            let caps = re.captures(&message).ok_or_else(|| {
+                                anyhow::anyhow!("SMS '{}' does not match /{}/", message, pattern)
+                            })?;
+                            caps.get(1)
+                                .or_else(|| caps.get(0))
+                                .map(|m| m.as_str().to_string())
+                                .unwrap_or_default()
+                        }
+                        None => message,
+                    };
+                    self.context.insert_var(var, value)?;
+                }
+                Ok(())
+            }
+
+            TestCommand::SetDateTime(params) => {
+                let value = self.context.substitute_vars(&params.value);
+                let parsed = crate::parser::types::SetDateTimeParams::parse(&value)?;
+                self.driver.set_date_time(parsed).await?;
+                self.date_time_overridden = parsed.is_some();
+                Ok(())
+            }
+
+            TestCommand::TapSystemButton(button) => {
+                let button = self.context.substitute_vars(button);
+                if self.driver.handle_system_dialog(&button).await? {
+                    Ok(())
+                } else {
+                    anyhow::bail!("No system dialog with a '{}' button is showing", button)
+                }
+            }
+
+            TestCommand::SetPermission(params) => {
+                let app_id = params
+                    .app_id
+                    .as_ref()
+                    .or(self.context.app_id.as_ref())
+                    .map(|a| self.context.substitute_vars(a))
+                    .ok_or_else(|| anyhow::anyhow!("setPermission requires appId"))?;
+                let mut perms = std::collections::HashMap::new();
+                perms.insert(
+                    self.context.substitute_vars(&params.permission),
+                    params.state.as_str().to_string(),
+                );
+                self.driver.set_permissions(&app_id, &perms).await
+            }
+
+            TestCommand::AssertAppVersion(params) => {
+                let app_id = params
+                    .app_id
+                    .as_ref()
+                    .or(self.context.app_id.as_ref())
+                    .map(|a| self.context.substitute_vars(a))
+                    .ok_or_else(|| anyhow::anyhow!("assertAppVersion requires appId"))?;
+                let installed = self.driver.get_app_version(&app_id).await?;
+
+                if let Some(expected) = &params.equals {
+                    let expected = self.context.substitute_vars(expected);
+                    if crate::utils::version::compare_versions(&installed.version, &expected)
+                        != std::cmp::Ordering::Equal
+                    {
+                        anyhow::bail!(
+                            "App {} version expected '{}', got '{}'",
+                            app_id,
+                            expected,
+                            installed.version
+                        );
+                    }
+                }
+                if let Some(min) = &params.min_version {
+                    let min = self.context.substitute_vars(min);
+                    if crate::utils::version::compare_versions(&installed.version, &min)
+                        == std::cmp::Ordering::Less
+                    {
+                        anyhow::bail!(
+                            "App {} version '{}' is older than minimum '{}'",
+                            app_id,
+                            installed.version,
+                            min
+                        );
+                    }
+                }
+                if let Some(expected) = &params.build_number {
+                    let expected = self.context.substitute_vars(expected);
+                    let actual = installed.build.unwrap_or_default();
+                    if actual != expected {
+                        anyhow::bail!(
+                            "App {} build number expected '{}', got '{}'",
+                            app_id,
+                            expected,
+                            actual
+                        );
+                    }
+                }
+                Ok(())
             }
 
-            TestCommand::SetNetwork(params) => {
+            TestCommand::BackgroundApp(params) => {
+                let app_id = params
+                    .app_id
+                    .as_ref()
+                    .or(self.context.app_id.as_ref())
+                    .map(|a| self.context.substitute_vars(a));
                 self.driver
-                    .set_network_connection(params.wifi, params.data)
-                    .await
-            }
-
-            TestCommand::ToggleAirplaneMode => self.driver.toggle_airplane_mode().await,
-
-            TestCommand::OpenNotifications => self.driver.open_notifications().await,
-
-            TestCommand::OpenQuickSettings => self.driver.open_quick_settings().await,
-
-            TestCommand::SetVolume(level) => self.driver.set_volume(*level).await,
-
-            TestCommand::LockDevice => self.driver.lock_device().await,
+                    .background_app(app_id.as_deref(), params.duration_ms)
+                    .await?;
 
-            TestCommand::UnlockDevice => self.driver.unlock_device().await,
+                if !params.verify_resume && params.wait_for.is_none() {
+                    return Ok(());
+                }
 
-            TestCommand::InstallApp(path) => {
-                let resolved_path = self.context.resolve_path(path);
-                self.driver
-                    .install_app(resolved_path.to_str().unwrap())
-                    .await
-            }
+                let app_id = app_id.ok_or_else(|| {
+                    anyhow::anyhow!("backgroundApp resume verification requires an appId")
+                })?;
+                let timeout = params
+                    .resume_timeout_ms
+                    .unwrap_or(self.context.default_timeout_ms);
+
+                // Poll for the app to regain focus
+                let start = std::time::Instant::now();
+                while !self.driver.is_app_in_foreground(&app_id).await? {
+                    if start.elapsed().as_millis() as u64 >= timeout {
+                        anyhow::bail!(
+                            "App {} did not return to the foreground within {}ms after backgroundApp",
+                            app_id,
+                            timeout
+                        );
+                    }
+                    tokio::time::sleep(std::time::Duration::from_millis(300)).await;
+                }
 
-            TestCommand::UninstallApp(pkg) => self.driver.uninstall_app(pkg).await,
+                if let Some(wait_for) = &params.wait_for {
+                    let selector = self
+                        .build_selector(
+                            &wait_for.text,
+                            &wait_for.regex,
+                            &wait_for.id,
+                            &wait_for.description,
+                            &wait_for.relative,
+                            &wait_for.css,
+                            &wait_for.xpath,
+                            &wait_for.placeholder,
+                            &wait_for.role,
+                            &wait_for.element_type,
+                            &wait_for.image,
+                            wait_for.index,
+                            &wait_for.scrollable,
+                            wait_for.exact,
+                            &wait_for.ocr,
+                        )
+                        .ok_or_else(|| {
+                            anyhow::anyhow!("No selector specified for backgroundApp waitFor")
+                        })?;
+                    if !self.driver.wait_for_element(&selector, timeout).await? {
+                        anyhow::bail!(
+                            "App {} resumed but {:?} was not visible within {}ms",
+                            app_id,
+                            selector,
+                            timeout
+                        );
+                    }
+                }
 
-            TestCommand::BackgroundApp(params) => {
-                let app_id = params.app_id.as_deref().or(self.context.app_id.as_deref());
-                self.driver.background_app(app_id, params.duration_ms).await
+                Ok(())
             }
 
             TestCommand::PressKey(params) => {
@@ -2137,7 +3485,7 @@ impl TestExecutor {
 
             TestCommand::GetClipboard(params) => match self.driver.get_clipboard().await {
                 Ok(content) => {
-                    self.context.set_var(&params.name, &content);
+                    self.context.set_var(&params.name, &content)?;
                     Ok(())
                 }
                 Err(e) => {
@@ -2164,37 +3512,113 @@ impl TestExecutor {
                 }
             }
 
+            TestCommand::AssertUrl(params) => {
+                if params.equals.is_none() && params.contains.is_none() && params.matches.is_none()
+                {
+                    anyhow::bail!("assertUrl requires equals, contains or matches");
+                }
+                let actual = self.driver.get_current_url().await?;
+                if let Some(expected) = &params.equals {
+                    let expected = self.context.substitute_vars(expected);
+                    if actual != expected {
+                        anyhow::bail!("URL expected '{}', got '{}'", expected, actual);
+                    }
+                }
+                if let Some(part) = &params.contains {
+                    let part = self.context.substitute_vars(part);
+                    if !actual.contains(&part) {
+                        anyhow::bail!("URL '{}' does not contain '{}'", actual, part);
+                    }
+                }
+                if let Some(pattern) = &params.matches {
+                    let pattern = self.context.substitute_vars(pattern);
+                    let re = regex::Regex::new(&pattern)
+                        .with_context(|| format!("Invalid assertUrl regex '{}'", pattern))?;
+                    if !re.is_match(&actual) {
+                        anyhow::bail!("URL '{}' does not match '{}'", actual, pattern);
+                    }
+                }
+                Ok(())
+            }
+
+            TestCommand::CaptureUrl(params) => {
+                let url = self.driver.get_current_url().await?;
+                self.context.set_var(&params.into, &url)?;
+                Ok(())
+            }
+
+            TestCommand::ExecuteJs(params) => {
+                let script = self.context.substitute_vars(&params.script);
+                let result = self.driver.execute_js(&script).await?;
+                if let Some(var) = &params.into {
+                    self.context.set_var(var, &result)?;
+                    self.emitter.emit(TestEvent::Log {
+                        message: format!("{} executeScript: {} = {}", "📝".blue(), var, result),
+                        depth: self.depth,
+                    });
+                }
+                Ok(())
+            }
+
             TestCommand::AssertTrue(params) => {
                 use super::js_engine::JsEngine;
                 use crate::parser::types::AssertTrueParams;
 
-                let (condition_str, soft) = match params {
-                    AssertTrueParams::Condition(c) => (c.condition.clone(), c.soft),
-                    AssertTrueParams::Expression(expr) => (expr.clone(), false),
+                let (condition_str, soft, message, retry, timeout) = match params {
+                    AssertTrueParams::Condition(c) => (
+                        c.condition.clone(),
+                        c.soft,
+                        c.message.clone(),
+                        c.retry.unwrap_or(0),
+                        c.timeout,
+                    ),
+                    AssertTrueParams::Expression(expr) => (expr.clone(), false, None, 0, None),
                 };
 
-                let result = {
-                    // Substitute variables first
-                    let substituted = self.context.substitute_vars(&condition_str);
-
-                    // Create JS engine with current context variables
-                    let mut engine = JsEngine::new();
-                    engine.set_vars(&self.context.vars);
-                    engine.set_vars(&self.context.env);
-
-                    // Evaluate the boolean expression
-                    match engine.eval_bool(&substituted) {
-                        Ok(true) => Ok(()),
-                        Ok(false) => Err(anyhow::anyhow!(
-                            "Assertion failed: {} evaluated to false",
-                            condition_str
-                        )),
-                        Err(e) => Err(anyhow::anyhow!(
-                            "Assertion error: {} - {}",
-                            condition_str,
-                            e
-                        )),
+                let deadline = timeout
+                    .map(|ms| std::time::Instant::now() + std::time::Duration::from_millis(ms));
+                let mut attempt = 0;
+                let result = loop {
+                    let outcome = {
+                        // Substitute variables first
+                        let substituted = self.context.substitute_vars(&condition_str);
+
+                        // Create JS engine with current context variables
+                        let mut engine = JsEngine::new();
+                        engine.set_vars(&self.context.vars);
+                        engine.set_builtins(&self.context.builtins);
+                        engine.set_vars(&self.context.env);
+
+                        // Evaluate the boolean expression
+                        engine.eval_bool(&substituted)
+                    };
+                    if let Ok(true) = outcome {
+                        break Ok(());
+                    }
+
+                    attempt += 1;
+                    let retry_left = attempt <= retry;
+                    let time_left = deadline.is_some_and(|d| std::time::Instant::now() < d);
+                    if !retry_left && !time_left {
+                        // A custom message replaces "evaluated to false", never the eval error
+                        let reason = match (message.as_ref(), outcome) {
+                            (Some(m), Ok(_)) => self.context.substitute_vars(m),
+                            (Some(m), Err(e)) => {
+                                format!(
+                                    "{} ({} - {})",
+                                    self.context.substitute_vars(m),
+                                    condition_str,
+                                    e
+                                )
+                            }
+                            (None, Ok(_)) => format!("{} evaluated to false", condition_str),
+                            (None, Err(e)) => format!("{} - {}", condition_str, e),
+                        };
+                        break Err(
+                            TesterError::assertion(format!("Assertion failed: {}", reason)).into(),
+                        );
                     }
+                    tokio::time::sleep(std::time::Duration::from_millis(500)).await;
                 };
 
                 self.handle_assertion(result, soft)
@@ -2206,6 +3630,7 @@ impl TestExecutor {
                 // Create a new JS engine and load current variables
                 let mut engine = JsEngine::new();
                 engine.set_vars(&self.context.vars);
+                engine.set_builtins(&self.context.builtins);
                 engine.set_vars(&self.context.env);
 
                 // Substitute variables first for ${var} syntax
@@ -2215,7 +3640,7 @@ impl TestExecutor {
                 match engine.eval_assignment(&substituted) {
                     Ok(Some((var_name, value))) => {
                         // Assignment expression - save the result
-                        self.context.set_var(&var_name, &value);
+                        self.context.set_var(&var_name, &value)?;
                         println!("  {} evalScript: {} = {}", "📝".blue(), var_name, value);
                     }
                     Ok(None) => {
@@ -2250,7 +3675,7 @@ impl TestExecutor {
                     &None, // role
                     &None, // element_type
                     &None, // image
-                    params.index.map(|i| i as u32),
+                    params.index,
                     &None,
                     false,
                     &params.ocr,
@@ -2259,14 +3684,14 @@ impl TestExecutor {
                 if let Some(sel) = selector {
                     match self.driver.get_element_text(&sel).await {
                         Ok(text) => {
-                            self.context.set_var("nl.copiedText", &text);
+                            self.context.set_builtin("copiedText", &text);
                             println!("  {} Copied text: '{}'", "📝".blue(), text);
                         }
                         Err(e) => {
                             println!("  {} Failed to extract text: {}", "⚠️".yellow(), e);
                             // Fallback mock if needed for specific tests
                             if let Some(fallback) = &params.text {
-                                self.context.set_var("nl.copiedText", fallback);
+                                self.context.set_builtin("copiedText", fallback);
                             }
                         }
                     }
@@ -2439,7 +3864,7 @@ impl TestExecutor {
                                     }
                                 }
                             };
-                            self.context.set_var(var_name, &val_str);
+                            self.context.set_var(var_name, &val_str)?;
 
                             self.emitter.emit(TestEvent::Log {
                                 message: format!(
@@ -2486,23 +3911,16 @@ impl TestExecutor {
 
             TestCommand::BuildGif(params) => {
                 use crate::parser::types::GifFrameInput;
-                use image::codecs::gif::{GifEncoder, Repeat};
-                use image::{Delay, Frame};
 
-                let output_path = self.context.output_path(&params.output);
-
-                // Determine loop count
-                let repeat = match params.loop_count {
-                    Some(n) => Repeat::Finite(n),
-                    None if params.loop_gif => Repeat::Infinite,
-                    None => Repeat::Finite(1),
-                };
+                let output_path = self
+                    .context
+                    .output_path(&self.context.substitute_vars(&params.output));
 
-                // Speed based on quality
-                let speed = match params.quality.as_str() {
-                    "high" => 1,
-                    "low" => 30,
-                    _ => 10, // medium
+                // Determine loop count (None = infinite)
+                let loop_count = match params.loop_count {
+                    Some(n) => Some(n),
+                    None if params.loop_gif => None,
+                    None => Some(1),
                 };
 
                 // Collect and process frames
@@ -2534,24 +3952,18 @@ impl TestExecutor {
                     frames.push((img.to_rgba8(), delay));
                 }
 
-                // Encode GIF
-                let file = std::fs::File::create(&output_path)?;
-                let mut encoder = GifEncoder::new_with_speed(file, speed);
-                encoder.set_repeat(repeat)?;
-
-                for (frame_img, delay_ms) in &frames {
-                    let frame = Frame::from_parts(
-                        frame_img.clone(),
-                        0,
-                        0,
-                        Delay::from_numer_denom_ms(*delay_ms, 1),
-                    );
-                    encoder.encode_frame(frame)?;
-                }
+                crate::utils::animation::write_animation(
+                    &output_path,
+                    &frames,
+                    params.format,
+                    loop_count,
+                    &params.quality,
+                )?;
 
                 println!(
-                    "  {} Built GIF: {} ({} frames, quality: {})",
+                    "  {} Built {:?}: {} ({} frames, quality: {})",
                     "🎬".green(),
+                    params.format,
                     output_path.display(),
                     frames.len(),
                     params.quality
@@ -2561,11 +3973,15 @@ impl TestExecutor {
 
             // Start auto-capture mode
             TestCommand::StartGifCapture(params) => {
+                if let Some(ref crop_str) = params.crop {
+                    parse_crop_region(crop_str)?;
+                }
                 self.auto_capture_frames.clear();
                 self.auto_capture_active = true;
                 self.auto_capture_interval = params.interval;
                 self.auto_capture_max = params.max_frames;
                 self.auto_capture_width = params.width;
+                self.auto_capture_crop = params.crop.clone();
                 self.auto_capture_last_time = std::time::Instant::now();
 
                 println!(
@@ -2579,29 +3995,17 @@ impl TestExecutor {
 
             // Stop auto-capture and build GIF
             TestCommand::StopGifCapture(params) => {
-                use image::codecs::gif::{GifEncoder, Repeat};
-                use image::{Delay, Frame};
-
                 self.auto_capture_active = false;
 
                 if self.auto_capture_frames.is_empty() {
                     anyhow::bail!("No frames captured! Make sure startGifCapture was called.");
                 }
 
-                let output_path = self.context.output_path(&params.output);
+                let output_path = self
+                    .context
+                    .output_path(&self.context.substitute_vars(&params.output));
                 let delay_ms = params.delay.unwrap_or(self.auto_capture_interval as u32);
 
-                let repeat = match params.loop_count {
-                    Some(n) => Repeat::Finite(n),
-                    None => Repeat::Infinite,
-                };
-
-                let speed = match params.quality.as_str() {
-                    "high" => 1,
-                    "low" => 30,
-                    _ => 10,
-                };
-
                 // Process frames
                 let mut processed_frames = Vec::new();
                 for bytes in &self.auto_capture_frames {
@@ -2614,30 +4018,24 @@ impl TestExecutor {
                         img = img.resize(w, h, image::imageops::FilterType::Lanczos3);
                     }
 
-                    processed_frames.push(img.to_rgba8());
+                    processed_frames.push((img.to_rgba8(), delay_ms));
                 }
 
-                // Encode GIF
-                let file = std::fs::File::create(&output_path)?;
-                let mut encoder = GifEncoder::new_with_speed(file, speed);
-                encoder.set_repeat(repeat)?;
-
-                for frame_img in &processed_frames {
-                    let frame = Frame::from_parts(
-                        frame_img.clone(),
-                        0,
-                        0,
-                        Delay::from_numer_denom_ms(delay_ms, 1),
-                    );
-                    encoder.encode_frame(frame)?;
-                }
+                crate::utils::animation::write_animation(
+                    &output_path,
+                    &processed_frames,
+                    params.format,
+                    params.loop_count,
+                    &params.quality,
+                )?;
 
                 let frame_count = self.auto_capture_frames.len();
                 self.auto_capture_frames.clear();
 
                 println!(
-                    "  {} Built smooth GIF: {} ({} frames, {}ms delay)",
+                    "  {} Built smooth {:?}: {} ({} frames, {}ms delay)",
                     "🎬".green(),
+                    params.format,
                     output_path.display(),
                     frame_count,
                     delay_ms
@@ -2694,12 +4092,24 @@ impl TestExecutor {
 
             // Mock Location Synchronization
             TestCommand::WaitForLocation(params) => {
+                use crate::driver::traits::LocationTolerance;
+                let tolerance = match (params.tolerance_meters, params.tolerance) {
+                    (Some(meters), _) => LocationTolerance::Meters(meters),
+                    // Web always read `tolerance` as degrees; mobile drivers as meters
+                    (None, Some(t)) if self.driver.platform_name() == "web" => {
+                        LocationTolerance::Degrees(t)
+                    }
+                    (None, Some(t)) => LocationTolerance::Meters(t),
+                    (None, None) => LocationTolerance::Meters(
+                        crate::parser::types::DEFAULT_LOCATION_TOLERANCE_METERS,
+                    ),
+                };
                 self.driver
                     .wait_for_location(
                         params.name.clone(),
                         params.lat,
                         params.lon,
-                        params.tolerance,
+                        tolerance,
                         params.timeout,
                     )
                     .await
@@ -2727,12 +4137,19 @@ impl TestExecutor {
             // Performance & Load Testing
             TestCommand::StartProfiling(params) => {
                 self.driver.start_profiling(params.clone()).await?;
+                self.profiling_started = Some(std::time::Instant::now());
+                self.perf_sample_interval_ms = params
+                    .as_ref()
+                    .and_then(|p| p.sampling_interval_ms)
+                    .unwrap_or(1000);
+                self.perf_last_sample = None;
                 println!("  {} Started performance profiling", "⚡".green());
                 Ok(())
             }
 
             TestCommand::StopProfiling(params) => {
                 self.driver.stop_profiling().await?;
+                self.profiling_started = None;
                 println!("  {} Stopped performance profiling", "⚡".green());
                 // Optional: Save report if path provided
                 if let Some(p) = params.as_ref().and_then(|x| x.save_path.as_ref()) {
@@ -2843,9 +4260,16 @@ impl TestExecutor {
                 Ok(())
             }
 
-            TestCommand::SetNetworkConditions(profile) => {
-                self.driver.set_network_conditions(profile).await?;
-                println!("  {} Set network profile: {}", "⚡".green(), profile);
+            TestCommand::SetNetworkConditions(input) => {
+                let input = match input {
+                    NetworkConditionsInput::Profile(profile) => {
+                        NetworkConditionsInput::Profile(self.context.substitute_vars(profile))
+                    }
+                    custom => custom.clone(),
+                };
+                let conditions = input.resolve()?;
+                self.driver.set_network_conditions(&conditions).await?;
+                println!("  {} Set network profile: {}", "⚡".green(), conditions);
                 Ok(())
             }
 
@@ -2854,48 +4278,8 @@ impl TestExecutor {
 
                 // Support "auto" keyword for auto-detection
                 if id_val.eq_ignore_ascii_case("auto") {
-                    // For Android, try to detect or create secondary display
-                    if self.driver.platform_name() == "android" {
-                        println!("  {} Auto-detecting secondary display...", "🔍".cyan());
-
-                        // 1. Try to detect existing Android Auto display first
-                        if let Ok(Some(id)) = self.driver.detect_android_auto_display().await {
-                            println!("  {} Selected found display ID: {}", "📺".green(), id);
-                            self.driver.select_display(id).await?;
-                        } else {
-                            // 2. If not found, create overlay display for simulation
-                            println!(
-                                "  {} No suitable display found, creating overlay...",
-                                "⚠️".yellow()
-                            );
-                            let _ = std::process::Command::new("adb")
-                                .args(&[
-                                    "shell",
-                                    "settings",
-                                    "put",
-                                    "global",
-                                    "overlay_display_devices",
-                                    "1024x768/120",
-                                ])
-                                .output();
-
-                            // Wait for display to be created
-                            tokio::time::sleep(tokio::time::Duration::from_millis(1500)).await;
-
-                            // Re-detect to find the new overlay
-                            if let Ok(Some(id)) = self.driver.detect_android_auto_display().await {
-                                println!(
-                                    "  {} Created and selected overlay display ID: {}",
-                                    "📺".green(),
-                                    id
-                                );
-                                self.driver.select_display(id).await?;
-                            } else {
-                                // Fallback to likely ID 2
-                                self.driver.select_display(2).await?;
-                                println!("  {} Created overlay display and selected Display 2 (fallback)", "📺".green());
-                            }
-                        }
+                    if matches!(self.driver.platform_name(), "android" | "android_auto") {
+                        self.driver.select_auto_display().await?;
                     } else {
                         println!(
                             "  {} Auto-detect display only supported on Android",
@@ -2906,24 +4290,6 @@ impl TestExecutor {
                     let id = id_val
                         .parse::<u32>()
                         .map_err(|e| anyhow::anyhow!("Invalid display ID '{}': {}", id_val, e))?;
-
-                    // If switching back to display 0, cleanup overlay display
-                    if id == 0 && self.driver.platform_name() == "android" {
-                        let _ = std::process::Command::new("adb")
-                            .args(&[
-                                "shell",
-                                "settings",
-                                "delete",
-                                "global",
-                                "overlay_display_devices",
-                            ])
-                            .output();
-                        println!(
-                            "  {} Removed overlay display and switched to main display",
-                            "🧹".cyan()
-                        );
-                    }
-
                     self.driver.select_display(id).await?;
                 }
                 Ok(())
@@ -2959,6 +4325,32 @@ impl TestExecutor {
                     .await
             }
 
+            TestCommand::AssertAudioPlaying(params) => {
+                let app = params.app.as_ref().map(|a| self.context.substitute_vars(a));
+                let timeout = params.timeout.unwrap_or(self.context.default_timeout_ms);
+                let deadline =
+                    std::time::Instant::now() + std::time::Duration::from_millis(timeout);
+                // Playback state changes a moment after the tap that starts/stops it
+                loop {
+                    if self.driver.is_audio_playing(app.as_deref()).await? == params.playing {
+                        return Ok(());
+                    }
+                    if std::time::Instant::now() >= deadline {
+                        anyhow::bail!(
+                            "Expected {} to be {} within {}ms",
+                            app.as_deref().unwrap_or("any app"),
+                            if params.playing {
+                                "playing"
+                            } else {
+                                "not playing"
+                            },
+                            timeout
+                        );
+                    }
+                    tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+                }
+            }
+
             TestCommand::SendLarkMessage(params) => {
                 let webhook_url = self.context.substitute_vars(&params.webhook);
 
@@ -3124,14 +4516,14 @@ impl TestExecutor {
         role: &Option<String>,
         element_type: &Option<String>,
         image: &Option<String>,
-        index: Option<u32>,
+        index: Option<i32>,
         scrollable: &Option<crate::parser::types::ScrollableParams>,
         exact: bool,
         ocr: &Option<crate::parser::types::OcrSelectorInput>,
     ) -> Option<crate::driver::traits::Selector> {
         use crate::driver::traits::Selector;
 
-        let idx = index.unwrap_or(0) as usize;
+        let idx = index.unwrap_or(0);
 
         let primary = if let Some(r) = regex {
             Selector::TextRegex(self.context.substitute_vars(r), idx)
@@ -3189,6 +4581,7 @@ impl TestExecutor {
         };
 
         if let Some(rel) = relative {
+            let rel = &rel.resolve_id_anchors();
             let (dir, anchor_input) = if let Some(input) = &rel.right_of {
                 (crate::driver::traits::RelativeDirection::RightOf, input)
             } else if let Some(input) = &rel.left_of {
@@ -3210,7 +4603,7 @@ impl TestExecutor {
                             serde_json::from_str::<crate::parser::types::AnchorParams>(&subst)
                         {
                             // Recursive build for anchor from strict Struct(p) logic below
-                            let idx = p.index.unwrap_or(0) as usize;
+                            let idx = p.index.unwrap_or(0);
 
                             if let Some(r) = &p.regex {
                                 Selector::TextRegex(self.context.substitute_vars(r), idx)
@@ -3258,13 +4651,13 @@ impl TestExecutor {
                                 Selector::Text(subst, 0, false)
                             }
                         }
+                    } else if let Some(selector) = Selector::from_dsl(&subst) {
+                        // `id=header`, `"literal text"`, ...
+                        selector
+                    } else if crate::parser::types::is_regex_string(&subst) {
+                        Selector::TextRegex(subst, 0)
                     } else {
-                        // Not a JSON string
-                        if crate::parser::types::is_regex_string(&subst) {
-                            Selector::TextRegex(subst, 0)
-                        } else {
-                            Selector::Text(subst, 0, false)
-                        }
+                        Selector::Text(subst, 0, false)
                     }
                 }
                 crate::parser::types::RelativeAnchorInput::Struct(p) => {
@@ -3319,7 +4712,7 @@ impl TestExecutor {
 
                     // Recursive build for anchor (simplified manual recursion)
                     // We map AnchorParams fields to build_selector-like logic
-                    let idx = params.index.unwrap_or(0) as usize;
+                    let idx = params.index.unwrap_or(0);
 
                     if let Some(r) = &params.regex {
                         Selector::TextRegex(self.context.substitute_vars(r), idx)
@@ -3367,6 +4760,7 @@ impl TestExecutor {
                 anchor: Box::new(anchor_selector),
                 direction: dir,
                 max_dist: rel.max_dist,
+                angle: rel.angle,
             })
         } else {
             Some(primary)
@@ -3473,14 +4867,7 @@ impl TestExecutor {
 
     /// Crop image by percentage region
     fn crop_image(&self, bytes: &[u8], crop_str: &str) -> Result<Vec<u8>> {
-        let parts: Vec<f32> = crop_str
-            .split(',')
-            .filter_map(|s| s.trim().trim_end_matches('%').parse().ok())
-            .collect();
-
-        if parts.len() != 4 {
-            anyhow::bail!("Invalid crop format, expected: left%,top%,width%,height%");
-        }
+        let parts = parse_crop_region(crop_str)?;
 
         let img = image::load_from_memory(bytes)?;
         let (w, h) = (img.width() as f32, img.height() as f32);
@@ -3497,6 +4884,55 @@ impl TestExecutor {
         Ok(buf.into_inner())
     }
 
+    /// Snapshot performance metrics after a command while profiling is active
+    async fn record_perf_sample(
+        &mut self,
+        flow_name: &str,
+        flow_path: &str,
+        index: usize,
+        force: bool,
+    ) {
+        let Some(started) = self.profiling_started else {
+            return;
+        };
+        if !force
+            && self.perf_last_sample.is_some_and(|last| {
+                (last.elapsed().as_millis() as u64) < self.perf_sample_interval_ms
+            })
+        {
+            return;
+        }
+        self.perf_last_sample = Some(std::time::Instant::now());
+        let metrics = match self.driver.get_performance_metrics().await {
+            Ok(m) if !m.is_empty() => m,
+            Ok(_) => return,
+            Err(e) => {
+                log::debug!("Performance sample skipped: {}", e);
+                return;
+            }
+        };
+
+        let sample = crate::report::types::PerformanceSample {
+            elapsed_ms: started.elapsed().as_millis() as u64,
+            command_index: index,
+            metrics,
+        };
+        match self
+            .perf_samples
+            .iter_mut()
+            .find(|p| p.flow_path == flow_path)
+        {
+            Some(flow) => flow.samples.push(sample),
+            None => self
+                .perf_samples
+                .push(crate::report::types::FlowPerformance {
+                    flow_name: flow_name.to_string(),
+                    flow_path: flow_path.to_string(),
+                    samples: vec![sample],
+                }),
+        }
+    }
+
     /// Try to auto-capture a GIF frame if interval has passed
     async fn try_auto_capture(&mut self) {
         if !self.auto_capture_active {
@@ -3518,8 +4954,15 @@ impl TestExecutor {
         let temp_path = format!("/tmp/auto_gif_frame_{}.png", uuid::Uuid::new_v4());
         if let Ok(()) = self.driver.take_screenshot(&temp_path).await {
             if let Ok(bytes) = std::fs::read(&temp_path) {
-                self.auto_capture_frames.push(bytes);
                 std::fs::remove_file(&temp_path).ok();
+                let frame = match self.auto_capture_crop {
+                    Some(ref crop_str) => self.crop_image(&bytes, crop_str),
+                    None => Ok(bytes),
+                };
+                match frame {
+                    Ok(frame) => self.auto_capture_frames.push(frame),
+                    Err(e) => log::debug!("Auto-capture frame skipped: {}", e),
+                }
             }
         }
 
@@ -3530,7 +4973,14 @@ impl TestExecutor {
     pub async fn finish(&mut self) -> Result<()> {
         self.session.finish();
 
+        if let Err(e) = self.driver.teardown().await {
+            println!("{} Driver teardown failed: {}", "⚠️".yellow(), e);
+        }
+
         let summary = self.session.summary();
+        if let Some(sink) = &self.summary_sink {
+            sink.lock().unwrap().push(summary.clone());
+        }
         self.emitter.emit(TestEvent::SessionFinished {
             summary: summary.clone(),
         });
@@ -3552,15 +5002,17 @@ impl TestExecutor {
         tokio::time::sleep(tokio::time::Duration::from_millis(200)).await;
 
         // Save JSON report
-        let report_path = self.context.output_path("test-results.json");
-        let json = serde_json::to_string_pretty(&report_data)?;
-        std::fs::write(&report_path, json)?;
-
-        println!(
-            "\n{} JSON report saved to: {}",
-            "📄".to_string().blue(),
-            report_path.display().to_string().cyan()
-        );
+        if crate::report::format_enabled(self.report_formats.as_deref(), "json") {
+            let report_path = self.context.output_path("test-results.json");
+            let json = serde_json::to_string_pretty(&report_data)?;
+            std::fs::write(&report_path, json)?;
+
+            println!(
+                "\n{} JSON report saved to: {}",
+                "📄".to_string().blue(),
+                report_path.display().to_string().cyan()
+            );
+        }
 
         // Generate and save HTML report
         let html_path = self.context.output_path("report.html");
@@ -3570,18 +5022,23 @@ impl TestExecutor {
             flows: report_data.flows,
             summary: report_data.summary,
             generated_at: chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+            performance: self.perf_samples.clone(),
         };
 
-        crate::report::html::generate(&test_results, Some(&html_path)).await?;
+        if crate::report::format_enabled(self.report_formats.as_deref(), "html") {
+            crate::report::html::generate(&test_results, Some(&html_path)).await?;
 
-        println!(
-            "{} HTML report saved to: {}",
-            "📊".to_string().blue(),
-            html_path.display().to_string().cyan()
-        );
+            println!(
+                "{} HTML report saved to: {}",
+                "📊".to_string().blue(),
+                html_path.display().to_string().cyan()
+            );
+        }
 
         // Generate and save JUnit report
-        crate::report::junit::write_report(&test_results, &self.context.output_dir)?;
+        if crate::report::format_enabled(self.report_formats.as_deref(), "junit") {
+            crate::report::junit::write_report(&test_results, &self.context.output_dir)?;
+        }
 
         Ok(())
     }
@@ -3594,6 +5051,7 @@ impl TestExecutor {
                 use super::js_engine::JsEngine;
                 let mut engine = JsEngine::new();
                 engine.set_vars(&self.context.vars);
+                engine.set_builtins(&self.context.builtins);
                 engine.set_vars(&self.context.env);
                 engine.eval_bool(&subst).unwrap_or(false)
             }
@@ -3643,3 +5101,396 @@ impl TestExecutor {
         true
     }
 }
+
+/// Load `forEach` items from a CSV (one object per row) or JSON array file
+fn load_for_each_items(path: &Path) -> Result<Vec<serde_json::Value>> {
+    let is_json = path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("json"));
+    if is_json {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read forEach data: {}", path.display()))?;
+        let value: serde_json::Value = serde_json::from_str(&content)
+            .with_context(|| format!("Invalid JSON in {}", path.display()))?;
+        return match value {
+            serde_json::Value::Array(items) => Ok(items),
+            _ => anyhow::bail!(
+                "forEach data file must contain a JSON array: {}",
+                path.display()
+            ),
+        };
+    }
+
+    let file = File::open(path)
+        .with_context(|| format!("Failed to open forEach data: {}", path.display()))?;
+    let mut rdr = csv::Reader::from_reader(file);
+    let mut items = Vec::new();
+    for record in rdr.deserialize() {
+        let row: HashMap<String, String> = record.context("Failed to parse CSV record")?;
+        items.push(serde_json::to_value(row)?);
+    }
+    Ok(items)
+}
+
+/// Parse a "left%,top%,width%,height%" crop region
+fn parse_crop_region(crop_str: &str) -> Result<[f32; 4]> {
+    let parts: Vec<f32> = crop_str
+        .split(',')
+        .filter_map(|s| s.trim().trim_end_matches('%').parse().ok())
+        .collect();
+
+    match parts[..] {
+        [left, top, width, height] => Ok([left, top, width, height]),
+        _ => anyhow::bail!("Invalid crop format, expected: left%,top%,width%,height%"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::driver::traits::{Selector, SwipeDirection};
+    use crate::runner::state::CommandStatus;
+    use async_trait::async_trait;
+
+    /// Driver whose screen shows only the text "present"
+    #[derive(Default)]
+    struct FakeDriver {
+        landscape: std::sync::atomic::AtomicBool,
+    }
+
+    fn shows(selector: &Selector) -> bool {
+        matches!(selector, Selector::Text(text, _, _) if text == "present")
+    }
+
+    #[async_trait]
+    impl PlatformDriver for FakeDriver {
+        fn platform_name(&self) -> &str {
+            "fake"
+        }
+        fn device_serial(&self) -> Option<String> {
+            None
+        }
+        async fn launch_app(&self, _app_id: &str, _clear_state: bool) -> Result<()> {
+            Ok(())
+        }
+        async fn stop_app(&self, _app_id: &str) -> Result<()> {
+            Ok(())
+        }
+        async fn tap(&self, _selector: &Selector) -> Result<()> {
+            Ok(())
+        }
+        async fn long_press(&self, _selector: &Selector, _duration_ms: u64) -> Result<()> {
+            Ok(())
+        }
+        async fn double_tap(&self, _selector: &Selector) -> Result<()> {
+            Ok(())
+        }
+        async fn right_click(&self, _selector: &Selector) -> Result<()> {
+            Ok(())
+        }
+        async fn input_text(&self, _text: &str, _unicode: bool) -> Result<()> {
+            Ok(())
+        }
+        async fn erase_text(&self, _char_count: Option<u32>) -> Result<()> {
+            Ok(())
+        }
+        async fn hide_keyboard(&self) -> Result<()> {
+            Ok(())
+        }
+        async fn swipe(
+            &self,
+            _direction: SwipeDirection,
+            _duration_ms: Option<u64>,
+            _from: Option<Selector>,
+        ) -> Result<()> {
+            Ok(())
+        }
+        async fn scroll_until_visible(
+            &self,
+            selector: &Selector,
+            _max_scrolls: u32,
+            _direction: Option<SwipeDirection>,
+            _from: Option<Selector>,
+        ) -> Result<bool> {
+            Ok(shows(selector))
+        }
+        async fn is_visible(&self, selector: &Selector) -> Result<bool> {
+            Ok(shows(selector))
+        }
+        async fn wait_for_element(&self, selector: &Selector, _timeout_ms: u64) -> Result<bool> {
+            Ok(shows(selector))
+        }
+        async fn wait_for_absence(&self, selector: &Selector, _timeout_ms: u64) -> Result<bool> {
+            Ok(!shows(selector))
+        }
+        async fn get_element_text(&self, _selector: &Selector) -> Result<String> {
+            Ok("present".to_string())
+        }
+        async fn open_link(&self, _url: &str, _app_id: Option<&str>) -> Result<()> {
+            Ok(())
+        }
+        async fn compare_screenshot(
+            &self,
+            _reference_path: &Path,
+            _tolerance_percent: f64,
+        ) -> Result<f64> {
+            Ok(0.0)
+        }
+        async fn take_screenshot(&self, _path: &str) -> Result<()> {
+            Ok(())
+        }
+        async fn start_recording(&self, _path: &str) -> Result<()> {
+            Ok(())
+        }
+        async fn stop_recording(&self) -> Result<()> {
+            Ok(())
+        }
+        async fn back(&self) -> Result<()> {
+            Ok(())
+        }
+        async fn home(&self) -> Result<()> {
+            Ok(())
+        }
+        async fn get_screen_size(&self) -> Result<(u32, u32)> {
+            if self.landscape.load(std::sync::atomic::Ordering::Relaxed) {
+                Ok((1920, 1080))
+            } else {
+                Ok((1080, 1920))
+            }
+        }
+        async fn set_orientation(&self, mode: crate::parser::types::Orientation) -> Result<()> {
+            use crate::parser::types::Orientation;
+            let landscape = matches!(
+                mode,
+                Orientation::Landscape | Orientation::LandscapeLeft | Orientation::LandscapeRight
+            );
+            self.landscape
+                .store(landscape, std::sync::atomic::Ordering::Relaxed);
+            Ok(())
+        }
+        async fn dump_ui_hierarchy(&self) -> Result<String> {
+            Ok(String::new())
+        }
+        async fn dump_logs(&self, _limit: u32) -> Result<String> {
+            Ok(String::new())
+        }
+    }
+
+    /// Runs `yaml` as a flow file and hands the executor to `check`. Uses its own
+    /// thread because the executor's futures outgrow the default test stack.
+    fn run_flow(yaml: &str, check: impl FnOnce(&TestExecutor, Result<()>) + Send + 'static) {
+        let yaml = yaml.to_string();
+        std::thread::Builder::new()
+            .stack_size(64 * 1024 * 1024)
+            .spawn(move || {
+                let runtime = tokio::runtime::Builder::new_current_thread()
+                    .enable_all()
+                    .build()
+                    .unwrap();
+                runtime.block_on(async {
+                    let dir = std::env::temp_dir().join(format!("lumi_exec_{}", Uuid::new_v4()));
+                    std::fs::create_dir_all(&dir).unwrap();
+                    let path = dir.join("flow.yaml");
+                    std::fs::write(&path, yaml).unwrap();
+
+                    let mut executor = TestExecutor::new(
+                        Box::new(FakeDriver::default()),
+                        Some(&dir),
+                        false,
+                        false,
+                        false,
+                        false,
+                        None,
+                    );
+                    let result = executor.run_file(&path, None, None).await;
+                    std::fs::remove_dir_all(&dir).ok();
+                    check(&executor, result);
+                });
+            })
+            .unwrap()
+            .join()
+            .unwrap();
+    }
+
+    #[test]
+    fn test_header_env_rejects_builtin_names() {
+        run_flow(
+            "appId: com.example\nenv:\n  nl.platform: web\n---\n- back\n",
+            |_, result| {
+                let message = format!("{:#}", result.unwrap_err());
+                assert!(
+                    message.contains("reserved for built-in variables"),
+                    "{}",
+                    message
+                );
+            },
+        );
+    }
+
+    #[test]
+    fn test_set_orientation_refreshes_screen_builtins() {
+        run_flow(
+            r#"
+appId: com.example
+---
+- setVar:
+    name: portraitWidth
+    value: "${nl.screenWidth}"
+- setOrientation:
+    mode: LANDSCAPE
+"#,
+            |executor, result| {
+                result.unwrap();
+                let context = &executor.context;
+                assert_eq!(
+                    context.vars.get("portraitWidth").map(String::as_str),
+                    Some("1080")
+                );
+                assert_eq!(context.get_var("nl.screenWidth").as_deref(), Some("1920"));
+                assert_eq!(context.get_var("nl.screenHeight").as_deref(), Some("1080"));
+            },
+        );
+    }
+
+    #[test]
+    fn test_conditional_is_not_skipped_by_nested_optional() {
+        run_flow(
+            r#"
+appId: com.example
+---
+- conditional:
+    condition:
+      visible: "present"
+    then:
+      - tapOn:
+          text: "missing"
+          optional: true
+      - assertVisible:
+          text: "present"
+          screenshot: true
+"#,
+            |executor, result| {
+                result.unwrap();
+                let flow = executor.session.flows.last().unwrap();
+                assert_eq!(flow.commands[0].status, CommandStatus::Passed);
+                assert_eq!(flow.commands[0].screenshot_path, None);
+            },
+        );
+    }
+
+    #[test]
+    fn test_assert_all_passes_after_earlier_soft_failure() {
+        run_flow(
+            r#"
+appId: com.example
+---
+- assertVisible:
+    text: "missing"
+    soft: true
+- assertAll:
+    assertions:
+      - assertVisible: "present"
+- setVar:
+    name: after
+    value: "ran"
+"#,
+            |executor, result| {
+                let message = format!("{:#}", result.unwrap_err());
+                assert!(message.contains("1 soft assertion failures"), "{}", message);
+                assert!(!message.contains("assertions failed"), "{}", message);
+                assert_eq!(
+                    executor.context.vars.get("after").map(String::as_str),
+                    Some("ran")
+                );
+            },
+        );
+    }
+
+    #[test]
+    fn test_timeout_inside_assert_all_restores_block_state() {
+        run_flow(
+            r#"
+appId: com.example
+---
+- assertVisible:
+    text: "missing"
+    soft: true
+- assertAll:
+    assertions:
+      - wait: 5000
+  timeout: 100
+"#,
+            |executor, result| {
+                // The earlier soft failure still fails the flow
+                let message = format!("{:#}", result.unwrap_err());
+                assert!(message.contains("1 soft assertion failures"), "{}", message);
+                assert_eq!(executor.collect_failures, None);
+                assert!(executor.assert_all_frames.is_empty());
+                assert!(executor.block_failures.is_empty());
+                assert_eq!(executor.soft_errors.len(), 1);
+                assert!(executor.flow_stack.is_empty());
+
+                let block = &executor.session.flows[0];
+                assert_eq!(block.flow_name, "assertAll");
+                assert_eq!(block.status, crate::runner::state::FlowStatus::Failed);
+                assert!(matches!(
+                    block.commands[0].status,
+                    CommandStatus::Failed { .. }
+                ));
+                let flow = &executor.session.flows[1];
+                assert!(matches!(
+                    &flow.commands[1].status,
+                    CommandStatus::Failed { error } if error.contains("timed out")
+                ));
+            },
+        );
+    }
+
+    #[test]
+    fn test_timeout_inside_for_each_restores_loop_var() {
+        run_flow(
+            r#"
+appId: com.example
+---
+- forEach:
+    items: ["a", "b"]
+    as: item
+    commands:
+      - wait: 5000
+  timeout: 100
+"#,
+            |executor, result| {
+                assert!(result.is_err());
+                assert!(!executor.context.vars.contains_key("item"));
+                assert!(executor.loop_vars.is_empty());
+                assert!(executor.flow_stack.is_empty());
+                assert_eq!(executor.session.flows.len(), 2);
+                assert_eq!(executor.session.flows[0].depth, 1);
+            },
+        );
+    }
+
+    #[test]
+    fn test_set_var_eval_result_is_not_substituted_again() {
+        run_flow(
+            r#"
+appId: com.example
+---
+- setVar:
+    name: secret
+    value: "hunter2"
+- setVar:
+    name: template
+    value: "'$' + '{secret}'"
+    eval: true
+"#,
+            |executor, result| {
+                result.unwrap();
+                assert_eq!(
+                    executor.context.vars.get("template").map(String::as_str),
+                    Some("${secret}")
+                );
+            },
+        );
+    }
+}
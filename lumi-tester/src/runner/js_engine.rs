@@ -12,6 +12,9 @@ use boa_engine::{
 };
 use std::collections::HashMap;
 
+/// `nl.*` builtins exposed to JavaScript as numbers; every other builtin is a string
+const NUMERIC_BUILTINS: &[&str] = &["screenWidth", "screenHeight", "timestamp", "anyOfIndex"];
+
 /// JavaScript evaluation engine
 pub struct JsEngine {
     context: Context,
@@ -41,6 +44,27 @@ impl JsEngine {
         }
     }
 
+    /// Expose the built-in runtime values as an `nl` object (`nl.screenWidth / 2`);
+    /// only the known numeric builtins become numbers, so serials like `00008020` stay strings
+    pub fn set_builtins(&mut self, builtins: &HashMap<String, String>) {
+        let nl: serde_json::Map<String, serde_json::Value> = builtins
+            .iter()
+            .map(|(key, value)| {
+                let number = NUMERIC_BUILTINS
+                    .contains(&key.as_str())
+                    .then(|| value.parse::<f64>().ok())
+                    .flatten();
+                let value = match number {
+                    Some(n) => serde_json::json!(n),
+                    None => serde_json::Value::String(value.clone()),
+                };
+                (key.clone(), value)
+            })
+            .collect();
+        let js_code = format!("var nl = {};", serde_json::Value::Object(nl));
+        let _ = self.context.eval(Source::from_bytes(&js_code));
+    }
+
     /// Execute a script file content and return the 'output' global variable as a JSON string
     pub fn execute_script_with_output(&mut self, script_content: &str) -> Result<String, String> {
         // 1. Inject 'output' object
@@ -243,6 +267,22 @@ mod tests {
         assert_eq!(engine.eval("name").unwrap(), "test");
     }
 
+    #[test]
+    fn test_eval_with_builtins() {
+        let mut engine = JsEngine::new();
+        let builtins = HashMap::from([
+            ("screenWidth".to_string(), "1080".to_string()),
+            ("platform".to_string(), "android".to_string()),
+            ("deviceSerial".to_string(), "00008020".to_string()),
+        ]);
+        engine.set_builtins(&builtins);
+
+        assert_eq!(engine.eval("nl.screenWidth / 2").unwrap(), "540");
+        assert_eq!(engine.eval("nl.platform").unwrap(), "android");
+        assert_eq!(engine.eval("nl.deviceSerial").unwrap(), "00008020");
+        assert_eq!(engine.eval("typeof nl.deviceSerial").unwrap(), "string");
+    }
+
     #[test]
     fn test_assignment() {
         let mut engine = JsEngine::new();
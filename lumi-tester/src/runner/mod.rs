@@ -1,33 +1,176 @@
+pub mod changed;
 pub mod context;
+pub mod dependencies;
 pub mod events;
 pub mod executor;
 pub mod js_engine;
+pub mod progress;
 pub mod shell;
 pub mod state;
 
 use anyhow::Result;
 use colored::Colorize;
+use std::collections::HashSet;
 use std::path::{Path, PathBuf};
 
+use crate::driver::traits::RecordingOptions;
+use crate::parser::types::TestCommand;
+
 pub use events::*;
 pub use state::*;
 
+/// Browser options given on the command line; they override the YAML header
+#[derive(Debug, Clone, Default)]
+pub struct WebRunOptions {
+    /// Force a visible browser window even when `LUMI_HEADLESS` is set
+    pub headed: bool,
+    /// Playwright slowMo in milliseconds
+    pub slow_mo_ms: Option<u64>,
+    /// Save a Chrome trace of every flow next to the other run artifacts
+    pub web_trace: bool,
+    /// Attach to this remote browser instead of launching one
+    pub connect_url: Option<String>,
+}
+
+impl WebRunOptions {
+    fn apply(&self, config: &mut crate::driver::web::WebDriverConfig) {
+        if self.headed {
+            config.headless = false;
+        }
+        if self.slow_mo_ms.is_some() {
+            config.slow_mo_ms = self.slow_mo_ms;
+        }
+        if self.connect_url.is_some() {
+            config.connect_url = self.connect_url.clone();
+        }
+        if self.web_trace {
+            config.trace = true;
+        }
+    }
+}
+
+/// Settings of one `lumi-tester run`, shared by every device session
+#[derive(Debug, Clone, Default)]
+pub struct RunOptions {
+    pub continue_on_failure: bool,
+    /// Stop the suite at the first failing file
+    pub fail_fast: bool,
+    pub parallel: bool,
+    pub workers: Option<usize>,
+    pub order: FileOrder,
+    pub record: bool,
+    pub snapshot: bool,
+    pub report: bool,
+    pub events_jsonl: bool,
+    /// Write the combined summary as one JSON line to this file at the end
+    pub json_summary: Option<PathBuf>,
+    pub tags: Option<Vec<String>>,
+    pub command_index: Option<usize>,
+    pub command_name: Option<String>,
+    pub shutdown_simulator: bool,
+    pub web: WebRunOptions,
+    pub recording: RecordingOptions,
+    /// Skip files the progress marker records as passed
+    pub resume: bool,
+    /// Only run files changed since this git ref
+    pub changed_since: Option<String>,
+    pub verbosity: ConsoleVerbosity,
+    /// Android speed profile (`fast`, `normal`, `safe`)
+    pub speed: Option<String>,
+    /// Command timeout for flows without `defaultTimeout`
+    pub default_timeout_ms: Option<u64>,
+    /// Formats written by `report`; all when unset
+    pub report_formats: Option<Vec<String>>,
+    /// Reference directory for `assertScreenshot`
+    pub baselines_dir: Option<PathBuf>,
+    /// Command types reported as skipped instead of run
+    pub skip_commands: Vec<String>,
+    /// Keep only the recordings of failed flows
+    pub record_on_failure: bool,
+    /// Webhook that receives the run summary
+    pub notify: Option<crate::report::notify::NotifyTarget>,
+}
+
+/// State the device sessions of one run share
+#[derive(Clone)]
+struct SuiteState {
+    summaries: SummarySink,
+    progress: progress::RunProgress,
+    dependencies: std::sync::Arc<dependencies::FlowDependencies>,
+    /// Set by the first failing file under `--fail-fast` so every session stops
+    stopped: std::sync::Arc<std::sync::atomic::AtomicBool>,
+}
+
+/// Order in which collected test files are run (`--order`)
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum FileOrder {
+    /// Sorted by path
+    #[default]
+    Alphabetical,
+    /// Most recently modified first
+    Mtime,
+    /// Shuffled; a fixed seed reproduces the same order
+    Random(Option<u64>),
+}
+
+impl std::str::FromStr for FileOrder {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (name, seed) = match s.split_once(':') {
+            Some((name, seed)) => (name, Some(seed)),
+            None => (s, None),
+        };
+        match (name.trim().to_ascii_lowercase().as_str(), seed) {
+            ("alphabetical" | "alpha" | "path", None) => Ok(Self::Alphabetical),
+            ("mtime", None) => Ok(Self::Mtime),
+            ("random", None) => Ok(Self::Random(None)),
+            ("random", Some(seed)) => seed
+                .trim()
+                .parse()
+                .map(|n| Self::Random(Some(n)))
+                .map_err(|_| anyhow::anyhow!("Invalid random seed: {}", seed)),
+            _ => anyhow::bail!(
+                "Unknown order '{}' (expected alphabetical, mtime or random[:seed])",
+                s
+            ),
+        }
+    }
+}
+
+impl FileOrder {
+    /// Sort `files` in place; returns the seed used for a random order
+    pub fn apply(&self, files: &mut [PathBuf]) -> Option<u64> {
+        use rand::{seq::SliceRandom, SeedableRng};
+
+        // Path order first so ties and shuffles don't depend on the filesystem
+        files.sort();
+        match self {
+            Self::Alphabetical => None,
+            Self::Mtime => {
+                files.sort_by_cached_key(|f| {
+                    std::cmp::Reverse(std::fs::metadata(f).and_then(|m| m.modified()).ok())
+                });
+                None
+            }
+            Self::Random(seed) => {
+                let seed = seed.unwrap_or_else(rand::random);
+                files.shuffle(&mut rand::rngs::StdRng::seed_from_u64(seed));
+                Some(seed)
+            }
+        }
+    }
+}
+
 /// Run tests from a file or directory
 pub async fn run_tests(
     path: &Path,
     platform: &str,
     devices: Option<Vec<String>>,
     output: &Path,
-    continue_on_failure: bool,
-    parallel: bool,
-    record: bool,
-    snapshot: bool,
-    report: bool,
-    events_jsonl: bool,
-    tags: Option<Vec<String>>,
-    command_index: Option<usize>,
-    command_name: Option<String>,
+    options: RunOptions,
 ) -> Result<()> {
+    let quiet = options.verbosity == ConsoleVerbosity::Quiet;
     let platform = platform
         .trim_matches('"')
         .trim_matches('\'')
@@ -89,30 +232,111 @@ pub async fn run_tests(
         all_files.push(path.to_path_buf());
     }
 
+    // Resolved on the full collection so names still match after filtering
+    let dependencies = dependencies::FlowDependencies::resolve(&all_files)?;
+
+    if let Some(git_ref) = &options.changed_since {
+        let changed = changed::changed_files(path, git_ref)?;
+        let base_dir = if path.is_dir() {
+            path
+        } else {
+            path.parent().unwrap_or(Path::new("."))
+        };
+        let hooks: Vec<PathBuf> = ["setup.yaml", "setup.yml", "teardown.yaml", "teardown.yml"]
+            .iter()
+            .map(|f| base_dir.join(f))
+            .collect();
+        let before = all_files.len();
+        all_files = changed::filter_changed(all_files, &hooks, &changed);
+        if !quiet {
+            println!(
+                "{} Changed since {}: running {} of {} flow(s)",
+                "🔍".cyan(),
+                git_ref,
+                all_files.len(),
+                before
+            );
+        }
+    }
+
+    let progress = progress::ProgressMarker::start(output, options.resume)?;
+    if options.resume {
+        let before = all_files.len();
+        all_files.retain(|file| !progress.is_passed(file));
+        if !quiet {
+            println!(
+                "{} Resuming from {}: skipping {} already passed flow(s), {} left",
+                "⏩".cyan(),
+                progress.path().display(),
+                before - all_files.len(),
+                all_files.len()
+            );
+        }
+    }
+    let progress: progress::RunProgress = std::sync::Arc::new(std::sync::Mutex::new(progress));
+
     if all_files.is_empty() {
-        println!("{} No test files found.", "ℹ".blue());
+        if !quiet {
+            println!("{} No test files found.", "ℹ".blue());
+        }
+        if let Some(file) = &options.json_summary {
+            write_json_summary(file, &SummarySink::default(), true)?;
+        }
         return Ok(());
     }
+    if let Some(seed) = options.order.apply(&mut all_files) {
+        if !quiet {
+            println!(
+                "{} Random file order (reproduce with --order random:{})",
+                "🔀".yellow(),
+                seed
+            );
+        }
+    }
+    if !dependencies.is_empty() {
+        all_files = dependencies.sort(all_files)?;
+    }
+    let summaries = SummarySink::default();
+    let suite = SuiteState {
+        summaries: summaries.clone(),
+        progress,
+        dependencies: std::sync::Arc::new(dependencies),
+        stopped: Default::default(),
+    };
 
     // 3. Execution logic
-    if parallel && device_serials.len() > 1 {
-        println!(
-            "{} Parallel execution enabled across {} devices",
-            "🚀".yellow(),
-            device_serials.len()
+    let workers = options.workers.filter(|&n| n > 0);
+    let result = if (options.parallel && device_serials.len() > 1) || workers.is_some_and(|n| n > 1)
+    {
+        // Only browsers can host several sessions; devices would fight over input
+        let tasks = plan_worker_tasks(
+            &all_files,
+            device_serials.len(),
+            workers,
+            platform == "web",
+            &suite.dependencies,
         );
+        // More tasks than devices means some device hosts several sessions
+        let shared_devices = tasks.len() > device_serials.len();
+        let limit = workers.unwrap_or(tasks.len());
+        if !quiet {
+            println!(
+                "{} Parallel execution enabled across {} devices ({} tasks, {} at a time)",
+                "🚀".yellow(),
+                device_serials.len(),
+                tasks.len(),
+                limit
+            );
+        }
 
-        let chunk_size = (all_files.len() as f64 / device_serials.len() as f64).ceil() as usize;
-        let chunks = all_files.chunks(chunk_size);
-
+        let semaphore = std::sync::Arc::new(tokio::sync::Semaphore::new(limit));
         let mut handles = Vec::new();
         let path_owned = path.to_path_buf();
         let platform_owned = platform.clone();
-        let output_owned = Some(output.to_path_buf());
+        let started_at = std::time::SystemTime::now();
 
-        for (i, chunk) in chunks.enumerate() {
-            let device = device_serials[i].clone();
-            let files = chunk.to_vec();
+        for (i, (device_index, files)) in tasks.into_iter().enumerate() {
+            let device = device_serials[device_index].clone();
 
             // Auto-detect platform from device ID if possible
             let mut device_platform = platform_owned.clone();
@@ -129,39 +353,65 @@ pub async fn run_tests(
                 }
             }
 
-            let output = output_owned.clone();
+            // Sessions sharing a device get their own directory so reports don't collide
+            let output = if shared_devices {
+                output.join(format!("worker-{}", i + 1))
+            } else {
+                output.to_path_buf()
+            };
+            let semaphore = semaphore.clone();
             let base_path = path_owned.clone();
-            let tags_chunk = tags.clone();
-            let cmd_idx = command_index;
-            let cmd_name = command_name.clone();
+            let options = options.clone();
+            let suite = suite.clone();
 
             let handle = tokio::spawn(async move {
+                let _permit = semaphore.acquire_owned().await?;
+                // Another task already failed under --fail-fast; don't start this one
+                if suite.stopped.load(std::sync::atomic::Ordering::Relaxed) {
+                    return Ok(());
+                }
                 run_on_device(
                     &base_path,
                     &files,
                     &device_platform,
                     Some(&device),
-                    output.as_deref(),
-                    continue_on_failure,
-                    record,
-                    snapshot,
-                    report,
-                    events_jsonl,
-                    tags_chunk,
-                    cmd_idx,
-                    cmd_name,
+                    Some(&output),
+                    &options,
+                    suite,
                 )
                 .await
             });
             handles.push(handle);
         }
 
+        let mut failed_tasks = 0;
         for handle in handles {
-            let _ = handle.await?;
+            if handle.await?.is_err() {
+                failed_tasks += 1;
+            }
+        }
+
+        if !quiet {
+            println!("{} All parallel test tasks finished.", "✅".green());
         }
 
-        println!("{} All parallel test tasks finished.", "✅".green());
-        Ok(())
+        // Each device writes its own report; combine them so CI gets one artifact
+        if options.report {
+            crate::report::write_merged_report(
+                output,
+                started_at,
+                options.report_formats.as_deref(),
+            )
+            .await?;
+        }
+        if failed_tasks > 0 {
+            Err(anyhow::anyhow!(
+                "Tests failed in {} parallel task(s)",
+                failed_tasks
+            ))
+        } else {
+            Ok(())
+        }
     } else {
         // Sequential run on primary device (or all files on one device)
         let primary_device = device_serials.first().map(|s| s.as_str());
@@ -171,17 +421,163 @@ pub async fn run_tests(
             &platform,
             primary_device,
             Some(output),
-            continue_on_failure,
-            record,
-            snapshot,
-            report,
-            events_jsonl,
-            tags,
-            command_index,
-            command_name,
+            &options,
+            suite,
         )
         .await
+    };
+
+    if let Some(file) = &options.json_summary {
+        write_json_summary(file, &summaries, result.is_ok())?;
     }
+    let summary = TestSummary::merge(&summaries.lock().unwrap());
+    crate::report::notify::notify(
+        options.notify.as_ref(),
+        &summary,
+        result.is_ok(),
+        output,
+        options.report,
+    )
+    .await;
+    result
+}
+
+/// Write the combined session summary as one JSON line for CI (`--json-summary`).
+/// `-` prints it on stdout, which main has already freed of console output.
+fn write_json_summary(file: &Path, summaries: &SummarySink, passed: bool) -> Result<()> {
+    use anyhow::Context;
+
+    let summary = TestSummary::merge(&summaries.lock().unwrap());
+    let mut value = serde_json::to_value(&summary)?;
+    value["status"] = serde_json::json!(if passed { "passed" } else { "failed" });
+    if file == Path::new("-") {
+        return crate::utils::console::write_machine_line(&serde_json::to_string(&value)?)
+            .context("Failed to print JSON summary");
+    }
+    if let Some(dir) = file.parent().filter(|d| !d.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(file, format!("{}\n", serde_json::to_string(&value)?))
+        .with_context(|| format!("Failed to write JSON summary to {}", file.display()))
+}
+
+/// Split `files` into parallel tasks and pick a device index for each.
+///
+/// Without `workers` every device gets one chunk. With `workers` on web the files
+/// are split into `max(workers, devices)` chunks handed out round-robin, so one
+/// browser hosts several sessions while the semaphore caps how many run at once.
+/// A phone or simulator runs one session at a time, so elsewhere there is never
+/// more than one chunk per device. Files linked by `dependsOn` always land in the
+/// same chunk, in order.
+fn plan_worker_tasks(
+    files: &[PathBuf],
+    device_count: usize,
+    workers: Option<usize>,
+    shared_sessions: bool,
+    dependencies: &dependencies::FlowDependencies,
+) -> Vec<(usize, Vec<PathBuf>)> {
+    let task_count = workers
+        .filter(|_| shared_sessions)
+        .map_or(device_count, |n| n.max(device_count))
+        .clamp(1, files.len().max(1));
+    let chunk_size = files.len().div_ceil(task_count).max(1);
+    let mut chunks: Vec<Vec<PathBuf>> = Vec::new();
+    for group in dependencies.groups(files) {
+        match chunks.last_mut() {
+            Some(chunk) if chunk.len() < chunk_size => chunk.extend(group),
+            _ => chunks.push(group),
+        }
+    }
+    chunks
+        .into_iter()
+        .enumerate()
+        .map(|(i, chunk)| (i % device_count.max(1), chunk))
+        .collect()
+}
+
+/// Directory holding the `setup`/`teardown` hooks of a run over `base_path`
+fn hook_dir(base_path: &Path) -> &Path {
+    if base_path.is_dir() {
+        base_path
+    } else {
+        base_path.parent().unwrap_or(Path::new("."))
+    }
+}
+
+/// Whether the flow file at `path`, or any subflow it runs, has a command
+/// matching `pred`. Subflow paths resolve like `runFlow` does, against the
+/// directory of the file the run started from; ones with `${vars}` can't be
+/// followed. Each file is read once.
+fn file_uses_command(
+    path: &Path,
+    pred: &dyn Fn(&TestCommand) -> bool,
+    seen: &mut HashSet<PathBuf>,
+) -> bool {
+    let base_dir = path.parent().unwrap_or(Path::new("."));
+    subflow_uses_command(path, base_dir, pred, seen)
+}
+
+fn subflow_uses_command(
+    path: &Path,
+    base_dir: &Path,
+    pred: &dyn Fn(&TestCommand) -> bool,
+    seen: &mut HashSet<PathBuf>,
+) -> bool {
+    if !seen.insert(path.to_path_buf()) {
+        return false;
+    }
+    let Ok(flow) = crate::parser::yaml::parse_test_file(path) else {
+        return false;
+    };
+    commands_use_command(&flow.commands, base_dir, pred, seen)
+}
+
+fn commands_use_command(
+    commands: &[TestCommand],
+    base_dir: &Path,
+    pred: &dyn Fn(&TestCommand) -> bool,
+    seen: &mut HashSet<PathBuf>,
+) -> bool {
+    use crate::parser::yaml::parse_commands_from_value;
+
+    let uses_file = |path: &str, seen: &mut HashSet<PathBuf>| {
+        !path.contains("${") && subflow_uses_command(&base_dir.join(path), base_dir, pred, seen)
+    };
+    let uses_value = |value: &serde_yaml::Value, seen: &mut HashSet<PathBuf>| {
+        parse_commands_from_value(value)
+            .is_ok_and(|nested| commands_use_command(&nested, base_dir, pred, seen))
+    };
+    commands.iter().any(|command| {
+        pred(command)
+            || match command {
+                TestCommand::Repeat(p) => commands_use_command(&p.commands, base_dir, pred, seen),
+                TestCommand::Retry(p) => commands_use_command(&p.commands, base_dir, pred, seen),
+                TestCommand::AssertAll(p) => {
+                    commands_use_command(&p.assertions, base_dir, pred, seen)
+                }
+                TestCommand::WaitForAny(p) => p
+                    .options
+                    .iter()
+                    .any(|o| commands_use_command(&o.then, base_dir, pred, seen)),
+                TestCommand::ForEach(p) => {
+                    commands_use_command(&p.commands, base_dir, pred, seen)
+                        || p.flow.as_deref().is_some_and(|f| uses_file(f, seen))
+                }
+                TestCommand::RunFlow(input) => {
+                    let params = input.clone().into_inner();
+                    params
+                        .commands
+                        .as_deref()
+                        .is_some_and(|c| commands_use_command(c, base_dir, pred, seen))
+                        || params.path.as_deref().is_some_and(|f| uses_file(f, seen))
+                }
+                TestCommand::Conditional(p) => {
+                    uses_value(&p.then, seen)
+                        || p.else_cmd.as_ref().is_some_and(|e| uses_value(e, seen))
+                }
+                _ => false,
+            }
+    })
 }
 
 /// Run a set of files on a specific device
@@ -191,14 +587,8 @@ async fn run_on_device(
     platform: &str,
     device: Option<&str>,
     output: Option<&Path>,
-    continue_on_failure: bool,
-    record: bool,
-    snapshot: bool,
-    report: bool,
-    events_jsonl: bool,
-    tags: Option<Vec<String>>,
-    command_index: Option<usize>,
-    command_name: Option<String>,
+    options: &RunOptions,
+    suite: SuiteState,
 ) -> Result<()> {
     // Pre-parse first file to extract web driver config (for close_when_finish support)
     let web_config = if platform == "web" && !files.is_empty() {
@@ -214,6 +604,10 @@ async fn run_on_device(
                 config.close_when_finish = close;
             }
 
+            if flow.connect_url.is_some() {
+                config.connect_url = flow.connect_url.clone();
+            }
+
             // Apply browser type if specified
             if let Some(ref b) = flow.browser {
                 config.browser_type = match b.to_lowercase().as_str() {
@@ -222,6 +616,19 @@ async fn run_on_device(
                     _ => BrowserType::Chromium,
                 };
             }
+            // Only open a debugging port when some flow, subflow or hook will
+            // throttle the network
+            let is_throttle = |c: &TestCommand| matches!(c, TestCommand::SetNetworkConditions(_));
+            let mut seen = HashSet::new();
+            let hooks = ["setup.yaml", "setup.yml", "teardown.yaml", "teardown.yml"]
+                .iter()
+                .map(|f| hook_dir(base_path).join(f))
+                .filter(|p| p.exists());
+            config.network_emulation = files
+                .iter()
+                .cloned()
+                .chain(hooks)
+                .any(|f| file_uses_command(&f, &is_throttle, &mut seen));
             Some(config)
         } else {
             None
@@ -236,17 +643,37 @@ async fn run_on_device(
         .trim_matches('\'')
         .to_ascii_lowercase();
 
+    let speed = options
+        .speed
+        .as_deref()
+        .map(crate::driver::android::driver::SpeedProfile::from_str);
     let driver: Box<dyn crate::driver::traits::PlatformDriver> = match platform_clean.as_str() {
-        "android" => Box::new(crate::driver::android::AndroidDriver::new(device).await?),
+        "android" => {
+            let mut driver = crate::driver::android::AndroidDriver::new(device).await?;
+            if let Some(profile) = speed {
+                driver.set_speed_profile(profile);
+            }
+            Box::new(driver)
+        }
         "android_auto" => {
-            Box::new(crate::driver::android_auto::AndroidAutoDriver::new(device, true).await?)
+            let mut driver =
+                crate::driver::android_auto::AndroidAutoDriver::new(device, true).await?;
+            if let Some(profile) = speed {
+                driver.set_speed_profile(profile);
+            }
+            Box::new(driver)
         }
         "web" => {
             use crate::driver::web::{WebDriver, WebDriverConfig};
-            let config = web_config.unwrap_or_else(WebDriverConfig::default);
+            let mut config = web_config.unwrap_or_else(WebDriverConfig::default);
+            options.web.apply(&mut config);
             Box::new(WebDriver::new(config).await?)
         }
-        "ios" => Box::new(crate::driver::ios::IosDriver::new(device).await?),
+        "ios" => Box::new(
+            crate::driver::ios::IosDriver::new(device)
+                .await?
+                .with_shutdown_on_finish(options.shutdown_simulator),
+        ),
         "macos" => Box::new(crate::driver::macos::MacosDriver::new()),
         "windows" => Box::new(crate::driver::windows::WindowsDriver::new()),
         _ => anyhow::bail!("Unknown platform: {}", platform_clean),
@@ -255,18 +682,26 @@ async fn run_on_device(
     let mut executor = executor::TestExecutor::new_with_events(
         driver,
         output,
-        continue_on_failure,
-        record,
-        snapshot,
-        report,
-        tags,
-        events_jsonl,
-    );
-    let base_dir = if base_path.is_dir() {
-        base_path
-    } else {
-        base_path.parent().unwrap_or(Path::new("."))
-    };
+        options.continue_on_failure,
+        options.record,
+        options.snapshot,
+        options.report,
+        options.tags.clone(),
+        options.events_jsonl,
+        options.verbosity,
+    )
+    .with_recording_options(options.recording.clone())
+    .with_skip_commands(options.skip_commands.clone())
+    .with_record_on_failure(options.record_on_failure)
+    .with_report_formats(options.report_formats.clone())
+    .with_baselines_dir(options.baselines_dir.clone())
+    .with_default_timeout(
+        options
+            .default_timeout_ms
+            .unwrap_or(context::DEFAULT_TIMEOUT_MS),
+    )
+    .with_summary_sink(suite.summaries);
+    let base_dir = hook_dir(base_path);
 
     // 1. Run Setup hook
     for f in ["setup.yaml", "setup.yml"] {
@@ -280,17 +715,69 @@ async fn run_on_device(
         }
     }
 
-    // 2. Run Main files
+    // 2. Run Main files; a failing file doesn't stop the rest unless fail-fast
+    let mut failed_files = Vec::new();
+    executor.emit(TestEvent::SuiteStarted {
+        total_flows: files.len(),
+    });
+    // Single-command reruns don't prove a whole flow passed
+    let track_progress = options.command_index.is_none() && options.command_name.is_none();
+    // Files that failed or were skipped; their dependents are skipped too
+    let mut not_passed: HashSet<&PathBuf> = HashSet::new();
     for file in files {
-        if let Err(e) = executor
-            .run_file(file, command_index, command_name.as_deref())
-            .await
-        {
-            let _ = executor.finish().await;
-            return Err(e);
+        if suite.stopped.load(std::sync::atomic::Ordering::Relaxed) {
+            break;
+        }
+        let blocked = suite
+            .dependencies
+            .prerequisites(file)
+            .iter()
+            .find(|p| not_passed.contains(p));
+        if let Some(prerequisite) = blocked {
+            let reason = format!("Prerequisite {} did not pass", prerequisite.display());
+            if options.verbosity != ConsoleVerbosity::Quiet {
+                println!("{} {}: {}", "○".yellow(), file.display(), reason);
+            }
+            executor.skip_file(file, &reason);
+            not_passed.insert(file);
+            continue;
+        }
+
+        let failed_before = executor.failed_commands();
+        let result = executor
+            .run_file(file, options.command_index, options.command_name.as_deref())
+            .await;
+        let passed = result.is_ok() && executor.failed_commands() == failed_before;
+        if !passed {
+            not_passed.insert(file);
+        }
+        if track_progress {
+            if let Err(e) = suite.progress.lock().unwrap().record(file, passed) {
+                println!("{} Could not update progress marker: {}", "⚠️".yellow(), e);
+            }
+        }
+        if let Err(e) = result {
+            if options.fail_fast {
+                suite
+                    .stopped
+                    .store(true, std::sync::atomic::Ordering::Relaxed);
+                let _ = executor.finish().await;
+                return Err(e);
+            }
+            if options.verbosity != ConsoleVerbosity::Quiet {
+                println!(
+                    "{} {} failed: {} (continuing with remaining files)",
+                    "✗".red(),
+                    file.display(),
+                    e
+                );
+            }
+            failed_files.push(file.display().to_string());
         }
     }
 
+    executor.emit(TestEvent::SuiteFinished);
+
     // 3. Run Teardown hook
     for f in ["teardown.yaml", "teardown.yml"] {
         let p = base_dir.join(f);
@@ -303,5 +790,131 @@ async fn run_on_device(
         }
     }
 
-    executor.finish().await
+    executor.finish().await?;
+
+    if !failed_files.is_empty() {
+        anyhow::bail!(
+            "{} of {} test files failed:\n  {}",
+            failed_files.len(),
+            files.len(),
+            failed_files.join("\n  ")
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn files(n: usize) -> Vec<PathBuf> {
+        (0..n)
+            .map(|i| PathBuf::from(format!("{}.yaml", i)))
+            .collect()
+    }
+
+    #[test]
+    fn test_plan_worker_tasks() {
+        let no_deps = dependencies::FlowDependencies::default();
+        // One chunk per device by default
+        let tasks = plan_worker_tasks(&files(5), 2, None, false, &no_deps);
+        assert_eq!(tasks.len(), 2);
+        assert_eq!(tasks[0].1.len(), 3);
+        assert_eq!(tasks[1].0, 1);
+
+        // Four workers on two browsers: two sessions per browser
+        let tasks = plan_worker_tasks(&files(8), 2, Some(4), true, &no_deps);
+        let devices: Vec<usize> = tasks.iter().map(|(d, _)| *d).collect();
+        assert_eq!(devices, vec![0, 1, 0, 1]);
+        assert!(tasks.iter().all(|(_, f)| f.len() == 2));
+
+        // A phone or simulator never hosts two sessions
+        assert_eq!(
+            plan_worker_tasks(&files(8), 2, Some(4), false, &no_deps).len(),
+            2
+        );
+
+        // Never more tasks than files
+        assert_eq!(
+            plan_worker_tasks(&files(2), 1, Some(8), true, &no_deps).len(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_file_uses_command_follows_subflows() {
+        let dir = std::env::temp_dir().join(format!("lumi_uses_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(dir.join("flows")).unwrap();
+        let write = |name: &str, body: &str| {
+            std::fs::write(dir.join(name), format!("appId: com.example\n---\n{}", body)).unwrap()
+        };
+        write("main.yaml", "- runFlow: flows/slow.yaml\n");
+        write(
+            "flows/slow.yaml",
+            "- repeat:\n    times: 2\n    commands:\n      - setNetworkConditions: 3g\n",
+        );
+        write(
+            "mentions.yaml",
+            "# setNetworkConditions is not used here\n- inputText: setNetworkConditions\n",
+        );
+
+        let throttles = |c: &TestCommand| matches!(c, TestCommand::SetNetworkConditions(_));
+        let uses = |name: &str| file_uses_command(&dir.join(name), &throttles, &mut HashSet::new());
+        assert!(uses("main.yaml"));
+        assert!(!uses("mentions.yaml"));
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_merge_summaries() {
+        let summary = |id: &str, passed, failed, ms| TestSummary {
+            session_id: id.to_string(),
+            total_flows: 2,
+            total_commands: passed + failed,
+            passed,
+            failed,
+            skipped: 0,
+            total_duration_ms: Some(ms),
+            failed_flows: if failed > 0 {
+                vec![format!("{}-flow", id)]
+            } else {
+                Vec::new()
+            },
+        };
+        let merged = TestSummary::merge(&[summary("a", 5, 0, 900), summary("b", 3, 1, 1500)]);
+        assert_eq!(merged.session_id, "a,b");
+        assert_eq!(merged.total_flows, 4);
+        assert_eq!((merged.passed, merged.failed), (8, 1));
+        assert_eq!(merged.failed_flows, vec!["b-flow".to_string()]);
+        assert_eq!(merged.total_duration_ms, Some(1500));
+
+        let empty = TestSummary::merge(&[]);
+        assert_eq!(empty.total_commands, 0);
+        assert_eq!(empty.total_duration_ms, None);
+    }
+
+    #[test]
+    fn test_file_order() {
+        assert_eq!("mtime".parse::<FileOrder>().unwrap(), FileOrder::Mtime);
+        assert_eq!(
+            "random:42".parse::<FileOrder>().unwrap(),
+            FileOrder::Random(Some(42))
+        );
+        assert!("random:abc".parse::<FileOrder>().is_err());
+        assert!("size".parse::<FileOrder>().is_err());
+
+        let mut sorted = files(10);
+        sorted.reverse();
+        assert_eq!(FileOrder::Alphabetical.apply(&mut sorted), None);
+        assert_eq!(sorted[0], PathBuf::from("0.yaml"));
+
+        // Same seed, same order, whatever the starting order
+        let mut a = files(10);
+        let mut b = files(10);
+        b.reverse();
+        assert_eq!(FileOrder::Random(Some(7)).apply(&mut a), Some(7));
+        FileOrder::Random(Some(7)).apply(&mut b);
+        assert_eq!(a, b);
+        assert_ne!(a, files(10));
+    }
 }
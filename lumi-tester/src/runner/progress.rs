@@ -0,0 +1,125 @@
+//! Progress marker for resuming interrupted suites (`--resume`)
+//!
+//! Every finished flow file is recorded in `<output>/progress.json` as soon as its
+//! result is known, so a crash or Ctrl+C leaves a marker that matches the flows
+//! that actually completed. `--resume` skips the files recorded as passed.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+
+pub const PROGRESS_FILE: &str = "progress.json";
+
+/// Shared by parallel sessions writing the same marker
+pub type RunProgress = std::sync::Arc<std::sync::Mutex<ProgressMarker>>;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProgressMarker {
+    /// Flow files (absolute paths) whose commands all passed
+    #[serde(default)]
+    pub passed: BTreeSet<String>,
+    /// Flow files that failed; rerun on resume
+    #[serde(default)]
+    pub failed: BTreeSet<String>,
+    #[serde(skip)]
+    path: PathBuf,
+}
+
+impl ProgressMarker {
+    /// Load the marker in `output` when resuming, otherwise start a fresh one
+    pub fn start(output: &Path, resume: bool) -> Result<Self> {
+        let path = output.join(PROGRESS_FILE);
+        let mut marker = if resume && path.is_file() {
+            let content = std::fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read {}", path.display()))?;
+            serde_json::from_str(&content)
+                .with_context(|| format!("Invalid progress marker {}", path.display()))?
+        } else {
+            // Drop a stale marker so an interrupted fresh run can't resume from it
+            std::fs::remove_file(&path).ok();
+            ProgressMarker::default()
+        };
+        marker.path = path;
+        Ok(marker)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn is_passed(&self, file: &Path) -> bool {
+        self.passed.contains(&key(file))
+    }
+
+    /// Record a finished flow file and persist the marker
+    pub fn record(&mut self, file: &Path, passed: bool) -> Result<()> {
+        let key = key(file);
+        if passed {
+            self.failed.remove(&key);
+            self.passed.insert(key);
+        } else {
+            self.passed.remove(&key);
+            self.failed.insert(key);
+        }
+        self.save()
+    }
+
+    /// Write through a temp file so an interrupted write never leaves half a marker
+    fn save(&self) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let tmp = self.path.with_extension("json.tmp");
+        std::fs::write(&tmp, serde_json::to_string_pretty(self)?)?;
+        std::fs::rename(&tmp, &self.path)
+            .with_context(|| format!("Failed to write {}", self.path.display()))
+    }
+}
+
+/// Flow files are keyed by absolute path so relative and absolute invocations match
+fn key(file: &Path) -> String {
+    std::fs::canonicalize(file)
+        .or_else(|_| std::path::absolute(file))
+        .unwrap_or_else(|_| file.to_path_buf())
+        .display()
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_progress_marker_round_trip() {
+        let out = std::env::temp_dir().join(format!("lumi-progress-{}", uuid::Uuid::new_v4()));
+        let login = out.join("login.yaml");
+        let checkout = out.join("checkout.yaml");
+        std::fs::create_dir_all(&out).unwrap();
+        std::fs::write(&login, "- back\n").unwrap();
+        std::fs::write(&checkout, "- back\n").unwrap();
+
+        let mut marker = ProgressMarker::start(&out, false).unwrap();
+        marker.record(&login, true).unwrap();
+        marker.record(&checkout, false).unwrap();
+
+        let resumed = ProgressMarker::start(&out, true).unwrap();
+        assert!(resumed.is_passed(&login));
+        assert!(!resumed.is_passed(&checkout));
+        assert_eq!(resumed.failed.len(), 1);
+
+        // A fresh run ignores the old marker
+        assert!(ProgressMarker::start(&out, false)
+            .unwrap()
+            .passed
+            .is_empty());
+
+        // A later pass moves the flow out of the failed set
+        let mut resumed = resumed;
+        resumed.record(&checkout, true).unwrap();
+        assert!(resumed.failed.is_empty());
+
+        std::fs::remove_dir_all(&out).ok();
+    }
+}
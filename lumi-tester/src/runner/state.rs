@@ -131,6 +131,36 @@ pub struct FlowState {
     pub total_duration_ms: Option<u64>,
     pub error: Option<String>,
     pub video_path: Option<String>,
+    /// Device serial/UDID the flow ran on
+    pub device: Option<String>,
+    /// Tags, title and description from the flow header
+    pub metadata: FlowMetadata,
+    /// Unique per run, so nested flows can point at their parent
+    pub id: String,
+    /// Nesting level: 0 for a test file, +1 per runFlow/repeat/retry/forEach/waitForAny
+    pub depth: usize,
+    /// Flow and command that started this nested flow
+    pub parent: Option<FlowParent>,
+}
+
+/// Where a nested flow was started from
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct FlowParent {
+    pub flow_id: String,
+    pub command_index: usize,
+}
+
+/// Flow header information carried into reports
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct FlowMetadata {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -140,7 +170,14 @@ pub enum FlowStatus {
     Running,
     Passed,
     Failed,
-    PartiallyPassed { passed: u32, failed: u32 },
+    PartiallyPassed {
+        passed: u32,
+        failed: u32,
+    },
+    /// Not run, e.g. because a `dependsOn` prerequisite did not pass
+    Skipped {
+        reason: String,
+    },
 }
 
 impl FlowState {
@@ -156,6 +193,11 @@ impl FlowState {
             total_duration_ms: None,
             error: None,
             video_path: None,
+            device: None,
+            metadata: FlowMetadata::default(),
+            id: uuid::Uuid::new_v4().to_string(),
+            depth: 0,
+            parent: None,
         }
     }
 
@@ -215,6 +257,12 @@ impl FlowState {
             total_duration_ms: self.total_duration_ms,
             error: self.error.clone(),
             video_path: self.video_path.clone(),
+            device: self.device.clone(),
+            metadata: self.metadata.clone(),
+            id: self.id.clone(),
+            depth: self.depth,
+            parent: self.parent.clone(),
+            nested_duration_ms: None,
         }
     }
 }
@@ -229,6 +277,20 @@ pub struct FlowStateReport {
     pub total_duration_ms: Option<u64>,
     pub error: Option<String>,
     pub video_path: Option<String>,
+    /// Device that ran the flow
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub device: Option<String>,
+    #[serde(flatten, default)]
+    pub metadata: FlowMetadata,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub id: String,
+    #[serde(default)]
+    pub depth: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent: Option<FlowParent>,
+    /// Time spent in the nested flows this flow started (already part of its total)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nested_duration_ms: Option<u64>,
 }
 
 /// Global test session state
@@ -293,6 +355,18 @@ impl TestSessionState {
                 .as_millis() as u64
         });
 
+        let failed_flows = self
+            .flows
+            .iter()
+            .filter(|flow| {
+                matches!(
+                    flow.status,
+                    FlowStatus::Failed | FlowStatus::PartiallyPassed { .. }
+                )
+            })
+            .map(|flow| flow.flow_name.clone())
+            .collect();
+
         TestSummary {
             session_id: self.session_id.clone(),
             total_flows: self.flows.len() as u32,
@@ -301,6 +375,7 @@ impl TestSessionState {
             failed,
             skipped,
             total_duration_ms,
+            failed_flows,
         }
     }
 
@@ -308,12 +383,62 @@ impl TestSessionState {
     pub fn to_report(&self) -> TestSessionReport {
         TestSessionReport {
             session_id: self.session_id.clone(),
-            flows: self.flows.iter().map(|f| f.to_report()).collect(),
+            flows: flow_tree(&self.flows),
             summary: self.summary(),
         }
     }
 }
 
+/// Flows in tree order: each flow followed by the nested flows it started, in
+/// command order, with child durations rolled up into `nested_duration_ms`.
+/// Nested flows finish (and are recorded) before their parent.
+fn flow_tree(flows: &[FlowState]) -> Vec<FlowStateReport> {
+    fn visit(index: usize, flows: &[FlowState], out: &mut Vec<FlowStateReport>) {
+        let flow = &flows[index];
+        let children: Vec<usize> = {
+            let mut children: Vec<usize> = (0..flows.len())
+                .filter(|&i| {
+                    flows[i]
+                        .parent
+                        .as_ref()
+                        .is_some_and(|p| p.flow_id == flow.id)
+                })
+                .collect();
+            // Stable: attempts of the same command keep their run order
+            children.sort_by_key(|&i| flows[i].parent.as_ref().map(|p| p.command_index));
+            children
+        };
+
+        let mut report = flow.to_report();
+        if !children.is_empty() {
+            report.nested_duration_ms = Some(
+                children
+                    .iter()
+                    .filter_map(|&i| flows[i].total_duration_ms)
+                    .sum(),
+            );
+        }
+        out.push(report);
+        for child in children {
+            visit(child, flows, out);
+        }
+    }
+
+    let ids: std::collections::HashSet<&str> = flows.iter().map(|f| f.id.as_str()).collect();
+    let mut out = Vec::with_capacity(flows.len());
+    for (index, flow) in flows.iter().enumerate() {
+        // Roots, plus nested flows whose parent never got recorded
+        let orphan = flow
+            .parent
+            .as_ref()
+            .is_none_or(|p| !ids.contains(p.flow_id.as_str()));
+        if orphan {
+            visit(index, flows, &mut out);
+        }
+    }
+    out
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct TestSummary {
@@ -324,6 +449,35 @@ pub struct TestSummary {
     pub failed: u32,
     pub skipped: u32,
     pub total_duration_ms: Option<u64>,
+    /// Names of flows with at least one failed command
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub failed_flows: Vec<String>,
+}
+
+/// Where parallel sessions drop their summaries for the runner to combine
+pub type SummarySink = std::sync::Arc<std::sync::Mutex<Vec<TestSummary>>>;
+
+impl TestSummary {
+    /// Combine the summaries of parallel sessions; the duration is the longest one
+    pub fn merge(summaries: &[TestSummary]) -> TestSummary {
+        TestSummary {
+            session_id: summaries
+                .iter()
+                .map(|s| s.session_id.as_str())
+                .collect::<Vec<_>>()
+                .join(","),
+            total_flows: summaries.iter().map(|s| s.total_flows).sum(),
+            total_commands: summaries.iter().map(|s| s.total_commands).sum(),
+            passed: summaries.iter().map(|s| s.passed).sum(),
+            failed: summaries.iter().map(|s| s.failed).sum(),
+            skipped: summaries.iter().map(|s| s.skipped).sum(),
+            total_duration_ms: summaries.iter().filter_map(|s| s.total_duration_ms).max(),
+            failed_flows: summaries
+                .iter()
+                .flat_map(|s| s.failed_flows.iter().cloned())
+                .collect(),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -333,3 +487,56 @@ pub struct TestSessionReport {
     pub flows: Vec<FlowStateReport>,
     pub summary: TestSummary,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn flow(name: &str, parent: Option<(&FlowState, usize)>, ms: u64) -> FlowState {
+        let mut flow = FlowState::new(name, "", Vec::new());
+        flow.total_duration_ms = Some(ms);
+        if let Some((parent, command_index)) = parent {
+            flow.depth = parent.depth + 1;
+            flow.parent = Some(FlowParent {
+                flow_id: parent.id.clone(),
+                command_index,
+            });
+        }
+        flow
+    }
+
+    #[test]
+    fn test_report_nests_flows_and_rolls_up_durations() {
+        let login = flow("login", None, 900);
+        let retry_1 = flow("Retry attempt #1", Some((&login, 3)), 200);
+        let retry_2 = flow("Retry attempt #2", Some((&login, 3)), 250);
+        let subflow = flow("otp.yaml", Some((&login, 1)), 300);
+        let inner = flow("Repeat #1", Some((&subflow, 0)), 120);
+        let checkout = flow("checkout", None, 400);
+
+        let mut session = TestSessionState::new("s");
+        // Recorded in completion order: children before parents
+        for f in [inner, subflow, retry_1, retry_2, login, checkout] {
+            session.add_flow(f);
+        }
+
+        let report = session.to_report();
+        let order: Vec<(&str, usize, Option<u64>)> = report
+            .flows
+            .iter()
+            .map(|f| (f.flow_name.as_str(), f.depth, f.nested_duration_ms))
+            .collect();
+        assert_eq!(
+            order,
+            vec![
+                ("login", 0, Some(750)),
+                ("otp.yaml", 1, Some(120)),
+                ("Repeat #1", 2, None),
+                ("Retry attempt #1", 1, None),
+                ("Retry attempt #2", 1, None),
+                ("checkout", 0, None),
+            ]
+        );
+        assert_eq!(report.flows[1].parent.as_ref().unwrap().command_index, 1);
+    }
+}
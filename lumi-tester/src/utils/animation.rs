@@ -0,0 +1,185 @@
+//! Animated capture encoding (GIF, WebP, APNG)
+//!
+//! `buildGif` and `stopGifCapture` collect RGBA frames and hand them here.
+//! GIF goes through the `image` crate, APNG through the `png` crate, and
+//! animated WebP is produced by ffmpeg (`libwebp_anim`) from an APNG
+//! intermediate so per-frame delays survive the conversion.
+
+use crate::parser::types::AnimationFormat;
+use anyhow::{Context, Result};
+use image::RgbaImage;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+
+/// A processed frame and how long it stays on screen (ms)
+pub type AnimationFrame = (RgbaImage, u32);
+
+/// Encode `frames` into `path`.
+///
+/// `loop_count` of `None` loops forever; `quality` is "low", "medium" or "high".
+pub fn write_animation(
+    path: &Path,
+    frames: &[AnimationFrame],
+    format: AnimationFormat,
+    loop_count: Option<u16>,
+    quality: &str,
+) -> Result<()> {
+    if frames.is_empty() {
+        anyhow::bail!("No frames to encode");
+    }
+    match format {
+        AnimationFormat::Gif => write_gif(path, frames, loop_count, quality),
+        AnimationFormat::Apng => write_apng(path, frames, loop_count, quality),
+        AnimationFormat::Webp => write_webp(path, frames, loop_count, quality),
+    }
+}
+
+fn write_gif(
+    path: &Path,
+    frames: &[AnimationFrame],
+    loop_count: Option<u16>,
+    quality: &str,
+) -> Result<()> {
+    use image::codecs::gif::{GifEncoder, Repeat};
+    use image::{Delay, Frame};
+
+    let speed = match quality {
+        "high" => 1,
+        "low" => 30,
+        _ => 10, // medium
+    };
+
+    let file = File::create(path)?;
+    let mut encoder = GifEncoder::new_with_speed(file, speed);
+    encoder.set_repeat(match loop_count {
+        Some(n) => Repeat::Finite(n),
+        None => Repeat::Infinite,
+    })?;
+
+    for (img, delay_ms) in frames {
+        let frame = Frame::from_parts(img.clone(), 0, 0, Delay::from_numer_denom_ms(*delay_ms, 1));
+        encoder.encode_frame(frame)?;
+    }
+    Ok(())
+}
+
+fn write_apng(
+    path: &Path,
+    frames: &[AnimationFrame],
+    loop_count: Option<u16>,
+    quality: &str,
+) -> Result<()> {
+    let (width, height) = frames[0].0.dimensions();
+
+    let file = BufWriter::new(File::create(path)?);
+    let mut encoder = png::Encoder::new(file, width, height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.set_compression(match quality {
+        "high" => png::Compression::Best,
+        "low" => png::Compression::Fast,
+        _ => png::Compression::Default,
+    });
+    // APNG uses 0 plays for "loop forever"
+    encoder.set_animated(frames.len() as u32, loop_count.unwrap_or(0) as u32)?;
+
+    let mut writer = encoder.write_header()?;
+    for (img, delay_ms) in frames {
+        // Every APNG frame must match the canvas, crops can differ by a pixel
+        let resized;
+        let img = if img.dimensions() == (width, height) {
+            img
+        } else {
+            resized =
+                image::imageops::resize(img, width, height, image::imageops::FilterType::Lanczos3);
+            &resized
+        };
+        writer.set_frame_delay((*delay_ms).min(u16::MAX as u32) as u16, 1000)?;
+        writer.write_image_data(img.as_raw())?;
+    }
+    writer.finish()?;
+    Ok(())
+}
+
+fn write_webp(
+    path: &Path,
+    frames: &[AnimationFrame],
+    loop_count: Option<u16>,
+    quality: &str,
+) -> Result<()> {
+    let ffmpeg = crate::utils::binary_resolver::find_ffmpeg()
+        .context("WebP output needs ffmpeg (with libwebp); use format: apng or gif instead")?;
+
+    let intermediate =
+        std::env::temp_dir().join(format!("lumi_anim_{}.apng", uuid::Uuid::new_v4()));
+    write_apng(&intermediate, frames, loop_count, "low")?;
+
+    let q = match quality {
+        "high" => "90",
+        "low" => "50",
+        _ => "75",
+    };
+    let output = std::process::Command::new(ffmpeg)
+        .args(["-y", "-loglevel", "error", "-i"])
+        .arg(&intermediate)
+        .args(["-vsync", "0", "-c:v", "libwebp_anim", "-q:v", q, "-loop"])
+        .arg(loop_count.unwrap_or(0).to_string())
+        .arg(path)
+        .output();
+    std::fs::remove_file(&intermediate).ok();
+
+    let output = output.context("Failed to run ffmpeg")?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "ffmpeg failed to encode WebP: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frames() -> Vec<AnimationFrame> {
+        vec![
+            (
+                RgbaImage::from_pixel(4, 3, image::Rgba([255, 0, 0, 255])),
+                100,
+            ),
+            (
+                RgbaImage::from_pixel(4, 3, image::Rgba([0, 0, 255, 255])),
+                250,
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_write_apng_keeps_frames_and_loops() {
+        let path = std::env::temp_dir().join(format!("lumi_test_{}.png", uuid::Uuid::new_v4()));
+        write_animation(&path, &frames(), AnimationFormat::Apng, Some(2), "medium").unwrap();
+
+        let decoder = png::Decoder::new(File::open(&path).unwrap());
+        let reader = decoder.read_info().unwrap();
+        let info = reader.info();
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!((info.width, info.height), (4, 3));
+        let actl = info.animation_control().unwrap();
+        assert_eq!(actl.num_frames, 2);
+        assert_eq!(actl.num_plays, 2);
+    }
+
+    #[test]
+    fn test_write_gif_and_empty_frames() {
+        let path = std::env::temp_dir().join(format!("lumi_test_{}.gif", uuid::Uuid::new_v4()));
+        write_animation(&path, &frames(), AnimationFormat::Gif, None, "low").unwrap();
+        let bytes = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+        assert!(bytes.starts_with(b"GIF89a"));
+
+        assert!(write_animation(&path, &[], AnimationFormat::Gif, None, "low").is_err());
+    }
+}
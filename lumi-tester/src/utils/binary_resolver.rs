@@ -1,11 +1,92 @@
 use anyhow::Result;
+use colored::Colorize;
 use dirs;
+use std::collections::HashMap;
 use std::path::PathBuf;
 use std::process::Command;
+use std::sync::{Mutex, OnceLock};
+
+/// adb cũ hơn bản này (platform-tools 29) dễ lỗi với thiết bị nhiều màn hình (`input -d`)
+pub const MIN_ADB_VERSION: (u32, u32, u32) = (1, 0, 41);
+
+/// Lỗi khi không tìm thấy binary, kèm danh sách các đường dẫn đã thử
+#[derive(Debug)]
+pub struct BinaryNotFound {
+    pub name: String,
+    /// Biến môi trường dùng để chỉ định đường dẫn, ví dụ `LUMI_ADB_PATH`
+    pub env_var: String,
+    pub searched: Vec<String>,
+}
+
+impl std::fmt::Display for BinaryNotFound {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Could not find static or system binary '{}' (set {} to override)\nChecked paths:",
+            self.name, self.env_var
+        )?;
+        for path in &self.searched {
+            write!(f, "\n  - {}", path)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for BinaryNotFound {}
+
+/// Biến môi trường override cho binary: `adb.exe` -> `LUMI_ADB_PATH`
+pub fn override_env_var(name: &str) -> String {
+    let base = name.strip_suffix(".exe").unwrap_or(name);
+    let base: String = base
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_uppercase()
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    format!("LUMI_{}_PATH", base)
+}
+
+/// Đường dẫn cấu hình trong lumi.toml (ví dụ `adb`), theo tên biến môi trường override
+fn configured_paths() -> &'static Mutex<HashMap<String, PathBuf>> {
+    static PATHS: OnceLock<Mutex<HashMap<String, PathBuf>>> = OnceLock::new();
+    PATHS.get_or_init(Default::default)
+}
 
-/// Tìm binary từ bundled resources hoặc install directory (chỉ dùng static file, không dùng system PATH)
+/// Chỉ định đường dẫn cho binary `name` như `LUMI_<NAME>_PATH`; biến môi trường vẫn được ưu tiên
+pub fn set_configured_path(name: &str, path: PathBuf) {
+    configured_paths()
+        .lock()
+        .unwrap()
+        .insert(override_env_var(name), path);
+}
+
+/// Tìm binary: override qua `LUMI_<NAME>_PATH`, bundled resources, install directory, SDK, rồi system PATH
 pub fn find_binary(name: &str) -> Result<PathBuf> {
     let mut checked_paths = Vec::new();
+    let env_var = override_env_var(name);
+
+    // 0. Override tường minh qua biến môi trường hoặc lumi.toml (file binary hoặc thư mục chứa nó); sai thì báo lỗi, không tự fallback
+    let configured = || configured_paths().lock().unwrap().get(&env_var).cloned();
+    if let Some(path) = std::env::var_os(&env_var)
+        .map(PathBuf::from)
+        .or_else(configured)
+    {
+        let path = if path.is_dir() { path.join(name) } else { path };
+        if path.is_file() {
+            return Ok(path);
+        }
+        checked_paths.push(format!("{}: {:?} (not found)", env_var, path));
+        return Err(BinaryNotFound {
+            name: name.to_string(),
+            env_var,
+            searched: checked_paths,
+        }
+        .into());
+    }
 
     // 1. Tìm từ bundled resources (trong app bundle)
     let (bundled_path, mut bundled_paths) = find_bundled_binary_with_logs(name);
@@ -47,16 +128,31 @@ pub fn find_binary(name: &str) -> Result<PathBuf> {
         }
     }
 
-    // 3. Fallback to system PATH
+    // 3. Android SDK (ANDROID_HOME / ANDROID_SDK_ROOT) cho ADB
+    if name == "adb" || name == "adb.exe" {
+        for var in ["ANDROID_HOME", "ANDROID_SDK_ROOT"] {
+            if let Some(sdk) = std::env::var_os(var) {
+                let adb_path = PathBuf::from(sdk).join("platform-tools").join(name);
+                checked_paths.push(format!("{} (ADB): {:?}", var, adb_path));
+                if adb_path.is_file() {
+                    return Ok(adb_path);
+                }
+            }
+        }
+    }
+
+    // 4. Fallback to system PATH
     if let Ok(path) = which::which(name) {
         return Ok(path);
     }
+    checked_paths.push(format!("System PATH: {}", name));
 
-    Err(anyhow::anyhow!(
-        "Could not find static or system binary '{}'. Checked paths:\n{}",
-        name,
-        checked_paths.join("\n")
-    ))
+    Err(BinaryNotFound {
+        name: name.to_string(),
+        env_var,
+        searched: checked_paths,
+    }
+    .into())
 }
 
 /// Tìm binary từ bundled resources
@@ -189,16 +285,51 @@ fn find_bundled_binary_with_logs(name: &str) -> (Option<PathBuf>, Vec<String>) {
     (None, checked_paths)
 }
 
-/// Tìm ADB binary (có thể là adb hoặc adb.exe)
+/// Tìm ADB binary (có thể là adb hoặc adb.exe); cảnh báo một lần nếu adb quá cũ
 pub fn find_adb() -> Result<PathBuf> {
     #[cfg(windows)]
-    {
-        find_binary("adb.exe").or_else(|_| find_binary("adb"))
-    }
+    let path = find_binary("adb.exe").or_else(|_| find_binary("adb"))?;
     #[cfg(not(windows))]
-    {
-        find_binary("adb")
-    }
+    let path = find_binary("adb")?;
+
+    static VERSION_CHECKED: OnceLock<()> = OnceLock::new();
+    VERSION_CHECKED.get_or_init(|| {
+        if let Some(version) = adb_version(&path) {
+            if version < MIN_ADB_VERSION {
+                eprintln!(
+                    "{} adb {} at {} is older than {}; display targeting (`input -d`) may fail. Update platform-tools or set LUMI_ADB_PATH.",
+                    "⚠️".yellow(),
+                    format_version(version),
+                    path.display(),
+                    format_version(MIN_ADB_VERSION)
+                );
+            }
+        }
+    });
+    Ok(path)
+}
+
+/// Phiên bản của `adb version`, ví dụ `(1, 0, 41)`
+pub fn adb_version(adb_path: &std::path::Path) -> Option<(u32, u32, u32)> {
+    let output = Command::new(adb_path).arg("version").output().ok()?;
+    parse_adb_version(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Đọc dòng `Android Debug Bridge version 1.0.41`
+pub fn parse_adb_version(output: &str) -> Option<(u32, u32, u32)> {
+    let line = output
+        .lines()
+        .find(|l| l.starts_with("Android Debug Bridge version"))?;
+    let mut parts = line
+        .rsplit(' ')
+        .next()?
+        .split('.')
+        .map(|p| p.trim().parse::<u32>().ok());
+    Some((parts.next()??, parts.next()??, parts.next()??))
+}
+
+pub fn format_version((major, minor, patch): (u32, u32, u32)) -> String {
+    format!("{}.{}.{}", major, minor, patch)
 }
 
 /// Tìm IDB binary
@@ -239,8 +370,15 @@ pub fn idb_command() -> Result<Command> {
     Ok(cmd)
 }
 
-/// Find bundled APK file (for ADBKeyBoard, etc.)
+/// Find bundled APK file (for ADBKeyBoard, etc.); `LUMI_APK_DIR` is checked first
 pub fn find_apk(name: &str) -> Option<PathBuf> {
+    if let Some(dir) = std::env::var_os("LUMI_APK_DIR") {
+        let apk_path = PathBuf::from(dir).join(name);
+        if apk_path.exists() {
+            return Some(apk_path);
+        }
+    }
+
     // Try bundled resources first
     if let Ok(exe_path) = std::env::current_exe() {
         #[cfg(target_os = "macos")]
@@ -294,3 +432,20 @@ pub fn find_apk(name: &str) -> Option<PathBuf> {
 
     None
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_adb_version_and_override_var() {
+        let output = "Android Debug Bridge version 1.0.41\nVersion 35.0.1-11580240\nInstalled as /usr/bin/adb\n";
+        assert_eq!(parse_adb_version(output), Some((1, 0, 41)));
+        assert!(
+            parse_adb_version("Android Debug Bridge version 1.0.32\n").unwrap() < MIN_ADB_VERSION
+        );
+        assert_eq!(parse_adb_version("adb: command not found"), None);
+        assert_eq!(override_env_var("adb.exe"), "LUMI_ADB_PATH");
+        assert_eq!(override_env_var("ffmpeg"), "LUMI_FFMPEG_PATH");
+    }
+}
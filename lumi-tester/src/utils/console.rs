@@ -0,0 +1,48 @@
+//! Stdout for machine-readable output
+//!
+//! `--json-summary -` prints the summary on stdout. Console output (ours and
+//! child processes') is moved to stderr first so the two never mix.
+
+use std::io::Write;
+use std::sync::{Mutex, OnceLock};
+
+/// The process's original stdout, once console output was moved to stderr
+static MACHINE_STDOUT: OnceLock<Mutex<std::fs::File>> = OnceLock::new();
+
+/// Send everything written to stdout from now on to stderr, keeping the original
+/// stdout for [`write_machine_line`]. Unix only; elsewhere console output stays
+/// on stdout.
+pub fn reserve_stdout() -> std::io::Result<()> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::io::FromRawFd;
+
+        std::io::stdout().flush()?;
+        // SAFETY: plain fd duplication; `fd` is owned by the File from here on
+        let fd = unsafe { libc::dup(libc::STDOUT_FILENO) };
+        if fd < 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+        if unsafe { libc::dup2(libc::STDERR_FILENO, libc::STDOUT_FILENO) } < 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+        let _ = MACHINE_STDOUT.set(Mutex::new(unsafe { std::fs::File::from_raw_fd(fd) }));
+    }
+    Ok(())
+}
+
+/// Write one line to the stdout kept by [`reserve_stdout`], or to stdout
+pub fn write_machine_line(line: &str) -> std::io::Result<()> {
+    match MACHINE_STDOUT.get() {
+        Some(out) => {
+            let mut out = out.lock().unwrap();
+            writeln!(out, "{}", line)?;
+            out.flush()
+        }
+        None => {
+            let mut out = std::io::stdout().lock();
+            writeln!(out, "{}", line)?;
+            out.flush()
+        }
+    }
+}
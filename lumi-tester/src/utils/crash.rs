@@ -0,0 +1,125 @@
+//! Crash / ANR detection in logcat output
+//!
+//! Looks for `FATAL EXCEPTION` blocks (AndroidRuntime) and `ANR in <pkg>`
+//! reports (ActivityManager) and returns the block so it can be attached
+//! to the failure report.
+
+/// Maximum number of log lines kept from a crash block
+const MAX_TRACE_LINES: usize = 60;
+
+/// Return the part of `logs` written after the `marker` line.
+///
+/// When the marker has rotated out of the buffer the whole dump is returned.
+pub fn logs_after_marker<'a>(logs: &'a str, marker: Option<&str>) -> &'a str {
+    let Some(marker) = marker.filter(|m| !m.is_empty()) else {
+        return logs;
+    };
+    match logs.rfind(marker) {
+        Some(pos) => {
+            let rest = &logs[pos + marker.len()..];
+            rest.strip_prefix('\n').unwrap_or(rest)
+        }
+        None => logs,
+    }
+}
+
+/// Last non-empty line of a log dump, used as the marker for the next scan
+pub fn last_log_line(logs: &str) -> Option<String> {
+    logs.lines()
+        .rev()
+        .find(|l| !l.trim().is_empty())
+        .map(|l| l.to_string())
+}
+
+/// Find the first crash or ANR for `app_id` (any app when `None`) and return its log block
+pub fn find_crash(logs: &str, app_id: Option<&str>) -> Option<String> {
+    let lines: Vec<&str> = logs.lines().collect();
+
+    for (i, line) in lines.iter().enumerate() {
+        if line.contains("FATAL EXCEPTION") {
+            // "Process: <pkg>, PID: <pid>" follows the FATAL EXCEPTION line
+            let matches_app = match app_id {
+                Some(pkg) => lines
+                    .iter()
+                    .skip(i + 1)
+                    .take(3)
+                    .any(|l| l.contains(&format!("Process: {},", pkg))),
+                None => true,
+            };
+            if matches_app {
+                return Some(collect_block(&lines[i..], "AndroidRuntime"));
+            }
+        } else if let Some(pos) = line.find("ANR in ") {
+            let target = line[pos + "ANR in ".len()..]
+                .split_whitespace()
+                .next()
+                .unwrap_or("");
+            if app_id.is_none_or(|pkg| target == pkg) {
+                return Some(collect_block(&lines[i..], "ActivityManager"));
+            }
+        }
+    }
+
+    None
+}
+
+/// Collect consecutive lines from the same logcat tag, starting at the first line
+fn collect_block(lines: &[&str], tag: &str) -> String {
+    let mut block = Vec::new();
+    for (i, line) in lines.iter().enumerate() {
+        if i > 0 && !line.contains(tag) {
+            break;
+        }
+        block.push(*line);
+        if block.len() >= MAX_TRACE_LINES {
+            break;
+        }
+    }
+    block.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LOGS: &str = "\
+10-17 10:00:00.000  1000  1000 I ActivityManager: Start proc com.other
+10-17 10:00:01.000  4321  4321 E AndroidRuntime: FATAL EXCEPTION: main
+10-17 10:00:01.000  4321  4321 E AndroidRuntime: Process: com.example.app, PID: 4321
+10-17 10:00:01.000  4321  4321 E AndroidRuntime: java.lang.IllegalStateException: boom
+10-17 10:00:01.000  4321  4321 E AndroidRuntime: \tat com.example.app.Main.onCreate(Main.kt:12)
+10-17 10:00:01.100  1000  1000 W ActivityManager: Force finishing activity com.example.app/.Main
+10-17 10:00:05.000  1000  1020 E ActivityManager: ANR in com.slow.app (com.slow.app/.Main)
+10-17 10:00:05.000  1000  1020 E ActivityManager: PID: 5555
+10-17 10:00:05.000  1000  1020 E ActivityManager: Reason: Input dispatching timed out
+10-17 10:00:06.000  2000  2000 I chatty: done";
+
+    #[test]
+    fn test_find_crash_for_package() {
+        let trace = find_crash(LOGS, Some("com.example.app")).unwrap();
+        assert!(trace.starts_with("10-17 10:00:01.000"));
+        assert!(trace.contains("IllegalStateException: boom"));
+        assert!(trace.contains("Main.kt:12"));
+        assert!(!trace.contains("Force finishing"));
+
+        let anr = find_crash(LOGS, Some("com.slow.app")).unwrap();
+        assert!(anr.contains("ANR in com.slow.app"));
+        assert!(anr.contains("Reason: Input dispatching timed out"));
+        assert!(!anr.contains("chatty"));
+
+        assert!(find_crash(LOGS, Some("com.other")).is_none());
+    }
+
+    #[test]
+    fn test_logs_after_marker() {
+        let marker = "10-17 10:00:01.100  1000  1000 W ActivityManager: Force finishing activity com.example.app/.Main";
+        let rest = logs_after_marker(LOGS, Some(marker));
+        assert!(rest.starts_with("10-17 10:00:05.000"));
+        assert!(find_crash(rest, Some("com.example.app")).is_none());
+        assert_eq!(logs_after_marker(LOGS, Some("rotated away")), LOGS);
+        assert_eq!(
+            last_log_line(LOGS).as_deref(),
+            Some("10-17 10:00:06.000  2000  2000 I chatty: done")
+        );
+    }
+}
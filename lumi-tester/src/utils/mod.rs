@@ -1,4 +1,8 @@
+pub mod animation;
 pub mod binary_resolver;
 pub mod config;
+pub mod console;
+pub mod crash;
+pub mod version;
 
 pub mod system;
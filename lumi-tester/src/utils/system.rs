@@ -5,26 +5,325 @@ use std::io::Cursor;
 use std::path::{Path, PathBuf};
 
 pub enum SystemCommand {
-    Install { all: bool },
+    /// Scope: android, web or all; `browsers` empty means chromium
+    Install {
+        all: bool,
+        platform: String,
+        browsers: Vec<String>,
+    },
+    /// Scope: android, ios, web or all
+    Doctor { platform: String },
 }
 
 pub async fn handle_system_command(command: SystemCommand) -> Result<()> {
     match command {
-        SystemCommand::Install { all } => install_components(all).await,
+        SystemCommand::Install {
+            all,
+            platform,
+            browsers,
+        } => install_components(all, &platform, &browsers).await,
+        SystemCommand::Doctor { platform } => run_doctor(&platform).await,
     }
 }
 
-async fn install_components(_all: bool) -> Result<()> {
+/// One line of `system doctor` output
+struct Check {
+    name: &'static str,
+    result: std::result::Result<String, String>,
+    /// How to fix a failed check
+    fix: &'static str,
+    /// A failure is only a warning and doesn't fail `doctor`
+    optional: bool,
+}
+
+impl Check {
+    fn new(name: &'static str, result: Result<String>, fix: &'static str) -> Self {
+        Self {
+            name,
+            result: result.map_err(|e| e.to_string()),
+            fix,
+            optional: false,
+        }
+    }
+
+    fn optional(name: &'static str, result: Result<String>, fix: &'static str) -> Self {
+        Self {
+            optional: true,
+            ..Self::new(name, result, fix)
+        }
+    }
+}
+
+async fn run_doctor(platform: &str) -> Result<()> {
+    use crate::driver::android::adb;
+    use crate::utils::binary_resolver;
+
+    if !matches!(platform, "android" | "android_auto" | "ios" | "web" | "all") {
+        anyhow::bail!("Unknown platform for doctor: {}", platform);
+    }
+    let wants = |p: &str| platform == "all" || platform == p;
+
+    println!("{}", "Checking lumi-tester environment...".blue().bold());
+    let mut checks = Vec::new();
+
+    // Android
+    if wants("android") || wants("android_auto") {
+        let adb_path = binary_resolver::find_adb();
+        let adb_found = adb_path.is_ok();
+        checks.push(Check::new(
+            "adb",
+            adb_path.and_then(|p| {
+                match binary_resolver::adb_version(&p) {
+                    Some(v) if v < binary_resolver::MIN_ADB_VERSION => anyhow::bail!(
+                        "{} is adb {}, older than {}",
+                        p.display(),
+                        binary_resolver::format_version(v),
+                        binary_resolver::format_version(binary_resolver::MIN_ADB_VERSION)
+                    ),
+                    Some(v) => Ok(format!("{} ({})", p.display(), binary_resolver::format_version(v))),
+                    None => Ok(p.display().to_string()),
+                }
+            }),
+            "run `lumi-tester system install --platform android`, or point LUMI_ADB_PATH (lumi.toml `adb`) at a recent platform-tools",
+        ));
+        if adb_found {
+            let devices = adb::get_devices()
+                .await
+                .map(|d| {
+                    d.into_iter()
+                        .filter(|d| d.state == "device")
+                        .map(|d| d.serial)
+                        .collect::<Vec<_>>()
+                })
+                .unwrap_or_default();
+            checks.push(Check::new(
+                "Android devices",
+                if devices.is_empty() {
+                    Err(anyhow::anyhow!("no device in `adb devices`"))
+                } else {
+                    Ok(devices.join(", "))
+                },
+                "connect a device with USB debugging enabled or start an emulator",
+            ));
+            for serial in &devices {
+                let imes = adb::shell(Some(serial), "ime list -s")
+                    .await
+                    .unwrap_or_default();
+                checks.push(Check::new(
+                "ADBKeyBoard",
+                if imes.contains("com.android.adbkeyboard") {
+                    Ok(serial.clone())
+                } else {
+                    Err(anyhow::anyhow!("not installed on {}", serial))
+                },
+                "needed for Unicode inputText; it is installed on first run when ADBKeyboard.apk is bundled",
+            ));
+            }
+        }
+    }
+
+    // iOS (macOS hosts only)
+    if wants("ios") && cfg!(target_os = "macos") {
+        checks.push(Check::new(
+            "idb",
+            binary_resolver::find_idb().map(|p| p.display().to_string()),
+            "pip3 install fb-idb && brew install facebook/fb/idb-companion",
+        ));
+        let host = std::env::var("WDA_HOST").unwrap_or_else(|_| "localhost".to_string());
+        let port = crate::driver::ios::wda::DEFAULT_WDA_PORT;
+        let client = crate::driver::ios::wda::WdaClient::with_host(&host, port);
+        let ready = tokio::time::timeout(std::time::Duration::from_secs(3), client.is_ready())
+            .await
+            .unwrap_or(Ok(false))
+            .unwrap_or(false);
+        checks.push(Check::optional(
+            "WebDriverAgent",
+            if ready {
+                Ok(format!("{}:{}", host, port))
+            } else {
+                Err(anyhow::anyhow!("not reachable at {}:{}", host, port))
+            },
+            "only needed for real iOS devices: start WDA on the device (WDA_HOST for remote hosts)",
+        ));
+    }
+
+    // Web
+    if wants("web") {
+        let install_dir = get_install_dir()?;
+        checks.push(Check::new(
+            "Playwright driver",
+            playwright_driver_path(&install_dir.join("playwright"))
+                .map(|p| p.display().to_string())
+                .context("not installed"),
+            "run `lumi-tester system install --platform web`",
+        ));
+        let browsers = playwright_browsers_dir()
+            .map(|dir| installed_browsers(&dir))
+            .unwrap_or_default();
+        checks.push(Check::new(
+            "Playwright browsers",
+            if browsers.is_empty() {
+                Err(anyhow::anyhow!("no browser downloaded"))
+            } else {
+                Ok(browsers.join(", "))
+            },
+            "run `lumi-tester system install --platform web [--browser firefox,webkit]`",
+        ));
+    }
+
+    // OCR (image/text fallback on devices)
+    if platform != "web" {
+        checks.push(Check::new(
+        "OCR",
+        crate::driver::ocr::OcrEngine::check_backend(),
+        if cfg!(target_os = "macos") {
+            "swiftc -O -o ~/.lumi-tester/ocr_helper resources/ocr_helper.swift"
+        } else {
+            "install tesseract with English and Vietnamese data (tesseract-ocr-eng, tesseract-ocr-vie)"
+        },
+    ));
+    }
+
+    let mut problems = 0;
+    let mut warnings = 0;
+    for check in &checks {
+        match &check.result {
+            Ok(detail) => println!(
+                "  {} {:<20} {}",
+                "OK ".green().bold(),
+                check.name,
+                detail.dimmed()
+            ),
+            Err(error) => {
+                let label = if check.optional {
+                    warnings += 1;
+                    "WARN".yellow().bold()
+                } else {
+                    problems += 1;
+                    "FIX".red().bold()
+                };
+                // Resolver errors list every searched path; the first line is enough here
+                let summary = error.lines().next().unwrap_or_default();
+                println!("  {} {:<20} {}", label, check.name, summary);
+                println!("      {} {}", "→".yellow(), check.fix);
+            }
+        }
+    }
+
+    if problems > 0 {
+        anyhow::bail!("{} check(s) need attention", problems);
+    }
+    if warnings > 0 {
+        println!(
+            "\n{} ({} optional check(s) not met)",
+            "Everything required looks good".green().bold(),
+            warnings
+        );
+    } else {
+        println!("\n{}", "Everything looks good!".green().bold());
+    }
+    Ok(())
+}
+
+/// Playwright driver inside `<install>/playwright` (`playwright.sh` on Unix builds)
+fn playwright_driver_path(pw_dir: &Path) -> Option<PathBuf> {
+    let name = if cfg!(windows) {
+        "playwright.exe"
+    } else {
+        "playwright"
+    };
+    [pw_dir.join(name), pw_dir.join("playwright.sh")]
+        .into_iter()
+        .find(|p| p.exists())
+}
+
+/// Where Playwright keeps downloaded browsers (`PLAYWRIGHT_BROWSERS_PATH` or the OS cache)
+fn playwright_browsers_dir() -> Option<PathBuf> {
+    if let Some(dir) = std::env::var_os("PLAYWRIGHT_BROWSERS_PATH") {
+        return Some(PathBuf::from(dir));
+    }
+    if cfg!(target_os = "macos") {
+        dirs::home_dir().map(|h| h.join("Library/Caches/ms-playwright"))
+    } else if cfg!(windows) {
+        dirs::data_local_dir().map(|d| d.join("ms-playwright"))
+    } else {
+        dirs::cache_dir().map(|d| d.join("ms-playwright"))
+    }
+}
+
+/// Browsers present in a Playwright cache, e.g. `chromium-1091` -> `chromium`
+fn installed_browsers(dir: &Path) -> Vec<String> {
+    let mut browsers: Vec<String> = fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.path().is_dir())
+        .filter_map(|e| {
+            let name = e.file_name().to_string_lossy().to_string();
+            let (browser, build) = name.rsplit_once('-')?;
+            build
+                .chars()
+                .all(|c| c.is_ascii_digit())
+                .then(|| browser.to_string())
+        })
+        .filter(|b| matches!(b.as_str(), "chromium" | "firefox" | "webkit"))
+        .collect();
+    browsers.sort();
+    browsers.dedup();
+    browsers
+}
+
+/// Browsers Playwright can download for the web driver
+const PLAYWRIGHT_BROWSERS: [&str; 3] = ["chromium", "firefox", "webkit"];
+
+/// `--browser` values to Playwright browser names: empty means chromium, `all` expands
+fn resolve_browsers(requested: &[String]) -> Result<Vec<String>> {
+    if requested.is_empty() {
+        return Ok(vec!["chromium".to_string()]);
+    }
+    let mut browsers = Vec::new();
+    for browser in requested {
+        let names: Vec<&str> = match browser.as_str() {
+            "all" => PLAYWRIGHT_BROWSERS.to_vec(),
+            b if PLAYWRIGHT_BROWSERS.contains(&b) => vec![b],
+            other => anyhow::bail!(
+                "Unknown browser: {} (expected {} or all)",
+                other,
+                PLAYWRIGHT_BROWSERS.join(", ")
+            ),
+        };
+        for name in names {
+            if !browsers.iter().any(|b| b == name) {
+                browsers.push(name.to_string());
+            }
+        }
+    }
+    Ok(browsers)
+}
+
+async fn install_components(_all: bool, platform: &str, browsers: &[String]) -> Result<()> {
+    if !matches!(platform, "android" | "android_auto" | "web" | "all") {
+        anyhow::bail!(
+            "Unknown platform for install: {} (expected android, web or all)",
+            platform
+        );
+    }
+    let browsers = resolve_browsers(browsers)?;
+
     println!("{}", "Checking system components...".blue().bold());
 
     let install_dir = get_install_dir()?;
     fs::create_dir_all(&install_dir)?;
 
     // 1. Check and install ADB
-    install_adb(&install_dir).await?;
+    if platform != "web" {
+        install_adb(&install_dir).await?;
+    }
 
     // 2. Check and install Playwright
-    install_playwright(&install_dir).await?;
+    if matches!(platform, "web" | "all") {
+        install_playwright(&install_dir, &browsers).await?;
+    }
 
     println!("\n{}", "All system components are ready!".green().bold());
     println!("Installation directory: {}", install_dir.display());
@@ -91,7 +390,7 @@ async fn install_adb(install_dir: &Path) -> Result<()> {
     Ok(())
 }
 
-async fn install_playwright(install_dir: &Path) -> Result<()> {
+async fn install_playwright(install_dir: &Path, browsers: &[String]) -> Result<()> {
     let pw_dir = install_dir.join("playwright");
     fs::create_dir_all(&pw_dir)?;
 
@@ -175,17 +474,22 @@ async fn install_playwright(install_dir: &Path) -> Result<()> {
         println!("{} Playwright driver installed.", "✓".green());
     }
 
-    // Install browsers
-    println!("{} Installing Playwright browsers...", "⬇️".yellow());
+    // Always let the driver install: it skips browsers whose required revision is
+    // already cached and downloads the new one after a driver upgrade
+    println!(
+        "{} Installing Playwright browsers: {}...",
+        "⬇️".yellow(),
+        browsers.join(", ")
+    );
     let status = std::process::Command::new(&driver_path)
         .arg("install")
-        .arg("chromium")
+        .args(browsers)
         .arg("ffmpeg")
         .status()
         .context("Failed to run playwright install")?;
 
     if !status.success() {
-        anyhow::bail!("Failed to install browsers");
+        anyhow::bail!("Failed to install browsers: {}", browsers.join(", "));
     }
 
     println!("{} Playwright browsers installed.", "✓".green());
@@ -306,3 +610,35 @@ fn extract_tar_gz(archive_path: &Path, target_dir: &Path) -> Result<()> {
     archive.unpack(target_dir)?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_installed_browsers_reads_playwright_cache() {
+        let dir = std::env::temp_dir().join(format!("lumi_pw_{}", uuid::Uuid::new_v4()));
+        for name in [
+            "chromium-1091",
+            "chromium-1084",
+            "webkit-1944",
+            "ffmpeg-1009",
+            ".links",
+        ] {
+            fs::create_dir_all(dir.join(name)).unwrap();
+        }
+        assert_eq!(installed_browsers(&dir), vec!["chromium", "webkit"]);
+        assert!(installed_browsers(&dir.join("missing")).is_empty());
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_resolve_browsers_defaults_and_expands_all() {
+        assert_eq!(resolve_browsers(&[]).unwrap(), vec!["chromium"]);
+        assert_eq!(
+            resolve_browsers(&["webkit".to_string(), "all".to_string()]).unwrap(),
+            vec!["webkit", "chromium", "firefox"]
+        );
+        assert!(resolve_browsers(&["safari".to_string()]).is_err());
+    }
+}
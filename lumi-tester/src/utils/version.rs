@@ -0,0 +1,60 @@
+//! Version string comparison
+//!
+//! Compares dotted versions ("1.10.2" > "1.9") component by component,
+//! numerically where possible. Pre-release suffixes ("2.0.0-beta") sort
+//! before the plain release, as in semver.
+
+use std::cmp::Ordering;
+
+/// Compare two version strings
+pub fn compare_versions(a: &str, b: &str) -> Ordering {
+    let (a_core, a_pre) = split_pre_release(a);
+    let (b_core, b_pre) = split_pre_release(b);
+
+    let a_parts: Vec<&str> = a_core.split('.').collect();
+    let b_parts: Vec<&str> = b_core.split('.').collect();
+
+    for i in 0..a_parts.len().max(b_parts.len()) {
+        // Missing components count as zero ("1.2" == "1.2.0")
+        let x = a_parts.get(i).copied().unwrap_or("0");
+        let y = b_parts.get(i).copied().unwrap_or("0");
+        let ord = match (x.parse::<u64>(), y.parse::<u64>()) {
+            (Ok(x), Ok(y)) => x.cmp(&y),
+            _ => x.cmp(y),
+        };
+        if ord != Ordering::Equal {
+            return ord;
+        }
+    }
+
+    match (a_pre, b_pre) {
+        (None, None) => Ordering::Equal,
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (Some(x), Some(y)) => x.cmp(y),
+    }
+}
+
+fn split_pre_release(v: &str) -> (&str, Option<&str>) {
+    let v = v.trim().trim_start_matches(['v', 'V']);
+    // Build metadata never affects ordering
+    let v = v.split('+').next().unwrap_or(v);
+    match v.split_once('-') {
+        Some((core, pre)) => (core, Some(pre)),
+        None => (v, None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compare_versions() {
+        assert_eq!(compare_versions("1.10.0", "1.9.3"), Ordering::Greater);
+        assert_eq!(compare_versions("1.2", "1.2.0"), Ordering::Equal);
+        assert_eq!(compare_versions("v2.0.0", "2.0.0+45"), Ordering::Equal);
+        assert_eq!(compare_versions("2.0.0-beta", "2.0.0"), Ordering::Less);
+        assert_eq!(compare_versions("3.1.0", "3.1.0-rc1"), Ordering::Greater);
+    }
+}